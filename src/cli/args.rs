@@ -1,3 +1,6 @@
+use super::command::Command;
+use crate::core::LogLevel;
+use crate::io::Overwrite;
 use clap::Parser;
 
 #[derive(Parser, Debug)]
@@ -5,19 +8,185 @@ use clap::Parser;
 #[command(about = env!("CARGO_PKG_DESCRIPTION"), long_about = None)]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(author = env!("CARGO_PKG_AUTHORS"))]
+#[command(
+	after_help = "Commands: convert, probe, batch and analyze take their own subset of these flags (ffmpreg <command> --help); bench, preview, vad, slice and serve are tools of their own; ffmpreg completions <bash|zsh|fish> prints a shell completion script"
+)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+#[command(disable_help_subcommand = true)]
 pub struct Args {
-	#[arg(short, long, value_name = "FILE", help = "Input file or glob pattern")]
+	#[command(subcommand)]
+	pub command: Option<Command>,
+
+	#[arg(
+		short,
+		long,
+		value_name = "FILE",
+		required_unless_present_any = ["job", "watch", "help_man", "diff"],
+		default_value = "",
+		hide_default_value = true,
+		help = "Input file, glob pattern, directory or - for stdin; repeat to open several inputs"
+	)]
 	pub input: String,
 
-	#[arg(short, long, value_name = "FILE", help = "Output file or directory")]
+	// every -i after the first, moved here by `normalize_args`
+	#[arg(long = "more-input", value_name = "FILE", hide = true)]
+	pub more_inputs: Vec<String>,
+
+	#[arg(short, long, value_name = "FILE", help = "Output file, directory or - for stdout")]
 	pub output: Option<String>,
 
+	// every -o after the first: more outputs written from the same decode
+	#[arg(long = "more-output", value_name = "FILE", hide = true)]
+	pub more_outputs: Vec<String>,
+
+	#[arg(
+		short,
+		long,
+		value_name = "FORMAT",
+		help = "Container of the output, overriding its extension, and of an input without one: wav, flac, y4m, avi, mp4"
+	)]
+	pub format: Option<String>,
+
+	#[arg(
+		long = "input-format",
+		value_name = "FORMAT",
+		help = "Container of the input, overriding its extension: wav, flac, y4m, avi, mp4"
+	)]
+	pub input_format: Option<String>,
+
+	#[arg(
+		long,
+		help = "Descend into subdirectories of a directory input, mirroring them in the output"
+	)]
+	pub recursive: bool,
+
+	#[arg(
+		long = "ext",
+		value_name = "EXT",
+		value_delimiter = ',',
+		help = "Only take files with these extensions from a batch input, e.g. wav,flac"
+	)]
+	pub extensions: Vec<String>,
+
+	#[arg(short = 'y', long, conflicts_with = "no_overwrite", help = "Overwrite existing outputs")]
+	pub overwrite: bool,
+
+	#[arg(
+		short = 'n',
+		long = "no-overwrite",
+		visible_alias = "skip-existing",
+		help = "Skip inputs whose output already exists"
+	)]
+	pub no_overwrite: bool,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Record finished batch items in FILE and skip the ones it lists"
+	)]
+	pub resume: Option<String>,
+
+	#[arg(
+		long = "on-error",
+		value_name = "POLICY",
+		default_value = "continue",
+		help = "When a batch file fails: continue with the rest or stop"
+	)]
+	pub on_error: String,
+
+	#[arg(
+		long,
+		value_name = "NAME",
+		conflicts_with = "job",
+		help = "Settings bundle: podcast, archive, voicemail, web-video, or one from the config file"
+	)]
+	pub preset: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Config file of defaults and presets (default ~/.config/ffmpreg/config.toml)"
+	)]
+	pub config: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "IMAGE",
+		conflicts_with_all = ["job", "watch", "show"],
+		help = "Embed a jpeg or png as front cover art in wav, flac or mp4 output"
+	)]
+	pub cover: Option<String>,
+
 	#[arg(long, help = "Show frame information (like ffprobe)")]
 	pub show: bool,
 
 	#[arg(long, help = "Output in JSON format")]
 	pub json: bool,
 
+	#[arg(
+		long,
+		value_name = "FORMAT",
+		help = "With --show, print as human (default), json, csv or xml"
+	)]
+	pub print_format: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "TOOL",
+		help = "With --show, print JSON in another tool's layout: ffprobe"
+	)]
+	pub compat: Option<String>,
+
+	#[arg(
+		long,
+		help = "With --show, list every packet: stream, byte offset, size, pts, dts, keyframe"
+	)]
+	pub packets: bool,
+
+	#[arg(
+		long,
+		help = "With --show, read every packet to count frames and time wav and y4m exactly"
+	)]
+	pub count_frames: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["show", "packets"],
+		help = "Print the RIFF chunk tree of a wav or avi: fourcc, offset, size, nesting"
+	)]
+	pub chunks: bool,
+
+	#[arg(
+		long,
+		value_name = "MODE",
+		num_args = 0..=1,
+		default_missing_value = "summary",
+		conflicts_with_all = ["show", "packets", "chunks"],
+		help = "Print min, average and max bitrate per second: summary (default), sparkline or csv"
+	)]
+	pub bitrate: Option<String>,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["show", "packets", "chunks", "bitrate"],
+		help = "List the keyframes of each video stream: time, pts, byte offset, size"
+	)]
+	pub keyframes: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["show", "packets", "chunks", "bitrate", "keyframes"],
+		help = "Decode the audio and print integrated LUFS, true peak, RMS and loudness range"
+	)]
+	pub stats: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["show", "packets", "chunks", "bitrate", "keyframes", "stats"],
+		help = "Demux and decode the whole file, verify its CRCs and MD5, and report damaged frames"
+	)]
+	pub check: bool,
+
 	#[arg(long, value_name = "INDEX", help = "Filter by stream index")]
 	pub stream: Option<usize>,
 
@@ -27,6 +196,14 @@ pub struct Args {
 	#[arg(long = "hex-limit", value_name = "N", default_value = "8", help = "Hex bytes per frame")]
 	pub hex_limit: usize,
 
+	#[arg(
+		long,
+		value_name = "UNITS",
+		default_value = "binary",
+		help = "Size units in show output (binary, decimal, bytes)"
+	)]
+	pub units: String,
+
 	#[arg(
 		long = "apply",
 		value_name = "FILTER",
@@ -34,12 +211,316 @@ pub struct Args {
 	)]
 	pub transforms: Vec<String>,
 
-	#[arg(long, value_name = "CODEC", help = "Output codec (pcm, adpcm)")]
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Keep only the ranges listed in an EDL file (text or JSON)"
+	)]
+	pub edl: Option<String>,
+
+	#[arg(
+		long = "export-edl",
+		value_name = "FILE",
+		help = "Write the ranges between the silences --apply silencedetect finds to an EDL file"
+	)]
+	pub export_edl: Option<String>,
+
+	#[arg(long, value_name = "TIME", help = "Start at this timestamp (e.g., 1:30, 90s)")]
+	pub ss: Option<String>,
+
+	#[arg(long, value_name = "TIME", help = "Stop at this timestamp of the input")]
+	pub to: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		conflicts_with = "to",
+		help = "Stop after this much output (e.g., 10s)"
+	)]
+	pub duration: Option<String>,
+
+	#[arg(
+		long = "loop",
+		value_name = "N",
+		help = "Play the input N times, or 'inf' to repeat until --to/--duration"
+	)]
+	pub loop_count: Option<String>,
+
+	#[arg(
+		long = "auto-fade",
+		value_name = "DURATION",
+		help = "Fade audio in/out at each EDL boundary (e.g., 200ms)"
+	)]
+	pub auto_fade: Option<String>,
+
+	#[arg(
+		long = "sample-frames",
+		value_name = "SPEC",
+		help = "Export video frames at an interval (e.g., every=2s,scale=256x256,crop=224x224)"
+	)]
+	pub sample_frames: Option<String>,
+
+	#[arg(
+		long = "contact-sheet",
+		value_name = "GRID",
+		help = "Write a grid of evenly spaced, timestamped frames to -o (e.g., 4x3,width=240)"
+	)]
+	pub contact_sheet: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		num_args = 0..=1,
+		default_missing_value = "-",
+		help = "Draw the audio's min/max peaks to a .png or .ppm, or in the terminal without a file"
+	)]
+	pub waveform: Option<String>,
+
+	#[arg(
+		long = "output-template",
+		value_name = "TEMPLATE",
+		default_value = "frames/{stem}_{n}.png",
+		help = "Image path for --sample-frames, with the input's {stem} and the sample number filled in as in the default"
+	)]
+	pub output_template: String,
+
+	#[arg(
+		long = "metadata-stream",
+		value_name = "SPEC:KEY=VALUE",
+		help = "Set stream metadata, usually written as --metadata:s:a:0 language=eng"
+	)]
+	pub stream_metadata: Vec<String>,
+
+	#[arg(long, value_name = "CODEC", help = "Output codec, e.g. pcm_s24le for wav")]
 	pub codec: Option<String>,
+
+	#[arg(long, help = "Tag the output with ReplayGain track gain and peak (wav, flac)")]
+	pub replaygain: bool,
+
+	#[arg(long, help = "Write video frames in reverse order (y4m)")]
+	pub reverse: bool,
+
+	#[arg(
+		long,
+		value_name = "MODE",
+		num_args = 0..=1,
+		default_missing_value = "line",
+		help = "Report progress on stderr: line (default on a terminal), json or off"
+	)]
+	pub progress: Option<String>,
+
+	#[arg(
+		long,
+		conflicts_with = "show",
+		help = "Report wall and CPU time, speed, packets and frames per second and peak memory on stderr"
+	)]
+	pub benchmark: bool,
+
+	#[arg(
+		long,
+		value_name = "N",
+		default_value_t = 1,
+		help = "Run decoding, transforms and encoding on up to N threads (0 for one per core)"
+	)]
+	pub threads: usize,
+
+	#[arg(short, long, action = clap::ArgAction::Count, help = "Log more detail on stderr; repeat for debug and trace")]
+	pub verbose: u8,
+
+	#[arg(short, long, conflicts_with = "verbose", help = "Log nothing and report no progress")]
+	pub quiet: bool,
+
+	#[arg(
+		long = "log-level",
+		value_name = "LEVEL",
+		help = "Log level: off, error, warning (default), info, debug or trace"
+	)]
+	pub log_level: Option<String>,
+
+	#[arg(
+		long = "error-format",
+		value_name = "FORMAT",
+		default_value = "text",
+		help = "Print a failure as text or as a JSON object on stderr"
+	)]
+	pub error_format: String,
+
+	#[arg(long = "help-man", help = "Print the man page (roff) on stdout and exit")]
+	pub help_man: bool,
+
+	#[arg(long, value_name = "FILE", help = "Composite a second y4m onto every frame")]
+	pub overlay: Option<String>,
+
+	#[arg(
+		long = "overlay-at",
+		value_name = "X,Y",
+		default_value = "0,0",
+		requires = "overlay",
+		help = "Top-left position of --overlay"
+	)]
+	pub overlay_at: String,
+
+	#[arg(
+		long = "overlay-alpha",
+		value_name = "ALPHA",
+		default_value = "1",
+		requires = "overlay",
+		help = "Opacity of --overlay from 0 to 1"
+	)]
+	pub overlay_alpha: f32,
+
+	#[arg(
+		long = "filter-graph",
+		value_name = "GRAPH",
+		help = "Run a filter graph after --apply, e.g. '[in]split[a][b];[a]scale=64x36[s];[b][s]overlay=8:8[out]'"
+	)]
+	pub filter_graph: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		num_args = 0..=1,
+		default_missing_value = "",
+		conflicts_with = "input",
+		help = "Run the conversions listed in a TOML or JSON job file, or the config's [[job]] tables"
+	)]
+	pub job: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "DIR",
+		conflicts_with_all = ["input", "job"],
+		help = "Watch DIR and convert files into -o as they appear"
+	)]
+	pub watch: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "2s",
+		help = "With --watch, wait until a new file has stopped growing for this long"
+	)]
+	pub settle: String,
+
+	#[arg(long, value_name = "FILE", help = "Append another audio input after the -i inputs")]
+	pub concat: Option<String>,
+
+	#[arg(long, value_name = "DURATION", help = "Overlap joined inputs by this much (e.g., 500ms)")]
+	pub crossfade: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Report per-frame and average PSNR/SSIM of --input against this y4m"
+	)]
+	pub compare: Option<String>,
+
+	#[arg(
+		long,
+		num_args = 2,
+		value_names = ["A", "B"],
+		conflicts_with_all = ["input", "compare"],
+		help = "Decode two wav, flac or y4m files and report whether they are bit-identical"
+	)]
+	pub diff: Vec<String>,
+}
+
+// every flag at its default, which a command fills in with its own
+impl Default for Args {
+	fn default() -> Self {
+		<Self as clap::Parser>::try_parse_from(["ffmpreg", "--input", ""])
+			.expect("the flag form parses with defaults alone")
+	}
 }
 
 impl Args {
 	pub fn parse() -> Self {
-		<Self as clap::Parser>::parse()
+		<Self as clap::Parser>::parse_from(normalize_args(std::env::args()))
+	}
+
+	pub fn try_parse() -> Result<Self, clap::Error> {
+		<Self as clap::Parser>::try_parse_from(normalize_args(std::env::args()))
+	}
+
+	// existing outputs are refused unless -y or -n says otherwise
+	pub fn overwrite(&self) -> Overwrite {
+		match (self.overwrite, self.no_overwrite) {
+			(true, _) => Overwrite::Replace,
+			(_, true) => Overwrite::Skip,
+			_ => Overwrite::Refuse,
+		}
+	}
+
+	// --log-level wins over -q, which wins over the -v count
+	pub fn log_level(&self) -> Option<LogLevel> {
+		match &self.log_level {
+			Some(name) => LogLevel::from_name(name),
+			None if self.quiet => Some(LogLevel::Off),
+			None => Some(LogLevel::from_verbosity(self.verbose)),
+		}
+	}
+
+	// --threads 0 takes every core there is
+	pub fn threads(&self) -> usize {
+		match self.threads {
+			0 => std::thread::available_parallelism().map(usize::from).unwrap_or(1),
+			n => n,
+		}
+	}
+
+	// every -i in the order given
+	pub fn inputs(&self) -> Vec<String> {
+		std::iter::once(&self.input).chain(&self.more_inputs).cloned().collect()
 	}
 }
+
+// clap cannot declare `--metadata:s:a:0`, so fold the specifier into the value:
+// `--metadata:s:a:0 language=eng` becomes `--metadata-stream s:a:0:language=eng`.
+// nor can a String flag be given twice, so in the flag form every -i after the
+// first becomes `--more-input` and every -o after the first `--more-output`;
+// the commands take theirs as lists
+pub fn normalize_args<I: IntoIterator<Item = String>>(args: I) -> Vec<String> {
+	let mut out = Vec::new();
+	let mut iter = args.into_iter().peekable();
+	out.extend(iter.next());
+	// the flag form has no positional arguments, so a first word names a command
+	let command = iter.peek().is_some_and(|arg| !arg.starts_with('-'));
+	let mut seen_input = false;
+	let mut seen_output = false;
+
+	while let Some(arg) = iter.next() {
+		if let Some(spec) = arg.strip_prefix("--metadata:") {
+			let value = iter.next().unwrap_or_default();
+			out.push("--metadata-stream".to_string());
+			out.push(format!("{}:{}", spec, value));
+			continue;
+		}
+		let inline = arg.strip_prefix("--input=").or(arg.strip_prefix("-i").filter(|v| !v.is_empty()));
+		if !command && (arg == "-i" || arg == "--input" || inline.is_some()) {
+			if seen_input {
+				out.push("--more-input".to_string());
+				out.push(match inline {
+					Some(value) => value.trim_start_matches('=').to_string(),
+					None => iter.next().unwrap_or_default(),
+				});
+				continue;
+			}
+			seen_input = true;
+		}
+		let inline = arg.strip_prefix("--output=").or(arg.strip_prefix("-o").filter(|v| !v.is_empty()));
+		if !command && (arg == "-o" || arg == "--output" || inline.is_some()) {
+			if seen_output {
+				out.push("--more-output".to_string());
+				out.push(match inline {
+					Some(value) => value.trim_start_matches('=').to_string(),
+					None => iter.next().unwrap_or_default(),
+				});
+				continue;
+			}
+			seen_output = true;
+		}
+		out.push(arg);
+	}
+
+	out
+}
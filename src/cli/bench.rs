@@ -0,0 +1,153 @@
+use crate::codecs::{FlacDecoder, PcmDecoder, RawVideoDecoder};
+use crate::container::{
+	AviReader, FlacReader, MediaType, Mp3Reader, Mp4Reader, OggReader, WavReader, Y4mReader,
+};
+use crate::core::{Decoder, Demuxer, Frame, Packet};
+use crate::io::{FileAdapter, IoError, IoResult};
+use crate::show::units::{SizeUnits, format_duration, format_size, format_thousands};
+use clap::{Parser, Subcommand};
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Debug)]
+#[command(name = "ffmpreg bench", bin_name = "ffmpreg bench", about = "Measure decoder throughput")]
+pub struct BenchArgs {
+	#[command(subcommand)]
+	pub command: BenchCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BenchCommand {
+	/// Decode every packet of a file and discard the frames
+	Decode {
+		#[arg(value_name = "FILE")]
+		input: String,
+
+		#[arg(long, required = true, help = "Discard decoded frames (like ffmpeg -f null -)")]
+		null: bool,
+	},
+}
+
+impl BenchArgs {
+	pub fn run(&self) -> std::io::Result<()> {
+		match &self.command {
+			BenchCommand::Decode { input, .. } => {
+				let stats = decode_null(input).map_err(std::io::Error::from)?;
+				stats.print(input);
+				Ok(())
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DecodeStats {
+	pub packets: u64,
+	pub frames: u64,
+	pub packet_bytes: u64,
+	pub frame_bytes: u64,
+	pub elapsed: Duration,
+	// false when the container has no decoder yet and only demuxing was timed
+	pub decoded: bool,
+}
+
+impl DecodeStats {
+	pub fn frames_per_second(&self) -> f64 {
+		let secs = self.elapsed.as_secs_f64();
+		if secs > 0.0 { self.frames as f64 / secs } else { 0.0 }
+	}
+
+	pub fn bytes_per_second(&self) -> f64 {
+		let secs = self.elapsed.as_secs_f64();
+		if secs > 0.0 { self.frame_bytes as f64 / secs } else { 0.0 }
+	}
+
+	fn count_packet(&mut self, packet: &Packet) {
+		self.packets += 1;
+		self.packet_bytes += packet.data.len() as u64;
+	}
+
+	fn count_frame(&mut self, frame: &Frame) {
+		self.frames += 1;
+		self.frame_bytes += frame.size() as u64;
+	}
+
+	pub fn print(&self, path: &str) {
+		println!("bench: {}", path);
+		if !self.decoded {
+			println!("  no decoder for this container, timing demux only");
+		}
+		println!(
+			"  packets: {}  ({})",
+			format_thousands(self.packets),
+			format_size(self.packet_bytes, SizeUnits::Binary)
+		);
+		println!(
+			"  frames: {}  ({})",
+			format_thousands(self.frames),
+			format_size(self.frame_bytes, SizeUnits::Binary)
+		);
+		println!("  time: {}", format_duration(self.elapsed.as_secs_f64()));
+		println!(
+			"  speed: {:.1} frames/s  {}/s",
+			self.frames_per_second(),
+			format_size(self.bytes_per_second() as u64, SizeUnits::Binary)
+		);
+	}
+}
+
+// decode everything and write nowhere: the loop keeps only counters, so the
+// measured time is the demuxer and decoder alone
+pub fn decode_null(path: &str) -> IoResult<DecodeStats> {
+	let input = FileAdapter::open(path)?;
+	let start = Instant::now();
+
+	let mut stats = match MediaType::from_extension(path) {
+		MediaType::Wav => {
+			let mut reader = WavReader::new(input)?;
+			let decoder = PcmDecoder::new(reader.format());
+			decode_all(&mut reader, decoder)?
+		}
+		MediaType::Y4m => {
+			let mut reader = Y4mReader::new(input)?;
+			let decoder = RawVideoDecoder::new(reader.format());
+			decode_all(&mut reader, decoder)?
+		}
+		MediaType::Flac => {
+			let mut reader = FlacReader::new(input)?;
+			let decoder = FlacDecoder::new(reader.format());
+			decode_all(&mut reader, decoder)?
+		}
+		MediaType::Avi => demux_all(&mut AviReader::new(input)?)?,
+		MediaType::Mp4 => demux_all(&mut Mp4Reader::new(input)?)?,
+		MediaType::Mp3 => demux_all(&mut Mp3Reader::new(input)?)?,
+		MediaType::Ogg => demux_all(&mut OggReader::new(input)?)?,
+		MediaType::Unknown => return Err(IoError::invalid_data("unsupported file format")),
+	};
+
+	stats.elapsed = start.elapsed();
+	Ok(stats)
+}
+
+fn decode_all<D: Demuxer, C: Decoder>(reader: &mut D, mut decoder: C) -> IoResult<DecodeStats> {
+	let mut stats = DecodeStats { decoded: true, ..DecodeStats::default() };
+
+	while let Some(packet) = reader.read_packet()? {
+		stats.count_packet(&packet);
+		if let Some(frame) = decoder.decode(packet)? {
+			stats.count_frame(&frame);
+		}
+	}
+	while let Some(frame) = decoder.flush()? {
+		stats.count_frame(&frame);
+	}
+
+	Ok(stats)
+}
+
+fn demux_all<D: Demuxer>(reader: &mut D) -> IoResult<DecodeStats> {
+	let mut stats = DecodeStats::default();
+	while let Some(packet) = reader.read_packet()? {
+		stats.count_packet(&packet);
+	}
+	Ok(stats)
+}
@@ -0,0 +1,666 @@
+use super::args::Args;
+use super::bench::BenchArgs;
+use super::completions::CompletionsArgs;
+use super::pipeline::{is_batch_pattern, is_directory};
+use super::preview::PreviewArgs;
+use super::slice::SliceArgs;
+use super::vad::VadArgs;
+use crate::serve::ServeArgs;
+use clap::Subcommand;
+
+// `ffmpreg <command> ...`. convert, probe, batch and analyze split the flag
+// form by what a run does: each takes its input as a positional argument and
+// only the flags that mean something to it, so anything else is refused
+// instead of quietly changing what happens, and each turns into the same
+// `Args` the flag form parses to. the rest are tools of their own
+#[derive(Subcommand, Debug)]
+pub enum Command {
+	Convert(Box<ConvertArgs>),
+	Probe(ProbeArgs),
+	Batch(BatchArgs),
+	#[command(alias = "analyse")]
+	Analyze(AnalyzeArgs),
+	Bench(BenchArgs),
+	Preview(PreviewArgs),
+	Vad(VadArgs),
+	Slice(SliceArgs),
+	Serve(ServeArgs),
+	// left out of the help: packagers run it once
+	#[command(hide = true)]
+	Completions(CompletionsArgs),
+}
+
+// logging, errors and the config apply whatever the command
+#[derive(clap::Args, Debug)]
+pub struct CommonArgs {
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Config file of defaults and presets (default ~/.config/ffmpreg/config.toml)"
+	)]
+	pub config: Option<String>,
+
+	#[arg(short, long, action = clap::ArgAction::Count, help = "Log more detail on stderr; repeat for debug and trace")]
+	pub verbose: u8,
+
+	#[arg(short, long, conflicts_with = "verbose", help = "Log nothing and report no progress")]
+	pub quiet: bool,
+
+	#[arg(
+		long = "log-level",
+		value_name = "LEVEL",
+		help = "Log level: off, error, warning (default), info, debug or trace"
+	)]
+	pub log_level: Option<String>,
+
+	#[arg(
+		long = "error-format",
+		value_name = "FORMAT",
+		default_value = "text",
+		help = "Print a failure as text or as a JSON object on stderr"
+	)]
+	pub error_format: String,
+}
+
+impl From<CommonArgs> for Args {
+	fn from(common: CommonArgs) -> Self {
+		Args {
+			config: common.config,
+			verbose: common.verbose,
+			quiet: common.quiet,
+			log_level: common.log_level,
+			error_format: common.error_format,
+			..Args::default()
+		}
+	}
+}
+
+#[derive(clap::Args, Debug)]
+pub struct OverwriteArgs {
+	#[arg(short = 'y', long, conflicts_with = "no_overwrite", help = "Overwrite existing outputs")]
+	pub overwrite: bool,
+
+	#[arg(
+		short = 'n',
+		long = "no-overwrite",
+		visible_alias = "skip-existing",
+		help = "Skip inputs whose output already exists"
+	)]
+	pub no_overwrite: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Convert one input into one output")]
+pub struct ConvertArgs {
+	#[arg(value_name = "FILE", value_parser = single_input, help = "Input file, or - for stdin")]
+	pub file: String,
+
+	#[arg(value_name = "OUTPUT", help = "Output file, or - for stdout; any -o add further outputs")]
+	pub output: Option<String>,
+
+	#[arg(
+		short,
+		long = "input",
+		value_name = "FILE",
+		help = "A further input after FILE, for --filter-graph or --crossfade"
+	)]
+	pub inputs: Vec<String>,
+
+	#[arg(
+		short,
+		long = "output",
+		value_name = "FILE",
+		help = "Output file, or a further one written from the same decode"
+	)]
+	pub outputs: Vec<String>,
+
+	#[arg(
+		short,
+		long,
+		value_name = "FORMAT",
+		help = "Container of the output, overriding its extension, and of an input without one: wav, flac, y4m, avi, mp4"
+	)]
+	pub format: Option<String>,
+
+	#[arg(
+		long = "input-format",
+		value_name = "FORMAT",
+		help = "Container of the input, overriding its extension: wav, flac, y4m, avi, mp4"
+	)]
+	pub input_format: Option<String>,
+
+	#[command(flatten)]
+	pub overwrite: OverwriteArgs,
+
+	#[arg(
+		long,
+		value_name = "NAME",
+		help = "Settings bundle: podcast, archive, voicemail, web-video, or one from the config file"
+	)]
+	pub preset: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "IMAGE",
+		help = "Embed a jpeg or png as front cover art in wav, flac or mp4 output"
+	)]
+	pub cover: Option<String>,
+
+	#[arg(
+		long = "apply",
+		value_name = "FILTER",
+		help = "Apply transform (e.g., gain=2.0, normalize)"
+	)]
+	pub transforms: Vec<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Keep only the ranges listed in an EDL file (text or JSON)"
+	)]
+	pub edl: Option<String>,
+
+	#[arg(long, value_name = "TIME", help = "Start at this timestamp (e.g., 1:30, 90s)")]
+	pub ss: Option<String>,
+
+	#[arg(long, value_name = "TIME", help = "Stop at this timestamp of the input")]
+	pub to: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		conflicts_with = "to",
+		help = "Stop after this much output (e.g., 10s)"
+	)]
+	pub duration: Option<String>,
+
+	#[arg(
+		long = "loop",
+		value_name = "N",
+		help = "Play the input N times, or 'inf' to repeat until --to/--duration"
+	)]
+	pub loop_count: Option<String>,
+
+	#[arg(
+		long = "auto-fade",
+		value_name = "DURATION",
+		help = "Fade audio in/out at each EDL boundary (e.g., 200ms)"
+	)]
+	pub auto_fade: Option<String>,
+
+	#[arg(
+		long = "metadata-stream",
+		value_name = "SPEC:KEY=VALUE",
+		help = "Set stream metadata, usually written as --metadata:s:a:0 language=eng"
+	)]
+	pub stream_metadata: Vec<String>,
+
+	#[arg(long, value_name = "CODEC", help = "Output codec, e.g. pcm_s24le for wav")]
+	pub codec: Option<String>,
+
+	#[arg(long, help = "Tag the output with ReplayGain track gain and peak (wav, flac)")]
+	pub replaygain: bool,
+
+	#[arg(long, help = "Write video frames in reverse order (y4m)")]
+	pub reverse: bool,
+
+	#[arg(
+		long,
+		value_name = "MODE",
+		num_args = 0..=1,
+		default_missing_value = "line",
+		help = "Report progress on stderr: line (default on a terminal), json or off"
+	)]
+	pub progress: Option<String>,
+
+	#[arg(
+		long,
+		help = "Report wall and CPU time, speed, packets and frames per second and peak memory on stderr"
+	)]
+	pub benchmark: bool,
+
+	#[arg(
+		long,
+		value_name = "N",
+		default_value_t = 1,
+		help = "Run decoding, transforms and encoding on up to N threads (0 for one per core)"
+	)]
+	pub threads: usize,
+
+	#[arg(long, help = "Output in JSON format")]
+	pub json: bool,
+
+	#[arg(long, value_name = "FILE", help = "Composite a second y4m onto every frame")]
+	pub overlay: Option<String>,
+
+	#[arg(
+		long = "overlay-at",
+		value_name = "X,Y",
+		default_value = "0,0",
+		requires = "overlay",
+		help = "Top-left position of --overlay"
+	)]
+	pub overlay_at: String,
+
+	#[arg(
+		long = "overlay-alpha",
+		value_name = "ALPHA",
+		default_value = "1",
+		requires = "overlay",
+		help = "Opacity of --overlay from 0 to 1"
+	)]
+	pub overlay_alpha: f32,
+
+	#[arg(
+		long = "filter-graph",
+		value_name = "GRAPH",
+		help = "Run a filter graph after --apply, e.g. '[in]split[a][b];[a]scale=64x36[s];[b][s]overlay=8:8[out]'"
+	)]
+	pub filter_graph: Option<String>,
+
+	#[arg(long, value_name = "FILE", help = "Append another audio input after the -i inputs")]
+	pub concat: Option<String>,
+
+	#[arg(long, value_name = "DURATION", help = "Overlap joined inputs by this much (e.g., 500ms)")]
+	pub crossfade: Option<String>,
+
+	#[command(flatten)]
+	pub common: CommonArgs,
+}
+
+// FILE comes first and any -i after it; OUTPUT likewise before any -o
+impl From<ConvertArgs> for Args {
+	fn from(convert: ConvertArgs) -> Self {
+		let mut outputs = convert.output.into_iter().chain(convert.outputs);
+		Args {
+			input: convert.file,
+			more_inputs: convert.inputs,
+			output: outputs.next(),
+			more_outputs: outputs.collect(),
+			format: convert.format,
+			input_format: convert.input_format,
+			overwrite: convert.overwrite.overwrite,
+			no_overwrite: convert.overwrite.no_overwrite,
+			preset: convert.preset,
+			cover: convert.cover,
+			transforms: convert.transforms,
+			edl: convert.edl,
+			ss: convert.ss,
+			to: convert.to,
+			duration: convert.duration,
+			loop_count: convert.loop_count,
+			auto_fade: convert.auto_fade,
+			stream_metadata: convert.stream_metadata,
+			codec: convert.codec,
+			replaygain: convert.replaygain,
+			reverse: convert.reverse,
+			progress: convert.progress,
+			benchmark: convert.benchmark,
+			threads: convert.threads,
+			json: convert.json,
+			overlay: convert.overlay,
+			overlay_at: convert.overlay_at,
+			overlay_alpha: convert.overlay_alpha,
+			filter_graph: convert.filter_graph,
+			concat: convert.concat,
+			crossfade: convert.crossfade,
+			..Args::from(convert.common)
+		}
+	}
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Print the streams and first frames of a file")]
+pub struct ProbeArgs {
+	#[arg(value_name = "FILE", help = "Input file, or - for stdin")]
+	pub file: String,
+
+	#[arg(
+		short,
+		long,
+		value_name = "FORMAT",
+		help = "Container of the input, overriding its extension: wav, flac, y4m, avi, mp4"
+	)]
+	pub format: Option<String>,
+
+	#[arg(long, help = "Output in JSON format")]
+	pub json: bool,
+
+	#[arg(long, value_name = "FORMAT", help = "Print as human (default), json, csv or xml")]
+	pub print_format: Option<String>,
+
+	#[arg(long, value_name = "TOOL", help = "Print JSON in another tool's layout: ffprobe")]
+	pub compat: Option<String>,
+
+	#[arg(long, help = "List every packet: stream, byte offset, size, pts, dts, keyframe")]
+	pub packets: bool,
+
+	#[arg(long, help = "Read every packet to count frames and time wav and y4m exactly")]
+	pub count_frames: bool,
+
+	#[arg(
+		long,
+		conflicts_with = "packets",
+		help = "Print the RIFF chunk tree of a wav or avi: fourcc, offset, size, nesting"
+	)]
+	pub chunks: bool,
+
+	#[arg(
+		long,
+		value_name = "MODE",
+		num_args = 0..=1,
+		default_missing_value = "summary",
+		conflicts_with_all = ["packets", "chunks"],
+		help = "Print min, average and max bitrate per second: summary (default), sparkline or csv"
+	)]
+	pub bitrate: Option<String>,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["packets", "chunks", "bitrate"],
+		help = "List the keyframes of each video stream: time, pts, byte offset, size"
+	)]
+	pub keyframes: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["packets", "chunks", "bitrate", "keyframes"],
+		help = "Decode the audio and print integrated LUFS, true peak, RMS and loudness range"
+	)]
+	pub stats: bool,
+
+	#[arg(long, value_name = "INDEX", help = "Filter by stream index")]
+	pub stream: Option<usize>,
+
+	#[arg(long, value_name = "N", default_value = "10", help = "Number of frames to preview")]
+	pub frames: usize,
+
+	#[arg(long = "hex-limit", value_name = "N", default_value = "8", help = "Hex bytes per frame")]
+	pub hex_limit: usize,
+
+	#[arg(
+		long,
+		value_name = "UNITS",
+		default_value = "binary",
+		help = "Size units in show output (binary, decimal, bytes)"
+	)]
+	pub units: String,
+
+	#[arg(
+		long = "apply",
+		value_name = "FILTER",
+		help = "Apply transform (e.g., gain=2.0, normalize)"
+	)]
+	pub transforms: Vec<String>,
+
+	#[command(flatten)]
+	pub common: CommonArgs,
+}
+
+// the flag form's --show
+impl From<ProbeArgs> for Args {
+	fn from(probe: ProbeArgs) -> Self {
+		Args {
+			input: probe.file,
+			show: true,
+			format: probe.format,
+			json: probe.json,
+			print_format: probe.print_format,
+			compat: probe.compat,
+			packets: probe.packets,
+			count_frames: probe.count_frames,
+			chunks: probe.chunks,
+			bitrate: probe.bitrate,
+			keyframes: probe.keyframes,
+			stats: probe.stats,
+			stream: probe.stream,
+			frames: probe.frames,
+			hex_limit: probe.hex_limit,
+			units: probe.units,
+			transforms: probe.transforms,
+			..Args::from(probe.common)
+		}
+	}
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Convert every file of a glob or directory into -o")]
+pub struct BatchArgs {
+	#[arg(value_name = "GLOB|DIR", value_parser = many_inputs, help = "Glob pattern or directory")]
+	pub file: String,
+
+	#[arg(short, long, value_name = "DIR", help = "Output directory")]
+	pub output: Option<String>,
+
+	#[arg(
+		short,
+		long,
+		value_name = "FORMAT",
+		help = "Container of the outputs, overriding their extensions: wav, flac, y4m, avi, mp4"
+	)]
+	pub format: Option<String>,
+
+	#[arg(
+		long,
+		help = "Descend into subdirectories of a directory input, mirroring them in the output"
+	)]
+	pub recursive: bool,
+
+	#[arg(
+		long = "ext",
+		value_name = "EXT",
+		value_delimiter = ',',
+		help = "Only take files with these extensions, e.g. wav,flac"
+	)]
+	pub extensions: Vec<String>,
+
+	#[command(flatten)]
+	pub overwrite: OverwriteArgs,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Record finished batch items in FILE and skip the ones it lists"
+	)]
+	pub resume: Option<String>,
+
+	#[arg(
+		long = "on-error",
+		value_name = "POLICY",
+		default_value = "continue",
+		help = "When a file fails: continue with the rest or stop"
+	)]
+	pub on_error: String,
+
+	#[arg(
+		long,
+		value_name = "NAME",
+		help = "Settings bundle: podcast, archive, voicemail, web-video, or one from the config file"
+	)]
+	pub preset: Option<String>,
+
+	#[arg(
+		long = "apply",
+		value_name = "FILTER",
+		help = "Apply transform (e.g., gain=2.0, normalize)"
+	)]
+	pub transforms: Vec<String>,
+
+	#[arg(long, value_name = "CODEC", help = "Output codec, e.g. pcm_s24le for wav")]
+	pub codec: Option<String>,
+
+	#[command(flatten)]
+	pub common: CommonArgs,
+}
+
+impl From<BatchArgs> for Args {
+	fn from(batch: BatchArgs) -> Self {
+		Args {
+			input: batch.file,
+			output: batch.output,
+			format: batch.format,
+			recursive: batch.recursive,
+			extensions: batch.extensions,
+			overwrite: batch.overwrite.overwrite,
+			no_overwrite: batch.overwrite.no_overwrite,
+			resume: batch.resume,
+			on_error: batch.on_error,
+			preset: batch.preset,
+			transforms: batch.transforms,
+			codec: batch.codec,
+			..Args::from(batch.common)
+		}
+	}
+}
+
+#[derive(clap::Args, Debug)]
+#[command(
+	about = "Measure a file: audio levels by default, or --compare, --sample-frames or --contact-sheet"
+)]
+pub struct AnalyzeArgs {
+	#[arg(value_name = "FILE", help = "Input file, or - for stdin")]
+	pub file: String,
+
+	#[arg(short, long, value_name = "FILE", help = "Where --contact-sheet or --waveform is written")]
+	pub output: Option<String>,
+
+	#[arg(long, help = "Output in JSON format")]
+	pub json: bool,
+
+	#[arg(long, value_name = "FORMAT", help = "Print as human (default), json, csv or xml")]
+	pub print_format: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "MODE",
+		num_args = 0..=1,
+		default_missing_value = "summary",
+		help = "Print min, average and max bitrate per second: summary (default), sparkline or csv"
+	)]
+	pub bitrate: Option<String>,
+
+	#[arg(
+		long,
+		conflicts_with = "bitrate",
+		help = "Decode the audio and print integrated LUFS, true peak, RMS and loudness range"
+	)]
+	pub stats: bool,
+
+	#[arg(
+		long,
+		conflicts_with_all = ["bitrate", "stats"],
+		help = "Demux and decode the whole file, verify its CRCs and MD5, and report damaged frames"
+	)]
+	pub check: bool,
+
+	#[arg(long, value_name = "INDEX", help = "Filter by stream index")]
+	pub stream: Option<usize>,
+
+	#[arg(
+		long,
+		value_name = "UNITS",
+		default_value = "binary",
+		help = "Size units in show output (binary, decimal, bytes)"
+	)]
+	pub units: String,
+
+	#[arg(
+		long = "apply",
+		value_name = "FILTER",
+		help = "Apply transform (e.g., gain=2.0, normalize)"
+	)]
+	pub transforms: Vec<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "Report per-frame and average PSNR/SSIM of FILE against this y4m"
+	)]
+	pub compare: Option<String>,
+
+	#[arg(
+		long = "sample-frames",
+		value_name = "SPEC",
+		help = "Export video frames at an interval (e.g., every=2s,scale=256x256,crop=224x224)"
+	)]
+	pub sample_frames: Option<String>,
+
+	#[arg(
+		long = "contact-sheet",
+		value_name = "GRID",
+		help = "Write a grid of evenly spaced, timestamped frames to -o (e.g., 4x3,width=240)"
+	)]
+	pub contact_sheet: Option<String>,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		num_args = 0..=1,
+		default_missing_value = "-",
+		help = "Draw the audio's min/max peaks to a .png or .ppm, or in the terminal without a file"
+	)]
+	pub waveform: Option<String>,
+
+	#[arg(
+		long = "output-template",
+		value_name = "TEMPLATE",
+		default_value = "frames/{stem}_{n}.png",
+		help = "Image path for --sample-frames, with the input's {stem} and the sample number filled in as in the default"
+	)]
+	pub output_template: String,
+
+	#[command(flatten)]
+	pub common: CommonArgs,
+}
+
+// with nothing else asked for, levels per channel of every audio stream
+impl From<AnalyzeArgs> for Args {
+	fn from(analyze: AnalyzeArgs) -> Self {
+		let levels = analyze.compare.is_none()
+			&& analyze.sample_frames.is_none()
+			&& analyze.contact_sheet.is_none()
+			&& analyze.waveform.is_none()
+			&& analyze.bitrate.is_none()
+			&& !analyze.stats
+			&& !analyze.check;
+		let mut transforms = analyze.transforms;
+		if levels && !transforms.iter().any(|t| t == "astats") {
+			transforms.push("astats".to_string());
+		}
+		Args {
+			input: analyze.file,
+			show: levels,
+			output: analyze.output,
+			json: analyze.json,
+			print_format: analyze.print_format,
+			bitrate: analyze.bitrate,
+			stats: analyze.stats,
+			check: analyze.check,
+			stream: analyze.stream,
+			units: analyze.units,
+			transforms,
+			compare: analyze.compare,
+			sample_frames: analyze.sample_frames,
+			contact_sheet: analyze.contact_sheet,
+			waveform: analyze.waveform,
+			output_template: analyze.output_template,
+			..Args::from(analyze.common)
+		}
+	}
+}
+
+fn single_input(value: &str) -> Result<String, String> {
+	if is_batch_pattern(value) || is_directory(value) {
+		return Err(
+			"convert takes a single input; use `ffmpreg batch` for globs and directories".into(),
+		);
+	}
+	Ok(value.to_string())
+}
+
+fn many_inputs(value: &str) -> Result<String, String> {
+	if !is_batch_pattern(value) && !is_directory(value) {
+		return Err("batch takes a glob or a directory".into());
+	}
+	Ok(value.to_string())
+}
@@ -0,0 +1,374 @@
+use super::args::Args;
+use clap::builder::PossibleValue;
+use clap::{Arg, CommandFactory, ValueEnum};
+
+// the shells `ffmpreg completions` writes a script for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+	Bash,
+	Zsh,
+	Fish,
+}
+
+pub const SHELLS: [Shell; 3] = [Shell::Bash, Shell::Zsh, Shell::Fish];
+
+impl Shell {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"bash" => Some(Shell::Bash),
+			"zsh" => Some(Shell::Zsh),
+			"fish" => Some(Shell::Fish),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Shell::Bash => "bash",
+			Shell::Zsh => "zsh",
+			Shell::Fish => "fish",
+		}
+	}
+
+	// read off `cli()`, so a new flag or command completes without a change here
+	pub fn script(&self) -> String {
+		let cli = cli();
+		match self {
+			Shell::Bash => bash(&cli),
+			Shell::Zsh => zsh(&cli),
+			Shell::Fish => fish(&cli),
+		}
+	}
+}
+
+impl ValueEnum for Shell {
+	fn value_variants<'a>() -> &'a [Self] {
+		&SHELLS
+	}
+
+	fn to_possible_value(&self) -> Option<PossibleValue> {
+		Some(PossibleValue::new(self.name()))
+	}
+}
+
+// `ffmpreg completions <shell>`
+#[derive(clap::Args, Debug)]
+#[command(about = "Print a shell completion script on stdout")]
+pub struct CompletionsArgs {
+	#[arg(value_name = "SHELL", value_enum)]
+	pub shell: Shell,
+}
+
+// every way to call ffmpreg as one tree: the flag form at the root, the
+// commands and tools under it. completions and the man page are made from it
+pub fn cli() -> clap::Command {
+	let mut cli = Args::command();
+	cli.build();
+	cli
+}
+
+fn visible_commands(cli: &clap::Command) -> impl Iterator<Item = &clap::Command> {
+	cli.get_subcommands().filter(|command| !command.is_hide_set())
+}
+
+// every command of the tree, each with the commands leading down to it from
+// the root, so nested ones such as `bench decode` complete as well
+fn walk(cli: &clap::Command) -> Vec<Vec<&clap::Command>> {
+	let mut paths = vec![vec![cli]];
+	let mut i = 0;
+	while i < paths.len() {
+		let path = paths[i].clone();
+		let last = path[path.len() - 1];
+		paths.extend(last.get_subcommands().map(|command| [path.as_slice(), &[command]].concat()));
+		i += 1;
+	}
+	paths
+}
+
+// the name a command is typed as, and the aliases it also answers to
+fn names(command: &clap::Command) -> Vec<&str> {
+	std::iter::once(command.get_name()).chain(command.get_all_aliases()).collect()
+}
+
+// the identifier of a command in the scripts: its path joined by `__`
+fn id(path: &[&clap::Command]) -> String {
+	path.iter().map(|command| command.get_name()).collect::<Vec<_>>().join("__")
+}
+
+fn flags(command: &clap::Command) -> impl Iterator<Item = &Arg> {
+	command.get_arguments().filter(|arg| !arg.is_hide_set() && !arg.is_positional())
+}
+
+// every way a flag is written, its visible aliases included
+fn flag_names(arg: &Arg) -> Vec<String> {
+	let shorts =
+		arg.get_short().into_iter().chain(arg.get_visible_short_aliases().unwrap_or_default());
+	let longs = arg.get_long().into_iter().chain(arg.get_visible_aliases().unwrap_or_default());
+	let shorts = shorts.map(|short| format!("-{}", short));
+	shorts.chain(longs.map(|long| format!("--{}", long))).collect()
+}
+
+fn takes_value(arg: &Arg) -> bool {
+	arg.get_action().takes_values()
+}
+
+fn possible_values(arg: &Arg) -> Vec<String> {
+	let values = arg.get_possible_values();
+	values.iter().filter(|v| !v.is_hide_set()).map(|v| v.get_name().to_string()).collect()
+}
+
+// what the first positional argument of a command may be
+fn positional_values(command: &clap::Command) -> Vec<String> {
+	command.get_positionals().next().map(possible_values).unwrap_or_default()
+}
+
+// the word after the command: its own subcommands, or what its first
+// positional argument may be
+fn first_words(command: &clap::Command) -> Vec<String> {
+	let mut words: Vec<String> =
+		visible_commands(command).map(|command| command.get_name().to_string()).collect();
+	words.extend(positional_values(command));
+	words
+}
+
+fn help(arg: &Arg) -> String {
+	let help = arg.get_help().map(|help| help.to_string()).unwrap_or_default();
+	help.lines().next().unwrap_or_default().to_string()
+}
+
+fn about(command: &clap::Command) -> String {
+	let about = command.get_about().map(|about| about.to_string()).unwrap_or_default();
+	about.lines().next().unwrap_or_default().to_string()
+}
+
+// the words before the cursor walk down the tree to the innermost command
+// typed; its flags, and its subcommands right after it, are then offered
+fn bash(cli: &clap::Command) -> String {
+	let paths = walk(cli);
+	let options =
+		|command: &clap::Command| flags(command).flat_map(flag_names).collect::<Vec<_>>().join(" ");
+
+	let mut script = String::from("_ffmpreg() {\n\tlocal cur prev cmd depth opts words i\n");
+	script.push_str("\tcur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+	script.push_str("\tprev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+	script.push_str(&format!("\tcmd=\"{}\"\n\tdepth=0\n", cli.get_name()));
+	script.push_str(
+		"\tfor ((i = 1; i < COMP_CWORD; i++)); do\n\t\tcase \"${cmd},${COMP_WORDS[i]}\" in\n",
+	);
+	for path in paths.iter().filter(|path| path.len() > 1) {
+		let parent = id(&path[..path.len() - 1]);
+		let patterns: Vec<String> =
+			names(path[path.len() - 1]).iter().map(|name| format!("{},{}", parent, name)).collect();
+		script.push_str(&format!(
+			"\t\t\t{})\n\t\t\t\tcmd=\"{}\"\n\t\t\t\tdepth=$i\n\t\t\t\t;;\n",
+			patterns.join("|"),
+			id(path)
+		));
+	}
+	script.push_str("\t\tesac\n\tdone\n");
+
+	script.push_str("\tcase \"$cmd\" in\n");
+	for path in &paths {
+		let command = path[path.len() - 1];
+		script.push_str(&format!(
+			"\t\t{})\n\t\t\topts=\"{}\"\n\t\t\twords=\"{}\"\n\t\t\t;;\n",
+			id(path),
+			options(command),
+			first_words(command).join(" ")
+		));
+	}
+	script.push_str("\tesac\n");
+
+	// flags that take one of a few values, whichever command they belong to
+	let mut seen = Vec::new();
+	script.push_str("\tcase \"$prev\" in\n");
+	for path in &paths {
+		for arg in flags(path[path.len() - 1]) {
+			let values = possible_values(arg);
+			let pattern = flag_names(arg).join("|");
+			if values.is_empty() || seen.contains(&pattern) {
+				continue;
+			}
+			script.push_str(&format!(
+				"\t\t{})\n\t\t\tCOMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n\t\t\treturn\n\t\t\t;;\n",
+				pattern,
+				values.join(" ")
+			));
+			seen.push(pattern);
+		}
+	}
+	script.push_str("\tesac\n");
+
+	script.push_str(concat!(
+		"\tif [[ \"$cur\" == -* ]]; then\n",
+		"\t\tCOMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n",
+		"\telif [[ $COMP_CWORD -eq $((depth + 1)) && -n \"$words\" ]]; then\n",
+		"\t\tCOMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n",
+		"\telse\n",
+		"\t\tCOMPREPLY=($(compgen -f -- \"$cur\"))\n",
+		"\tfi\n",
+		"}\n\n",
+		"complete -o filenames -F _ffmpreg ffmpreg\n"
+	));
+	script
+}
+
+// inside a single quoted zsh word and the brackets of an _arguments spec
+fn zsh_escape(text: &str) -> String {
+	text
+		.replace('\\', "\\\\")
+		.replace('\'', "'\\''")
+		.replace('[', "\\[")
+		.replace(']', "\\]")
+		.replace(':', "\\:")
+}
+
+fn zsh_spec(arg: &Arg) -> String {
+	let repeats = matches!(arg.get_action(), clap::ArgAction::Append | clap::ArgAction::Count);
+	let value = if !takes_value(arg) {
+		String::new()
+	} else {
+		let name = arg.get_value_names().and_then(|names| names.first()).map(|name| name.to_string());
+		let name = name.unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+		let values = possible_values(arg);
+		let action = if !values.is_empty() {
+			format!("({})", values.join(" "))
+		} else if ["FILE", "DIR", "PATH"].iter().any(|kind| name.contains(kind)) {
+			"_files".to_string()
+		} else {
+			" ".to_string()
+		};
+		format!(":{}:{}", zsh_escape(&name), action)
+	};
+	let help = format!("[{}]{}", zsh_escape(&help(arg)), value);
+	let names = flag_names(arg);
+	match names.as_slice() {
+		[] => String::new(),
+		[name] if repeats => format!("'*{}{}'", name, help),
+		[name] => format!("'{}{}'", name, help),
+		names if repeats => format!("'*'{{{}}}'{}'", names.join(","), help),
+		names => format!("'({})'{{{}}}'{}'", names.join(" "), names.join(","), help),
+	}
+}
+
+fn zsh_arguments(command: &clap::Command, indent: &str) -> String {
+	let mut specs: Vec<String> = flags(command).map(zsh_spec).filter(|s| !s.is_empty()).collect();
+	let words = first_words(command);
+	if !words.is_empty() {
+		specs.push(format!("':{}:({})'", command.get_name(), words.join(" ")));
+	}
+	specs.push("'*:file:_files'".to_string());
+	let separator = format!(" \\\n{}\t", indent);
+	format!("{}_arguments -s{}{}\n", indent, separator, specs.join(&separator))
+}
+
+// a function per command: one with subcommands describes them and hands the
+// rest of the line to the one typed, with the words shifted down to it
+fn zsh_function(path: &[&clap::Command]) -> String {
+	let command = path[path.len() - 1];
+	let mut function = format!("_{}() {{\n", id(path));
+	if command.get_subcommands().next().is_none() {
+		function.push_str(&zsh_arguments(command, "\t"));
+		function.push_str("}\n");
+		return function;
+	}
+
+	function.push_str("\tlocal -a commands\n\tcommands=(\n");
+	for sub in visible_commands(command) {
+		function.push_str(&format!("\t\t'{}:{}'\n", sub.get_name(), zsh_escape(&about(sub))));
+	}
+	function.push_str("\t)\n");
+	let display: Vec<&str> = path.iter().map(|command| command.get_name()).collect();
+	function.push_str(&format!(
+		"\tif (( CURRENT == 2 )) && [[ $words[CURRENT] != -* ]]; then\n\t\t_describe -t commands '{} command' commands\n\t\treturn\n\tfi\n",
+		display.join(" ")
+	));
+	function.push_str("\tcase $words[2] in\n");
+	for sub in command.get_subcommands() {
+		let sub_path = [path, &[sub]].concat();
+		function.push_str(&format!(
+			"\t\t{})\n\t\t\tshift words\n\t\t\t(( CURRENT-- ))\n\t\t\t_{}\n\t\t\t;;\n",
+			names(sub).join("|"),
+			id(&sub_path)
+		));
+	}
+	function.push_str("\t\t*)\n");
+	function.push_str(&zsh_arguments(command, "\t\t\t"));
+	function.push_str("\t\t\t;;\n\tesac\n}\n");
+	function
+}
+
+fn zsh(cli: &clap::Command) -> String {
+	let mut script = format!("#compdef {}\n", cli.get_name());
+	for path in walk(cli) {
+		script.push('\n');
+		script.push_str(&zsh_function(&path));
+	}
+	script.push_str(&format!("\n_{} \"$@\"\n", cli.get_name()));
+	script
+}
+
+fn fish_escape(text: &str) -> String {
+	text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish_flag(condition: &str, arg: &Arg) -> String {
+	let mut line = format!("complete -c ffmpreg -n '{}'", condition);
+	for name in flag_names(arg) {
+		match name.strip_prefix("--") {
+			Some(long) => line.push_str(&format!(" -l {}", long)),
+			None => line.push_str(&format!(" -s {}", &name[1..])),
+		}
+	}
+	let values = possible_values(arg);
+	if !values.is_empty() {
+		line.push_str(&format!(" -x -a '{}'", values.join(" ")));
+	} else if takes_value(arg) {
+		line.push_str(" -r");
+	}
+	let help = help(arg);
+	if !help.is_empty() {
+		line.push_str(&format!(" -d '{}'", fish_escape(&help)));
+	}
+	line
+}
+
+// a command's completions apply once every command above it has been typed
+// and none below it has
+fn fish(cli: &clap::Command) -> String {
+	let mut script = String::from("complete -c ffmpreg -e\n");
+	for path in walk(cli) {
+		let command = path[path.len() - 1];
+		let mut conditions: Vec<String> = path[1..]
+			.iter()
+			.map(|command| format!("__fish_seen_subcommand_from {}", names(command).join(" ")))
+			.collect();
+		let below: Vec<&str> = command.get_subcommands().flat_map(names).collect();
+		if !below.is_empty() {
+			conditions.push(format!("not __fish_seen_subcommand_from {}", below.join(" ")));
+		}
+		let condition = conditions.join("; and ");
+
+		for sub in visible_commands(command) {
+			script.push_str(&format!(
+				"complete -c ffmpreg -n '{}' -f -a {} -d '{}'\n",
+				condition,
+				sub.get_name(),
+				fish_escape(&about(sub))
+			));
+		}
+		let values = positional_values(command);
+		if !values.is_empty() {
+			script.push_str(&format!(
+				"complete -c ffmpreg -n '{}' -f -a '{}'\n",
+				condition,
+				values.join(" ")
+			));
+		}
+		for arg in flags(command) {
+			script.push_str(&fish_flag(&condition, arg));
+			script.push('\n');
+		}
+	}
+	script
+}
@@ -0,0 +1,117 @@
+use super::job::JobConfig;
+use super::preset::{BUILTIN_PRESETS, Preset};
+use crate::core::LogLevel;
+use crate::io::json::JsonValue;
+use crate::io::{IoError, IoResult, toml};
+use std::path::{Path, PathBuf};
+
+// the user's settings file, so a team can share one set of defaults. flags on
+// the command line win over it; its transforms run ahead of --apply
+//
+//   output = "converted/"
+//   log-level = "info"
+//   apply = ["dcremove"]
+//
+//   [[preset]]
+//   name = "interview"
+//   rate = 48000
+//
+//   [[job]]
+//   input = "inbox/*.wav"
+//   output = "mastered/"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+	// directory a batch or --watch writes to without -o, and a single
+	// conversion writes into under the input's name
+	pub output: Option<String>,
+	pub log_level: Option<LogLevel>,
+	pub apply: Vec<String>,
+	// one of the same name as a built-in replaces it
+	pub presets: Vec<Preset>,
+	// run by --job without a file
+	pub jobs: Vec<JobConfig>,
+}
+
+impl Config {
+	pub fn parse(text: &str) -> IoResult<Self> {
+		let root = toml::parse(text)?;
+		let Some(fields) = root.as_object() else {
+			return Err(IoError::invalid_data("config must be a table of options"));
+		};
+		let mut config = Config::default();
+		for (key, value) in fields {
+			match key.as_str() {
+				"output" => config.output = Some(string(value)?),
+				"log-level" => {
+					let level = LogLevel::from_name(&string(value)?);
+					config.log_level =
+						Some(level.ok_or_else(|| IoError::invalid_data("config names an unknown log-level"))?);
+				}
+				"apply" | "transforms" => match value {
+					JsonValue::Array(items) => {
+						config.apply.extend(items.iter().map(string).collect::<IoResult<Vec<_>>>()?)
+					}
+					value => config.apply.push(string(value)?),
+				},
+				"preset" => config.presets = tables(value, Preset::from_value)?,
+				"job" => config.jobs = tables(value, JobConfig::from_value)?,
+				_ => return Err(IoError::invalid_data("config has an unknown option")),
+			}
+		}
+		Ok(config)
+	}
+
+	pub fn load(path: &Path) -> IoResult<Self> {
+		Self::parse(&std::fs::read_to_string(path)?)
+	}
+
+	// $FFMPREG_CONFIG, else config.toml under $XDG_CONFIG_HOME/ffmpreg or
+	// ~/.config/ffmpreg
+	pub fn default_path() -> Option<PathBuf> {
+		if let Some(path) = std::env::var_os("FFMPREG_CONFIG") {
+			return Some(PathBuf::from(path));
+		}
+		let base = std::env::var_os("XDG_CONFIG_HOME")
+			.map(PathBuf::from)
+			.or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+		Some(base.join("ffmpreg").join("config.toml"))
+	}
+
+	// the file at `default_path`, or nothing when there is none
+	pub fn load_default() -> IoResult<Self> {
+		match Self::default_path() {
+			Some(path) if path.is_file() => Self::load(&path),
+			_ => Ok(Self::default()),
+		}
+	}
+
+	pub fn preset(&self, name: &str) -> Option<Preset> {
+		self.presets.iter().rev().find(|p| p.name == name).cloned().or_else(|| Preset::builtin(name))
+	}
+
+	// built-ins first, then the config's own
+	pub fn preset_names(&self) -> Vec<String> {
+		let mut names: Vec<String> = BUILTIN_PRESETS.iter().map(|n| n.to_string()).collect();
+		for preset in &self.presets {
+			if !names.contains(&preset.name) {
+				names.push(preset.name.clone());
+			}
+		}
+		names
+	}
+}
+
+// a [[table]] list
+fn tables<T>(value: &JsonValue, parse: fn(&JsonValue) -> IoResult<T>) -> IoResult<Vec<T>> {
+	match value {
+		JsonValue::Array(items) => items.iter().map(parse).collect(),
+		_ => Err(IoError::invalid_data("config 'preset' and 'job' must be [[table]] lists")),
+	}
+}
+
+fn string(value: &JsonValue) -> IoResult<String> {
+	value
+		.as_str()
+		.map(str::to_string)
+		.ok_or_else(|| IoError::invalid_data("config option must be a string"))
+}
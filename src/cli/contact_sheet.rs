@@ -0,0 +1,113 @@
+use super::sample::write_image;
+use crate::codecs::RawVideoDecoder;
+use crate::container::{MediaType, Y4mReader};
+use crate::core::{Decoder, Demuxer, Transform};
+use crate::image::RgbImage;
+use crate::io::{FileAdapter, IoError, IoResult};
+use crate::transform::{DrawText, Scale};
+
+// dark grey between and around the tiles
+const GAP: u32 = 4;
+const BACKGROUND: u8 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactSheetSpec {
+	pub columns: u32,
+	pub rows: u32,
+	pub tile_width: u32,
+}
+
+impl ContactSheetSpec {
+	// 4x3[,width=240]: columns x rows, tile width in pixels
+	pub fn parse(spec: &str) -> IoResult<Self> {
+		let mut parts = spec.split(',').map(str::trim);
+		let grid = parts.next().unwrap_or("");
+		let (columns, rows) = grid
+			.split_once('x')
+			.and_then(|(c, r)| Some((c.parse::<u32>().ok()?, r.parse::<u32>().ok()?)))
+			.filter(|&(c, r)| c > 0 && r > 0)
+			.ok_or_else(|| {
+				IoError::invalid_data("contact sheet grid must be COLUMNSxROWS (e.g., 4x3)")
+			})?;
+
+		let mut tile_width = 240;
+		for part in parts.filter(|p| !p.is_empty()) {
+			match part.split_once('=') {
+				Some(("width", value)) => {
+					tile_width = value
+						.parse()
+						.ok()
+						.filter(|w| *w >= 16)
+						.ok_or_else(|| IoError::invalid_data("contact sheet width must be at least 16"))?
+				}
+				_ => return Err(IoError::invalid_data("unknown contact sheet option")),
+			}
+		}
+		Ok(Self { columns, rows, tile_width })
+	}
+}
+
+// one frame per tile, evenly spaced over the video, each scaled down and
+// stamped with its timestamp
+pub fn contact_sheet(input: &str, spec: &ContactSheetSpec) -> IoResult<RgbImage> {
+	if MediaType::from_extension(input) != MediaType::Y4m {
+		return Err(IoError::invalid_data("contact sheets need a decodable video input (y4m)"));
+	}
+	let mut reader = Y4mReader::new(FileAdapter::open(input)?)?;
+	let format = reader.format();
+	let mut total = 0u64;
+	while reader.read_packet()?.is_some() {
+		total += 1;
+	}
+	if total == 0 {
+		return Err(IoError::invalid_data("input has no frames"));
+	}
+
+	let tiles = (spec.columns * spec.rows) as u64;
+	let picks: Vec<u64> = (0..tiles.min(total)).map(|i| i * total / tiles.min(total)).collect();
+	let tile_w = spec.tile_width;
+	let tile_h =
+		((tile_w as u64 * format.height as u64 / format.width.max(1) as u64) as u32 / 2 * 2).max(2);
+	let scale = Scale::new(format.width, format.height, tile_w, tile_h);
+	let mut stamp = DrawText::new("%{pts}").with_position(2, 2).with_scale(1);
+
+	let sheet_w = spec.columns * tile_w + (spec.columns + 1) * GAP;
+	let sheet_h = spec.rows * tile_h + (spec.rows + 1) * GAP;
+	let mut sheet =
+		RgbImage::new(sheet_w, sheet_h, vec![BACKGROUND; (sheet_w * sheet_h * 3) as usize]);
+
+	let mut reader = Y4mReader::new(FileAdapter::open(input)?)?;
+	let mut decoder = RawVideoDecoder::new(format);
+	let (mut index, mut placed) = (0u64, 0u32);
+	while let Some(packet) = reader.read_packet()? {
+		let wanted = picks.get(placed as usize) == Some(&index);
+		index += 1;
+		if !wanted {
+			continue;
+		}
+		let Some(frame) = decoder.decode(packet)? else {
+			continue;
+		};
+		let frame = stamp.apply(scale.apply_yuv420(&frame)?)?;
+		let Some(video) = frame.video() else {
+			continue;
+		};
+		let tile = RgbImage::from_frame(video)?;
+		let (x0, y0) = (
+			GAP + (placed % spec.columns) * (tile_w + GAP),
+			GAP + (placed / spec.columns) * (tile_h + GAP),
+		);
+		for row in 0..tile_h {
+			let src = (row * tile_w * 3) as usize;
+			let dst = (((y0 + row) * sheet_w + x0) * 3) as usize;
+			sheet.data[dst..dst + (tile_w * 3) as usize]
+				.copy_from_slice(&tile.data[src..src + (tile_w * 3) as usize]);
+		}
+		placed += 1;
+	}
+	Ok(sheet)
+}
+
+pub fn write_contact_sheet(input: &str, spec: &ContactSheetSpec, output: &str) -> IoResult<()> {
+	write_image(output, &contact_sheet(input, spec)?)
+}
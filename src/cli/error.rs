@@ -0,0 +1,192 @@
+use crate::io::IoError;
+use crate::io::json::escape;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// what went wrong, as far as a script wrapping the cli needs to know. each
+// kind exits with its own code; anything that fits none of them exits 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+	Failure,
+	// a flag, argument or config value that cannot work
+	InvalidInput,
+	// a container, codec or conversion this build does not handle
+	Unsupported,
+	// a file that cannot be opened, read or written
+	Io,
+	// media that is damaged or is not what its name says
+	CorruptData,
+}
+
+impl ErrorKind {
+	// 2 is also what a flag clap rejects exits with
+	pub fn exit_code(&self) -> i32 {
+		match self {
+			ErrorKind::Failure => 1,
+			ErrorKind::InvalidInput => 2,
+			ErrorKind::Unsupported => 3,
+			ErrorKind::Io => 4,
+			ErrorKind::CorruptData => 5,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			ErrorKind::Failure => "failure",
+			ErrorKind::InvalidInput => "invalid_input",
+			ErrorKind::Unsupported => "unsupported",
+			ErrorKind::Io => "io",
+			ErrorKind::CorruptData => "corrupt_data",
+		}
+	}
+
+	pub fn of(error: &std::io::Error) -> Self {
+		use std::io::ErrorKind as Io;
+		match error.kind() {
+			Io::InvalidInput => ErrorKind::InvalidInput,
+			Io::Unsupported => ErrorKind::Unsupported,
+			Io::InvalidData | Io::UnexpectedEof => ErrorKind::CorruptData,
+			Io::Other => ErrorKind::Failure,
+			_ => ErrorKind::Io,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+	// "Error: message"
+	#[default]
+	Text,
+	// {"error":{"code":3,"kind":"unsupported","message":"..."}}
+	Json,
+}
+
+impl ErrorFormat {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"text" => Some(ErrorFormat::Text),
+			"json" => Some(ErrorFormat::Json),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+	pub kind: ErrorKind,
+	pub message: String,
+}
+
+impl CliError {
+	pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+		Self { kind, message: message.into() }
+	}
+
+	pub fn invalid_input(message: impl Into<String>) -> Self {
+		Self::new(ErrorKind::InvalidInput, message)
+	}
+
+	// the first line of what clap would print, minus its "error: "
+	pub fn from_clap(error: &clap::Error) -> Self {
+		let text = error.render().to_string();
+		let line = text.lines().next().unwrap_or_default();
+		Self::invalid_input(line.strip_prefix("error: ").unwrap_or(line).trim())
+	}
+
+	pub fn from_io(error: &std::io::Error) -> Self {
+		Self::new(ErrorKind::of(error), error.to_string())
+	}
+
+	// a file named by a flag (a config, an edl, cover art) that does not parse
+	// is a bad argument rather than damaged media
+	pub fn from_argument_file(context: &str, error: std::io::Error) -> Self {
+		let kind = match ErrorKind::of(&error) {
+			ErrorKind::CorruptData => ErrorKind::InvalidInput,
+			kind => kind,
+		};
+		Self::new(kind, format!("{}: {}", context, error))
+	}
+
+	pub fn exit_code(&self) -> i32 {
+		self.kind.exit_code()
+	}
+
+	pub fn render(&self, format: ErrorFormat) -> String {
+		match format {
+			ErrorFormat::Text => format!("Error: {}", self.message),
+			ErrorFormat::Json => format!(
+				"{{\"error\":{{\"code\":{},\"kind\":\"{}\",\"message\":\"{}\"}}}}",
+				self.exit_code(),
+				self.kind.name(),
+				escape(&self.message)
+			),
+		}
+	}
+}
+
+impl std::fmt::Display for CliError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.message)
+	}
+}
+
+impl From<std::io::Error> for CliError {
+	fn from(error: std::io::Error) -> Self {
+		Self::from_io(&error)
+	}
+}
+
+impl From<IoError> for CliError {
+	fn from(error: IoError) -> Self {
+		Self::from_io(&error.into())
+	}
+}
+
+// process wide like the log level, so every exit path prints the same way
+static JSON: AtomicBool = AtomicBool::new(false);
+
+pub fn set_format(format: ErrorFormat) {
+	JSON.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+pub fn format() -> ErrorFormat {
+	if JSON.load(Ordering::Relaxed) { ErrorFormat::Json } else { ErrorFormat::Text }
+}
+
+// --error-format is picked out of argv before clap runs, so a flag clap
+// rejects is reported the way the caller asked. the last one given wins
+pub fn scan_format<I: IntoIterator<Item = String>>(args: I) -> Option<ErrorFormat> {
+	let mut format = None;
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		if arg == "--" {
+			break;
+		}
+		let value = match arg.strip_prefix("--error-format") {
+			Some("") => args.next(),
+			Some(rest) => rest.strip_prefix('=').map(str::to_string),
+			None => continue,
+		};
+		format = value.as_deref().and_then(ErrorFormat::from_name).or(format);
+	}
+	format
+}
+
+// help and version go out as clap prints them, and so does a rejected flag
+// unless json was asked for
+pub fn exit_clap(error: clap::Error) -> ! {
+	use clap::error::ErrorKind as Clap;
+	match error.kind() {
+		Clap::DisplayHelp | Clap::DisplayVersion | Clap::DisplayHelpOnMissingArgumentOrSubcommand => {
+			error.exit()
+		}
+		_ if format() == ErrorFormat::Text => error.exit(),
+		_ => exit(CliError::from_clap(&error)),
+	}
+}
+
+// prints the error on stderr in the chosen format and exits with its code
+pub fn exit(error: impl Into<CliError>) -> ! {
+	let error = error.into();
+	eprintln!("{}", error.render(format()));
+	std::process::exit(error.exit_code());
+}
@@ -0,0 +1,252 @@
+use super::pipeline::{Pipeline, expand_pattern, is_batch_pattern, is_directory};
+use crate::codecs::CodecId;
+use crate::core::parse_duration;
+use crate::edl::EditDecisionList;
+use crate::io::json::{self, JsonValue};
+use crate::io::{IoError, IoResult, Overwrite, toml};
+use std::path::Path;
+
+// one conversion in a job file. the keys are the command line flags without
+// their dashes:
+//
+//   [[job]]
+//   input = "raw/*.wav"
+//   output = "mastered/"
+//   apply = ["highpass=80", "loudnorm=-16,-1.5"]
+//   codec = "pcm_s24le"
+//
+// an input glob or an output directory makes it a batch job, writing each
+// match into the directory under its own name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobConfig {
+	pub input: String,
+	pub output: Option<String>,
+	pub apply: Vec<String>,
+	pub codec: Option<String>,
+	pub ss: Option<f64>,
+	pub to: Option<f64>,
+	pub duration: Option<f64>,
+	// None plays once; Some(None) repeats until `to` or `duration`
+	pub passes: Option<Option<u32>>,
+	pub auto_fade: Option<f64>,
+	pub edl: Option<String>,
+	pub filter_graph: Option<String>,
+	pub replaygain: bool,
+	pub reverse: bool,
+}
+
+// a list of jobs, run in order. TOML files list them as [[job]] tables and
+// JSON files as a "jobs" array; a file holding a single job may also put its
+// keys at the top level
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobFile {
+	pub jobs: Vec<JobConfig>,
+	// applies to every job; existing outputs are refused by default
+	pub overwrite: Overwrite,
+}
+
+impl JobFile {
+	pub fn load(path: &str) -> IoResult<Self> {
+		let text = std::fs::read_to_string(path)?;
+		let is_json = path.to_lowercase().ends_with(".json") || text.trim_start().starts_with('{');
+		if is_json { Self::parse_json(&text) } else { Self::parse_toml(&text) }
+	}
+
+	pub fn parse_toml(text: &str) -> IoResult<Self> {
+		Self::from_value(&toml::parse(text)?)
+	}
+
+	pub fn parse_json(text: &str) -> IoResult<Self> {
+		Self::from_value(&json::parse(text)?)
+	}
+
+	pub fn from_value(root: &JsonValue) -> IoResult<Self> {
+		let list = root.get("job").or_else(|| root.get("jobs"));
+		let jobs = match list {
+			Some(JsonValue::Array(items)) => {
+				items.iter().map(JobConfig::from_value).collect::<IoResult<Vec<_>>>()?
+			}
+			Some(_) => return Err(IoError::invalid_data("job file 'jobs' must be a list of tables")),
+			None => vec![JobConfig::from_value(root)?],
+		};
+		if jobs.is_empty() {
+			return Err(IoError::invalid_data("job file lists no jobs"));
+		}
+		Ok(Self { jobs, overwrite: Overwrite::Refuse })
+	}
+
+	pub fn with_overwrite(mut self, overwrite: Overwrite) -> Self {
+		self.overwrite = overwrite;
+		self
+	}
+
+	pub fn run(&self) -> std::io::Result<()> {
+		for job in &self.jobs {
+			job.run(self.overwrite)?;
+		}
+		Ok(())
+	}
+}
+
+impl JobConfig {
+	pub fn from_value(value: &JsonValue) -> IoResult<Self> {
+		let Some(fields) = value.as_object() else {
+			return Err(IoError::invalid_data("a job must be a table of options"));
+		};
+		let mut job = JobConfig::default();
+		for (key, value) in fields {
+			match key.as_str() {
+				"input" => job.input = string(value)?,
+				"output" => job.output = Some(string(value)?),
+				"apply" | "transforms" => job.apply.extend(strings(value)?),
+				"codec" => {
+					let name = string(value)?;
+					if CodecId::from_name(&name).is_none() {
+						return Err(IoError::invalid_data("job names an unknown codec"));
+					}
+					job.codec = Some(name);
+				}
+				"ss" => job.ss = Some(seconds(value)?),
+				"to" => job.to = Some(seconds(value)?),
+				"duration" => job.duration = Some(seconds(value)?),
+				"auto-fade" => job.auto_fade = Some(seconds(value)?),
+				"loop" => job.passes = Some(passes(value)?),
+				"edl" => job.edl = Some(string(value)?),
+				"filter-graph" => job.filter_graph = Some(string(value)?),
+				"replaygain" => job.replaygain = boolean(value)?,
+				"reverse" => job.reverse = boolean(value)?,
+				_ => return Err(IoError::invalid_data("job has an unknown option")),
+			}
+		}
+		if job.input.is_empty() {
+			return Err(IoError::invalid_data("job requires 'input'"));
+		}
+		if job.to.is_some() && job.duration.is_some() {
+			return Err(IoError::invalid_data("job sets both 'to' and 'duration'"));
+		}
+		Ok(job)
+	}
+
+	pub fn is_batch(&self) -> bool {
+		is_batch_pattern(&self.input)
+			|| self.output.as_deref().is_some_and(|output| output.ends_with('/') || is_directory(output))
+	}
+
+	// the transcoder for one input of this job
+	pub fn pipeline(&self, input: String, output: Option<String>) -> IoResult<Pipeline> {
+		let mut pipeline = Pipeline::new(input, output, false, self.apply.clone());
+		if let Some(name) = &self.codec
+			&& let Some(codec) = CodecId::from_name(name)
+		{
+			pipeline = pipeline.with_codec(codec);
+		}
+		if let Some(path) = &self.edl {
+			pipeline = pipeline.with_edl(EditDecisionList::load(path)?);
+		}
+		if self.ss.is_some() || self.to.is_some() || self.duration.is_some() {
+			let start = self.ss.unwrap_or(0.0);
+			pipeline = pipeline.with_trim(start, self.to.or(self.duration.map(|d| start + d)));
+		}
+		if let Some(passes) = self.passes {
+			pipeline = pipeline.with_loop(passes);
+		}
+		if let Some(seconds) = self.auto_fade {
+			pipeline = pipeline.with_auto_fade(seconds);
+		}
+		if let Some(graph) = &self.filter_graph {
+			pipeline = pipeline.with_filter_graph(graph.clone());
+		}
+		Ok(pipeline.with_replaygain(self.replaygain).with_reverse(self.reverse))
+	}
+
+	pub fn run(&self, overwrite: Overwrite) -> std::io::Result<()> {
+		let to_io = std::io::Error::from;
+		let skip = |output: &str| -> std::io::Result<bool> {
+			let skip = !overwrite.check(Path::new(output)).map_err(to_io)?;
+			if skip {
+				println!("  skipped: {} exists", output);
+			}
+			Ok(skip)
+		};
+		if !self.is_batch() {
+			println!("Processing: {}", self.input);
+			if let Some(output) = &self.output
+				&& skip(output)?
+			{
+				return Ok(());
+			}
+			self
+				.pipeline(self.input.clone(), self.output.clone())
+				.map_err(to_io)?
+				.with_overwrite(overwrite)
+				.run()?;
+			if let Some(output) = &self.output {
+				println!("  -> {}", output);
+			}
+			return Ok(());
+		}
+
+		let output_dir = self.output.clone().unwrap_or_else(|| "out".to_string());
+		let files = expand_pattern(&self.input)?;
+		if files.is_empty() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no files matching pattern: {}", self.input),
+			));
+		}
+		std::fs::create_dir_all(&output_dir)?;
+		for input in files {
+			let file_name = Path::new(&input).file_name().and_then(|n| n.to_str()).unwrap_or("output");
+			let output = Path::new(&output_dir).join(file_name).to_string_lossy().into_owned();
+			println!("Processing: {}", input);
+			if skip(&output)? {
+				continue;
+			}
+			self.pipeline(input, Some(output.clone())).map_err(to_io)?.with_overwrite(overwrite).run()?;
+			println!("  -> {}", output);
+		}
+		Ok(())
+	}
+}
+
+fn string(value: &JsonValue) -> IoResult<String> {
+	value
+		.as_str()
+		.map(str::to_string)
+		.ok_or_else(|| IoError::invalid_data("job option must be a string"))
+}
+
+// a single transform is accepted in place of a list
+fn strings(value: &JsonValue) -> IoResult<Vec<String>> {
+	match value {
+		JsonValue::Array(items) => items.iter().map(string).collect(),
+		value => Ok(vec![string(value)?]),
+	}
+}
+
+fn boolean(value: &JsonValue) -> IoResult<bool> {
+	value.as_bool().ok_or_else(|| IoError::invalid_data("job option must be true or false"))
+}
+
+// a number of seconds or a duration such as "1:30" or "500ms"
+fn seconds(value: &JsonValue) -> IoResult<f64> {
+	let seconds = match value {
+		JsonValue::Number(n) => Some(*n),
+		JsonValue::String(text) => parse_duration(text),
+		_ => None,
+	};
+	seconds
+		.filter(|s| s.is_finite() && *s >= 0.0)
+		.ok_or_else(|| IoError::invalid_data("job time must be seconds or a duration like 1:30"))
+}
+
+fn passes(value: &JsonValue) -> IoResult<Option<u32>> {
+	match value {
+		JsonValue::String(text) if text == "inf" => Ok(None),
+		JsonValue::Number(n) if *n == -1.0 => Ok(None),
+		JsonValue::Number(n) if *n >= 0.0 && n.fract() == 0.0 && *n <= u32::MAX as f64 => {
+			Ok(Some(*n as u32))
+		}
+		_ => Err(IoError::invalid_data("job 'loop' must be a count or \"inf\"")),
+	}
+}
@@ -0,0 +1,136 @@
+use super::error::ErrorKind;
+use clap::Arg;
+
+// `ffmpreg --help-man`: a man page in roff, from the same tree as the shell
+// completions, for packagers to install as ffmpreg.1
+pub fn render(cli: &clap::Command) -> String {
+	let name = cli.get_name();
+	let mut page = format!(
+		".TH {} 1 \"\" \"{} {}\" \"User Commands\"\n",
+		name.to_uppercase(),
+		name,
+		cli.get_version().unwrap_or_default()
+	);
+	page.push_str(&format!(".SH NAME\n{} \\- {}\n", name, escape(&about(cli))));
+
+	page.push_str(".SH SYNOPSIS\n");
+	page.push_str(&format!(
+		"\\fB{}\\fR [\\fIOPTIONS\\fR] \\fB\\-i\\fR \\fIFILE\\fR [\\fB\\-o\\fR \\fIFILE\\fR]\n",
+		name
+	));
+	page.push_str(&format!(
+		".br\n\\fB{}\\fR \\fICOMMAND\\fR [\\fIOPTIONS\\fR] \\fIFILE\\fR ...\n",
+		name
+	));
+	if let Some(text) = cli.get_after_help() {
+		page.push_str(&format!(".SH DESCRIPTION\n{}\n", escape(&text.to_string())));
+	}
+
+	page.push_str(".SH OPTIONS\n");
+	options(&mut page, cli);
+
+	page.push_str(".SH COMMANDS\n");
+	for command in cli.get_subcommands().filter(|command| !command.is_hide_set()) {
+		page.push_str(&format!(
+			".SS \"{} {}\"\n{}\n",
+			name,
+			command.get_name(),
+			escape(&about(command))
+		));
+		for sub in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+			page.push_str(&format!(".TP\n\\fB{}\\fR\n{}\n", escape(sub.get_name()), escape(&about(sub))));
+		}
+		options(&mut page, command);
+	}
+
+	page.push_str(".SH EXIT STATUS\n.TP\n0\nSuccess\n");
+	let kinds = [
+		(ErrorKind::Failure, "Any other failure"),
+		(ErrorKind::InvalidInput, "A flag, argument or config value that cannot work"),
+		(ErrorKind::Unsupported, "A container, codec or conversion this build does not handle"),
+		(ErrorKind::Io, "A file that cannot be opened, read or written"),
+		(ErrorKind::CorruptData, "Media that is damaged or is not what its name says"),
+	];
+	for (kind, text) in kinds {
+		page.push_str(&format!(
+			".TP\n{}\n{} (\\fB{}\\fR)\n",
+			kind.exit_code(),
+			text,
+			escape(kind.name())
+		));
+	}
+
+	if let Some(author) = cli.get_author() {
+		page.push_str(&format!(".SH AUTHOR\n{}\n", escape(author)));
+	}
+	page
+}
+
+// a .TP entry per visible argument, positionals first
+fn options(page: &mut String, command: &clap::Command) {
+	let args =
+		command.get_positionals().chain(command.get_arguments().filter(|arg| !arg.is_positional()));
+	for arg in args.filter(|arg| !arg.is_hide_set()) {
+		page.push_str(&format!(".TP\n{}\n", term(arg)));
+		let mut help = arg.get_help().map(|help| help.to_string()).unwrap_or_default();
+		let values: Vec<String> = arg
+			.get_possible_values()
+			.iter()
+			.filter(|value| !value.is_hide_set())
+			.map(|value| value.get_name().to_string())
+			.collect();
+		if !values.is_empty() {
+			help.push_str(&format!(" [possible values: {}]", values.join(", ")));
+		}
+		let defaults: Vec<String> =
+			arg.get_default_values().iter().map(|value| value.to_string_lossy().to_string()).collect();
+		if arg.get_action().takes_values()
+			&& !arg.is_hide_default_value_set()
+			&& defaults.iter().any(|value| !value.is_empty())
+		{
+			help.push_str(&format!(" [default: {}]", defaults.join(", ")));
+		}
+		page.push_str(&escape(&help));
+		page.push('\n');
+	}
+}
+
+// `-i, --input FILE` with the flags in bold and the value in italics
+fn term(arg: &Arg) -> String {
+	let value = arg
+		.get_value_names()
+		.and_then(|names| names.first())
+		.map(|name| name.to_string())
+		.unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+	if arg.is_positional() {
+		return format!("\\fI{}\\fR", escape(&value));
+	}
+	let mut names = Vec::new();
+	if let Some(short) = arg.get_short() {
+		names.push(format!("\\fB\\-{}\\fR", short));
+	}
+	if let Some(long) = arg.get_long() {
+		names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+	}
+	let mut term = names.join(", ");
+	if arg.get_action().takes_values() {
+		term.push_str(&format!(" \\fI{}\\fR", escape(&value)));
+	}
+	term
+}
+
+fn about(command: &clap::Command) -> String {
+	command.get_about().map(|about| about.to_string()).unwrap_or_default()
+}
+
+// backslashes and dashes read literally, and no line starting as a request
+fn escape(text: &str) -> String {
+	let text = text.replace('\\', "\\e").replace('-', "\\-");
+	let lines: Vec<String> = text
+		.lines()
+		.map(
+			|line| if line.starts_with(['.', '\'']) { format!("\\&{}", line) } else { line.to_string() },
+		)
+		.collect();
+	lines.join("\n")
+}
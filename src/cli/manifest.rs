@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+// the progress of a batch, kept on disk so an interrupted run can be resumed.
+// one line per event, appended and flushed as it happens:
+//
+//   started<TAB>input<TAB>output
+//   done<TAB>input<TAB>output
+//
+// an item started but never done was cut off mid-write, so its output is a
+// partial file the resumed run replaces
+pub struct BatchManifest {
+	file: File,
+	done: HashSet<(String, String)>,
+	started: HashSet<(String, String)>,
+}
+
+impl BatchManifest {
+	pub fn open(path: &Path) -> std::io::Result<Self> {
+		let mut done = HashSet::new();
+		let mut started = HashSet::new();
+		if path.is_file() {
+			for line in BufReader::new(File::open(path)?).lines() {
+				let line = line?;
+				let mut fields = line.split('\t');
+				let (Some(event), Some(input), Some(output)) =
+					(fields.next(), fields.next(), fields.next())
+				else {
+					// a line cut short by the interruption itself
+					continue;
+				};
+				let key = (unescape(input), unescape(output));
+				match event {
+					"started" => {
+						started.insert(key);
+					}
+					"done" => {
+						started.remove(&key);
+						done.insert(key);
+					}
+					_ => {}
+				}
+			}
+		}
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(Self { file, done, started })
+	}
+
+	pub fn is_done(&self, input: &str, output: &str) -> bool {
+		self.done.contains(&(input.to_string(), output.to_string()))
+	}
+
+	// started by an earlier run that never got to `finish`
+	pub fn is_unfinished(&self, input: &str, output: &str) -> bool {
+		self.started.contains(&(input.to_string(), output.to_string()))
+	}
+
+	pub fn start(&mut self, input: &str, output: &str) -> std::io::Result<()> {
+		self.record("started", input, output)
+	}
+
+	pub fn finish(&mut self, input: &str, output: &str) -> std::io::Result<()> {
+		self.record("done", input, output)?;
+		self.started.remove(&(input.to_string(), output.to_string()));
+		self.done.insert((input.to_string(), output.to_string()));
+		Ok(())
+	}
+
+	fn record(&mut self, event: &str, input: &str, output: &str) -> std::io::Result<()> {
+		writeln!(self.file, "{}\t{}\t{}", event, escape(input), escape(output))?;
+		self.file.flush()
+	}
+}
+
+// tabs and newlines in a path would split its line
+fn escape(text: &str) -> String {
+	text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			out.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('t') => out.push('\t'),
+			Some('n') => out.push('\n'),
+			Some(other) => out.push(other),
+			None => out.push('\\'),
+		}
+	}
+	out
+}
@@ -1,5 +1,31 @@
 pub mod args;
+pub mod bench;
+pub mod command;
+pub mod completions;
+pub mod config;
+pub mod contact_sheet;
+pub mod error;
+pub mod job;
+pub mod man;
+pub mod manifest;
 pub mod pipeline;
+pub mod preset;
+pub mod preview;
+pub mod sample;
+pub mod slice;
+pub mod template;
+pub mod vad;
+pub mod watch;
+pub mod waveform;
 
-pub use args::Args;
-pub use pipeline::{BatchPipeline, Pipeline, is_batch_pattern, is_directory};
+// moved into the library, re-exported under their old paths
+pub use crate::show::units as format;
+pub use crate::transcode::metadata;
+
+pub use args::{Args, normalize_args};
+pub use config::Config;
+pub use job::{JobConfig, JobFile};
+pub use metadata::StreamMetadata;
+pub use pipeline::{
+	BatchPipeline, BatchReport, ErrorPolicy, Pipeline, is_batch_pattern, is_directory,
+};
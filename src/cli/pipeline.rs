@@ -1,587 +1,467 @@
-use crate::codecs::{PcmDecoder, PcmEncoder, RawVideoDecoder, RawVideoEncoder};
-use crate::container::{
-	AviReader, AviWriter, FlacFormat, FlacReader, FlacWriter, Mp4Reader, Mp4Writer, WavReader,
-	WavWriter, Y4mReader, Y4mWriter,
-};
-use crate::core::{Decoder, Demuxer, Encoder, Muxer, Timebase, Transform};
-use crate::io::{
-	BufferedWriter, IoError, IoErrorKind, IoResult, MediaRead, MediaSeek, MediaWrite, SeekFrom,
-};
-use crate::transform::{TransformChain, parse_transform};
-use std::fs::File;
-use std::path::Path;
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum MediaType {
-	Wav,
-	Y4m,
-	Flac,
-	Avi,
-	Mp4,
-	Unknown,
-}
-
-impl MediaType {
-	pub fn from_extension(path: &str) -> Self {
-		let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
-		match ext.as_str() {
-			"wav" => MediaType::Wav,
-			"y4m" => MediaType::Y4m,
-			"flac" => MediaType::Flac,
-			"avi" => MediaType::Avi,
-			"mp4" | "m4a" | "m4v" => MediaType::Mp4,
-			_ => MediaType::Unknown,
-		}
-	}
-
-	pub fn is_audio(&self) -> bool {
-		matches!(self, MediaType::Wav | MediaType::Flac)
-	}
+use super::manifest::BatchManifest;
+use super::template::{TemplateVars, is_template, render};
+use crate::codecs::CodecId;
+use crate::core::log;
+use crate::io::{AllowAll, Overwrite, PathAccess, PathPolicy};
+use crate::show::units::{SizeUnits, format_size, format_thousands};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// the transcoder and file helpers moved into the library; these names stay
+// so existing callers keep compiling
+pub use crate::container::MediaType;
+pub use crate::io::FileAdapter;
+pub use crate::transcode::Transcoder as Pipeline;
 
-	pub fn is_video(&self) -> bool {
-		matches!(self, MediaType::Y4m | MediaType::Avi | MediaType::Mp4)
-	}
+pub struct BatchPipeline {
+	input_pattern: String,
+	output_dir: String,
+	show_mode: bool,
+	transforms: Vec<String>,
+	format: Option<MediaType>,
+	// container of the outputs, replacing each input's extension
+	output_format: Option<MediaType>,
+	codec: Option<CodecId>,
+	// a directory input descends into subdirectories, mirrored under the output
+	recursive: bool,
+	// lowercase extensions to keep; empty keeps every known media type
+	extensions: Vec<String>,
+	overwrite: Overwrite,
+	// manifest of finished items; see `BatchManifest`
+	resume: Option<PathBuf>,
+	on_error: ErrorPolicy,
+	path_policy: Arc<dyn PathPolicy>,
 }
 
-pub struct FileAdapter {
-	file: File,
+// what a batch does when one file fails to convert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+	// note the failure and go on with the next file
+	#[default]
+	Continue,
+	// leave the remaining files unconverted
+	Stop,
 }
 
-impl FileAdapter {
-	pub fn open(path: &str) -> IoResult<Self> {
-		let file = File::open(path)?;
-		Ok(Self { file })
-	}
-
-	pub fn create(path: &str) -> IoResult<Self> {
-		let file = File::create(path)?;
-		Ok(Self { file })
+impl ErrorPolicy {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"continue" => Some(ErrorPolicy::Continue),
+			"stop" | "abort" => Some(ErrorPolicy::Stop),
+			_ => None,
+		}
 	}
 }
 
-impl MediaRead for FileAdapter {
-	fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
-		use std::io::Read;
-		self.file.read(buf).map_err(IoError::from)
-	}
+// how a batch went, item by item
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchReport {
+	pub files: usize,
+	pub converted: usize,
+	// the output existed and -n said to leave it
+	pub skipped: usize,
+	// finished in an earlier run, per the resume manifest
+	pub resumed: usize,
+	// input path and the error it failed with
+	pub failed: Vec<(String, String)>,
+	// not attempted because an earlier file failed under `ErrorPolicy::Stop`
+	pub not_run: usize,
+	pub bytes: u64,
 }
 
-impl MediaWrite for FileAdapter {
-	fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
-		use std::io::Write;
-		self.file.write(buf).map_err(IoError::from)
+impl BatchReport {
+	pub fn is_success(&self) -> bool {
+		self.failed.is_empty()
 	}
 
-	fn flush(&mut self) -> IoResult<()> {
-		use std::io::Write;
-		self.file.flush().map_err(IoError::from)
-	}
-}
-
-impl MediaSeek for FileAdapter {
-	fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
-		use std::io::Seek;
-		self.file.seek(pos.into()).map_err(IoError::from)
+	// "Processed 12 files: 9 converted, 1 skipped, 2 failed, 3.1 MiB written"
+	// and a line per failure
+	pub fn summary(&self) -> String {
+		let mut counts = vec![format!("{} converted", format_thousands(self.converted as u64))];
+		let mut count = |n: usize, what: &str| {
+			if n > 0 {
+				counts.push(format!("{} {}", format_thousands(n as u64), what));
+			}
+		};
+		count(self.skipped, "skipped");
+		count(self.resumed, "already done");
+		count(self.failed.len(), "failed");
+		count(self.not_run, "not run");
+		let mut summary = format!(
+			"Processed {} files: {}, {} written",
+			format_thousands(self.files as u64),
+			counts.join(", "),
+			format_size(self.bytes, SizeUnits::default())
+		);
+		for (input, reason) in &self.failed {
+			summary.push_str(&format!("\n  failed: {}: {}", input, reason));
+		}
+		summary
 	}
 }
 
-pub struct Pipeline {
-	input_path: String,
-	output_path: Option<String>,
-	show_mode: bool,
-	transforms: Vec<String>,
+// what happens to one item of a batch
+enum Step {
+	Run(Overwrite),
+	Exists,
+	Done,
 }
 
-impl Pipeline {
+impl BatchPipeline {
 	pub fn new(
-		input_path: String,
-		output_path: Option<String>,
+		input_pattern: String,
+		output_dir: String,
 		show_mode: bool,
 		transforms: Vec<String>,
 	) -> Self {
-		Self { input_path, output_path, show_mode, transforms }
+		Self {
+			input_pattern,
+			output_dir,
+			show_mode,
+			transforms,
+			format: None,
+			output_format: None,
+			codec: None,
+			recursive: false,
+			extensions: Vec::new(),
+			overwrite: Overwrite::Refuse,
+			resume: None,
+			on_error: ErrorPolicy::default(),
+			path_policy: Arc::new(AllowAll),
+		}
 	}
 
-	pub fn run(&self) -> std::io::Result<()> {
-		self.run_io().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+	// read and write every file of the batch as this container
+	pub fn with_format(mut self, format: MediaType) -> Self {
+		self.format = Some(format);
+		self
 	}
 
-	fn run_io(&self) -> IoResult<()> {
-		let input_type = MediaType::from_extension(&self.input_path);
-		let output_type =
-			self.output_path.as_ref().map(|p| MediaType::from_extension(p)).unwrap_or(input_type);
-
-		if self.show_mode {
-			return self.run_show(input_type);
-		}
-
-		match (input_type, output_type) {
-			(MediaType::Wav, MediaType::Wav) => self.run_wav_to_wav(),
-			(MediaType::Wav, MediaType::Flac) => self.run_wav_to_flac(),
-			(MediaType::Flac, MediaType::Wav) => self.run_flac_to_wav(),
-			(MediaType::Flac, MediaType::Flac) => self.run_flac_to_flac(),
-			(MediaType::Y4m, MediaType::Y4m) => self.run_y4m_transcode(),
-			(MediaType::Avi, MediaType::Avi) => self.run_avi_passthrough(),
-			(MediaType::Mp4, MediaType::Mp4) => self.run_mp4_passthrough(),
-			(_, _) => {
-				Err(IoError::with_message(IoErrorKind::InvalidData, "unsupported format conversion"))
-			}
-		}
+	// write every output as this container, named with its extension
+	pub fn with_output_format(mut self, format: MediaType) -> Self {
+		self.output_format = Some(format);
+		self
 	}
 
-	fn run_show(&self, media_type: MediaType) -> IoResult<()> {
-		match media_type {
-			MediaType::Wav => self.run_wav_show(),
-			MediaType::Flac => self.run_flac_show(),
-			MediaType::Y4m => self.run_y4m_show(),
-			MediaType::Avi => self.run_avi_show(),
-			MediaType::Mp4 => self.run_mp4_show(),
-			MediaType::Unknown => {
-				Err(IoError::with_message(IoErrorKind::InvalidData, "unsupported file format"))
-			}
-		}
+	pub fn with_codec(mut self, codec: CodecId) -> Self {
+		self.codec = Some(codec);
+		self
 	}
 
-	fn run_wav_show(&self) -> IoResult<()> {
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = WavReader::new(input)?;
-		let format = reader.format();
-		let mut decoder = PcmDecoder::new(format);
-
-		println!("Format: WAV");
-		println!("  Channels: {}", format.channels);
-		println!("  Sample Rate: {} Hz", format.sample_rate);
-		println!("  Bit Depth: {}", format.bit_depth);
-		println!("\nFrames:");
-
-		let mut frame_idx = 0u64;
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					if let Some(frame) = decoder.decode(packet)? {
-						if let Some(audio_frame) = frame.audio() {
-							println!(
-								"  Frame {}: pts={}, samples={}, channels={}, rate={}",
-								frame_idx,
-								frame.pts,
-								audio_frame.nb_samples,
-								audio_frame.channels,
-								audio_frame.sample_rate
-							);
-						} else if let Some(video_frame) = frame.video() {
-							println!(
-								"  Frame {}: pts={}, width={}, height={}",
-								frame_idx, frame.pts, video_frame.width, video_frame.height
-							);
-						}
-						frame_idx += 1;
-						if frame_idx >= 10 {
-							println!("  ... (showing first 10 frames)");
-							break;
-						}
-					}
-				}
-				None => break,
-			}
-		}
-
-		Ok(())
+	pub fn with_recursive(mut self, recursive: bool) -> Self {
+		self.recursive = recursive;
+		self
 	}
 
-	fn run_flac_show(&self) -> IoResult<()> {
-		let input = FileAdapter::open(&self.input_path)?;
-		let reader = FlacReader::new(input)?;
-		let format = reader.format();
-
-		println!("Format: FLAC");
-		println!("  Channels: {}", format.channels);
-		println!("  Sample Rate: {} Hz", format.sample_rate);
-		println!("  Bits per Sample: {}", format.bits_per_sample);
-		println!("  Total Samples: {}", format.total_samples);
-		println!("  Min Block Size: {}", format.min_block_size);
-		println!("  Max Block Size: {}", format.max_block_size);
-
-		Ok(())
+	pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+		self.extensions = extensions
+			.into_iter()
+			.map(|ext| ext.trim_start_matches('.').to_lowercase())
+			.filter(|ext| !ext.is_empty())
+			.collect();
+		self
 	}
 
-	fn run_y4m_show(&self) -> IoResult<()> {
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = Y4mReader::new(input)?;
-		let format = reader.format();
-		let mut decoder = RawVideoDecoder::new(format.clone());
-
-		println!("Format: Y4M");
-		println!("  Resolution: {}x{}", format.width, format.height);
-		println!("  Framerate: {}/{}", format.framerate_num, format.framerate_den);
-		println!("  Colorspace: {:?}", format.colorspace);
-		println!("\nFrames:");
-
-		let mut frame_idx = 0u64;
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					if let Some(frame) = decoder.decode(packet)? {
-						println!(
-							"  Frame {}: pts={}, size={}x{}, fps={}/{}",
-							frame_idx,
-							frame.pts,
-							format.width,
-							format.height,
-							format.framerate_num,
-							format.framerate_den
-						);
-						frame_idx += 1;
-						if frame_idx >= 10 {
-							println!("  ... (showing first 10 frames)");
-							break;
-						}
-					}
-				}
-				None => break,
-			}
-		}
-
-		Ok(())
+	// by default a batch that would replace any existing file writes nothing
+	pub fn with_overwrite(mut self, overwrite: Overwrite) -> Self {
+		self.overwrite = overwrite;
+		self
 	}
 
-	fn run_avi_show(&self) -> IoResult<()> {
-		let input = FileAdapter::open(&self.input_path)?;
-		let reader = AviReader::new(input)?;
-		let format = reader.format();
-
-		println!("Format: AVI");
-		println!("  Resolution: {}x{}", format.main_header.width, format.main_header.height);
-		println!("  Total Frames: {}", format.main_header.total_frames);
-		println!(
-			"  Framerate: ~{:.2} fps",
-			1_000_000.0 / format.main_header.microseconds_per_frame as f64
-		);
-		println!("  Streams: {}", format.streams.len());
-
-		for (i, stream) in format.streams.iter().enumerate() {
-			println!("  Stream {}: {:?}", i, stream.header.stream_type);
-		}
-
-		Ok(())
+	// record finished items in `manifest` and skip the ones an earlier run
+	// recorded, so an interrupted batch picks up where it stopped
+	pub fn with_resume(mut self, manifest: impl Into<PathBuf>) -> Self {
+		self.resume = Some(manifest.into());
+		self
 	}
 
-	fn run_mp4_show(&self) -> IoResult<()> {
-		let input = FileAdapter::open(&self.input_path)?;
-		let reader = Mp4Reader::new(input)?;
-		let format = reader.format();
-
-		println!("Format: MP4");
-		println!("  Brand: {}", String::from_utf8_lossy(&format.major_brand));
-		println!("  Timescale: {}", format.timescale);
-		println!("  Duration: {}", format.duration);
-		println!("  Tracks: {}", format.tracks.len());
-
-		for (i, track) in format.tracks.iter().enumerate() {
-			println!("  Track {}: {:?}", i, track.track_type);
-			if track.width > 0 && track.height > 0 {
-				println!("    Resolution: {}x{}", track.width, track.height);
-			}
-			if track.sample_rate > 0 {
-				println!("    Sample Rate: {}", track.sample_rate);
-				println!("    Channels: {}", track.channels);
-			}
-		}
-
-		Ok(())
+	pub fn with_on_error(mut self, on_error: ErrorPolicy) -> Self {
+		self.on_error = on_error;
+		self
 	}
 
-	fn run_wav_to_wav(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
-
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = WavReader::new(input)?;
-		let format = reader.format();
-
-		let output = FileAdapter::create(&output_path)?;
-		let mut writer = WavWriter::new(output, format)?;
-
-		let mut decoder = PcmDecoder::new(format);
-		let timebase = Timebase::new(1, format.sample_rate);
-		let mut encoder = PcmEncoder::new(timebase);
-
-		let mut transform_chain = self.build_transform_chain()?;
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
 
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					if let Some(frame) = decoder.decode(packet)? {
-						let processed =
-							if transform_chain.is_empty() { frame } else { transform_chain.apply(frame)? };
-						if let Some(pkt) = encoder.encode(processed)? {
-							writer.write_packet(pkt)?;
-						}
-					}
-				}
-				None => break,
-			}
+	// converts every file and prints the summary; an error when any file failed
+	pub fn run(&self) -> std::io::Result<()> {
+		let report = self.run_report()?;
+		if !self.show_mode {
+			println!("{}", report.summary());
 		}
-
-		writer.finalize()?;
-		Ok(())
+		if report.is_success() {
+			return Ok(());
+		}
+		Err(std::io::Error::other(format!(
+			"{} of {} files failed",
+			format_thousands(report.failed.len() as u64),
+			format_thousands(report.files as u64)
+		)))
 	}
 
-	fn run_wav_to_flac(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
+	// errors about the batch as a whole, such as no matching files or a
+	// refused overwrite, come back before anything is written; a file that
+	// fails to convert is in the report instead
+	pub fn run_report(&self) -> std::io::Result<BatchReport> {
+		let files = self.input_files()?;
 
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = WavReader::new(input)?;
-		let wav_format = reader.format();
-
-		let flac_format = FlacFormat {
-			sample_rate: wav_format.sample_rate,
-			channels: wav_format.channels,
-			bits_per_sample: wav_format.bit_depth as u8,
-			..FlacFormat::default()
-		};
+		if files.is_empty() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no files matching pattern: {}", self.input_pattern),
+			));
+		}
 
-		let output = FileAdapter::create(&output_path)?;
-		let mut writer = FlacWriter::new(output, flac_format)?;
+		// a template names each output itself, so there is no one directory to make
+		let template = is_template(&self.output_dir);
+		if !template {
+			self.check_output_dir(Path::new(&self.output_dir))?;
+			std::fs::create_dir_all(&self.output_dir)?;
+		}
 
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					writer.write_packet(packet)?;
+		let mut jobs: Vec<(String, Option<String>)> = Vec::with_capacity(files.len());
+		for (index, (input_path, relative)) in files.into_iter().enumerate() {
+			let output_path = if self.show_mode {
+				None
+			} else if template {
+				let mut vars = TemplateVars::for_input(&self.output_dir, &input_path, &relative, index + 1);
+				if let Some(format) = self.output_format {
+					vars.ext = format.extension().to_string();
 				}
-				None => break,
-			}
+				Some(render(&self.output_dir, &vars)?)
+			} else {
+				let mut output = Path::new(&self.output_dir).join(relative);
+				if let Some(format) = self.output_format {
+					output.set_extension(format.extension());
+				}
+				Some(output.to_string_lossy().to_string())
+			};
+			jobs.push((input_path, output_path));
 		}
 
-		writer.finalize()?;
-		Ok(())
-	}
-
-	fn run_flac_to_wav(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
-
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = FlacReader::new(input)?;
-		let flac_format = reader.format();
-
-		let wav_format = crate::container::WavFormat {
-			sample_rate: flac_format.sample_rate,
-			channels: flac_format.channels,
-			bit_depth: flac_format.bits_per_sample as u16,
-		};
-
-		let output = FileAdapter::create(&output_path)?;
-		let mut writer = WavWriter::new(output, wav_format)?;
-
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					writer.write_packet(packet)?;
-				}
-				None => break,
+		// a template that leaves out {name} or {index} can send two inputs to one file
+		let mut seen = std::collections::HashMap::new();
+		for (input_path, output_path) in &jobs {
+			if let Some(out) = output_path
+				&& let Some(first) = seen.insert(out.clone(), input_path.clone())
+			{
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::InvalidInput,
+					format!("{} and {} would both be written to {}", first, input_path, out),
+				));
 			}
 		}
 
-		writer.finalize()?;
-		Ok(())
-	}
-
-	fn run_flac_to_flac(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
-
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = FlacReader::new(input)?;
-		let format = reader.format().clone();
-
-		let output = FileAdapter::create(&output_path)?;
-		let mut writer = FlacWriter::new(output, format)?;
-
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					writer.write_packet(packet)?;
+		let mut manifest = self.resume.as_deref().map(BatchManifest::open).transpose()?;
+
+		// every output is checked before the first is written, so a refused
+		// batch leaves the directory as it was. an item the manifest has as
+		// started but not done left a partial output, which is replaced
+		let mut steps = Vec::with_capacity(jobs.len());
+		for (input_path, output_path) in &jobs {
+			let key = output_path.as_deref().unwrap_or_default();
+			let step = match (&manifest, output_path) {
+				(Some(manifest), _) if manifest.is_done(input_path, key) => Step::Done,
+				(Some(manifest), _) if manifest.is_unfinished(input_path, key) => {
+					Step::Run(Overwrite::Replace)
 				}
-				None => break,
-			}
+				(_, Some(out)) => match self.overwrite.check(Path::new(out)) {
+					Ok(true) => Step::Run(self.overwrite),
+					Ok(false) => Step::Exists,
+					Err(e) => {
+						return Err(std::io::Error::new(
+							std::io::ErrorKind::AlreadyExists,
+							format!("{}: {}", out, e),
+						));
+					}
+				},
+				(_, None) => Step::Run(self.overwrite),
+			};
+			steps.push(step);
 		}
 
-		writer.finalize()?;
-		Ok(())
-	}
-
-	fn run_y4m_transcode(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
-
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = Y4mReader::new(input)?;
-		let format = reader.format();
-
-		let output = FileAdapter::create(&output_path)?;
-		let buf_writer: BufferedWriter<FileAdapter> = BufferedWriter::new(output);
-		let mut writer = Y4mWriter::new(buf_writer, format.clone())?;
-
-		let timebase = Timebase::new(format.framerate_den, format.framerate_num);
-		let mut decoder = RawVideoDecoder::new(format);
-		let mut encoder = RawVideoEncoder::new(timebase);
+		let mut report = BatchReport { files: jobs.len(), ..BatchReport::default() };
+		for ((input_path, output_path), step) in jobs.into_iter().zip(steps) {
+			if self.on_error == ErrorPolicy::Stop && !report.failed.is_empty() {
+				report.not_run += 1;
+				continue;
+			}
+			let overwrite = match step {
+				Step::Run(overwrite) => overwrite,
+				Step::Exists => {
+					println!("Skipping: {} ({} exists)", input_path, output_path.unwrap_or_default());
+					report.skipped += 1;
+					continue;
+				}
+				Step::Done => {
+					println!("Skipping: {} (done in an earlier run)", input_path);
+					report.resumed += 1;
+					continue;
+				}
+			};
 
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					if let Some(frame) = decoder.decode(packet)? {
-						if let Some(pkt) = encoder.encode(frame)? {
-							writer.write_packet(pkt)?;
-						}
+			println!("Processing: {}", input_path);
+			match self.convert(&input_path, output_path.as_deref(), overwrite, &mut manifest) {
+				Ok(()) => {
+					report.converted += 1;
+					if let Some(out) = output_path {
+						report.bytes += std::fs::metadata(&out).map(|m| m.len()).unwrap_or(0);
+						println!("  -> {}", out);
 					}
 				}
-				None => break,
+				Err(e) => {
+					println!("  failed: {}", e);
+					report.failed.push((input_path, e.to_string()));
+				}
 			}
 		}
 
-		writer.finalize()?;
-		Ok(())
+		Ok(report)
 	}
 
-	fn run_avi_passthrough(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
-
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = AviReader::new(input)?;
-		let format = reader.format().clone();
-
-		let output = FileAdapter::create(&output_path)?;
-		let mut writer = AviWriter::new(output, format)?;
+	fn convert(
+		&self,
+		input_path: &str,
+		output_path: Option<&str>,
+		overwrite: Overwrite,
+		manifest: &mut Option<BatchManifest>,
+	) -> std::io::Result<()> {
+		let mut pipeline = Pipeline::new(
+			input_path.to_string(),
+			output_path.map(String::from),
+			self.show_mode,
+			self.transforms.clone(),
+		)
+		.with_overwrite(overwrite)
+		.with_path_policy(self.path_policy.clone());
+		if let Some(format) = self.format {
+			pipeline = pipeline.with_format(format);
+		}
+		if let Some(codec) = self.codec {
+			pipeline = pipeline.with_codec(codec);
+		}
 
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					writer.write_packet(packet)?;
-				}
-				None => break,
-			}
+		// subdirectories of a recursive input or a template are created as
+		// they are needed
+		if let Some(parent) = output_path.and_then(|out| Path::new(out).parent())
+			&& !parent.as_os_str().is_empty()
+		{
+			self.check_output_dir(parent)?;
+			std::fs::create_dir_all(parent)?;
 		}
 
-		writer.finalize()?;
+		let key = output_path.unwrap_or_default();
+		if let Some(manifest) = manifest {
+			manifest.start(input_path, key)?;
+		}
+		if self.show_mode {
+			print!("{}", pipeline.show()?);
+		} else {
+			pipeline.run()?;
+		}
+		if let Some(manifest) = manifest {
+			manifest.finish(input_path, key)?;
+		}
 		Ok(())
 	}
 
-	fn run_mp4_passthrough(&self) -> IoResult<()> {
-		let output_path = self.require_output()?;
-
-		let input = FileAdapter::open(&self.input_path)?;
-		let mut reader = Mp4Reader::new(input)?;
-		let format = reader.format().clone();
-
-		let output = FileAdapter::create(&output_path)?;
-		let mut writer = Mp4Writer::new(output, format)?;
-
-		loop {
-			match reader.read_packet()? {
-				Some(packet) => {
-					writer.write_packet(packet)?;
-				}
-				None => break,
-			}
-		}
-
-		writer.finalize()?;
-		Ok(())
+	fn check_output_dir(&self, dir: &Path) -> std::io::Result<()> {
+		self
+			.path_policy
+			.check(dir, PathAccess::Write)
+			.map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied, e.to_string()))
 	}
 
-	fn require_output(&self) -> IoResult<String> {
-		self.output_path.clone().ok_or_else(|| {
-			IoError::with_message(IoErrorKind::InvalidData, "output path required for transcoding")
-		})
+	// every input with the path its output takes under the output directory:
+	// the file name for a glob, the path below the input for a directory
+	fn input_files(&self) -> std::io::Result<Vec<(String, PathBuf)>> {
+		let root = Path::new(&self.input_pattern);
+		let files: Vec<(String, PathBuf)> = if root.is_dir() {
+			// an output directory inside the input is not read back as input
+			let skip = Path::new(&self.output_dir).canonicalize().ok();
+			walk_directory(root, self.recursive, skip.as_deref())?
+				.into_iter()
+				.map(|path| {
+					let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+					(path.to_string_lossy().to_string(), relative)
+				})
+				.collect()
+		} else {
+			expand_pattern(&self.input_pattern)?
+				.into_iter()
+				.map(|input_path| {
+					let file_name = Path::new(&input_path).file_name().map(PathBuf::from);
+					let file_name = file_name.unwrap_or_else(|| PathBuf::from("output.wav"));
+					(input_path, file_name)
+				})
+				.collect()
+		};
+		Ok(files.into_iter().filter(|(path, _)| self.wants(path)).collect())
 	}
 
-	fn build_transform_chain(&self) -> IoResult<TransformChain> {
-		let mut transform_chain = TransformChain::new();
-		for spec in &self.transforms {
-			let t = parse_transform(spec)?;
-			transform_chain.add(t);
+	fn wants(&self, path: &str) -> bool {
+		if self.extensions.is_empty() {
+			// a glob names its own files; a directory holds whatever it holds
+			return !Path::new(&self.input_pattern).is_dir()
+				|| MediaType::from_extension(path) != MediaType::Unknown;
 		}
-		Ok(transform_chain)
+		let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+		self.extensions.iter().any(|wanted| wanted.eq_ignore_ascii_case(ext))
 	}
 }
 
-pub struct BatchPipeline {
-	input_pattern: String,
-	output_dir: String,
-	show_mode: bool,
-	transforms: Vec<String>,
-}
-
-impl BatchPipeline {
-	pub fn new(
-		input_pattern: String,
-		output_dir: String,
-		show_mode: bool,
-		transforms: Vec<String>,
-	) -> Self {
-		Self { input_pattern, output_dir, show_mode, transforms }
-	}
-
-	pub fn run(&self) -> std::io::Result<()> {
-		let files = self.expand_glob()?;
-
-		if files.is_empty() {
-			return Err(std::io::Error::new(
-				std::io::ErrorKind::NotFound,
-				format!("no files matching pattern: {}", self.input_pattern),
-			));
-		}
-
-		std::fs::create_dir_all(&self.output_dir)?;
-
-		for input_path in files {
-			let file_name =
-				Path::new(&input_path).file_name().and_then(|n| n.to_str()).unwrap_or("output.wav");
-
-			let output_path =
-				if self.show_mode { None } else { Some(format!("{}/{}", self.output_dir, file_name)) };
-
-			let pipeline = Pipeline::new(
-				input_path.clone(),
-				output_path.clone(),
-				self.show_mode,
-				self.transforms.clone(),
-			);
-
-			println!("Processing: {}", input_path);
-			pipeline.run()?;
-
-			if let Some(out) = output_path {
-				println!("  -> {}", out);
+// the files in `dir`, sorted, and in its subdirectories when `recursive`.
+// symlinked directories are not followed, so a link loop cannot recurse, and
+// `skip` leaves out one directory (compared canonically)
+pub fn walk_directory(
+	dir: &Path,
+	recursive: bool,
+	skip: Option<&Path>,
+) -> std::io::Result<Vec<PathBuf>> {
+	let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+	entries.sort_by_key(|entry| entry.file_name());
+
+	let mut files = Vec::new();
+	for entry in entries {
+		let file_type = entry.file_type()?;
+		let path = entry.path();
+		if file_type.is_dir() {
+			if recursive && skip.is_none_or(|skip| path.canonicalize().ok().as_deref() != Some(skip)) {
+				files.extend(walk_directory(&path, true, skip)?);
 			}
+		} else if path.is_file() {
+			files.push(path);
 		}
-
-		Ok(())
 	}
+	Ok(files)
+}
 
-	fn expand_glob(&self) -> std::io::Result<Vec<String>> {
-		let mut files = Vec::new();
-
-		if self.input_pattern.contains('*') {
-			let pattern = &self.input_pattern;
-			for entry in glob::glob(pattern).map_err(|e| {
-				std::io::Error::new(
-					std::io::ErrorKind::InvalidInput,
-					format!("invalid glob pattern: {}", e),
-				)
-			})? {
-				match entry {
-					Ok(path) => {
-						if path.is_file() {
-							files.push(path.to_string_lossy().to_string());
-						}
-					}
-					Err(e) => {
-						eprintln!("warning: failed to read entry: {}", e);
+// the files a batch input names: every match of a glob, or the path itself
+pub fn expand_pattern(pattern: &str) -> std::io::Result<Vec<String>> {
+	let mut files = Vec::new();
+
+	if pattern.contains('*') {
+		for entry in glob::glob(pattern).map_err(|e| {
+			std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("invalid glob pattern: {}", e))
+		})? {
+			match entry {
+				Ok(path) => {
+					if path.is_file() {
+						files.push(path.to_string_lossy().to_string());
 					}
 				}
+				Err(e) => {
+					log::warn("batch", format_args!("failed to read entry: {}", e));
+				}
 			}
-		} else {
-			files.push(self.input_pattern.clone());
 		}
-
-		Ok(files)
+	} else {
+		files.push(pattern.to_string());
 	}
+
+	Ok(files)
 }
 
 pub fn is_batch_pattern(input: &str) -> bool {
@@ -0,0 +1,129 @@
+use crate::codecs::CodecId;
+use crate::container::MediaType;
+use crate::io::json::JsonValue;
+use crate::io::{IoError, IoResult};
+
+// a named bundle of settings for `--preset`. the rate becomes a resample ahead
+// of the preset's transforms, and the container names outputs that have no
+// extension of their own
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Preset {
+	pub name: String,
+	pub codec: Option<String>,
+	pub rate: Option<u32>,
+	pub apply: Vec<String>,
+	pub container: Option<MediaType>,
+}
+
+pub const BUILTIN_PRESETS: [&str; 4] = ["podcast", "archive", "voicemail", "web-video"];
+
+impl Preset {
+	pub fn builtin(name: &str) -> Option<Self> {
+		let preset = |codec: Option<&str>, rate, apply: &[&str], container| Preset {
+			name: name.to_string(),
+			codec: codec.map(String::from),
+			rate,
+			apply: apply.iter().map(|t| t.to_string()).collect(),
+			container,
+		};
+		match name {
+			// spoken word at the level podcast platforms ask for
+			"podcast" => Some(preset(
+				Some("pcm_s16le"),
+				Some(44100),
+				&["mono", "highpass=80", "loudnorm=-16,-1.5"],
+				Some(MediaType::Wav),
+			)),
+			// lossless and untouched, just smaller
+			"archive" => Some(preset(None, None, &[], Some(MediaType::Flac))),
+			// telephone band: 8 kHz mono, 300-3400 Hz, 8-bit
+			"voicemail" => Some(preset(
+				Some("pcm_u8"),
+				Some(8000),
+				&["mono", "highpass=300", "lowpass=3400", "normalize"],
+				Some(MediaType::Wav),
+			)),
+			// 720p 4:2:0, letterboxed. video is only re-encoded as y4m, so the
+			// container is left to the output name
+			"web-video" => Some(preset(None, None, &["scale=1280x720:fit", "format=yuv420"], None)),
+			_ => None,
+		}
+	}
+
+	// one `[[preset]]` table of a config file:
+	//
+	//   [[preset]]
+	//   name = "interview"
+	//   codec = "pcm_s24le"
+	//   rate = 48000
+	//   apply = ["highpass=100", "loudnorm=-19"]
+	//   container = "wav"
+	pub fn from_value(value: &JsonValue) -> IoResult<Self> {
+		let Some(fields) = value.as_object() else {
+			return Err(IoError::invalid_data("a preset must be a table of options"));
+		};
+		let mut preset = Preset::default();
+		for (key, value) in fields {
+			match key.as_str() {
+				"name" => preset.name = string(value)?,
+				"codec" => {
+					let name = string(value)?;
+					if CodecId::from_name(&name).is_none() {
+						return Err(IoError::invalid_data("preset names an unknown codec"));
+					}
+					preset.codec = Some(name);
+				}
+				"rate" | "sample-rate" => {
+					let rate = value.as_f64().filter(|r| *r >= 1.0 && r.fract() == 0.0);
+					let rate =
+						rate.ok_or_else(|| IoError::invalid_data("preset rate must be a whole number"))?;
+					preset.rate = Some(rate as u32);
+				}
+				"apply" | "transforms" => match value {
+					JsonValue::Array(items) => {
+						preset.apply.extend(items.iter().map(string).collect::<IoResult<Vec<_>>>()?)
+					}
+					value => preset.apply.push(string(value)?),
+				},
+				"container" | "format" => match MediaType::from_name(&string(value)?) {
+					MediaType::Unknown => {
+						return Err(IoError::invalid_data("preset names an unknown container"));
+					}
+					container => preset.container = Some(container),
+				},
+				_ => return Err(IoError::invalid_data("preset has an unknown option")),
+			}
+		}
+		if preset.name.is_empty() {
+			return Err(IoError::invalid_data("preset requires 'name'"));
+		}
+		Ok(preset)
+	}
+
+	// the preset's transforms, resample first, for ahead of any --apply
+	pub fn transforms(&self) -> Vec<String> {
+		self
+			.rate
+			.map(|rate| format!("resample={}", rate))
+			.into_iter()
+			.chain(self.apply.clone())
+			.collect()
+	}
+
+	// `output` with the preset's container as its extension, when it has none
+	pub fn output_name(&self, output: &str) -> String {
+		match self.container {
+			Some(container) if MediaType::from_extension(output) == MediaType::Unknown => {
+				format!("{}.{}", output, container.extension())
+			}
+			_ => output.to_string(),
+		}
+	}
+}
+
+fn string(value: &JsonValue) -> IoResult<String> {
+	value
+		.as_str()
+		.map(str::to_string)
+		.ok_or_else(|| IoError::invalid_data("preset option must be a string"))
+}
@@ -0,0 +1,215 @@
+use super::pipeline::expand_pattern;
+use crate::codecs::{FlacDecoder, PcmDecoder, PcmEncoder};
+use crate::container::{FlacReader, MediaType, WavFormat, WavReader, WavWriter};
+use crate::core::{
+	Decoder, Demuxer, Encoder, Frame, FrameAudio, Muxer, SampleFormat, Timebase, parse_duration,
+};
+use crate::io::{FileAdapter, IoError, IoResult};
+use clap::Parser;
+use std::path::Path;
+
+// the loudest a clip's peak may get from normalizing, in dBFS
+const PEAK_CEILING_DB: f64 = -1.0;
+
+#[derive(Parser, Debug)]
+#[command(
+	name = "ffmpreg preview",
+	bin_name = "ffmpreg preview",
+	about = "Cut loudness-matched preview clips from the loudest part of each input"
+)]
+pub struct PreviewArgs {
+	#[arg(short, long, value_name = "FILE", help = "Input file or glob pattern")]
+	pub input: String,
+
+	#[arg(short, long, value_name = "DIR", default_value = "previews", help = "Output directory")]
+	pub output: String,
+
+	#[arg(long, value_name = "DURATION", default_value = "30s", help = "Clip length")]
+	pub duration: String,
+
+	#[arg(
+		long,
+		value_name = "DBFS",
+		default_value = "-14",
+		allow_negative_numbers = true,
+		help = "RMS level every clip is matched to"
+	)]
+	pub target: f64,
+
+	#[arg(long, value_name = "SUFFIX", default_value = "_preview", help = "Appended to file names")]
+	pub suffix: String,
+}
+
+impl PreviewArgs {
+	pub fn run(&self) -> std::io::Result<()> {
+		let Some(seconds) = parse_duration(&self.duration).filter(|s| *s > 0.0) else {
+			return Err(std::io::Error::other(format!("invalid --duration '{}'", self.duration)));
+		};
+
+		let files = expand_pattern(&self.input)?;
+		if files.is_empty() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no files matching pattern: {}", self.input),
+			));
+		}
+		std::fs::create_dir_all(&self.output)?;
+
+		for input in files {
+			let output = preview_path(&input, &self.output, &self.suffix);
+			let clip = make_preview(&input, &output, seconds, self.target)
+				.map_err(|e| std::io::Error::other(format!("{}: {}", input, e)))?;
+			println!(
+				"{} -> {}  (from {:.2}s, {:.2}s, {:+.1} dB)",
+				input, output, clip.start, clip.duration, clip.gain_db
+			);
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewClip {
+	pub start: f64,
+	pub duration: f64,
+	pub gain_db: f64,
+}
+
+// out/<stem><suffix>.wav
+pub fn preview_path(input: &str, output_dir: &str, suffix: &str) -> String {
+	let stem = Path::new(input).file_stem().and_then(|s| s.to_str()).unwrap_or("preview");
+	format!("{}/{}{}.wav", output_dir, stem, suffix)
+}
+
+pub fn make_preview(
+	input: &str,
+	output: &str,
+	clip_seconds: f64,
+	target_db: f64,
+) -> IoResult<PreviewClip> {
+	let audio = decode_audio(input)?;
+	let channels = audio.channels.max(1) as usize;
+	let samples: Vec<i16> =
+		audio.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+
+	let window = (clip_seconds * audio.sample_rate as f64).round() as usize;
+	let start = loudest_window(&samples, channels, window);
+	let end = (start + window).min(samples.len() / channels);
+
+	let mut clip = samples[start * channels..end * channels].to_vec();
+	let gain_db = normalize_rms(&mut clip, target_db);
+
+	let format =
+		WavFormat { channels: audio.channels, sample_rate: audio.sample_rate, bit_depth: 16 };
+	let data: Vec<u8> = clip.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let timebase = Timebase::new(1, audio.sample_rate);
+	let frame =
+		Frame::new_audio(FrameAudio::new(data, audio.sample_rate, audio.channels), timebase, 0);
+
+	let mut writer = WavWriter::new(FileAdapter::create(output)?, format)?;
+	if let Some(packet) = PcmEncoder::new(timebase).encode(frame)? {
+		writer.write_packet(packet)?;
+	}
+	writer.finalize()?;
+
+	let sample_rate = audio.sample_rate as f64;
+	Ok(PreviewClip {
+		start: start as f64 / sample_rate,
+		duration: (end - start) as f64 / sample_rate,
+		gain_db,
+	})
+}
+
+// start (in sample frames) of the `window` long stretch with the most energy.
+// inputs shorter than the window start at 0.
+pub fn loudest_window(samples: &[i16], channels: usize, window: usize) -> usize {
+	let frames = samples.len() / channels.max(1);
+	if window == 0 || frames <= window {
+		return 0;
+	}
+
+	let energy = |idx: usize| -> f64 {
+		samples[idx * channels..(idx + 1) * channels].iter().map(|&s| (s as f64) * (s as f64)).sum()
+	};
+
+	let mut sum: f64 = (0..window).map(energy).sum();
+	let (mut best, mut best_sum) = (0, sum);
+	for start in 1..=frames - window {
+		sum += energy(start + window - 1) - energy(start - 1);
+		if sum > best_sum {
+			best = start;
+			best_sum = sum;
+		}
+	}
+	best
+}
+
+pub fn rms_dbfs(samples: &[i16]) -> f64 {
+	if samples.is_empty() {
+		return f64::NEG_INFINITY;
+	}
+	let mean = samples.iter().map(|&s| (s as f64) * (s as f64)).sum::<f64>() / samples.len() as f64;
+	20.0 * (mean.sqrt() / 32768.0).log10()
+}
+
+// scales the clip to `target_db` RMS, or less when that would take its peak
+// over PEAK_CEILING_DB: a quieter clip beats a clipped one. returns the gain
+// applied; silence is left alone.
+pub fn normalize_rms(samples: &mut [i16], target_db: f64) -> f64 {
+	let current = rms_dbfs(samples);
+	if !current.is_finite() {
+		return 0.0;
+	}
+	let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0) as f64;
+	let headroom = PEAK_CEILING_DB - 20.0 * (peak / 32768.0).log10();
+	let gain_db = (target_db - current).min(headroom);
+	let gain = 10f64.powf(gain_db / 20.0);
+	for sample in samples.iter_mut() {
+		*sample = (*sample as f64 * gain).round().clamp(-32768.0, 32767.0) as i16;
+	}
+	gain_db
+}
+
+// the whole file as one s16 frame
+pub fn decode_audio(path: &str) -> IoResult<FrameAudio> {
+	let input = FileAdapter::open(path)?;
+	match MediaType::from_extension(path) {
+		MediaType::Wav => {
+			let mut reader = WavReader::new(input)?;
+			let format = reader.format();
+			let decoder = PcmDecoder::new(format);
+			collect_audio(&mut reader, decoder, format.sample_rate, format.channels)
+		}
+		MediaType::Flac => {
+			let mut reader = FlacReader::new(input)?;
+			let format = reader.format().clone();
+			let (sample_rate, channels) = (format.sample_rate, format.channels);
+			collect_audio(&mut reader, FlacDecoder::new(&format), sample_rate, channels)
+		}
+		_ => Err(IoError::invalid_data("expected a wav or flac input")),
+	}
+}
+
+fn collect_audio<D: Demuxer, C: Decoder>(
+	reader: &mut D,
+	mut decoder: C,
+	sample_rate: u32,
+	channels: u8,
+) -> IoResult<FrameAudio> {
+	let mut data = Vec::new();
+	let mut append = |frame: Frame| {
+		if let Some(mut audio) = frame.audio().cloned() {
+			audio.convert(SampleFormat::S16);
+			data.extend_from_slice(&audio.data);
+		}
+	};
+	while let Some(packet) = reader.read_packet()? {
+		if let Some(frame) = decoder.decode(packet)? {
+			append(frame);
+		}
+	}
+	while let Some(frame) = decoder.flush()? {
+		append(frame);
+	}
+	Ok(FrameAudio::new(data, sample_rate, channels))
+}
@@ -0,0 +1,128 @@
+use super::pipeline::expand_pattern;
+use crate::codecs::RawVideoDecoder;
+use crate::container::{MediaType, Y4mReader};
+use crate::core::{Decoder, Demuxer, parse_duration};
+use crate::image::{RgbImage, png};
+use crate::io::{FileAdapter, IoError, IoResult};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSampleSpec {
+	pub every: f64,
+	pub scale: Option<(u32, u32)>,
+	pub crop: Option<(u32, u32)>,
+}
+
+impl FrameSampleSpec {
+	// every=2s[,scale=256x256][,crop=224x224]
+	pub fn parse(spec: &str) -> IoResult<Self> {
+		let mut every = None;
+		let mut scale = None;
+		let mut crop = None;
+
+		for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+			let (key, value) =
+				part.split_once('=').ok_or_else(|| IoError::invalid_data("expected key=value"))?;
+			match key {
+				"every" => every = parse_duration(value).filter(|s| *s > 0.0),
+				"scale" => {
+					scale = Some(parse_size(value).ok_or_else(|| IoError::invalid_data("scale must be WxH"))?)
+				}
+				"crop" => {
+					crop = Some(parse_size(value).ok_or_else(|| IoError::invalid_data("crop must be WxH"))?)
+				}
+				_ => return Err(IoError::invalid_data("unknown frame sampling option")),
+			}
+		}
+
+		let every =
+			every.ok_or_else(|| IoError::invalid_data("frame sampling needs every=DURATION"))?;
+		Ok(Self { every, scale, crop })
+	}
+
+	// resize first, then crop the middle, the usual dataset preprocessing order
+	pub fn prepare(&self, image: &RgbImage) -> RgbImage {
+		let image = match self.scale {
+			Some((width, height)) => image.resize(width, height),
+			None => image.clone(),
+		};
+		match self.crop {
+			Some((width, height)) => image.center_crop(width, height),
+			None => image,
+		}
+	}
+}
+
+fn parse_size(text: &str) -> Option<(u32, u32)> {
+	let (width, height) = text.split_once('x')?;
+	let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+	(width > 0 && height > 0).then_some((width, height))
+}
+
+// {stem} is the input name without extension, {n} the sample number
+pub fn render_template(template: &str, input: &str, n: usize) -> String {
+	let stem = Path::new(input).file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+	template.replace("{stem}", stem).replace("{n}", &n.to_string())
+}
+
+pub fn sample_batch(pattern: &str, spec: &FrameSampleSpec, template: &str) -> IoResult<usize> {
+	let files = expand_pattern(pattern)?;
+	if files.is_empty() {
+		return Err(IoError::with_message(crate::io::IoErrorKind::NotFound, "no matching input files"));
+	}
+
+	let mut total = 0;
+	for input in &files {
+		let written = sample_frames(input, spec, template)?;
+		println!("{}: {} frames", input, written.len());
+		total += written.len();
+	}
+	Ok(total)
+}
+
+// writes one image per `every` seconds of video and returns the paths
+pub fn sample_frames(input: &str, spec: &FrameSampleSpec, template: &str) -> IoResult<Vec<String>> {
+	if MediaType::from_extension(input) != MediaType::Y4m {
+		return Err(IoError::invalid_data("frame sampling needs a decodable video input (y4m)"));
+	}
+	let mut reader = Y4mReader::new(FileAdapter::open(input)?)?;
+	let mut decoder = RawVideoDecoder::new(reader.format());
+
+	let mut written = Vec::new();
+	let mut next_time = 0.0;
+	while let Some(packet) = reader.read_packet()? {
+		let Some(frame) = decoder.decode(packet)? else {
+			continue;
+		};
+		let time = frame.timebase.to_seconds(frame.pts);
+		if time + 1e-9 < next_time {
+			continue;
+		}
+		let Some(video) = frame.video() else {
+			continue;
+		};
+
+		let image = spec.prepare(&RgbImage::from_frame(video)?);
+		let path = render_template(template, input, written.len());
+		write_image(&path, &image)?;
+		written.push(path);
+		// a gap longer than `every` yields one image, not a burst of repeats
+		while next_time <= time + 1e-9 {
+			next_time += spec.every;
+		}
+	}
+	Ok(written)
+}
+
+pub(crate) fn write_image(path: &str, image: &RgbImage) -> IoResult<()> {
+	let data = match Path::new(path).extension().and_then(|e| e.to_str()) {
+		Some("png") => png::encode(image),
+		Some("ppm") => image.to_ppm(),
+		_ => return Err(IoError::invalid_data("output template must end in .png or .ppm")),
+	};
+	if let Some(parent) = Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+		std::fs::create_dir_all(parent)?;
+	}
+	std::fs::write(path, data)?;
+	Ok(())
+}
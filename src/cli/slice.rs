@@ -0,0 +1,255 @@
+use super::pipeline::expand_pattern;
+use super::preview::decode_audio;
+use crate::codecs::PcmEncoder;
+use crate::container::{WavFormat, WavWriter};
+use crate::core::{Encoder, Frame, FrameAudio, Muxer, Timebase, Transform, parse_duration};
+use crate::io::{FileAdapter, IoError, IoResult};
+use crate::transform::Resample;
+use clap::Parser;
+use std::path::Path;
+
+#[derive(Parser, Debug)]
+#[command(
+	name = "ffmpreg slice",
+	bin_name = "ffmpreg slice",
+	about = "Cut recordings into fixed-length mono windows for datasets"
+)]
+pub struct SliceArgs {
+	#[arg(short, long, value_name = "FILE", help = "Input file or glob pattern")]
+	pub input: String,
+
+	#[arg(short, long, value_name = "DIR", default_value = "slices", help = "Output directory")]
+	pub output: String,
+
+	#[arg(long, value_name = "DURATION", default_value = "10s", help = "Window length")]
+	pub window: String,
+
+	#[arg(long, value_name = "FRACTION", default_value = "0.5", help = "Overlap between windows")]
+	pub overlap: f64,
+
+	#[arg(long, value_name = "HZ", default_value = "16000", help = "Output sample rate")]
+	pub rate: u32,
+
+	#[arg(long, value_name = "FORMAT", default_value = "wav", help = "Window format (wav, f32)")]
+	pub format: String,
+}
+
+impl SliceArgs {
+	pub fn run(&self) -> std::io::Result<()> {
+		let to_io = std::io::Error::from;
+		let Some(window) = parse_duration(&self.window).filter(|s| *s > 0.0) else {
+			return Err(std::io::Error::other(format!("invalid --window '{}'", self.window)));
+		};
+		let Some(format) = SliceFormat::from_name(&self.format) else {
+			return Err(std::io::Error::other(format!("unknown --format '{}' (wav, f32)", self.format)));
+		};
+		let options = SliceOptions { window, overlap: self.overlap, sample_rate: self.rate, format };
+		options.validate().map_err(to_io)?;
+
+		let files = expand_pattern(&self.input)?;
+		if files.is_empty() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no files matching pattern: {}", self.input),
+			));
+		}
+		std::fs::create_dir_all(&self.output)?;
+
+		let mut manifest = Manifest::new();
+		for input in &files {
+			let count = slice_file(input, &self.output, &options, &mut manifest)
+				.map_err(|e| std::io::Error::other(format!("{}: {}", input, e)))?;
+			println!("{}: {} windows", input, count);
+		}
+
+		let manifest_path = format!("{}/manifest.csv", self.output);
+		std::fs::write(&manifest_path, manifest.to_csv())?;
+		println!("wrote {} windows, manifest {}", manifest.rows.len(), manifest_path);
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliceFormat {
+	Wav,
+	// headerless little-endian f32 in -1..1
+	F32,
+}
+
+impl SliceFormat {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"wav" => Some(SliceFormat::Wav),
+			"f32" | "raw" => Some(SliceFormat::F32),
+			_ => None,
+		}
+	}
+
+	pub fn extension(&self) -> &'static str {
+		match self {
+			SliceFormat::Wav => "wav",
+			SliceFormat::F32 => "f32",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SliceOptions {
+	pub window: f64,
+	pub overlap: f64,
+	pub sample_rate: u32,
+	pub format: SliceFormat,
+}
+
+impl SliceOptions {
+	pub fn validate(&self) -> IoResult<()> {
+		if !(0.0..1.0).contains(&self.overlap) {
+			return Err(IoError::invalid_data("overlap must be at least 0 and below 1"));
+		}
+		if self.sample_rate == 0 {
+			return Err(IoError::invalid_data("sample rate must be positive"));
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestRow {
+	pub file: String,
+	pub source: String,
+	pub start: f64,
+	pub end: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+	pub rows: Vec<ManifestRow>,
+}
+
+impl Manifest {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn to_csv(&self) -> String {
+		let mut out = String::from("file,source,start,end\n");
+		for row in &self.rows {
+			out.push_str(&format!(
+				"{},{},{:.3},{:.3}\n",
+				csv_field(&row.file),
+				csv_field(&row.source),
+				row.start,
+				row.end
+			));
+		}
+		out
+	}
+}
+
+fn csv_field(text: &str) -> String {
+	if text.contains([',', '"', '\n']) {
+		format!("\"{}\"", text.replace('"', "\"\""))
+	} else {
+		text.to_string()
+	}
+}
+
+// (start, end) sample offsets of every full window; a trailing partial window
+// is dropped so every item in the dataset has the same length
+pub fn window_ranges(total: usize, window: usize, overlap: f64) -> Vec<(usize, usize)> {
+	if window == 0 {
+		return Vec::new();
+	}
+	let hop = ((window as f64 * (1.0 - overlap)).round() as usize).max(1);
+	let mut ranges = Vec::new();
+	let mut start = 0;
+	while start + window <= total {
+		ranges.push((start, start + window));
+		start += hop;
+	}
+	ranges
+}
+
+pub fn mix_to_mono(samples: &[i16], channels: usize) -> Vec<i16> {
+	if channels <= 1 {
+		return samples.to_vec();
+	}
+	samples
+		.chunks_exact(channels)
+		.map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / channels as i32) as i16)
+		.collect()
+}
+
+// the whole input mixed to mono and resampled to `sample_rate`
+pub(crate) fn decode_mono(input: &str, sample_rate: u32) -> IoResult<Vec<i16>> {
+	let audio = decode_audio(input)?;
+	let samples: Vec<i16> =
+		audio.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+	let mono = mix_to_mono(&samples, audio.channels.max(1) as usize);
+
+	let data: Vec<u8> = mono.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let frame = Frame::new_audio(
+		FrameAudio::new(data, audio.sample_rate, 1),
+		Timebase::new(1, audio.sample_rate),
+		0,
+	);
+	let resampled = Resample::new(sample_rate).apply(frame)?;
+	Ok(
+		resampled
+			.audio()
+			.map(|a| a.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect())
+			.unwrap_or_default(),
+	)
+}
+
+pub fn slice_file(
+	input: &str,
+	output_dir: &str,
+	options: &SliceOptions,
+	manifest: &mut Manifest,
+) -> IoResult<usize> {
+	let samples = decode_mono(input, options.sample_rate)?;
+
+	let rate = options.sample_rate as f64;
+	let window = (options.window * rate).round() as usize;
+	let stem = Path::new(input).file_stem().and_then(|s| s.to_str()).unwrap_or("slice");
+	let ranges = window_ranges(samples.len(), window, options.overlap);
+
+	for (idx, &(start, end)) in ranges.iter().enumerate() {
+		let name = format!("{}_{:05}.{}", stem, idx, options.format.extension());
+		let path = format!("{}/{}", output_dir, name);
+		write_window(&path, &samples[start..end], options)?;
+		manifest.rows.push(ManifestRow {
+			file: name,
+			source: input.to_string(),
+			start: start as f64 / rate,
+			end: end as f64 / rate,
+		});
+	}
+	Ok(ranges.len())
+}
+
+fn write_window(path: &str, samples: &[i16], options: &SliceOptions) -> IoResult<()> {
+	match options.format {
+		SliceFormat::F32 => {
+			let data: Vec<u8> =
+				samples.iter().flat_map(|&s| (s as f32 / 32768.0).to_le_bytes()).collect();
+			std::fs::write(path, data)?;
+		}
+		SliceFormat::Wav => write_mono_wav(path, samples, options.sample_rate)?,
+	}
+	Ok(())
+}
+
+pub(crate) fn write_mono_wav(path: &str, samples: &[i16], sample_rate: u32) -> IoResult<()> {
+	let format = WavFormat { channels: 1, sample_rate, bit_depth: 16 };
+	let timebase = Timebase::new(1, sample_rate);
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let frame = Frame::new_audio(FrameAudio::new(data, sample_rate, 1), timebase, 0);
+
+	let mut writer = WavWriter::new(FileAdapter::create(path)?, format)?;
+	if let Some(packet) = PcmEncoder::new(timebase).encode(frame)? {
+		writer.write_packet(packet)?;
+	}
+	writer.finalize()
+}
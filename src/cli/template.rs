@@ -0,0 +1,143 @@
+use crate::show::{Show, ShowOptions, StreamInfo};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// what a batch output template such as `out/{name}_{rate}hz.{ext}` can name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateVars {
+	// input file name without its extension, also `{stem}`
+	pub name: String,
+	// 1-based position in the batch; `{index:03}` pads it
+	pub index: usize,
+	// first stream's codec, e.g. pcm_s16le, flac, rawvideo
+	pub codec: String,
+	// sample rate of audio, frames per second of video
+	pub rate: String,
+	// the day the batch ran, yyyy-mm-dd (utc)
+	pub date: String,
+	// input extension as written
+	pub ext: String,
+	// directory below a recursive input, empty at its top
+	pub dir: String,
+}
+
+impl TemplateVars {
+	// `codec` and `rate` need the input opened, so they are only filled when
+	// `template` names them
+	pub fn for_input(template: &str, input: &str, relative: &Path, index: usize) -> Self {
+		let path = Path::new(input);
+		let text = |value: Option<&std::ffi::OsStr>| {
+			value.and_then(|v| v.to_str()).unwrap_or_default().to_string()
+		};
+		let mut vars = TemplateVars {
+			name: text(path.file_stem()),
+			index,
+			date: today(),
+			ext: text(path.extension()),
+			dir: relative.parent().map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default(),
+			..TemplateVars::default()
+		};
+		if template.contains("{codec}") || template.contains("{rate}") {
+			(vars.codec, vars.rate) = probe(input);
+		}
+		vars
+	}
+}
+
+pub fn is_template(output: &str) -> bool {
+	output.contains('{')
+}
+
+pub fn render(template: &str, vars: &TemplateVars) -> std::io::Result<String> {
+	let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+	let mut out = String::with_capacity(template.len());
+	let mut rest = template;
+	while let Some(open) = rest.find('{') {
+		out.push_str(&rest[..open]);
+		let Some(close) = rest[open..].find('}') else {
+			return Err(invalid(format!("output template '{}' has an unclosed '{{'", template)));
+		};
+		let field = &rest[open + 1..open + close];
+		let (key, width) = match field.split_once(':') {
+			Some((key, width)) => (key, Some(width)),
+			None => (field, None),
+		};
+		let value = match key {
+			"name" | "stem" => vars.name.clone(),
+			"index" => match width {
+				Some(width) => {
+					let width: usize = width.parse().map_err(|_| {
+						invalid(format!("invalid index width '{}' (e.g., {{index:03}})", width))
+					})?;
+					format!("{:0width$}", vars.index, width = width)
+				}
+				None => vars.index.to_string(),
+			},
+			"codec" => vars.codec.clone(),
+			"rate" => vars.rate.clone(),
+			"date" => vars.date.clone(),
+			"ext" => vars.ext.clone(),
+			"dir" => vars.dir.clone(),
+			other => {
+				return Err(invalid(format!(
+					"unknown template variable '{{{}}}' (name, index, codec, rate, date, ext, dir)",
+					other
+				)));
+			}
+		};
+		out.push_str(&value);
+		rest = &rest[open + close + 1..];
+	}
+	out.push_str(rest);
+	Ok(out)
+}
+
+// codec and rate of the first stream; "unknown" when the input cannot be read
+fn probe(input: &str) -> (String, String) {
+	let unknown = || ("unknown".to_string(), "unknown".to_string());
+	let Ok(info) = Show::new(input.to_string(), ShowOptions::default()).analyze() else {
+		return unknown();
+	};
+	let Some(stream) = info.streams.first() else {
+		return unknown();
+	};
+	// "rawvideo (yuv420p)" and "pcm (tag=1)" keep only the word a file name can hold
+	let codec = |codec: &str| codec.split_whitespace().next().unwrap_or("unknown").to_string();
+	match stream {
+		StreamInfo::Audio(audio) => (codec(&audio.codec), audio.sample_rate.to_string()),
+		StreamInfo::Video(video) => (codec(&video.codec), format_rate(&video.frame_rate)),
+	}
+}
+
+// "30/1" or "30.00" as 30, "30000/1001" as 29.97
+fn format_rate(frame_rate: &str) -> String {
+	let fps = match frame_rate.split_once('/') {
+		Some((num, den)) => match (num.parse::<f64>(), den.parse::<f64>()) {
+			(Ok(num), Ok(den)) if den > 0.0 => num / den,
+			_ => return frame_rate.replace('/', "_"),
+		},
+		None => frame_rate.parse().unwrap_or(0.0),
+	};
+	let text = format!("{:.2}", fps);
+	text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub fn today() -> String {
+	let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+	let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+	format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// days since 1970-01-01 to a gregorian date (howard hinnant's algorithm)
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+	let z = days + 719_468;
+	let era = z.div_euclid(146_097);
+	let doe = z.rem_euclid(146_097);
+	let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+	let year = yoe + era * 400 + i64::from(month <= 2);
+	(year, month, day)
+}
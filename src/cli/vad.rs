@@ -0,0 +1,197 @@
+use super::pipeline::expand_pattern;
+use super::slice::{Manifest, ManifestRow, decode_mono, write_mono_wav};
+use crate::core::parse_duration;
+use crate::io::{IoError, IoResult};
+use clap::Parser;
+use std::path::Path;
+
+#[derive(Parser, Debug)]
+#[command(
+	name = "ffmpreg vad",
+	bin_name = "ffmpreg vad",
+	about = "Split recordings into speech segments for speech recognition"
+)]
+pub struct VadArgs {
+	#[arg(short, long, value_name = "FILE", help = "Input file or glob pattern")]
+	pub input: String,
+
+	#[arg(short, long, value_name = "DIR", default_value = "segments", help = "Output directory")]
+	pub output: String,
+
+	#[arg(
+		long,
+		value_name = "DB",
+		default_value = "-40",
+		allow_hyphen_values = true,
+		help = "Frames louder than this (dBFS RMS) may be speech"
+	)]
+	pub threshold: f32,
+
+	#[arg(long = "min-speech", value_name = "DURATION", default_value = "250ms")]
+	pub min_speech: String,
+
+	#[arg(
+		long = "min-silence",
+		value_name = "DURATION",
+		default_value = "300ms",
+		help = "Shorter pauses do not end a segment"
+	)]
+	pub min_silence: String,
+
+	#[arg(
+		long,
+		value_name = "DURATION",
+		default_value = "100ms",
+		help = "Context kept on each side"
+	)]
+	pub padding: String,
+
+	#[arg(long, value_name = "HZ", default_value = "16000", help = "Output sample rate")]
+	pub rate: u32,
+}
+
+impl VadArgs {
+	pub fn run(&self) -> std::io::Result<()> {
+		let to_io = std::io::Error::from;
+		let duration = |flag: &str, value: &str| {
+			parse_duration(value)
+				.ok_or_else(|| std::io::Error::other(format!("invalid --{} '{}'", flag, value)))
+		};
+		let options = VadOptions {
+			threshold_db: self.threshold,
+			min_speech: duration("min-speech", &self.min_speech)?,
+			min_silence: duration("min-silence", &self.min_silence)?,
+			padding: duration("padding", &self.padding)?,
+			sample_rate: self.rate,
+		};
+		options.validate().map_err(to_io)?;
+
+		let files = expand_pattern(&self.input)?;
+		if files.is_empty() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no files matching pattern: {}", self.input),
+			));
+		}
+		std::fs::create_dir_all(&self.output)?;
+
+		let mut manifest = Manifest::new();
+		for input in &files {
+			let count = split_file(input, &self.output, &options, &mut manifest)
+				.map_err(|e| std::io::Error::other(format!("{}: {}", input, e)))?;
+			println!("{}: {} segments", input, count);
+		}
+
+		let manifest_path = format!("{}/manifest.csv", self.output);
+		std::fs::write(&manifest_path, manifest.to_csv())?;
+		println!("wrote {} segments, manifest {}", manifest.rows.len(), manifest_path);
+		Ok(())
+	}
+}
+
+// durations in seconds
+#[derive(Debug, Clone, Copy)]
+pub struct VadOptions {
+	pub threshold_db: f32,
+	pub min_speech: f64,
+	pub min_silence: f64,
+	pub padding: f64,
+	pub sample_rate: u32,
+}
+
+impl Default for VadOptions {
+	fn default() -> Self {
+		Self {
+			threshold_db: -40.0,
+			min_speech: 0.25,
+			min_silence: 0.3,
+			padding: 0.1,
+			sample_rate: 16000,
+		}
+	}
+}
+
+impl VadOptions {
+	pub fn validate(&self) -> IoResult<()> {
+		if self.sample_rate == 0 {
+			return Err(IoError::invalid_data("sample rate must be positive"));
+		}
+		if self.min_speech < 0.0 || self.min_silence < 0.0 || self.padding < 0.0 {
+			return Err(IoError::invalid_data("vad durations must not be negative"));
+		}
+		Ok(())
+	}
+}
+
+// analysis frame length; short enough to catch word onsets
+const FRAME_SECONDS: f64 = 0.02;
+
+// above this many sign changes per sample a loud frame is hiss rather than
+// voice; unvoiced consonants land above it too, which `padding` and the
+// `min_silence` bridge make up for
+const MAX_ZERO_CROSSING_RATE: f64 = 0.35;
+
+// (start, end) sample offsets of the speech in `samples`
+pub fn detect_speech(
+	samples: &[i16],
+	sample_rate: u32,
+	options: &VadOptions,
+) -> Vec<(usize, usize)> {
+	let rate = sample_rate as f64;
+	let frame_len = ((FRAME_SECONDS * rate).round() as usize).max(1);
+	let threshold = 10f64.powf(options.threshold_db as f64 / 20.0) * 32768.0;
+
+	let mut segments: Vec<(usize, usize)> = Vec::new();
+	for (idx, frame) in samples.chunks(frame_len).enumerate() {
+		let rms = (frame.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / frame.len() as f64).sqrt();
+		let crossings = frame.windows(2).filter(|pair| (pair[0] < 0) != (pair[1] < 0)).count();
+		let zero_crossing_rate = crossings as f64 / frame.len() as f64;
+		if rms < threshold || zero_crossing_rate > MAX_ZERO_CROSSING_RATE {
+			continue;
+		}
+
+		let start = idx * frame_len;
+		let end = start + frame.len();
+		let gap = (options.min_silence * rate) as usize;
+		match segments.last_mut() {
+			Some(last) if start <= last.1 + gap => last.1 = end,
+			_ => segments.push((start, end)),
+		}
+	}
+
+	let min_speech = (options.min_speech * rate) as usize;
+	let padding = (options.padding * rate) as usize;
+	let mut padded: Vec<(usize, usize)> = Vec::new();
+	for (start, end) in segments.into_iter().filter(|(start, end)| end - start >= min_speech) {
+		let (start, end) = (start.saturating_sub(padding), (end + padding).min(samples.len()));
+		match padded.last_mut() {
+			Some(last) if start <= last.1 => last.1 = end,
+			_ => padded.push((start, end)),
+		}
+	}
+	padded
+}
+
+pub fn split_file(
+	input: &str,
+	output_dir: &str,
+	options: &VadOptions,
+	manifest: &mut Manifest,
+) -> IoResult<usize> {
+	let samples = decode_mono(input, options.sample_rate)?;
+	let rate = options.sample_rate as f64;
+	let stem = Path::new(input).file_stem().and_then(|s| s.to_str()).unwrap_or("segment");
+	let segments = detect_speech(&samples, options.sample_rate, options);
+
+	for (idx, &(start, end)) in segments.iter().enumerate() {
+		let name = format!("{}_{:05}.wav", stem, idx);
+		write_mono_wav(&format!("{}/{}", output_dir, name), &samples[start..end], options.sample_rate)?;
+		manifest.rows.push(ManifestRow {
+			file: name,
+			source: input.to_string(),
+			start: start as f64 / rate,
+			end: end as f64 / rate,
+		});
+	}
+	Ok(segments.len())
+}
@@ -0,0 +1,226 @@
+use super::pipeline::{MediaType, Pipeline, walk_directory};
+use crate::codecs::CodecId;
+use crate::core::log;
+use crate::io::{AllowAll, Overwrite, PathAccess, PathPolicy};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+// size and modification time, which stop changing once a copy has finished
+type FileStamp = (u64, Option<SystemTime>);
+
+// converts files as they appear in a directory. there is no portable change
+// notification without a dependency, so the directory is polled; a new file
+// is only converted once its stamp has held still for `settle`, so a file
+// still being copied in is not read half-written
+pub struct Watcher {
+	dir: PathBuf,
+	output_dir: PathBuf,
+	transforms: Vec<String>,
+	format: Option<MediaType>,
+	output_format: Option<MediaType>,
+	codec: Option<CodecId>,
+	recursive: bool,
+	extensions: Vec<String>,
+	overwrite: Overwrite,
+	interval: Duration,
+	settle: Duration,
+	path_policy: Arc<dyn PathPolicy>,
+	// files waiting to settle: the stamp last seen and since when
+	pending: HashMap<PathBuf, (FileStamp, Instant)>,
+	// files converted (or failed) at the stamp they had then
+	handled: HashMap<PathBuf, FileStamp>,
+}
+
+impl Watcher {
+	pub fn new(
+		dir: impl Into<PathBuf>,
+		output_dir: impl Into<PathBuf>,
+		transforms: Vec<String>,
+	) -> Self {
+		Self {
+			dir: dir.into(),
+			output_dir: output_dir.into(),
+			transforms,
+			format: None,
+			output_format: None,
+			codec: None,
+			recursive: false,
+			extensions: Vec::new(),
+			overwrite: Overwrite::Refuse,
+			interval: Duration::from_secs(1),
+			settle: Duration::from_secs(2),
+			path_policy: Arc::new(AllowAll),
+			pending: HashMap::new(),
+			handled: HashMap::new(),
+		}
+	}
+
+	pub fn with_format(mut self, format: MediaType) -> Self {
+		self.format = Some(format);
+		self
+	}
+
+	pub fn with_output_format(mut self, format: MediaType) -> Self {
+		self.output_format = Some(format);
+		self
+	}
+
+	pub fn with_codec(mut self, codec: CodecId) -> Self {
+		self.codec = Some(codec);
+		self
+	}
+
+	pub fn with_recursive(mut self, recursive: bool) -> Self {
+		self.recursive = recursive;
+		self
+	}
+
+	pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+		self.extensions = extensions
+			.into_iter()
+			.map(|ext| ext.trim_start_matches('.').to_lowercase())
+			.filter(|ext| !ext.is_empty())
+			.collect();
+		self
+	}
+
+	pub fn with_overwrite(mut self, overwrite: Overwrite) -> Self {
+		self.overwrite = overwrite;
+		self
+	}
+
+	// how often the directory is listed
+	pub fn with_interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+
+	// how long a file has to stay the same size before it is converted
+	pub fn with_settle(mut self, settle: Duration) -> Self {
+		self.settle = settle;
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	// watches until the process is stopped. files already in the directory
+	// are left alone; only the ones that arrive afterwards are converted
+	pub fn run(&mut self) -> std::io::Result<()> {
+		self.prime()?;
+		println!("Watching: {} -> {}", self.dir.display(), self.output_dir.display());
+		loop {
+			for path in self.poll(Instant::now())? {
+				self.convert_logged(&path);
+			}
+			std::thread::sleep(self.interval);
+		}
+	}
+
+	// marks everything present now as handled
+	pub fn prime(&mut self) -> std::io::Result<()> {
+		for (path, stamp) in self.scan()? {
+			self.handled.insert(path, stamp);
+		}
+		Ok(())
+	}
+
+	// the files that have settled by `now` and are due a conversion; each is
+	// returned once per version of its contents
+	pub fn poll(&mut self, now: Instant) -> std::io::Result<Vec<PathBuf>> {
+		let files = self.scan()?;
+		// a file deleted while settling is forgotten
+		self.pending.retain(|path, _| files.iter().any(|(file, _)| file == path));
+
+		let mut ready = Vec::new();
+		for (path, stamp) in files {
+			if self.handled.get(&path) == Some(&stamp) {
+				continue;
+			}
+			match self.pending.get(&path) {
+				Some((seen, since)) if *seen == stamp => {
+					if now.duration_since(*since) >= self.settle {
+						self.pending.remove(&path);
+						self.handled.insert(path.clone(), stamp);
+						ready.push(path);
+					}
+				}
+				_ => {
+					self.pending.insert(path, (stamp, now));
+				}
+			}
+		}
+		Ok(ready)
+	}
+
+	// converts one input into the mirrored path under the output directory
+	pub fn convert(&self, path: &Path) -> std::io::Result<PathBuf> {
+		let relative = path.strip_prefix(&self.dir).unwrap_or(path);
+		let mut output = self.output_dir.join(relative);
+		if let Some(format) = self.output_format {
+			output.set_extension(format.extension());
+		}
+		if let Some(parent) = output.parent() {
+			self
+				.path_policy
+				.check(parent, PathAccess::Write)
+				.map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied, e.to_string()))?;
+			std::fs::create_dir_all(parent)?;
+		}
+
+		let mut pipeline = Pipeline::new(
+			path.to_string_lossy().to_string(),
+			Some(output.to_string_lossy().to_string()),
+			false,
+			self.transforms.clone(),
+		)
+		.with_overwrite(self.overwrite)
+		.with_path_policy(self.path_policy.clone());
+		if let Some(format) = self.format {
+			pipeline = pipeline.with_format(format);
+		}
+		if let Some(codec) = self.codec {
+			pipeline = pipeline.with_codec(codec);
+		}
+		pipeline.run()?;
+		Ok(output)
+	}
+
+	// a daemon outlives any one bad file, so failures are reported and skipped
+	fn convert_logged(&self, path: &Path) {
+		println!("Processing: {}", path.display());
+		match self.convert(path) {
+			Ok(output) => println!("  -> {}", output.display()),
+			Err(e) => log::error("watch", format_args!("{}: {}", path.display(), e)),
+		}
+	}
+
+	fn scan(&self) -> std::io::Result<Vec<(PathBuf, FileStamp)>> {
+		// outputs written inside the watched directory are not picked up again
+		let skip = self.output_dir.canonicalize().ok();
+		let mut files = Vec::new();
+		for path in walk_directory(&self.dir, self.recursive, skip.as_deref())? {
+			if !self.wants(&path) {
+				continue;
+			}
+			// a file can vanish between the listing and this
+			let Ok(metadata) = std::fs::metadata(&path) else {
+				continue;
+			};
+			files.push((path, (metadata.len(), metadata.modified().ok())));
+		}
+		Ok(files)
+	}
+
+	fn wants(&self, path: &Path) -> bool {
+		let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+		if self.extensions.is_empty() {
+			return MediaType::from_extension(&path.to_string_lossy()) != MediaType::Unknown;
+		}
+		self.extensions.iter().any(|wanted| wanted.eq_ignore_ascii_case(ext))
+	}
+}
@@ -0,0 +1,126 @@
+use super::preview::decode_audio;
+use super::sample::write_image;
+use crate::image::RgbImage;
+use crate::io::{IoError, IoResult};
+
+// a lane per channel, a pixel row between lanes
+const IMAGE_WIDTH: u32 = 1200;
+const LANE_HEIGHT: u32 = 120;
+const BACKGROUND: u8 = 32;
+const CENTER: [u8; 3] = [72, 72, 72];
+const WAVE: [u8; 3] = [96, 200, 128];
+
+// the terminal fallback: a character per column, `TEXT_ROWS` lines per channel
+const TEXT_WIDTH: usize = 80;
+const TEXT_ROWS: usize = 8;
+
+// the lowest and highest sample of each column, per channel, in -1.0..=1.0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+	pub sample_rate: u32,
+	pub samples: usize,
+	pub channels: Vec<Vec<(f32, f32)>>,
+}
+
+impl Waveform {
+	// each of `columns` covers an equal share of the samples; with fewer
+	// samples than columns, some columns share one
+	pub fn from_planes(planes: &[Vec<f32>], sample_rate: u32, columns: usize) -> Self {
+		let samples = planes.first().map_or(0, Vec::len);
+		let channels = planes
+			.iter()
+			.map(|plane| {
+				(0..columns)
+					.map(|column| {
+						let start = column * samples / columns;
+						let end = ((column + 1) * samples / columns).max(start + 1).min(samples);
+						let span = plane.get(start..end).unwrap_or_default();
+						let min = span.iter().copied().fold(0.0f32, f32::min);
+						let max = span.iter().copied().fold(0.0f32, f32::max);
+						(min.max(-1.0), max.min(1.0))
+					})
+					.collect()
+			})
+			.collect();
+		Self { sample_rate, samples, channels }
+	}
+
+	pub fn duration(&self) -> f64 {
+		self.samples as f64 / self.sample_rate.max(1) as f64
+	}
+
+	pub fn to_image(&self, lane_height: u32) -> RgbImage {
+		let width = self.channels.first().map_or(0, Vec::len) as u32;
+		let lanes = self.channels.len() as u32;
+		let height = (lanes * lane_height + lanes.saturating_sub(1)).max(1);
+		let mut image = RgbImage::new(width, height, vec![BACKGROUND; (width * height * 3) as usize]);
+		let mut put = |x: u32, y: u32, color: [u8; 3]| {
+			let idx = ((y * width + x) * 3) as usize;
+			image.data[idx..idx + 3].copy_from_slice(&color);
+		};
+		for (lane, columns) in self.channels.iter().enumerate() {
+			let top = lane as u32 * (lane_height + 1);
+			for x in 0..width {
+				put(x, top + lane_height / 2, CENTER);
+			}
+			for (x, &(min, max)) in columns.iter().enumerate() {
+				for y in row(max, lane_height as usize)..=row(min, lane_height as usize) {
+					put(x as u32, top + y as u32, WAVE);
+				}
+			}
+		}
+		image
+	}
+
+	// a block where the column reaches, a line through the middle where it
+	// does not
+	pub fn to_text(&self, rows: usize) -> String {
+		let mut out = String::new();
+		for (channel, columns) in self.channels.iter().enumerate() {
+			out.push_str(&format!("  channel {}\n", channel));
+			for line in 0..rows {
+				let text: String = columns
+					.iter()
+					.map(|&(min, max)| {
+						if (row(max, rows)..=row(min, rows)).contains(&line) {
+							'█'
+						} else if line == rows / 2 {
+							'─'
+						} else {
+							' '
+						}
+					})
+					.collect();
+				out.push_str(&format!("  {}\n", text.trim_end()));
+			}
+		}
+		out
+	}
+}
+
+// the row of `rows` a level falls on, full scale at the top
+fn row(level: f32, rows: usize) -> usize {
+	(((1.0 - level) / 2.0 * rows as f32) as usize).min(rows.saturating_sub(1))
+}
+
+pub fn waveform(input: &str, columns: usize) -> IoResult<Waveform> {
+	let audio = decode_audio(input)?;
+	if audio.data.is_empty() {
+		return Err(IoError::invalid_data("input has no audio"));
+	}
+	Ok(Waveform::from_planes(&audio.planes_f32(), audio.sample_rate, columns))
+}
+
+// `output` a .png or .ppm image, or None to draw it in the terminal
+pub fn run_waveform(input: &str, output: Option<&str>) -> IoResult<()> {
+	match output {
+		Some(output) => {
+			write_image(output, &waveform(input, IMAGE_WIDTH as usize)?.to_image(LANE_HEIGHT))
+		}
+		None => {
+			let wave = waveform(input, TEXT_WIDTH)?;
+			print!("{}  {:.2} s\n{}", input, wave.duration(), wave.to_text(TEXT_ROWS));
+			Ok(())
+		}
+	}
+}
@@ -6,6 +6,7 @@ use crate::io::IoResult;
 
 pub struct FlacDecoder {
 	stream_info: FlacStreamInfo,
+	planar: bool,
 }
 
 impl FlacDecoder {
@@ -20,11 +21,18 @@ impl FlacDecoder {
 			bits_per_sample: format.bits_per_sample,
 			total_samples: format.total_samples,
 		};
-		Self { stream_info }
+		Self { stream_info, planar: false }
 	}
 
 	pub fn from_stream_info(stream_info: FlacStreamInfo) -> Self {
-		Self { stream_info }
+		Self { stream_info, planar: false }
+	}
+
+	// hand 16-bit streams out as `SampleFormat::S16P`, the way flac stores
+	// them, instead of interleaving every block
+	pub fn with_planar(mut self, planar: bool) -> Self {
+		self.planar = planar;
+		self
 	}
 
 	fn samples_to_bytes(&self, samples: &[Vec<i32>]) -> Vec<u8> {
@@ -68,11 +76,15 @@ impl Decoder for FlacDecoder {
 	fn decode(&mut self, packet: Packet) -> IoResult<Option<Frame>> {
 		let flac_frame = decode_frame(&packet.data, &self.stream_info)?;
 
-		let output = self.samples_to_bytes(&flac_frame.samples);
-		let nb_samples = flac_frame.block_size;
-
-		let audio = FrameAudio::new(output, self.stream_info.sample_rate, self.stream_info.channels)
-			.with_nb_samples(nb_samples);
+		let audio = if self.planar && self.stream_info.bits_per_sample == 16 {
+			let planes: Vec<Vec<i16>> =
+				flac_frame.samples.iter().map(|plane| plane.iter().map(|&s| s as i16).collect()).collect();
+			FrameAudio::from_planes_i16(&planes, self.stream_info.sample_rate)
+		} else {
+			let output = self.samples_to_bytes(&flac_frame.samples);
+			FrameAudio::new(output, self.stream_info.sample_rate, self.stream_info.channels)
+				.with_nb_samples(flac_frame.block_size)
+		};
 
 		let frame = Frame::new_audio(audio, packet.timebase, packet.stream_index).with_pts(packet.pts);
 
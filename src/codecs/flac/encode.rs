@@ -1,6 +1,6 @@
 use super::FlacStreamInfo;
 use super::frame::encode_frame;
-use crate::core::{Encoder, Frame, Packet, Timebase};
+use crate::core::{Encoder, Frame, FrameAudio, FrameData, Packet, SampleFormat, Timebase};
 use crate::io::IoResult;
 
 pub struct FlacEncoder {
@@ -66,17 +66,32 @@ impl FlacEncoder {
 
 		channel_samples
 	}
+
+	// planar and f32 frames already come apart per channel; s16 values are
+	// shifted to the stream's bit depth
+	fn planes_to_samples(&self, audio: &FrameAudio) -> Vec<Vec<i32>> {
+		let bits = self.stream_info.bits_per_sample as i32;
+		audio
+			.planes_i16()
+			.into_iter()
+			.map(|plane| {
+				plane
+					.into_iter()
+					.map(|s| if bits >= 16 { (s as i32) << (bits - 16) } else { (s as i32) >> (16 - bits) })
+					.collect()
+			})
+			.collect()
+	}
 }
 
 impl Encoder for FlacEncoder {
 	fn encode(&mut self, frame: Frame) -> IoResult<Option<Packet>> {
-		let data_bytes = match &frame.data {
-			crate::core::FrameData::Audio(audio) => &audio.data,
-			crate::core::FrameData::Video(video) => &video.data,
+		let samples = match &frame.data {
+			FrameData::Audio(audio) if audio.format != SampleFormat::S16 => self.planes_to_samples(audio),
+			FrameData::Audio(audio) => self.bytes_to_samples(&audio.data),
+			FrameData::Video(video) => self.bytes_to_samples(&video.data),
 		};
 
-		let samples = self.bytes_to_samples(data_bytes);
-
 		if samples.is_empty() || samples[0].is_empty() {
 			return Ok(None);
 		}
@@ -293,7 +293,7 @@ pub fn encode_frame(
 		_ => {}
 	}
 
-	let header_crc = 0u8;
+	let header_crc = crc8(writer.bytes());
 	writer.write_bits(header_crc as u32, 8);
 
 	let bps = stream_info.bits_per_sample;
@@ -318,8 +318,24 @@ pub fn encode_frame(
 
 	writer.align_to_byte();
 
-	let frame_crc = 0u16;
+	let frame_crc = crc16(writer.bytes());
 	writer.write_bits(frame_crc as u32, 16);
 
 	writer.finish()
 }
+
+// the frame header's check, polynomial x^8 + x^2 + x + 1, from the sync code on
+pub fn crc8(data: &[u8]) -> u8 {
+	data.iter().fold(0u8, |crc, &byte| {
+		(0..8).fold(crc ^ byte, |crc, _| if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 })
+	})
+}
+
+// the whole frame's check, polynomial x^16 + x^15 + x^2 + 1, up to the crc itself
+pub fn crc16(data: &[u8]) -> u16 {
+	data.iter().fold(0u16, |crc, &byte| {
+		(0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| {
+			if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 }
+		})
+	})
+}
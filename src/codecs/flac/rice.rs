@@ -1,5 +1,8 @@
 use crate::io::{IoError, IoResult};
 
+// the longest unary run either side accepts
+const MAX_QUOTIENT: u32 = 32;
+
 pub struct BitReader<'a> {
 	data: &'a [u8],
 	byte_pos: usize,
@@ -64,7 +67,7 @@ impl<'a> BitReader<'a> {
 		let mut count = 0u32;
 		while !self.read_bit()? {
 			count += 1;
-			if count > 32 {
+			if count > MAX_QUOTIENT {
 				return Err(IoError::invalid_data("unary value too large"));
 			}
 		}
@@ -231,6 +234,11 @@ impl BitWriter {
 		}
 	}
 
+	// the whole bytes written so far
+	pub fn bytes(&self) -> &[u8] {
+		&self.data
+	}
+
 	pub fn align_to_byte(&mut self) {
 		if self.bit_pos != 0 {
 			self.data.push(self.current_byte);
@@ -261,14 +269,32 @@ pub fn encode_residual(
 ) {
 	writer.write_bits(0, 2);
 
-	let rice_param = estimate_rice_parameter(&residuals[predictor_order..]);
-	let rice_param = rice_param.min(14);
+	let residuals = &residuals[predictor_order..];
+	// one large residual (a step in the signal) would need a longer unary run
+	// than a decoder takes, so the parameter grows to fit it, and past 14 the
+	// partition is stored as plain signed values
+	let largest = residuals.iter().map(|&r| encode_rice_signed(r)).max().unwrap_or(0);
+	let mut rice_param = estimate_rice_parameter(residuals).min(14);
+	while rice_param < 14 && largest >> rice_param > MAX_QUOTIENT {
+		rice_param += 1;
+	}
 
 	writer.write_bits(0, 4);
 
+	if largest >> rice_param > MAX_QUOTIENT {
+		let widest = residuals.iter().map(|&r| if r < 0 { !r } else { r } as u32).max().unwrap_or(0);
+		let bits = 33 - widest.leading_zeros();
+		writer.write_bits(15, 4);
+		writer.write_bits(bits, 5);
+		for &residual in residuals {
+			writer.write_bits_signed(residual, bits);
+		}
+		return;
+	}
+
 	writer.write_bits(rice_param as u32, 4);
 
-	for &residual in &residuals[predictor_order..] {
+	for &residual in residuals {
 		let unsigned = encode_rice_signed(residual);
 		let quotient = unsigned >> rice_param;
 		let remainder = unsigned & ((1 << rice_param) - 1);
@@ -0,0 +1,86 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecId {
+	PcmU8,
+	PcmS16Le,
+	PcmS16Be,
+	PcmS24Le,
+	PcmS24Be,
+	PcmS32Le,
+	PcmS32Be,
+	Adpcm,
+	MsAdpcm,
+	Alaw,
+	Ulaw,
+	Flac,
+	RawVideo,
+}
+
+impl CodecId {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"pcm_u8" | "u8" => Some(CodecId::PcmU8),
+			"pcm" | "pcm_s16le" | "s16le" => Some(CodecId::PcmS16Le),
+			"pcm_s16be" | "s16be" => Some(CodecId::PcmS16Be),
+			"pcm_s24le" | "s24le" => Some(CodecId::PcmS24Le),
+			"pcm_s24be" | "s24be" => Some(CodecId::PcmS24Be),
+			"pcm_s32le" | "s32le" => Some(CodecId::PcmS32Le),
+			"pcm_s32be" | "s32be" => Some(CodecId::PcmS32Be),
+			"adpcm" | "adpcm_ima" => Some(CodecId::Adpcm),
+			"adpcm_ms" => Some(CodecId::MsAdpcm),
+			"alaw" | "pcm_alaw" => Some(CodecId::Alaw),
+			"ulaw" | "mulaw" | "pcm_mulaw" => Some(CodecId::Ulaw),
+			"flac" => Some(CodecId::Flac),
+			"rawvideo" => Some(CodecId::RawVideo),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			CodecId::PcmU8 => "pcm_u8",
+			CodecId::PcmS16Le => "pcm_s16le",
+			CodecId::PcmS16Be => "pcm_s16be",
+			CodecId::PcmS24Le => "pcm_s24le",
+			CodecId::PcmS24Be => "pcm_s24be",
+			CodecId::PcmS32Le => "pcm_s32le",
+			CodecId::PcmS32Be => "pcm_s32be",
+			CodecId::Adpcm => "adpcm_ima",
+			CodecId::MsAdpcm => "adpcm_ms",
+			CodecId::Alaw => "pcm_alaw",
+			CodecId::Ulaw => "pcm_mulaw",
+			CodecId::Flac => "flac",
+			CodecId::RawVideo => "rawvideo",
+		}
+	}
+
+	pub fn pcm_from_bit_depth(bit_depth: u16, big_endian: bool) -> Option<Self> {
+		match (bit_depth, big_endian) {
+			(8, _) => Some(CodecId::PcmU8),
+			(16, false) => Some(CodecId::PcmS16Le),
+			(16, true) => Some(CodecId::PcmS16Be),
+			(24, false) => Some(CodecId::PcmS24Le),
+			(24, true) => Some(CodecId::PcmS24Be),
+			(32, false) => Some(CodecId::PcmS32Le),
+			(32, true) => Some(CodecId::PcmS32Be),
+			_ => None,
+		}
+	}
+
+	pub fn is_pcm(&self) -> bool {
+		self.pcm_bytes_per_sample().is_some()
+	}
+
+	pub fn is_big_endian(&self) -> bool {
+		matches!(self, CodecId::PcmS16Be | CodecId::PcmS24Be | CodecId::PcmS32Be)
+	}
+
+	pub fn pcm_bytes_per_sample(&self) -> Option<usize> {
+		match self {
+			CodecId::PcmU8 => Some(1),
+			CodecId::PcmS16Le | CodecId::PcmS16Be => Some(2),
+			CodecId::PcmS24Le | CodecId::PcmS24Be => Some(3),
+			CodecId::PcmS32Le | CodecId::PcmS32Be => Some(4),
+			_ => None,
+		}
+	}
+}
@@ -1,11 +1,13 @@
 pub mod adpcm;
 pub mod flac;
 pub mod g711;
+pub mod id;
 pub mod pcm;
 pub mod rawvideo;
 
 pub use adpcm::{AdpcmDecoder, AdpcmEncoder, MsAdpcmDecoder, MsAdpcmEncoder};
 pub use flac::{FlacDecoder, FlacEncoder};
 pub use g711::{AlawDecoder, AlawEncoder, UlawDecoder, UlawEncoder};
+pub use id::CodecId;
 pub use pcm::{PcmDecoder, PcmEncoder};
 pub use rawvideo::{RawVideoDecoder, RawVideoEncoder};
@@ -1,22 +1,58 @@
+use super::sample::decode_samples;
+use crate::codecs::CodecId;
 use crate::container::WavFormat;
-use crate::core::{Decoder, Frame, FrameAudio, Packet};
+use crate::core::{Decoder, Frame, FrameAudio, Packet, log};
 use crate::io::IoResult;
 
 pub struct PcmDecoder {
 	format: WavFormat,
+	codec: CodecId,
 }
 
 impl PcmDecoder {
 	pub fn new(format: WavFormat) -> Self {
-		Self { format }
+		let codec = format.codec_id();
+		Self { format, codec }
+	}
+
+	pub fn with_codec(mut self, codec: CodecId) -> Self {
+		self.codec = codec;
+		self
+	}
+
+	pub fn codec(&self) -> CodecId {
+		self.codec
 	}
 }
 
 impl Decoder for PcmDecoder {
 	fn decode(&mut self, packet: Packet) -> IoResult<Option<Frame>> {
-		let nb_samples = packet.size() / self.format.bytes_per_frame();
-		let audio = FrameAudio::new(packet.data, self.format.sample_rate, self.format.channels)
-			.with_nb_samples(nb_samples);
+		let bytes_per_sample = self.codec.pcm_bytes_per_sample().unwrap_or(2);
+		let bytes_per_frame = (bytes_per_sample * self.format.channels as usize).max(1);
+		let nb_samples = packet.size() / bytes_per_frame;
+		let partial = packet.size() - nb_samples * bytes_per_frame;
+		if partial > 0 {
+			log::warn(
+				"pcm",
+				format_args!(
+					"packet at {} ends with {} bytes of a partial sample frame",
+					packet.pts, partial
+				),
+			);
+		}
+
+		let audio = match self.codec {
+			CodecId::PcmS16Le => {
+				FrameAudio::new(packet.data, self.format.sample_rate, self.format.channels)
+			}
+			codec => {
+				let (data, format) = decode_samples(&packet.data, codec);
+				let mut audio = FrameAudio::new(data, self.format.sample_rate, self.format.channels);
+				audio.format = format;
+				audio
+			}
+		}
+		.with_nb_samples(nb_samples);
 
 		let frame = Frame::new_audio(audio, packet.timebase, packet.stream_index).with_pts(packet.pts);
 
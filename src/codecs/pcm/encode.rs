@@ -1,13 +1,25 @@
+use super::sample::encode_samples;
+use crate::codecs::CodecId;
 use crate::core::{Encoder, Frame, Packet, Timebase};
 use crate::io::IoResult;
 
 pub struct PcmEncoder {
 	timebase: Timebase,
+	codec: CodecId,
 }
 
 impl PcmEncoder {
 	pub fn new(timebase: Timebase) -> Self {
-		Self { timebase }
+		Self { timebase, codec: CodecId::PcmS16Le }
+	}
+
+	pub fn with_codec(mut self, codec: CodecId) -> Self {
+		self.codec = codec;
+		self
+	}
+
+	pub fn codec(&self) -> CodecId {
+		self.codec
 	}
 }
 
@@ -15,7 +27,8 @@ impl Encoder for PcmEncoder {
 	fn encode(&mut self, frame: Frame) -> IoResult<Option<Packet>> {
 		match frame.data {
 			crate::core::FrameData::Audio(audio) => {
-				let packet = Packet::new(audio.data, frame.stream_index, self.timebase).with_pts(frame.pts);
+				let data = encode_samples(audio, self.codec);
+				let packet = Packet::new(data, frame.stream_index, self.timebase).with_pts(frame.pts);
 				Ok(Some(packet))
 			}
 			crate::core::FrameData::Video(video) => {
@@ -1,5 +1,6 @@
 pub mod decode;
 pub mod encode;
+pub mod sample;
 
 pub use decode::PcmDecoder;
 pub use encode::PcmEncoder;
@@ -0,0 +1,82 @@
+use crate::codecs::CodecId;
+use crate::core::{FrameAudio, SampleFormat};
+
+// every PCM variant converts through these functions instead of per-container
+// code. u8 and 16-bit samples become interleaved s16le; 24 and 32-bit ones
+// become interleaved f32, which holds 24 bits exactly, so they keep their
+// depth on the way through and are written back at it.
+
+pub fn decode_samples(data: &[u8], codec: CodecId) -> (Vec<u8>, SampleFormat) {
+	let deep = |value: i32| (value as f32 / 2147483648.0).to_le_bytes();
+	match codec {
+		CodecId::PcmS24Le => {
+			let data = data.chunks_exact(3).flat_map(|c| deep(i32::from_le_bytes([0, c[0], c[1], c[2]])));
+			(data.collect(), SampleFormat::F32)
+		}
+		CodecId::PcmS24Be => {
+			let data = data.chunks_exact(3).flat_map(|c| deep(i32::from_be_bytes([c[0], c[1], c[2], 0])));
+			(data.collect(), SampleFormat::F32)
+		}
+		CodecId::PcmS32Le => {
+			let data =
+				data.chunks_exact(4).flat_map(|c| deep(i32::from_le_bytes([c[0], c[1], c[2], c[3]])));
+			(data.collect(), SampleFormat::F32)
+		}
+		CodecId::PcmS32Be => {
+			let data =
+				data.chunks_exact(4).flat_map(|c| deep(i32::from_be_bytes([c[0], c[1], c[2], c[3]])));
+			(data.collect(), SampleFormat::F32)
+		}
+		codec => (to_s16le(data, codec), SampleFormat::S16),
+	}
+}
+
+pub fn encode_samples(mut audio: FrameAudio, codec: CodecId) -> Vec<u8> {
+	let bytes_per_sample = codec.pcm_bytes_per_sample().unwrap_or(2);
+	if bytes_per_sample < 3 {
+		audio.convert(SampleFormat::S16);
+		return match codec {
+			CodecId::PcmS16Le => audio.data,
+			codec => from_s16le(&audio.data, codec),
+		};
+	}
+
+	let samples = audio.samples_f32().into_iter().map(|sample| {
+		let value = (sample as f64 * 2147483648.0).round().clamp(i32::MIN as f64, i32::MAX as f64);
+		value as i32
+	});
+	match codec {
+		// rounded to 24 bits first, or the truncation would bias every sample
+		CodecId::PcmS24Le | CodecId::PcmS24Be => samples
+			.flat_map(|value| {
+				let value = ((value as i64 + 128) >> 8).min(0x7f_ffff) as i32;
+				let bytes = value.to_le_bytes();
+				if codec == CodecId::PcmS24Le {
+					[bytes[0], bytes[1], bytes[2]]
+				} else {
+					[bytes[2], bytes[1], bytes[0]]
+				}
+			})
+			.collect(),
+		CodecId::PcmS32Be => samples.flat_map(i32::to_be_bytes).collect(),
+		_ => samples.flat_map(i32::to_le_bytes).collect(),
+	}
+}
+
+fn to_s16le(data: &[u8], codec: CodecId) -> Vec<u8> {
+	match codec {
+		CodecId::PcmU8 => data.iter().flat_map(|&b| (((b as i16) - 128) << 8).to_le_bytes()).collect(),
+		CodecId::PcmS16Be => data.chunks_exact(2).flat_map(|c| [c[1], c[0]]).collect(),
+		_ => data.to_vec(),
+	}
+}
+
+fn from_s16le(data: &[u8], codec: CodecId) -> Vec<u8> {
+	match codec {
+		CodecId::PcmU8 => {
+			data.chunks_exact(2).map(|c| ((i16::from_le_bytes([c[0], c[1]]) >> 8) + 128) as u8).collect()
+		}
+		CodecId::PcmS16Be => data.chunks_exact(2).flat_map(|c| [c[1], c[0]]).collect(),
+		_ => data.to_vec(),
+	}
+}
@@ -1,5 +1,5 @@
 use crate::container::Y4mFormat;
-use crate::core::{Decoder, Frame, FrameVideo, Packet, VideoFormat};
+use crate::core::{Decoder, Frame, FrameVideo, Packet, log};
 use crate::io::IoResult;
 
 pub struct RawVideoDecoder {
@@ -14,8 +14,23 @@ impl RawVideoDecoder {
 
 impl Decoder for RawVideoDecoder {
 	fn decode(&mut self, packet: Packet) -> IoResult<Option<Frame>> {
-		let video =
-			FrameVideo::new(packet.data, self.format.width, self.format.height, VideoFormat::YUV420);
+		if packet.size() != self.format.frame_size() {
+			log::warn(
+				"rawvideo",
+				format_args!(
+					"frame {} holds {} bytes where {} were expected",
+					packet.pts,
+					packet.size(),
+					self.format.frame_size()
+				),
+			);
+		}
+		let video = FrameVideo::new(
+			packet.data,
+			self.format.width,
+			self.format.height,
+			self.format.video_format(),
+		);
 		let frame = Frame::new_video(video, packet.timebase, packet.stream_index).with_pts(packet.pts);
 		Ok(Some(frame))
 	}
@@ -8,6 +8,19 @@ pub const RIFF_SIGNATURE: &[u8; 4] = b"RIFF";
 pub const AVI_SIGNATURE: &[u8; 4] = b"AVI ";
 pub const LIST_SIGNATURE: &[u8; 4] = b"LIST";
 
+// idx1 flag of a chunk a decoder can start at
+pub const AVIIF_KEYFRAME: u32 = 0x10;
+
+// one idx1 entry: `offset` is from the movi list's type fourcc, though some
+// writers make it from the start of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AviIndexEntry {
+	pub chunk_id: [u8; 4],
+	pub flags: u32,
+	pub offset: u32,
+	pub size: u32,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StreamType {
 	Video,
@@ -1,18 +1,19 @@
 use super::{
-	AVI_SIGNATURE, AviFormat, AviMainHeader, AviStream, AviStreamHeader, BitmapInfoHeader,
-	LIST_SIGNATURE, RIFF_SIGNATURE, StreamType, WaveFormatEx,
+	AVI_SIGNATURE, AviFormat, AviIndexEntry, AviMainHeader, AviStream, AviStreamHeader,
+	BitmapInfoHeader, LIST_SIGNATURE, RIFF_SIGNATURE, StreamType, WaveFormatEx,
 };
-use crate::core::{Demuxer, Packet, Timebase};
+use crate::container::fourcc_name;
+use crate::core::{Demuxer, Packet, Timebase, log};
 use crate::io::{IoError, IoResult, MediaRead, ReadPrimitives};
 
 pub struct AviReader<R: MediaRead> {
 	reader: R,
 	format: AviFormat,
 	timebase: Timebase,
-	#[allow(dead_code)]
 	movi_start: u64,
 	current_pos: u64,
 	eof: bool,
+	index: Vec<AviIndexEntry>,
 }
 
 impl<R: MediaRead> AviReader<R> {
@@ -25,13 +26,31 @@ impl<R: MediaRead> AviReader<R> {
 		};
 		let timebase = Timebase::new(1, fps);
 
-		Ok(Self { reader, format, timebase, movi_start, current_pos: movi_start, eof: false })
+		Ok(Self {
+			reader,
+			format,
+			timebase,
+			movi_start,
+			current_pos: movi_start,
+			eof: false,
+			index: Vec::new(),
+		})
 	}
 
 	pub fn format(&self) -> &AviFormat {
 		&self.format
 	}
 
+	// where the first chunk of movi starts, just past its type fourcc
+	pub fn movi_start(&self) -> u64 {
+		self.movi_start
+	}
+
+	// the idx1 entries, once the packets before them have all been read
+	pub fn index(&self) -> &[AviIndexEntry] {
+		&self.index
+	}
+
 	fn read_header(reader: &mut R) -> IoResult<(AviFormat, u64)> {
 		let mut fourcc = [0u8; 4];
 		reader.read_exact(&mut fourcc)?;
@@ -57,7 +76,10 @@ impl<R: MediaRead> AviReader<R> {
 			let mut chunk_id = [0u8; 4];
 			match reader.read_exact(&mut chunk_id) {
 				Ok(()) => {}
-				Err(_) => break,
+				Err(e) => {
+					log::warn("avi", format_args!("header ended without a movi list ({})", e));
+					break;
+				}
 			}
 
 			let chunk_size = reader.read_u32_le()? as u64;
@@ -75,10 +97,18 @@ impl<R: MediaRead> AviReader<R> {
 					movi_start = pos;
 					break;
 				} else {
+					log::debug(
+						"avi",
+						format_args!("skipped {} list ({} bytes)", fourcc_name(&list_type), chunk_size),
+					);
 					Self::skip_bytes(reader, chunk_size - 4)?;
 					pos += chunk_size - 4;
 				}
 			} else {
+				log::debug(
+					"avi",
+					format_args!("skipped {} chunk ({} bytes)", fourcc_name(&chunk_id), chunk_size),
+				);
 				Self::skip_bytes(reader, chunk_size)?;
 				pos += chunk_size;
 			}
@@ -103,6 +133,8 @@ impl<R: MediaRead> AviReader<R> {
 
 			if &chunk_id == b"avih" {
 				format.main_header = Self::parse_avih(reader)?;
+				// the four reserved words after the fields read
+				Self::skip_bytes(reader, chunk_size.saturating_sub(40))?;
 				remaining -= chunk_size;
 			} else if &chunk_id == LIST_SIGNATURE {
 				let mut list_type = [0u8; 4];
@@ -293,6 +325,24 @@ impl<R: MediaRead> Demuxer for AviReader<R> {
 			Err(e) => return Err(e),
 		}
 
+		// the index follows movi, so it is the end of the packets
+		if &chunk_id == b"idx1" {
+			self.eof = true;
+			let size = self.reader.read_u32_le()?;
+			for _ in 0..size / 16 {
+				let mut chunk_id = [0u8; 4];
+				if self.reader.read_exact(&mut chunk_id).is_err() {
+					log::warn("avi", format_args!("idx1 is cut short"));
+					break;
+				}
+				let flags = self.reader.read_u32_le()?;
+				let offset = self.reader.read_u32_le()?;
+				let size = self.reader.read_u32_le()?;
+				self.index.push(AviIndexEntry { chunk_id, flags, offset, size });
+			}
+			return Ok(None);
+		}
+
 		if &chunk_id == LIST_SIGNATURE {
 			let list_size = self.reader.read_u32_le()? as u64;
 			let mut list_type = [0u8; 4];
@@ -301,6 +351,7 @@ impl<R: MediaRead> Demuxer for AviReader<R> {
 			if &list_type == b"rec " {
 				return self.read_packet();
 			} else {
+				log::debug("avi", format_args!("skipped {} list in movi", fourcc_name(&list_type)));
 				Self::skip_bytes(&mut self.reader, list_size - 4)?;
 				return self.read_packet();
 			}
@@ -319,7 +370,9 @@ impl<R: MediaRead> Demuxer for AviReader<R> {
 
 		if chunk_size % 2 == 1 {
 			let mut pad = [0u8; 1];
-			let _ = self.reader.read_exact(&mut pad);
+			if self.reader.read_exact(&mut pad).is_err() {
+				log::warn("avi", format_args!("{} chunk is missing its pad byte", fourcc_name(&chunk_id)));
+			}
 		}
 
 		let pts = self.current_pos as i64;
@@ -1,4 +1,6 @@
-use super::{AVI_SIGNATURE, AviFormat, LIST_SIGNATURE, RIFF_SIGNATURE};
+use super::{
+	AVI_SIGNATURE, AVIIF_KEYFRAME, AviFormat, AviIndexEntry, LIST_SIGNATURE, RIFF_SIGNATURE,
+};
 use crate::core::{Muxer, Packet};
 use crate::io::{IoResult, MediaSeek, MediaWrite, SeekFrom, WritePrimitives};
 
@@ -7,14 +9,7 @@ pub struct AviWriter<W: MediaWrite + MediaSeek> {
 	format: AviFormat,
 	frame_count: u32,
 	movi_start: u64,
-	index_entries: Vec<IndexEntry>,
-}
-
-struct IndexEntry {
-	chunk_id: [u8; 4],
-	flags: u32,
-	offset: u32,
-	size: u32,
+	index_entries: Vec<AviIndexEntry>,
 }
 
 impl<W: MediaWrite + MediaSeek> AviWriter<W> {
@@ -134,6 +129,20 @@ impl<W: MediaWrite + MediaSeek> AviWriter<W> {
 	}
 
 	fn write_index(&mut self) -> IoResult<()> {
+		// a stream whose packets were never flagged is taken as all keyframes,
+		// as it was before the flags were looked at
+		let flagged: Vec<[u8; 4]> = self
+			.index_entries
+			.iter()
+			.filter(|entry| entry.flags & AVIIF_KEYFRAME != 0)
+			.map(|entry| entry.chunk_id)
+			.collect();
+		for entry in &mut self.index_entries {
+			if !flagged.contains(&entry.chunk_id) {
+				entry.flags |= AVIIF_KEYFRAME;
+			}
+		}
+
 		self.writer.write_all(b"idx1")?;
 		self.writer.write_u32_le((self.index_entries.len() * 16) as u32)?;
 
@@ -176,9 +185,9 @@ impl<W: MediaWrite + MediaSeek> Muxer for AviWriter<W> {
 			self.writer.write_u8(0)?;
 		}
 
-		self.index_entries.push(IndexEntry {
+		self.index_entries.push(AviIndexEntry {
 			chunk_id,
-			flags: 0x10,
+			flags: if packet.keyframe { AVIIF_KEYFRAME } else { 0 },
 			offset,
 			size: packet.data.len() as u32,
 		});
@@ -191,7 +200,7 @@ impl<W: MediaWrite + MediaSeek> Muxer for AviWriter<W> {
 		let movi_end = self.writer.stream_position()?;
 		let movi_size = (movi_end - self.movi_start + 4) as u32;
 
-		self.writer.seek(SeekFrom::Start(self.movi_start - 4))?;
+		self.writer.seek(SeekFrom::Start(self.movi_start - 8))?;
 		self.writer.write_u32_le(movi_size)?;
 		self.writer.seek(SeekFrom::Start(movi_end))?;
 
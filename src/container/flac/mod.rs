@@ -4,6 +4,8 @@ pub mod write;
 pub use read::FlacReader;
 pub use write::FlacWriter;
 
+use crate::codecs::flac::frame::{crc8, crc16};
+
 pub const FLAC_SIGNATURE: &[u8; 4] = b"fLaC";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -112,3 +114,53 @@ pub fn parse_streaminfo(data: &[u8]) -> Option<FlacFormat> {
 		md5_signature,
 	})
 }
+
+// the length of the frame header at the start of `data`, crc included, and
+// the samples in the frame, when there is a header whose crc holds
+pub(crate) fn frame_header(data: &[u8]) -> Option<(usize, usize)> {
+	if data.len() < 6 || data[0] != 0xff || data[1] & 0xfe != 0xf8 {
+		return None;
+	}
+	let (block_size, sample_rate) = (data[2] >> 4, data[2] & 0x0f);
+	let (channels, sample_size) = (data[3] >> 4, (data[3] >> 1) & 0x07);
+	if block_size == 0 || sample_rate == 15 || channels > 10 {
+		return None;
+	}
+	if sample_size == 3 || sample_size == 7 || data[3] & 1 != 0 {
+		return None;
+	}
+	// the frame or sample number, utf-8 style in 1 to 7 bytes
+	let coded = match data[4].leading_ones() {
+		0 => 1,
+		n @ 2..=7 => n as usize,
+		_ => return None,
+	};
+	let mut len = 4 + coded;
+	if data.get(5..len)?.iter().any(|&byte| byte & 0xc0 != 0x80) {
+		return None;
+	}
+	let samples = match block_size {
+		1 => 192,
+		2..=5 => 576 << (block_size - 2),
+		// stored after the coded number, less one
+		6 => *data.get(len)? as usize + 1,
+		7 => u16::from_be_bytes([*data.get(len)?, *data.get(len + 1)?]) as usize + 1,
+		_ => 256 << (block_size - 8),
+	};
+	len += match block_size {
+		6 => 1,
+		7 => 2,
+		_ => 0,
+	};
+	len += match sample_rate {
+		12 => 1,
+		13 | 14 => 2,
+		_ => 0,
+	};
+	(crc8(data.get(..len)?) == *data.get(len)?).then_some((len + 1, samples))
+}
+
+pub(crate) fn frame_crc_ok(frame: &[u8]) -> bool {
+	let (body, crc) = frame.split_at(frame.len() - 2);
+	crc16(body) == u16::from_be_bytes([crc[0], crc[1]])
+}
@@ -1,28 +1,52 @@
-use super::{FLAC_SIGNATURE, FlacFormat, MetadataBlockType, parse_streaminfo};
-use crate::core::{Demuxer, Packet, Timebase};
-use crate::io::{IoError, IoResult, MediaRead, ReadPrimitives};
+use super::{
+	FLAC_SIGNATURE, FlacFormat, MetadataBlockType, frame_crc_ok, frame_header, parse_streaminfo,
+};
+use crate::container::metadata::VorbisComment;
+use crate::core::{Demuxer, Packet, Timebase, log};
+use crate::io::{IoError, IoErrorKind, IoResult, MediaRead, ReadPrimitives};
 
 pub struct FlacReader<R: MediaRead> {
 	reader: R,
 	format: FlacFormat,
+	comment: Option<VorbisComment>,
 	timebase: Timebase,
+	pending: Vec<u8>,
 	frame_count: u64,
+	next_pts: i64,
 	eof: bool,
 }
 
+// a frame header is at most 16 bytes: sync, four fields, a 7 byte coded
+// number, two bytes each of block size and sample rate, and the crc
+const MAX_HEADER_SIZE: usize = 16;
+const READ_CHUNK: usize = 4096;
+
 impl<R: MediaRead> FlacReader<R> {
 	pub fn new(mut reader: R) -> IoResult<Self> {
-		let format = Self::read_header(&mut reader)?;
+		let (format, comment) = Self::read_header(&mut reader)?;
 		let timebase = Timebase::new(1, format.sample_rate);
 
-		Ok(Self { reader, format, timebase, frame_count: 0, eof: false })
+		Ok(Self {
+			reader,
+			format,
+			comment,
+			timebase,
+			pending: Vec::new(),
+			frame_count: 0,
+			next_pts: 0,
+			eof: false,
+		})
 	}
 
 	pub fn format(&self) -> &FlacFormat {
 		&self.format
 	}
 
-	fn read_header(reader: &mut R) -> IoResult<FlacFormat> {
+	pub fn vorbis_comment(&self) -> Option<&VorbisComment> {
+		self.comment.as_ref()
+	}
+
+	fn read_header(reader: &mut R) -> IoResult<(FlacFormat, Option<VorbisComment>)> {
 		let mut signature = [0u8; 4];
 		reader.read_exact(&mut signature)?;
 
@@ -31,6 +55,7 @@ impl<R: MediaRead> FlacReader<R> {
 		}
 
 		let mut format = None;
+		let mut comment = None;
 
 		loop {
 			let header_byte = reader.read_u8()?;
@@ -46,6 +71,16 @@ impl<R: MediaRead> FlacReader<R> {
 
 			if block_type == MetadataBlockType::StreamInfo {
 				format = parse_streaminfo(&block_data);
+			} else if block_type == MetadataBlockType::VorbisComment {
+				comment = VorbisComment::parse(&block_data);
+				if comment.is_none() {
+					log::warn("flac", format_args!("ignored unreadable VORBIS_COMMENT block"));
+				}
+			} else {
+				log::debug(
+					"flac",
+					format_args!("skipped {:?} metadata block ({} bytes)", block_type, block_size),
+				);
 			}
 
 			if is_last {
@@ -53,69 +88,81 @@ impl<R: MediaRead> FlacReader<R> {
 			}
 		}
 
-		format.ok_or_else(|| IoError::invalid_data("no STREAMINFO block found"))
+		let format = format.ok_or_else(|| IoError::invalid_data("no STREAMINFO block found"))?;
+		Ok((format, comment))
 	}
 
-	fn read_frame(&mut self) -> IoResult<Option<Vec<u8>>> {
-		if self.eof {
+	// one frame, cut where the next frame header begins. a sync code with a
+	// good header crc can turn up inside a frame, so the cut is the first
+	// later header the frame crc holds up to, the way check finds frames
+	fn read_frame(&mut self) -> IoResult<Option<(Vec<u8>, usize)>> {
+		self.fill(MAX_HEADER_SIZE)?;
+		if self.pending.is_empty() {
 			return Ok(None);
 		}
-
-		let mut frame_data = Vec::with_capacity(self.format.max_block_size as usize * 4);
-		let mut header = [0u8; 2];
-
-		match self.reader.read_exact(&mut header) {
-			Ok(()) => {}
-			Err(e) if matches!(e.kind(), crate::io::IoErrorKind::UnexpectedEof) => {
-				self.eof = true;
-				return Ok(None);
-			}
-			Err(e) => return Err(e),
-		}
-
-		if header[0] != 0xFF || (header[1] & 0xFC) != 0xF8 {
+		let Some((_, block_size)) = frame_header(&self.pending) else {
+			log::warn(
+				"flac",
+				format_args!(
+					"no frame sync at frame {}, ignoring the rest of the stream",
+					self.frame_count
+				),
+			);
+			self.pending.clear();
 			self.eof = true;
 			return Ok(None);
-		}
-
-		frame_data.extend_from_slice(&header);
-
-		let max_frame_size = if self.format.max_frame_size > 0 {
-			self.format.max_frame_size as usize
-		} else {
-			self.format.max_block_size as usize * self.format.bytes_per_frame() * 2
 		};
 
-		let mut buf = vec![0u8; max_frame_size.min(65536)];
-		match self.reader.read(&mut buf) {
-			Ok(0) => {
-				self.eof = true;
-				if frame_data.is_empty() {
-					return Ok(None);
+		let longest = (self.format.max_frame_size as usize).max(1 << 16);
+		let mut scanned = 4;
+		let mut first_header = None;
+		loop {
+			let len = self.pending.len();
+			// a header near the end may still be short of bytes
+			let end = if self.eof { len } else { len.saturating_sub(MAX_HEADER_SIZE) };
+			let end = end.min(longest + 1);
+			for at in scanned..end {
+				if frame_header(&self.pending[at..]).is_none() {
+					continue;
 				}
-			}
-			Ok(n) => {
-				frame_data.extend_from_slice(&buf[..n]);
-			}
-			Err(e) if matches!(e.kind(), crate::io::IoErrorKind::UnexpectedEof) => {
-				self.eof = true;
-				if frame_data.is_empty() {
-					return Ok(None);
+				first_header.get_or_insert(at);
+				if frame_crc_ok(&self.pending[..at]) {
+					return Ok(Some((self.pending.drain(..at).collect(), block_size)));
 				}
 			}
-			Err(e) => return Err(e),
+			scanned = scanned.max(end);
+
+			if self.eof || end > longest {
+				// no header the crc holds up to: the stream ends here, or the
+				// frame is damaged and runs to the next header there is
+				let whole = self.eof && frame_crc_ok(&self.pending);
+				let cut = if whole { len } else { first_header.unwrap_or(end) };
+				return Ok(Some((self.pending.drain(..cut).collect(), block_size)));
+			}
+			self.fill(len + READ_CHUNK)?;
 		}
+	}
 
-		Ok(Some(frame_data))
+	fn fill(&mut self, want: usize) -> IoResult<()> {
+		let mut chunk = [0u8; READ_CHUNK];
+		while self.pending.len() < want && !self.eof {
+			match self.reader.read(&mut chunk) {
+				Ok(0) => self.eof = true,
+				Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+				Err(e) if matches!(e.kind(), IoErrorKind::UnexpectedEof) => self.eof = true,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
 	}
 }
 
 impl<R: MediaRead> Demuxer for FlacReader<R> {
 	fn read_packet(&mut self) -> IoResult<Option<Packet>> {
 		match self.read_frame()? {
-			Some(data) => {
-				let block_size = self.format.max_block_size as i64;
-				let pts = self.frame_count as i64 * block_size;
+			Some((data, block_size)) => {
+				let pts = self.next_pts;
+				self.next_pts += block_size as i64;
 				self.frame_count += 1;
 
 				Ok(Some(Packet::new(data, 0, self.timebase).with_pts(pts)))
@@ -1,16 +1,31 @@
 use super::{FLAC_SIGNATURE, FlacFormat};
+use crate::container::metadata::{CoverArt, VorbisComment};
 use crate::core::{Muxer, Packet};
-use crate::io::{IoResult, MediaWrite, WritePrimitives};
+use crate::io::{IoError, IoResult, MediaWrite, WritePrimitives};
 
 pub struct FlacWriter<W: MediaWrite> {
 	writer: W,
 	format: FlacFormat,
+	comment: Option<VorbisComment>,
+	picture: Option<CoverArt>,
 	header_written: bool,
 }
 
 impl<W: MediaWrite> FlacWriter<W> {
 	pub fn new(writer: W, format: FlacFormat) -> IoResult<Self> {
-		Ok(Self { writer, format, header_written: false })
+		Ok(Self { writer, format, comment: None, picture: None, header_written: false })
+	}
+
+	// written as a VORBIS_COMMENT block after STREAMINFO
+	pub fn with_vorbis_comment(mut self, comment: VorbisComment) -> Self {
+		self.comment = Some(comment);
+		self
+	}
+
+	// written as a PICTURE block (front cover) after any comments
+	pub fn with_picture(mut self, picture: CoverArt) -> Self {
+		self.picture = Some(picture);
+		self
 	}
 
 	fn write_header(&mut self) -> IoResult<()> {
@@ -20,13 +35,34 @@ impl<W: MediaWrite> FlacWriter<W> {
 
 		self.writer.write_all(FLAC_SIGNATURE)?;
 
-		let header_byte = 0x80 | 0x00;
-		self.writer.write_u8(header_byte)?;
+		let mut blocks = Vec::new();
+		if let Some(comment) = &self.comment {
+			blocks.push((0x04, comment.to_bytes()));
+		}
+		if let Some(picture) = &self.picture {
+			blocks.push((0x06, picture.to_flac_picture()));
+		}
+
+		let last_block = if blocks.is_empty() { 0x80 } else { 0x00 };
+		self.writer.write_u8(last_block)?;
 
 		self.writer.write_all(&[0x00, 0x00, 0x22])?;
 
 		self.write_streaminfo()?;
 
+		let count = blocks.len();
+		for (index, (block_type, block)) in blocks.into_iter().enumerate() {
+			// block lengths are 24 bits
+			if block.len() >= 1 << 24 {
+				return Err(IoError::invalid_data("flac metadata block is larger than 16 MiB"));
+			}
+			let size = block.len() as u32;
+			let last = if index + 1 == count { 0x80 } else { 0x00 };
+			self.writer.write_u8(last | block_type)?;
+			self.writer.write_all(&[(size >> 16) as u8, (size >> 8) as u8, size as u8])?;
+			self.writer.write_all(&block)?;
+		}
+
 		self.header_written = true;
 		Ok(())
 	}
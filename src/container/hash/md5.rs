@@ -0,0 +1,85 @@
+// rfc 1321, kept small: the hash muxer is its only user
+const SHIFTS: [u32; 64] = [
+	7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14,
+	20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15, 21, 6,
+	10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+pub struct Md5 {
+	state: [u32; 4],
+	buffer: Vec<u8>,
+	length: u64,
+}
+
+impl Md5 {
+	pub fn new() -> Self {
+		Self {
+			state: [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476],
+			buffer: Vec::with_capacity(64),
+			length: 0,
+		}
+	}
+
+	pub fn update(&mut self, data: &[u8]) {
+		self.length = self.length.wrapping_add(data.len() as u64);
+		let mut data = data;
+		if !self.buffer.is_empty() {
+			let take = (64 - self.buffer.len()).min(data.len());
+			self.buffer.extend_from_slice(&data[..take]);
+			data = &data[take..];
+			if self.buffer.len() < 64 {
+				return;
+			}
+			let block: [u8; 64] = self.buffer[..].try_into().unwrap();
+			self.compress(&block);
+			self.buffer.clear();
+		}
+		let mut blocks = data.chunks_exact(64);
+		for block in &mut blocks {
+			self.compress(block.try_into().unwrap());
+		}
+		self.buffer.extend_from_slice(blocks.remainder());
+	}
+
+	pub fn finish(mut self) -> [u8; 16] {
+		let bits = self.length.wrapping_mul(8);
+		self.update(&[0x80]);
+		while self.buffer.len() != 56 {
+			self.update(&[0]);
+		}
+		self.update(&bits.to_le_bytes());
+
+		let mut digest = [0u8; 16];
+		for (out, word) in digest.chunks_exact_mut(4).zip(self.state) {
+			out.copy_from_slice(&word.to_le_bytes());
+		}
+		digest
+	}
+
+	fn compress(&mut self, block: &[u8; 64]) {
+		let words: Vec<u32> =
+			block.chunks_exact(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())).collect();
+		let [mut a, mut b, mut c, mut d] = self.state;
+		for (i, shift) in SHIFTS.into_iter().enumerate() {
+			let (f, g) = match i / 16 {
+				0 => ((b & c) | (!b & d), i),
+				1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+				2 => (b ^ c ^ d, (3 * i + 5) % 16),
+				_ => (c ^ (b | !d), (7 * i) % 16),
+			};
+			// the sine table of the rfc, |sin(i + 1)| * 2^32
+			let k = ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32;
+			let rotated = a.wrapping_add(f).wrapping_add(k).wrapping_add(words[g]).rotate_left(shift);
+			(a, b, c, d) = (d, b.wrapping_add(rotated), b, c);
+		}
+		for (state, value) in self.state.iter_mut().zip([a, b, c, d]) {
+			*state = state.wrapping_add(value);
+		}
+	}
+}
+
+impl Default for Md5 {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,108 @@
+pub mod md5;
+pub mod sha256;
+
+pub use md5::Md5;
+pub use sha256::Sha256;
+
+use crate::core::{Muxer, Packet};
+use crate::io::{IoResult, MediaWrite, WritePrimitives};
+
+// outputs that are not a container: `null` decodes and discards, the hashes
+// print one digest of every encoded packet, like ffmpeg's `-f md5`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkFormat {
+	Null,
+	Md5,
+	Sha256,
+}
+
+impl SinkFormat {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"null" => Some(SinkFormat::Null),
+			"md5" => Some(SinkFormat::Md5),
+			"sha256" => Some(SinkFormat::Sha256),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			SinkFormat::Null => "null",
+			SinkFormat::Md5 => "md5",
+			SinkFormat::Sha256 => "sha256",
+		}
+	}
+}
+
+enum Digest {
+	Null,
+	Md5(Md5),
+	Sha256(Sha256),
+}
+
+// takes packets like any muxer; on finalize a hash writes "MD5=<hex>\n" (or
+// "SHA256=") to `writer`, and null writes nothing
+pub struct SinkWriter<W: MediaWrite> {
+	writer: Option<W>,
+	digest: Option<Digest>,
+	packets: u64,
+	bytes: u64,
+}
+
+impl<W: MediaWrite> SinkWriter<W> {
+	pub fn null() -> Self {
+		Self { writer: None, digest: Some(Digest::Null), packets: 0, bytes: 0 }
+	}
+
+	pub fn new(writer: W, format: SinkFormat) -> Self {
+		let digest = match format {
+			SinkFormat::Null => Digest::Null,
+			SinkFormat::Md5 => Digest::Md5(Md5::new()),
+			SinkFormat::Sha256 => Digest::Sha256(Sha256::new()),
+		};
+		Self { writer: Some(writer), digest: Some(digest), packets: 0, bytes: 0 }
+	}
+
+	pub fn packets(&self) -> u64 {
+		self.packets
+	}
+
+	pub fn bytes(&self) -> u64 {
+		self.bytes
+	}
+
+	pub fn into_inner(self) -> Option<W> {
+		self.writer
+	}
+}
+
+impl<W: MediaWrite> Muxer for SinkWriter<W> {
+	fn write_packet(&mut self, packet: Packet) -> IoResult<()> {
+		match &mut self.digest {
+			Some(Digest::Md5(md5)) => md5.update(&packet.data),
+			Some(Digest::Sha256(sha256)) => sha256.update(&packet.data),
+			Some(Digest::Null) | None => {}
+		}
+		self.packets += 1;
+		self.bytes += packet.size() as u64;
+		Ok(())
+	}
+
+	fn finalize(&mut self) -> IoResult<()> {
+		let line = match self.digest.take() {
+			Some(Digest::Md5(md5)) => format!("MD5={}\n", to_hex(&md5.finish())),
+			Some(Digest::Sha256(sha256)) => format!("SHA256={}\n", to_hex(&sha256.finish())),
+			Some(Digest::Null) | None => return Ok(()),
+		};
+		if let Some(writer) = &mut self.writer {
+			writer.write_all(line.as_bytes())?;
+			writer.flush()?;
+		}
+		Ok(())
+	}
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
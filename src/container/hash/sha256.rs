@@ -0,0 +1,99 @@
+// fips 180-4
+const ROUND_CONSTANTS: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+	0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+	0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+	0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+	0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+	0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+	0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+	0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub struct Sha256 {
+	state: [u32; 8],
+	buffer: Vec<u8>,
+	length: u64,
+}
+
+impl Sha256 {
+	pub fn new() -> Self {
+		Self {
+			state: [
+				0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+				0x5be0cd19,
+			],
+			buffer: Vec::with_capacity(64),
+			length: 0,
+		}
+	}
+
+	pub fn update(&mut self, data: &[u8]) {
+		self.length = self.length.wrapping_add(data.len() as u64);
+		let mut data = data;
+		if !self.buffer.is_empty() {
+			let take = (64 - self.buffer.len()).min(data.len());
+			self.buffer.extend_from_slice(&data[..take]);
+			data = &data[take..];
+			if self.buffer.len() < 64 {
+				return;
+			}
+			let block: [u8; 64] = self.buffer[..].try_into().unwrap();
+			self.compress(&block);
+			self.buffer.clear();
+		}
+		let mut blocks = data.chunks_exact(64);
+		for block in &mut blocks {
+			self.compress(block.try_into().unwrap());
+		}
+		self.buffer.extend_from_slice(blocks.remainder());
+	}
+
+	pub fn finish(mut self) -> [u8; 32] {
+		let bits = self.length.wrapping_mul(8);
+		self.update(&[0x80]);
+		while self.buffer.len() != 56 {
+			self.update(&[0]);
+		}
+		self.update(&bits.to_be_bytes());
+
+		let mut digest = [0u8; 32];
+		for (out, word) in digest.chunks_exact_mut(4).zip(self.state) {
+			out.copy_from_slice(&word.to_be_bytes());
+		}
+		digest
+	}
+
+	fn compress(&mut self, block: &[u8; 64]) {
+		let mut w = [0u32; 64];
+		for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+			*word = u32::from_be_bytes(bytes.try_into().unwrap());
+		}
+		for i in 16..64 {
+			let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+			let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+			w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+		for i in 0..64 {
+			let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+			let choice = (e & f) ^ (!e & g);
+			let t1 =
+				h.wrapping_add(s1).wrapping_add(choice).wrapping_add(ROUND_CONSTANTS[i]).wrapping_add(w[i]);
+			let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+			let majority = (a & b) ^ (a & c) ^ (b & c);
+			let t2 = s0.wrapping_add(majority);
+			(h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+		}
+		for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+			*state = state.wrapping_add(value);
+		}
+	}
+}
+
+impl Default for Sha256 {
+	fn default() -> Self {
+		Self::new()
+	}
+}
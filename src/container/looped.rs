@@ -0,0 +1,67 @@
+use crate::core::{Demuxer, Packet};
+use crate::io::IoResult;
+
+// replays a demuxer from the start when it runs out, shifting pts so the
+// timeline keeps counting up across passes. `open` produces a fresh demuxer
+// for each further pass and `duration` says how long a packet lasts in its
+// own timebase.
+pub struct Looped<D: Demuxer> {
+	inner: D,
+	open: Box<dyn FnMut() -> IoResult<D> + Send>,
+	duration: Box<dyn Fn(&Packet) -> i64 + Send>,
+	// further passes after the current one; None repeats forever
+	remaining: Option<u32>,
+	offset: i64,
+	end: i64,
+	pass_had_packets: bool,
+}
+
+impl<D: Demuxer> Looped<D> {
+	pub fn new(
+		inner: D,
+		passes: Option<u32>,
+		open: impl FnMut() -> IoResult<D> + Send + 'static,
+		duration: impl Fn(&Packet) -> i64 + Send + 'static,
+	) -> Self {
+		Self {
+			inner,
+			open: Box::new(open),
+			duration: Box::new(duration),
+			remaining: passes.map(|passes| passes.saturating_sub(1)),
+			offset: 0,
+			end: 0,
+			// the first pass may have been seeked to its end, so only reopened
+			// passes count as evidence of an empty input
+			pass_had_packets: true,
+		}
+	}
+}
+
+impl<D: Demuxer> Demuxer for Looped<D> {
+	fn read_packet(&mut self) -> IoResult<Option<Packet>> {
+		loop {
+			if let Some(packet) = self.inner.read_packet()? {
+				let pts = packet.pts + self.offset;
+				self.end = self.end.max(pts + (self.duration)(&packet));
+				self.pass_had_packets = true;
+				let dts = packet.dts + self.offset;
+				return Ok(Some(packet.with_pts(pts).with_dts(dts)));
+			}
+
+			// an empty input would otherwise spin forever
+			if !self.pass_had_packets || self.remaining == Some(0) {
+				return Ok(None);
+			}
+			if let Some(remaining) = &mut self.remaining {
+				*remaining -= 1;
+			}
+			self.inner = (self.open)()?;
+			self.offset = self.end;
+			self.pass_had_packets = false;
+		}
+	}
+
+	fn stream_count(&self) -> usize {
+		self.inner.stream_count()
+	}
+}
@@ -1,3 +1,4 @@
+use crate::io::{IoError, IoResult};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Default)]
@@ -131,6 +132,212 @@ impl Chapter {
 	}
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Disposition {
+	pub default: bool,
+	pub commentary: bool,
+}
+
+impl Disposition {
+	// accepts "default", "commentary", "default+commentary", or "0"/"none" to clear
+	pub fn from_names(text: &str) -> Option<Self> {
+		let mut disposition = Self::default();
+		for name in text.split('+').map(str::trim) {
+			match name.to_lowercase().as_str() {
+				"default" => disposition.default = true,
+				"commentary" => disposition.commentary = true,
+				"0" | "none" | "" => {}
+				_ => return None,
+			}
+		}
+		Some(disposition)
+	}
+
+	pub fn names(&self) -> Vec<&'static str> {
+		let mut names = Vec::new();
+		if self.default {
+			names.push("default");
+		}
+		if self.commentary {
+			names.push("commentary");
+		}
+		names
+	}
+
+	pub fn is_empty(&self) -> bool {
+		!self.default && !self.commentary
+	}
+}
+
+pub const UNDETERMINED_LANGUAGE: &str = "und";
+
+// ISO 639-2/T codes are three lowercase ascii letters
+pub fn is_valid_language(code: &str) -> bool {
+	code.len() == 3 && code.bytes().all(|b| b.is_ascii_lowercase())
+}
+
+// front cover artwork, embedded as a flac PICTURE block, an id3 APIC frame or
+// an mp4 covr atom. only jpeg and png are accepted, which is what players show
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverArt {
+	pub mime: &'static str,
+	pub width: u32,
+	pub height: u32,
+	pub data: Vec<u8>,
+}
+
+// picture type 3 in both flac and id3
+const FRONT_COVER: u8 = 3;
+
+impl CoverArt {
+	pub fn from_bytes(data: Vec<u8>) -> IoResult<Self> {
+		let (mime, size) = if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+			("image/jpeg", jpeg_size(&data))
+		} else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+			("image/png", png_size(&data))
+		} else {
+			return Err(IoError::invalid_data("cover art must be a jpeg or png image"));
+		};
+		let (width, height) = size.unwrap_or((0, 0));
+		Ok(Self { mime, width, height, data })
+	}
+
+	pub fn load(path: &str) -> IoResult<Self> {
+		Self::from_bytes(std::fs::read(path)?)
+	}
+
+	pub fn is_png(&self) -> bool {
+		self.mime == "image/png"
+	}
+
+	// the body of a flac PICTURE block
+	pub fn to_flac_picture(&self) -> Vec<u8> {
+		let mut out = Vec::with_capacity(32 + self.mime.len() + self.data.len());
+		out.extend_from_slice(&(FRONT_COVER as u32).to_be_bytes());
+		out.extend_from_slice(&(self.mime.len() as u32).to_be_bytes());
+		out.extend_from_slice(self.mime.as_bytes());
+		// no description
+		out.extend_from_slice(&0u32.to_be_bytes());
+		out.extend_from_slice(&self.width.to_be_bytes());
+		out.extend_from_slice(&self.height.to_be_bytes());
+		// colour depth; 0 for colours used means not indexed
+		out.extend_from_slice(&24u32.to_be_bytes());
+		out.extend_from_slice(&0u32.to_be_bytes());
+		out.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
+		out.extend_from_slice(&self.data);
+		out
+	}
+
+	// the body of an id3v2.4 APIC frame
+	pub fn to_apic(&self) -> Vec<u8> {
+		let mut out = Vec::with_capacity(16 + self.data.len());
+		out.push(0x03);
+		out.extend_from_slice(self.mime.as_bytes());
+		out.push(0);
+		out.push(FRONT_COVER);
+		out.push(0);
+		out.extend_from_slice(&self.data);
+		out
+	}
+}
+
+fn png_size(data: &[u8]) -> Option<(u32, u32)> {
+	// the IHDR chunk always comes first
+	if data.get(12..16)? != b"IHDR" {
+		return None;
+	}
+	let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+	let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+	Some((width, height))
+}
+
+// walks the marker segments to the first start-of-frame
+fn jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+	let mut pos = 2;
+	while pos + 4 <= data.len() {
+		if data[pos] != 0xFF {
+			return None;
+		}
+		let marker = data[pos + 1];
+		let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+		// SOF0-SOF15, except DHT (c4), JPG (c8) and DAC (cc)
+		if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+			let height = u16::from_be_bytes([*data.get(pos + 5)?, *data.get(pos + 6)?]);
+			let width = u16::from_be_bytes([*data.get(pos + 7)?, *data.get(pos + 8)?]);
+			return Some((width as u32, height as u32));
+		}
+		pos += 2 + length;
+	}
+	None
+}
+
+const ID3_TEXT_KEYS: &[(&str, &str)] = &[
+	("TIT1", "grouping"),
+	("TIT2", "title"),
+	("TIT3", "subtitle"),
+	("TPE1", "artist"),
+	("TPE2", "album_artist"),
+	("TPE3", "performer"),
+	("TALB", "album"),
+	("TCOM", "composer"),
+	("TCON", "genre"),
+	("TCOP", "copyright"),
+	("TDRC", "year"),
+	("TYER", "year"),
+	("TENC", "encoded_by"),
+	("TSSE", "encoder"),
+	("TLAN", "language"),
+	("TPOS", "disc"),
+	("TPUB", "publisher"),
+	("TRCK", "track"),
+	("TBPM", "bpm"),
+	("TSOA", "album-sort"),
+	("TSOP", "artist-sort"),
+	("TSOT", "title-sort"),
+];
+
+// text in an id3 frame's encoding: 0 latin-1, 1 utf-16 with a byte order
+// mark, 2 utf-16be, 3 utf-8. 2.4 separates several values with a null,
+// which are joined with "; "
+fn decode_id3_text(encoding: u8, data: &[u8]) -> Option<String> {
+	let text = match encoding {
+		0 => data.iter().map(|&b| b as char).collect(),
+		1 | 2 => {
+			let units =
+				data.chunks(2).map(|c| u16::from_be_bytes([c[0], c.get(1).copied().unwrap_or(0)]));
+			let mut units: Vec<u16> = units.collect();
+			// without a mark, encoding 1 is taken as little endian like most taggers write it
+			let little_endian = match units.first() {
+				Some(0xFEFF) => false,
+				Some(0xFFFE) => true,
+				_ => encoding == 1,
+			};
+			if matches!(units.first(), Some(0xFEFF | 0xFFFE)) {
+				units.remove(0);
+			}
+			if little_endian {
+				units.iter_mut().for_each(|unit| *unit = unit.swap_bytes());
+			}
+			String::from_utf16(&units).ok()?
+		}
+		3 => String::from_utf8(data.to_vec()).ok()?,
+		_ => return None,
+	};
+	let values: Vec<&str> = text.split('\0').filter(|value| !value.is_empty()).collect();
+	Some(values.join("; "))
+}
+
+// a null terminated string and what follows it; the terminator is two bytes
+// in utf-16
+fn split_id3_text(encoding: u8, data: &[u8]) -> Option<(&[u8], &[u8])> {
+	if matches!(encoding, 1 | 2) {
+		let end = data.chunks(2).position(|c| c == [0, 0])? * 2;
+		return Some((&data[..end], &data[end + 2..]));
+	}
+	let end = data.iter().position(|&b| b == 0)?;
+	Some((&data[..end], &data[end + 1..]))
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Id3v2Tag {
 	pub version: u8,
@@ -151,25 +358,115 @@ impl Id3v2Tag {
 	}
 
 	pub fn get_text_frame(&self, frame_id: &str) -> Option<String> {
-		self.frames.get(frame_id).and_then(|data| {
-			if data.is_empty() {
-				return None;
+		let (&encoding, text) = self.frames.get(frame_id)?.split_first()?;
+		decode_id3_text(encoding, text)
+	}
+
+	// TXXX can appear more than once, so each one is keyed by its description
+	pub fn set_user_text(&mut self, description: &str, text: &str) {
+		let mut data = vec![0x03];
+		data.extend_from_slice(description.as_bytes());
+		data.push(0);
+		data.extend_from_slice(text.as_bytes());
+		self.frames.insert(format!("TXXX:{}", description), data);
+	}
+
+	pub fn get_user_text(&self, description: &str) -> Option<String> {
+		let (&encoding, body) = self.frames.get(&format!("TXXX:{}", description))?.split_first()?;
+		decode_id3_text(encoding, split_id3_text(encoding, body)?.1)
+	}
+
+	// the text of a COMM frame, after its language and short description
+	pub fn get_comment(&self) -> Option<String> {
+		let (&encoding, body) = self.frames.get("COMM")?.split_first()?;
+		decode_id3_text(encoding, split_id3_text(encoding, body.get(3..)?)?.1)
+	}
+
+	// an ID3v2.3 or 2.4 tag as `to_bytes` writes it or another tagger did:
+	// text frames kept as they are, TXXX keyed by description. 2.2's three
+	// letter frames and unsynchronised tags are not read
+	pub fn parse(data: &[u8]) -> Option<Self> {
+		let synchsafe =
+			|b: &[u8]| b.iter().fold(0usize, |size, &byte| (size << 7) | (byte & 0x7F) as usize);
+		if data.get(..3)? != b"ID3" {
+			return None;
+		}
+		let (version, revision, flags) = (data[3], *data.get(4)?, *data.get(5)?);
+		if !matches!(version, 3 | 4) || flags & 0x80 != 0 {
+			return None;
+		}
+		let end = (10 + synchsafe(data.get(6..10)?)).min(data.len());
+		let mut pos = 10;
+		if flags & 0x40 != 0 {
+			let extended = data.get(10..14)?;
+			pos += if version == 4 {
+				synchsafe(extended)
+			} else {
+				4 + u32::from_be_bytes(extended.try_into().ok()?) as usize
+			};
+		}
+
+		let mut tag = Self { version, revision, flags: 0, frames: HashMap::new() };
+		while pos + 10 <= end {
+			let id = &data[pos..pos + 4];
+			// padding
+			if id[0] == 0 {
+				break;
 			}
-			let encoding = data[0];
-			let text_data = &data[1..];
-			match encoding {
-				0 => String::from_utf8(text_data.to_vec()).ok(),
-				1 | 2 => {
-					let chars: Vec<u16> = text_data
-						.chunks(2)
-						.map(|c| u16::from_le_bytes([c[0], c.get(1).copied().unwrap_or(0)]))
-						.collect();
-					String::from_utf16(&chars).ok()
+			let size_bytes = &data[pos + 4..pos + 8];
+			let size = if version == 4 {
+				synchsafe(size_bytes)
+			} else {
+				u32::from_be_bytes(size_bytes.try_into().ok()?) as usize
+			};
+			let body = data.get(pos + 10..pos + 10 + size)?;
+			let id = String::from_utf8_lossy(id).into_owned();
+			let key = match id.as_str() {
+				"TXXX" => {
+					let (&encoding, text) = body.split_first()?;
+					let description = split_id3_text(encoding, text)?.0;
+					format!("TXXX:{}", decode_id3_text(encoding, description)?)
 				}
-				3 => String::from_utf8(text_data.to_vec()).ok(),
-				_ => None,
-			}
-		})
+				_ => id,
+			};
+			tag.frames.insert(key, body.to_vec());
+			pos += 10 + size;
+		}
+		Some(tag)
+	}
+
+	// ID3v2.4 with synchsafe sizes; frames are written in key order so the
+	// output is stable
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let synchsafe = |size: usize| {
+			let size = size as u32;
+			[
+				(size >> 21) as u8 & 0x7F,
+				(size >> 14) as u8 & 0x7F,
+				(size >> 7) as u8 & 0x7F,
+				size as u8 & 0x7F,
+			]
+		};
+
+		let mut keys: Vec<&String> = self.frames.keys().collect();
+		keys.sort();
+		let mut body = Vec::new();
+		for key in keys {
+			let data = &self.frames[key];
+			body.extend_from_slice(&key.as_bytes()[..4]);
+			body.extend_from_slice(&synchsafe(data.len()));
+			body.extend_from_slice(&[0, 0]);
+			body.extend_from_slice(data);
+		}
+
+		let mut out = vec![b'I', b'D', b'3', self.version, self.revision, self.flags];
+		out.extend_from_slice(&synchsafe(body.len()));
+		out.extend_from_slice(&body);
+		out
+	}
+
+	pub fn set_picture(&mut self, cover: &CoverArt) {
+		self.frames.insert("APIC".to_string(), cover.to_apic());
 	}
 
 	pub fn set_title(&mut self, title: &str) {
@@ -196,28 +493,25 @@ impl Id3v2Tag {
 		self.set_text_frame("TRCK", track);
 	}
 
+	// text frames under the names ffprobe gives them, or their id when it has
+	// none; TXXX frames under their description and COMM as "comment"
 	pub fn to_metadata(&self) -> MediaMetadata {
 		let mut metadata = MediaMetadata::new();
-
-		if let Some(title) = self.get_text_frame("TIT2") {
-			metadata.add_tag("title", title);
-		}
-		if let Some(artist) = self.get_text_frame("TPE1") {
-			metadata.add_tag("artist", artist);
-		}
-		if let Some(album) = self.get_text_frame("TALB") {
-			metadata.add_tag("album", album);
-		}
-		if let Some(year) = self.get_text_frame("TDRC") {
-			metadata.add_tag("year", year);
-		}
-		if let Some(genre) = self.get_text_frame("TCON") {
-			metadata.add_tag("genre", genre);
-		}
-		if let Some(track) = self.get_text_frame("TRCK") {
-			metadata.add_tag("track", track);
+		for id in self.frames.keys() {
+			let tag = match id.strip_prefix("TXXX:") {
+				Some(description) => self.get_user_text(description).map(|text| (description, text)),
+				None if id == "COMM" => self.get_comment().map(|text| ("comment", text)),
+				None if id.starts_with('T') => {
+					let key =
+						ID3_TEXT_KEYS.iter().find(|(frame, _)| frame == id).map_or(id.as_str(), |k| k.1);
+					self.get_text_frame(id).map(|text| (key, text))
+				}
+				None => None,
+			};
+			if let Some((key, text)) = tag.filter(|(_, text)| !text.is_empty()) {
+				metadata.add_tag(key, text);
+			}
 		}
-
 		metadata
 	}
 }
@@ -246,6 +540,45 @@ impl VorbisComment {
 		self.comments.get(&key.to_uppercase())
 	}
 
+	// the body of a FLAC VORBIS_COMMENT block: little-endian lengths, no framing bit
+	pub fn parse(data: &[u8]) -> Option<Self> {
+		let mut pos = 0;
+		let read_string = |pos: &mut usize| -> Option<String> {
+			let len = u32::from_le_bytes(data.get(*pos..*pos + 4)?.try_into().ok()?) as usize;
+			let text = data.get(*pos + 4..*pos + 4 + len)?;
+			*pos += 4 + len;
+			String::from_utf8(text.to_vec()).ok()
+		};
+
+		let vendor = read_string(&mut pos)?;
+		let count = u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?);
+		pos += 4;
+
+		let mut comment = Self::new().with_vendor(vendor);
+		for _ in 0..count {
+			let entry = read_string(&mut pos)?;
+			let (key, value) = entry.split_once('=')?;
+			comment.add_comment(key, value);
+		}
+		Some(comment)
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut entries: Vec<String> =
+			self.comments.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+		entries.sort();
+
+		let mut out = Vec::new();
+		out.extend_from_slice(&(self.vendor.len() as u32).to_le_bytes());
+		out.extend_from_slice(self.vendor.as_bytes());
+		out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+		for entry in entries {
+			out.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+			out.extend_from_slice(entry.as_bytes());
+		}
+		out
+	}
+
 	pub fn to_metadata(&self) -> MediaMetadata {
 		let mut metadata = MediaMetadata::new();
 		for (key, value) in &self.comments {
@@ -1,12 +1,87 @@
 pub mod avi;
 pub mod flac;
+pub mod hash;
+pub mod looped;
 pub mod metadata;
+pub mod mp3;
 pub mod mp4;
+pub mod ogg;
+pub mod riff;
 pub mod wav;
 pub mod y4m;
 
 pub use avi::{AviFormat, AviReader, AviWriter};
 pub use flac::{FlacFormat, FlacReader, FlacWriter};
+pub use hash::{SinkFormat, SinkWriter};
+pub use looped::Looped;
+pub use mp3::{Mp3Format, Mp3Reader, Mp3Writer};
 pub use mp4::{Mp4Format, Mp4Reader, Mp4Writer};
+pub use ogg::{OggFormat, OggReader, OggWriter};
 pub use wav::{WavFormat, WavReader, WavWriter};
-pub use y4m::{Y4mFormat, Y4mReader, Y4mWriter};
+pub use y4m::{Colorspace, Y4mFormat, Y4mReader, Y4mWriter};
+
+use std::path::Path;
+
+// container picked from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaType {
+	Wav,
+	Y4m,
+	Flac,
+	Avi,
+	Mp4,
+	Mp3,
+	Ogg,
+	Unknown,
+}
+
+impl MediaType {
+	pub fn from_extension(path: &str) -> Self {
+		let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+		Self::from_name(ext)
+	}
+
+	// a container named as its extension, e.g. for `-f` on a pipe
+	pub fn from_name(name: &str) -> Self {
+		match name.to_lowercase().as_str() {
+			"wav" => MediaType::Wav,
+			"y4m" => MediaType::Y4m,
+			"flac" => MediaType::Flac,
+			"avi" => MediaType::Avi,
+			"mp4" | "m4a" | "m4v" => MediaType::Mp4,
+			"mp3" => MediaType::Mp3,
+			"ogg" | "oga" | "opus" => MediaType::Ogg,
+			_ => MediaType::Unknown,
+		}
+	}
+
+	pub fn extension(&self) -> &'static str {
+		match self {
+			MediaType::Wav => "wav",
+			MediaType::Y4m => "y4m",
+			MediaType::Flac => "flac",
+			MediaType::Avi => "avi",
+			MediaType::Mp4 => "mp4",
+			MediaType::Mp3 => "mp3",
+			MediaType::Ogg => "ogg",
+			MediaType::Unknown => "",
+		}
+	}
+
+	pub fn is_audio(&self) -> bool {
+		matches!(self, MediaType::Wav | MediaType::Flac | MediaType::Mp3 | MediaType::Ogg)
+	}
+
+	pub fn is_video(&self) -> bool {
+		matches!(self, MediaType::Y4m | MediaType::Avi | MediaType::Mp4)
+	}
+}
+
+// a chunk or box id for log messages: its characters when printable, else hex
+pub(crate) fn fourcc_name(id: &[u8]) -> String {
+	if id.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+		format!("'{}'", String::from_utf8_lossy(id))
+	} else {
+		format!("{:02x?}", id)
+	}
+}
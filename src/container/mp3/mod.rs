@@ -0,0 +1,197 @@
+pub mod read;
+pub mod write;
+
+pub use read::Mp3Reader;
+pub use write::Mp3Writer;
+
+pub const ID3V2_SIGNATURE: &[u8; 3] = b"ID3";
+
+// kbps by layer and bitrate index; index 0 is free format, which has no fixed
+// frame length and is not supported
+const MPEG1_BITRATES: [[u32; 15]; 3] = [
+	[0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+	[0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+	[0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+];
+// mpeg-2 and 2.5 share one table for layers ii and iii
+const MPEG2_BITRATES: [[u32; 15]; 3] = [
+	[0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+	[0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+	[0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+const MPEG1_SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegVersion {
+	Mpeg1,
+	Mpeg2,
+	Mpeg25,
+}
+
+// the 4 bytes every mpeg audio frame starts with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+	pub version: MpegVersion,
+	pub layer: u8,
+	pub bitrate_index: u8,
+	pub sample_rate: u32,
+	pub padding: bool,
+	pub protected: bool,
+	pub channels: u8,
+	pub bytes: [u8; 4],
+}
+
+impl FrameHeader {
+	pub fn parse(bytes: [u8; 4]) -> Option<Self> {
+		if bytes[0] != 0xFF || bytes[1] & 0xE0 != 0xE0 {
+			return None;
+		}
+		let version = match (bytes[1] >> 3) & 0x03 {
+			0 => MpegVersion::Mpeg25,
+			2 => MpegVersion::Mpeg2,
+			3 => MpegVersion::Mpeg1,
+			_ => return None,
+		};
+		let layer = match (bytes[1] >> 1) & 0x03 {
+			1 => 3,
+			2 => 2,
+			3 => 1,
+			_ => return None,
+		};
+		let bitrate_index = bytes[2] >> 4;
+		let rate_index = ((bytes[2] >> 2) & 0x03) as usize;
+		if bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+			return None;
+		}
+		let shift = match version {
+			MpegVersion::Mpeg1 => 0,
+			MpegVersion::Mpeg2 => 1,
+			MpegVersion::Mpeg25 => 2,
+		};
+		Some(Self {
+			version,
+			layer,
+			bitrate_index,
+			sample_rate: MPEG1_SAMPLE_RATES[rate_index] >> shift,
+			padding: bytes[2] & 0x02 != 0,
+			protected: bytes[1] & 0x01 == 0,
+			channels: if bytes[3] >> 6 == 3 { 1 } else { 2 },
+			bytes,
+		})
+	}
+
+	pub fn bitrate(&self) -> u32 {
+		let table = match self.version {
+			MpegVersion::Mpeg1 => &MPEG1_BITRATES,
+			_ => &MPEG2_BITRATES,
+		};
+		table[self.layer as usize - 1][self.bitrate_index as usize] * 1000
+	}
+
+	// samples per channel in the frame
+	pub fn samples(&self) -> u32 {
+		Mp3Format::from_header(self).samples_per_frame()
+	}
+
+	// whole frame, header included; layer i counts in 4-byte slots
+	pub fn frame_len(&self) -> usize {
+		let padding = self.padding as u32;
+		let len = match self.layer {
+			1 => (12 * self.bitrate() / self.sample_rate + padding) * 4,
+			_ => self.samples() / 8 * self.bitrate() / self.sample_rate + padding,
+		};
+		len as usize
+	}
+
+	// where a xing or info tag starts: after the header, crc and side info
+	pub fn side_info_end(&self) -> usize {
+		let side_info = match (self.version, self.channels) {
+			(MpegVersion::Mpeg1, 1) => 17,
+			(MpegVersion::Mpeg1, _) => 32,
+			(_, 1) => 9,
+			_ => 17,
+		};
+		4 + if self.protected { 2 } else { 0 } + side_info
+	}
+
+	// version, layer and rate do not change within a stream
+	pub fn same_stream(&self, other: &FrameHeader) -> bool {
+		(self.version, self.layer, self.sample_rate) == (other.version, other.layer, other.sample_rate)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mp3Format {
+	pub version: MpegVersion,
+	pub layer: u8,
+	pub sample_rate: u32,
+	pub channels: u8,
+	// from a xing, info or vbri header; players take the duration from it
+	pub frames: Option<u32>,
+}
+
+impl Mp3Format {
+	pub fn from_header(header: &FrameHeader) -> Self {
+		Self {
+			version: header.version,
+			layer: header.layer,
+			sample_rate: header.sample_rate,
+			channels: header.channels,
+			frames: None,
+		}
+	}
+
+	// samples per channel in each frame
+	pub fn samples_per_frame(&self) -> u32 {
+		match (self.layer, self.version) {
+			(1, _) => 384,
+			(3, MpegVersion::Mpeg2 | MpegVersion::Mpeg25) => 576,
+			_ => 1152,
+		}
+	}
+}
+
+// the frame count of a xing/info (lame) or vbri header: a first frame with no
+// audio that describes the stream. Some(0) is a header without a count
+pub fn info_tag_frames(frame: &[u8], header: &FrameHeader) -> Option<u32> {
+	let at = header.side_info_end();
+	let tag = frame.get(at..at + 4)?;
+	if tag == b"Xing" || tag == b"Info" {
+		let flags = u32::from_be_bytes(frame.get(at + 4..at + 8)?.try_into().ok()?);
+		if flags & 0x01 == 0 {
+			return Some(0);
+		}
+		return Some(u32::from_be_bytes(frame.get(at + 8..at + 12)?.try_into().ok()?));
+	}
+	// vbri always sits 32 bytes past the header
+	if frame.get(36..40) == Some(b"VBRI") {
+		return Some(u32::from_be_bytes(frame.get(50..54)?.try_into().ok()?));
+	}
+	None
+}
+
+// an info frame for `frames` audio frames and `bytes` of mpeg data (itself
+// included), built on the header of the stream's first frame. the bitrate is
+// raised when the frame would be too short to hold the tag; "Xing" marks a
+// variable bitrate stream and "Info" a constant one, as lame writes them
+pub fn info_frame(template: &FrameHeader, vbr: bool, frames: u32, bytes: u32) -> Vec<u8> {
+	let mut header = *template;
+	// no crc, no padding
+	header.bytes[1] |= 0x01;
+	header.bytes[2] &= !0x02;
+	header = FrameHeader::parse(header.bytes).unwrap_or(*template);
+	while header.frame_len() < header.side_info_end() + 16 && header.bitrate_index < 14 {
+		header.bytes[2] = (header.bytes[2] & 0x0F) | ((header.bitrate_index + 1) << 4);
+		header = FrameHeader::parse(header.bytes).unwrap_or(header);
+	}
+
+	let mut frame = vec![0u8; header.frame_len().max(header.side_info_end() + 16)];
+	frame[..4].copy_from_slice(&header.bytes);
+	let at = header.side_info_end();
+	frame[at..at + 4].copy_from_slice(if vbr { b"Xing" } else { b"Info" });
+	// frame count and byte count present
+	frame[at + 4..at + 8].copy_from_slice(&0x03u32.to_be_bytes());
+	frame[at + 8..at + 12].copy_from_slice(&frames.to_be_bytes());
+	frame[at + 12..at + 16].copy_from_slice(&bytes.to_be_bytes());
+	frame
+}
@@ -0,0 +1,148 @@
+use super::{FrameHeader, ID3V2_SIGNATURE, Mp3Format, info_tag_frames};
+use crate::core::{Demuxer, Packet, Timebase, log};
+use crate::io::{IoError, IoResult, MediaRead};
+
+// mpeg audio frames, one packet each, stamped with their first sample. an
+// id3v2 tag in front is kept for the writer; a xing/info header is read for
+// its frame count and not passed on, since it describes the whole input
+pub struct Mp3Reader<R: MediaRead> {
+	reader: R,
+	format: Mp3Format,
+	// header of the first frame, which later frames have to match
+	first: FrameHeader,
+	timebase: Timebase,
+	id3: Option<Vec<u8>>,
+	// bytes read ahead while looking for the first frame
+	pushback: Vec<u8>,
+	pending: Option<(FrameHeader, Vec<u8>)>,
+	samples: u64,
+}
+
+impl<R: MediaRead> Mp3Reader<R> {
+	pub fn new(mut reader: R) -> IoResult<Self> {
+		let mut head = [0u8; 10];
+		let read = read_full(&mut reader, &mut head)?;
+		let mut id3 = None;
+		let mut pushback = head[..read].to_vec();
+		if read == 10 && &head[..3] == ID3V2_SIGNATURE {
+			// a syncsafe size, plus a 10 byte footer when flagged
+			let size = head[6..10].iter().fold(0usize, |size, &b| (size << 7) | (b & 0x7F) as usize);
+			let footer = if head[5] & 0x10 != 0 { 10 } else { 0 };
+			let mut tag = head.to_vec();
+			tag.resize(10 + size + footer, 0);
+			if read_full(&mut reader, &mut tag[10..])? < size + footer {
+				return Err(IoError::invalid_data("id3v2 tag runs past the end of the file"));
+			}
+			id3 = Some(tag);
+			pushback.clear();
+		}
+
+		let Some((header, frame)) = next_frame(&mut reader, &mut pushback, None)? else {
+			return Err(IoError::invalid_data("no mpeg audio frame found"));
+		};
+		let mut format = Mp3Format::from_header(&header);
+		let mut pending = None;
+		match info_tag_frames(&frame, &header) {
+			Some(frames) => {
+				log::debug("mp3", format_args!("info header for {} frames", frames));
+				format.frames = (frames > 0).then_some(frames);
+			}
+			None => pending = Some((header, frame)),
+		}
+		let timebase = Timebase::new(1, header.sample_rate);
+		Ok(Self { reader, format, first: header, timebase, id3, pushback, pending, samples: 0 })
+	}
+
+	pub fn format(&self) -> &Mp3Format {
+		&self.format
+	}
+
+	// the raw id3v2 tag in front of the audio, header included
+	pub fn id3_tag(&self) -> Option<&[u8]> {
+		self.id3.as_deref()
+	}
+}
+
+// the next frame, skipping bytes that are not one; a stream header given
+// has to be matched, which keeps junk that happens to look like a header
+// from being taken for a frame
+fn next_frame<R: MediaRead>(
+	reader: &mut R,
+	pushback: &mut Vec<u8>,
+	stream: Option<&FrameHeader>,
+) -> IoResult<Option<(FrameHeader, Vec<u8>)>> {
+	let mut head = [0u8; 4];
+	if read_buffered(reader, pushback, &mut head)? < 4 {
+		return Ok(None);
+	}
+	let mut skipped = 0u64;
+	loop {
+		if let Some(header) = FrameHeader::parse(head)
+			&& stream.is_none_or(|stream| stream.same_stream(&header))
+		{
+			if skipped > 0 {
+				log::warn("mp3", format_args!("skipped {} bytes between frames", skipped));
+			}
+			let mut frame = head.to_vec();
+			frame.resize(header.frame_len(), 0);
+			if read_buffered(reader, pushback, &mut frame[4..])? < frame.len() - 4 {
+				log::warn("mp3", format_args!("dropped a truncated frame at the end of the file"));
+				return Ok(None);
+			}
+			return Ok(Some((header, frame)));
+		}
+		// an id3v1 tag closes the file
+		if &head[..3] == b"TAG" {
+			return Ok(None);
+		}
+		head.rotate_left(1);
+		if read_buffered(reader, pushback, &mut head[3..])? == 0 {
+			return Ok(None);
+		}
+		skipped += 1;
+	}
+}
+
+fn read_buffered<R: MediaRead>(
+	reader: &mut R,
+	pushback: &mut Vec<u8>,
+	buf: &mut [u8],
+) -> IoResult<usize> {
+	let taken = pushback.len().min(buf.len());
+	buf[..taken].copy_from_slice(&pushback[..taken]);
+	pushback.drain(..taken);
+	Ok(taken + read_full(reader, &mut buf[taken..])?)
+}
+
+// reads until `buf` is full or the input ends; how much was read
+fn read_full<R: MediaRead>(reader: &mut R, buf: &mut [u8]) -> IoResult<usize> {
+	let mut filled = 0;
+	while filled < buf.len() {
+		match reader.read(&mut buf[filled..])? {
+			0 => break,
+			n => filled += n,
+		}
+	}
+	Ok(filled)
+}
+
+impl<R: MediaRead> Demuxer for Mp3Reader<R> {
+	fn read_packet(&mut self) -> IoResult<Option<Packet>> {
+		let next = match self.pending.take() {
+			Some(frame) => Some(frame),
+			None => next_frame(&mut self.reader, &mut self.pushback, Some(&self.first))?,
+		};
+		let Some((header, frame)) = next else {
+			return Ok(None);
+		};
+		let pts = self.samples as i64;
+		self.samples += header.samples() as u64;
+		let mut packet = Packet::new(frame, 0, self.timebase).with_pts(pts).with_dts(pts);
+		packet.keyframe = true;
+		Ok(Some(packet))
+	}
+
+	fn stream_count(&self) -> usize {
+		1
+	}
+}
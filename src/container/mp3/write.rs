@@ -0,0 +1,102 @@
+use super::{FrameHeader, info_frame};
+use crate::core::{Muxer, Packet, log};
+use crate::io::{IoError, IoResult, MediaSeek, MediaWrite, SeekFrom, WritePrimitives};
+
+// writes mpeg audio frames behind a fresh info header, so the duration players
+// report is the frames actually written rather than whatever the input said.
+// packets are placed by their pts: one starting before the previous frame
+// ended repeats audio and is dropped, and a gap is closed up (mp3 has no way
+// to hold silence without encoding it) with a warning
+pub struct Mp3Writer<W: MediaWrite + MediaSeek> {
+	writer: W,
+	id3: Option<Vec<u8>>,
+	// where the info frame went and the first frame it was built from
+	info: Option<(u64, FrameHeader)>,
+	first_bitrate: Option<u8>,
+	vbr: bool,
+	frames: u32,
+	bytes: u64,
+	next_pts: Option<i64>,
+}
+
+impl<W: MediaWrite + MediaSeek> Mp3Writer<W> {
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			id3: None,
+			info: None,
+			first_bitrate: None,
+			vbr: false,
+			frames: 0,
+			bytes: 0,
+			next_pts: None,
+		}
+	}
+
+	// a raw id3v2 tag, as `Mp3Reader::id3_tag` returns it, written first
+	pub fn with_id3(mut self, tag: Vec<u8>) -> Self {
+		self.id3 = Some(tag);
+		self
+	}
+
+	fn info_frame(&self, header: &FrameHeader) -> Vec<u8> {
+		let len = info_frame(header, self.vbr, 0, 0).len() as u64;
+		let bytes = u32::try_from(self.bytes + len).unwrap_or(u32::MAX);
+		info_frame(header, self.vbr, self.frames, bytes)
+	}
+}
+
+impl<W: MediaWrite + MediaSeek> Muxer for Mp3Writer<W> {
+	fn write_packet(&mut self, packet: Packet) -> IoResult<()> {
+		let header = packet
+			.data
+			.first_chunk::<4>()
+			.and_then(|bytes| FrameHeader::parse(*bytes))
+			.ok_or_else(|| IoError::invalid_data("mp3 packet does not start with a frame header"))?;
+
+		if let Some(next) = self.next_pts {
+			if packet.pts < next {
+				log::debug("mp3", format_args!("dropped a frame overlapping the one before it"));
+				return Ok(());
+			}
+			if packet.pts > next {
+				log::warn(
+					"mp3",
+					format_args!("closed a gap of {} samples between frames", packet.pts - next),
+				);
+			}
+		}
+		self.next_pts = Some(packet.pts + header.samples() as i64);
+
+		if self.first_bitrate.is_none() {
+			if let Some(tag) = self.id3.take() {
+				self.writer.write_all(&tag)?;
+			}
+			// only layer iii players look for an info header
+			if header.layer == 3 {
+				let position = self.writer.stream_position()?;
+				let frame = self.info_frame(&header);
+				self.writer.write_all(&frame)?;
+				self.info = Some((position, header));
+			}
+		}
+		self.vbr |= self.first_bitrate.is_some_and(|bitrate| bitrate != header.bitrate_index);
+		self.first_bitrate.get_or_insert(header.bitrate_index);
+
+		self.writer.write_all(&packet.data)?;
+		self.frames = self.frames.saturating_add(1);
+		self.bytes += packet.size() as u64;
+		Ok(())
+	}
+
+	fn finalize(&mut self) -> IoResult<()> {
+		if let Some((position, header)) = self.info {
+			let frame = self.info_frame(&header);
+			let end = self.writer.stream_position()?;
+			self.writer.seek(SeekFrom::Start(position))?;
+			self.writer.write_all(&frame)?;
+			self.writer.seek(SeekFrom::Start(end))?;
+		}
+		self.writer.flush()
+	}
+}
@@ -4,6 +4,8 @@ pub mod write;
 pub use read::Mp4Reader;
 pub use write::Mp4Writer;
 
+use super::metadata::{Disposition, MediaMetadata, UNDETERMINED_LANGUAGE};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoxType {
 	Ftyp,
@@ -31,6 +33,7 @@ pub enum BoxType {
 	Meta,
 	Edts,
 	Elst,
+	Kind,
 	Unknown,
 }
 
@@ -62,6 +65,7 @@ impl BoxType {
 			b"meta" => BoxType::Meta,
 			b"edts" => BoxType::Edts,
 			b"elst" => BoxType::Elst,
+			b"kind" => BoxType::Kind,
 			_ => BoxType::Unknown,
 		}
 	}
@@ -93,6 +97,7 @@ impl BoxType {
 			BoxType::Meta => *b"meta",
 			BoxType::Edts => *b"edts",
 			BoxType::Elst => *b"elst",
+			BoxType::Kind => *b"kind",
 			BoxType::Unknown => *b"    ",
 		}
 	}
@@ -115,6 +120,8 @@ pub struct Mp4Format {
 	pub timescale: u32,
 	pub duration: u64,
 	pub tracks: Vec<Mp4Track>,
+	// the moov/udta/meta/ilst items, under MediaMetadata's names
+	pub metadata: MediaMetadata,
 }
 
 impl Default for Mp4Format {
@@ -126,6 +133,7 @@ impl Default for Mp4Format {
 			timescale: 1000,
 			duration: 0,
 			tracks: Vec::new(),
+			metadata: MediaMetadata::default(),
 		}
 	}
 }
@@ -144,6 +152,21 @@ pub struct Mp4Track {
 	pub chunk_offsets: Vec<u64>,
 	pub sample_to_chunk: Vec<(u32, u32, u32)>,
 	pub time_to_sample: Vec<(u32, u32)>,
+	// 1-based numbers of the samples a decoder can start at; without an
+	// stss box every sample is one
+	pub sync_samples: Option<Vec<u32>>,
+	pub language: String,
+	pub disposition: Disposition,
+}
+
+impl Mp4Track {
+	// `sample` counted from 0
+	pub fn is_sync_sample(&self, sample: usize) -> bool {
+		match &self.sync_samples {
+			Some(sync) => sync.binary_search(&(sample as u32 + 1)).is_ok(),
+			None => true,
+		}
+	}
 }
 
 impl Default for Mp4Track {
@@ -161,6 +184,9 @@ impl Default for Mp4Track {
 			chunk_offsets: Vec::new(),
 			sample_to_chunk: Vec::new(),
 			time_to_sample: Vec::new(),
+			sync_samples: None,
+			language: UNDETERMINED_LANGUAGE.to_string(),
+			disposition: Disposition { default: true, commentary: false },
 		}
 	}
 }
@@ -171,3 +197,40 @@ pub struct BoxHeader {
 	pub box_type: BoxType,
 	pub header_size: u8,
 }
+
+// tkhd flag marking a track as enabled; players treat it as the default track
+pub const TKHD_FLAG_ENABLED: u32 = 0x1;
+pub const TKHD_FLAG_IN_MOVIE: u32 = 0x2;
+
+// ilst item names and the MediaMetadata key each is read into; 0xa9 is ©
+pub const ILST_KEYS: [(&[u8; 4], &str); 9] = [
+	(b"\xa9nam", "title"),
+	(b"\xa9ART", "artist"),
+	(b"\xa9alb", "album"),
+	(b"\xa9day", "year"),
+	(b"\xa9gen", "genre"),
+	(b"\xa9cmt", "comment"),
+	(b"\xa9too", "encoder"),
+	(b"trkn", "track"),
+	(b"disk", "disc"),
+];
+
+// role scheme used by the udta/kind box to tag commentary tracks
+pub const KIND_ROLE_SCHEME: &str = "urn:mpeg:dash:role:2011";
+
+// mdhd packs ISO 639-2 codes as three 5-bit letters offset by 0x60
+pub fn pack_language(code: &str) -> u16 {
+	let bytes = code.as_bytes();
+	if bytes.len() != 3 {
+		return pack_language(UNDETERMINED_LANGUAGE);
+	}
+	bytes.iter().fold(0u16, |acc, &b| (acc << 5) | ((b.wrapping_sub(0x60) as u16) & 0x1F))
+}
+
+pub fn unpack_language(packed: u16) -> String {
+	let letters = [(packed >> 10) & 0x1F, (packed >> 5) & 0x1F, packed & 0x1F];
+	if letters.iter().any(|&l| l == 0 || l > 26) {
+		return UNDETERMINED_LANGUAGE.to_string();
+	}
+	letters.iter().map(|&l| (l as u8 + 0x60) as char).collect()
+}
@@ -1,5 +1,10 @@
-use super::{BoxHeader, BoxType, Mp4Format, Mp4Track, TrackType};
-use crate::core::{Demuxer, Packet, Timebase};
+use super::{
+	BoxHeader, BoxType, ILST_KEYS, KIND_ROLE_SCHEME, Mp4Format, Mp4Track, TKHD_FLAG_ENABLED,
+	TrackType, unpack_language,
+};
+use crate::container::fourcc_name;
+use crate::container::metadata::MediaMetadata;
+use crate::core::{Demuxer, Packet, Timebase, log};
 use crate::io::{IoResult, MediaRead, ReadPrimitives};
 
 pub struct Mp4Reader<R: MediaRead> {
@@ -68,6 +73,10 @@ impl<R: MediaRead> Mp4Reader<R> {
 					Self::skip_bytes(reader, content_size)?;
 				}
 				_ => {
+					log::debug(
+						"mp4",
+						format_args!("skipped top-level {:?} box ({} bytes)", header.box_type, content_size),
+					);
 					Self::skip_bytes(reader, content_size)?;
 				}
 			}
@@ -84,6 +93,7 @@ impl<R: MediaRead> Mp4Reader<R> {
 		reader.read_exact(&mut fourcc)?;
 
 		let box_type = BoxType::from_fourcc(&fourcc);
+		log::trace("mp4", format_args!("{} box, size field {}", fourcc_name(&fourcc), size));
 
 		let (actual_size, header_size) = if size == 1 {
 			let large_size = reader.read_u64_be()?;
@@ -128,6 +138,9 @@ impl<R: MediaRead> Mp4Reader<R> {
 					let track = Self::parse_trak(reader, content_size)?;
 					format.tracks.push(track);
 				}
+				BoxType::Udta => {
+					Self::parse_udta(reader, content_size, format)?;
+				}
 				_ => {
 					Self::skip_bytes(reader, content_size)?;
 				}
@@ -149,13 +162,13 @@ impl<R: MediaRead> Mp4Reader<R> {
 			let _modification_time = reader.read_u64_be()?;
 			format.timescale = reader.read_u32_be()?;
 			format.duration = reader.read_u64_be()?;
-			Self::skip_bytes(reader, size - 28)?;
+			Self::skip_bytes(reader, size.saturating_sub(32))?;
 		} else {
 			let _creation_time = reader.read_u32_be()?;
 			let _modification_time = reader.read_u32_be()?;
 			format.timescale = reader.read_u32_be()?;
 			format.duration = reader.read_u32_be()? as u64;
-			Self::skip_bytes(reader, size - 16)?;
+			Self::skip_bytes(reader, size.saturating_sub(20))?;
 		}
 
 		Ok(())
@@ -177,6 +190,9 @@ impl<R: MediaRead> Mp4Reader<R> {
 				BoxType::Mdia => {
 					Self::parse_mdia(reader, content_size, &mut track)?;
 				}
+				BoxType::Udta => {
+					Self::parse_track_udta(reader, content_size, &mut track)?;
+				}
 				_ => {
 					Self::skip_bytes(reader, content_size)?;
 				}
@@ -190,8 +206,10 @@ impl<R: MediaRead> Mp4Reader<R> {
 
 	fn parse_tkhd(reader: &mut R, size: u64, track: &mut Mp4Track) -> IoResult<()> {
 		let version = reader.read_u8()?;
-		let mut _flags = [0u8; 3];
-		reader.read_exact(&mut _flags)?;
+		let mut flags = [0u8; 3];
+		reader.read_exact(&mut flags)?;
+		let flags = u32::from_be_bytes([0, flags[0], flags[1], flags[2]]);
+		track.disposition.default = flags & TKHD_FLAG_ENABLED != 0;
 
 		if version == 1 {
 			let _creation_time = reader.read_u64_be()?;
@@ -199,14 +217,75 @@ impl<R: MediaRead> Mp4Reader<R> {
 			track.track_id = reader.read_u32_be()?;
 			let _reserved = reader.read_u32_be()?;
 			track.duration = reader.read_u64_be()?;
-			Self::skip_bytes(reader, size - 32)?;
+			Self::skip_bytes(reader, size.saturating_sub(36))?;
 		} else {
 			let _creation_time = reader.read_u32_be()?;
 			let _modification_time = reader.read_u32_be()?;
 			track.track_id = reader.read_u32_be()?;
 			let _reserved = reader.read_u32_be()?;
 			track.duration = reader.read_u32_be()? as u64;
-			Self::skip_bytes(reader, size - 20)?;
+			Self::skip_bytes(reader, size.saturating_sub(24))?;
+		}
+
+		Ok(())
+	}
+
+	// moov/udta/meta/ilst: iTunes-style items, each a box named for its key
+	// holding a data box
+	fn parse_udta(reader: &mut R, size: u64, format: &mut Mp4Format) -> IoResult<()> {
+		let mut remaining = size;
+
+		while remaining >= 8 {
+			let header = Self::read_box_header(reader)?;
+			remaining -= header.header_size as u64;
+			let content_size = header.size.saturating_sub(header.header_size as u64);
+
+			match header.box_type {
+				BoxType::Meta => {
+					let mut payload = vec![0u8; content_size as usize];
+					reader.read_exact(&mut payload)?;
+					// a full box: version and flags before the children
+					let children = payload.get(4..).unwrap_or(&[]);
+					if let Some(ilst) = child_box(children, b"ilst") {
+						parse_ilst(ilst, &mut format.metadata);
+					}
+				}
+				_ => {
+					Self::skip_bytes(reader, content_size)?;
+				}
+			}
+
+			remaining = remaining.saturating_sub(content_size);
+		}
+
+		Ok(())
+	}
+
+	fn parse_track_udta(reader: &mut R, size: u64, track: &mut Mp4Track) -> IoResult<()> {
+		let mut remaining = size;
+
+		while remaining >= 8 {
+			let header = Self::read_box_header(reader)?;
+			remaining -= header.header_size as u64;
+			let content_size = header.size.saturating_sub(header.header_size as u64);
+
+			match header.box_type {
+				BoxType::Kind => {
+					let mut payload = vec![0u8; content_size as usize];
+					reader.read_exact(&mut payload)?;
+					let mut fields = payload.get(4..).unwrap_or(&[]).split(|&b| b == 0);
+					let scheme = fields.next().unwrap_or(&[]);
+					let value = fields.next().unwrap_or(&[]);
+					if scheme == KIND_ROLE_SCHEME.as_bytes() && value == b"commentary" {
+						track.disposition.commentary = true;
+					}
+				}
+				_ => {
+					Self::skip_bytes(reader, content_size)?;
+				}
+			}
+
+			remaining = remaining.saturating_sub(content_size);
 		}
 
 		Ok(())
@@ -251,13 +330,15 @@ impl<R: MediaRead> Mp4Reader<R> {
 			let _modification_time = reader.read_u64_be()?;
 			track.timescale = reader.read_u32_be()?;
 			track.duration = reader.read_u64_be()?;
-			Self::skip_bytes(reader, size - 28)?;
+			track.language = unpack_language(reader.read_u16_be()?);
+			Self::skip_bytes(reader, size.saturating_sub(34))?;
 		} else {
 			let _creation_time = reader.read_u32_be()?;
 			let _modification_time = reader.read_u32_be()?;
 			track.timescale = reader.read_u32_be()?;
 			track.duration = reader.read_u32_be()? as u64;
-			Self::skip_bytes(reader, size - 16)?;
+			track.language = unpack_language(reader.read_u16_be()?);
+			Self::skip_bytes(reader, size.saturating_sub(22))?;
 		}
 
 		Ok(())
@@ -326,6 +407,9 @@ impl<R: MediaRead> Mp4Reader<R> {
 				BoxType::Stco => {
 					Self::parse_stco(reader, content_size, track)?;
 				}
+				BoxType::Stss => {
+					Self::parse_stss(reader, content_size, track)?;
+				}
 				BoxType::Co64 => {
 					Self::parse_co64(reader, content_size, track)?;
 				}
@@ -369,6 +453,21 @@ impl<R: MediaRead> Mp4Reader<R> {
 		Ok(())
 	}
 
+	fn parse_stss(reader: &mut R, _size: u64, track: &mut Mp4Track) -> IoResult<()> {
+		let _version_flags = reader.read_u32_be()?;
+		let entry_count = reader.read_u32_be()?;
+
+		let mut sync = Vec::with_capacity(entry_count.min(1 << 16) as usize);
+		for _ in 0..entry_count {
+			sync.push(reader.read_u32_be()?);
+		}
+		// in increasing order by the spec, which lookups rely on
+		sync.sort_unstable();
+		track.sync_samples = Some(sync);
+
+		Ok(())
+	}
+
 	fn parse_stsz(reader: &mut R, _size: u64, track: &mut Mp4Track) -> IoResult<()> {
 		let _version_flags = reader.read_u32_be()?;
 		let sample_size = reader.read_u32_be()?;
@@ -450,6 +549,13 @@ impl<R: MediaRead> Demuxer for Mp4Reader<R> {
 		match self.reader.read_exact(&mut data) {
 			Ok(()) => {}
 			Err(e) if matches!(e.kind(), crate::io::IoErrorKind::UnexpectedEof) => {
+				log::warn(
+					"mp4",
+					format_args!(
+						"media data ends inside sample {} of track {}, dropping the rest",
+						self.current_sample, self.current_track
+					),
+				);
 				self.eof = true;
 				return Ok(None);
 			}
@@ -470,3 +576,51 @@ impl<R: MediaRead> Demuxer for Mp4Reader<R> {
 		self.format.tracks.len()
 	}
 }
+
+// the payload of the first child box of `data` named `fourcc`
+fn child_box<'a>(data: &'a [u8], fourcc: &[u8; 4]) -> Option<&'a [u8]> {
+	let mut pos = 0;
+	while pos + 8 <= data.len() {
+		let size = u32::from_be_bytes(data[pos..pos + 4].try_into().ok()?) as usize;
+		if size < 8 || pos + size > data.len() {
+			return None;
+		}
+		if &data[pos + 4..pos + 8] == fourcc {
+			return Some(&data[pos + 8..pos + size]);
+		}
+		pos += size;
+	}
+	None
+}
+
+fn parse_ilst(ilst: &[u8], metadata: &mut MediaMetadata) {
+	let mut pos = 0;
+	while pos + 8 <= ilst.len() {
+		let size =
+			u32::from_be_bytes([ilst[pos], ilst[pos + 1], ilst[pos + 2], ilst[pos + 3]]) as usize;
+		if size < 8 || pos + size > ilst.len() {
+			log::warn("mp4", format_args!("ilst item at {} runs past the list, rest ignored", pos));
+			return;
+		}
+		let name = &ilst[pos + 4..pos + 8];
+		// data: a type (1 is utf-8 text, 0 binary), a locale, then the value
+		let value = child_box(&ilst[pos + 8..pos + size], b"data").filter(|data| data.len() >= 8);
+		if let Some(value) = value {
+			let (kind, value) = (value[3], &value[8..]);
+			match (ILST_KEYS.iter().find(|(fourcc, _)| fourcc[..] == *name), kind) {
+				(Some((_, key)), 1) => {
+					metadata.add_tag(*key, String::from_utf8_lossy(value).into_owned());
+				}
+				// trkn and disk are binary: 2 reserved bytes, the number, the total
+				(Some((_, key)), 0) if value.len() >= 4 => {
+					let number = u16::from_be_bytes([value[2], value[3]]);
+					let total = value.get(4..6).map_or(0, |t| u16::from_be_bytes([t[0], t[1]]));
+					let text = if total > 0 { format!("{}/{}", number, total) } else { number.to_string() };
+					metadata.add_tag(*key, text);
+				}
+				_ => {}
+			}
+		}
+		pos += size;
+	}
+}
@@ -1,4 +1,7 @@
-use super::{BoxType, Mp4Format};
+use super::{
+	BoxType, KIND_ROLE_SCHEME, Mp4Format, TKHD_FLAG_ENABLED, TKHD_FLAG_IN_MOVIE, pack_language,
+};
+use crate::container::metadata::CoverArt;
 use crate::core::{Muxer, Packet};
 use crate::io::{IoResult, MediaSeek, MediaWrite, SeekFrom, WritePrimitives};
 
@@ -8,6 +11,7 @@ pub struct Mp4Writer<W: MediaWrite + MediaSeek> {
 	samples: Vec<SampleInfo>,
 	mdat_start: u64,
 	mdat_size: u64,
+	cover: Option<CoverArt>,
 }
 
 struct SampleInfo {
@@ -15,6 +19,7 @@ struct SampleInfo {
 	#[allow(dead_code)]
 	duration: u32,
 	stream_index: usize,
+	keyframe: bool,
 }
 
 impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
@@ -22,7 +27,13 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		Self::write_ftyp(&mut writer, &format)?;
 		let mdat_start = Self::write_mdat_header(&mut writer)?;
 
-		Ok(Self { writer, format, samples: Vec::new(), mdat_start, mdat_size: 0 })
+		Ok(Self { writer, format, samples: Vec::new(), mdat_start, mdat_size: 0, cover: None })
+	}
+
+	// written as an iTunes-style covr atom under moov/udta/meta/ilst
+	pub fn with_cover(mut self, cover: CoverArt) -> Self {
+		self.cover = Some(cover);
+		self
 	}
 
 	fn write_ftyp(writer: &mut W, format: &Mp4Format) -> IoResult<()> {
@@ -59,6 +70,9 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		for (track_idx, track) in tracks.iter().enumerate() {
 			self.write_trak(track_idx, track)?;
 		}
+		if let Some(cover) = self.cover.take() {
+			self.write_cover_udta(&cover)?;
+		}
 
 		let moov_end = self.writer.stream_position()?;
 		let moov_size = (moov_end - moov_start) as u32;
@@ -102,6 +116,9 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 
 		self.write_tkhd(track)?;
 		self.write_mdia(track_idx, track)?;
+		if track.disposition.commentary {
+			self.write_track_udta()?;
+		}
 
 		let trak_end = self.writer.stream_position()?;
 		let trak_size = (trak_end - trak_start) as u32;
@@ -117,8 +134,12 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		self.writer.write_u32_be(tkhd_size)?;
 		self.writer.write_all(&BoxType::Tkhd.as_fourcc())?;
 
+		let mut flags = TKHD_FLAG_IN_MOVIE;
+		if track.disposition.default {
+			flags |= TKHD_FLAG_ENABLED;
+		}
 		self.writer.write_u8(0)?;
-		self.writer.write_all(&[0x00, 0x00, 0x03])?;
+		self.writer.write_all(&flags.to_be_bytes()[1..])?;
 		self.writer.write_u32_be(0)?;
 		self.writer.write_u32_be(0)?;
 		self.writer.write_u32_be(track.track_id)?;
@@ -141,6 +162,61 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		Ok(())
 	}
 
+	fn write_cover_udta(&mut self, cover: &CoverArt) -> IoResult<()> {
+		let data_size = 16 + cover.data.len() as u32;
+		let covr_size = 8 + data_size;
+		let ilst_size = 8 + covr_size;
+		let hdlr_size = 33u32;
+		let meta_size = 12 + hdlr_size + ilst_size;
+
+		self.writer.write_u32_be(8 + meta_size)?;
+		self.writer.write_all(&BoxType::Udta.as_fourcc())?;
+
+		self.writer.write_u32_be(meta_size)?;
+		self.writer.write_all(&BoxType::Meta.as_fourcc())?;
+		self.writer.write_u32_be(0)?;
+
+		self.writer.write_u32_be(hdlr_size)?;
+		self.writer.write_all(&BoxType::Hdlr.as_fourcc())?;
+		self.writer.write_u32_be(0)?;
+		self.writer.write_u32_be(0)?;
+		self.writer.write_all(b"mdir")?;
+		self.writer.write_all(b"appl")?;
+		self.writer.write_all(&[0u8; 8])?;
+		self.writer.write_u8(0)?;
+
+		self.writer.write_u32_be(ilst_size)?;
+		self.writer.write_all(b"ilst")?;
+		self.writer.write_u32_be(covr_size)?;
+		self.writer.write_all(b"covr")?;
+		self.writer.write_u32_be(data_size)?;
+		self.writer.write_all(b"data")?;
+		// well-known type 13 is jpeg, 14 png; then a zero locale
+		self.writer.write_u32_be(if cover.is_png() { 14 } else { 13 })?;
+		self.writer.write_u32_be(0)?;
+		self.writer.write_all(&cover.data)?;
+
+		Ok(())
+	}
+
+	fn write_track_udta(&mut self) -> IoResult<()> {
+		let value = b"commentary";
+		let kind_size = (8 + 4 + KIND_ROLE_SCHEME.len() + 1 + value.len() + 1) as u32;
+
+		self.writer.write_u32_be(8 + kind_size)?;
+		self.writer.write_all(&BoxType::Udta.as_fourcc())?;
+
+		self.writer.write_u32_be(kind_size)?;
+		self.writer.write_all(&BoxType::Kind.as_fourcc())?;
+		self.writer.write_u32_be(0)?;
+		self.writer.write_all(KIND_ROLE_SCHEME.as_bytes())?;
+		self.writer.write_u8(0)?;
+		self.writer.write_all(value)?;
+		self.writer.write_u8(0)?;
+
+		Ok(())
+	}
+
 	fn write_mdia(&mut self, track_idx: usize, track: &super::Mp4Track) -> IoResult<()> {
 		let mdia_start = self.writer.stream_position()?;
 		self.writer.write_u32_be(0)?;
@@ -170,7 +246,7 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		self.writer.write_u32_be(0)?;
 		self.writer.write_u32_be(track.timescale)?;
 		self.writer.write_u32_be(track.duration as u32)?;
-		self.writer.write_u16_be(0x55C4)?;
+		self.writer.write_u16_be(pack_language(&track.language))?;
 		self.writer.write_u16_be(0)?;
 
 		Ok(())
@@ -268,6 +344,7 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		self.write_stts(track_idx)?;
 		self.write_stsc(track_idx)?;
 		self.write_stsz(track_idx)?;
+		self.write_stss(track_idx)?;
 		self.write_stco(track_idx)?;
 
 		let stbl_end = self.writer.stream_position()?;
@@ -371,6 +448,34 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		Ok(())
 	}
 
+	// only for a track that flagged some packets keyframes and not others:
+	// with no stss every sample is a sync sample, which is also the safe
+	// reading of packets that were never flagged at all
+	fn write_stss(&mut self, track_idx: usize) -> IoResult<()> {
+		let track_samples: Vec<_> =
+			self.samples.iter().filter(|s| s.stream_index == track_idx).collect();
+		let sync: Vec<u32> = (1..=track_samples.len() as u32)
+			.zip(&track_samples)
+			.filter(|(_, sample)| sample.keyframe)
+			.map(|(number, _)| number)
+			.collect();
+		if sync.is_empty() || sync.len() == track_samples.len() {
+			return Ok(());
+		}
+
+		let stss_size = (16 + 4 * sync.len()) as u32;
+		self.writer.write_u32_be(stss_size)?;
+		self.writer.write_all(&BoxType::Stss.as_fourcc())?;
+		self.writer.write_u32_be(0)?;
+		self.writer.write_u32_be(sync.len() as u32)?;
+
+		for number in sync {
+			self.writer.write_u32_be(number)?;
+		}
+
+		Ok(())
+	}
+
 	fn write_stco(&mut self, track_idx: usize) -> IoResult<()> {
 		let track_samples: Vec<_> =
 			self.samples.iter().filter(|s| s.stream_index == track_idx).collect();
@@ -381,9 +486,12 @@ impl<W: MediaWrite + MediaSeek> Mp4Writer<W> {
 		self.writer.write_u32_be(0)?;
 		self.writer.write_u32_be(track_samples.len() as u32)?;
 
+		// samples of every track sit in mdat in the order they were written
 		let mut offset = self.mdat_start as u32;
-		for sample in &track_samples {
-			self.writer.write_u32_be(offset)?;
+		for sample in &self.samples {
+			if sample.stream_index == track_idx {
+				self.writer.write_u32_be(offset)?;
+			}
 			offset += sample.size;
 		}
 
@@ -397,7 +505,12 @@ impl<W: MediaWrite + MediaSeek> Muxer for Mp4Writer<W> {
 		self.writer.write_all(&packet.data)?;
 		self.mdat_size += size as u64;
 
-		self.samples.push(SampleInfo { size, duration: 1024, stream_index: packet.stream_index });
+		self.samples.push(SampleInfo {
+			size,
+			duration: 1024,
+			stream_index: packet.stream_index,
+			keyframe: packet.keyframe,
+		});
 
 		Ok(())
 	}
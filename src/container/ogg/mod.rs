@@ -0,0 +1,153 @@
+pub mod read;
+pub mod write;
+
+pub use read::OggReader;
+pub use write::OggWriter;
+
+pub const OGG_SIGNATURE: &[u8; 4] = b"OggS";
+
+// header type flags
+pub const PAGE_CONTINUED: u8 = 0x01;
+pub const PAGE_FIRST: u8 = 0x02;
+pub const PAGE_LAST: u8 = 0x04;
+
+// granule position of a page on which no packet ends
+pub const NO_GRANULE: i64 = -1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OggCodec {
+	Vorbis,
+	Opus,
+	Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OggFormat {
+	pub codec: OggCodec,
+	pub serial: u32,
+	pub sample_rate: u32,
+	pub channels: u8,
+	// opus decoders drop this many samples first; granules count them
+	pub pre_skip: u32,
+}
+
+impl OggFormat {
+	// from the first packet of the stream
+	pub fn parse(serial: u32, packet: &[u8]) -> Self {
+		let le32 =
+			|at: usize| packet.get(at..at + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+		if packet.len() >= 16 && packet.starts_with(b"\x01vorbis") {
+			return Self {
+				codec: OggCodec::Vorbis,
+				serial,
+				sample_rate: le32(12).unwrap_or(0),
+				channels: packet[11],
+				pre_skip: 0,
+			};
+		}
+		if packet.len() >= 12 && packet.starts_with(b"OpusHead") {
+			return Self {
+				codec: OggCodec::Opus,
+				serial,
+				sample_rate: le32(12).filter(|&rate| rate > 0).unwrap_or(48000),
+				channels: packet[9],
+				pre_skip: u16::from_le_bytes([packet[10], packet[11]]) as u32,
+			};
+		}
+		Self { codec: OggCodec::Unknown, serial, sample_rate: 0, channels: 0, pre_skip: 0 }
+	}
+
+	// granules tick at the sample rate, except for opus which is always 48 kHz
+	pub fn granule_rate(&self) -> u32 {
+		match self.codec {
+			OggCodec::Opus => 48000,
+			_ => self.sample_rate,
+		}
+	}
+
+	// the granule that ends `seconds` into the audio
+	pub fn granule_at(&self, seconds: f64) -> i64 {
+		(seconds * self.granule_rate() as f64).round() as i64 + self.pre_skip as i64
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OggPage {
+	pub flags: u8,
+	pub granule: i64,
+	pub serial: u32,
+	pub sequence: u32,
+	// segment sizes; a packet ends at each one under 255
+	pub lacing: Vec<u8>,
+	pub body: Vec<u8>,
+}
+
+impl OggPage {
+	// a whole serialized page; the crc is not checked
+	pub fn parse(data: &[u8]) -> Option<Self> {
+		if data.len() < 27 || !data.starts_with(OGG_SIGNATURE) {
+			return None;
+		}
+		let segments = data[26] as usize;
+		let lacing = data.get(27..27 + segments)?.to_vec();
+		let size = lacing.iter().map(|&len| len as usize).sum::<usize>();
+		let body = data.get(27 + segments..27 + segments + size)?.to_vec();
+		Some(Self {
+			flags: data[5],
+			granule: i64::from_le_bytes(data[6..14].try_into().ok()?),
+			serial: u32::from_le_bytes(data[14..18].try_into().ok()?),
+			sequence: u32::from_le_bytes(data[18..22].try_into().ok()?),
+			lacing,
+			body,
+		})
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut page = Vec::with_capacity(27 + self.lacing.len() + self.body.len());
+		page.extend_from_slice(OGG_SIGNATURE);
+		page.push(0);
+		page.push(self.flags);
+		page.extend_from_slice(&self.granule.to_le_bytes());
+		page.extend_from_slice(&self.serial.to_le_bytes());
+		page.extend_from_slice(&self.sequence.to_le_bytes());
+		page.extend_from_slice(&[0; 4]);
+		page.push(self.lacing.len() as u8);
+		page.extend_from_slice(&self.lacing);
+		page.extend_from_slice(&self.body);
+		let crc = crc32(&page);
+		page[22..26].copy_from_slice(&crc.to_le_bytes());
+		page
+	}
+
+	// the first packet that starts on this page
+	pub fn first_packet(&self) -> &[u8] {
+		let size = self.lacing.iter().position(|&len| len < 255).map_or(self.lacing.len(), |i| i + 1);
+		let size = self.lacing[..size].iter().map(|&len| len as usize).sum::<usize>();
+		&self.body[..size]
+	}
+
+	// drops the end of a packet begun on an earlier page, for when that page
+	// was not written. false when nothing is left
+	pub fn strip_continuation(&mut self) -> bool {
+		if self.flags & PAGE_CONTINUED == 0 {
+			return true;
+		}
+		self.flags &= !PAGE_CONTINUED;
+		let Some(end) = self.lacing.iter().position(|&len| len < 255) else {
+			return false;
+		};
+		let size = self.lacing[..=end].iter().map(|&len| len as usize).sum::<usize>();
+		self.lacing.drain(..=end);
+		self.body.drain(..size);
+		!self.lacing.is_empty()
+	}
+}
+
+// crc-32 with polynomial 0x04c11db7, msb first, no reflection or final xor
+pub fn crc32(data: &[u8]) -> u32 {
+	data.iter().fold(0u32, |crc, &byte| {
+		(0..8).fold(crc ^ ((byte as u32) << 24), |crc, _| {
+			if crc & 0x8000_0000 != 0 { (crc << 1) ^ 0x04C1_1DB7 } else { crc << 1 }
+		})
+	})
+}
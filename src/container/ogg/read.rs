@@ -0,0 +1,92 @@
+use super::{NO_GRANULE, OGG_SIGNATURE, OggFormat, OggPage, PAGE_FIRST, crc32};
+use crate::core::{Demuxer, Packet, Timebase, log};
+use crate::io::{IoError, IoErrorKind, IoResult, MediaRead, ReadPrimitives};
+
+// the pages of the first logical stream, one packet each with the page
+// serialized as it was read. a packet's pts is the granule the page starts
+// at, which is where the page before it in the stream ended
+pub struct OggReader<R: MediaRead> {
+	reader: R,
+	format: OggFormat,
+	timebase: Timebase,
+	pending: Option<OggPage>,
+	granule: i64,
+	other_streams: bool,
+}
+
+impl<R: MediaRead> OggReader<R> {
+	pub fn new(mut reader: R) -> IoResult<Self> {
+		let page = read_page(&mut reader)?.ok_or_else(|| IoError::invalid_data("empty ogg file"))?;
+		if page.flags & PAGE_FIRST == 0 {
+			return Err(IoError::invalid_data("ogg stream does not start with a first page"));
+		}
+		let format = OggFormat::parse(page.serial, page.first_packet());
+		let timebase = Timebase::new(1, format.granule_rate().max(1));
+		Ok(Self { reader, format, timebase, pending: Some(page), granule: 0, other_streams: false })
+	}
+
+	pub fn format(&self) -> &OggFormat {
+		&self.format
+	}
+
+	pub fn read_page(&mut self) -> IoResult<Option<OggPage>> {
+		if let Some(page) = self.pending.take() {
+			return Ok(Some(page));
+		}
+		while let Some(page) = read_page(&mut self.reader)? {
+			if page.serial == self.format.serial {
+				return Ok(Some(page));
+			}
+			if !self.other_streams {
+				log::warn("ogg", format_args!("only the first logical stream is read"));
+				self.other_streams = true;
+			}
+		}
+		Ok(None)
+	}
+}
+
+fn read_page<R: MediaRead>(reader: &mut R) -> IoResult<Option<OggPage>> {
+	let mut header = [0u8; 27];
+	match reader.read_exact(&mut header) {
+		Ok(()) => {}
+		Err(e) if matches!(e.kind(), IoErrorKind::UnexpectedEof) => return Ok(None),
+		Err(e) => return Err(e),
+	}
+	if &header[..4] != OGG_SIGNATURE {
+		return Err(IoError::invalid_data("no ogg page sync"));
+	}
+	let mut data = header.to_vec();
+	data.resize(27 + header[26] as usize, 0);
+	reader.read_exact(&mut data[27..])?;
+	let size = data[27..].iter().map(|&len| len as usize).sum::<usize>();
+	let start = data.len();
+	data.resize(start + size, 0);
+	reader.read_exact(&mut data[start..])?;
+
+	let crc = u32::from_le_bytes(data[22..26].try_into().unwrap());
+	data[22..26].fill(0);
+	if crc32(&data) != crc {
+		log::warn("ogg", format_args!("page crc mismatch"));
+	}
+	OggPage::parse(&data).map(Some).ok_or_else(|| IoError::invalid_data("malformed ogg page"))
+}
+
+impl<R: MediaRead> Demuxer for OggReader<R> {
+	fn read_packet(&mut self) -> IoResult<Option<Packet>> {
+		let Some(page) = self.read_page()? else {
+			return Ok(None);
+		};
+		let start = self.granule;
+		if page.granule != NO_GRANULE {
+			self.granule = page.granule;
+		}
+		let mut packet = Packet::new(page.to_bytes(), 0, self.timebase).with_pts(start).with_dts(start);
+		packet.keyframe = true;
+		Ok(Some(packet))
+	}
+
+	fn stream_count(&self) -> usize {
+		1
+	}
+}
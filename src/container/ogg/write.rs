@@ -0,0 +1,85 @@
+use super::{NO_GRANULE, OggPage, PAGE_FIRST, PAGE_LAST};
+use crate::core::{Muxer, Packet, log};
+use crate::io::{IoError, IoResult, MediaWrite, WritePrimitives};
+
+// writes the pages of one logical stream, renumbered, with the first and last
+// page flagged as such and granules rebased so they run on from the headers
+// without a jump. a page that starts away from where the previous one ended,
+// because pages before it were cut or repeat audio already written, moves the
+// granules after it by the difference, and the piece of a packet it carries
+// over from an unwritten page is dropped. header pages (granule 0, before any
+// audio) are copied as they are
+pub struct OggWriter<W: MediaWrite> {
+	writer: W,
+	sequence: u32,
+	// the last page is held back so it can be flagged on finalize
+	held: Option<OggPage>,
+	// source granule the next audio page should start at
+	expected: Option<i64>,
+	offset: i64,
+	// a cut happened and no page has picked up since
+	cut: bool,
+	end: Option<i64>,
+}
+
+impl<W: MediaWrite> OggWriter<W> {
+	pub fn new(writer: W) -> Self {
+		Self { writer, sequence: 0, held: None, expected: None, offset: 0, cut: false, end: None }
+	}
+
+	// source granule the audio stops at: a later end on the last page is
+	// trimmed away by the decoder
+	pub fn with_end_granule(mut self, end: i64) -> Self {
+		self.end = Some(end);
+		self
+	}
+
+	// `start` is the source granule the page begins at
+	pub fn write_page(&mut self, mut page: OggPage, start: i64) -> IoResult<()> {
+		if self.expected.is_some() || page.granule > 0 {
+			let expected = self.expected.unwrap_or(0);
+			if start != expected {
+				log::debug("ogg", format_args!("rebased granules by {} after a cut", start - expected));
+				self.offset += start - expected;
+				self.cut = true;
+			}
+			self.expected = Some(if page.granule == NO_GRANULE { start } else { page.granule });
+			if self.cut {
+				if !page.strip_continuation() {
+					return Ok(());
+				}
+				self.cut = false;
+			}
+			if page.granule != NO_GRANULE {
+				page.granule = page.granule.min(self.end.unwrap_or(i64::MAX)) - self.offset;
+			}
+		}
+
+		page.sequence = self.sequence;
+		page.flags &= !(PAGE_FIRST | PAGE_LAST);
+		if self.sequence == 0 {
+			page.flags |= PAGE_FIRST;
+		}
+		self.sequence += 1;
+		if let Some(held) = self.held.replace(page) {
+			self.writer.write_all(&held.to_bytes())?;
+		}
+		Ok(())
+	}
+}
+
+impl<W: MediaWrite> Muxer for OggWriter<W> {
+	fn write_packet(&mut self, packet: Packet) -> IoResult<()> {
+		let page = OggPage::parse(&packet.data)
+			.ok_or_else(|| IoError::invalid_data("ogg packet is not a page"))?;
+		self.write_page(page, packet.pts)
+	}
+
+	fn finalize(&mut self) -> IoResult<()> {
+		if let Some(mut page) = self.held.take() {
+			page.flags |= PAGE_LAST;
+			self.writer.write_all(&page.to_bytes())?;
+		}
+		self.writer.flush()
+	}
+}
@@ -0,0 +1,166 @@
+use super::metadata::{Id3v2Tag, MediaMetadata};
+use crate::io::{IoError, IoResult, MediaRead, MediaSeek, ReadPrimitives, SeekFrom};
+
+// LIST INFO items and the MediaMetadata key each is read into
+const INFO_KEYS: [(&[u8; 4], &str); 9] = [
+	(b"INAM", "title"),
+	(b"IART", "artist"),
+	(b"IPRD", "album"),
+	(b"ICRD", "year"),
+	(b"IGNR", "genre"),
+	(b"ICMT", "comment"),
+	(b"ITRK", "track"),
+	(b"ISFT", "encoder"),
+	(b"ICOP", "copyright"),
+];
+
+// one chunk of a RIFF file (wav, avi) as laid out on disk, whether or not
+// its contents make sense: `form` is the type of a RIFF or LIST, `depth` its
+// nesting, `problem` what is wrong with its header when something is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiffChunk {
+	pub fourcc: [u8; 4],
+	pub form: Option<[u8; 4]>,
+	pub offset: u64,
+	pub size: u32,
+	pub depth: usize,
+	pub problem: Option<String>,
+}
+
+impl RiffChunk {
+	pub fn is_list(&self) -> bool {
+		self.form.is_some()
+	}
+}
+
+// every chunk header in the file, in file order, each list followed by what
+// it holds. nothing is read but the headers, and a header that does not fit
+// is noted and walked past where that is possible rather than refused, since
+// the point is to see why a reader gives up on the file
+pub fn walk<R: MediaRead + MediaSeek>(reader: &mut R) -> IoResult<Vec<RiffChunk>> {
+	let len = reader.seek(SeekFrom::End(0))?;
+	reader.seek(SeekFrom::Start(0))?;
+	let mut signature = [0u8; 4];
+	if len < 12 || reader.read_exact(&mut signature).is_err() || &signature != b"RIFF" {
+		return Err(IoError::invalid_data("not a RIFF file"));
+	}
+	let mut chunks = Vec::new();
+	// an avi past 1 GiB goes on in further RIFF AVIX lists, so the top level
+	// is the whole file
+	walk_range(reader, 0, len, 0, &mut chunks)?;
+	Ok(chunks)
+}
+
+fn walk_range<R: MediaRead + MediaSeek>(
+	reader: &mut R,
+	start: u64,
+	end: u64,
+	depth: usize,
+	chunks: &mut Vec<RiffChunk>,
+) -> IoResult<()> {
+	let mut offset = start;
+	while offset + 8 <= end {
+		reader.seek(SeekFrom::Start(offset))?;
+		let mut fourcc = [0u8; 4];
+		reader.read_exact(&mut fourcc)?;
+		let size = reader.read_u32_le()?;
+		let is_list = matches!(&fourcc, b"RIFF" | b"LIST");
+		// what a writer that never went back to fill in its sizes leaves
+		let size_unset = size == 0 && fourcc == *b"RIFF";
+
+		let mut chunk = RiffChunk { fourcc, form: None, offset, size, depth, problem: None };
+		let mut body_end = offset + 8 + size as u64;
+		if is_list && size < 4 && !size_unset {
+			chunk.problem = Some(format!("a list needs at least 4 bytes for its type, size is {}", size));
+			chunks.push(chunk);
+			offset += 8;
+			continue;
+		}
+		if size_unset {
+			chunk.problem = Some("size is 0, read to the end of the file".to_string());
+			body_end = end;
+		} else if body_end > end {
+			chunk.problem = Some(format!("ends at {}, past the end of its parent at {}", body_end, end));
+			body_end = end;
+		}
+
+		if is_list {
+			let mut form = [0u8; 4];
+			reader.read_exact(&mut form)?;
+			chunk.form = Some(form);
+			chunks.push(chunk);
+			walk_range(reader, offset + 12, body_end, depth + 1, chunks)?;
+		} else {
+			chunks.push(chunk);
+		}
+
+		// chunks start on an even offset, after a pad byte if need be
+		offset = body_end + (body_end - offset) % 2;
+	}
+	if offset < end {
+		chunks.push(RiffChunk {
+			fourcc: *b"????",
+			form: None,
+			offset,
+			size: (end - offset) as u32,
+			depth,
+			problem: Some(format!("{} bytes too short for a chunk header", end - offset)),
+		});
+	}
+	Ok(())
+}
+
+// the tags of a wav or avi: the items of a LIST INFO, and an "id3 " chunk
+// as some taggers add to wav. items with no name of their own keep their
+// fourcc, and where both have a tag the id3 one is taken
+pub fn read_tags<R: MediaRead + MediaSeek>(reader: &mut R) -> IoResult<MediaMetadata> {
+	let chunks = walk(reader)?;
+	let mut metadata = MediaMetadata::new();
+	let mut id3 = None;
+	let mut info_depth = None;
+	for chunk in &chunks {
+		if info_depth.is_some_and(|depth| chunk.depth <= depth) {
+			info_depth = None;
+		}
+		if chunk.form == Some(*b"INFO") {
+			info_depth = Some(chunk.depth);
+			continue;
+		}
+		let in_info = info_depth.is_some();
+		if !in_info && !matches!(&chunk.fourcc, b"id3 " | b"ID3 ") || chunk.problem.is_some() {
+			continue;
+		}
+		reader.seek(SeekFrom::Start(chunk.offset + 8))?;
+		let mut body = vec![0u8; chunk.size as usize];
+		reader.read_exact(&mut body)?;
+		if !in_info {
+			id3 = Id3v2Tag::parse(&body);
+			continue;
+		}
+		// zero terminated, sometimes padded past that
+		let text = body.split(|&b| b == 0).next().unwrap_or(&[]);
+		let key = match INFO_KEYS.iter().find(|(fourcc, _)| **fourcc == chunk.fourcc) {
+			Some((_, key)) => key.to_string(),
+			None => fourcc_text(&chunk.fourcc),
+		};
+		metadata.add_tag(key, String::from_utf8_lossy(text).trim().to_string());
+	}
+	if let Some(id3) = id3 {
+		metadata.tags.extend(id3.to_metadata().tags);
+	}
+	Ok(metadata)
+}
+
+// "LIST", "idx1", and "\x00\x00\x00\x00" for bytes that are not text
+pub fn fourcc_text(fourcc: &[u8; 4]) -> String {
+	fourcc
+		.iter()
+		.map(|&byte| {
+			if byte.is_ascii_graphic() || byte == b' ' {
+				(byte as char).to_string()
+			} else {
+				format!("\\x{:02x}", byte)
+			}
+		})
+		.collect()
+}
@@ -4,6 +4,8 @@ pub mod write;
 pub use read::WavReader;
 pub use write::WavWriter;
 
+use crate::codecs::CodecId;
+
 #[derive(Debug, Clone, Copy)]
 pub struct WavFormat {
 	pub channels: u8,
@@ -19,4 +21,8 @@ impl WavFormat {
 	pub fn bytes_per_frame(&self) -> usize {
 		self.bytes_per_sample() * self.channels as usize
 	}
+
+	pub fn codec_id(&self) -> CodecId {
+		CodecId::pcm_from_bit_depth(self.bit_depth, false).unwrap_or(CodecId::PcmS16Le)
+	}
 }
@@ -1,26 +1,36 @@
 use super::WavFormat;
-use crate::core::{Demuxer, Packet, Timebase};
-use crate::io::{IoError, IoResult, MediaRead, ReadPrimitives};
+use crate::container::fourcc_name;
+use crate::core::{Demuxer, Packet, Timebase, log};
+use crate::io::{IoError, IoResult, MediaRead, MediaSeek, ReadPrimitives, SeekFrom};
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+// the subformat guid after its leading format tag: {xxxx0000-0000-0010-8000-00AA00389B71}
+const KSDATAFORMAT_TAIL: [u8; 14] =
+	[0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71];
 
 pub struct WavReader<R: MediaRead> {
 	reader: R,
 	format: WavFormat,
 	timebase: Timebase,
+	data_size: u64,
 	data_remaining: u64,
-	packet_count: u64,
+	data_offset: u64,
 }
 
 impl<R: MediaRead> WavReader<R> {
 	pub fn new(mut reader: R) -> IoResult<Self> {
-		let format = Self::read_header(&mut reader)?;
-		let (data_size, _) = Self::find_data_chunk(&mut reader)?;
+		let (format, header_size) = Self::read_header(&mut reader)?;
+		let (data_size, data_offset) = Self::find_data_chunk(&mut reader, header_size)?;
 
 		Ok(Self {
 			reader,
 			format,
 			timebase: Timebase::new(1, format.sample_rate),
+			data_size,
 			data_remaining: data_size,
-			packet_count: 0,
+			data_offset,
 		})
 	}
 
@@ -28,7 +38,23 @@ impl<R: MediaRead> WavReader<R> {
 		self.format
 	}
 
-	fn read_header(reader: &mut R) -> IoResult<WavFormat> {
+	// sample frame the next packet starts at
+	pub fn position(&self) -> u64 {
+		(self.data_size - self.data_remaining) / self.format.bytes_per_frame().max(1) as u64
+	}
+
+	// sample frames in the data chunk, as its header gives the size
+	pub fn total_frames(&self) -> u64 {
+		self.data_size / self.format.bytes_per_frame().max(1) as u64
+	}
+
+	// where the first sample of the data chunk is in the file
+	pub fn data_offset(&self) -> u64 {
+		self.data_offset
+	}
+
+	// the format and how many bytes of the file it took to find it
+	fn read_header(reader: &mut R) -> IoResult<(WavFormat, u64)> {
 		let mut buf = [0u8; 12];
 		reader.read_exact(&mut buf)?;
 
@@ -43,6 +69,7 @@ impl<R: MediaRead> WavReader<R> {
 		let channels;
 		let sample_rate;
 		let bit_depth;
+		let mut read = 12u64;
 
 		loop {
 			let mut chunk_header = [0u8; 8];
@@ -52,6 +79,7 @@ impl<R: MediaRead> WavReader<R> {
 			let chunk_size =
 				u32::from_le_bytes([chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]])
 					as usize;
+			read += 8 + chunk_size as u64;
 
 			if chunk_id == b"fmt " {
 				let mut fmt_buf = vec![0u8; chunk_size];
@@ -61,62 +89,115 @@ impl<R: MediaRead> WavReader<R> {
 					return Err(IoError::invalid_data("fmt chunk too small"));
 				}
 
+				let tag = u16::from_le_bytes([fmt_buf[0], fmt_buf[1]]);
+				// WAVE_FORMAT_EXTENSIBLE names the real format in its subformat guid
+				let tag = match tag {
+					WAVE_FORMAT_EXTENSIBLE if chunk_size >= 40 && fmt_buf[26..40] == KSDATAFORMAT_TAIL => {
+						u16::from_le_bytes([fmt_buf[24], fmt_buf[25]])
+					}
+					WAVE_FORMAT_EXTENSIBLE => {
+						return Err(IoError::invalid_data("extensible fmt chunk has no subformat"));
+					}
+					tag => tag,
+				};
+				match tag {
+					WAVE_FORMAT_PCM => {}
+					WAVE_FORMAT_IEEE_FLOAT => {
+						return Err(IoError::unsupported("floating point wav is not supported"));
+					}
+					_ => return Err(IoError::unsupported("only PCM wav is supported")),
+				}
+
 				channels = u16::from_le_bytes([fmt_buf[2], fmt_buf[3]]) as u8;
 				sample_rate = u32::from_le_bytes([fmt_buf[4], fmt_buf[5], fmt_buf[6], fmt_buf[7]]);
 				bit_depth = u16::from_le_bytes([fmt_buf[14], fmt_buf[15]]);
 
-				if bit_depth != 16 {
-					return Err(IoError::invalid_data("only 16-bit PCM supported"));
+				if !matches!(bit_depth, 8 | 16 | 24 | 32) {
+					return Err(IoError::invalid_data("only 8/16/24/32-bit PCM supported"));
 				}
 
 				break;
 			} else {
+				log::debug(
+					"wav",
+					format_args!("skipped {} chunk ({} bytes)", fourcc_name(chunk_id), chunk_size),
+				);
 				let mut skip = vec![0u8; chunk_size];
 				reader.read_exact(&mut skip)?;
 			}
 		}
 
-		Ok(WavFormat { channels, sample_rate, bit_depth })
+		Ok((WavFormat { channels, sample_rate, bit_depth }, read))
 	}
 
-	fn find_data_chunk(reader: &mut R) -> IoResult<(u64, u64)> {
+	// the data chunk's size and where its samples start, `offset` bytes in
+	fn find_data_chunk(reader: &mut R, mut offset: u64) -> IoResult<(u64, u64)> {
 		let mut buf = [0u8; 8];
 		loop {
 			reader.read_exact(&mut buf)?;
+			offset += 8;
 			let chunk_id = &buf[0..4];
 			let chunk_size = u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]) as u64;
 
 			if chunk_id == b"data" {
-				return Ok((chunk_size, 0));
+				return Ok((chunk_size, offset));
 			}
 
 			// skip unknown chunks (with padding for odd sizes)
+			log::debug(
+				"wav",
+				format_args!("skipped {} chunk ({} bytes)", fourcc_name(chunk_id), chunk_size),
+			);
 			let skip_size = chunk_size + (chunk_size % 2);
 			let mut skip_buf = vec![0u8; skip_size as usize];
 			reader.read_exact(&mut skip_buf)?;
+			offset += skip_size;
 		}
 	}
 }
 
+impl<R: MediaRead + MediaSeek> WavReader<R> {
+	// jumps to a sample frame within the data chunk, clamped to its end, and
+	// returns the frame the next packet will start at
+	pub fn seek_to_frame(&mut self, frame: u64) -> IoResult<u64> {
+		let bytes_per_frame = self.format.bytes_per_frame().max(1) as u64;
+		let consumed = self.data_size - self.data_remaining;
+		let target = (frame.saturating_mul(bytes_per_frame))
+			.min(self.data_size / bytes_per_frame * bytes_per_frame);
+		self.reader.seek(SeekFrom::Current(target as i64 - consumed as i64))?;
+		self.data_remaining = self.data_size - target;
+		Ok(self.position())
+	}
+}
+
 impl<R: MediaRead> Demuxer for WavReader<R> {
 	fn read_packet(&mut self) -> IoResult<Option<Packet>> {
 		if self.data_remaining == 0 {
 			return Ok(None);
 		}
 
-		let frame_size = 4096.min(self.data_remaining as usize);
+		// whole sample frames only, so a packet never splits a multichannel frame
+		let bytes_per_frame = self.format.bytes_per_frame().max(1);
+		let frame_size = (4096 / bytes_per_frame).max(1) * bytes_per_frame;
+		let frame_size = frame_size.min(self.data_remaining as usize);
 		let mut buf = vec![0u8; frame_size];
 		let read = self.reader.read(&mut buf)?;
 
 		if read == 0 {
+			// a streamed wav leaves the size at 0xFFFFFFFF and simply ends
+			if self.data_size != u32::MAX as u64 {
+				log::warn(
+					"wav",
+					format_args!("data chunk ends {} bytes short of its declared size", self.data_remaining),
+				);
+			}
 			return Ok(None);
 		}
 
 		buf.truncate(read);
 		self.data_remaining -= read as u64;
 
-		let pts = self.packet_count * read as u64 / self.format.bytes_per_frame() as u64;
-		self.packet_count += 1;
+		let pts = self.position() - (read / bytes_per_frame) as u64;
 
 		Ok(Some(Packet::new(buf, 0, self.timebase).with_pts(pts as i64)))
 	}
@@ -1,24 +1,60 @@
 use super::WavFormat;
+use crate::container::metadata::Id3v2Tag;
 use crate::core::{Muxer, Packet};
-use crate::io::{IoResult, MediaSeek, MediaWrite, SeekFrom, WritePrimitives};
+use crate::io::{IoError, IoResult, MediaSeek, MediaWrite, SeekFrom, WritePrimitives};
 
 pub struct WavWriter<W: MediaWrite + MediaSeek> {
 	writer: W,
+	format: WavFormat,
 	data_size: u32,
+	id3: Option<Id3v2Tag>,
+	// the format a streamed header went out with; None patches the header on finalize
+	streamed: Option<WavFormat>,
 }
 
 impl<W: MediaWrite + MediaSeek> WavWriter<W> {
 	pub fn new(mut writer: W, format: WavFormat) -> IoResult<Self> {
-		Self::write_header(&mut writer, format, 0)?;
-		Ok(Self { writer, data_size: 0 })
+		Self::write_header(&mut writer, format, 0, 0)?;
+		Ok(Self { writer, format, data_size: 0, id3: None, streamed: None })
 	}
 
-	fn write_header(writer: &mut W, format: WavFormat, data_size: u32) -> IoResult<()> {
+	// for outputs that cannot seek back, such as a pipe: the sizes are written
+	// as 0xFFFFFFFF ("until the end of the stream"), as players expect from a
+	// streamed wav, and never patched. the format cannot change afterwards and
+	// there is no room for an id3 chunk
+	pub fn streaming(mut writer: W, format: WavFormat) -> IoResult<Self> {
+		Self::write_header(&mut writer, format, u32::MAX, 0)?;
+		Ok(Self { writer, format, data_size: 0, id3: None, streamed: Some(format) })
+	}
+
+	pub fn format(&self) -> WavFormat {
+		self.format
+	}
+
+	// for transforms that change the channel count or rate: the header is
+	// rewritten with this format on finalize
+	pub fn set_format(&mut self, format: WavFormat) {
+		self.format = format;
+	}
+
+	// appended as an "id3 " chunk after the audio when the file is finalized
+	pub fn set_id3(&mut self, tag: Id3v2Tag) {
+		self.id3 = Some(tag);
+	}
+
+	// `trailing` is the size of any chunks after the data, which the riff size
+	// has to include
+	fn write_header(
+		writer: &mut W,
+		format: WavFormat,
+		data_size: u32,
+		trailing: u32,
+	) -> IoResult<()> {
 		let byte_rate = format.sample_rate * format.bytes_per_frame() as u32;
 		let block_align = format.bytes_per_frame() as u16;
 
 		writer.write_all(b"RIFF")?;
-		writer.write_all(&(36 + data_size).to_le_bytes())?;
+		writer.write_all(&36u32.saturating_add(data_size).saturating_add(trailing).to_le_bytes())?;
 		writer.write_all(b"WAVE")?;
 
 		writer.write_all(b"fmt ")?;
@@ -39,17 +75,54 @@ impl<W: MediaWrite + MediaSeek> WavWriter<W> {
 
 impl<W: MediaWrite + MediaSeek> Muxer for WavWriter<W> {
 	fn write_packet(&mut self, packet: Packet) -> IoResult<()> {
+		if self.streamed.is_some() {
+			return self.writer.write_all(&packet.data);
+		}
+		// the riff sizes are 32 bit; anything past 4 GiB cannot be described
+		let data_size = u32::try_from(packet.size())
+			.ok()
+			.and_then(|size| size.checked_add(self.data_size))
+			.filter(|size| size.checked_add(36).is_some())
+			.ok_or_else(|| IoError::invalid_data("wav data exceeds the 4 GiB riff limit"))?;
 		self.writer.write_all(&packet.data)?;
-		self.data_size += packet.size() as u32;
+		self.data_size = data_size;
 		Ok(())
 	}
 
 	fn finalize(&mut self) -> IoResult<()> {
+		if let Some(header) = self.streamed {
+			if self.id3.is_some() {
+				return Err(IoError::invalid_data("a streamed wav has no room for an id3 chunk"));
+			}
+			let format = self.format;
+			let same = (format.channels, format.sample_rate, format.bit_depth)
+				== (header.channels, header.sample_rate, header.bit_depth);
+			if !same {
+				return Err(IoError::invalid_data("wav format changed after the header was streamed"));
+			}
+			return self.writer.flush();
+		}
+
+		let mut trailing = 0u32;
+		if let Some(tag) = self.id3.take() {
+			let mut chunk = tag.to_bytes();
+			let chunk_size = chunk.len() as u32;
+			if self.data_size % 2 == 1 {
+				self.writer.write_all(&[0])?;
+				trailing += 1;
+			}
+			if chunk.len() % 2 == 1 {
+				chunk.push(0);
+			}
+			self.writer.write_all(b"id3 ")?;
+			self.writer.write_all(&chunk_size.to_le_bytes())?;
+			self.writer.write_all(&chunk)?;
+			trailing += 8 + chunk.len() as u32;
+		}
+
 		let current_pos = self.writer.stream_position()?;
-		self.writer.seek(SeekFrom::Start(4))?;
-		self.writer.write_all(&(36 + self.data_size).to_le_bytes())?;
-		self.writer.seek(SeekFrom::Start(40))?;
-		self.writer.write_all(&self.data_size.to_le_bytes())?;
+		self.writer.seek(SeekFrom::Start(0))?;
+		Self::write_header(&mut self.writer, self.format, self.data_size, trailing)?;
 		self.writer.seek(SeekFrom::Start(current_pos))?;
 		Ok(())
 	}
@@ -4,6 +4,8 @@ pub mod write;
 pub use read::Y4mReader;
 pub use write::Y4mWriter;
 
+use crate::core::VideoFormat;
+
 #[derive(Debug, Clone, Copy)]
 pub struct AspectRatio {
 	pub num: u32,
@@ -57,16 +59,11 @@ pub enum Interlacing {
 
 impl Y4mFormat {
 	pub fn frame_size(&self) -> usize {
-		let luma_size = (self.width * self.height) as usize;
-		let colorspace = self.colorspace.unwrap_or(Colorspace::C420);
-		match colorspace {
-			Colorspace::C420 | Colorspace::C420jpeg | Colorspace::C420paldv | Colorspace::C420mpeg2 => {
-				luma_size + luma_size / 2
-			}
-			Colorspace::C422 => luma_size * 2,
-			Colorspace::C444 => luma_size * 3,
-			Colorspace::Mono => luma_size,
-		}
+		self.video_format().frame_size(self.width, self.height)
+	}
+
+	pub fn video_format(&self) -> VideoFormat {
+		self.colorspace.unwrap_or(Colorspace::C420).video_format()
 	}
 }
 
@@ -85,6 +82,28 @@ impl Default for Y4mFormat {
 }
 
 impl Colorspace {
+	pub fn video_format(&self) -> VideoFormat {
+		match self {
+			Colorspace::C420 | Colorspace::C420jpeg | Colorspace::C420paldv | Colorspace::C420mpeg2 => {
+				VideoFormat::YUV420
+			}
+			Colorspace::C422 => VideoFormat::YUV422,
+			Colorspace::C444 => VideoFormat::YUV444,
+			Colorspace::Mono => VideoFormat::GRAY8,
+		}
+	}
+
+	// the plain tag for a planar format; rgb has none
+	pub fn from_video_format(format: VideoFormat) -> Option<Self> {
+		match format {
+			VideoFormat::YUV420 => Some(Colorspace::C420),
+			VideoFormat::YUV422 => Some(Colorspace::C422),
+			VideoFormat::YUV444 => Some(Colorspace::C444),
+			VideoFormat::GRAY8 => Some(Colorspace::Mono),
+			VideoFormat::RGB24 | VideoFormat::RGBA32 => None,
+		}
+	}
+
 	pub fn as_str(&self) -> &'static str {
 		match self {
 			Colorspace::C420 => "C420",
@@ -1,5 +1,5 @@
 use super::{AspectRatio, Colorspace, Interlacing, Y4mFormat};
-use crate::core::{Demuxer, Packet, Timebase};
+use crate::core::{Demuxer, Packet, Timebase, log};
 use crate::io::{BufferedReader, IoError, IoResult, MediaRead, ReadPrimitives};
 
 pub struct Y4mReader<R: MediaRead> {
@@ -7,22 +7,33 @@ pub struct Y4mReader<R: MediaRead> {
 	format: Y4mFormat,
 	timebase: Timebase,
 	frame_count: u64,
+	header_size: u64,
 }
 
 impl<R: MediaRead> Y4mReader<R> {
 	pub fn new(reader: R) -> IoResult<Self> {
 		let mut buf_reader = BufferedReader::new(reader);
-		let format = Self::read_header(&mut buf_reader)?;
+		let (format, header_size) = Self::read_header(&mut buf_reader)?;
 		let timebase = Timebase::new(format.framerate_den, format.framerate_num);
 
-		Ok(Self { reader: buf_reader, format, timebase, frame_count: 0 })
+		Ok(Self { reader: buf_reader, format, timebase, frame_count: 0, header_size })
 	}
 
 	pub fn format(&self) -> Y4mFormat {
 		self.format.clone()
 	}
 
-	fn read_header(reader: &mut BufferedReader<R>) -> IoResult<Y4mFormat> {
+	// bytes read from the input ahead of the next packet
+	pub fn read_ahead(&self) -> usize {
+		self.reader.buffer().len()
+	}
+
+	// the stream header line with its newline, where the first FRAME starts
+	pub fn header_size(&self) -> u64 {
+		self.header_size
+	}
+
+	fn read_header(reader: &mut BufferedReader<R>) -> IoResult<(Y4mFormat, u64)> {
 		let mut header = Vec::new();
 		loop {
 			let byte = reader.read_u8()?;
@@ -46,31 +57,51 @@ impl<R: MediaRead> Y4mReader<R> {
 				continue;
 			}
 			let (key, value) = param.split_at(1);
+			let ignored = |what: &str| {
+				log::warn("y4m", format_args!("ignored invalid {} '{}' in header", what, param));
+			};
 			match key {
-				"W" => format.width = value.parse().unwrap_or(format.width),
-				"H" => format.height = value.parse().unwrap_or(format.height),
-				"F" => {
-					if let Some((num, den)) = value.split_once(':') {
-						format.framerate_num = num.parse().unwrap_or(30);
-						format.framerate_den = den.parse().unwrap_or(1);
+				"W" => match value.parse() {
+					Ok(width) => format.width = width,
+					Err(_) => ignored("width"),
+				},
+				"H" => match value.parse() {
+					Ok(height) => format.height = height,
+					Err(_) => ignored("height"),
+				},
+				"F" => match value.split_once(':').map(|(num, den)| (num.parse(), den.parse())) {
+					Some((Ok(num), Ok(den))) if num > 0 && den > 0 => {
+						format.framerate_num = num;
+						format.framerate_den = den;
 					}
-				}
+					_ => {
+						format.framerate_num = 30;
+						format.framerate_den = 1;
+						log::warn("y4m", format_args!("invalid frame rate '{}', assuming 30 fps", param));
+					}
+				},
 				"I" => {
 					if let Some(c) = value.chars().next() {
-						format.interlacing = Interlacing::from_char(c).unwrap_or(Interlacing::Progressive);
+						format.interlacing = Interlacing::from_char(c).unwrap_or_else(|| {
+							ignored("interlacing");
+							Interlacing::Progressive
+						});
 					}
 				}
 				"C" => {
-					format.colorspace = Colorspace::from_str(value);
+					format.colorspace = Colorspace::from_str(param);
+					if format.colorspace.is_none() {
+						log::warn("y4m", format_args!("unknown colorspace '{}', assuming 420", param));
+					}
 				}
 				"A" => {
 					format.aspect_ratio = AspectRatio::from_str(value);
 				}
-				_ => {}
+				_ => log::debug("y4m", format_args!("ignored header parameter '{}'", param)),
 			}
 		}
 
-		Ok(format)
+		Ok((format, header.len() as u64 + 1))
 	}
 
 	fn read_frame_header(&mut self) -> IoResult<bool> {
@@ -1,6 +1,6 @@
-use super::Y4mFormat;
-use crate::core::{Muxer, Packet};
-use crate::io::{IoResult, MediaWrite, WritePrimitives};
+use super::{Colorspace, Y4mFormat};
+use crate::core::{FrameVideo, Muxer, Packet, Timebase, VideoFormat};
+use crate::io::{IoError, IoResult, MediaWrite, WritePrimitives};
 
 pub struct Y4mWriter<W: MediaWrite> {
 	writer: W,
@@ -13,6 +13,41 @@ impl<W: MediaWrite> Y4mWriter<W> {
 		Ok(Self { writer, header_written: false, format })
 	}
 
+	// the header follows the first frame, so transforms that resize or convert
+	// the video are announced correctly. the input's colorspace tag is kept
+	// while the pixel format is unchanged. a frame that no longer matches a
+	// header already written is an error, not a corrupt stream
+	pub fn match_frame(&mut self, video: &FrameVideo) -> IoResult<()> {
+		self.match_format(video.width, video.height, video.format)
+	}
+
+	pub fn match_format(&mut self, width: u32, height: u32, format: VideoFormat) -> IoResult<()> {
+		if self.header_written {
+			if (width, height, format)
+				!= (self.format.width, self.format.height, self.format.video_format())
+			{
+				return Err(IoError::invalid_data("video frame does not match the y4m header"));
+			}
+			return Ok(());
+		}
+		if format != self.format.video_format() {
+			let Some(colorspace) = Colorspace::from_video_format(format) else {
+				return Err(IoError::invalid_data("y4m holds planar yuv or gray video only"));
+			};
+			self.format.colorspace = Some(colorspace);
+		}
+		(self.format.width, self.format.height) = (width, height);
+		Ok(())
+	}
+
+	// the frame rate follows the first frame's tick, so a speed or fps change
+	// is what the header announces. later frames cannot change it
+	pub fn match_timebase(&mut self, timebase: Timebase) {
+		if !self.header_written && timebase.num > 0 && timebase.den > 0 {
+			(self.format.framerate_num, self.format.framerate_den) = (timebase.den, timebase.num);
+		}
+	}
+
 	fn write_header(&mut self) -> IoResult<()> {
 		if self.header_written {
 			return Ok(());
@@ -1,6 +1,6 @@
 use crate::core::Timebase;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FrameKind {
 	Audio,
 	Video,
@@ -17,22 +17,257 @@ pub enum VideoFormat {
 	GRAY8,
 }
 
+impl VideoFormat {
+	pub fn has_chroma_planes(&self) -> bool {
+		matches!(self, VideoFormat::YUV420 | VideoFormat::YUV422 | VideoFormat::YUV444)
+	}
+
+	pub fn chroma_subsampling(&self) -> (u32, u32) {
+		match self {
+			VideoFormat::YUV420 => (2, 2),
+			VideoFormat::YUV422 => (2, 1),
+			_ => (1, 1),
+		}
+	}
+
+	pub fn chroma_dimensions(&self, width: u32, height: u32) -> (u32, u32) {
+		if !self.has_chroma_planes() {
+			return (0, 0);
+		}
+		let (sub_x, sub_y) = self.chroma_subsampling();
+		(width / sub_x, height / sub_y)
+	}
+
+	pub fn frame_size(&self, width: u32, height: u32) -> usize {
+		let luma_size = (width * height) as usize;
+		match self {
+			VideoFormat::RGB24 => luma_size * 3,
+			VideoFormat::RGBA32 => luma_size * 4,
+			_ => {
+				let (uv_w, uv_h) = self.chroma_dimensions(width, height);
+				luma_size + 2 * (uv_w * uv_h) as usize
+			}
+		}
+	}
+}
+
+// channel order inside an interleaved frame. multichannel layouts follow the
+// wav (WAVE_FORMAT_EXTENSIBLE) speaker order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelLayout {
+	Mono,
+	Stereo,
+	// FL FR FC LFE BL BR
+	Surround51,
+	Unknown(u8),
+}
+
+impl ChannelLayout {
+	pub fn from_channels(channels: u8) -> Self {
+		match channels {
+			1 => ChannelLayout::Mono,
+			2 => ChannelLayout::Stereo,
+			6 => ChannelLayout::Surround51,
+			n => ChannelLayout::Unknown(n),
+		}
+	}
+
+	pub fn channels(&self) -> u8 {
+		match self {
+			ChannelLayout::Mono => 1,
+			ChannelLayout::Stereo => 2,
+			ChannelLayout::Surround51 => 6,
+			ChannelLayout::Unknown(n) => *n,
+		}
+	}
+}
+
+// how the samples in `FrameAudio::data` are stored. decoders and encoders
+// speak packed s16, or packed f32 (little-endian, full scale at ±1.0) for pcm
+// deeper than 16 bits; between transforms audio stays f32, so a chain of
+// them quantizes once instead of per stage.
+// the planar variants hold one channel after another, `nb_samples` each,
+// instead of interleaving them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleFormat {
+	#[default]
+	S16,
+	F32,
+	S16P,
+	F32P,
+}
+
+impl SampleFormat {
+	pub fn bytes_per_sample(&self) -> usize {
+		match self {
+			SampleFormat::S16 | SampleFormat::S16P => 2,
+			SampleFormat::F32 | SampleFormat::F32P => 4,
+		}
+	}
+
+	pub fn is_planar(&self) -> bool {
+		matches!(self, SampleFormat::S16P | SampleFormat::F32P)
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct FrameAudio {
 	pub data: Vec<u8>,
 	pub sample_rate: u32,
 	pub channels: u8,
+	pub layout: ChannelLayout,
 	pub nb_samples: usize,
+	pub format: SampleFormat,
 }
 impl FrameAudio {
 	pub fn new(data: Vec<u8>, sample_rate: u32, channels: u8) -> Self {
 		let nb_samples = data.len() / (channels as usize);
-		Self { data, sample_rate, channels, nb_samples }
+		let layout = ChannelLayout::from_channels(channels);
+		Self { data, sample_rate, channels, layout, nb_samples, format: SampleFormat::S16 }
+	}
+
+	// interleaved f32 frame, e.g. the output of a transform that builds new audio
+	pub fn from_f32(samples: &[f32], sample_rate: u32, channels: u8) -> Self {
+		let data = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+		let nb_samples = samples.len() / channels.max(1) as usize;
+		Self {
+			data,
+			sample_rate,
+			channels,
+			layout: ChannelLayout::from_channels(channels),
+			nb_samples,
+			format: SampleFormat::F32,
+		}
+	}
+
+	// one vector per channel, all the same length
+	pub fn from_planes_f32(planes: &[Vec<f32>], sample_rate: u32) -> Self {
+		let data = planes.iter().flatten().flat_map(|s| s.to_le_bytes()).collect();
+		Self::planar(data, planes, sample_rate, SampleFormat::F32P)
+	}
+
+	pub fn from_planes_i16(planes: &[Vec<i16>], sample_rate: u32) -> Self {
+		let data = planes.iter().flatten().flat_map(|s| s.to_le_bytes()).collect();
+		Self::planar(data, planes, sample_rate, SampleFormat::S16P)
+	}
+
+	fn planar<T>(data: Vec<u8>, planes: &[Vec<T>], sample_rate: u32, format: SampleFormat) -> Self {
+		let channels = planes.len() as u8;
+		Self {
+			data,
+			sample_rate,
+			channels,
+			layout: ChannelLayout::from_channels(channels),
+			nb_samples: planes.first().map_or(0, Vec::len),
+			format,
+		}
+	}
+
+	// every sample as f32 at ±1.0 full scale, interleaved, whatever the
+	// storage format
+	pub fn samples_f32(&self) -> Vec<f32> {
+		let stored = self.stored_f32();
+		if self.format.is_planar() { interleave(&stored, self.channels) } else { stored }
+	}
+
+	// stores interleaved `samples` back in the frame's own format; s16 rounds
+	// and clips
+	pub fn set_samples_f32(&mut self, samples: &[f32]) {
+		let planar;
+		let samples = if self.format.is_planar() {
+			planar = deinterleave(samples, self.channels).concat();
+			&planar
+		} else {
+			samples
+		};
+		self.data = match self.format {
+			SampleFormat::S16 | SampleFormat::S16P => {
+				samples.iter().flat_map(|&s| f32_to_s16(s).to_le_bytes()).collect()
+			}
+			SampleFormat::F32 | SampleFormat::F32P => {
+				samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+			}
+		};
+	}
+
+	// one vector per channel
+	pub fn planes_f32(&self) -> Vec<Vec<f32>> {
+		let stored = self.stored_f32();
+		if self.format.is_planar() {
+			split_planes(stored, self.channels)
+		} else {
+			deinterleave(&stored, self.channels)
+		}
+	}
+
+	// one vector per channel; s16 storage is read as is, f32 is rounded
+	pub fn planes_i16(&self) -> Vec<Vec<i16>> {
+		match self.format {
+			SampleFormat::S16 | SampleFormat::S16P => {
+				let stored: Vec<i16> =
+					self.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+				if self.format.is_planar() {
+					split_planes(stored, self.channels)
+				} else {
+					deinterleave(&stored, self.channels)
+				}
+			}
+			SampleFormat::F32 | SampleFormat::F32P => self
+				.planes_f32()
+				.into_iter()
+				.map(|plane| plane.into_iter().map(f32_to_s16).collect())
+				.collect(),
+		}
+	}
+
+	fn stored_f32(&self) -> Vec<f32> {
+		match self.format {
+			SampleFormat::S16 | SampleFormat::S16P => self
+				.data
+				.chunks_exact(2)
+				.map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0)
+				.collect(),
+			SampleFormat::F32 | SampleFormat::F32P => {
+				self.data.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()
+			}
+		}
+	}
+
+	pub fn convert(&mut self, format: SampleFormat) {
+		if self.format != format {
+			let samples = self.samples_f32();
+			self.format = format;
+			self.set_samples_f32(&samples);
+		}
 	}
 	pub fn with_nb_samples(mut self, nb_samples: usize) -> Self {
 		self.nb_samples = nb_samples;
 		self
 	}
+	pub fn with_layout(mut self, layout: ChannelLayout) -> Self {
+		self.layout = layout;
+		self
+	}
+}
+
+fn f32_to_s16(sample: f32) -> i16 {
+	(sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+}
+
+fn deinterleave<T: Copy>(samples: &[T], channels: u8) -> Vec<Vec<T>> {
+	let channels = channels.max(1) as usize;
+	(0..channels).map(|ch| samples.iter().skip(ch).step_by(channels).copied().collect()).collect()
+}
+
+fn interleave<T: Copy>(planar: &[T], channels: u8) -> Vec<T> {
+	let channels = channels.max(1) as usize;
+	let len = planar.len() / channels;
+	(0..len * channels).map(|idx| planar[(idx % channels) * len + idx / channels]).collect()
+}
+
+fn split_planes<T: Copy>(planar: Vec<T>, channels: u8) -> Vec<Vec<T>> {
+	let len = planar.len() / channels.max(1) as usize;
+	planar.chunks(len.max(1)).map(<[T]>::to_vec).collect()
 }
 #[derive(Debug, Clone)]
 pub struct FrameVideo {
@@ -54,21 +289,99 @@ pub enum FrameData {
 	// Caption(Vec<u8>),
 }
 
+// a value one transform leaves on a frame for whatever reads it later
+#[derive(Debug, Clone, PartialEq)]
+pub enum SideData {
+	Number(f64),
+	Text(String),
+	// x, y, width, height in luma pixels
+	Region(u32, u32, u32, u32),
+}
+
+// side data by name, in the order it was first set, e.g. the "cropdetect"
+// region or the "silence_start" time. setting a name again replaces its value
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+	entries: Vec<(String, SideData)>,
+}
+
+impl Metadata {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn set(&mut self, key: &str, value: SideData) {
+		match self.entries.iter_mut().find(|(name, _)| name == key) {
+			Some((_, existing)) => *existing = value,
+			None => self.entries.push((key.to_string(), value)),
+		}
+	}
+
+	pub fn get(&self, key: &str) -> Option<&SideData> {
+		self.entries.iter().find(|(name, _)| name == key).map(|(_, value)| value)
+	}
+
+	pub fn number(&self, key: &str) -> Option<f64> {
+		match self.get(key) {
+			Some(SideData::Number(value)) => Some(*value),
+			_ => None,
+		}
+	}
+
+	pub fn region(&self, key: &str) -> Option<(u32, u32, u32, u32)> {
+		match self.get(key) {
+			Some(SideData::Region(x, y, width, height)) => Some((*x, *y, *width, *height)),
+			_ => None,
+		}
+	}
+
+	pub fn remove(&mut self, key: &str) -> Option<SideData> {
+		let index = self.entries.iter().position(|(name, _)| name == key)?;
+		Some(self.entries.remove(index).1)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &SideData)> {
+		self.entries.iter().map(|(name, value)| (name.as_str(), value))
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	// adds the entries of `earlier` this one does not set itself
+	pub fn inherit(&mut self, earlier: Metadata) {
+		for (key, value) in earlier.entries {
+			if self.get(&key).is_none() {
+				self.entries.push((key, value));
+			}
+		}
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct Frame {
 	pub pts: i64,
 	pub timebase: Timebase,
 	pub stream_index: usize,
 	pub data: FrameData,
+	pub metadata: Metadata,
 }
 
 impl Frame {
 	pub fn new_audio(audio: FrameAudio, timebase: Timebase, stream_index: usize) -> Self {
-		Self { pts: 0, timebase, stream_index, data: FrameData::Audio(audio) }
+		Self::new(FrameData::Audio(audio), timebase, stream_index)
 	}
 
 	pub fn new_video(video: FrameVideo, timebase: Timebase, stream_index: usize) -> Self {
-		Self { pts: 0, timebase, stream_index, data: FrameData::Video(video) }
+		Self::new(FrameData::Video(video), timebase, stream_index)
+	}
+
+	fn new(data: FrameData, timebase: Timebase, stream_index: usize) -> Self {
+		Self { pts: 0, timebase, stream_index, data, metadata: Metadata::new() }
 	}
 
 	pub fn with_pts(mut self, pts: i64) -> Self {
@@ -76,6 +389,11 @@ impl Frame {
 		self
 	}
 
+	pub fn with_metadata(mut self, key: &str, value: SideData) -> Self {
+		self.metadata.set(key, value);
+		self
+	}
+
 	pub fn size(&self) -> usize {
 		match &self.data {
 			FrameData::Audio(a) => a.data.len(),
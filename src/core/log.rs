@@ -0,0 +1,107 @@
+use core::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// a small stderr logger for the things readers, writers and codecs work around
+// instead of failing: skipped chunks, fallback header values, truncated data.
+// the level is process wide, set once by the front end; messages are passed as
+// `format_args!` so a disabled level costs a comparison and nothing else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+	Off,
+	Error,
+	Warn,
+	Info,
+	Debug,
+	Trace,
+}
+
+impl LogLevel {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"off" | "quiet" | "none" => Some(LogLevel::Off),
+			"error" => Some(LogLevel::Error),
+			"warn" | "warning" => Some(LogLevel::Warn),
+			"info" => Some(LogLevel::Info),
+			"debug" => Some(LogLevel::Debug),
+			"trace" => Some(LogLevel::Trace),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			LogLevel::Off => "off",
+			LogLevel::Error => "error",
+			LogLevel::Warn => "warning",
+			LogLevel::Info => "info",
+			LogLevel::Debug => "debug",
+			LogLevel::Trace => "trace",
+		}
+	}
+
+	// each -v past the default warnings shows one more level
+	pub fn from_verbosity(count: u8) -> Self {
+		match count {
+			0 => LogLevel::Warn,
+			1 => LogLevel::Info,
+			2 => LogLevel::Debug,
+			_ => LogLevel::Trace,
+		}
+	}
+
+	fn from_u8(value: u8) -> Self {
+		match value {
+			0 => LogLevel::Off,
+			1 => LogLevel::Error,
+			2 => LogLevel::Warn,
+			3 => LogLevel::Info,
+			4 => LogLevel::Debug,
+			_ => LogLevel::Trace,
+		}
+	}
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Warn as u8);
+
+pub fn set_level(level: LogLevel) {
+	LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> LogLevel {
+	LogLevel::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+	level != LogLevel::Off && level <= self::level()
+}
+
+// "warning: [wav] skipped 'LIST' chunk (26 bytes)"
+pub fn format_record(level: LogLevel, target: &str, args: fmt::Arguments) -> String {
+	format!("{}: [{}] {}", level.name(), target, args)
+}
+
+pub fn log(level: LogLevel, target: &str, args: fmt::Arguments) {
+	if enabled(level) {
+		eprintln!("{}", format_record(level, target, args));
+	}
+}
+
+pub fn error(target: &str, args: fmt::Arguments) {
+	log(LogLevel::Error, target, args);
+}
+
+pub fn warn(target: &str, args: fmt::Arguments) {
+	log(LogLevel::Warn, target, args);
+}
+
+pub fn info(target: &str, args: fmt::Arguments) {
+	log(LogLevel::Info, target, args);
+}
+
+pub fn debug(target: &str, args: fmt::Arguments) {
+	log(LogLevel::Debug, target, args);
+}
+
+pub fn trace(target: &str, args: fmt::Arguments) {
+	log(LogLevel::Trace, target, args);
+}
@@ -1,9 +1,14 @@
 pub mod frame;
+pub mod log;
 pub mod packet;
 pub mod time;
 pub mod traits;
 
-pub use frame::{Frame, FrameAudio, FrameData, FrameKind, FrameVideo, VideoFormat};
+pub use frame::{
+	ChannelLayout, Frame, FrameAudio, FrameData, FrameKind, FrameVideo, Metadata, SampleFormat,
+	SideData, VideoFormat,
+};
+pub use log::LogLevel;
 pub use packet::Packet;
-pub use time::Timebase;
-pub use traits::{Decoder, Demuxer, Encoder, Muxer, Transform};
+pub use time::{Timebase, parse_duration, parse_timestamp};
+pub use traits::{Decoder, Demuxer, Encoder, Muxer, StreamFormat, Transform};
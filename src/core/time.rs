@@ -18,3 +18,39 @@ impl Timebase {
 		((seconds * self.den as f64) / self.num as f64) as i64
 	}
 }
+
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+	let text = text.trim();
+	if text.is_empty() {
+		return None;
+	}
+
+	let (negative, text) = match text.strip_prefix('-') {
+		Some(rest) => (true, rest),
+		None => (false, text),
+	};
+
+	let mut seconds = 0.0;
+	for part in text.split(':') {
+		let value: f64 = part.parse().ok()?;
+		if !value.is_finite() || value < 0.0 {
+			return None;
+		}
+		seconds = seconds * 60.0 + value;
+	}
+
+	Some(if negative { -seconds } else { seconds })
+}
+
+// "200ms", "1.5s" or anything parse_timestamp accepts
+pub fn parse_duration(text: &str) -> Option<f64> {
+	let text = text.trim();
+	let seconds = if let Some(ms) = text.strip_suffix("ms") {
+		ms.trim().parse::<f64>().ok()? / 1000.0
+	} else if let Some(secs) = text.strip_suffix('s') {
+		secs.trim().parse::<f64>().ok()?
+	} else {
+		parse_timestamp(text)?
+	};
+	(seconds.is_finite() && seconds >= 0.0).then_some(seconds)
+}
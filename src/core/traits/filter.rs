@@ -1,7 +1,37 @@
-use crate::core::Frame;
+use crate::core::{Frame, VideoFormat};
 use crate::io::IoResult;
 
+// what frames look like between two transforms, so a chain can be checked and
+// the output header written before the first frame goes through
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamFormat {
+	Audio { sample_rate: u32, channels: u8 },
+	Video { width: u32, height: u32, format: VideoFormat },
+}
+
 pub trait Transform: Send {
 	fn apply(&mut self, frame: Frame) -> IoResult<Frame>;
 	fn name(&self) -> &'static str;
+
+	// called once after the last frame; analysis transforms report here
+	fn finish(&mut self) -> IoResult<()> {
+		Ok(())
+	}
+
+	// true for transforms that handle `SampleFormat::F32` frames; the chain
+	// converts to s16 before any other transform
+	fn supports_f32(&self) -> bool {
+		false
+	}
+
+	// hands back whatever a delaying transform still holds once the input ends
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		Ok(None)
+	}
+
+	// the format frames leave in given the one they arrive in; an error means
+	// no frame of that format can get through. most transforms keep the format
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		Ok(input)
+	}
 }
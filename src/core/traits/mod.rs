@@ -7,5 +7,5 @@ pub mod mux;
 pub use decode::Decoder;
 pub use demux::Demuxer;
 pub use encode::Encoder;
-pub use filter::Transform;
+pub use filter::{StreamFormat, Transform};
 pub use mux::Muxer;
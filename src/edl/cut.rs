@@ -0,0 +1,151 @@
+use super::EditDecisionList;
+use crate::core::{Frame, FrameData, SampleFormat};
+
+// ranges are applied in source order: the cutter sees frames as they are
+// decoded, so it cannot reorder entries the way a non-linear editor would.
+pub struct EdlCutter {
+	ranges: Vec<(f64, f64)>,
+	source_samples: u64,
+	output_samples: u64,
+	output_frames: i64,
+	position: f64,
+	fade: f64,
+	// where the first frame sits in the source, for inputs opened past the start
+	offset: f64,
+}
+
+impl EdlCutter {
+	pub fn new(edl: &EditDecisionList) -> Self {
+		let mut ranges: Vec<(f64, f64)> = edl.entries.iter().map(|e| (e.start, e.end)).collect();
+		ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+		Self {
+			ranges,
+			source_samples: 0,
+			output_samples: 0,
+			output_frames: 0,
+			position: 0.0,
+			fade: 0.0,
+			offset: 0.0,
+		}
+	}
+
+	// fade audio in and out at every range boundary so the joins do not click
+	pub fn with_fade(mut self, seconds: f64) -> Self {
+		self.fade = seconds.max(0.0);
+		self
+	}
+
+	// the input was seeked to `seconds` before the first frame; audio sample
+	// counting starts there instead of at zero
+	pub fn starting_at(mut self, seconds: f64) -> Self {
+		self.offset = seconds.max(0.0);
+		self
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.ranges.last().is_none_or(|&(_, end)| self.position >= end)
+	}
+
+	pub fn cut(&mut self, frame: Frame) -> Option<Frame> {
+		match &frame.data {
+			FrameData::Audio(_) => self.cut_audio(frame),
+			FrameData::Video(_) => self.cut_video(frame),
+		}
+	}
+
+	fn cut_audio(&mut self, frame: Frame) -> Option<Frame> {
+		let mut audio = frame.audio()?.clone();
+		// ranges are cut out of interleaved samples, s16 or f32 as they came
+		match audio.format {
+			SampleFormat::S16P => audio.convert(SampleFormat::S16),
+			SampleFormat::F32P => audio.convert(SampleFormat::F32),
+			_ => {}
+		}
+		let channels = audio.channels.max(1) as usize;
+		let sample_rate = audio.sample_rate as f64;
+		let bytes_per_frame = channels * audio.format.bytes_per_sample();
+		let nb_samples = (audio.data.len() / bytes_per_frame) as u64;
+
+		let frame_start = self.source_samples + (self.offset * sample_rate).round() as u64;
+		let frame_end = frame_start + nb_samples;
+		self.source_samples += nb_samples;
+		self.position = frame_end as f64 / sample_rate;
+
+		let mut data = Vec::new();
+		for &(start, end) in &self.ranges {
+			let range_start = ((start * sample_rate).round() as u64).max(frame_start);
+			let range_end = ((end * sample_rate).round() as u64).min(frame_end);
+			if range_start >= range_end {
+				continue;
+			}
+			let from = (range_start - frame_start) as usize * bytes_per_frame;
+			let to = (range_end - frame_start) as usize * bytes_per_frame;
+			let offset = data.len();
+			data.extend_from_slice(&audio.data[from..to]);
+
+			if self.fade > 0.0 {
+				let bounds = (start * sample_rate, end * sample_rate);
+				let fade_samples = (self.fade * sample_rate).min((bounds.1 - bounds.0) / 2.0);
+				let layout = (audio.format, channels);
+				apply_fade(&mut data[offset..], layout, range_start, bounds, fade_samples);
+			}
+		}
+
+		if data.is_empty() {
+			return None;
+		}
+
+		let kept = (data.len() / bytes_per_frame) as u64;
+		audio.data = data;
+		audio.nb_samples = kept as usize;
+
+		let pts = frame.timebase.from_seconds(self.output_samples as f64 / sample_rate);
+		self.output_samples += kept;
+		Some(frame.with_data(FrameData::Audio(audio)).with_pts(pts))
+	}
+
+	fn cut_video(&mut self, frame: Frame) -> Option<Frame> {
+		let seconds = frame.timebase.to_seconds(frame.pts);
+		self.position = seconds;
+
+		let keep = self.ranges.iter().any(|&(start, end)| seconds >= start && seconds < end);
+		if !keep {
+			return None;
+		}
+
+		let pts = self.output_frames;
+		self.output_frames += 1;
+		Some(frame.with_pts(pts))
+	}
+}
+
+// gain ramps up over the first `fade_samples` of a range and down over its last
+fn apply_fade(
+	data: &mut [u8],
+	(format, channels): (SampleFormat, usize),
+	first: u64,
+	bounds: (f64, f64),
+	fade_samples: f64,
+) {
+	if fade_samples < 1.0 {
+		return;
+	}
+	let sample_bytes = format.bytes_per_sample();
+	for (idx, sample_frame) in data.chunks_exact_mut(channels * sample_bytes).enumerate() {
+		let position = (first + idx as u64) as f64;
+		let gain = ((position - bounds.0) / fade_samples).min((bounds.1 - position) / fade_samples);
+		if gain >= 1.0 {
+			continue;
+		}
+		let gain = gain.max(0.0);
+		for sample in sample_frame.chunks_exact_mut(sample_bytes) {
+			if sample_bytes == 4 {
+				let value = f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]) as f64;
+				sample.copy_from_slice(&((value * gain) as f32).to_le_bytes());
+			} else {
+				let value = i16::from_le_bytes([sample[0], sample[1]]) as f64 * gain;
+				sample.copy_from_slice(&(value.round() as i16).to_le_bytes());
+			}
+		}
+	}
+}
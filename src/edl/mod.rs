@@ -0,0 +1,219 @@
+pub mod cut;
+
+pub use cut::EdlCutter;
+
+use crate::container::metadata::{Chapter, MediaMetadata};
+use crate::core::parse_timestamp;
+use crate::io::json::{self, JsonValue};
+use crate::io::{IoError, IoResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdlEntry {
+	pub start: f64,
+	pub end: f64,
+	pub label: Option<String>,
+}
+
+impl EdlEntry {
+	pub fn new(start: f64, end: f64) -> Self {
+		Self { start, end, label: None }
+	}
+
+	pub fn with_label(mut self, label: impl Into<String>) -> Self {
+		self.label = Some(label.into());
+		self
+	}
+
+	pub fn duration(&self) -> f64 {
+		(self.end - self.start).max(0.0)
+	}
+}
+
+/// Ordered list of time ranges to keep from a source; rendering the list
+/// cuts each range out of the source and joins them back to back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditDecisionList {
+	pub source: Option<String>,
+	pub entries: Vec<EdlEntry>,
+}
+
+/// Implemented by analysis passes whose results can be replayed as cuts.
+pub trait EdlSource {
+	fn edit_decisions(&self) -> EditDecisionList;
+}
+
+impl EditDecisionList {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_source(mut self, source: impl Into<String>) -> Self {
+		self.source = Some(source.into());
+		self
+	}
+
+	pub fn add(&mut self, entry: EdlEntry) {
+		self.entries.push(entry);
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn total_duration(&self) -> f64 {
+		self.entries.iter().map(EdlEntry::duration).sum()
+	}
+
+	pub fn from_chapters(chapters: &[Chapter]) -> Self {
+		let mut edl = Self::new();
+		for chapter in chapters {
+			let mut entry = EdlEntry::new(chapter.start_seconds(), chapter.end_seconds());
+			entry.label = chapter.title.clone();
+			edl.add(entry);
+		}
+		edl
+	}
+
+	/// Turns a list of ranges to drop (e.g. silences) into the ranges to keep.
+	pub fn from_removals(removals: &[(f64, f64)], duration: f64) -> Self {
+		let mut sorted = removals.to_vec();
+		sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		let mut edl = Self::new();
+		let mut cursor = 0.0;
+		for (start, end) in sorted {
+			if start > cursor {
+				edl.add(EdlEntry::new(cursor, start.min(duration)));
+			}
+			cursor = cursor.max(end);
+		}
+		if cursor < duration {
+			edl.add(EdlEntry::new(cursor, duration));
+		}
+		edl
+	}
+
+	pub fn parse(text: &str) -> IoResult<Self> {
+		if text.trim_start().starts_with('{') {
+			return Self::parse_json(text);
+		}
+		Self::parse_text(text)
+	}
+
+	pub fn load(path: &str) -> IoResult<Self> {
+		let text = std::fs::read_to_string(path)?;
+		Self::parse(&text)
+	}
+
+	pub fn save(&self, path: &str) -> IoResult<()> {
+		let is_json = path.to_lowercase().ends_with(".json");
+		let text = if is_json { self.to_json() } else { self.to_text() };
+		std::fs::write(path, text)?;
+		Ok(())
+	}
+
+	fn parse_text(text: &str) -> IoResult<Self> {
+		let mut edl = Self::new();
+
+		for line in text.lines() {
+			let line = line.trim();
+			if line.is_empty() {
+				continue;
+			}
+			if let Some(source) = line.strip_prefix("# source:") {
+				edl.source = Some(source.trim().to_string());
+				continue;
+			}
+			if line.starts_with('#') {
+				continue;
+			}
+
+			let mut parts = line.splitn(3, char::is_whitespace);
+			let start = parts.next().and_then(parse_timestamp);
+			let end = parts.next().and_then(parse_timestamp);
+			let (Some(start), Some(end)) = (start, end) else {
+				return Err(IoError::invalid_data("invalid EDL line, expected: <start> <end> [label]"));
+			};
+			if end < start {
+				return Err(IoError::invalid_data("EDL entry ends before it starts"));
+			}
+
+			let mut entry = EdlEntry::new(start, end);
+			if let Some(label) = parts.next().map(str::trim).filter(|l| !l.is_empty()) {
+				entry.label = Some(label.to_string());
+			}
+			edl.add(entry);
+		}
+
+		Ok(edl)
+	}
+
+	fn parse_json(text: &str) -> IoResult<Self> {
+		let root = json::parse(text)?;
+		let mut edl = Self::new();
+		edl.source = root.get("source").and_then(JsonValue::as_str).map(str::to_string);
+
+		let entries = root
+			.get("entries")
+			.and_then(JsonValue::as_array)
+			.ok_or_else(|| IoError::invalid_data("EDL JSON requires an 'entries' array"))?;
+
+		for item in entries {
+			let start = item.get("start").and_then(JsonValue::as_f64);
+			let end = item.get("end").and_then(JsonValue::as_f64);
+			let (Some(start), Some(end)) = (start, end) else {
+				return Err(IoError::invalid_data("EDL JSON entry requires numeric 'start' and 'end'"));
+			};
+			if end < start {
+				return Err(IoError::invalid_data("EDL entry ends before it starts"));
+			}
+			let mut entry = EdlEntry::new(start, end);
+			entry.label = item.get("label").and_then(JsonValue::as_str).map(str::to_string);
+			edl.add(entry);
+		}
+
+		Ok(edl)
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut out = String::new();
+		if let Some(source) = &self.source {
+			out.push_str(&format!("# source: {}\n", source));
+		}
+		for entry in &self.entries {
+			out.push_str(&format!("{:.6} {:.6}", entry.start, entry.end));
+			if let Some(label) = &entry.label {
+				out.push(' ');
+				out.push_str(label);
+			}
+			out.push('\n');
+		}
+		out
+	}
+
+	pub fn to_json(&self) -> String {
+		let mut out = String::from("{");
+		if let Some(source) = &self.source {
+			out.push_str(&format!("\"source\":\"{}\",", json::escape(source)));
+		}
+		out.push_str("\"entries\":[");
+		for (idx, entry) in self.entries.iter().enumerate() {
+			if idx > 0 {
+				out.push(',');
+			}
+			out.push_str(&format!("{{\"start\":{:.6},\"end\":{:.6}", entry.start, entry.end));
+			if let Some(label) = &entry.label {
+				out.push_str(&format!(",\"label\":\"{}\"", json::escape(label)));
+			}
+			out.push('}');
+		}
+		out.push_str("]}");
+		out
+	}
+}
+
+impl EdlSource for MediaMetadata {
+	fn edit_decisions(&self) -> EditDecisionList {
+		EditDecisionList::from_chapters(&self.chapters)
+	}
+}
@@ -0,0 +1,95 @@
+pub mod png;
+
+use crate::core::{FrameVideo, VideoFormat};
+use crate::io::{IoError, IoResult};
+use crate::transform::video::colorspace::{ColorMatrix, to_rgb24};
+
+// packed 8-bit rgb, row after row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage {
+	pub width: u32,
+	pub height: u32,
+	pub data: Vec<u8>,
+}
+
+impl RgbImage {
+	pub fn new(width: u32, height: u32, data: Vec<u8>) -> Self {
+		Self { width, height, data }
+	}
+
+	pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+		let idx = ((y * self.width + x) * 3) as usize;
+		[self.data[idx], self.data[idx + 1], self.data[idx + 2]]
+	}
+
+	pub fn from_frame(frame: &FrameVideo) -> IoResult<Self> {
+		let (width, height) = (frame.width, frame.height);
+		if frame.data.len() < frame.format.frame_size(width, height) {
+			return Err(IoError::invalid_data("video frame is smaller than its format"));
+		}
+
+		let pixels = (width * height) as usize;
+		let data = match frame.format {
+			VideoFormat::RGB24 => frame.data[..pixels * 3].to_vec(),
+			VideoFormat::RGBA32 => {
+				frame.data.chunks_exact(4).take(pixels).flat_map(|p| [p[0], p[1], p[2]]).collect()
+			}
+			VideoFormat::GRAY8 => frame.data[..pixels].iter().flat_map(|&g| [g, g, g]).collect(),
+			VideoFormat::YUV420 | VideoFormat::YUV422 | VideoFormat::YUV444 => {
+				to_rgb24(frame, ColorMatrix::Bt601)?.data
+			}
+		};
+		Ok(Self { width, height, data })
+	}
+
+	// bilinear
+	pub fn resize(&self, width: u32, height: u32) -> Self {
+		if width == self.width && height == self.height {
+			return self.clone();
+		}
+		let mut data = Vec::with_capacity((width * height * 3) as usize);
+		let scale_x = self.width as f32 / width.max(1) as f32;
+		let scale_y = self.height as f32 / height.max(1) as f32;
+
+		for y in 0..height {
+			let src_y = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (self.height - 1) as f32);
+			let (y0, fy) = (src_y as u32, src_y.fract());
+			let y1 = (y0 + 1).min(self.height - 1);
+			for x in 0..width {
+				let src_x = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (self.width - 1) as f32);
+				let (x0, fx) = (src_x as u32, src_x.fract());
+				let x1 = (x0 + 1).min(self.width - 1);
+				let (a, b) = (self.pixel(x0, y0), self.pixel(x1, y0));
+				let (c, d) = (self.pixel(x0, y1), self.pixel(x1, y1));
+				for ch in 0..3 {
+					let top = a[ch] as f32 + (b[ch] as f32 - a[ch] as f32) * fx;
+					let bottom = c[ch] as f32 + (d[ch] as f32 - c[ch] as f32) * fx;
+					data.push((top + (bottom - top) * fy).round() as u8);
+				}
+			}
+		}
+		Self { width, height, data }
+	}
+
+	// keeps the middle `width` x `height`, clamped to the image
+	pub fn center_crop(&self, width: u32, height: u32) -> Self {
+		let width = width.min(self.width);
+		let height = height.min(self.height);
+		let x = (self.width - width) / 2;
+		let y = (self.height - height) / 2;
+
+		let mut data = Vec::with_capacity((width * height * 3) as usize);
+		for row in y..y + height {
+			let start = ((row * self.width + x) * 3) as usize;
+			data.extend_from_slice(&self.data[start..start + (width * 3) as usize]);
+		}
+		Self { width, height, data }
+	}
+
+	// binary ppm (P6)
+	pub fn to_ppm(&self) -> Vec<u8> {
+		let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+		out.extend_from_slice(&self.data);
+		out
+	}
+}
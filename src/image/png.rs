@@ -0,0 +1,74 @@
+use super::RgbImage;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+// the largest payload a stored deflate block can hold
+const MAX_STORED_BLOCK: usize = 65535;
+
+// 8-bit truecolor png. the image data is zlib framed but not compressed
+// (stored deflate blocks), which every decoder accepts and needs no tables.
+pub fn encode(image: &RgbImage) -> Vec<u8> {
+	let mut out = SIGNATURE.to_vec();
+
+	let mut header = Vec::with_capacity(13);
+	header.extend_from_slice(&image.width.to_be_bytes());
+	header.extend_from_slice(&image.height.to_be_bytes());
+	header.extend_from_slice(&[8, 2, 0, 0, 0]);
+	write_chunk(&mut out, b"IHDR", &header);
+
+	let stride = image.width as usize * 3;
+	let mut raw = Vec::with_capacity((stride + 1) * image.height as usize);
+	for row in image.data.chunks_exact(stride.max(1)).take(image.height as usize) {
+		raw.push(0);
+		raw.extend_from_slice(row);
+	}
+	write_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+	write_chunk(&mut out, b"IEND", &[]);
+	out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+	out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+	let start = out.len();
+	out.extend_from_slice(kind);
+	out.extend_from_slice(data);
+	let crc = crc32(&out[start..]);
+	out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+	let mut out = vec![0x78, 0x01];
+	let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+	if blocks.peek().is_none() {
+		out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+	}
+	while let Some(block) = blocks.next() {
+		let last = blocks.peek().is_none();
+		out.push(last as u8);
+		let len = block.len() as u16;
+		out.extend_from_slice(&len.to_le_bytes());
+		out.extend_from_slice(&(!len).to_le_bytes());
+		out.extend_from_slice(block);
+	}
+	out.extend_from_slice(&adler32(data).to_be_bytes());
+	out
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+	let mut crc = 0xffff_ffffu32;
+	for &byte in data {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+	let (mut a, mut b) = (1u32, 0u32);
+	for &byte in data {
+		a = (a + byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+	(b << 16) | a
+}
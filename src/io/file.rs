@@ -0,0 +1,57 @@
+use super::{
+	IoError, IoResult, MediaRead, MediaSeek, MediaWrite, PathAccess, PathPolicy, SeekFrom,
+};
+use std::fs::File;
+use std::path::Path;
+
+pub struct FileAdapter {
+	file: File,
+}
+
+impl FileAdapter {
+	pub fn open(path: &str) -> IoResult<Self> {
+		let file = File::open(path)?;
+		Ok(Self { file })
+	}
+
+	pub fn create(path: &str) -> IoResult<Self> {
+		let file = File::create(path)?;
+		Ok(Self { file })
+	}
+
+	pub fn open_with(path: &str, policy: &dyn PathPolicy) -> IoResult<Self> {
+		policy.check(Path::new(path), PathAccess::Read)?;
+		Self::open(path)
+	}
+
+	pub fn create_with(path: &str, policy: &dyn PathPolicy) -> IoResult<Self> {
+		policy.check(Path::new(path), PathAccess::Write)?;
+		Self::create(path)
+	}
+}
+
+impl MediaRead for FileAdapter {
+	fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+		use std::io::Read;
+		self.file.read(buf).map_err(IoError::from)
+	}
+}
+
+impl MediaWrite for FileAdapter {
+	fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+		use std::io::Write;
+		self.file.write(buf).map_err(IoError::from)
+	}
+
+	fn flush(&mut self) -> IoResult<()> {
+		use std::io::Write;
+		self.file.flush().map_err(IoError::from)
+	}
+}
+
+impl MediaSeek for FileAdapter {
+	fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+		use std::io::Seek;
+		self.file.seek(pos.into()).map_err(IoError::from)
+	}
+}
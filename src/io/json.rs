@@ -0,0 +1,228 @@
+use crate::io::{IoError, IoResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<JsonValue>),
+	Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+	pub fn get(&self, key: &str) -> Option<&JsonValue> {
+		match self {
+			JsonValue::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		if let JsonValue::String(s) = self { Some(s) } else { None }
+	}
+
+	pub fn as_f64(&self) -> Option<f64> {
+		if let JsonValue::Number(n) = self { Some(*n) } else { None }
+	}
+
+	pub fn as_bool(&self) -> Option<bool> {
+		if let JsonValue::Bool(b) = self { Some(*b) } else { None }
+	}
+
+	pub fn as_array(&self) -> Option<&[JsonValue]> {
+		if let JsonValue::Array(items) = self { Some(items) } else { None }
+	}
+
+	pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+		if let JsonValue::Object(fields) = self { Some(fields) } else { None }
+	}
+
+	pub fn is_null(&self) -> bool {
+		matches!(self, JsonValue::Null)
+	}
+}
+
+pub fn parse(text: &str) -> IoResult<JsonValue> {
+	let mut parser = Parser { bytes: text.as_bytes(), pos: 0 };
+	let value = parser.parse_value()?;
+	parser.skip_whitespace();
+	if parser.pos != parser.bytes.len() {
+		return Err(IoError::invalid_data("trailing characters after JSON value"));
+	}
+	Ok(value)
+}
+
+pub fn escape(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+			c => out.push(c),
+		}
+	}
+	out
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+}
+
+impl Parser<'_> {
+	fn skip_whitespace(&mut self) {
+		while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+			self.pos += 1;
+		}
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.bytes.get(self.pos).copied()
+	}
+
+	fn expect(&mut self, byte: u8) -> IoResult<()> {
+		self.skip_whitespace();
+		if self.peek() != Some(byte) {
+			return Err(IoError::invalid_data("unexpected character in JSON"));
+		}
+		self.pos += 1;
+		Ok(())
+	}
+
+	fn parse_value(&mut self) -> IoResult<JsonValue> {
+		self.skip_whitespace();
+		match self.peek() {
+			Some(b'{') => self.parse_object(),
+			Some(b'[') => self.parse_array(),
+			Some(b'"') => Ok(JsonValue::String(self.parse_string()?)),
+			Some(b't') => self.parse_literal("true", JsonValue::Bool(true)),
+			Some(b'f') => self.parse_literal("false", JsonValue::Bool(false)),
+			Some(b'n') => self.parse_literal("null", JsonValue::Null),
+			Some(b'-' | b'0'..=b'9') => self.parse_number(),
+			_ => Err(IoError::invalid_data("unexpected character in JSON")),
+		}
+	}
+
+	fn parse_literal(&mut self, literal: &str, value: JsonValue) -> IoResult<JsonValue> {
+		if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+			self.pos += literal.len();
+			return Ok(value);
+		}
+		Err(IoError::invalid_data("invalid JSON literal"))
+	}
+
+	fn parse_number(&mut self) -> IoResult<JsonValue> {
+		let start = self.pos;
+		while let Some(b) = self.peek() {
+			if b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E') {
+				self.pos += 1;
+			} else {
+				break;
+			}
+		}
+		let text = core::str::from_utf8(&self.bytes[start..self.pos])
+			.map_err(|_| IoError::invalid_data("invalid JSON number"))?;
+		text
+			.parse::<f64>()
+			.map(JsonValue::Number)
+			.map_err(|_| IoError::invalid_data("invalid JSON number"))
+	}
+
+	fn parse_string(&mut self) -> IoResult<String> {
+		self.expect(b'"')?;
+		let mut out = Vec::new();
+		loop {
+			let Some(b) = self.peek() else {
+				return Err(IoError::invalid_data("unterminated JSON string"));
+			};
+			self.pos += 1;
+			match b {
+				b'"' => break,
+				b'\\' => {
+					let Some(esc) = self.peek() else {
+						return Err(IoError::invalid_data("unterminated JSON string"));
+					};
+					self.pos += 1;
+					match esc {
+						b'"' => out.push(b'"'),
+						b'\\' => out.push(b'\\'),
+						b'/' => out.push(b'/'),
+						b'n' => out.push(b'\n'),
+						b'r' => out.push(b'\r'),
+						b't' => out.push(b'\t'),
+						b'b' => out.push(0x08),
+						b'f' => out.push(0x0C),
+						b'u' => {
+							let hex = self
+								.bytes
+								.get(self.pos..self.pos + 4)
+								.and_then(|h| core::str::from_utf8(h).ok())
+								.and_then(|h| u32::from_str_radix(h, 16).ok())
+								.ok_or_else(|| IoError::invalid_data("invalid JSON unicode escape"))?;
+							self.pos += 4;
+							let c = char::from_u32(hex).unwrap_or('\u{FFFD}');
+							let mut buf = [0u8; 4];
+							out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+						}
+						_ => return Err(IoError::invalid_data("invalid JSON escape")),
+					}
+				}
+				_ => out.push(b),
+			}
+		}
+		String::from_utf8(out).map_err(|_| IoError::invalid_data("invalid UTF-8 in JSON string"))
+	}
+
+	fn parse_array(&mut self) -> IoResult<JsonValue> {
+		self.expect(b'[')?;
+		let mut items = Vec::new();
+		self.skip_whitespace();
+		if self.peek() == Some(b']') {
+			self.pos += 1;
+			return Ok(JsonValue::Array(items));
+		}
+		loop {
+			items.push(self.parse_value()?);
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.pos += 1,
+				Some(b']') => {
+					self.pos += 1;
+					return Ok(JsonValue::Array(items));
+				}
+				_ => return Err(IoError::invalid_data("expected ',' or ']' in JSON array")),
+			}
+		}
+	}
+
+	fn parse_object(&mut self) -> IoResult<JsonValue> {
+		self.expect(b'{')?;
+		let mut fields = Vec::new();
+		self.skip_whitespace();
+		if self.peek() == Some(b'}') {
+			self.pos += 1;
+			return Ok(JsonValue::Object(fields));
+		}
+		loop {
+			self.skip_whitespace();
+			let key = self.parse_string()?;
+			self.expect(b':')?;
+			let value = self.parse_value()?;
+			fields.push((key, value));
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.pos += 1,
+				Some(b'}') => {
+					self.pos += 1;
+					return Ok(JsonValue::Object(fields));
+				}
+				_ => return Err(IoError::invalid_data("expected ',' or '}' in JSON object")),
+			}
+		}
+	}
+}
@@ -1,15 +1,23 @@
 mod cursor;
+mod file;
+pub mod json;
+pub mod policy;
 mod reader;
 mod seek;
 pub mod stdio;
+pub mod toml;
 mod writer;
 
 pub use cursor::Cursor;
+pub use file::FileAdapter;
+pub use policy::{AllowAll, Overwrite, PathAccess, PathPolicy, RootedPolicy};
 pub use reader::{
-	BufferedReader, BufferedWriter, DEFAULT_BUFFER_SIZE, MediaRead, ReadPrimitives, StdReadAdapter,
+	BufferedReader, BufferedWriter, CountingReader, DEFAULT_BUFFER_SIZE, MediaRead, ReadPrimitives,
+	StdReadAdapter,
 };
 
 pub use seek::{MediaSeek, SeekFrom, SeekableReader, SeekableWriter, StdSeekAdapter};
+pub use stdio::{MediaInput, MediaOutput, PIPE, PipeWriter, is_pipe};
 pub use writer::{MediaWrite, StdWriteAdapter, WritePrimitives};
 
 #[derive(Debug)]
@@ -18,6 +26,9 @@ pub enum IoErrorKind {
 	WriteZero,
 	Interrupted,
 	InvalidData,
+	// an option or argument that cannot work, as opposed to damaged media
+	InvalidInput,
+	Unsupported,
 	NotSeekable,
 	PermissionDenied,
 	NotFound,
@@ -68,6 +79,16 @@ impl IoError {
 		Self::with_message(IoErrorKind::InvalidData, message)
 	}
 
+	#[inline]
+	pub const fn invalid_input(message: &'static str) -> Self {
+		Self::with_message(IoErrorKind::InvalidInput, message)
+	}
+
+	#[inline]
+	pub const fn unsupported(message: &'static str) -> Self {
+		Self::with_message(IoErrorKind::Unsupported, message)
+	}
+
 	#[inline]
 	pub const fn not_seekable() -> Self {
 		Self::new(IoErrorKind::NotSeekable)
@@ -92,6 +113,8 @@ impl From<std::io::Error> for IoError {
 			std::io::ErrorKind::WriteZero => IoErrorKind::WriteZero,
 			std::io::ErrorKind::Interrupted => IoErrorKind::Interrupted,
 			std::io::ErrorKind::InvalidData => IoErrorKind::InvalidData,
+			std::io::ErrorKind::InvalidInput => IoErrorKind::InvalidInput,
+			std::io::ErrorKind::Unsupported => IoErrorKind::Unsupported,
 			std::io::ErrorKind::PermissionDenied => IoErrorKind::PermissionDenied,
 			std::io::ErrorKind::NotFound => IoErrorKind::NotFound,
 			std::io::ErrorKind::AlreadyExists => IoErrorKind::AlreadyExists,
@@ -102,4 +125,25 @@ impl From<std::io::Error> for IoError {
 	}
 }
 
+// keeps the kind, so callers of the std-facing `run` methods can still tell a
+// bad option from a damaged file; the message reads as before
+impl From<IoError> for std::io::Error {
+	fn from(err: IoError) -> Self {
+		let kind = match err.kind {
+			IoErrorKind::UnexpectedEof => std::io::ErrorKind::UnexpectedEof,
+			IoErrorKind::WriteZero => std::io::ErrorKind::WriteZero,
+			IoErrorKind::Interrupted => std::io::ErrorKind::Interrupted,
+			IoErrorKind::InvalidData => std::io::ErrorKind::InvalidData,
+			IoErrorKind::InvalidInput => std::io::ErrorKind::InvalidInput,
+			IoErrorKind::Unsupported | IoErrorKind::NotSeekable => std::io::ErrorKind::Unsupported,
+			IoErrorKind::PermissionDenied => std::io::ErrorKind::PermissionDenied,
+			IoErrorKind::NotFound => std::io::ErrorKind::NotFound,
+			IoErrorKind::AlreadyExists => std::io::ErrorKind::AlreadyExists,
+			IoErrorKind::WouldBlock => std::io::ErrorKind::WouldBlock,
+			IoErrorKind::Other => std::io::ErrorKind::Other,
+		};
+		std::io::Error::new(kind, err.to_string())
+	}
+}
+
 pub type IoResult<T> = Result<T, IoError>;
@@ -0,0 +1,129 @@
+use super::{IoError, IoErrorKind, IoResult};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathAccess {
+	Read,
+	Write,
+}
+
+// every file the library opens on behalf of a caller (inputs, outputs and any
+// sidecar it reads or writes) goes through a policy first. embedders that take
+// paths from untrusted users install a restrictive one.
+pub trait PathPolicy: core::fmt::Debug + Send + Sync {
+	fn check(&self, path: &Path, access: PathAccess) -> IoResult<()>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl PathPolicy for AllowAll {
+	fn check(&self, _path: &Path, _access: PathAccess) -> IoResult<()> {
+		Ok(())
+	}
+}
+
+// what to do when an output file is already there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overwrite {
+	// fail before anything is written
+	#[default]
+	Refuse,
+	// truncate it and write the new output (-y)
+	Replace,
+	// leave it alone and move on (-n)
+	Skip,
+}
+
+impl Overwrite {
+	// whether `path` should be written; Err when it exists and must not be
+	// touched. directories are left to the caller
+	pub fn check(self, path: &Path) -> IoResult<bool> {
+		if !path.is_file() {
+			return Ok(true);
+		}
+		match self {
+			Overwrite::Replace => Ok(true),
+			Overwrite::Skip => Ok(false),
+			Overwrite::Refuse => Err(IoError::with_message(
+				IoErrorKind::AlreadyExists,
+				"output file already exists (-y to overwrite, -n to skip)",
+			)),
+		}
+	}
+}
+
+// only allows paths that resolve inside one of the root directories. `..` and
+// symlinks are resolved before comparing, so neither can be used to escape.
+#[derive(Debug, Clone, Default)]
+pub struct RootedPolicy {
+	read_roots: Vec<PathBuf>,
+	write_roots: Vec<PathBuf>,
+}
+
+impl RootedPolicy {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_root(self, root: impl AsRef<Path>) -> IoResult<Self> {
+		self.with_read_root(root.as_ref())?.with_write_root(root.as_ref())
+	}
+
+	pub fn with_read_root(mut self, root: impl AsRef<Path>) -> IoResult<Self> {
+		self.read_roots.push(root.as_ref().canonicalize()?);
+		Ok(self)
+	}
+
+	pub fn with_write_root(mut self, root: impl AsRef<Path>) -> IoResult<Self> {
+		self.write_roots.push(root.as_ref().canonicalize()?);
+		Ok(self)
+	}
+
+	fn roots(&self, access: PathAccess) -> &[PathBuf] {
+		match access {
+			PathAccess::Read => &self.read_roots,
+			PathAccess::Write => &self.write_roots,
+		}
+	}
+}
+
+impl PathPolicy for RootedPolicy {
+	fn check(&self, path: &Path, access: PathAccess) -> IoResult<()> {
+		let resolved = resolve(path)?;
+		if self.roots(access).iter().any(|root| resolved.starts_with(root)) {
+			return Ok(());
+		}
+		let message = match access {
+			PathAccess::Read => "path is outside the allowed input directories",
+			PathAccess::Write => "path is outside the allowed output directories",
+		};
+		Err(IoError::with_message(IoErrorKind::PermissionDenied, message))
+	}
+}
+
+// absolute, with `.`/`..` removed and symlinks followed for the part of the
+// path that already exists (an output file usually does not exist yet)
+pub fn resolve(path: &Path) -> IoResult<PathBuf> {
+	let absolute =
+		if path.is_absolute() { path.to_path_buf() } else { std::env::current_dir()?.join(path) };
+
+	let mut resolved = PathBuf::new();
+	for component in absolute.components() {
+		match component {
+			Component::ParentDir => {
+				resolved.pop();
+			}
+			Component::CurDir => {}
+			other => {
+				resolved.push(other);
+				// follow symlinks as soon as they appear so a later `..` pops the
+				// link target rather than the link itself
+				if let Ok(real) = resolved.canonicalize() {
+					resolved = real;
+				}
+			}
+		}
+	}
+	Ok(resolved)
+}
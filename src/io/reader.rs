@@ -1,4 +1,6 @@
 use crate::io::{IoError, IoResult};
+use std::cell::Cell;
+use std::rc::Rc;
 
 pub const DEFAULT_BUFFER_SIZE: usize = 8192;
 
@@ -20,6 +22,20 @@ pub trait ReadPrimitives: MediaRead {
 		Ok(())
 	}
 
+	// everything left, for formats that are scanned whole
+	fn read_to_end(&mut self) -> IoResult<Vec<u8>> {
+		let mut data = Vec::new();
+		let mut buf = vec![0u8; 1 << 16];
+		loop {
+			match self.read(&mut buf) {
+				Ok(0) => return Ok(data),
+				Ok(n) => data.extend_from_slice(&buf[..n]),
+				Err(e) if matches!(e.kind(), crate::io::IoErrorKind::Interrupted) => continue,
+				Err(e) => return Err(e),
+			}
+		}
+	}
+
 	#[inline]
 	fn read_u8(&mut self) -> IoResult<u8> {
 		let mut buf = [0u8; 1];
@@ -266,6 +282,31 @@ impl MediaRead for &[u8] {
 	}
 }
 
+// passes reads through and counts the bytes handed over. the count is
+// shared, so it can be read while a demuxer owns the reader
+pub struct CountingReader<R> {
+	inner: R,
+	position: Rc<Cell<u64>>,
+}
+
+impl<R> CountingReader<R> {
+	pub fn new(inner: R) -> Self {
+		Self { inner, position: Rc::new(Cell::new(0)) }
+	}
+
+	pub fn position(&self) -> Rc<Cell<u64>> {
+		self.position.clone()
+	}
+}
+
+impl<R: MediaRead> MediaRead for CountingReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+		let read = self.inner.read(buf)?;
+		self.position.set(self.position.get() + read as u64);
+		Ok(read)
+	}
+}
+
 pub struct BufferedWriter<W, const N: usize = DEFAULT_BUFFER_SIZE> {
 	inner: W,
 	buffer: Vec<u8>,
@@ -1,5 +1,9 @@
-use crate::io::{IoError, IoResult, MediaRead, MediaWrite};
+use crate::io::{
+	Cursor, DEFAULT_BUFFER_SIZE, FileAdapter, IoError, IoErrorKind, IoResult, MediaRead, MediaSeek,
+	MediaWrite, PathPolicy, SeekFrom, StdReadAdapter, StdWriteAdapter, WritePrimitives,
+};
 use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
 
 pub struct StdinAdapter;
 
@@ -76,3 +80,179 @@ impl MediaWrite for StdioSink {
 		}
 	}
 }
+
+// the path the cli uses for stdin and stdout
+pub const PIPE: &str = "-";
+
+pub fn is_pipe(path: &str) -> bool {
+	path == PIPE
+}
+
+// stdin is read to the end the first time it is opened and kept, so a pipe
+// can be seeked and opened again like a file (looping, two-pass transforms)
+static STDIN: Mutex<Option<Arc<[u8]>>> = Mutex::new(None);
+
+pub fn read_stdin() -> IoResult<Arc<[u8]>> {
+	let mut cached = STDIN.lock().map_err(|_| IoError::invalid_data("stdin cache is poisoned"))?;
+	if let Some(data) = cached.as_ref() {
+		return Ok(data.clone());
+	}
+	let mut stdin = StdReadAdapter::new(std::io::stdin().lock());
+	let mut data = Vec::new();
+	let mut buf = [0u8; DEFAULT_BUFFER_SIZE];
+	loop {
+		match stdin.read(&mut buf) {
+			Ok(0) => break,
+			Ok(n) => data.extend_from_slice(&buf[..n]),
+			Err(e) if matches!(e.kind(), IoErrorKind::Interrupted) => continue,
+			Err(e) => return Err(e),
+		}
+	}
+	let data: Arc<[u8]> = data.into();
+	*cached = Some(data.clone());
+	Ok(data)
+}
+
+// a file, or stdin when the path is "-"
+pub enum MediaInput {
+	File(FileAdapter),
+	Pipe(Cursor<Arc<[u8]>>),
+}
+
+impl MediaInput {
+	pub fn open(path: &str, policy: &dyn PathPolicy) -> IoResult<Self> {
+		if is_pipe(path) {
+			return Ok(MediaInput::Pipe(Cursor::new(read_stdin()?)));
+		}
+		Ok(MediaInput::File(FileAdapter::open_with(path, policy)?))
+	}
+}
+
+impl MediaRead for MediaInput {
+	fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+		match self {
+			MediaInput::File(file) => file.read(buf),
+			MediaInput::Pipe(pipe) => pipe.read(buf),
+		}
+	}
+}
+
+impl MediaSeek for MediaInput {
+	fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+		match self {
+			MediaInput::File(file) => file.seek(pos),
+			MediaInput::Pipe(pipe) => pipe.seek(pos),
+		}
+	}
+}
+
+// a writer that cannot seek, for muxers that ask to. streaming passes bytes
+// straight through and only "seeks" to where it already is; buffered holds
+// everything in memory, seeks anywhere in it, and hands it over on flush
+pub struct PipeWriter<W> {
+	inner: W,
+	held: Option<Cursor<Vec<u8>>>,
+	written: u64,
+}
+
+impl<W: MediaWrite> PipeWriter<W> {
+	pub fn streaming(inner: W) -> Self {
+		Self { inner, held: None, written: 0 }
+	}
+
+	pub fn buffered(inner: W) -> Self {
+		Self { inner, held: Some(Cursor::new(Vec::new())), written: 0 }
+	}
+
+	pub fn into_inner(self) -> W {
+		self.inner
+	}
+}
+
+impl<W: MediaWrite> MediaWrite for PipeWriter<W> {
+	fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+		match &mut self.held {
+			Some(held) => held.write(buf),
+			None => {
+				let n = self.inner.write(buf)?;
+				self.written += n as u64;
+				Ok(n)
+			}
+		}
+	}
+
+	fn flush(&mut self) -> IoResult<()> {
+		if let Some(held) = self.held.take() {
+			let data = held.into_inner();
+			self.inner.write_all(&data)?;
+			self.written += data.len() as u64;
+		}
+		self.inner.flush()
+	}
+}
+
+impl<W: MediaWrite> MediaSeek for PipeWriter<W> {
+	fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+		if let Some(held) = &mut self.held {
+			return held.seek(pos);
+		}
+		match pos {
+			SeekFrom::Start(n) if n == self.written => Ok(n),
+			SeekFrom::Current(0) | SeekFrom::End(0) => Ok(self.written),
+			_ => Err(IoError::not_seekable()),
+		}
+	}
+}
+
+// a file, or stdout when the path is "-"
+pub enum MediaOutput {
+	File(FileAdapter),
+	Pipe(PipeWriter<StdWriteAdapter<std::io::Stdout>>),
+}
+
+impl MediaOutput {
+	// stdout streams; muxers that patch earlier bytes need `create_buffered`
+	pub fn create(path: &str, policy: &dyn PathPolicy) -> IoResult<Self> {
+		if is_pipe(path) {
+			return Ok(MediaOutput::Pipe(PipeWriter::streaming(StdWriteAdapter::new(std::io::stdout()))));
+		}
+		Ok(MediaOutput::File(FileAdapter::create_with(path, policy)?))
+	}
+
+	// stdout collects the whole output and writes it when the muxer flushes
+	pub fn create_buffered(path: &str, policy: &dyn PathPolicy) -> IoResult<Self> {
+		if is_pipe(path) {
+			return Ok(MediaOutput::Pipe(PipeWriter::buffered(StdWriteAdapter::new(std::io::stdout()))));
+		}
+		Ok(MediaOutput::File(FileAdapter::create_with(path, policy)?))
+	}
+
+	pub fn is_pipe(&self) -> bool {
+		matches!(self, MediaOutput::Pipe(_))
+	}
+}
+
+impl MediaWrite for MediaOutput {
+	fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+		match self {
+			MediaOutput::File(file) => file.write(buf),
+			MediaOutput::Pipe(pipe) => pipe.write(buf),
+		}
+	}
+
+	fn flush(&mut self) -> IoResult<()> {
+		match self {
+			MediaOutput::File(file) => file.flush(),
+			MediaOutput::Pipe(pipe) => pipe.flush(),
+		}
+	}
+}
+
+impl MediaSeek for MediaOutput {
+	fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+		match self {
+			MediaOutput::File(file) => file.seek(pos),
+			MediaOutput::Pipe(pipe) => pipe.seek(pos),
+		}
+	}
+}
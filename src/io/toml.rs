@@ -0,0 +1,298 @@
+use crate::io::json::JsonValue;
+use crate::io::{IoError, IoResult};
+
+// the part of TOML a config file needs: `key = value` lines with strings,
+// numbers, booleans, arrays and inline tables, `[table]` and `[[array]]`
+// headers, and # comments. dotted keys, dates and multi-line strings are not
+// supported. the document comes back as a json value so callers read both
+// formats the same way
+pub fn parse(text: &str) -> IoResult<JsonValue> {
+	let mut parser = Parser { bytes: text.as_bytes(), pos: 0 };
+	let mut root: Vec<(String, JsonValue)> = Vec::new();
+	let mut header: Option<(String, bool)> = None;
+
+	loop {
+		parser.skip_blank();
+		let Some(b) = parser.peek() else {
+			break;
+		};
+		if b == b'[' {
+			parser.pos += 1;
+			let is_array = parser.peek() == Some(b'[');
+			if is_array {
+				parser.pos += 1;
+			}
+			parser.skip_spaces();
+			let name = parser.parse_key()?;
+			parser.skip_spaces();
+			parser.expect(b']')?;
+			if is_array {
+				parser.expect(b']')?;
+			}
+			open_table(&mut root, &name, is_array)?;
+			header = Some((name, is_array));
+		} else {
+			let key = parser.parse_key()?;
+			parser.skip_spaces();
+			parser.expect(b'=')?;
+			parser.skip_spaces();
+			let value = parser.parse_value()?;
+			insert(current_table(&mut root, header.as_ref())?, key, value)?;
+		}
+		parser.end_line()?;
+	}
+
+	Ok(JsonValue::Object(root))
+}
+
+fn open_table(root: &mut Vec<(String, JsonValue)>, name: &str, is_array: bool) -> IoResult<()> {
+	let existing = root.iter_mut().find(|(key, _)| key == name).map(|(_, value)| value);
+	match (existing, is_array) {
+		(None, false) => root.push((name.to_string(), JsonValue::Object(Vec::new()))),
+		(None, true) => {
+			root.push((name.to_string(), JsonValue::Array(vec![JsonValue::Object(Vec::new())])))
+		}
+		(Some(JsonValue::Array(items)), true) => items.push(JsonValue::Object(Vec::new())),
+		_ => return Err(IoError::invalid_data("TOML table is defined twice")),
+	}
+	Ok(())
+}
+
+fn current_table<'a>(
+	root: &'a mut Vec<(String, JsonValue)>,
+	header: Option<&(String, bool)>,
+) -> IoResult<&'a mut Vec<(String, JsonValue)>> {
+	let Some((name, _)) = header else {
+		return Ok(root);
+	};
+	let table = root.iter_mut().find(|(key, _)| key == name).map(|(_, value)| value);
+	let table = match table {
+		Some(JsonValue::Array(items)) => items.last_mut(),
+		table => table,
+	};
+	match table {
+		Some(JsonValue::Object(fields)) => Ok(fields),
+		_ => Err(IoError::invalid_data("TOML table is missing")),
+	}
+}
+
+fn insert(table: &mut Vec<(String, JsonValue)>, key: String, value: JsonValue) -> IoResult<()> {
+	if table.iter().any(|(existing, _)| *existing == key) {
+		return Err(IoError::invalid_data("TOML key is defined twice"));
+	}
+	table.push((key, value));
+	Ok(())
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+}
+
+impl Parser<'_> {
+	fn peek(&self) -> Option<u8> {
+		self.bytes.get(self.pos).copied()
+	}
+
+	fn skip_spaces(&mut self) {
+		while matches!(self.peek(), Some(b' ' | b'\t')) {
+			self.pos += 1;
+		}
+	}
+
+	fn skip_comment(&mut self) {
+		if self.peek() == Some(b'#') {
+			while !matches!(self.peek(), None | Some(b'\n')) {
+				self.pos += 1;
+			}
+		}
+	}
+
+	// whitespace, newlines and comments, as allowed between lines and inside arrays
+	fn skip_blank(&mut self) {
+		loop {
+			match self.peek() {
+				Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+				Some(b'#') => self.skip_comment(),
+				_ => return,
+			}
+		}
+	}
+
+	fn end_line(&mut self) -> IoResult<()> {
+		self.skip_spaces();
+		self.skip_comment();
+		match self.peek() {
+			None | Some(b'\n') => Ok(()),
+			Some(b'\r') if self.bytes.get(self.pos + 1) == Some(&b'\n') => Ok(()),
+			_ => Err(IoError::invalid_data("unexpected text after TOML value")),
+		}
+	}
+
+	fn expect(&mut self, byte: u8) -> IoResult<()> {
+		if self.peek() != Some(byte) {
+			return Err(IoError::invalid_data("unexpected character in TOML"));
+		}
+		self.pos += 1;
+		Ok(())
+	}
+
+	fn parse_key(&mut self) -> IoResult<String> {
+		match self.peek() {
+			Some(b'"') => self.parse_basic_string(),
+			Some(b'\'') => self.parse_literal_string(),
+			_ => {
+				let start = self.pos;
+				while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+				{
+					self.pos += 1;
+				}
+				if self.pos == start {
+					return Err(IoError::invalid_data("expected a TOML key"));
+				}
+				if self.peek() == Some(b'.') {
+					return Err(IoError::invalid_data("dotted TOML keys are not supported"));
+				}
+				Ok(String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned())
+			}
+		}
+	}
+
+	fn parse_value(&mut self) -> IoResult<JsonValue> {
+		match self.peek() {
+			Some(b'"') => Ok(JsonValue::String(self.parse_basic_string()?)),
+			Some(b'\'') => Ok(JsonValue::String(self.parse_literal_string()?)),
+			Some(b'[') => self.parse_array(),
+			Some(b'{') => self.parse_inline_table(),
+			Some(b't') => self.parse_word("true", JsonValue::Bool(true)),
+			Some(b'f') => self.parse_word("false", JsonValue::Bool(false)),
+			Some(b'-' | b'+' | b'0'..=b'9') => self.parse_number(),
+			_ => Err(IoError::invalid_data("unexpected character in TOML value")),
+		}
+	}
+
+	fn parse_word(&mut self, word: &str, value: JsonValue) -> IoResult<JsonValue> {
+		if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+			self.pos += word.len();
+			return Ok(value);
+		}
+		Err(IoError::invalid_data("invalid TOML value"))
+	}
+
+	// integers and floats, with the `_` separators TOML allows
+	fn parse_number(&mut self) -> IoResult<JsonValue> {
+		let start = self.pos;
+		while matches!(self.peek(), Some(b) if b.is_ascii_digit() || b"+-._eE".contains(&b)) {
+			self.pos += 1;
+		}
+		let text: String = String::from_utf8_lossy(&self.bytes[start..self.pos]).replace('_', "");
+		text
+			.parse::<f64>()
+			.map(JsonValue::Number)
+			.map_err(|_| IoError::invalid_data("invalid TOML number"))
+	}
+
+	fn parse_basic_string(&mut self) -> IoResult<String> {
+		self.expect(b'"')?;
+		let mut out = Vec::new();
+		loop {
+			let Some(b) = self.peek() else {
+				return Err(IoError::invalid_data("unterminated TOML string"));
+			};
+			self.pos += 1;
+			match b {
+				b'"' => break,
+				b'\n' => return Err(IoError::invalid_data("unterminated TOML string")),
+				b'\\' => {
+					let Some(esc) = self.peek() else {
+						return Err(IoError::invalid_data("unterminated TOML string"));
+					};
+					self.pos += 1;
+					match esc {
+						b'"' => out.push(b'"'),
+						b'\\' => out.push(b'\\'),
+						b'n' => out.push(b'\n'),
+						b'r' => out.push(b'\r'),
+						b't' => out.push(b'\t'),
+						b'u' => {
+							let hex = self
+								.bytes
+								.get(self.pos..self.pos + 4)
+								.and_then(|h| core::str::from_utf8(h).ok())
+								.and_then(|h| u32::from_str_radix(h, 16).ok())
+								.ok_or_else(|| IoError::invalid_data("invalid TOML unicode escape"))?;
+							self.pos += 4;
+							let c = char::from_u32(hex).unwrap_or('\u{FFFD}');
+							let mut buf = [0u8; 4];
+							out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+						}
+						_ => return Err(IoError::invalid_data("invalid TOML escape")),
+					}
+				}
+				_ => out.push(b),
+			}
+		}
+		String::from_utf8(out).map_err(|_| IoError::invalid_data("invalid UTF-8 in TOML string"))
+	}
+
+	// 'single quoted' strings take backslashes as they are, handy for paths
+	fn parse_literal_string(&mut self) -> IoResult<String> {
+		self.expect(b'\'')?;
+		let start = self.pos;
+		while !matches!(self.peek(), None | Some(b'\'' | b'\n')) {
+			self.pos += 1;
+		}
+		let text = String::from_utf8(self.bytes[start..self.pos].to_vec())
+			.map_err(|_| IoError::invalid_data("invalid UTF-8 in TOML string"))?;
+		self.expect(b'\'')?;
+		Ok(text)
+	}
+
+	// arrays may span lines and end with a trailing comma
+	fn parse_array(&mut self) -> IoResult<JsonValue> {
+		self.expect(b'[')?;
+		let mut items = Vec::new();
+		loop {
+			self.skip_blank();
+			if self.peek() == Some(b']') {
+				self.pos += 1;
+				return Ok(JsonValue::Array(items));
+			}
+			items.push(self.parse_value()?);
+			self.skip_blank();
+			match self.peek() {
+				Some(b',') => self.pos += 1,
+				Some(b']') => {}
+				_ => return Err(IoError::invalid_data("expected ',' or ']' in TOML array")),
+			}
+		}
+	}
+
+	fn parse_inline_table(&mut self) -> IoResult<JsonValue> {
+		self.expect(b'{')?;
+		let mut fields = Vec::new();
+		self.skip_spaces();
+		if self.peek() == Some(b'}') {
+			self.pos += 1;
+			return Ok(JsonValue::Object(fields));
+		}
+		loop {
+			self.skip_spaces();
+			let key = self.parse_key()?;
+			self.skip_spaces();
+			self.expect(b'=')?;
+			self.skip_spaces();
+			let value = self.parse_value()?;
+			insert(&mut fields, key, value)?;
+			self.skip_spaces();
+			match self.peek() {
+				Some(b',') => self.pos += 1,
+				Some(b'}') => {
+					self.pos += 1;
+					return Ok(JsonValue::Object(fields));
+				}
+				_ => return Err(IoError::invalid_data("expected ',' or '}' in TOML inline table")),
+			}
+		}
+	}
+}
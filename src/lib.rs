@@ -1,7 +1,15 @@
+#[cfg(feature = "cli")]
 pub mod cli;
 pub mod codecs;
 pub mod container;
 pub mod core;
+pub mod edl;
+pub mod image;
 pub mod io;
+#[cfg(feature = "cli")]
+pub mod serve;
 pub mod show;
+pub mod transcode;
 pub mod transform;
+
+pub use transcode::{Capability, can_convert};
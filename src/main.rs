@@ -1,43 +1,669 @@
-use ffmpreg::cli::{Args, BatchPipeline, Pipeline, is_batch_pattern, is_directory};
-use ffmpreg::show::{Show, ShowOptions};
+use ffmpreg::cli::command::Command;
+use ffmpreg::cli::completions::cli;
+use ffmpreg::cli::contact_sheet::{ContactSheetSpec, write_contact_sheet};
+use ffmpreg::cli::error::{self, CliError, ErrorFormat, ErrorKind};
+use ffmpreg::cli::format::SizeUnits;
+use ffmpreg::cli::man;
+use ffmpreg::cli::sample::{FrameSampleSpec, sample_batch};
+use ffmpreg::cli::template::{TemplateVars, is_template, render};
+use ffmpreg::cli::watch::Watcher;
+use ffmpreg::cli::waveform::run_waveform;
+use ffmpreg::cli::{
+	Args, BatchPipeline, Config, ErrorPolicy, JobFile, Pipeline, StreamMetadata, is_batch_pattern,
+	is_directory,
+};
+use ffmpreg::codecs::CodecId;
+use ffmpreg::container::metadata::CoverArt;
+use ffmpreg::container::{MediaType, SinkFormat};
+use ffmpreg::core::{log, parse_duration};
+use ffmpreg::edl::EditDecisionList;
+use ffmpreg::io::{Overwrite, is_pipe};
+use ffmpreg::show::{BitrateMode, Show, ShowFormat, ShowOptions};
+use ffmpreg::transcode::{Benchmark, Check, Compare, Concat, Diff, EdlExport, ProgressMode};
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Arc;
 
 fn main() {
-	let args = Args::parse();
+	if let Some(format) = error::scan_format(std::env::args()) {
+		error::set_format(format);
+	}
+
+	// the tools run on their own; convert, probe, batch and analyze stand
+	// for a flag form
+	let mut args = Args::try_parse().unwrap_or_else(|e| error::exit_clap(e));
+	let mut args = match args.command.take() {
+		None => args,
+		Some(Command::Convert(convert)) => Args::from(*convert),
+		Some(Command::Probe(probe)) => Args::from(probe),
+		Some(Command::Batch(batch)) => Args::from(batch),
+		Some(Command::Analyze(analyze)) => Args::from(analyze),
+		Some(Command::Bench(bench)) => return run_tool(bench.run()),
+		Some(Command::Preview(preview)) => return run_tool(preview.run()),
+		Some(Command::Vad(vad)) => return run_tool(vad.run()),
+		Some(Command::Slice(slice)) => return run_tool(slice.run()),
+		Some(Command::Serve(serve)) => return run_tool(serve.run()),
+		Some(Command::Completions(completions)) => {
+			print!("{}", completions.shell.script());
+			return;
+		}
+	};
+	if args.help_man {
+		print!("{}", man::render(&cli()));
+		return;
+	}
+	match ErrorFormat::from_name(&args.error_format) {
+		Some(format) => error::set_format(format),
+		None => error::exit(CliError::invalid_input(format!(
+			"unknown --error-format '{}' (text or json)",
+			args.error_format
+		))),
+	}
+	let config = match args.config.as_deref() {
+		Some(path) => Config::load(Path::new(path)),
+		None => Config::load_default(),
+	};
+	let config = config.unwrap_or_else(|e| {
+		error::exit(CliError::from_argument_file("config file", e.into()));
+	});
 
-	let result = if args.show {
+	// -v, -q and --log-level each replace the config's level
+	let level_flag = args.log_level.is_some() || args.quiet || args.verbose > 0;
+	match config.log_level.filter(|_| !level_flag).or(args.log_level()) {
+		Some(level) => log::set_level(level),
+		None => {
+			let name = args.log_level.as_deref().unwrap_or_default();
+			error::exit(CliError::invalid_input(format!(
+				"unknown --log-level '{}' (off, error, warning, info, debug or trace)",
+				name
+			)));
+		}
+	}
+
+	// the config's transforms run first, then a preset's, then --apply; a
+	// preset's codec stands in for a missing --codec
+	let preset = args.preset.as_deref().map(|name| {
+		config.preset(name).unwrap_or_else(|| {
+			error::exit(CliError::invalid_input(format!(
+				"unknown preset '{}' ({})",
+				name,
+				config.preset_names().join(", ")
+			)));
+		})
+	});
+	let mut transforms = config.apply.clone();
+	if let Some(preset) = &preset {
+		transforms.extend(preset.transforms());
+		if args.codec.is_none() {
+			args.codec = preset.codec.clone();
+		}
+	}
+	args.transforms = transforms.into_iter().chain(args.transforms.drain(..)).collect();
+	let codec = args.codec.as_deref().map(|name| match CodecId::from_name(name) {
+		Some(codec) if codec.is_big_endian() => error::exit(CliError::invalid_input(format!(
+			"codec '{}' is big-endian pcm, which wav cannot hold",
+			name
+		))),
+		Some(codec) => codec,
+		None => error::exit(CliError::invalid_input(format!("unknown codec '{}'", name))),
+	});
+	let container = preset.as_ref().and_then(|preset| preset.container);
+
+	// --watch is a long-running ingest loop rather than one conversion
+	if let Some(dir) = &args.watch {
+		if args.output.is_none() {
+			args.output = config.output.clone();
+		}
+		if let Err(e) = run_watch(&args, dir, codec, container) {
+			error::exit(e);
+		}
+		return;
+	}
+
+	let single_input = args.show
+		|| args.chunks
+		|| args.bitrate.is_some()
+		|| args.keyframes
+		|| args.stats
+		|| args.check
+		|| args.sample_frames.is_some()
+		|| args.contact_sheet.is_some()
+		|| args.waveform.is_some()
+		|| args.compare.is_some()
+		|| !args.diff.is_empty()
+		|| args.export_edl.is_some()
+		|| is_batch_pattern(&args.input)
+		|| is_directory(&args.input);
+	if !args.more_inputs.is_empty() && single_input {
+		error::exit(CliError::invalid_input("only conversions take more than one -i"));
+	}
+
+	// `-f md5`, `-f sha256` and `-f null` (or `-o null`) take the place of the
+	// output file: a digest of the encoded packets, or nothing at all
+	let sink_format = args.format.as_deref().and_then(SinkFormat::from_name);
+	let sink = sink_format.or((args.output.as_deref() == Some("null")).then_some(SinkFormat::Null));
+
+	// `-i -` and `-o -` are stdin and stdout, whose container -f names. on
+	// files -f overrides the output's extension and stands in for an input's
+	// that says nothing; --input-format overrides the input's. a digest on
+	// stdout needs no container
+	let piped =
+		is_pipe(&args.input) || (sink.is_none() && args.output.as_deref().is_some_and(is_pipe));
+	let input_format = match args.input_format.as_deref().map(MediaType::from_name) {
+		Some(MediaType::Unknown) => {
+			error::exit(CliError::invalid_input(format!(
+				"unknown --input-format '{}' (wav, flac, y4m, avi, mp4)",
+				args.input_format.as_deref().unwrap_or("")
+			)));
+		}
+		input_format => input_format,
+	};
+	let format =
+		match args.format.as_deref().filter(|_| sink_format.is_none()).map(MediaType::from_name) {
+			Some(MediaType::Unknown) => {
+				error::exit(CliError::invalid_input(format!(
+					"unknown format '{}' (wav, flac, y4m, avi, mp4, md5, sha256 or null)",
+					args.format.as_deref().unwrap_or("")
+				)));
+			}
+			Some(format) => Some(format),
+			None if sink.is_none() && args.output.as_deref().is_some_and(is_pipe) => {
+				error::exit(CliError::invalid_input("writing - needs a format, e.g. -f wav"));
+			}
+			None if is_pipe(&args.input) && input_format.is_none() => {
+				error::exit(CliError::invalid_input("reading - needs a format, e.g. -f wav"));
+			}
+			None => None,
+		};
+	if (format.is_some() || input_format.is_some())
+		&& (args.sample_frames.is_some()
+			|| args.contact_sheet.is_some()
+			|| args.waveform.is_some()
+			|| args.compare.is_some()
+			|| !args.diff.is_empty()
+			|| args.export_edl.is_some()
+			|| args.concat.is_some()
+			|| (!args.more_inputs.is_empty() && args.filter_graph.is_none()))
+	{
+		error::exit(CliError::invalid_input("-f only applies to conversions and --show"));
+	}
+	if input_format.is_some()
+		&& (args.job.is_some() || is_batch_pattern(&args.input) || is_directory(&args.input))
+	{
+		error::exit(CliError::invalid_input("--input-format applies to a single input"));
+	}
+	if sink.is_some()
+		&& (single_input
+			|| args.job.is_some()
+			|| args.concat.is_some()
+			|| (!args.more_inputs.is_empty() && args.filter_graph.is_none()))
+	{
+		error::exit(CliError::invalid_input("null and hash outputs only apply to a single conversion"));
+	}
+	if piped
+		&& (single_input || args.job.is_some() || args.concat.is_some() || !args.more_inputs.is_empty())
+	{
+		error::exit(CliError::invalid_input("only a single conversion reads or writes a pipe"));
+	}
+
+	// a batch renders an output template per file; a single input fills it in here
+	let batch_input = is_batch_pattern(&args.input) || is_directory(&args.input);
+	if let Some(template) = args.output.clone().filter(|output| is_template(output))
+		&& !batch_input
+		&& args.job.is_none()
+	{
+		let name = Path::new(&args.input).file_name().map(Path::new).unwrap_or(Path::new(""));
+		match render(&template, &TemplateVars::for_input(&template, &args.input, name, 1)) {
+			Ok(output) => args.output = Some(output),
+			Err(e) => error::exit(CliError::invalid_input(e.to_string())),
+		}
+	}
+
+	// a single conversion without -o goes into the config's output directory
+	if let Some(dir) = &config.output
+		&& args.output.is_none()
+		&& !single_input
+		&& args.job.is_none()
+		&& args.concat.is_none()
+		&& args.more_inputs.is_empty()
+		&& sink.is_none()
+		&& !is_pipe(&args.input)
+	{
+		let mut output = Path::new(dir).join(Path::new(&args.input).file_name().unwrap_or_default());
+		if let Some(container) = container {
+			output.set_extension(container.extension());
+		}
+		if let Err(e) = std::fs::create_dir_all(dir) {
+			error::exit(CliError::new(ErrorKind::of(&e), format!("{}: {}", dir, e)));
+		}
+		args.output = Some(output.to_string_lossy().to_string());
+	}
+
+	// a preset's container names a single output given without an extension
+	if let Some(preset) = &preset
+		&& !batch_input
+		&& let Some(output) =
+			args.output.clone().filter(|o| !is_pipe(o) && o != "null" && !is_directory(o))
+	{
+		args.output = Some(preset.output_name(&output));
+	}
+
+	if args.cover.is_some()
+		&& (single_input
+			|| args.concat.is_some()
+			|| !args.more_inputs.is_empty()
+			|| args.output.as_deref().is_some_and(is_directory))
+	{
+		error::exit(CliError::invalid_input("--cover applies to a single conversion"));
+	}
+
+	if args.benchmark
+		&& (single_input
+			|| args.job.is_some()
+			|| args.concat.is_some()
+			|| (!args.more_inputs.is_empty() && args.filter_graph.is_none())
+			|| args.output.as_deref().is_some_and(is_directory))
+	{
+		error::exit(CliError::invalid_input("--benchmark applies to a single conversion"));
+	}
+
+	if !args.more_outputs.is_empty()
+		&& (single_input
+			|| args.job.is_some()
+			|| args.concat.is_some()
+			|| !args.more_inputs.is_empty()
+			|| args.output.as_deref().is_some_and(is_directory))
+	{
+		error::exit(CliError::invalid_input("more than one -o applies to a single conversion"));
+	}
+
+	if args.compat.is_some() && !args.show {
+		error::exit(CliError::invalid_input("--compat applies to --show"));
+	}
+	if args.packets && !args.show {
+		error::exit(CliError::invalid_input("--packets applies to --show"));
+	}
+	if args.print_format.is_some() && !args.show {
+		error::exit(CliError::invalid_input("--print-format applies to --show"));
+	}
+
+	if args.resume.is_some() && (!batch_input || args.show) {
+		error::exit(CliError::invalid_input("--resume applies to a glob or directory input"));
+	}
+	let Some(on_error) = ErrorPolicy::from_name(&args.on_error) else {
+		error::exit(CliError::invalid_input(format!(
+			"unknown --on-error '{}' (continue or stop)",
+			args.on_error
+		)));
+	};
+	if args.recursive && !is_directory(&args.input) {
+		error::exit(CliError::invalid_input("--recursive needs a directory input"));
+	}
+
+	// -n leaves an existing output file alone, which is reported instead of "ok"
+	let skipped = args.overwrite() == Overwrite::Skip
+		&& sink != Some(SinkFormat::Null)
+		&& args.output.as_deref().is_some_and(|output| !is_pipe(output) && Path::new(output).is_file());
+
+	let result = if let Some(path) = &args.job {
+		// a bare --job runs the config's [[job]] tables
+		let jobs = if path.is_empty() {
+			match config.jobs.is_empty() {
+				true => {
+					Err(std::io::Error::other("--job without a file needs [[job]] tables in the config"))
+				}
+				false => Ok(JobFile { jobs: config.jobs.clone(), ..JobFile::default() }),
+			}
+		} else {
+			JobFile::load(path).map_err(std::io::Error::from)
+		};
+		jobs.and_then(|jobs| jobs.with_overwrite(args.overwrite()).run())
+	} else if args.show || args.chunks || args.bitrate.is_some() || args.keyframes || args.stats {
+		let Some(units) = SizeUnits::from_name(&args.units) else {
+			error::exit(CliError::invalid_input(format!(
+				"unknown units '{}' (expected binary, decimal or bytes)",
+				args.units
+			)));
+		};
+		let ffprobe = match args.compat.as_deref() {
+			None => false,
+			Some("ffprobe") => true,
+			Some(name) => {
+				error::exit(CliError::invalid_input(format!("unknown --compat '{}' (ffprobe)", name)));
+			}
+		};
+		let show_format = match args.print_format.as_deref() {
+			Some(name) => ShowFormat::from_name(name).unwrap_or_else(|| {
+				error::exit(CliError::invalid_input(format!(
+					"unknown --print-format '{}' (human, json, csv or xml)",
+					name
+				)));
+			}),
+			None if args.json || ffprobe => ShowFormat::Json,
+			None => ShowFormat::Human,
+		};
+		if ffprobe && show_format != ShowFormat::Json {
+			error::exit(CliError::invalid_input("--compat ffprobe prints json"));
+		}
+		if args.chunks && (ffprobe || !matches!(show_format, ShowFormat::Human | ShowFormat::Json)) {
+			error::exit(CliError::invalid_input("--chunks prints human or json"));
+		}
+		let bitrate = args.bitrate.as_deref().map(|name| {
+			BitrateMode::from_name(name).unwrap_or_else(|| {
+				error::exit(CliError::invalid_input(format!(
+					"unknown --bitrate '{}' (summary, sparkline or csv)",
+					name
+				)));
+			})
+		});
+		if bitrate.is_some()
+			&& (ffprobe || !matches!(show_format, ShowFormat::Human | ShowFormat::Json))
+		{
+			error::exit(CliError::invalid_input("--bitrate prints human or json"));
+		}
+		if args.keyframes && (ffprobe || !matches!(show_format, ShowFormat::Human | ShowFormat::Json)) {
+			error::exit(CliError::invalid_input("--keyframes prints human or json"));
+		}
+		if args.stats && (ffprobe || !matches!(show_format, ShowFormat::Human | ShowFormat::Json)) {
+			error::exit(CliError::invalid_input("--stats prints human or json"));
+		}
 		let opts = ShowOptions {
-			json: args.json,
+			format: show_format,
 			stream_filter: args.stream,
 			frame_limit: args.frames,
 			hex_limit: args.hex_limit,
+			units,
+			// `--show --apply astats` adds per-channel levels to each audio stream
+			astats: args.transforms.iter().any(|t| t.split('=').next() == Some("astats")),
+			packets: args.packets,
+			count_frames: args.count_frames,
+			ffprobe,
+			chunks: args.chunks,
+			bitrate,
+			keyframes: args.keyframes,
+			stats: args.stats,
 		};
-		let show = Show::new(args.input.clone(), opts);
+		let mut show = Show::new(args.input.clone(), opts);
+		if let Some(format) = input_format.or(format) {
+			show = show.with_format(format);
+		}
 		show.run()
-	} else if is_batch_pattern(&args.input) {
-		let output_dir = args.output.clone().unwrap_or_else(|| "out".to_string());
-		let batch = BatchPipeline::new(args.input.clone(), output_dir, false, args.transforms.clone());
+	} else if let Some(spec) = &args.sample_frames {
+		FrameSampleSpec::parse(spec)
+			.and_then(|spec| sample_batch(&args.input, &spec, &args.output_template))
+			.map(|_| ())
+			.map_err(std::io::Error::from)
+	} else if let Some(grid) = &args.contact_sheet {
+		let Some(output) = args.output.clone() else {
+			error::exit(CliError::invalid_input("--contact-sheet needs an output image (.png or .ppm)"));
+		};
+		ContactSheetSpec::parse(grid)
+			.and_then(|spec| write_contact_sheet(&args.input, &spec, &output))
+			.map_err(std::io::Error::from)
+	} else if let Some(output) = &args.waveform {
+		let output = Some(output.as_str()).filter(|output| *output != "-");
+		run_waveform(&args.input, output).map_err(std::io::Error::from)
+	} else if args.check {
+		let mut check = Check::new(args.input.clone()).with_json(args.json);
+		if let Some(format) = input_format.or(format) {
+			check = check.with_format(format);
+		}
+		check.run().map_err(std::io::Error::from)
+	} else if let Some(reference) = &args.compare {
+		Compare::new(args.input.clone(), reference.clone())
+			.with_json(args.json)
+			.run()
+			.map_err(std::io::Error::from)
+	} else if let [a, b] = args.diff.as_slice() {
+		Diff::new(a.clone(), b.clone()).with_json(args.json).run().map_err(std::io::Error::from)
+	} else if let Some(path) = &args.export_edl {
+		EdlExport::new(args.input.clone(), args.transforms.clone())
+			.run(path)
+			.map_err(std::io::Error::from)
+	} else if args.concat.is_some() || (!args.more_inputs.is_empty() && args.filter_graph.is_none()) {
+		// several -i without a filter graph to read them are played one after another
+		let Some(output) = args.output.clone() else {
+			error::exit(CliError::invalid_input("joining inputs needs an output file"));
+		};
+		let mut inputs = args.inputs().into_iter().chain(args.concat.clone());
+		let (Some(first), Some(second)) = (inputs.next(), inputs.next()) else {
+			error::exit(CliError::invalid_input("joining needs at least two inputs"));
+		};
+		let crossfade = args.crossfade.as_deref().map(parse_duration).unwrap_or(Some(0.0));
+		let Some(crossfade) = crossfade else {
+			error::exit(CliError::invalid_input("invalid --crossfade duration (e.g., 500ms)"));
+		};
+		inputs
+			.fold(Concat::new(first, second, output), Concat::with_input)
+			.with_crossfade(crossfade)
+			.run()
+			.map_err(std::io::Error::from)
+	} else if is_batch_pattern(&args.input) || is_directory(&args.input) {
+		let output_dir =
+			args.output.clone().or(config.output.clone()).unwrap_or_else(|| "out".to_string());
+		let mut batch =
+			BatchPipeline::new(args.input.clone(), output_dir, false, args.transforms.clone())
+				.with_overwrite(args.overwrite())
+				.with_recursive(args.recursive)
+				.with_extensions(args.extensions.clone());
+		if let Some(format) = format {
+			batch = batch.with_format(format);
+		}
+		if let Some(container) = container {
+			batch = batch.with_output_format(container);
+		}
+		if let Some(codec) = codec {
+			batch = batch.with_codec(codec);
+		}
+		if let Some(manifest) = &args.resume {
+			batch = batch.with_resume(manifest);
+		}
+		batch = batch.with_on_error(on_error);
 		batch.run()
 	} else if args.output.as_ref().map(|o| is_directory(o)).unwrap_or(false) {
 		let output_dir = args.output.clone().unwrap();
-		let batch = BatchPipeline::new(args.input.clone(), output_dir, false, args.transforms.clone());
+		let mut batch =
+			BatchPipeline::new(args.input.clone(), output_dir, false, args.transforms.clone())
+				.with_overwrite(args.overwrite())
+				.with_recursive(args.recursive)
+				.with_extensions(args.extensions.clone());
+		if let Some(format) = format {
+			batch = batch.with_format(format);
+		}
+		if let Some(container) = container {
+			batch = batch.with_output_format(container);
+		}
+		if let Some(codec) = codec {
+			batch = batch.with_codec(codec);
+		}
+		if let Some(manifest) = &args.resume {
+			batch = batch.with_resume(manifest);
+		}
+		batch = batch.with_on_error(on_error);
 		batch.run()
 	} else {
-		let pipeline =
-			Pipeline::new(args.input.clone(), args.output.clone(), false, args.transforms.clone());
-		pipeline.run()
+		let mut pipeline =
+			Pipeline::new(args.input.clone(), args.output.clone(), false, args.transforms.clone())
+				.with_overwrite(args.overwrite());
+		let stream_metadata: Result<Vec<_>, _> =
+			args.stream_metadata.iter().map(|m| StreamMetadata::parse_combined(m)).collect();
+		match stream_metadata {
+			Ok(stream_metadata) => pipeline = pipeline.with_stream_metadata(stream_metadata),
+			Err(e) => error::exit(CliError::invalid_input(e.to_string())),
+		}
+		if let Some(path) = &args.edl {
+			match EditDecisionList::load(path) {
+				Ok(edl) => pipeline = pipeline.with_edl(edl),
+				Err(e) => error::exit(CliError::from_argument_file(path, e.into())),
+			}
+		}
+		if let Some(codec) = codec {
+			pipeline = pipeline.with_codec(codec);
+		}
+		if let Some(path) = &args.cover {
+			match CoverArt::load(path) {
+				Ok(cover) => pipeline = pipeline.with_cover(cover),
+				Err(e) => {
+					error::exit(CliError::from_argument_file(path, e.into()));
+				}
+			}
+		}
+		if let Some(text) = &args.auto_fade {
+			let Some(seconds) = parse_duration(text) else {
+				error::exit(CliError::invalid_input(format!(
+					"invalid --auto-fade duration '{}' (e.g., 200ms)",
+					text
+				)));
+			};
+			pipeline = pipeline.with_auto_fade(seconds);
+		}
+		if args.ss.is_some() || args.to.is_some() || args.duration.is_some() {
+			let time = |flag: &str, text: &Option<String>| match text.as_deref().map(parse_duration) {
+				Some(None) => {
+					error::exit(CliError::invalid_input(format!(
+						"invalid {} time '{}'",
+						flag,
+						text.as_deref().unwrap_or("")
+					)));
+				}
+				Some(Some(seconds)) => Some(seconds),
+				None => None,
+			};
+			let start = time("--ss", &args.ss).unwrap_or(0.0);
+			let end = time("--to", &args.to).or(time("--duration", &args.duration).map(|d| start + d));
+			pipeline = pipeline.with_trim(start, end);
+		}
+		if let Some(text) = &args.loop_count {
+			let passes = match text.as_str() {
+				"inf" | "-1" => None,
+				n => match n.parse::<u32>() {
+					Ok(n) => Some(n),
+					Err(_) => {
+						error::exit(CliError::invalid_input(format!(
+							"invalid --loop count '{}' (a number or 'inf')",
+							text
+						)));
+					}
+				},
+			};
+			pipeline = pipeline.with_loop(passes);
+		}
+		if let Some(path) = &args.overlay {
+			let position =
+				args.overlay_at.split_once(',').and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)));
+			let Some((x, y)) = position else {
+				error::exit(CliError::invalid_input(format!(
+					"invalid --overlay-at '{}' (e.g., 10,20)",
+					args.overlay_at
+				)));
+			};
+			pipeline = pipeline.with_overlay(path.clone(), x, y, args.overlay_alpha);
+		}
+		if let Some(graph) = &args.filter_graph {
+			pipeline = pipeline.with_filter_graph(graph.clone()).with_inputs(args.more_inputs.clone());
+		}
+		if let Some(format) = format {
+			pipeline = pipeline.with_format(format);
+		}
+		if let Some(format) = input_format {
+			pipeline = pipeline.with_input_format(format);
+		}
+		if let Some(sink) = sink {
+			pipeline = pipeline.with_sink(sink);
+		}
+		let progress = match args.progress.as_deref() {
+			Some(name) => ProgressMode::from_name(name).unwrap_or_else(|| {
+				error::exit(CliError::invalid_input(format!(
+					"unknown --progress '{}' (line, json or off)",
+					name
+				)));
+			}),
+			None if args.quiet => ProgressMode::Off,
+			None if std::io::stderr().is_terminal() => ProgressMode::Line,
+			None => ProgressMode::Off,
+		};
+		pipeline = pipeline.with_progress(progress).with_threads(args.threads());
+		pipeline = pipeline.with_replaygain(args.replaygain).with_reverse(args.reverse);
+		pipeline = pipeline.with_tee(args.more_outputs.clone());
+		// with -o null this times decoding (and any transforms) alone
+		let benchmark = args.benchmark.then(|| Arc::new(Benchmark::start()));
+		if let Some(benchmark) = &benchmark {
+			pipeline = pipeline.with_benchmark(benchmark.clone());
+		}
+		let result = pipeline.run();
+		if let Some(benchmark) = benchmark.filter(|_| result.is_ok()) {
+			let report = benchmark.report();
+			eprintln!("{}", if args.json { report.to_json() } else { report.to_text() });
+		}
+		result
 	};
 
 	match result {
 		Ok(()) => {
-			if !args.show {
-				if let Some(output) = &args.output {
+			// stdout carries the output itself when it is a pipe
+			if !args.show
+				&& let Some(output) = args.output.as_deref().filter(|output| !is_pipe(output))
+			{
+				if skipped {
+					println!("skipped: {} exists", output);
+				} else {
 					println!("ok: {} -> {}", args.input, output);
+					for output in &args.more_outputs {
+						println!("ok: {} -> {}", args.input, output);
+					}
 				}
 			}
 		}
 		Err(e) => {
-			eprintln!("Error: {}", e);
-			std::process::exit(1);
+			error::exit(e);
+		}
+	}
+}
+
+fn run_tool(result: std::io::Result<()>) {
+	if let Err(e) = result {
+		error::exit(e);
+	}
+}
+
+fn run_watch(
+	args: &Args,
+	dir: &str,
+	codec: Option<CodecId>,
+	container: Option<MediaType>,
+) -> std::io::Result<()> {
+	let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+	if !is_directory(dir) {
+		return Err(invalid(format!("--watch needs a directory, got '{}'", dir)));
+	}
+	let Some(output_dir) = args.output.clone() else {
+		return Err(invalid("--watch needs -o or an output in the config file".to_string()));
+	};
+	if is_pipe(&output_dir) || is_template(&output_dir) {
+		return Err(invalid("--watch writes into an output directory".to_string()));
+	}
+	let settle = parse_duration(&args.settle)
+		.filter(|seconds| *seconds >= 0.0)
+		.ok_or_else(|| invalid(format!("invalid --settle duration '{}' (e.g., 2s)", args.settle)))?;
+
+	let mut watcher = Watcher::new(dir, output_dir, args.transforms.clone())
+		.with_overwrite(args.overwrite())
+		.with_recursive(args.recursive)
+		.with_extensions(args.extensions.clone())
+		.with_settle(std::time::Duration::from_secs_f64(settle));
+	if let Some(codec) = codec {
+		watcher = watcher.with_codec(codec);
+	}
+	if let Some(container) = container {
+		watcher = watcher.with_output_format(container);
+	}
+	if let Some(name) = &args.format {
+		match MediaType::from_name(name) {
+			MediaType::Unknown => {
+				return Err(invalid(format!("unknown format '{}' (wav, flac, y4m, avi, mp4)", name)));
+			}
+			format => watcher = watcher.with_format(format),
 		}
 	}
+	watcher.run()
 }
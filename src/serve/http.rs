@@ -0,0 +1,152 @@
+use crate::io::{IoError, IoResult};
+use std::io::{BufRead, BufReader, Read, Write};
+
+const MAX_HEADER_LINES: usize = 64;
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+	pub method: String,
+	pub path: String,
+	pub query: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl Request {
+	pub fn new(method: &str, target: &str) -> Self {
+		let (path, query) = match target.split_once('?') {
+			Some((path, query)) => (path, parse_query(query)),
+			None => (target, Vec::new()),
+		};
+		Self { method: method.to_string(), path: path.to_string(), query, body: Vec::new() }
+	}
+
+	pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+		self.body = body.into();
+		self
+	}
+
+	pub fn query_param(&self, key: &str) -> Option<&str> {
+		self.query.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+	}
+
+	pub fn body_str(&self) -> IoResult<&str> {
+		core::str::from_utf8(&self.body).map_err(|_| IoError::invalid_data("request body is not UTF-8"))
+	}
+
+	pub fn read_from<R: Read>(stream: R) -> IoResult<Self> {
+		let mut reader = BufReader::new(stream);
+
+		let mut line = String::new();
+		reader.read_line(&mut line)?;
+		let mut parts = line.split_whitespace();
+		let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+			return Err(IoError::invalid_data("malformed HTTP request line"));
+		};
+		let mut request = Self::new(method, target);
+
+		let mut content_length = 0;
+		for _ in 0..MAX_HEADER_LINES {
+			let mut header = String::new();
+			reader.read_line(&mut header)?;
+			let header = header.trim_end();
+			if header.is_empty() {
+				break;
+			}
+			if let Some((name, value)) = header.split_once(':')
+				&& name.trim().eq_ignore_ascii_case("content-length")
+			{
+				content_length = value
+					.trim()
+					.parse()
+					.map_err(|_| IoError::invalid_data("invalid Content-Length header"))?;
+			}
+		}
+
+		if content_length > MAX_BODY_SIZE {
+			return Err(IoError::invalid_data("request body too large"));
+		}
+		request.body = vec![0; content_length];
+		reader.read_exact(&mut request.body)?;
+
+		Ok(request)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+	pub status: u16,
+	pub body: String,
+}
+
+impl Response {
+	pub fn json(status: u16, body: impl Into<String>) -> Self {
+		Self { status, body: body.into() }
+	}
+
+	pub fn error(status: u16, message: &str) -> Self {
+		Self::json(status, format!("{{\"error\":\"{}\"}}", crate::io::json::escape(message)))
+	}
+
+	pub fn write_to<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+		write!(
+			writer,
+			"HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+			self.status,
+			reason_phrase(self.status),
+			self.body.len(),
+			self.body
+		)?;
+		writer.flush()
+	}
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+	match status {
+		200 => "OK",
+		201 => "Created",
+		400 => "Bad Request",
+		403 => "Forbidden",
+		404 => "Not Found",
+		405 => "Method Not Allowed",
+		500 => "Internal Server Error",
+		_ => "Unknown",
+	}
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+	query
+		.split('&')
+		.filter(|pair| !pair.is_empty())
+		.map(|pair| {
+			let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+			(percent_decode(key), percent_decode(value))
+		})
+		.collect()
+}
+
+pub fn percent_decode(text: &str) -> String {
+	let bytes = text.as_bytes();
+	let mut out = Vec::with_capacity(bytes.len());
+	let mut idx = 0;
+
+	while idx < bytes.len() {
+		match bytes[idx] {
+			b'+' => out.push(b' '),
+			b'%' if idx + 2 < bytes.len() => {
+				let hex = core::str::from_utf8(&bytes[idx + 1..idx + 3]).ok();
+				match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+					Some(byte) => {
+						out.push(byte);
+						idx += 2;
+					}
+					None => out.push(b'%'),
+				}
+			}
+			b => out.push(b),
+		}
+		idx += 1;
+	}
+
+	String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,136 @@
+use crate::container::MediaType;
+use crate::io::json::{self, JsonValue};
+use crate::io::{IoError, IoResult, PathPolicy};
+use crate::transcode::Transcoder;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobSpec {
+	pub input: String,
+	pub output: String,
+	pub transforms: Vec<String>,
+	pub priority: i32,
+}
+
+impl JobSpec {
+	// {"input": "in.wav", "output": "out.wav", "transforms": ["volume=2"], "priority": 5}
+	pub fn from_json(text: &str) -> IoResult<Self> {
+		Self::from_value(&json::parse(text)?)
+	}
+
+	pub fn from_value(root: &JsonValue) -> IoResult<Self> {
+		let field = |key: &str| root.get(key).and_then(JsonValue::as_str).map(str::to_string);
+
+		let input = field("input").ok_or_else(|| IoError::invalid_data("job requires 'input'"))?;
+		let output = field("output").ok_or_else(|| IoError::invalid_data("job requires 'output'"))?;
+
+		let mut transforms = Vec::new();
+		if let Some(items) = root.get("transforms").and_then(JsonValue::as_array) {
+			for item in items {
+				let spec =
+					item.as_str().ok_or_else(|| IoError::invalid_data("transforms must be strings"))?;
+				transforms.push(spec.to_string());
+			}
+		}
+
+		let priority = root.get("priority").and_then(JsonValue::as_f64).unwrap_or(0.0) as i32;
+
+		Ok(Self { input, output, transforms, priority })
+	}
+
+	pub fn class(&self) -> JobClass {
+		if MediaType::from_extension(&self.input).is_video() {
+			JobClass::Video
+		} else {
+			JobClass::Audio
+		}
+	}
+
+	pub fn run(&self, path_policy: Arc<dyn PathPolicy>) -> std::io::Result<()> {
+		Transcoder::new(self.input.clone(), Some(self.output.clone()), false, self.transforms.clone())
+			.with_path_policy(path_policy)
+			.run()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobClass {
+	Audio,
+	Video,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobState {
+	Queued,
+	Running,
+	Done,
+	Failed(String),
+}
+
+impl JobState {
+	pub fn name(&self) -> &'static str {
+		match self {
+			JobState::Queued => "queued",
+			JobState::Running => "running",
+			JobState::Done => "done",
+			JobState::Failed(_) => "failed",
+		}
+	}
+
+	pub fn from_name(name: &str, error: Option<&str>) -> Option<Self> {
+		match name {
+			"queued" => Some(JobState::Queued),
+			"running" => Some(JobState::Running),
+			"done" => Some(JobState::Done),
+			"failed" => Some(JobState::Failed(error.unwrap_or_default().to_string())),
+			_ => None,
+		}
+	}
+
+	pub fn is_finished(&self) -> bool {
+		matches!(self, JobState::Done | JobState::Failed(_))
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+	pub id: u64,
+	pub spec: JobSpec,
+	pub state: JobState,
+}
+
+impl Job {
+	pub fn to_json(&self) -> String {
+		let transforms: Vec<String> =
+			self.spec.transforms.iter().map(|t| format!("\"{}\"", json::escape(t))).collect();
+		let mut out = format!(
+			"{{\"id\":{},\"status\":\"{}\",\"priority\":{},\"input\":\"{}\",\"output\":\"{}\",\"transforms\":[{}]",
+			self.id,
+			self.state.name(),
+			self.spec.priority,
+			json::escape(&self.spec.input),
+			json::escape(&self.spec.output),
+			transforms.join(",")
+		);
+		if let JobState::Failed(message) = &self.state {
+			out.push_str(&format!(",\"error\":\"{}\"", json::escape(message)));
+		}
+		out.push('}');
+		out
+	}
+
+	pub fn from_value(value: &JsonValue) -> IoResult<Self> {
+		let id = value
+			.get("id")
+			.and_then(JsonValue::as_f64)
+			.ok_or_else(|| IoError::invalid_data("job requires 'id'"))?;
+		let error = value.get("error").and_then(JsonValue::as_str);
+		let state = value
+			.get("status")
+			.and_then(JsonValue::as_str)
+			.and_then(|name| JobState::from_name(name, error))
+			.ok_or_else(|| IoError::invalid_data("job has an unknown status"))?;
+		let spec = JobSpec::from_value(value)?;
+		Ok(Self { id: id as u64, spec, state })
+	}
+}
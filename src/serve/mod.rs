@@ -0,0 +1,195 @@
+pub mod http;
+pub mod job;
+pub mod queue;
+
+pub use http::{Request, Response};
+pub use job::{Job, JobClass, JobSpec, JobState};
+pub use queue::{ConcurrencyLimits, JobQueue};
+
+use crate::core::log;
+use crate::io::{IoErrorKind, PathAccess, PathPolicy, RootedPolicy};
+use crate::show::{Show, ShowOptions};
+use clap::Parser;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Parser, Debug)]
+#[command(name = "ffmpreg serve", bin_name = "ffmpreg serve", about = "Run the HTTP job API")]
+pub struct ServeArgs {
+	#[arg(
+		long,
+		value_name = "ADDR",
+		default_value = "127.0.0.1:8080",
+		help = "Address to listen on"
+	)]
+	pub listen: String,
+
+	#[arg(long, value_name = "FILE", help = "Persist the job queue here and resume it on restart")]
+	pub state: Option<String>,
+
+	#[arg(
+		long = "audio-jobs",
+		value_name = "N",
+		value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+		default_value = "2",
+		help = "Concurrent audio jobs"
+	)]
+	pub audio_jobs: usize,
+
+	#[arg(
+		long = "video-jobs",
+		value_name = "N",
+		value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+		default_value = "1",
+		help = "Concurrent video jobs"
+	)]
+	pub video_jobs: usize,
+
+	#[arg(
+		long = "root",
+		value_name = "DIR",
+		help = "Only read and write files under DIR (multiple allowed)"
+	)]
+	pub roots: Vec<String>,
+}
+
+impl ServeArgs {
+	pub fn run(&self) -> std::io::Result<()> {
+		let limits = ConcurrencyLimits { audio: self.audio_jobs, video: self.video_jobs };
+		let mut queue = JobQueue::new().with_limits(limits);
+		if !self.roots.is_empty() {
+			queue = queue.with_path_policy(self.path_policy()?);
+		}
+		if let Some(path) = &self.state {
+			queue = queue.with_state_file(path).map_err(std::io::Error::from)?;
+		}
+		queue.dispatch();
+
+		let server = Server::bind(&self.listen)?.with_queue(queue);
+		println!("listening on http://{}", server.local_addr()?);
+		server.run()
+	}
+
+	fn path_policy(&self) -> std::io::Result<Arc<dyn PathPolicy>> {
+		let mut policy = RootedPolicy::new();
+		for root in &self.roots {
+			policy = policy.with_root(root).map_err(std::io::Error::from)?;
+		}
+		Ok(Arc::new(policy))
+	}
+}
+
+pub struct Server {
+	listener: TcpListener,
+	jobs: JobQueue,
+}
+
+impl Server {
+	pub fn bind(addr: &str) -> std::io::Result<Self> {
+		let listener = TcpListener::bind(addr)?;
+		Ok(Self { listener, jobs: JobQueue::new() })
+	}
+
+	pub fn with_queue(mut self, jobs: JobQueue) -> Self {
+		self.jobs = jobs;
+		self
+	}
+
+	pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+		self.listener.local_addr()
+	}
+
+	pub fn jobs(&self) -> &JobQueue {
+		&self.jobs
+	}
+
+	pub fn run(&self) -> std::io::Result<()> {
+		for stream in self.listener.incoming() {
+			let stream = match stream {
+				Ok(stream) => stream,
+				Err(e) => {
+					log::warn("serve", format_args!("failed to accept connection: {}", e));
+					continue;
+				}
+			};
+			let jobs = self.jobs.clone();
+			std::thread::spawn(move || handle_connection(stream, &jobs));
+		}
+		Ok(())
+	}
+}
+
+fn handle_connection(mut stream: TcpStream, jobs: &JobQueue) {
+	let response = match Request::read_from(&mut stream) {
+		Ok(request) => route(&request, jobs),
+		Err(e) => Response::error(400, &e.to_string()),
+	};
+	if let Err(e) = response.write_to(&mut stream) {
+		log::warn("serve", format_args!("failed to write response: {}", e));
+	}
+}
+
+// POST /jobs          submit {"input","output","transforms","priority"}
+// GET  /jobs          list all jobs
+// GET  /jobs/{id}     poll one job
+// GET  /probe?path=[&frames=N]   show output as JSON
+pub fn route(request: &Request, jobs: &JobQueue) -> Response {
+	let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+
+	match (request.method.as_str(), segments.as_slice()) {
+		("GET", ["health"]) => Response::json(200, "{\"status\":\"ok\"}"),
+		("GET", ["jobs"]) => {
+			let items: Vec<String> = jobs.list().iter().map(Job::to_json).collect();
+			Response::json(200, format!("[{}]", items.join(",")))
+		}
+		("POST", ["jobs"]) => submit_job(request, jobs),
+		("GET", ["jobs", id]) => match id.parse().ok().and_then(|id| jobs.get(id)) {
+			Some(job) => Response::json(200, job.to_json()),
+			None => Response::error(404, "job not found"),
+		},
+		("GET", ["probe"]) => probe(request, jobs),
+		(_, ["jobs"]) | (_, ["jobs", _]) | (_, ["probe"]) | (_, ["health"]) => {
+			Response::error(405, "method not allowed")
+		}
+		_ => Response::error(404, "not found"),
+	}
+}
+
+fn submit_job(request: &Request, jobs: &JobQueue) -> Response {
+	let spec = match request.body_str().and_then(JobSpec::from_json) {
+		Ok(spec) => spec,
+		Err(e) => return Response::error(400, &e.to_string()),
+	};
+
+	// refuse up front instead of queueing a job that can only fail
+	let policy = jobs.path_policy();
+	let allowed = policy
+		.check(Path::new(&spec.input), PathAccess::Read)
+		.and_then(|_| policy.check(Path::new(&spec.output), PathAccess::Write));
+	if let Err(e) = allowed {
+		return Response::error(403, &e.to_string());
+	}
+
+	match jobs.submit(spec) {
+		Ok(job) => Response::json(201, job.to_json()),
+		Err(e) => Response::error(500, &e.to_string()),
+	}
+}
+
+fn probe(request: &Request, jobs: &JobQueue) -> Response {
+	let Some(path) = request.query_param("path") else {
+		return Response::error(400, "probe requires a 'path' query parameter");
+	};
+
+	let frame_limit = request.query_param("frames").and_then(|n| n.parse().ok()).unwrap_or(0);
+	let show = Show::new(path.to_string(), ShowOptions { frame_limit, ..ShowOptions::default() })
+		.with_path_policy(jobs.path_policy());
+	match show.to_json() {
+		Ok(json) => Response::json(200, json),
+		Err(e) if matches!(e.kind(), IoErrorKind::PermissionDenied) => {
+			Response::error(403, &e.to_string())
+		}
+		Err(e) => Response::error(400, &e.to_string()),
+	}
+}
@@ -0,0 +1,236 @@
+use super::job::{Job, JobClass, JobSpec, JobState};
+use crate::core::log;
+use crate::io::json::{self, JsonValue};
+use crate::io::{AllowAll, IoError, IoResult, PathPolicy};
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// how many jobs of each class run at once. 0 holds that class's jobs in the
+// queue; `ffmpreg serve` refuses it, since a server that never starts them is
+// a mistake there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+	pub audio: usize,
+	pub video: usize,
+}
+
+impl ConcurrencyLimits {
+	pub fn for_class(&self, class: JobClass) -> usize {
+		match class {
+			JobClass::Audio => self.audio,
+			JobClass::Video => self.video,
+		}
+	}
+}
+
+impl Default for ConcurrencyLimits {
+	fn default() -> Self {
+		Self { audio: 2, video: 1 }
+	}
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+	next_id: u64,
+	jobs: Vec<Job>,
+}
+
+impl QueueState {
+	fn running(&self, class: JobClass) -> usize {
+		self.jobs.iter().filter(|j| j.state == JobState::Running && j.spec.class() == class).count()
+	}
+
+	// highest priority first, then submission order
+	fn next_runnable(&self, limits: &ConcurrencyLimits) -> Option<usize> {
+		let mut best: Option<usize> = None;
+		for (idx, job) in self.jobs.iter().enumerate() {
+			if job.state != JobState::Queued {
+				continue;
+			}
+			let class = job.spec.class();
+			if self.running(class) >= limits.for_class(class) {
+				continue;
+			}
+			let better = best.is_none_or(|b| job.spec.priority > self.jobs[b].spec.priority);
+			if better {
+				best = Some(idx);
+			}
+		}
+		best
+	}
+
+	fn to_json(&self) -> String {
+		let jobs: Vec<String> = self.jobs.iter().map(Job::to_json).collect();
+		format!("{{\"next_id\":{},\"jobs\":[{}]}}", self.next_id, jobs.join(","))
+	}
+
+	fn from_json(text: &str) -> IoResult<Self> {
+		let root = json::parse(text)?;
+		let mut state = Self::default();
+
+		let items = root
+			.get("jobs")
+			.and_then(JsonValue::as_array)
+			.ok_or_else(|| IoError::invalid_data("queue state requires a 'jobs' array"))?;
+		for item in items {
+			let mut job = Job::from_value(item)?;
+			// a job that was running when the process died is started again
+			if job.state == JobState::Running {
+				job.state = JobState::Queued;
+			}
+			state.jobs.push(job);
+		}
+
+		let max_id = state.jobs.iter().map(|j| j.id).max().unwrap_or(0);
+		let next_id = root.get("next_id").and_then(JsonValue::as_f64).unwrap_or(0.0) as u64;
+		state.next_id = next_id.max(max_id);
+		Ok(state)
+	}
+}
+
+// shared between connection threads and the threads running jobs. every state
+// change is written to the state file (when set) so a restart picks up where
+// the previous process stopped.
+#[derive(Debug, Clone)]
+pub struct JobQueue {
+	state: Arc<Mutex<QueueState>>,
+	limits: ConcurrencyLimits,
+	state_path: Option<PathBuf>,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl Default for JobQueue {
+	fn default() -> Self {
+		Self {
+			state: Arc::default(),
+			limits: ConcurrencyLimits::default(),
+			state_path: None,
+			path_policy: Arc::new(AllowAll),
+		}
+	}
+}
+
+impl JobQueue {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	pub fn with_limits(mut self, limits: ConcurrencyLimits) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> IoResult<Self> {
+		let path = path.into();
+		if path.exists() {
+			let text = std::fs::read_to_string(&path)?;
+			*self.state.lock().unwrap() = QueueState::from_json(&text)?;
+		}
+		self.state_path = Some(path);
+		Ok(self)
+	}
+
+	pub fn limits(&self) -> ConcurrencyLimits {
+		self.limits
+	}
+
+	pub fn path_policy(&self) -> Arc<dyn PathPolicy> {
+		self.path_policy.clone()
+	}
+
+	pub fn submit(&self, spec: JobSpec) -> IoResult<Job> {
+		let job = {
+			let mut state = self.state.lock().unwrap();
+			state.next_id += 1;
+			let job = Job { id: state.next_id, spec, state: JobState::Queued };
+			state.jobs.push(job.clone());
+			self.persist(&state)?;
+			job
+		};
+		self.dispatch();
+		Ok(job)
+	}
+
+	pub fn get(&self, id: u64) -> Option<Job> {
+		self.state.lock().unwrap().jobs.iter().find(|j| j.id == id).cloned()
+	}
+
+	pub fn list(&self) -> Vec<Job> {
+		self.state.lock().unwrap().jobs.clone()
+	}
+
+	// queued jobs in the order they will be started
+	pub fn pending(&self) -> Vec<Job> {
+		let mut pending: Vec<Job> =
+			self.list().into_iter().filter(|j| j.state == JobState::Queued).collect();
+		pending.sort_by(|a, b| b.spec.priority.cmp(&a.spec.priority).then(a.id.cmp(&b.id)));
+		pending
+	}
+
+	pub fn is_idle(&self) -> bool {
+		self.state.lock().unwrap().jobs.iter().all(|j| j.state.is_finished())
+	}
+
+	// start as many queued jobs as the class limits allow
+	pub fn dispatch(&self) {
+		let mut state = self.state.lock().unwrap();
+
+		while let Some(idx) = state.next_runnable(&self.limits) {
+			state.jobs[idx].state = JobState::Running;
+			let job = state.jobs[idx].clone();
+
+			// a panicking job fails like any other, so its class slot is freed
+			let queue = self.clone();
+			std::thread::spawn(move || {
+				let run = AssertUnwindSafe(|| job.spec.run(queue.path_policy()));
+				let result = match std::panic::catch_unwind(run) {
+					Ok(Ok(())) => JobState::Done,
+					Ok(Err(e)) => JobState::Failed(e.to_string()),
+					Err(panic) => JobState::Failed(panic_message(panic.as_ref())),
+				};
+				queue.finish(job.id, result);
+			});
+		}
+
+		if let Err(e) = self.persist(&state) {
+			log::warn("serve", format_args!("failed to save job queue: {}", e));
+		}
+	}
+
+	fn finish(&self, id: u64, result: JobState) {
+		{
+			let mut state = self.state.lock().unwrap();
+			if let Some(job) = state.jobs.iter_mut().find(|j| j.id == id) {
+				job.state = result;
+			}
+		}
+		self.dispatch();
+	}
+
+	fn persist(&self, state: &QueueState) -> IoResult<()> {
+		let Some(path) = &self.state_path else {
+			return Ok(());
+		};
+		// write then rename so a crash never leaves a half-written state file
+		let tmp = path.with_extension("tmp");
+		std::fs::write(&tmp, state.to_json())?;
+		std::fs::rename(&tmp, path)?;
+		Ok(())
+	}
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+	let message = panic
+		.downcast_ref::<&str>()
+		.copied()
+		.or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+		.unwrap_or("unknown cause");
+	format!("job panicked: {}", message)
+}
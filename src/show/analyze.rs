@@ -1,13 +1,23 @@
 use crate::codecs::{PcmDecoder, RawVideoDecoder};
+use crate::container::metadata::{Disposition, Id3v2Tag};
+use crate::container::ogg::OggCodec;
+use crate::container::riff;
 use crate::container::{
-	AviReader, FlacReader, Mp4Reader, WavFormat, WavReader, Y4mFormat, Y4mReader,
+	AviReader, FlacReader, MediaType, Mp3Reader, Mp4Format, Mp4Reader, OggReader, WavFormat,
+	WavReader, Y4mFormat, Y4mReader,
 };
-use crate::core::{Decoder, Demuxer};
-use crate::io::{IoResult, MediaSeek, SeekFrom};
+use crate::core::{Decoder, Demuxer, Transform};
+use crate::io::{AllowAll, IoErrorKind, IoResult, MediaSeek, ReadPrimitives, SeekFrom};
+use crate::transcode::check::flac_frames;
+use crate::transcode::decode_audio;
+use crate::transform::{AStats, ChannelStats};
+use std::collections::BTreeMap;
 
 use super::format::bytes_to_hex;
+use super::packets::mp4_packets;
 use super::types::{
-	AudioStreamInfo, FileInfo, FrameInfo, MediaInfo, ShowOptions, StreamInfo, VideoStreamInfo,
+	AudioStreamInfo, FileInfo, FrameInfo, MediaInfo, PacketInfo, ShowOptions, StreamInfo,
+	VideoStreamInfo,
 };
 
 pub fn analyze_wav<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
@@ -19,13 +29,15 @@ where
 	let mut wav_reader = WavReader::new(input)?;
 	let format = wav_reader.format();
 
-	let duration = calculate_wav_duration(&format, file_size);
-	let stream = build_audio_stream(&format);
+	let duration = calculate_wav_duration(&wav_reader, file_size);
+	let stream = build_audio_stream(&format, measure_stats(path, opts)?);
 	let frames = collect_wav_frames(&mut wav_reader, &format, opts)?;
 
-	let file_info = FileInfo { path: path.to_string(), duration, size: file_size };
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Wav, duration, size: file_size };
+	let tags = riff_tags(path)?;
 
-	Ok(MediaInfo { file: file_info, streams: vec![stream], frames })
+	Ok(MediaInfo { file: file_info, streams: vec![stream], frames, packets: None, tags })
 }
 
 pub fn analyze_y4m<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
@@ -37,13 +49,46 @@ where
 	let mut y4m_reader = Y4mReader::new(input)?;
 	let format = y4m_reader.format();
 
-	let duration = calculate_y4m_duration(&format, file_size);
+	let duration = calculate_y4m_duration(&format, y4m_reader.header_size(), file_size);
 	let stream = build_video_stream(&format);
 	let frames = collect_y4m_frames(&mut y4m_reader, &format, opts)?;
 
-	let file_info = FileInfo { path: path.to_string(), duration, size: file_size };
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Y4m, duration, size: file_size };
 
-	Ok(MediaInfo { file: file_info, streams: vec![stream], frames })
+	Ok(MediaInfo {
+		file: file_info,
+		streams: vec![stream],
+		frames,
+		packets: None,
+		tags: BTreeMap::new(),
+	})
+}
+
+// `--count-frames`: a frame per packet of each stream. wav and y4m headers
+// only hint at how long they are, so their duration becomes what the packets
+// add up to; the other containers record theirs exactly already
+pub fn count_frames(info: &mut MediaInfo, packets: &[PacketInfo]) {
+	for stream in info.streams.iter_mut() {
+		let index = stream.index();
+		let count = packets.iter().filter(|packet| packet.stream_index == index).count() as u64;
+		match stream {
+			StreamInfo::Video(video) => video.frame_count = Some(count),
+			StreamInfo::Audio(audio) => audio.frame_count = Some(count),
+		}
+	}
+
+	info.file.duration = match (info.file.container, info.streams.first()) {
+		(MediaType::Wav, Some(StreamInfo::Audio(audio))) if audio.sample_rate > 0 => {
+			let bytes: u64 = packets.iter().map(|packet| packet.size as u64).sum();
+			let bytes_per_frame = (audio.channels as u64 * audio.bit_depth as u64 / 8).max(1);
+			(bytes / bytes_per_frame) as f64 / audio.sample_rate as f64
+		}
+		(MediaType::Y4m, Some(StreamInfo::Video(video))) if video.fps() > 0.0 => {
+			packets.len() as f64 / video.fps()
+		}
+		_ => info.file.duration,
+	};
 }
 
 fn measure_file_size<R: MediaSeek>(mut reader: R) -> IoResult<u64> {
@@ -52,27 +97,36 @@ fn measure_file_size<R: MediaSeek>(mut reader: R) -> IoResult<u64> {
 	Ok(size)
 }
 
-fn open_file(path: &str) -> IoResult<crate::cli::pipeline::FileAdapter> {
-	crate::cli::pipeline::FileAdapter::open(path)
+// LIST INFO and id3 tags of a wav or avi
+fn riff_tags(path: &str) -> IoResult<BTreeMap<String, String>> {
+	let metadata = riff::read_tags(&mut open_file(path)?)?;
+	Ok(metadata.tags.into_iter().collect())
 }
 
-fn calculate_wav_duration(format: &WavFormat, file_size: u64) -> f64 {
-	let header_size = 44u64;
-	let data_size = file_size.saturating_sub(header_size);
-	let bytes_per_second = format.sample_rate as u64 * format.bytes_per_frame() as u64;
+fn open_file(path: &str) -> IoResult<crate::io::FileAdapter> {
+	crate::io::FileAdapter::open(path)
+}
 
-	if bytes_per_second == 0 {
+// the data chunk's declared size, unless the file ends first: truncated,
+// or a streamed wav that leaves the size at 0xFFFFFFFF
+fn calculate_wav_duration<R: crate::io::MediaRead>(reader: &WavReader<R>, file_size: u64) -> f64 {
+	let format = reader.format();
+	let in_file =
+		file_size.saturating_sub(reader.data_offset()) / format.bytes_per_frame().max(1) as u64;
+	let frames = reader.total_frames().min(in_file);
+
+	if format.sample_rate == 0 {
 		return 0.0;
 	}
 
-	data_size as f64 / bytes_per_second as f64
+	frames as f64 / format.sample_rate as f64
 }
 
-fn calculate_y4m_duration(format: &Y4mFormat, file_size: u64) -> f64 {
-	let header_approx = 100u64;
+// every frame is a FRAME line, bare unless it carries parameters, and the picture
+fn calculate_y4m_duration(format: &Y4mFormat, header_size: u64, file_size: u64) -> f64 {
 	let frame_header = 6u64;
 	let frame_size = format.frame_size() as u64 + frame_header;
-	let data_size = file_size.saturating_sub(header_approx);
+	let data_size = file_size.saturating_sub(header_size);
 	let frame_count = data_size / frame_size;
 
 	let fps = format.framerate_num as f64 / format.framerate_den as f64;
@@ -84,18 +138,33 @@ fn calculate_y4m_duration(format: &Y4mFormat, file_size: u64) -> f64 {
 	frame_count as f64 / fps
 }
 
-fn build_audio_stream(format: &WavFormat) -> StreamInfo {
+fn build_audio_stream(format: &WavFormat, stats: Vec<ChannelStats>) -> StreamInfo {
 	let info = AudioStreamInfo {
 		index: 0,
-		codec: "pcm_s16le".to_string(),
+		codec: format.codec_id().name().to_string(),
 		sample_rate: format.sample_rate,
 		channels: format.channels,
 		bit_depth: format.bit_depth,
+		language: None,
+		disposition: Disposition::default(),
+		stats,
+		frame_count: None,
 	};
 
 	StreamInfo::Audio(info)
 }
 
+// runs the whole file through `AStats`; only asked for, since it decodes
+// every sample
+fn measure_stats(path: &str, opts: &ShowOptions) -> IoResult<Vec<ChannelStats>> {
+	if !opts.astats {
+		return Ok(Vec::new());
+	}
+	let mut astats = AStats::new();
+	decode_audio(path, &AllowAll, |frame| astats.apply(frame).map(drop))?;
+	Ok(astats.stats().to_vec())
+}
+
 fn build_video_stream(format: &Y4mFormat) -> StreamInfo {
 	let pix_fmt = resolve_pixel_format(format);
 	let frame_rate = format!("{}/{}", format.framerate_num, format.framerate_den);
@@ -113,6 +182,9 @@ fn build_video_stream(format: &Y4mFormat) -> StreamInfo {
 		aspect_ratio,
 		display_aspect,
 		field_order: field_order.to_string(),
+		language: None,
+		disposition: Disposition::default(),
+		frame_count: None,
 	};
 
 	StreamInfo::Video(info)
@@ -127,9 +199,9 @@ fn resolve_pixel_format(format: &Y4mFormat) -> &'static str {
 	use crate::container::y4m::Colorspace;
 
 	match colorspace {
-		Colorspace::C420 | Colorspace::C420jpeg | Colorspace::C420paldv | Colorspace::C420mpeg2 => {
-			"yuv420p"
-		}
+		Colorspace::C420 | Colorspace::C420paldv | Colorspace::C420mpeg2 => "yuv420p",
+		// full range, which ffmpeg marks in the name
+		Colorspace::C420jpeg => "yuvj420p",
 		Colorspace::C422 => "yuv422p",
 		Colorspace::C444 => "yuv444p",
 		Colorspace::Mono => "gray",
@@ -147,12 +219,18 @@ fn resolve_field_order(format: &Y4mFormat) -> &'static str {
 	}
 }
 
+// in lowest terms, 16:9 rather than 5120:2880
 fn calculate_display_aspect(format: &Y4mFormat) -> Option<String> {
 	let aspect = format.aspect_ratio?;
-	let dar_num = format.width * aspect.num;
-	let dar_den = format.height * aspect.den;
+	let dar_num = format.width as u64 * aspect.num as u64;
+	let dar_den = format.height as u64 * aspect.den as u64;
+	let divisor = gcd(dar_num, dar_den).max(1);
+
+	Some(format!("{}:{}", dar_num / divisor, dar_den / divisor))
+}
 
-	Some(format!("{}:{}", dar_num, dar_den))
+fn gcd(a: u64, b: u64) -> u64 {
+	if b == 0 { a } else { gcd(b, a % b) }
 }
 
 fn collect_wav_frames<R: crate::io::MediaRead>(
@@ -172,7 +250,11 @@ fn collect_wav_frames<R: crate::io::MediaRead>(
 			break;
 		}
 
-		let packet = reader.read_packet()?;
+		// a frame cut short ends the preview; counting frames warns about it
+		let packet = match reader.read_packet() {
+			Err(e) if matches!(e.kind(), IoErrorKind::UnexpectedEof) => break,
+			packet => packet?,
+		};
 
 		let Some(pkt) = packet else {
 			break;
@@ -215,7 +297,11 @@ fn collect_y4m_frames<R: crate::io::MediaRead>(
 			break;
 		}
 
-		let packet = reader.read_packet()?;
+		// a frame cut short ends the preview; counting frames warns about it
+		let packet = match reader.read_packet() {
+			Err(e) if matches!(e.kind(), IoErrorKind::UnexpectedEof) => break,
+			packet => packet?,
+		};
 
 		let Some(pkt) = packet else {
 			break;
@@ -238,7 +324,7 @@ fn collect_y4m_frames<R: crate::io::MediaRead>(
 	Ok(frames)
 }
 
-pub fn analyze_flac<R>(reader: R, path: &str, _opts: &ShowOptions) -> IoResult<MediaInfo>
+pub fn analyze_flac<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
 where
 	R: crate::io::MediaRead + MediaSeek,
 {
@@ -259,20 +345,128 @@ where
 		sample_rate: format.sample_rate,
 		channels: format.channels,
 		bit_depth: format.bits_per_sample as u16,
+		language: None,
+		disposition: Disposition::default(),
+		stats: measure_stats(path, opts)?,
+		frame_count: None,
 	});
 
-	let file_info = FileInfo { path: path.to_string(), duration, size: file_size };
-	Ok(MediaInfo { file: file_info, streams: vec![stream], frames: Vec::new() })
+	let tags = flac_reader.vorbis_comment().map(|comment| comment.to_metadata().tags);
+	let tags = tags.unwrap_or_default().into_iter().collect();
+	// the reader hands over fixed size chunks, so frames are found by their sync codes
+	let data = open_file(path)?.read_to_end()?;
+	let hex_preview_limit = 256.max(opts.hex_limit);
+	let spans = flac_frames(&data)?.2;
+	let frames = spans
+		.iter()
+		.take(opts.frame_limit)
+		.enumerate()
+		.map(|(index, span)| FrameInfo {
+			index: index as u64,
+			pts: index as i64,
+			keyframe: true,
+			size: span.end - span.start,
+			hex: bytes_to_hex(&data[span.start..span.end], hex_preview_limit),
+		})
+		.collect();
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Flac, duration, size: file_size };
+	Ok(MediaInfo { file: file_info, streams: vec![stream], frames, packets: None, tags })
+}
+
+// the duration is the info header's frame count when there is one, as players
+// take it, else the frames in the file
+pub fn analyze_mp3<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
+where
+	R: crate::io::MediaRead + MediaSeek,
+{
+	let file_size = measure_file_size(reader)?;
+	let mut mp3_reader = Mp3Reader::new(open_file(path)?)?;
+	let format = *mp3_reader.format();
+	let frames = match format.frames {
+		Some(frames) => frames as u64,
+		None => {
+			let mut counting = Mp3Reader::new(open_file(path)?)?;
+			let mut frames = 0u64;
+			while counting.read_packet()?.is_some() {
+				frames += 1;
+			}
+			frames
+		}
+	};
+	let duration = (frames * format.samples_per_frame() as u64) as f64 / format.sample_rate as f64;
+
+	let stream = StreamInfo::Audio(AudioStreamInfo {
+		index: 0,
+		codec: format!("mp{}", format.layer),
+		sample_rate: format.sample_rate,
+		channels: format.channels,
+		bit_depth: 0,
+		language: None,
+		disposition: Disposition::default(),
+		stats: measure_stats(path, opts)?,
+		frame_count: None,
+	});
+	let tags = mp3_reader.id3_tag().and_then(Id3v2Tag::parse).map(|tag| tag.to_metadata().tags);
+	let tags = tags.unwrap_or_default().into_iter().collect();
+	let frames = collect_packet_frames(&mut mp3_reader, 0, opts)?;
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Mp3, duration, size: file_size };
+	Ok(MediaInfo { file: file_info, streams: vec![stream], frames, packets: None, tags })
 }
 
-pub fn analyze_avi<R>(reader: R, path: &str, _opts: &ShowOptions) -> IoResult<MediaInfo>
+// the duration is the last granule, less the opus pre-skip
+pub fn analyze_ogg<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
+where
+	R: crate::io::MediaRead + MediaSeek,
+{
+	let file_size = measure_file_size(reader)?;
+	let mut ogg_reader = OggReader::new(open_file(path)?)?;
+	let format = *ogg_reader.format();
+	let mut counting = OggReader::new(open_file(path)?)?;
+	let mut granule = 0i64;
+	while let Some(page) = counting.read_page()? {
+		granule = granule.max(page.granule);
+	}
+	let duration =
+		(granule - format.pre_skip as i64).max(0) as f64 / format.granule_rate().max(1) as f64;
+
+	let codec = match format.codec {
+		OggCodec::Vorbis => "vorbis",
+		OggCodec::Opus => "opus",
+		OggCodec::Unknown => "unknown",
+	};
+	let stream = StreamInfo::Audio(AudioStreamInfo {
+		index: 0,
+		codec: codec.to_string(),
+		sample_rate: format.sample_rate,
+		channels: format.channels,
+		bit_depth: 0,
+		language: None,
+		disposition: Disposition::default(),
+		stats: measure_stats(path, opts)?,
+		frame_count: None,
+	});
+	let frames = collect_packet_frames(&mut ogg_reader, 0, opts)?;
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Ogg, duration, size: file_size };
+	Ok(MediaInfo {
+		file: file_info,
+		streams: vec![stream],
+		frames,
+		packets: None,
+		tags: BTreeMap::new(),
+	})
+}
+
+pub fn analyze_avi<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
 where
 	R: crate::io::MediaRead + MediaSeek,
 {
 	let file_size = measure_file_size(reader)?;
 	let input = open_file(path)?;
-	let avi_reader = AviReader::new(input)?;
-	let format = avi_reader.format();
+	let mut avi_reader = AviReader::new(input)?;
+	let format = avi_reader.format().clone();
 
 	let fps = if format.main_header.microseconds_per_frame > 0 {
 		1_000_000.0 / format.main_header.microseconds_per_frame as f64
@@ -296,6 +490,9 @@ where
 						aspect_ratio: None,
 						display_aspect: None,
 						field_order: "progressive".to_string(),
+						language: None,
+						disposition: Disposition::default(),
+						frame_count: None,
 					}));
 				}
 			}
@@ -307,6 +504,10 @@ where
 						sample_rate: af.samples_per_sec,
 						channels: af.channels as u8,
 						bit_depth: af.bits_per_sample,
+						language: None,
+						disposition: Disposition::default(),
+						stats: Vec::new(),
+						frame_count: None,
 					}));
 				}
 			}
@@ -314,11 +515,14 @@ where
 		}
 	}
 
-	let file_info = FileInfo { path: path.to_string(), duration, size: file_size };
-	Ok(MediaInfo { file: file_info, streams, frames: Vec::new() })
+	let frames = collect_packet_frames(&mut avi_reader, frame_stream(&streams, opts), opts)?;
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Avi, duration, size: file_size };
+	let tags = riff_tags(path)?;
+	Ok(MediaInfo { file: file_info, streams, frames, packets: None, tags })
 }
 
-pub fn analyze_mp4<R>(reader: R, path: &str, _opts: &ShowOptions) -> IoResult<MediaInfo>
+pub fn analyze_mp4<R>(reader: R, path: &str, opts: &ShowOptions) -> IoResult<MediaInfo>
 where
 	R: crate::io::MediaRead + MediaSeek,
 {
@@ -350,6 +554,9 @@ where
 					aspect_ratio: None,
 					display_aspect: None,
 					field_order: "progressive".to_string(),
+					language: Some(track.language.clone()),
+					disposition: track.disposition,
+					frame_count: None,
 				}));
 			}
 			crate::container::mp4::TrackType::Audio => {
@@ -359,12 +566,81 @@ where
 					sample_rate: track.sample_rate,
 					channels: track.channels as u8,
 					bit_depth: 16,
+					language: Some(track.language.clone()),
+					disposition: track.disposition,
+					stats: Vec::new(),
+					frame_count: None,
 				}));
 			}
 			_ => {}
 		}
 	}
 
-	let file_info = FileInfo { path: path.to_string(), duration, size: file_size };
-	Ok(MediaInfo { file: file_info, streams, frames: Vec::new() })
+	let frames = collect_mp4_frames(path, format, frame_stream(&streams, opts), opts)?;
+	let file_info =
+		FileInfo { path: path.to_string(), container: MediaType::Mp4, duration, size: file_size };
+	let tags = format.metadata.tags.clone().into_iter().collect();
+	Ok(MediaInfo { file: file_info, streams, frames, packets: None, tags })
+}
+
+// frames are listed for one stream: the one asked for, else the first
+fn frame_stream(streams: &[StreamInfo], opts: &ShowOptions) -> usize {
+	opts.stream_filter.or(streams.first().map(StreamInfo::index)).unwrap_or(0)
+}
+
+// the packets of one stream as they come, nothing decoded: flac frames and
+// avi chunks are each a frame already. pts counts the stream's frames, as
+// the avi reader's own pts is a byte position
+fn collect_packet_frames<D: Demuxer>(
+	demuxer: &mut D,
+	stream_index: usize,
+	opts: &ShowOptions,
+) -> IoResult<Vec<FrameInfo>> {
+	let mut frames = Vec::new();
+	let hex_preview_limit = 256.max(opts.hex_limit);
+	while frames.len() < opts.frame_limit {
+		let Some(pkt) = demuxer.read_packet()? else {
+			break;
+		};
+		if pkt.stream_index != stream_index {
+			continue;
+		}
+		let index = frames.len() as u64;
+		frames.push(FrameInfo {
+			index,
+			pts: index as i64,
+			keyframe: pkt.keyframe || demuxer.stream_count() == 1,
+			size: pkt.data.len(),
+			hex: bytes_to_hex(&pkt.data, hex_preview_limit),
+		});
+	}
+	Ok(frames)
+}
+
+// the mp4 reader does not hand packets over, so each sample is read from
+// where the sample tables put it
+fn collect_mp4_frames(
+	path: &str,
+	format: &Mp4Format,
+	stream_index: usize,
+	opts: &ShowOptions,
+) -> IoResult<Vec<FrameInfo>> {
+	let mut input = open_file(path)?;
+	let hex_preview_limit = 256.max(opts.hex_limit);
+	let samples =
+		mp4_packets(format).into_iter().filter(|packet| packet.stream_index == stream_index);
+	let mut frames = Vec::new();
+	for (index, sample) in samples.take(opts.frame_limit).enumerate() {
+		let mut data = vec![0u8; sample.size];
+		input.seek(SeekFrom::Start(sample.offset))?;
+		input.read_exact(&mut data)?;
+		frames.push(FrameInfo {
+			index: index as u64,
+			pts: sample.pts,
+			keyframe: sample.keyframe,
+			size: sample.size,
+			hex: bytes_to_hex(&data, hex_preview_limit),
+		});
+	}
+	Ok(frames)
 }
@@ -0,0 +1,154 @@
+use crate::io::json::escape;
+
+use super::types::PacketInfo;
+use super::units::format_thousands;
+
+// how `--bitrate` prints: the figures alone, with a bar per second under
+// them, or a row per second for a spreadsheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitrateMode {
+	#[default]
+	Summary,
+	Sparkline,
+	Csv,
+}
+
+impl BitrateMode {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"summary" => Some(BitrateMode::Summary),
+			"sparkline" => Some(BitrateMode::Sparkline),
+			"csv" => Some(BitrateMode::Csv),
+			_ => None,
+		}
+	}
+}
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const BARS_PER_LINE: usize = 60;
+
+// packet bytes summed into one bucket per second of the file, by pts
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitrateStats {
+	pub duration: f64,
+	pub bytes: Vec<u64>,
+}
+
+impl BitrateStats {
+	// `duration` is the container's; packets past it stretch it
+	pub fn from_packets<'a>(
+		packets: impl IntoIterator<Item = &'a PacketInfo>,
+		duration: f64,
+	) -> Self {
+		let mut bytes: Vec<u64> = Vec::new();
+		let mut duration = duration.max(0.0);
+		for packet in packets {
+			let second = packet.time.max(0.0) as usize;
+			if bytes.len() <= second {
+				bytes.resize(second + 1, 0);
+			}
+			bytes[second] += packet.size as u64;
+			duration = duration.max(packet.time);
+		}
+		bytes.resize((duration.ceil() as usize).max(bytes.len()), 0);
+		Self { duration, bytes }
+	}
+
+	// bits per second in each bucket; the last, when the file ends partway
+	// through it, over the part it covers
+	pub fn rates(&self) -> Vec<f64> {
+		let last = self.bytes.len().saturating_sub(1);
+		self
+			.bytes
+			.iter()
+			.enumerate()
+			.map(|(second, &bytes)| {
+				let span = if second == last { self.duration - second as f64 } else { 1.0 };
+				let span = if span > 0.0 && span < 1.0 { span } else { 1.0 };
+				bytes as f64 * 8.0 / span
+			})
+			.collect()
+	}
+
+	pub fn average(&self) -> f64 {
+		if self.duration <= 0.0 {
+			return 0.0;
+		}
+		self.bytes.iter().sum::<u64>() as f64 * 8.0 / self.duration
+	}
+
+	// the lowest and highest second, each with where it is
+	pub fn min(&self) -> Option<(usize, f64)> {
+		self.rates().into_iter().enumerate().min_by(|a, b| a.1.total_cmp(&b.1))
+	}
+
+	pub fn max(&self) -> Option<(usize, f64)> {
+		self.rates().into_iter().enumerate().max_by(|a, b| a.1.total_cmp(&b.1))
+	}
+}
+
+pub fn to_text(path: &str, stats: &BitrateStats, mode: BitrateMode) -> String {
+	if mode == BitrateMode::Csv {
+		return to_csv(stats);
+	}
+	let kbps = |bits: f64| format_thousands((bits / 1000.0).round() as u64);
+	let mut out = format!("{}\n  bitrate over {:.2} s\n", path, stats.duration);
+	let (Some((min_at, min)), Some((max_at, max))) = (stats.min(), stats.max()) else {
+		out.push_str("  no packets\n");
+		return out;
+	};
+	out.push_str(&format!(
+		"  min {} kb/s (at {} s)  avg {} kb/s  max {} kb/s (at {} s)\n",
+		kbps(min),
+		min_at,
+		kbps(stats.average()),
+		kbps(max),
+		max_at
+	));
+	if mode == BitrateMode::Sparkline {
+		out.push_str(&sparkline(&stats.rates(), max));
+	}
+	out
+}
+
+// a bar per second scaled to the busiest, a line a minute with its start
+fn sparkline(rates: &[f64], max: f64) -> String {
+	let mut out = String::new();
+	for (line, chunk) in rates.chunks(BARS_PER_LINE).enumerate() {
+		let bars: String = chunk
+			.iter()
+			.map(|&rate| {
+				let level = if max > 0.0 { rate / max * (BARS.len() - 1) as f64 } else { 0.0 };
+				BARS[(level.round() as usize).min(BARS.len() - 1)]
+			})
+			.collect();
+		out.push_str(&format!("  {:>5}s {}\n", line * BARS_PER_LINE, bars));
+	}
+	out
+}
+
+pub fn to_csv(stats: &BitrateStats) -> String {
+	let mut out = String::from("second,bytes,kbps\n");
+	for (second, (bytes, rate)) in stats.bytes.iter().zip(stats.rates()).enumerate() {
+		out.push_str(&format!("{},{},{:.3}\n", second, bytes, rate / 1000.0));
+	}
+	out
+}
+
+pub fn to_json(path: &str, stats: &BitrateStats) -> String {
+	let point = |value: Option<(usize, f64)>| match value {
+		Some((second, rate)) => format!("{{\"second\":{},\"kbps\":{:.3}}}", second, rate / 1000.0),
+		None => "null".to_string(),
+	};
+	let seconds: Vec<String> =
+		stats.rates().iter().map(|rate| format!("{:.3}", rate / 1000.0)).collect();
+	format!(
+		"{{\"file\":\"{}\",\"duration\":{:.6},\"avg_kbps\":{:.3},\"min\":{},\"max\":{},\"kbps\":[{}]}}",
+		escape(path),
+		stats.duration,
+		stats.average() / 1000.0,
+		point(stats.min()),
+		point(stats.max()),
+		seconds.join(",")
+	)
+}
@@ -0,0 +1,59 @@
+use crate::container::riff::{RiffChunk, fourcc_text};
+use crate::io::json::escape;
+
+use super::units::format_thousands;
+
+// `--chunks`: the chunk tree indented by nesting, one line per header,
+// with whatever is wrong with one on the line under it
+//
+//   RIFF 'WAVE'  offset 0  size 36,044
+//     fmt   offset 12  size 16
+//     data  offset 36  size 36,000
+pub fn to_text(path: &str, chunks: &[RiffChunk]) -> String {
+	let mut out = format!("{}\n", path);
+	for chunk in chunks {
+		let indent = "  ".repeat(chunk.depth + 1);
+		let name = match &chunk.form {
+			Some(form) => format!("{} '{}'", fourcc_text(&chunk.fourcc), fourcc_text(form)),
+			None => fourcc_text(&chunk.fourcc),
+		};
+		out.push_str(&format!(
+			"{}{:<4}  offset {}  size {}\n",
+			indent,
+			name,
+			chunk.offset,
+			format_thousands(chunk.size as u64)
+		));
+		if let Some(problem) = &chunk.problem {
+			out.push_str(&format!("{}  ! {}\n", indent, problem));
+		}
+	}
+	out
+}
+
+// the same flat list, `depth` giving the nesting
+pub fn to_json(path: &str, chunks: &[RiffChunk]) -> String {
+	let chunks: Vec<String> = chunks
+		.iter()
+		.map(|chunk| {
+			let form = match &chunk.form {
+				Some(form) => format!("\"{}\"", escape(&fourcc_text(form))),
+				None => "null".to_string(),
+			};
+			let problem = match &chunk.problem {
+				Some(problem) => format!("\"{}\"", escape(problem)),
+				None => "null".to_string(),
+			};
+			format!(
+				"{{\"fourcc\":\"{}\",\"form\":{},\"offset\":{},\"size\":{},\"depth\":{},\"problem\":{}}}",
+				escape(&fourcc_text(&chunk.fourcc)),
+				form,
+				chunk.offset,
+				chunk.size,
+				chunk.depth,
+				problem
+			)
+		})
+		.collect();
+	format!("{{\"file\":\"{}\",\"chunks\":[{}]}}", escape(path), chunks.join(","))
+}
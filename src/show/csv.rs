@@ -0,0 +1,132 @@
+use super::types::{FrameInfo, MediaInfo, PacketInfo, ShowOptions, StreamInfo};
+
+// one table for a spreadsheet: a row for the file, one per stream, one per
+// packet when they are listed, then one per frame, each filling the columns
+// that apply to it and leaving the rest empty. `kind` says which a row is
+const HEADER: [&str; 21] = [
+	"kind",
+	"index",
+	"media_type",
+	"codec",
+	"duration",
+	"size",
+	"sample_rate",
+	"channels",
+	"bit_depth",
+	"width",
+	"height",
+	"pix_fmt",
+	"frame_rate",
+	"language",
+	"default",
+	"pts",
+	"keyframe",
+	"path",
+	"stream_index",
+	"offset",
+	"dts",
+];
+
+pub fn to_csv(info: &MediaInfo, opts: &ShowOptions) -> String {
+	let mut out = row(HEADER.iter().map(|name| name.to_string()).collect());
+
+	let mut file = empty();
+	file[0] = "file".to_string();
+	file[4] = format!("{:.6}", info.file.duration);
+	file[5] = info.file.size.to_string();
+	file[17] = info.file.path.clone();
+	out.push_str(&row(file));
+
+	let streams = info.streams.iter().filter(|s| opts.stream_filter.is_none_or(|f| f == s.index()));
+	for stream in streams {
+		out.push_str(&row(stream_row(stream)));
+	}
+
+	for (index, packet) in info.packets.iter().flatten().enumerate() {
+		out.push_str(&row(packet_row(index, packet, &info.streams)));
+	}
+
+	let media_type = match info.streams.first() {
+		Some(StreamInfo::Video(_)) => "video",
+		_ => "audio",
+	};
+	for frame in &info.frames {
+		out.push_str(&row(frame_row(frame, media_type)));
+	}
+	out
+}
+
+fn empty() -> Vec<String> {
+	vec![String::new(); HEADER.len()]
+}
+
+fn stream_row(stream: &StreamInfo) -> Vec<String> {
+	let mut row = empty();
+	row[0] = "stream".to_string();
+	row[1] = stream.index().to_string();
+	match stream {
+		StreamInfo::Video(video) => {
+			row[2] = "video".to_string();
+			row[3] = video.codec.clone();
+			row[9] = video.width.to_string();
+			row[10] = video.height.to_string();
+			row[11] = video.pix_fmt.clone();
+			row[12] = video.frame_rate.clone();
+			row[13] = video.language.clone().unwrap_or_default();
+			row[14] = (video.disposition.default as u8).to_string();
+		}
+		StreamInfo::Audio(audio) => {
+			row[2] = "audio".to_string();
+			row[3] = audio.codec.clone();
+			row[6] = audio.sample_rate.to_string();
+			row[7] = audio.channels.to_string();
+			row[8] = audio.bit_depth.to_string();
+			row[13] = audio.language.clone().unwrap_or_default();
+			row[14] = (audio.disposition.default as u8).to_string();
+		}
+	}
+	row
+}
+
+fn packet_row(index: usize, packet: &PacketInfo, streams: &[StreamInfo]) -> Vec<String> {
+	let mut row = empty();
+	row[0] = "packet".to_string();
+	row[1] = index.to_string();
+	row[2] = match streams.iter().find(|stream| stream.index() == packet.stream_index) {
+		Some(StreamInfo::Video(_)) => "video".to_string(),
+		Some(StreamInfo::Audio(_)) => "audio".to_string(),
+		None => String::new(),
+	};
+	row[5] = packet.size.to_string();
+	row[15] = packet.pts.to_string();
+	row[16] = (packet.keyframe as u8).to_string();
+	row[18] = packet.stream_index.to_string();
+	row[19] = packet.offset.to_string();
+	row[20] = packet.dts.to_string();
+	row
+}
+
+fn frame_row(frame: &FrameInfo, media_type: &str) -> Vec<String> {
+	let mut row = empty();
+	row[0] = "frame".to_string();
+	row[1] = frame.index.to_string();
+	row[2] = media_type.to_string();
+	row[5] = frame.size.to_string();
+	row[15] = frame.pts.to_string();
+	row[16] = (frame.keyframe as u8).to_string();
+	row
+}
+
+fn row(fields: Vec<String>) -> String {
+	let fields: Vec<String> = fields.iter().map(|field| quote(field)).collect();
+	format!("{}\n", fields.join(","))
+}
+
+// rfc 4180: quoted when it holds a comma, quote or line break, quotes doubled
+fn quote(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
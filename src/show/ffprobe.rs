@@ -0,0 +1,282 @@
+use crate::container::MediaType;
+use crate::container::metadata::Disposition;
+use crate::io::json::escape;
+
+use super::types::{
+	AudioStreamInfo, FrameInfo, MediaInfo, PacketInfo, StreamInfo, VideoStreamInfo,
+};
+
+// `--show --json --compat ffprobe`: what `ffprobe -of json -show_frames
+// -show_streams -show_format` prints, key for key, so scripts written against
+// ffprobe read it unchanged. like ffprobe, sizes, rates and times are strings
+// and counts are numbers; fields ffmpreg has no value for are left out
+pub fn to_json(info: &MediaInfo) -> String {
+	let stream = info.streams.first();
+	let frames: Vec<String> = info.frames.iter().map(|frame| render_frame(frame, stream)).collect();
+	let streams: Vec<String> = info.streams.iter().map(render_stream).collect();
+	// with -show_packets too, ffprobe writes them first
+	let packets = match &info.packets {
+		Some(packets) => {
+			let packets: Vec<String> =
+				packets.iter().map(|packet| render_packet(packet, &info.streams)).collect();
+			format!("\"packets\":[{}],", packets.join(","))
+		}
+		None => String::new(),
+	};
+	format!(
+		"{{{}\"frames\":[{}],\"streams\":[{}],\"format\":{}}}",
+		packets,
+		frames.join(","),
+		streams.join(","),
+		render_format(info)
+	)
+}
+
+fn render_packet(packet: &PacketInfo, streams: &[StreamInfo]) -> String {
+	let stream = streams.iter().find(|stream| stream.index() == packet.stream_index);
+	let codec_type = match stream {
+		Some(StreamInfo::Video(_)) => "video",
+		Some(StreamInfo::Audio(_)) => "audio",
+		None => "data",
+	};
+	let mut out = format!(
+		"{{\"codec_type\":\"{}\",\"stream_index\":{},\"pts\":{},",
+		codec_type, packet.stream_index, packet.pts
+	);
+	let time_base = stream.and_then(time_base);
+	if let Some((num, den)) = time_base {
+		out.push_str(&format!("\"pts_time\":\"{:.6}\",", packet.pts as f64 * num as f64 / den as f64));
+	}
+	out.push_str(&format!("\"dts\":{},", packet.dts));
+	if let Some((num, den)) = time_base {
+		out.push_str(&format!("\"dts_time\":\"{:.6}\",", packet.dts as f64 * num as f64 / den as f64));
+	}
+	out.push_str(&format!(
+		"\"size\":\"{}\",\"pos\":\"{}\",\"flags\":\"{}__\"}}",
+		packet.size,
+		packet.offset,
+		if packet.keyframe { 'K' } else { '_' }
+	));
+	out
+}
+
+fn render_frame(frame: &FrameInfo, stream: Option<&StreamInfo>) -> String {
+	let mut out = String::from("{");
+	let media_type = match stream {
+		Some(StreamInfo::Video(_)) => "video",
+		_ => "audio",
+	};
+	out.push_str(&format!("\"media_type\":\"{}\",\"stream_index\":0,", media_type));
+	out.push_str(&format!("\"key_frame\":{},", frame.keyframe as u8));
+	out.push_str(&format!("\"pts\":{},", frame.pts));
+	if let Some((num, den)) = stream.and_then(time_base) {
+		let seconds = frame.pts as f64 * num as f64 / den as f64;
+		out.push_str(&format!("\"pts_time\":\"{:.6}\",", seconds));
+	}
+	out.push_str(&format!("\"pkt_size\":\"{}\"", frame.size));
+	match stream {
+		Some(StreamInfo::Video(video)) => out.push_str(&format!(
+			",\"width\":{},\"height\":{},\"pix_fmt\":\"{}\"",
+			video.width,
+			video.height,
+			escape(&video.pix_fmt)
+		)),
+		Some(StreamInfo::Audio(audio)) => {
+			out.push_str(&format!(",\"channels\":{}", audio.channels));
+			if let Some(bytes) = bytes_per_frame(audio) {
+				out.push_str(&format!(",\"nb_samples\":{}", frame.size / bytes));
+			}
+		}
+		None => {}
+	}
+	out.push('}');
+	out
+}
+
+fn render_stream(stream: &StreamInfo) -> String {
+	match stream {
+		StreamInfo::Video(video) => render_video_stream(video),
+		StreamInfo::Audio(audio) => render_audio_stream(audio),
+	}
+}
+
+fn render_video_stream(stream: &VideoStreamInfo) -> String {
+	let mut out = String::from("{");
+	out.push_str(&format!("\"index\":{},", stream.index));
+	out.push_str(&format!("\"codec_name\":\"{}\",", escape(codec_name(&stream.codec))));
+	out.push_str("\"codec_type\":\"video\",");
+	out.push_str(&format!("\"width\":{},\"height\":{},", stream.width, stream.height));
+	out.push_str(&format!("\"pix_fmt\":\"{}\",", escape(&stream.pix_fmt)));
+	out.push_str(&format!("\"field_order\":\"{}\",", field_order(&stream.field_order)));
+	if let Some(aspect) = &stream.aspect_ratio {
+		out.push_str(&format!("\"sample_aspect_ratio\":\"{}\",", escape(aspect)));
+	}
+	if let Some(aspect) = &stream.display_aspect {
+		out.push_str(&format!("\"display_aspect_ratio\":\"{}\",", escape(aspect)));
+	}
+	match frame_rate(&stream.frame_rate) {
+		Some((num, den)) => out.push_str(&format!(
+			"\"r_frame_rate\":\"{num}/{den}\",\"avg_frame_rate\":\"{num}/{den}\",\"time_base\":\"{den}/{num}\","
+		)),
+		None => out.push_str("\"r_frame_rate\":\"0/0\",\"avg_frame_rate\":\"0/0\","),
+	}
+	if let Some(count) = stream.frame_count {
+		out.push_str(&format!("\"nb_read_frames\":\"{}\",", count));
+	}
+	out.push_str(&render_disposition(&stream.disposition));
+	out.push_str(&render_tags(&stream.language));
+	out.push('}');
+	out
+}
+
+fn render_audio_stream(stream: &AudioStreamInfo) -> String {
+	let mut out = String::from("{");
+	out.push_str(&format!("\"index\":{},", stream.index));
+	out.push_str(&format!("\"codec_name\":\"{}\",", escape(codec_name(&stream.codec))));
+	out.push_str("\"codec_type\":\"audio\",");
+	if let Some(format) = stream.sample_fmt() {
+		out.push_str(&format!("\"sample_fmt\":\"{}\",", format));
+	}
+	out.push_str(&format!("\"sample_rate\":\"{}\",", stream.sample_rate));
+	out.push_str(&format!("\"channels\":{},", stream.channels));
+	if let Some(layout) = channel_layout(stream.channels) {
+		out.push_str(&format!("\"channel_layout\":\"{}\",", layout));
+	}
+	out.push_str(&format!("\"bits_per_sample\":{},", stream.bit_depth));
+	out.push_str("\"r_frame_rate\":\"0/0\",\"avg_frame_rate\":\"0/0\",");
+	if stream.sample_rate > 0 {
+		out.push_str(&format!("\"time_base\":\"1/{}\",", stream.sample_rate));
+	}
+	if let Some(count) = stream.frame_count {
+		out.push_str(&format!("\"nb_read_frames\":\"{}\",", count));
+	}
+	out.push_str(&render_disposition(&stream.disposition));
+	out.push_str(&render_tags(&stream.language));
+	out.push('}');
+	out
+}
+
+// every flag ffprobe lists, 0 for the ones ffmpreg does not track
+fn render_disposition(disposition: &Disposition) -> String {
+	let flags = [
+		("default", disposition.default),
+		("dub", false),
+		("original", false),
+		("comment", disposition.commentary),
+		("lyrics", false),
+		("karaoke", false),
+		("forced", false),
+		("hearing_impaired", false),
+		("visual_impaired", false),
+		("clean_effects", false),
+		("attached_pic", false),
+		("timed_thumbnails", false),
+	];
+	let fields: Vec<String> =
+		flags.iter().map(|(name, set)| format!("\"{}\":{}", name, *set as u8)).collect();
+	format!("\"disposition\":{{{}}}", fields.join(","))
+}
+
+// ffprobe leaves `tags` out when a stream has none
+fn render_tags(language: &Option<String>) -> String {
+	match language {
+		Some(language) => format!(",\"tags\":{{\"language\":\"{}\"}}", escape(language)),
+		None => String::new(),
+	}
+}
+
+fn render_format(info: &MediaInfo) -> String {
+	let (name, long_name) = format_names(info.file.container);
+	let mut out = String::from("{");
+	out.push_str(&format!("\"filename\":\"{}\",", escape(&info.file.path)));
+	out.push_str(&format!("\"nb_streams\":{},\"nb_programs\":0,", info.streams.len()));
+	out.push_str(&format!("\"format_name\":\"{}\",\"format_long_name\":\"{}\",", name, long_name));
+	out.push_str("\"start_time\":\"0.000000\",");
+	out.push_str(&format!("\"duration\":\"{:.6}\",", info.file.duration));
+	out.push_str(&format!("\"size\":\"{}\",", info.file.size));
+	if info.file.duration > 0.0 {
+		let bit_rate = (info.file.size as f64 * 8.0 / info.file.duration) as u64;
+		out.push_str(&format!("\"bit_rate\":\"{}\",", bit_rate));
+	}
+	out.push_str("\"probe_score\":100");
+	// ffprobe leaves `tags` out when there are none
+	if !info.tags.is_empty() {
+		let tags: Vec<String> = info
+			.tags
+			.iter()
+			.map(|(key, value)| format!("\"{}\":\"{}\"", escape(key), escape(value)))
+			.collect();
+		out.push_str(&format!(",\"tags\":{{{}}}", tags.join(",")));
+	}
+	out.push('}');
+	out
+}
+
+// the names ffprobe's demuxers go by
+fn format_names(container: MediaType) -> (&'static str, &'static str) {
+	match container {
+		MediaType::Wav => ("wav", "WAV / WAVE (Waveform Audio)"),
+		MediaType::Y4m => ("yuv4mpegpipe", "YUV4MPEG pipe"),
+		MediaType::Flac => ("flac", "raw FLAC"),
+		MediaType::Avi => ("avi", "AVI (Audio Video Interleaved)"),
+		MediaType::Mp4 => ("mov,mp4,m4a,3gp,3g2,mj2", "QuickTime / MOV"),
+		MediaType::Mp3 => ("mp3", "MP2/3 (MPEG audio layer 2/3)"),
+		MediaType::Ogg => ("ogg", "Ogg"),
+		MediaType::Unknown => ("unknown", "unknown"),
+	}
+}
+
+// "rawvideo (yuv420p)" and "pcm (tag=1)" name the codec first
+fn codec_name(codec: &str) -> &str {
+	codec.split_whitespace().next().unwrap_or(codec)
+}
+
+fn field_order(order: &str) -> &'static str {
+	match order {
+		"top field first" => "tt",
+		"bottom field first" => "bb",
+		"progressive" => "progressive",
+		_ => "unknown",
+	}
+}
+
+fn channel_layout(channels: u8) -> Option<&'static str> {
+	match channels {
+		1 => Some("mono"),
+		2 => Some("stereo"),
+		6 => Some("5.1"),
+		8 => Some("7.1"),
+		_ => None,
+	}
+}
+
+// "30000/1001" as it is, "29.97" as thousandths
+fn frame_rate(rate: &str) -> Option<(u64, u64)> {
+	if let Some((num, den)) = rate.split_once('/') {
+		return Some((num.parse().ok()?, den.parse().ok()?)).filter(|(_, den)| *den > 0);
+	}
+	let fps: f64 = rate.parse().ok()?;
+	if fps <= 0.0 {
+		return None;
+	}
+	if fps.fract() == 0.0 {
+		Some((fps as u64, 1))
+	} else {
+		Some(((fps * 1000.0).round() as u64, 1000))
+	}
+}
+
+// the unit of a stream's pts: a sample of audio, a frame of video
+fn time_base(stream: &StreamInfo) -> Option<(u64, u64)> {
+	match stream {
+		StreamInfo::Audio(audio) => (audio.sample_rate > 0).then_some((1, audio.sample_rate as u64)),
+		StreamInfo::Video(video) => frame_rate(&video.frame_rate).map(|(num, den)| (den, num)),
+	}
+}
+
+// bytes of one sample on every channel, for whole-sample pcm codecs
+fn bytes_per_frame(stream: &AudioStreamInfo) -> Option<usize> {
+	let bytes = (stream.bit_depth as usize / 8) * stream.channels as usize;
+	(stream.codec.starts_with("pcm_") && stream.bit_depth.is_multiple_of(8) && bytes > 0)
+		.then_some(bytes)
+}
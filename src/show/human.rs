@@ -1,6 +1,10 @@
-use super::format::{format_duration, format_size, format_xxd_style};
+use crate::container::metadata::Disposition;
+
+use super::units::{format_duration, format_size, format_thousands};
+
+use super::format::format_xxd_style;
 use super::types::{
-	AudioStreamInfo, FrameInfo, MediaInfo, ShowOptions, StreamInfo, VideoStreamInfo,
+	AudioStreamInfo, FrameInfo, MediaInfo, PacketInfo, ShowOptions, StreamInfo, VideoStreamInfo,
 };
 
 const RESET: &str = "\x1b[0m";
@@ -9,14 +13,18 @@ const DIM: &str = "\x1b[2m";
 const CYAN: &str = "\x1b[36m";
 
 pub fn render(info: &MediaInfo, opts: &ShowOptions) {
-	render_file_header(info);
+	render_file_header(info, opts);
+	render_tags(info);
 	render_streams(info, opts);
+	if let Some(packets) = &info.packets {
+		render_packets(packets, opts);
+	}
 	render_frames(info, opts);
 }
 
-fn render_file_header(info: &MediaInfo) {
+fn render_file_header(info: &MediaInfo, opts: &ShowOptions) {
 	let duration = format_duration(info.file.duration);
-	let size = format_size(info.file.size);
+	let size = format_size(info.file.size, opts.units);
 
 	println!();
 	println!("{}{}{}", CYAN, &info.file.path, RESET);
@@ -31,6 +39,19 @@ fn render_file_header(info: &MediaInfo) {
 	println!();
 }
 
+// the way ffprobe lists them under the input
+fn render_tags(info: &MediaInfo) {
+	if info.tags.is_empty() {
+		return;
+	}
+	println!("{}Metadata{}", BOLD, RESET);
+	let width = info.tags.keys().map(|key| key.len()).max().unwrap_or(0);
+	for (key, value) in &info.tags {
+		println!("  {:<width$}: {}", key, value, width = width);
+	}
+	println!();
+}
+
 fn render_streams(info: &MediaInfo, opts: &ShowOptions) {
 	for stream in &info.streams {
 		let should_skip = opts.stream_filter.is_some_and(|f| f != stream.index());
@@ -47,40 +68,83 @@ fn render_streams(info: &MediaInfo, opts: &ShowOptions) {
 }
 
 fn render_video_stream(stream: &VideoStreamInfo) {
-	let fps_decimal = calculate_fps(&stream.frame_rate);
-
 	println!("{}Video Stream #{}{}", BOLD, stream.index, RESET);
 	println!(
 		"  codec: {}  resolution: {}x{}  fps: {:.2}",
-		stream.codec, stream.width, stream.height, fps_decimal
+		stream.codec,
+		stream.width,
+		stream.height,
+		stream.fps()
 	);
 	println!("  format: {}  field: {}", stream.pix_fmt, stream.field_order);
+	render_frame_count(stream.frame_count);
+	render_stream_tags(&stream.language, &stream.disposition);
 	println!();
 }
 
-fn calculate_fps(frame_rate: &str) -> f64 {
-	let parts: Vec<&str> = frame_rate.split('/').collect();
-
-	if parts.len() != 2 {
-		return 0.0;
+fn render_audio_stream(stream: &AudioStreamInfo) {
+	println!("{}Audio Stream #{}{}", BOLD, stream.index, RESET);
+	println!(
+		"  codec: {}  sample_rate: {} Hz  channels: {}  bit_depth: {}",
+		stream.codec,
+		format_thousands(stream.sample_rate as u64),
+		stream.channels,
+		stream.bit_depth
+	);
+	render_frame_count(stream.frame_count);
+	render_stream_tags(&stream.language, &stream.disposition);
+	for (idx, stats) in stream.stats.iter().enumerate() {
+		println!(
+			"  ch{}: peak {:.2} dB  rms {:.2} dB  dc {:.6}  zero crossings {}  clipped {}",
+			idx + 1,
+			stats.peak_db(),
+			stats.rms_db(),
+			stats.dc_offset(),
+			stats.zero_crossings,
+			stats.clipped
+		);
 	}
+	println!();
+}
 
-	let num: f64 = parts[0].parse().unwrap_or(0.0);
-	let den: f64 = parts[1].parse().unwrap_or(1.0);
+fn render_frame_count(frame_count: Option<u64>) {
+	if let Some(count) = frame_count {
+		println!("  frames: {}", format_thousands(count));
+	}
+}
 
-	if den == 0.0 {
-		return 0.0;
+fn render_stream_tags(language: &Option<String>, disposition: &Disposition) {
+	if language.is_none() && disposition.is_empty() {
+		return;
 	}
 
-	num / den
+	let language = language.as_deref().unwrap_or("und");
+	let disposition =
+		if disposition.is_empty() { "none".to_string() } else { disposition.names().join(", ") };
+	println!("  language: {}  disposition: {}", language, disposition);
 }
 
-fn render_audio_stream(stream: &AudioStreamInfo) {
-	println!("{}Audio Stream #{}{}", BOLD, stream.index, RESET);
+fn render_packets(packets: &[PacketInfo], opts: &ShowOptions) {
+	println!("{}Packets{} ({})", BOLD, RESET, format_thousands(packets.len() as u64));
 	println!(
-		"  codec: {}  sample_rate: {} Hz  channels: {}  bit_depth: {}",
-		stream.codec, stream.sample_rate, stream.channels, stream.bit_depth
+		"{}  {:>8}  {:>6}  {:>12}  {:>10}  {:>12}  {:>12}  key{}",
+		DIM, "#", "stream", "offset", "size", "pts", "dts", RESET
 	);
+	let packets = packets.iter().enumerate();
+	for (index, packet) in
+		packets.filter(|(_, p)| opts.stream_filter.is_none_or(|f| f == p.stream_index))
+	{
+		println!(
+			"  {:>8}  {:>6}  {:>12}  {:>10}  {:>12}  {:>12}  {}",
+			index,
+			packet.stream_index,
+			packet.offset,
+			packet.size,
+			packet.pts,
+			packet.dts,
+			if packet.keyframe { "K" } else { "-" }
+		);
+	}
 	println!();
 }
 
@@ -106,7 +170,7 @@ fn render_frames_xxd(info: &MediaInfo, opts: &ShowOptions) {
 			DIM,
 			frame.index,
 			frame.pts,
-			format_frame_size(frame.size),
+			format_size(frame.size as u64, opts.units),
 			RESET
 		);
 		let xxd_output = format_xxd_style(&frame.hex, opts.hex_limit);
@@ -117,21 +181,6 @@ fn render_frames_xxd(info: &MediaInfo, opts: &ShowOptions) {
 	render_remaining_count(&info.frames, opts.frame_limit);
 }
 
-fn format_frame_size(bytes: usize) -> String {
-	const KB: usize = 1024;
-	const MB: usize = KB * 1024;
-
-	if bytes >= MB {
-		return format!("{:.1} MB", bytes as f64 / MB as f64);
-	}
-
-	if bytes >= KB {
-		return format!("{:.1} KB", bytes as f64 / KB as f64);
-	}
-
-	format!("{} B", bytes)
-}
-
 fn render_remaining_count(frames: &[FrameInfo], limit: usize) {
 	let total = frames.len();
 	let remaining = total.saturating_sub(limit);
@@ -140,5 +189,5 @@ fn render_remaining_count(frames: &[FrameInfo], limit: usize) {
 		return;
 	}
 
-	println!("... {} more frames", remaining);
+	println!("... {} more frames", format_thousands(remaining as u64));
 }
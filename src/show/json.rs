@@ -1,92 +1,165 @@
+use crate::container::metadata::Disposition;
+
 use super::format::hex_without_spaces;
-use super::types::{AudioStreamInfo, FrameInfo, MediaInfo, StreamInfo, VideoStreamInfo};
-
-pub fn render(info: &MediaInfo) {
-	print!("{{");
-	render_file_info(info);
-	render_streams(&info.streams);
-	render_frames(&info.frames);
-	println!("}}");
+use crate::transform::ChannelStats;
+
+use super::ffprobe;
+use super::types::{
+	AudioStreamInfo, FrameInfo, MediaInfo, PacketInfo, ShowOptions, StreamInfo, VideoStreamInfo,
+};
+
+pub fn render(info: &MediaInfo, opts: &ShowOptions) {
+	if opts.ffprobe {
+		println!("{}", ffprobe::to_json(info));
+		return;
+	}
+	println!("{}", to_json(info));
+}
+
+pub fn to_json(info: &MediaInfo) -> String {
+	let mut out = String::new();
+	out.push('{');
+	render_file_info(&mut out, info);
+	render_streams(&mut out, &info.streams);
+	if let Some(packets) = &info.packets {
+		render_packets(&mut out, packets);
+	}
+	render_frames(&mut out, &info.frames);
+	out.push('}');
+	out
 }
 
-fn render_file_info(info: &MediaInfo) {
+fn render_file_info(out: &mut String, info: &MediaInfo) {
 	let path = escape(&info.file.path);
 	let duration = info.file.duration;
 	let size = info.file.size;
 
-	print!("\"file\":\"{}\",", path);
-	print!("\"duration\":{:.2},", duration);
-	print!("\"size\":{},", size);
+	out.push_str(&format!("\"file\":\"{}\",", path));
+	out.push_str(&format!("\"duration\":{:.2},", duration));
+	out.push_str(&format!("\"size\":{},", size));
+	let tags: Vec<String> = info
+		.tags
+		.iter()
+		.map(|(key, value)| format!("\"{}\":\"{}\"", escape(key), escape(value)))
+		.collect();
+	out.push_str(&format!("\"tags\":{{{}}},", tags.join(",")));
 }
 
-fn render_streams(streams: &[StreamInfo]) {
-	print!("\"streams\":[");
+fn render_streams(out: &mut String, streams: &[StreamInfo]) {
+	out.push_str("\"streams\":[");
 
 	for (idx, stream) in streams.iter().enumerate() {
 		let needs_comma = idx > 0;
 
 		if needs_comma {
-			print!(",");
+			out.push(',');
 		}
 
 		match stream {
-			StreamInfo::Video(v) => render_video_stream(v),
-			StreamInfo::Audio(a) => render_audio_stream(a),
+			StreamInfo::Video(v) => render_video_stream(out, v),
+			StreamInfo::Audio(a) => render_audio_stream(out, a),
 		}
 	}
 
-	print!("],");
+	out.push_str("],");
 }
 
-fn render_video_stream(stream: &VideoStreamInfo) {
-	print!("{{");
-	print!("\"index\":{},", stream.index);
-	print!("\"type\":\"video\",");
-	print!("\"codec\":\"{}\",", escape(&stream.codec));
-	print!("\"pix_fmt\":\"{}\",", escape(&stream.pix_fmt));
-	print!("\"width\":{},", stream.width);
-	print!("\"height\":{},", stream.height);
-	print!("\"frame_rate\":\"{}\"", escape(&stream.frame_rate));
-	print!("}}");
+fn render_video_stream(out: &mut String, stream: &VideoStreamInfo) {
+	out.push('{');
+	out.push_str(&format!("\"index\":{},", stream.index));
+	out.push_str("\"type\":\"video\",");
+	out.push_str(&format!("\"codec\":\"{}\",", escape(&stream.codec)));
+	out.push_str(&format!("\"pix_fmt\":\"{}\",", escape(&stream.pix_fmt)));
+	out.push_str(&format!("\"width\":{},", stream.width));
+	out.push_str(&format!("\"height\":{},", stream.height));
+	out.push_str(&format!("\"frame_rate\":\"{}\",", escape(&stream.frame_rate)));
+	render_frame_count(out, stream.frame_count);
+	render_stream_tags(out, &stream.language, &stream.disposition);
+	out.push('}');
+}
+
+fn render_audio_stream(out: &mut String, stream: &AudioStreamInfo) {
+	out.push('{');
+	out.push_str(&format!("\"index\":{},", stream.index));
+	out.push_str("\"type\":\"audio\",");
+	out.push_str(&format!("\"codec\":\"{}\",", escape(&stream.codec)));
+	match stream.sample_fmt() {
+		Some(format) => out.push_str(&format!("\"sample_fmt\":\"{}\",", format)),
+		None => out.push_str("\"sample_fmt\":null,"),
+	}
+	out.push_str(&format!("\"sample_rate\":{},", stream.sample_rate));
+	out.push_str(&format!("\"channels\":{},", stream.channels));
+	out.push_str(&format!("\"bit_depth\":{},", stream.bit_depth));
+	render_frame_count(out, stream.frame_count);
+	render_stream_tags(out, &stream.language, &stream.disposition);
+	render_stats(out, &stream.stats);
+	out.push('}');
+}
+
+fn render_frame_count(out: &mut String, frame_count: Option<u64>) {
+	if let Some(count) = frame_count {
+		out.push_str(&format!("\"frames\":{},", count));
+	}
+}
+
+fn render_stream_tags(out: &mut String, language: &Option<String>, disposition: &Disposition) {
+	match language {
+		Some(language) => out.push_str(&format!("\"language\":\"{}\",", escape(language))),
+		None => out.push_str("\"language\":null,"),
+	}
+	out.push_str(&format!(
+		"\"disposition\":{{\"default\":{},\"commentary\":{}}}",
+		disposition.default as u8, disposition.commentary as u8
+	));
+}
+
+fn render_stats(out: &mut String, stats: &[ChannelStats]) {
+	if stats.is_empty() {
+		return;
+	}
+	let channels: Vec<String> = stats.iter().map(ChannelStats::to_json).collect();
+	out.push_str(&format!(",\"stats\":[{}]", channels.join(",")));
 }
 
-fn render_audio_stream(stream: &AudioStreamInfo) {
-	print!("{{");
-	print!("\"index\":{},", stream.index);
-	print!("\"type\":\"audio\",");
-	print!("\"codec\":\"{}\",", escape(&stream.codec));
-	print!("\"sample_rate\":{},", stream.sample_rate);
-	print!("\"channels\":{},", stream.channels);
-	print!("\"bit_depth\":{}", stream.bit_depth);
-	print!("}}");
+fn render_packets(out: &mut String, packets: &[PacketInfo]) {
+	let packets: Vec<String> = packets
+		.iter()
+		.map(|p| {
+			format!(
+				"{{\"stream_index\":{},\"offset\":{},\"size\":{},\"pts\":{},\"dts\":{},\"keyframe\":{}}}",
+				p.stream_index, p.offset, p.size, p.pts, p.dts, p.keyframe
+			)
+		})
+		.collect();
+	out.push_str(&format!("\"packets\":[{}],", packets.join(",")));
 }
 
-fn render_frames(frames: &[FrameInfo]) {
-	print!("\"frames\":[");
+fn render_frames(out: &mut String, frames: &[FrameInfo]) {
+	out.push_str("\"frames\":[");
 
 	for (idx, frame) in frames.iter().enumerate() {
 		let needs_comma = idx > 0;
 
 		if needs_comma {
-			print!(",");
+			out.push(',');
 		}
 
-		render_frame(frame);
+		render_frame(out, frame);
 	}
 
-	print!("]");
+	out.push(']');
 }
 
-fn render_frame(frame: &FrameInfo) {
+fn render_frame(out: &mut String, frame: &FrameInfo) {
 	let hex = hex_without_spaces(&frame.hex);
 
-	print!("{{");
-	print!("\"index\":{},", frame.index);
-	print!("\"pts\":{},", frame.pts);
-	print!("\"keyframe\":{},", frame.keyframe);
-	print!("\"size\":{},", frame.size);
-	print!("\"hex\":\"{}\"", escape(&hex));
-	print!("}}");
+	out.push('{');
+	out.push_str(&format!("\"index\":{},", frame.index));
+	out.push_str(&format!("\"pts\":{},", frame.pts));
+	out.push_str(&format!("\"keyframe\":{},", frame.keyframe));
+	out.push_str(&format!("\"size\":{},", frame.size));
+	out.push_str(&format!("\"hex\":\"{}\"", escape(&hex)));
+	out.push('}');
 }
 
 fn escape(s: &str) -> String {
@@ -0,0 +1,59 @@
+use crate::io::json::escape;
+
+use super::types::PacketInfo;
+use super::units::format_thousands;
+
+// the keyframes of one video stream, in file order
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamKeyframes {
+	pub stream_index: usize,
+	pub keyframes: Vec<PacketInfo>,
+}
+
+// `--keyframes`: a line per keyframe under its stream, where a lossless cut
+// can start
+//
+//   stream 0: 2 keyframes
+//     time 0.000000  pts 0  offset 100  size 4,096
+//     time 2.000000  pts 60  offset 81,920  size 3,980
+pub fn to_text(path: &str, streams: &[StreamKeyframes]) -> String {
+	let mut out = format!("{}\n", path);
+	if streams.is_empty() {
+		out.push_str("  no video streams\n");
+	}
+	for stream in streams {
+		let count = stream.keyframes.len();
+		let plural = if count == 1 { "" } else { "s" };
+		out.push_str(&format!("  stream {}: {} keyframe{}\n", stream.stream_index, count, plural));
+		for keyframe in &stream.keyframes {
+			out.push_str(&format!(
+				"    time {:.6}  pts {}  offset {}  size {}\n",
+				keyframe.time,
+				keyframe.pts,
+				keyframe.offset,
+				format_thousands(keyframe.size as u64)
+			));
+		}
+	}
+	out
+}
+
+pub fn to_json(path: &str, streams: &[StreamKeyframes]) -> String {
+	let streams: Vec<String> = streams
+		.iter()
+		.map(|stream| {
+			let keyframes: Vec<String> = stream
+				.keyframes
+				.iter()
+				.map(|keyframe| {
+					format!(
+						"{{\"time\":{:.6},\"pts\":{},\"offset\":{},\"size\":{}}}",
+						keyframe.time, keyframe.pts, keyframe.offset, keyframe.size
+					)
+				})
+				.collect();
+			format!("{{\"index\":{},\"keyframes\":[{}]}}", stream.stream_index, keyframes.join(","))
+		})
+		.collect();
+	format!("{{\"file\":\"{}\",\"streams\":[{}]}}", escape(path), streams.join(","))
+}
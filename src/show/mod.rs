@@ -1,29 +1,77 @@
 mod analyze;
+mod bitrate;
+mod chunks;
+mod csv;
+mod ffprobe;
 mod format;
 mod human;
 mod json;
+mod keyframes;
+mod packets;
+mod stats;
 mod types;
+pub mod units;
+mod xml;
 
-pub use types::{MediaInfo, ShowOptions};
+pub use bitrate::{BitrateMode, BitrateStats};
+pub use keyframes::StreamKeyframes;
+pub use packets::mp4_packets;
+pub use stats::LoudnessStats;
+pub use types::{MediaInfo, PacketInfo, ShowFormat, ShowOptions, StreamInfo};
 
-use crate::cli::pipeline::{FileAdapter, MediaType};
-use crate::io::IoResult;
+use crate::container::MediaType;
+use crate::container::riff::{self, RiffChunk};
+use crate::core::Transform;
+use crate::io::{AllowAll, FileAdapter, IoError, IoResult, PathPolicy};
+use crate::transcode::decode_audio_as;
+use crate::transform::{AStats, LoudnessMeter};
+use std::sync::Arc;
 
 pub struct Show {
 	input_path: String,
 	opts: ShowOptions,
+	// overrides the extension, for files without one
+	format: Option<MediaType>,
+	path_policy: Arc<dyn PathPolicy>,
 }
 
 impl Show {
 	pub fn new(input_path: String, opts: ShowOptions) -> Self {
-		Self { input_path, opts }
+		Self { input_path, opts, format: None, path_policy: Arc::new(AllowAll) }
+	}
+
+	pub fn with_format(mut self, format: MediaType) -> Self {
+		self.format = Some(format);
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
 	}
 
 	pub fn run(&self) -> std::io::Result<()> {
-		self.run_io().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+		self.run_io().map_err(std::io::Error::from)
 	}
 
 	fn run_io(&self) -> IoResult<()> {
+		if self.opts.chunks {
+			print!("{}", self.to_chunks()?);
+			return Ok(());
+		}
+		if self.opts.bitrate.is_some() {
+			print!("{}", self.to_bitrate()?);
+			return Ok(());
+		}
+		if self.opts.keyframes {
+			print!("{}", self.to_keyframes()?);
+			return Ok(());
+		}
+		if self.opts.stats {
+			print!("{}", self.to_stats()?);
+			return Ok(());
+		}
+
 		let info = self.analyze()?;
 
 		self.render(&info);
@@ -31,26 +79,171 @@ impl Show {
 		Ok(())
 	}
 
-	fn analyze(&self) -> IoResult<MediaInfo> {
-		let media_type = MediaType::from_extension(&self.input_path);
-		let input = FileAdapter::open(&self.input_path)?;
+	pub fn to_json(&self) -> IoResult<String> {
+		let info = self.analyze()?;
+		if self.opts.ffprobe {
+			return Ok(ffprobe::to_json(&info));
+		}
+		Ok(json::to_json(&info))
+	}
 
-		match media_type {
+	pub fn to_csv(&self) -> IoResult<String> {
+		Ok(csv::to_csv(&self.analyze()?, &self.opts))
+	}
+
+	pub fn to_xml(&self) -> IoResult<String> {
+		Ok(xml::to_xml(&self.analyze()?, &self.opts))
+	}
+
+	// the chunk tree as text, or as json with `ShowFormat::Json`
+	pub fn to_chunks(&self) -> IoResult<String> {
+		let chunks = self.chunks()?;
+		Ok(match self.opts.format {
+			ShowFormat::Json => format!("{}\n", chunks::to_json(&self.input_path, &chunks)),
+			_ => chunks::to_text(&self.input_path, &chunks),
+		})
+	}
+
+	// packet sizes per second of every stream, or the one `stream_filter` picks
+	pub fn bitrate(&self) -> IoResult<BitrateStats> {
+		let opts = ShowOptions { frame_limit: 0, astats: false, packets: true, ..self.opts.clone() };
+		let show = Show {
+			input_path: self.input_path.clone(),
+			opts,
+			format: self.format,
+			path_policy: self.path_policy.clone(),
+		};
+		let info = show.analyze()?;
+		let packets = info.packets.iter().flatten();
+		let packets =
+			packets.filter(|packet| self.opts.stream_filter.is_none_or(|s| s == packet.stream_index));
+		Ok(BitrateStats::from_packets(packets, info.file.duration))
+	}
+
+	// with `ShowFormat::Json` the json, otherwise as `opts.bitrate` says
+	pub fn to_bitrate(&self) -> IoResult<String> {
+		let stats = self.bitrate()?;
+		Ok(match self.opts.format {
+			ShowFormat::Json => format!("{}\n", bitrate::to_json(&self.input_path, &stats)),
+			_ => bitrate::to_text(&self.input_path, &stats, self.opts.bitrate.unwrap_or_default()),
+		})
+	}
+
+	// the keyframes of each video stream, or the one `stream_filter` picks:
+	// idx1 flags for avi, the sync sample table for mp4, and every frame of
+	// y4m, which has nothing but keyframes
+	pub fn keyframes(&self) -> IoResult<Vec<StreamKeyframes>> {
+		let opts = ShowOptions { frame_limit: 0, astats: false, packets: true, ..self.opts.clone() };
+		let show = Show {
+			input_path: self.input_path.clone(),
+			opts,
+			format: self.format,
+			path_policy: self.path_policy.clone(),
+		};
+		let info = show.analyze()?;
+		let packets = info.packets.unwrap_or_default();
+		let streams = info.streams.iter().filter(|stream| matches!(stream, StreamInfo::Video(_)));
+		let streams =
+			streams.filter(|stream| self.opts.stream_filter.is_none_or(|s| s == stream.index()));
+		Ok(
+			streams
+				.map(|stream| StreamKeyframes {
+					stream_index: stream.index(),
+					keyframes: packets
+						.iter()
+						.filter(|packet| packet.stream_index == stream.index() && packet.keyframe)
+						.cloned()
+						.collect(),
+				})
+				.collect(),
+		)
+	}
+
+	pub fn to_keyframes(&self) -> IoResult<String> {
+		let streams = self.keyframes()?;
+		Ok(match self.opts.format {
+			ShowFormat::Json => format!("{}\n", keyframes::to_json(&self.input_path, &streams)),
+			_ => keyframes::to_text(&self.input_path, &streams),
+		})
+	}
+
+	// decodes the audio in full through the R128 meter and astats. wav and
+	// flac carry one audio stream, which `stream_filter` can leave out
+	pub fn stats(&self) -> IoResult<Vec<LoudnessStats>> {
+		let media_type = self.format.unwrap_or_else(|| MediaType::from_extension(&self.input_path));
+		let mut meter = LoudnessMeter::new();
+		let mut astats = AStats::new();
+		decode_audio_as(&self.input_path, media_type, self.path_policy.as_ref(), |frame| {
+			if let Some(audio) = frame.audio() {
+				meter.push(audio);
+			}
+			astats.apply(frame).map(drop)
+		})?;
+		if self.opts.stream_filter.is_some_and(|stream| stream != 0) {
+			return Ok(Vec::new());
+		}
+
+		let channels = astats.stats();
+		let power: f64 = channels.iter().map(|channel| 10f64.powf(channel.rms_db() / 10.0)).sum();
+		let loudness = meter.loudness();
+		Ok(vec![LoudnessStats {
+			stream_index: 0,
+			integrated: loudness.integrated,
+			true_peak: loudness.true_peak,
+			rms: 10.0 * (power / channels.len().max(1) as f64).log10(),
+			range: meter.loudness_range(),
+		}])
+	}
+
+	pub fn to_stats(&self) -> IoResult<String> {
+		let streams = self.stats()?;
+		Ok(match self.opts.format {
+			ShowFormat::Json => format!("{}\n", stats::to_json(&self.input_path, &streams)),
+			_ => stats::to_text(&self.input_path, &streams),
+		})
+	}
+
+	// wav and avi are both RIFF, so either walks the same way
+	pub fn chunks(&self) -> IoResult<Vec<RiffChunk>> {
+		let media_type = self.format.unwrap_or_else(|| MediaType::from_extension(&self.input_path));
+		if !matches!(media_type, MediaType::Wav | MediaType::Avi) {
+			return Err(IoError::unsupported("--chunks reads RIFF files: wav and avi"));
+		}
+		let mut input = FileAdapter::open_with(&self.input_path, self.path_policy.as_ref())?;
+		riff::walk(&mut input)
+	}
+
+	pub fn analyze(&self) -> IoResult<MediaInfo> {
+		let media_type = self.format.unwrap_or_else(|| MediaType::from_extension(&self.input_path));
+		let input = FileAdapter::open_with(&self.input_path, self.path_policy.as_ref())?;
+
+		let mut info = match media_type {
 			MediaType::Wav => analyze::analyze_wav(input, &self.input_path, &self.opts),
 			MediaType::Y4m => analyze::analyze_y4m(input, &self.input_path, &self.opts),
 			MediaType::Flac => analyze::analyze_flac(input, &self.input_path, &self.opts),
 			MediaType::Avi => analyze::analyze_avi(input, &self.input_path, &self.opts),
 			MediaType::Mp4 => analyze::analyze_mp4(input, &self.input_path, &self.opts),
-			MediaType::Unknown => Err(crate::io::IoError::invalid_data("unsupported file format")),
+			MediaType::Mp3 => analyze::analyze_mp3(input, &self.input_path, &self.opts),
+			MediaType::Ogg => analyze::analyze_ogg(input, &self.input_path, &self.opts),
+			MediaType::Unknown => Err(IoError::invalid_data("unsupported file format")),
+		}?;
+		if self.opts.packets || self.opts.count_frames {
+			let input = FileAdapter::open_with(&self.input_path, self.path_policy.as_ref())?;
+			let packets = packets::list_packets(input, media_type)?;
+			if self.opts.count_frames {
+				analyze::count_frames(&mut info, &packets);
+			}
+			info.packets = self.opts.packets.then_some(packets);
 		}
+		Ok(info)
 	}
 
 	fn render(&self, info: &MediaInfo) {
-		if self.opts.json {
-			json::render(info);
-			return;
+		match self.opts.format {
+			ShowFormat::Human => human::render(info, &self.opts),
+			ShowFormat::Json => json::render(info, &self.opts),
+			ShowFormat::Csv => print!("{}", csv::to_csv(info, &self.opts)),
+			ShowFormat::Xml => print!("{}", xml::to_xml(info, &self.opts)),
 		}
-
-		human::render(info, &self.opts);
 	}
 }
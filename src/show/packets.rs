@@ -0,0 +1,205 @@
+use crate::container::avi::{AVIIF_KEYFRAME, AviStream, StreamType};
+use crate::container::mp4::TrackType;
+use crate::container::{
+	AviReader, MediaType, Mp3Reader, Mp4Format, Mp4Reader, OggReader, WavReader, Y4mReader,
+};
+use crate::core::{Demuxer, Packet, Timebase, log};
+use crate::io::{CountingReader, IoError, IoErrorKind, IoResult, MediaRead, ReadPrimitives};
+use crate::transcode::check::flac_frames;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use super::types::PacketInfo;
+
+// every packet of the file as the demuxer hands it over, nothing decoded.
+// offsets come from counting the bytes the demuxer reads, so they hold for
+// any reader without each one keeping track. the exception is mp4, whose
+// sample tables already say where everything is
+pub fn list_packets<R: MediaRead>(input: R, media_type: MediaType) -> IoResult<Vec<PacketInfo>> {
+	let input = CountingReader::new(input);
+	let position = input.position();
+	match media_type {
+		MediaType::Wav => collect(WavReader::new(input)?, &position, media_type),
+		MediaType::Y4m => {
+			// the reader buffers ahead, so what it has not handed over yet is not behind it
+			// a file cut off inside a frame lists the whole frames before it
+			let mut reader = Y4mReader::new(input)?;
+			let mut packets = Vec::new();
+			loop {
+				let packet = match reader.read_packet() {
+					Ok(Some(packet)) => packet,
+					Ok(None) => break,
+					Err(e) if matches!(e.kind(), IoErrorKind::UnexpectedEof) => {
+						log::warn(
+							"show",
+							format_args!("y4m frame {} is cut short, stopped there", packets.len()),
+						);
+						break;
+					}
+					Err(e) => return Err(e),
+				};
+				let end = position.get() - reader.read_ahead() as u64;
+				packets.push(packet_info(&packet, end, media_type));
+			}
+			Ok(packets)
+		}
+		MediaType::Flac => {
+			// the reader hands over fixed size chunks, so frames are found by
+			// their sync codes in the whole file
+			let mut input = input;
+			let data = input.read_to_end()?;
+			let (format, _, frames) = flac_frames(&data)?;
+			let timebase = Timebase::new(1, format.sample_rate.max(1));
+			let mut pts = 0i64;
+			let mut packets = Vec::new();
+			for frame in frames {
+				packets.push(PacketInfo {
+					stream_index: 0,
+					offset: frame.start as u64,
+					size: frame.end - frame.start,
+					pts,
+					dts: pts,
+					keyframe: true,
+					time: timebase.to_seconds(pts),
+				});
+				pts += frame.block_size as i64;
+			}
+			Ok(packets)
+		}
+		MediaType::Avi => {
+			// the avi reader's pts is a byte position, so time is counted here
+			// from each stream's rate: a frame per chunk, or for audio with a
+			// sample size, that many bytes per sample
+			let mut reader = AviReader::new(input)?;
+			let format = reader.format().clone();
+			let mut units = vec![0u64; format.streams.len()];
+			let mut packets = Vec::new();
+			while let Some(packet) = reader.read_packet()? {
+				let mut info = packet_info(&packet, position.get(), media_type);
+				info.time = 0.0;
+				if let Some(stream) = format.streams.get(packet.stream_index) {
+					let header = &stream.header;
+					let unit = if header.rate > 0 {
+						header.scale as f64 / header.rate as f64
+					} else {
+						format.main_header.microseconds_per_frame as f64 / 1_000_000.0
+					};
+					let count = &mut units[packet.stream_index];
+					info.time = *count as f64 * unit;
+					*count += match header.sample_size {
+						0 => 1,
+						size => packet.data.len() as u64 / size as u64,
+					};
+				}
+				packets.push(info);
+			}
+			mark_avi_keyframes(&mut packets, &reader, &format.streams);
+			Ok(packets)
+		}
+		MediaType::Mp4 => Ok(mp4_packets(Mp4Reader::new(input)?.format())),
+		MediaType::Mp3 => collect(Mp3Reader::new(input)?, &position, media_type),
+		MediaType::Ogg => collect(OggReader::new(input)?, &position, media_type),
+		MediaType::Unknown => Err(IoError::invalid_data("unsupported file format")),
+	}
+}
+
+fn collect<D: Demuxer>(
+	mut demuxer: D,
+	position: &Cell<u64>,
+	media_type: MediaType,
+) -> IoResult<Vec<PacketInfo>> {
+	let mut packets = Vec::new();
+	while let Some(packet) = demuxer.read_packet()? {
+		let end = position.get();
+		packets.push(packet_info(&packet, end, media_type));
+	}
+	Ok(packets)
+}
+
+// keyframes in an avi are whatever idx1 flags as one. its offsets are from
+// the movi type fourcc, or from the start of the file with some writers, so
+// the first entry decides which. with no index only audio is known to be key
+fn mark_avi_keyframes<R: MediaRead>(
+	packets: &mut [PacketInfo],
+	reader: &AviReader<R>,
+	streams: &[AviStream],
+) {
+	for packet in packets.iter_mut() {
+		packet.keyframe = streams
+			.get(packet.stream_index)
+			.is_some_and(|stream| stream.header.stream_type == StreamType::Audio);
+	}
+	let index = reader.index();
+	let Some(first) = index.first() else { return };
+	let relative = reader.movi_start().saturating_sub(4);
+	let starts: HashMap<u64, usize> =
+		packets.iter().enumerate().map(|(i, packet)| (packet.offset.saturating_sub(8), i)).collect();
+	let base = if starts.contains_key(&(relative + first.offset as u64)) { relative } else { 0 };
+	for entry in index {
+		if let Some(&i) = starts.get(&(base + entry.offset as u64)) {
+			packets[i].keyframe |= entry.flags & AVIIF_KEYFRAME != 0;
+		}
+	}
+}
+
+fn packet_info(packet: &Packet, end: u64, media_type: MediaType) -> PacketInfo {
+	let size = packet.data.len() as u64;
+	// a packet is the last thing read for it, bar the pad byte after an odd avi chunk
+	let pad = if media_type == MediaType::Avi { size % 2 } else { 0 };
+	PacketInfo {
+		stream_index: packet.stream_index,
+		offset: end.saturating_sub(size + pad),
+		size: packet.data.len(),
+		pts: packet.pts,
+		// no reader here reorders frames, so decode order is presentation order
+		// unless the reader says otherwise
+		dts: if packet.dts != 0 { packet.dts } else { packet.pts },
+		// pcm, flac and raw video packets all stand alone
+		keyframe: packet.keyframe
+			|| matches!(media_type, MediaType::Wav | MediaType::Flac | MediaType::Y4m),
+		time: packet.timebase.to_seconds(packet.pts),
+	}
+}
+
+// mp4 samples live wherever the sample tables say, in any order, so they
+// are listed from the tables rather than by reading: chunk offsets, samples
+// per chunk and sample sizes place each one, the time to sample table gives
+// its pts. the whole file in offset order, every track interleaved
+pub fn mp4_packets(format: &Mp4Format) -> Vec<PacketInfo> {
+	let mut packets = Vec::new();
+	for (stream_index, track) in format.tracks.iter().enumerate() {
+		let mut sizes = track.sample_sizes.iter();
+		let mut deltas =
+			track.time_to_sample.iter().flat_map(|&(count, delta)| (0..count).map(move |_| delta));
+		let mut pts = 0i64;
+		let mut sample = 0;
+		for (chunk, &chunk_offset) in track.chunk_offsets.iter().enumerate() {
+			// the last stsc entry starting at or before this chunk (1-based)
+			let per_chunk = track
+				.sample_to_chunk
+				.iter()
+				.rev()
+				.find(|(first, _, _)| *first as usize <= chunk + 1)
+				.map_or(1, |(_, samples, _)| *samples);
+			let mut offset = chunk_offset;
+			for _ in 0..per_chunk {
+				let Some(&size) = sizes.next() else { break };
+				packets.push(PacketInfo {
+					stream_index,
+					offset,
+					size: size as usize,
+					pts,
+					dts: pts,
+					// with no stss every sample is a sync sample
+					keyframe: track.track_type == TrackType::Audio || track.is_sync_sample(sample),
+					time: pts as f64 / track.timescale.max(1) as f64,
+				});
+				offset += size as u64;
+				sample += 1;
+				pts += deltas.next().unwrap_or(1) as i64;
+			}
+		}
+	}
+	packets.sort_by_key(|packet| packet.offset);
+	packets
+}
@@ -0,0 +1,54 @@
+use crate::io::json::escape;
+
+// what `--stats` measures of an audio stream, all over the whole stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessStats {
+	pub stream_index: usize,
+	// gated integrated loudness in LUFS, -inf for silence
+	pub integrated: f64,
+	// 4x oversampled peak in dBTP
+	pub true_peak: f64,
+	// every channel's samples together, in dBFS
+	pub rms: f64,
+	// loudness range in LU
+	pub range: f64,
+}
+
+// `--stats`: a line per audio stream
+//
+//   stream 0: integrated -23.0 LUFS  true peak -1.0 dBTP  rms -20.4 dBFS  range 6.2 LU
+pub fn to_text(path: &str, streams: &[LoudnessStats]) -> String {
+	let mut out = format!("{}\n", path);
+	if streams.is_empty() {
+		out.push_str("  no audio streams\n");
+	}
+	for stats in streams {
+		out.push_str(&format!(
+			"  stream {}: integrated {:.1} LUFS  true peak {:.1} dBTP  rms {:.1} dBFS  range {:.1} LU\n",
+			stats.stream_index, stats.integrated, stats.true_peak, stats.rms, stats.range
+		));
+	}
+	out
+}
+
+pub fn to_json(path: &str, streams: &[LoudnessStats]) -> String {
+	let streams: Vec<String> = streams
+		.iter()
+		.map(|stats| {
+			format!(
+				"{{\"index\":{},\"integrated_lufs\":{},\"true_peak_dbtp\":{},\"rms_dbfs\":{},\"range_lu\":{:.2}}}",
+				stats.stream_index,
+				json_level(stats.integrated),
+				json_level(stats.true_peak),
+				json_level(stats.rms),
+				stats.range
+			)
+		})
+		.collect();
+	format!("{{\"file\":\"{}\",\"streams\":[{}]}}", escape(path), streams.join(","))
+}
+
+// silence has no finite level
+fn json_level(db: f64) -> String {
+	if db.is_finite() { format!("{:.2}", db) } else { "null".to_string() }
+}
@@ -1,20 +1,84 @@
+use super::bitrate::BitrateMode;
+use super::units::SizeUnits;
+use crate::container::MediaType;
+use crate::container::metadata::Disposition;
+use crate::transform::ChannelStats;
+use std::collections::BTreeMap;
+
+// how `--show` prints what it found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShowFormat {
+	// aligned text with colors for a terminal
+	#[default]
+	Human,
+	Json,
+	// a header, then a row per stream and per frame
+	Csv,
+	Xml,
+}
+
+impl ShowFormat {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"human" | "text" => Some(ShowFormat::Human),
+			"json" => Some(ShowFormat::Json),
+			"csv" => Some(ShowFormat::Csv),
+			"xml" => Some(ShowFormat::Xml),
+			_ => None,
+		}
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct ShowOptions {
-	pub json: bool,
+	pub format: ShowFormat,
 	pub stream_filter: Option<usize>,
 	pub frame_limit: usize,
 	pub hex_limit: usize,
+	pub units: SizeUnits,
+	// decode wav and flac audio in full to fill `AudioStreamInfo::stats`
+	pub astats: bool,
+	// list every packet the demuxer reads, with its byte offset
+	pub packets: bool,
+	// with `ShowFormat::Json`, the keys and layout ffprobe's json writer uses
+	pub ffprobe: bool,
+	// print the RIFF chunk tree instead of streams and frames
+	pub chunks: bool,
+	// print bitrate per second instead of streams and frames
+	pub bitrate: Option<BitrateMode>,
+	// print each video stream's keyframes instead of streams and frames
+	pub keyframes: bool,
+	// print loudness, true peak, rms and loudness range of each audio stream
+	pub stats: bool,
+	// walk every packet to count each stream's frames, and take wav and y4m
+	// durations from what was read rather than the file size
+	pub count_frames: bool,
 }
 
 impl Default for ShowOptions {
 	fn default() -> Self {
-		Self { json: false, stream_filter: None, frame_limit: 10, hex_limit: 128 }
+		Self {
+			format: ShowFormat::Human,
+			stream_filter: None,
+			frame_limit: 10,
+			hex_limit: 128,
+			units: SizeUnits::default(),
+			astats: false,
+			packets: false,
+			ffprobe: false,
+			chunks: false,
+			bitrate: None,
+			keyframes: false,
+			stats: false,
+			count_frames: false,
+		}
 	}
 }
 
 #[derive(Debug, Clone)]
 pub struct FileInfo {
 	pub path: String,
+	pub container: MediaType,
 	pub duration: f64,
 	pub size: u64,
 }
@@ -45,6 +109,24 @@ pub struct VideoStreamInfo {
 	pub aspect_ratio: Option<String>,
 	pub display_aspect: Option<String>,
 	pub field_order: String,
+	pub language: Option<String>,
+	pub disposition: Disposition,
+	// packets read, with `ShowOptions::count_frames`
+	pub frame_count: Option<u64>,
+}
+
+impl VideoStreamInfo {
+	// `frame_rate` as a number, from either `num/den` or a decimal
+	pub fn fps(&self) -> f64 {
+		match self.frame_rate.split_once('/') {
+			Some((num, den)) => {
+				let num: f64 = num.parse().unwrap_or(0.0);
+				let den: f64 = den.parse().unwrap_or(1.0);
+				if den == 0.0 { 0.0 } else { num / den }
+			}
+			None => self.frame_rate.parse().unwrap_or(0.0),
+		}
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +136,35 @@ pub struct AudioStreamInfo {
 	pub sample_rate: u32,
 	pub channels: u8,
 	pub bit_depth: u16,
+	pub language: Option<String>,
+	pub disposition: Disposition,
+	// one entry per channel, empty unless `ShowOptions::astats` is set
+	pub stats: Vec<ChannelStats>,
+	// packets read, with `ShowOptions::count_frames`
+	pub frame_count: Option<u64>,
+}
+
+impl AudioStreamInfo {
+	// what ffmpeg's decoder for the codec hands over, by ffmpeg's name: 24 bit
+	// pcm and flac widen to s32, the lossy codecs decode to planar float.
+	// None when the codec is not known
+	pub fn sample_fmt(&self) -> Option<&'static str> {
+		let by_depth = || match self.bit_depth {
+			8 => Some("u8"),
+			16 => Some("s16"),
+			24 | 32 => Some("s32"),
+			_ => None,
+		};
+		match self.codec.as_str() {
+			"pcm_alaw" | "pcm_mulaw" => Some("s16"),
+			"adpcm_ima" | "adpcm_ms" => Some("s16p"),
+			"flac" if self.bit_depth <= 16 => Some("s16"),
+			"flac" => Some("s32"),
+			"mp1" | "mp2" | "mp3" | "aac" | "vorbis" | "opus" => Some("fltp"),
+			codec if codec.starts_with("pcm") => by_depth(),
+			_ => None,
+		}
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -65,9 +176,27 @@ pub struct FrameInfo {
 	pub hex: String,
 }
 
+// a packet as the demuxer read it, before any decoding
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketInfo {
+	pub stream_index: usize,
+	// where its payload starts in the file
+	pub offset: u64,
+	pub size: usize,
+	pub pts: i64,
+	pub dts: i64,
+	pub keyframe: bool,
+	// pts in seconds
+	pub time: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct MediaInfo {
 	pub file: FileInfo,
 	pub streams: Vec<StreamInfo>,
 	pub frames: Vec<FrameInfo>,
+	// every packet of the file, with `ShowOptions::packets`
+	pub packets: Option<Vec<PacketInfo>>,
+	// title, artist and the like from the container's tags, by key
+	pub tags: BTreeMap<String, String>,
 }
@@ -0,0 +1,71 @@
+// human-readable numbers for terminal output. everything here is locale
+// independent: '.' is always the decimal point and ',' the group separator.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+	#[default]
+	Binary,
+	Decimal,
+	Bytes,
+}
+
+impl SizeUnits {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"binary" | "iec" => Some(SizeUnits::Binary),
+			"decimal" | "si" => Some(SizeUnits::Decimal),
+			"bytes" | "raw" => Some(SizeUnits::Bytes),
+			_ => None,
+		}
+	}
+}
+
+pub fn format_size(bytes: u64, units: SizeUnits) -> String {
+	let (base, names): (f64, [&str; 4]) = match units {
+		SizeUnits::Binary => (1024.0, ["KiB", "MiB", "GiB", "TiB"]),
+		SizeUnits::Decimal => (1000.0, ["kB", "MB", "GB", "TB"]),
+		SizeUnits::Bytes => return format!("{} B", format_thousands(bytes)),
+	};
+
+	if (bytes as f64) < base {
+		return format!("{} B", bytes);
+	}
+
+	let mut value = bytes as f64 / base;
+	let mut unit = 0;
+	while value >= base && unit < names.len() - 1 {
+		value /= base;
+		unit += 1;
+	}
+
+	format!("{:.2} {}", value, names[unit])
+}
+
+pub fn format_duration(seconds: f64) -> String {
+	if !seconds.is_finite() {
+		return "--:--:--.---".to_string();
+	}
+
+	let sign = if seconds < 0.0 { "-" } else { "" };
+	let total_ms = (seconds.abs() * 1000.0).round() as u64;
+	let hours = total_ms / 3_600_000;
+	let minutes = (total_ms / 60_000) % 60;
+	let secs = (total_ms / 1000) % 60;
+	let millis = total_ms % 1000;
+
+	format!("{}{:02}:{:02}:{:02}.{:03}", sign, hours, minutes, secs, millis)
+}
+
+pub fn format_thousands(value: u64) -> String {
+	let digits = value.to_string();
+	let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+
+	for (idx, c) in digits.chars().enumerate() {
+		if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+			out.push(',');
+		}
+		out.push(c);
+	}
+
+	out
+}
@@ -0,0 +1,150 @@
+use crate::container::metadata::Disposition;
+use crate::transform::ChannelStats;
+
+use super::types::{
+	AudioStreamInfo, FrameInfo, MediaInfo, ShowOptions, StreamInfo, VideoStreamInfo,
+};
+
+// the same tree as the json, every value an attribute:
+// <ffmpreg><file/><tags>...</tags><streams>...</streams><packets>...</packets><frames>...</frames></ffmpreg>
+pub fn to_xml(info: &MediaInfo, opts: &ShowOptions) -> String {
+	let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ffmpreg>\n");
+	out.push_str(&format!(
+		"\t<file path=\"{}\" duration=\"{:.6}\" size=\"{}\"/>\n",
+		escape(&info.file.path),
+		info.file.duration,
+		info.file.size
+	));
+
+	if !info.tags.is_empty() {
+		out.push_str("\t<tags>\n");
+		for (key, value) in &info.tags {
+			out.push_str(&format!("\t\t<tag key=\"{}\" value=\"{}\"/>\n", escape(key), escape(value)));
+		}
+		out.push_str("\t</tags>\n");
+	}
+
+	out.push_str("\t<streams>\n");
+	let streams = info.streams.iter().filter(|s| opts.stream_filter.is_none_or(|f| f == s.index()));
+	for stream in streams {
+		match stream {
+			StreamInfo::Video(video) => render_video_stream(&mut out, video),
+			StreamInfo::Audio(audio) => render_audio_stream(&mut out, audio),
+		}
+	}
+	out.push_str("\t</streams>\n");
+
+	if let Some(packets) = &info.packets {
+		out.push_str("\t<packets>\n");
+		for packet in packets {
+			out.push_str(&format!(
+				"\t\t<packet stream_index=\"{}\" offset=\"{}\" size=\"{}\" pts=\"{}\" dts=\"{}\" keyframe=\"{}\"/>\n",
+				packet.stream_index,
+				packet.offset,
+				packet.size,
+				packet.pts,
+				packet.dts,
+				packet.keyframe as u8
+			));
+		}
+		out.push_str("\t</packets>\n");
+	}
+
+	out.push_str("\t<frames>\n");
+	for frame in &info.frames {
+		render_frame(&mut out, frame);
+	}
+	out.push_str("\t</frames>\n</ffmpreg>\n");
+	out
+}
+
+fn render_video_stream(out: &mut String, stream: &VideoStreamInfo) {
+	out.push_str(&format!(
+		"\t\t<stream index=\"{}\" type=\"video\" codec=\"{}\" pix_fmt=\"{}\" width=\"{}\" height=\"{}\" frame_rate=\"{}\" field_order=\"{}\"{}{}>\n",
+		stream.index,
+		escape(&stream.codec),
+		escape(&stream.pix_fmt),
+		stream.width,
+		stream.height,
+		escape(&stream.frame_rate),
+		escape(&stream.field_order),
+		frame_count(stream.frame_count),
+		language(&stream.language)
+	));
+	render_disposition(out, &stream.disposition);
+	out.push_str("\t\t</stream>\n");
+}
+
+fn render_audio_stream(out: &mut String, stream: &AudioStreamInfo) {
+	out.push_str(&format!(
+		"\t\t<stream index=\"{}\" type=\"audio\" codec=\"{}\" sample_rate=\"{}\" channels=\"{}\" bit_depth=\"{}\"{}{}>\n",
+		stream.index,
+		escape(&stream.codec),
+		stream.sample_rate,
+		stream.channels,
+		stream.bit_depth,
+		frame_count(stream.frame_count),
+		language(&stream.language)
+	));
+	render_disposition(out, &stream.disposition);
+	if !stream.stats.is_empty() {
+		out.push_str("\t\t\t<stats>\n");
+		for channel in &stream.stats {
+			render_channel(out, channel);
+		}
+		out.push_str("\t\t\t</stats>\n");
+	}
+	out.push_str("\t\t</stream>\n");
+}
+
+fn frame_count(frame_count: Option<u64>) -> String {
+	match frame_count {
+		Some(count) => format!(" frames=\"{}\"", count),
+		None => String::new(),
+	}
+}
+
+fn language(language: &Option<String>) -> String {
+	match language {
+		Some(language) => format!(" language=\"{}\"", escape(language)),
+		None => String::new(),
+	}
+}
+
+fn render_disposition(out: &mut String, disposition: &Disposition) {
+	out.push_str(&format!(
+		"\t\t\t<disposition default=\"{}\" commentary=\"{}\"/>\n",
+		disposition.default as u8, disposition.commentary as u8
+	));
+}
+
+// silence has no finite level: where the json says null the attribute is left out
+fn render_channel(out: &mut String, channel: &ChannelStats) {
+	let db = |name: &str, db: f64| {
+		if db.is_finite() { format!(" {}=\"{:.2}\"", name, db) } else { String::new() }
+	};
+	out.push_str(&format!(
+		"\t\t\t\t<channel dc_offset=\"{:.6}\"{}{} zero_crossings=\"{}\" clipped=\"{}\"/>\n",
+		channel.dc_offset(),
+		db("peak_db", channel.peak_db()),
+		db("rms_db", channel.rms_db()),
+		channel.zero_crossings,
+		channel.clipped
+	));
+}
+
+fn render_frame(out: &mut String, frame: &FrameInfo) {
+	out.push_str(&format!(
+		"\t\t<frame index=\"{}\" pts=\"{}\" keyframe=\"{}\" size=\"{}\"/>\n",
+		frame.index, frame.pts, frame.keyframe as u8, frame.size
+	));
+}
+
+fn escape(text: &str) -> String {
+	text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
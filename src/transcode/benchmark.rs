@@ -0,0 +1,163 @@
+use crate::show::units::{SizeUnits, format_duration, format_size, format_thousands};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// tallies a run for `--benchmark`: packets read, frames decoded and how far
+// into the input it got. the run's progress feeds it, from whichever thread
+// reads the input, so the counters are atomic and it is shared behind an Arc
+#[derive(Debug)]
+pub struct Benchmark {
+	started: Instant,
+	cpu_started: Option<Duration>,
+	packets: AtomicU64,
+	frames: AtomicU64,
+	// input seconds, as f64 bits
+	media: AtomicU64,
+}
+
+impl Benchmark {
+	pub fn start() -> Self {
+		Self {
+			started: Instant::now(),
+			cpu_started: cpu_time(),
+			packets: AtomicU64::new(0),
+			frames: AtomicU64::new(0),
+			media: AtomicU64::new(0),
+		}
+	}
+
+	pub fn count_packet(&self) {
+		self.packets.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn count_frame(&self) {
+		self.frames.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn set_media_time(&self, seconds: f64) {
+		self.media.store(seconds.to_bits(), Ordering::Relaxed);
+	}
+
+	pub fn report(&self) -> BenchmarkReport {
+		let cpu = match (self.cpu_started, cpu_time()) {
+			(Some(start), Some(now)) => Some(now.saturating_sub(start)),
+			_ => None,
+		};
+		BenchmarkReport {
+			wall: self.started.elapsed(),
+			cpu,
+			media: f64::from_bits(self.media.load(Ordering::Relaxed)),
+			packets: self.packets.load(Ordering::Relaxed),
+			frames: self.frames.load(Ordering::Relaxed),
+			peak_memory: peak_memory(),
+		}
+	}
+}
+
+impl Default for Benchmark {
+	fn default() -> Self {
+		Self::start()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkReport {
+	pub wall: Duration,
+	// user plus system time of the whole process; None off linux
+	pub cpu: Option<Duration>,
+	// input seconds converted
+	pub media: f64,
+	pub packets: u64,
+	// frames decoded; 0 for a copy that never decodes
+	pub frames: u64,
+	// peak resident set size in bytes; None off linux
+	pub peak_memory: Option<u64>,
+}
+
+impl BenchmarkReport {
+	// input seconds per wall clock second
+	pub fn speed(&self) -> Option<f64> {
+		let wall = self.wall.as_secs_f64();
+		(wall > 0.0 && self.media > 0.0).then(|| self.media / wall)
+	}
+
+	pub fn packets_per_second(&self) -> f64 {
+		per_second(self.packets, self.wall)
+	}
+
+	pub fn frames_per_second(&self) -> f64 {
+		per_second(self.frames, self.wall)
+	}
+
+	// bench: wall 00:00:01.200  cpu 00:00:02.100  speed 50.00x
+	//   packets: 1,204 (1003.3/s)  frames: 1,204 (1003.3/s)
+	//   peak memory: 12.4 MiB
+	pub fn to_text(&self) -> String {
+		let mut text = format!("bench: wall {}", format_duration(self.wall.as_secs_f64()));
+		if let Some(cpu) = self.cpu {
+			text.push_str(&format!("  cpu {}", format_duration(cpu.as_secs_f64())));
+		}
+		if let Some(speed) = self.speed() {
+			text.push_str(&format!("  speed {:.2}x", speed));
+		}
+		text.push_str(&format!(
+			"\n  packets: {} ({:.1}/s)",
+			format_thousands(self.packets),
+			self.packets_per_second()
+		));
+		if self.frames > 0 {
+			text.push_str(&format!(
+				"  frames: {} ({:.1}/s)",
+				format_thousands(self.frames),
+				self.frames_per_second()
+			));
+		}
+		if let Some(bytes) = self.peak_memory {
+			text.push_str(&format!("\n  peak memory: {}", format_size(bytes, SizeUnits::Binary)));
+		}
+		text
+	}
+
+	pub fn to_json(&self) -> String {
+		let number = |value: Option<f64>| match value.filter(|v| v.is_finite()) {
+			Some(value) => format!("{:.3}", value),
+			None => "null".to_string(),
+		};
+		format!(
+			"{{\"wall\":{},\"cpu\":{},\"media\":{},\"speed\":{},\"packets\":{},\"packets_per_second\":{},\"frames\":{},\"frames_per_second\":{},\"peak_memory\":{}}}",
+			number(Some(self.wall.as_secs_f64())),
+			number(self.cpu.map(|cpu| cpu.as_secs_f64())),
+			number(Some(self.media)),
+			number(self.speed()),
+			self.packets,
+			number(Some(self.packets_per_second())),
+			self.frames,
+			number(Some(self.frames_per_second())),
+			self.peak_memory.map_or("null".to_string(), |bytes| bytes.to_string())
+		)
+	}
+}
+
+fn per_second(count: u64, wall: Duration) -> f64 {
+	let secs = wall.as_secs_f64();
+	if secs > 0.0 { count as f64 / secs } else { 0.0 }
+}
+
+// user plus system time from /proc, in the clock ticks of the kernel's fixed
+// 100 Hz user-facing clock
+fn cpu_time() -> Option<Duration> {
+	let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+	// the command name in parentheses may hold spaces, so count from after it
+	let fields: Vec<&str> = stat.get(stat.rfind(')')? + 2..)?.split(' ').collect();
+	let utime: u64 = fields.get(11)?.parse().ok()?;
+	let stime: u64 = fields.get(12)?.parse().ok()?;
+	Some(Duration::from_millis((utime + stime) * 10))
+}
+
+// the high water mark of the resident set from /proc
+fn peak_memory() -> Option<u64> {
+	let status = std::fs::read_to_string("/proc/self/status").ok()?;
+	let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+	let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+	Some(kib * 1024)
+}
@@ -0,0 +1,59 @@
+use crate::codecs::CodecId;
+use crate::container::MediaType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+	// decoded and re-encoded: transforms, edl cuts and a codec choice all apply
+	Supported,
+	// packets are copied across unchanged, so transforms have no effect
+	PassthroughOnly,
+	// built without the named cargo feature. nothing is feature gated yet, but
+	// front ends should already treat this as "not in this build".
+	RequiresFeature(&'static str),
+	Unsupported,
+}
+
+impl Capability {
+	pub fn is_possible(&self) -> bool {
+		matches!(self, Capability::Supported | Capability::PassthroughOnly)
+	}
+}
+
+// what `Transcoder` can do for the pair of containers, optionally producing
+// `codec` in the output. answers without touching any file so a front end can
+// grey out options up front.
+pub fn can_convert(from: MediaType, to: MediaType, codec: Option<CodecId>) -> Capability {
+	match (from, to) {
+		(MediaType::Wav, MediaType::Wav) => match codec {
+			None => Capability::Supported,
+			Some(codec) if codec.is_pcm() && !codec.is_big_endian() => Capability::Supported,
+			Some(_) => Capability::Unsupported,
+		},
+		(MediaType::Y4m, MediaType::Y4m) => match codec {
+			None | Some(CodecId::RawVideo) => Capability::Supported,
+			Some(_) => Capability::Unsupported,
+		},
+		(MediaType::Wav, MediaType::Flac)
+		| (MediaType::Flac, MediaType::Wav)
+		| (MediaType::Flac, MediaType::Flac)
+		| (MediaType::Avi, MediaType::Avi)
+		| (MediaType::Mp4, MediaType::Mp4)
+		| (MediaType::Mp3, MediaType::Mp3)
+		| (MediaType::Ogg, MediaType::Ogg) => {
+			// a copy keeps whatever the container already holds
+			if codec.is_none() || codec == native_codec(to) {
+				Capability::PassthroughOnly
+			} else {
+				Capability::Unsupported
+			}
+		}
+		_ => Capability::Unsupported,
+	}
+}
+
+fn native_codec(media_type: MediaType) -> Option<CodecId> {
+	match media_type {
+		MediaType::Flac => Some(CodecId::Flac),
+		_ => None,
+	}
+}
@@ -0,0 +1,353 @@
+use crate::codecs::flac::FlacStreamInfo;
+use crate::codecs::flac::frame::decode_frame;
+use crate::codecs::{PcmDecoder, RawVideoDecoder};
+use crate::container::flac::{frame_crc_ok, frame_header, parse_streaminfo};
+use crate::container::hash::Md5;
+use crate::container::{
+	AviReader, FlacFormat, MediaType, Mp3Reader, Mp4Reader, OggReader, WavReader, Y4mReader,
+};
+use crate::core::{Decoder, Demuxer};
+use crate::io::json::escape;
+use crate::io::{
+	AllowAll, CountingReader, FileAdapter, IoError, IoResult, MediaSeek, PathPolicy, ReadPrimitives,
+	SeekFrom,
+};
+use crate::show::mp4_packets;
+use std::sync::Arc;
+
+// listed in full up to this many; the count goes on past it
+const LISTED_ERRORS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckError {
+	// where in the file the damaged frame, or whatever is wrong, starts
+	pub offset: u64,
+	pub message: String,
+}
+
+// what `--check` found: frames read, how many of them were damaged, and
+// every problem in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+	pub frames: u64,
+	pub damaged: u64,
+	pub errors: Vec<CheckError>,
+	// whether the decoded audio matches a flac's STREAMINFO md5; None when it
+	// has none or a damaged frame left nothing to compare
+	pub md5: Option<bool>,
+}
+
+impl CheckReport {
+	pub fn is_ok(&self) -> bool {
+		self.errors.is_empty() && self.md5 != Some(false)
+	}
+
+	pub fn first_error(&self) -> Option<&CheckError> {
+		self.errors.first()
+	}
+
+	fn error(&mut self, offset: u64, message: impl Into<String>) {
+		self.errors.push(CheckError { offset, message: message.into() });
+	}
+
+	// one line when all is well, otherwise the first errors under it
+	pub fn to_text(&self, path: &str) -> String {
+		let md5 = match self.md5 {
+			Some(true) => ", md5 ok",
+			Some(false) => ", md5 mismatch",
+			None => "",
+		};
+		if self.is_ok() {
+			return format!("{}: ok, {} frames{}\n", path, self.frames, md5);
+		}
+		let mut out = format!("{}: {} of {} frames damaged{}\n", path, self.damaged, self.frames, md5);
+		for error in self.errors.iter().take(LISTED_ERRORS) {
+			out.push_str(&format!("  offset {}: {}\n", error.offset, error.message));
+		}
+		if self.errors.len() > LISTED_ERRORS {
+			out.push_str(&format!("  and {} more\n", self.errors.len() - LISTED_ERRORS));
+		}
+		out
+	}
+
+	pub fn to_json(&self, path: &str) -> String {
+		let md5 = match self.md5 {
+			Some(matches) => matches.to_string(),
+			None => "null".to_string(),
+		};
+		let errors: Vec<String> = self
+			.errors
+			.iter()
+			.map(|error| {
+				format!("{{\"offset\":{},\"message\":\"{}\"}}", error.offset, escape(&error.message))
+			})
+			.collect();
+		format!(
+			"{{\"file\":\"{}\",\"ok\":{},\"frames\":{},\"damaged\":{},\"md5\":{},\"errors\":[{}]}}",
+			escape(path),
+			self.is_ok(),
+			self.frames,
+			self.damaged,
+			md5,
+			errors.join(",")
+		)
+	}
+}
+
+// demuxes and decodes a whole file, checking every crc and md5 the format
+// has: flac frames are found by their sync code and header crc, so one bad
+// frame does not hide the rest; other formats stop at the first error
+pub struct Check {
+	input: String,
+	json: bool,
+	format: Option<MediaType>,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl Check {
+	pub fn new(input: String) -> Self {
+		Self { input, json: false, format: None, path_policy: Arc::new(AllowAll) }
+	}
+
+	pub fn with_json(mut self, json: bool) -> Self {
+		self.json = json;
+		self
+	}
+
+	pub fn with_format(mut self, format: MediaType) -> Self {
+		self.format = Some(format);
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	// prints the report, and fails when it found anything
+	pub fn run(&self) -> IoResult<()> {
+		let report = self.verify()?;
+		if self.json {
+			println!("{}", report.to_json(&self.input));
+		} else {
+			print!("{}", report.to_text(&self.input));
+		}
+		if !report.is_ok() {
+			return Err(IoError::invalid_data("the file is damaged"));
+		}
+		Ok(())
+	}
+
+	pub fn verify(&self) -> IoResult<CheckReport> {
+		let media_type = self.format.unwrap_or_else(|| MediaType::from_extension(&self.input));
+		let input = CountingReader::new(self.open()?);
+		let position = input.position();
+		let mut report = CheckReport::default();
+		match media_type {
+			MediaType::Flac => {
+				// read whole, to look for each frame's sync code past a bad one
+				let mut input = input;
+				let data = input.read_to_end()?;
+				check_flac(&data, &mut report)?;
+			}
+			MediaType::Wav => {
+				let mut reader = WavReader::new(input)?;
+				let mut decoder = PcmDecoder::new(reader.format());
+				check_packets(&mut reader, Some(&mut decoder), |_| position.get(), &mut report);
+				let missing = reader.total_frames().saturating_sub(reader.position());
+				if report.errors.is_empty() && missing > 0 {
+					report.error(position.get(), format!("data chunk ends {} frames short", missing));
+				}
+			}
+			MediaType::Y4m => {
+				let mut reader = Y4mReader::new(input)?;
+				let mut decoder = RawVideoDecoder::new(reader.format());
+				let offset = |reader: &Y4mReader<_>| position.get() - reader.read_ahead() as u64;
+				check_packets(&mut reader, Some(&mut decoder), offset, &mut report);
+			}
+			MediaType::Avi => {
+				let mut reader = AviReader::new(input)?;
+				check_packets(&mut reader, None::<&mut PcmDecoder>, |_| position.get(), &mut report);
+			}
+			MediaType::Mp3 => {
+				let mut reader = Mp3Reader::new(input)?;
+				check_packets(&mut reader, None::<&mut PcmDecoder>, |_| position.get(), &mut report);
+			}
+			MediaType::Ogg => {
+				let mut reader = OggReader::new(input)?;
+				check_packets(&mut reader, None::<&mut PcmDecoder>, |_| position.get(), &mut report);
+			}
+			MediaType::Mp4 => {
+				let len = self.open()?.seek(SeekFrom::End(0))?;
+				// samples are where the tables say, so each must lie inside the file
+				for packet in mp4_packets(Mp4Reader::new(input)?.format()) {
+					report.frames += 1;
+					if packet.offset + packet.size as u64 > len {
+						report.damaged += 1;
+						report.error(packet.offset, "sample runs past the end of the file");
+					}
+				}
+			}
+			MediaType::Unknown => return Err(IoError::invalid_data("unsupported file format")),
+		}
+		Ok(report)
+	}
+
+	fn open(&self) -> IoResult<FileAdapter> {
+		FileAdapter::open_with(&self.input, self.path_policy.as_ref())
+	}
+}
+
+// the first error ends it: without a sync code to look for there is no
+// telling where the next good packet starts
+fn check_packets<D: Demuxer, C: Decoder>(
+	reader: &mut D,
+	mut decoder: Option<&mut C>,
+	offset: impl Fn(&D) -> u64,
+	report: &mut CheckReport,
+) {
+	loop {
+		let start = offset(reader);
+		match reader.read_packet() {
+			Ok(Some(packet)) => {
+				report.frames += 1;
+				if let Some(decoder) = decoder.as_mut()
+					&& let Err(e) = decoder.decode(packet)
+				{
+					report.damaged += 1;
+					report.error(start, e.to_string());
+					return;
+				}
+			}
+			Ok(None) => return,
+			Err(e) => {
+				report.damaged += 1;
+				report.error(start, e.to_string());
+				return;
+			}
+		}
+	}
+}
+
+// one frame the sync scan found: where it lies in the file, the samples its
+// header says it holds, and whether its crc held
+pub(crate) struct FlacFrameSpan {
+	pub start: usize,
+	pub end: usize,
+	pub block_size: usize,
+	pub sound: bool,
+}
+
+// STREAMINFO, where the audio starts and every frame of a whole flac file,
+// found by sync code and header crc rather than by trusting sizes. a sync
+// code with a good header crc can turn up inside a frame, so a frame ends at
+// the first later start its crc holds up to
+pub(crate) fn flac_frames(data: &[u8]) -> IoResult<(FlacFormat, usize, Vec<FlacFrameSpan>)> {
+	let (format, audio_start) = flac_header(data)?;
+	let starts: Vec<(usize, usize)> = (audio_start..data.len())
+		.filter_map(|at| frame_header(&data[at..]).map(|(_, block_size)| (at, block_size)))
+		.collect();
+
+	let longest = (format.max_frame_size as usize).max(1 << 16);
+	let mut frames = Vec::new();
+	let mut next = 0;
+	while next < starts.len() {
+		let (start, block_size) = starts[next];
+		let ends = starts[next + 1..].iter().map(|&(at, _)| at).chain([data.len()]);
+		let ends: Vec<usize> = ends.take_while(|&end| end - start <= longest).collect();
+		let sound = ends.iter().position(|&end| end - start >= 4 && frame_crc_ok(&data[start..end]));
+		let (end, skipped) = match sound {
+			Some(i) => (ends[i], i),
+			None => (starts.get(next + 1).map_or(data.len(), |&(at, _)| at), 0),
+		};
+		next += 1 + skipped;
+		frames.push(FlacFrameSpan { start, end, block_size, sound: sound.is_some() });
+	}
+	Ok((format, audio_start, frames))
+}
+
+fn check_flac(data: &[u8], report: &mut CheckReport) -> IoResult<()> {
+	let (format, audio_start, frames) = flac_frames(data)?;
+	let stream_info = FlacStreamInfo {
+		min_block_size: format.min_block_size,
+		max_block_size: format.max_block_size,
+		min_frame_size: format.min_frame_size,
+		max_frame_size: format.max_frame_size,
+		sample_rate: format.sample_rate,
+		channels: format.channels,
+		bits_per_sample: format.bits_per_sample,
+		total_samples: format.total_samples,
+	};
+	if frames.first().map(|frame| frame.start) != Some(audio_start) {
+		let first = frames.first().map_or(data.len(), |frame| frame.start);
+		report
+			.error(audio_start as u64, format!("{} bytes before the first frame", first - audio_start));
+	}
+
+	let bytes_per_sample = format.bits_per_sample.div_ceil(8) as usize;
+	let mut md5 = Md5::new();
+	let mut samples = 0u64;
+	for frame in &frames {
+		report.frames += 1;
+		if !frame.sound {
+			report.damaged += 1;
+			report.error(frame.start as u64, "frame crc mismatch");
+			continue;
+		}
+		match decode_frame(&data[frame.start..frame.end], &stream_info) {
+			Ok(frame) => {
+				samples += frame.block_size as u64;
+				for i in 0..frame.block_size {
+					for channel in &frame.samples {
+						md5.update(&channel[i].to_le_bytes()[..bytes_per_sample]);
+					}
+				}
+			}
+			Err(e) => {
+				report.damaged += 1;
+				report.error(frame.start as u64, e.to_string());
+			}
+		}
+	}
+
+	if report.damaged == 0 {
+		if format.total_samples > 0 && samples != format.total_samples {
+			report.error(
+				data.len() as u64,
+				format!("{} samples decoded, STREAMINFO says {}", samples, format.total_samples),
+			);
+		}
+		// all zeros is what an encoder that did not compute it writes
+		if format.md5_signature != [0; 16] {
+			report.md5 = Some(md5.finish() == format.md5_signature);
+		}
+	}
+	Ok(())
+}
+
+// STREAMINFO and where the first frame starts, past every metadata block
+fn flac_header(data: &[u8]) -> IoResult<(FlacFormat, usize)> {
+	if !data.starts_with(b"fLaC") {
+		return Err(IoError::invalid_data("not a FLAC file"));
+	}
+	let mut at = 4;
+	let mut format = None;
+	loop {
+		let Some(header) = data.get(at..at + 4) else {
+			return Err(IoError::invalid_data("metadata blocks run past the end of the file"));
+		};
+		let size = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+		let body = data
+			.get(at + 4..at + 4 + size)
+			.ok_or_else(|| IoError::invalid_data("metadata blocks run past the end of the file"))?;
+		if header[0] & 0x7f == 0 {
+			format = parse_streaminfo(body);
+		}
+		at += 4 + size;
+		if header[0] & 0x80 != 0 {
+			break;
+		}
+	}
+	let format = format.ok_or_else(|| IoError::invalid_data("no STREAMINFO block found"))?;
+	Ok((format, at))
+}
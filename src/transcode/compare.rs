@@ -0,0 +1,231 @@
+use crate::codecs::RawVideoDecoder;
+use crate::container::{MediaType, Y4mReader};
+use crate::core::{Decoder, Demuxer, FrameData, FrameVideo};
+use crate::io::{AllowAll, FileAdapter, IoError, IoResult, PathPolicy};
+use std::sync::Arc;
+
+// scores for one frame pair, measured on luma. psnr is infinite for
+// identical frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameScore {
+	pub mse: f64,
+	pub psnr: f64,
+	pub ssim: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comparison {
+	pub frames: Vec<FrameScore>,
+	// frames left over in the longer input
+	pub unmatched: usize,
+}
+
+impl Comparison {
+	// from the mean squared error over all frames, so one identical frame does
+	// not make the average infinite
+	pub fn average_psnr(&self) -> f64 {
+		let mse = self.frames.iter().map(|f| f.mse).sum::<f64>() / self.frames.len().max(1) as f64;
+		psnr_from_mse(mse)
+	}
+
+	pub fn average_ssim(&self) -> f64 {
+		self.frames.iter().map(|f| f.ssim).sum::<f64>() / self.frames.len().max(1) as f64
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut out = String::new();
+		for (idx, frame) in self.frames.iter().enumerate() {
+			out.push_str(&format!(
+				"frame {}: psnr {} ssim {:.6}\n",
+				idx,
+				text_db(frame.psnr),
+				frame.ssim
+			));
+		}
+		out.push_str(&format!(
+			"average: psnr {} ssim {:.6} ({} frames)\n",
+			text_db(self.average_psnr()),
+			self.average_ssim(),
+			self.frames.len()
+		));
+		if self.unmatched > 0 {
+			out.push_str(&format!("{} frames had no counterpart and were skipped\n", self.unmatched));
+		}
+		out
+	}
+
+	pub fn to_json(&self) -> String {
+		let frames: Vec<String> = self
+			.frames
+			.iter()
+			.map(|f| format!("{{\"psnr\":{},\"ssim\":{:.6}}}", json_db(f.psnr), f.ssim))
+			.collect();
+		format!(
+			"{{\"frames\":[{}],\"average\":{{\"psnr\":{},\"ssim\":{:.6}}},\"unmatched\":{}}}",
+			frames.join(","),
+			json_db(self.average_psnr()),
+			self.average_ssim(),
+			self.unmatched
+		)
+	}
+}
+
+fn text_db(db: f64) -> String {
+	if db.is_finite() { format!("{:.2} dB", db) } else { "inf".to_string() }
+}
+
+fn json_db(db: f64) -> String {
+	if db.is_finite() { format!("{:.4}", db) } else { "null".to_string() }
+}
+
+fn psnr_from_mse(mse: f64) -> f64 {
+	if mse == 0.0 { f64::INFINITY } else { 10.0 * (255.0 * 255.0 / mse).log10() }
+}
+
+pub fn mse(a: &[u8], b: &[u8]) -> f64 {
+	let sum: f64 = a.iter().zip(b).map(|(&x, &y)| (x as f64 - y as f64).powi(2)).sum();
+	sum / a.len().min(b.len()).max(1) as f64
+}
+
+pub fn psnr(a: &[u8], b: &[u8]) -> f64 {
+	psnr_from_mse(mse(a, b))
+}
+
+// mean ssim over 8x8 windows placed every 4 pixels; planes smaller than a
+// window are scored as one window
+pub fn ssim(a: &[u8], b: &[u8], width: u32, height: u32) -> f64 {
+	const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+	const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+	let (width, height) = (width as usize, height as usize);
+	let (win_w, win_h) = (width.min(8), height.min(8));
+	if win_w == 0 || win_h == 0 {
+		return 1.0;
+	}
+
+	let (mut total, mut windows) = (0.0, 0usize);
+	let starts = |size: usize, window: usize| (0..=size - window).step_by(4);
+	for y0 in starts(height, win_h) {
+		for x0 in starts(width, win_w) {
+			let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+			for y in y0..y0 + win_h {
+				for x in x0..x0 + win_w {
+					let (va, vb) = (a[y * width + x] as f64, b[y * width + x] as f64);
+					sum_a += va;
+					sum_b += vb;
+					sum_aa += va * va;
+					sum_bb += vb * vb;
+					sum_ab += va * vb;
+				}
+			}
+			let n = (win_w * win_h) as f64;
+			let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+			let var_a = sum_aa / n - mean_a * mean_a;
+			let var_b = sum_bb / n - mean_b * mean_b;
+			let covariance = sum_ab / n - mean_a * mean_b;
+			total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+				/ ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+			windows += 1;
+		}
+	}
+	total / windows as f64
+}
+
+pub fn score_frames(a: &FrameVideo, b: &FrameVideo) -> IoResult<FrameScore> {
+	if (a.width, a.height) != (b.width, b.height) {
+		return Err(IoError::invalid_data("compared videos differ in size"));
+	}
+	let luma = (a.width * a.height) as usize;
+	if a.data.len() < luma || b.data.len() < luma {
+		return Err(IoError::invalid_data("video frame is smaller than its format"));
+	}
+	let (a_luma, b_luma) = (&a.data[..luma], &b.data[..luma]);
+	let mse = mse(a_luma, b_luma);
+	Ok(FrameScore { mse, psnr: psnr_from_mse(mse), ssim: ssim(a_luma, b_luma, a.width, a.height) })
+}
+
+// decodes two y4m files in lockstep and scores each frame of `input` against
+// the same frame of `reference`
+pub struct Compare {
+	input: String,
+	reference: String,
+	json: bool,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl Compare {
+	pub fn new(input: String, reference: String) -> Self {
+		Self { input, reference, json: false, path_policy: Arc::new(AllowAll) }
+	}
+
+	pub fn with_json(mut self, json: bool) -> Self {
+		self.json = json;
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	pub fn run(&self) -> IoResult<()> {
+		let comparison = self.measure()?;
+		if self.json {
+			println!("{}", comparison.to_json());
+		} else {
+			print!("{}", comparison.to_text());
+		}
+		Ok(())
+	}
+
+	pub fn measure(&self) -> IoResult<Comparison> {
+		let mut input = self.open(&self.input)?;
+		let mut reference = self.open(&self.reference)?;
+		let mut comparison = Comparison::default();
+		loop {
+			match (input.next_frame()?, reference.next_frame()?) {
+				(Some(a), Some(b)) => comparison.frames.push(score_frames(&a, &b)?),
+				(Some(_), None) => comparison.unmatched += 1 + input.drain()?,
+				(None, Some(_)) => comparison.unmatched += 1 + reference.drain()?,
+				(None, None) => break,
+			}
+		}
+		Ok(comparison)
+	}
+
+	fn open(&self, path: &str) -> IoResult<VideoSource> {
+		if MediaType::from_extension(path) != MediaType::Y4m {
+			return Err(IoError::invalid_data("compare needs y4m inputs"));
+		}
+		let reader = Y4mReader::new(FileAdapter::open_with(path, self.path_policy.as_ref())?)?;
+		let decoder = RawVideoDecoder::new(reader.format());
+		Ok(VideoSource { reader, decoder })
+	}
+}
+
+struct VideoSource {
+	reader: Y4mReader<FileAdapter>,
+	decoder: RawVideoDecoder,
+}
+
+impl VideoSource {
+	fn next_frame(&mut self) -> IoResult<Option<FrameVideo>> {
+		while let Some(packet) = self.reader.read_packet()? {
+			if let Some(frame) = self.decoder.decode(packet)? {
+				let FrameData::Video(video) = frame.data else {
+					return Err(IoError::invalid_data("y4m decoder produced a non-video frame"));
+				};
+				return Ok(Some(video));
+			}
+		}
+		Ok(None)
+	}
+
+	// frames left after the other input ended
+	fn drain(&mut self) -> IoResult<usize> {
+		let mut count = 0;
+		while self.next_frame()?.is_some() {
+			count += 1;
+		}
+		Ok(count)
+	}
+}
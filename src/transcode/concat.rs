@@ -0,0 +1,188 @@
+use super::decode_audio;
+use crate::codecs::PcmEncoder;
+use crate::container::{FlacReader, MediaType, WavFormat, WavReader, WavWriter};
+use crate::core::{
+	ChannelLayout, Encoder, Frame, FrameAudio, Muxer, SampleFormat, Timebase, Transform,
+};
+use crate::io::{AllowAll, FileAdapter, IoError, IoResult, PathPolicy};
+use crate::transform::{ChannelMixer, Crossfade, Resample, TransformChain};
+use std::sync::Arc;
+
+// joins audio files into one wav, overlapping the end of each with the start
+// of the next. the output takes the first input's rate and channel count; the
+// others are resampled and channel-mixed to match.
+pub struct Concat {
+	first: String,
+	second: String,
+	// inputs after the second, in order
+	more: Vec<String>,
+	output: String,
+	crossfade: f64,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl Concat {
+	pub fn new(first: String, second: String, output: String) -> Self {
+		Self {
+			first,
+			second,
+			more: Vec::new(),
+			output,
+			crossfade: 0.0,
+			path_policy: Arc::new(AllowAll),
+		}
+	}
+
+	// appends another input after the ones already given
+	pub fn with_input(mut self, path: String) -> Self {
+		self.more.push(path);
+		self
+	}
+
+	// overlap in seconds; 0 (the default) butts the inputs together
+	pub fn with_crossfade(mut self, seconds: f64) -> Self {
+		self.crossfade = seconds;
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	pub fn run(&self) -> IoResult<()> {
+		if MediaType::from_extension(&self.output) != MediaType::Wav {
+			return Err(IoError::invalid_data("concatenated output must be wav"));
+		}
+		if !(self.crossfade.is_finite() && self.crossfade >= 0.0) {
+			return Err(IoError::invalid_data("crossfade duration must not be negative"));
+		}
+
+		let policy = self.path_policy.as_ref();
+		let (rate, channels) = probe_audio(&self.first, policy)?;
+
+		let format = WavFormat { channels, sample_rate: rate, bit_depth: 16 };
+		let mut out = AudioOut {
+			writer: WavWriter::new(FileAdapter::create_with(&self.output, policy)?, format)?,
+			encoder: PcmEncoder::new(Timebase::new(1, rate)),
+			rate,
+			channels,
+			pts: 0,
+		};
+
+		// the last `tail` samples of each input but the last are held back and
+		// only written blended into the next
+		let duration_ms = (self.crossfade * 1000.0) as f32;
+		let tail = (duration_ms * rate as f32 / 1000.0) as usize * channels as usize;
+		let mut held: Vec<i16> = Vec::new();
+		let hold = |out: &mut AudioOut, held: &mut Vec<i16>, samples: Vec<i16>| -> IoResult<()> {
+			held.extend(samples);
+			if held.len() > tail {
+				let kept = held.split_off(held.len() - tail);
+				out.write(std::mem::replace(held, kept))?;
+			}
+			Ok(())
+		};
+
+		decode_audio(&self.first, policy, |frame| match frame.audio() {
+			Some(audio) => hold(&mut out, &mut held, samples_i16(audio)),
+			None => Ok(()),
+		})?;
+
+		let mut rest = std::iter::once(&self.second).chain(&self.more).peekable();
+		while let Some(path) = rest.next() {
+			let is_last = rest.peek().is_none();
+			let (input_rate, input_channels) = probe_audio(path, policy)?;
+			let mut crossfade = Crossfade::new(duration_ms, rate, channels);
+			crossfade.feed_previous(&out.frame(std::mem::take(&mut held)));
+			crossfade.start_crossfade();
+
+			let mut convert = TransformChain::new();
+			if input_rate != rate {
+				convert.add(Box::new(Resample::new(rate)));
+			}
+			if input_channels != channels {
+				convert.add(Box::new(ChannelMixer::new(ChannelLayout::from_channels(channels))));
+			}
+
+			let emit = |out: &mut AudioOut, held: &mut Vec<i16>, frame: Frame| -> IoResult<()> {
+				match frame.audio() {
+					Some(audio) if audio.channels != channels => {
+						Err(IoError::invalid_data("cannot mix an input down to the first input's channels"))
+					}
+					Some(_) if is_last => out.write_frame(frame),
+					Some(audio) => hold(out, held, samples_i16(audio)),
+					None => Ok(()),
+				}
+			};
+			decode_audio(path, policy, |frame| {
+				let frame = crossfade.apply(convert.apply(frame)?)?;
+				emit(&mut out, &mut held, frame)
+			})?;
+
+			// an input shorter than the overlap leaves part of the tail unplayed
+			let remaining = crossfade.remaining();
+			if remaining > 0 {
+				let silence = out.frame(vec![0; remaining * channels as usize]);
+				emit(&mut out, &mut held, crossfade.apply(silence)?)?;
+			}
+		}
+
+		out.writer.finalize()
+	}
+}
+
+struct AudioOut {
+	writer: WavWriter<FileAdapter>,
+	encoder: PcmEncoder,
+	rate: u32,
+	channels: u8,
+	pts: i64,
+}
+
+impl AudioOut {
+	fn frame(&self, samples: Vec<i16>) -> Frame {
+		let nb_samples = samples.len() / self.channels.max(1) as usize;
+		let data = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+		let audio = FrameAudio::new(data, self.rate, self.channels).with_nb_samples(nb_samples);
+		Frame::new_audio(audio, Timebase::new(1, self.rate), 0)
+	}
+
+	fn write(&mut self, samples: Vec<i16>) -> IoResult<()> {
+		let frame = self.frame(samples);
+		self.write_frame(frame)
+	}
+
+	// restamps the frame so the output timeline runs on from the previous one
+	fn write_frame(&mut self, frame: Frame) -> IoResult<()> {
+		let frame = frame.with_pts(self.pts);
+		self.pts += frame.audio().map(|a| a.nb_samples as i64).unwrap_or(0);
+		if let Some(packet) = self.encoder.encode(frame)? {
+			self.writer.write_packet(packet)?;
+		}
+		Ok(())
+	}
+}
+
+// interleaved s16, whatever the frame's storage format
+fn samples_i16(audio: &FrameAudio) -> Vec<i16> {
+	let mut audio = audio.clone();
+	audio.convert(SampleFormat::S16);
+	audio.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+// sample rate and channel count from the container header
+fn probe_audio(path: &str, policy: &dyn PathPolicy) -> IoResult<(u32, u8)> {
+	let input = FileAdapter::open_with(path, policy)?;
+	match MediaType::from_extension(path) {
+		MediaType::Wav => {
+			let format = WavReader::new(input)?.format();
+			Ok((format.sample_rate, format.channels))
+		}
+		MediaType::Flac => {
+			let reader = FlacReader::new(input)?;
+			Ok((reader.format().sample_rate, reader.format().channels))
+		}
+		_ => Err(IoError::invalid_data("audio input must be wav or flac")),
+	}
+}
@@ -0,0 +1,281 @@
+use super::source::{Frames, open_frames};
+use crate::core::{FrameData, StreamFormat};
+use crate::io::json::escape;
+use crate::io::{AllowAll, IoError, IoErrorKind, IoResult, PathPolicy};
+use std::sync::Arc;
+
+// what `--diff` found between two decoded streams
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+	// what each input decodes to; when these differ nothing else is compared
+	pub formats: (StreamFormat, StreamFormat),
+	// sample frames of audio, pictures of video
+	pub lengths: (u64, u64),
+	// the sample frame or picture the first difference is in, counted from 0
+	pub first_difference: Option<u64>,
+	// samples of any channel, or bytes of a picture, that differ
+	pub differences: u64,
+	// the largest difference of one sample: of full scale for audio, in 8 bit
+	// levels for video
+	pub max_deviation: f64,
+}
+
+impl DiffReport {
+	pub fn is_identical(&self) -> bool {
+		self.formats.0 == self.formats.1
+			&& self.lengths.0 == self.lengths.1
+			&& self.first_difference.is_none()
+	}
+
+	fn is_audio(&self) -> bool {
+		matches!(self.formats.0, StreamFormat::Audio { .. })
+	}
+
+	// "a.wav and b.flac are identical", or why not
+	pub fn to_text(&self, a: &str, b: &str) -> String {
+		let (unit, one) = if self.is_audio() { ("samples", "sample") } else { ("frames", "frame") };
+		if self.is_identical() {
+			return format!("{} and {} are identical ({} {})\n", a, b, self.lengths.0, unit);
+		}
+		let mut out = format!("{} and {} differ\n", a, b);
+		if self.formats.0 != self.formats.1 {
+			out.push_str(&format!(
+				"  formats: {} vs {}\n",
+				describe(&self.formats.0),
+				describe(&self.formats.1)
+			));
+			return out;
+		}
+		if let Some(first) = self.first_difference {
+			let time = match self.formats.0 {
+				StreamFormat::Audio { sample_rate, .. } if sample_rate > 0 => {
+					format!(" ({:.6} s)", first as f64 / sample_rate as f64)
+				}
+				_ => String::new(),
+			};
+			out.push_str(&format!("  first difference: {} {}{}\n", one, first, time));
+			let deviation = if self.is_audio() {
+				format!("{:.6} of full scale ({:.1} dBFS)", self.max_deviation, db(self.max_deviation))
+			} else {
+				format!("{} levels", self.max_deviation)
+			};
+			out.push_str(&format!(
+				"  {} {} differ, max deviation {}\n",
+				self.differences,
+				if self.is_audio() { "samples" } else { "bytes" },
+				deviation
+			));
+		}
+		if self.lengths.0 != self.lengths.1 {
+			out.push_str(&format!("  lengths: {} vs {} {}\n", self.lengths.0, self.lengths.1, unit));
+			if self.first_difference.is_none() {
+				out.push_str(&format!("  the first {} {} match\n", self.compared(), unit));
+			} else {
+				out.push_str(&format!("  compared over the first {} {}\n", self.compared(), unit));
+			}
+		}
+		out
+	}
+
+	// how far both inputs go, which is all the differences are counted over
+	pub fn compared(&self) -> u64 {
+		self.lengths.0.min(self.lengths.1)
+	}
+
+	pub fn to_json(&self, a: &str, b: &str) -> String {
+		let first = self.first_difference.map_or("null".to_string(), |first| first.to_string());
+		format!(
+			"{{\"a\":\"{}\",\"b\":\"{}\",\"identical\":{},\"formats\":[\"{}\",\"{}\"],\"lengths\":[{},{}],\"compared\":{},\"first_difference\":{},\"differences\":{},\"max_deviation\":{}}}",
+			escape(a),
+			escape(b),
+			self.is_identical(),
+			describe(&self.formats.0),
+			describe(&self.formats.1),
+			self.lengths.0,
+			self.lengths.1,
+			self.compared(),
+			first,
+			self.differences,
+			self.max_deviation
+		)
+	}
+}
+
+fn describe(format: &StreamFormat) -> String {
+	match format {
+		StreamFormat::Audio { sample_rate, channels } => {
+			format!("audio {} Hz {} channels", sample_rate, channels)
+		}
+		StreamFormat::Video { width, height, format } => {
+			format!("video {}x{} {:?}", width, height, format)
+		}
+	}
+}
+
+fn db(level: f64) -> f64 {
+	20.0 * level.log10()
+}
+
+// decodes two wav, flac or y4m files side by side and compares what they
+// decode to, whatever the container or codec: a wav against the flac made of
+// it is identical when every sample is
+pub struct Diff {
+	a: String,
+	b: String,
+	json: bool,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl Diff {
+	pub fn new(a: String, b: String) -> Self {
+		Self { a, b, json: false, path_policy: Arc::new(AllowAll) }
+	}
+
+	pub fn with_json(mut self, json: bool) -> Self {
+		self.json = json;
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	// prints the report and fails when the streams differ, as diff(1) does
+	pub fn run(&self) -> IoResult<()> {
+		let report = self.compare()?;
+		if self.json {
+			println!("{}", report.to_json(&self.a, &self.b));
+		} else {
+			print!("{}", report.to_text(&self.a, &self.b));
+		}
+		if !report.is_identical() {
+			return Err(IoError::with_message(IoErrorKind::Other, "the streams differ"));
+		}
+		Ok(())
+	}
+
+	pub fn compare(&self) -> IoResult<DiffReport> {
+		let (format_a, mut a) = open_frames(&self.a, self.path_policy.as_ref())?;
+		let (format_b, mut b) = open_frames(&self.b, self.path_policy.as_ref())?;
+		let mut report = DiffReport {
+			formats: (format_a, format_b),
+			lengths: (0, 0),
+			first_difference: None,
+			differences: 0,
+			max_deviation: 0.0,
+		};
+		if format_a != format_b {
+			return Ok(report);
+		}
+		match format_a {
+			StreamFormat::Audio { channels, .. } => {
+				compare_audio(&mut a, &mut b, channels.max(1) as usize, &mut report)?
+			}
+			StreamFormat::Video { .. } => compare_video(&mut a, &mut b, &mut report)?,
+		}
+		Ok(report)
+	}
+}
+
+// samples as f32, which holds 16 and 24 bit ones exactly, so a wav and a flac
+// of other bit depths compare by value. each side keeps what the other has not
+// caught up with, since their frames need not be the same length
+fn compare_audio(
+	a: &mut Frames,
+	b: &mut Frames,
+	channels: usize,
+	report: &mut DiffReport,
+) -> IoResult<()> {
+	let (mut pending_a, mut pending_b) = (Vec::new(), Vec::new());
+	let (mut done_a, mut done_b) = (false, false);
+	let mut compared = 0u64;
+	loop {
+		if pending_a.is_empty() && !done_a {
+			done_a = !next_samples(a, &mut pending_a)?;
+		}
+		if pending_b.is_empty() && !done_b {
+			done_b = !next_samples(b, &mut pending_b)?;
+		}
+		let count = pending_a.len().min(pending_b.len());
+		if count == 0 {
+			break;
+		}
+		for (i, (x, y)) in pending_a.drain(..count).zip(pending_b.drain(..count)).enumerate() {
+			if x != y {
+				let sample = compared + i as u64;
+				report.first_difference.get_or_insert(sample / channels as u64);
+				report.differences += 1;
+				report.max_deviation = report.max_deviation.max((x - y).abs() as f64);
+			}
+		}
+		compared += count as u64;
+	}
+	let rest = |pending: &Vec<f32>, frames: &mut Frames| -> IoResult<u64> {
+		let mut total = pending.len() as u64;
+		while let Some(frame) = frames.next()? {
+			if let FrameData::Audio(audio) = frame.data {
+				total += (audio.nb_samples * channels) as u64;
+			}
+		}
+		Ok(total)
+	};
+	let left_a = if done_a { pending_a.len() as u64 } else { rest(&pending_a, a)? };
+	let left_b = if done_b { pending_b.len() as u64 } else { rest(&pending_b, b)? };
+	report.lengths = ((compared + left_a) / channels as u64, (compared + left_b) / channels as u64);
+	Ok(())
+}
+
+// false once the input has no more audio
+fn next_samples(frames: &mut Frames, pending: &mut Vec<f32>) -> IoResult<bool> {
+	while let Some(frame) = frames.next()? {
+		if let FrameData::Audio(audio) = frame.data {
+			pending.extend(audio.samples_f32());
+			if !pending.is_empty() {
+				return Ok(true);
+			}
+		}
+	}
+	Ok(false)
+}
+
+fn compare_video(a: &mut Frames, b: &mut Frames, report: &mut DiffReport) -> IoResult<()> {
+	let mut index = 0u64;
+	loop {
+		match (a.next()?, b.next()?) {
+			(Some(x), Some(y)) => {
+				let (FrameData::Video(x), FrameData::Video(y)) = (x.data, y.data) else {
+					return Err(IoError::invalid_data("y4m decoder produced a non-video frame"));
+				};
+				for (p, q) in x.data.iter().zip(&y.data) {
+					if p != q {
+						report.first_difference.get_or_insert(index);
+						report.differences += 1;
+						report.max_deviation = report.max_deviation.max(p.abs_diff(*q) as f64);
+					}
+				}
+				index += 1;
+			}
+			(Some(_), None) => {
+				let mut count = index + 1;
+				while a.next()?.is_some() {
+					count += 1;
+				}
+				report.lengths = (count, index);
+				return Ok(());
+			}
+			(None, Some(_)) => {
+				let mut count = index + 1;
+				while b.next()?.is_some() {
+					count += 1;
+				}
+				report.lengths = (index, count);
+				return Ok(());
+			}
+			(None, None) => {
+				report.lengths = (index, index);
+				return Ok(());
+			}
+		}
+	}
+}
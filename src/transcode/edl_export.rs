@@ -0,0 +1,73 @@
+use super::source::open_frames;
+use crate::core::{StreamFormat, Transform};
+use crate::edl::EditDecisionList;
+use crate::io::{AllowAll, IoError, IoResult, PathAccess, PathPolicy};
+use crate::transform::{SilenceDetect, TransformChain, TransformSpec};
+use std::path::Path;
+use std::sync::Arc;
+
+// the analysis half of an edit: runs the silencedetect of the transforms over
+// an input and keeps what lies between the silences as an edit decision list,
+// which `--edl` renders later against the same file. transforms before the
+// silencedetect shape what counts as silence (a highpass, say) but must not
+// retime the audio, since the list is in the input's time
+pub struct EdlExport {
+	input: String,
+	transforms: Vec<String>,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl EdlExport {
+	pub fn new(input: String, transforms: Vec<String>) -> Self {
+		Self { input, transforms, path_policy: Arc::new(AllowAll) }
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	// writes the list to `path`, as JSON when it ends in .json
+	pub fn run(&self, path: &str) -> IoResult<()> {
+		let edl = self.analyze()?;
+		self.path_policy.check(Path::new(path), PathAccess::Write)?;
+		edl.save(path)
+	}
+
+	pub fn analyze(&self) -> IoResult<EditDecisionList> {
+		let mut specs = Vec::new();
+		for spec in &self.transforms {
+			specs.extend(TransformSpec::parse_list(spec)?);
+		}
+		let found = specs.iter().enumerate().find_map(|(index, spec)| match spec.target() {
+			&TransformSpec::SilenceDetect { noise_db, min_duration, .. } => {
+				Some((index, noise_db, min_duration))
+			}
+			_ => None,
+		});
+		let Some((index, noise_db, min_duration)) = found else {
+			return Err(IoError::invalid_input("exporting an edl needs a silencedetect transform"));
+		};
+		specs.truncate(index);
+		let mut chain = TransformChain::from_specs_with(specs, self.path_policy.as_ref())?;
+		let mut detect = SilenceDetect::new(noise_db, min_duration);
+
+		let (format, mut frames) = open_frames(&self.input, self.path_policy.as_ref())?;
+		let StreamFormat::Audio { sample_rate, .. } = format else {
+			return Err(IoError::invalid_input("silence detection needs an audio input"));
+		};
+		let mut samples = 0u64;
+		while let Some(frame) = frames.next()? {
+			samples += frame.audio().map_or(0, |audio| audio.nb_samples as u64);
+			detect.apply(chain.apply(frame)?)?;
+		}
+		if let Some(tail) = chain.flush()? {
+			detect.apply(tail)?;
+		}
+
+		let duration = samples as f64 / sample_rate.max(1) as f64;
+		let silences: Vec<(f64, f64)> =
+			detect.close().iter().map(|range| (range.start, range.end)).collect();
+		Ok(EditDecisionList::from_removals(&silences, duration).with_source(self.input.clone()))
+	}
+}
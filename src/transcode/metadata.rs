@@ -0,0 +1,113 @@
+use crate::container::metadata::{Disposition, is_valid_language};
+use crate::container::mp4::{Mp4Format, TrackType};
+use crate::io::{IoError, IoResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamSelector {
+	Audio,
+	Video,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamMetadataValue {
+	Language(String),
+	Disposition(Disposition),
+}
+
+// one `--metadata:s:a:0 language=eng` assignment; `index` counts streams of the
+// selected type, so s:a:1 is the second audio stream whatever its track number
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamMetadata {
+	pub selector: Option<StreamSelector>,
+	pub index: Option<usize>,
+	pub value: StreamMetadataValue,
+}
+
+impl StreamMetadata {
+	// `spec` is `s[:a|v][:N]`, `assignment` is `key=value`
+	pub fn parse(spec: &str, assignment: &str) -> IoResult<Self> {
+		let mut parts = spec.split(':');
+		if parts.next() != Some("s") {
+			return Err(IoError::invalid_data("stream metadata specifier must start with 's'"));
+		}
+
+		let mut selector = None;
+		let mut index = None;
+		for part in parts {
+			match part {
+				"a" if selector.is_none() && index.is_none() => selector = Some(StreamSelector::Audio),
+				"v" if selector.is_none() && index.is_none() => selector = Some(StreamSelector::Video),
+				_ if index.is_none() => {
+					let n = part.parse().map_err(|_| IoError::invalid_data("invalid stream specifier"))?;
+					index = Some(n);
+				}
+				_ => return Err(IoError::invalid_data("invalid stream specifier")),
+			}
+		}
+
+		let (key, value) = assignment
+			.split_once('=')
+			.ok_or_else(|| IoError::invalid_data("stream metadata must be key=value"))?;
+
+		let value = match key {
+			"language" => {
+				let code = value.to_lowercase();
+				if !is_valid_language(&code) {
+					return Err(IoError::invalid_data("language must be a three-letter ISO 639-2 code"));
+				}
+				StreamMetadataValue::Language(code)
+			}
+			"disposition" => {
+				let disposition = Disposition::from_names(value)
+					.ok_or_else(|| IoError::invalid_data("unknown disposition (default, commentary)"))?;
+				StreamMetadataValue::Disposition(disposition)
+			}
+			_ => return Err(IoError::invalid_data("unsupported stream metadata key")),
+		};
+
+		Ok(Self { selector, index, value })
+	}
+
+	// the CLI form `s:a:0:language=eng`, as produced by `args::normalize_args`
+	pub fn parse_combined(text: &str) -> IoResult<Self> {
+		let (target, value) = text
+			.split_once('=')
+			.ok_or_else(|| IoError::invalid_data("stream metadata must be key=value"))?;
+		let (spec, key) =
+			target.rsplit_once(':').ok_or_else(|| IoError::invalid_data("invalid stream specifier"))?;
+		Self::parse(spec, &format!("{}={}", key, value))
+	}
+
+	pub fn apply_mp4(&self, format: &mut Mp4Format) -> IoResult<()> {
+		let mut matched = false;
+		let mut position = 0;
+
+		for track in format.tracks.iter_mut() {
+			let selected = match self.selector {
+				Some(StreamSelector::Audio) => track.track_type == TrackType::Audio,
+				Some(StreamSelector::Video) => track.track_type == TrackType::Video,
+				None => true,
+			};
+			if !selected {
+				continue;
+			}
+
+			let is_target = self.index.is_none_or(|index| index == position);
+			position += 1;
+			if !is_target {
+				continue;
+			}
+
+			match &self.value {
+				StreamMetadataValue::Language(code) => track.language = code.clone(),
+				StreamMetadataValue::Disposition(disposition) => track.disposition = *disposition,
+			}
+			matched = true;
+		}
+
+		if !matched {
+			return Err(IoError::invalid_data("stream metadata specifier matches no stream"));
+		}
+		Ok(())
+	}
+}
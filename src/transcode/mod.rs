@@ -0,0 +1,1651 @@
+pub mod benchmark;
+pub mod capability;
+pub mod check;
+pub mod compare;
+pub mod concat;
+pub mod diff;
+pub mod edl_export;
+pub mod metadata;
+pub mod progress;
+mod source;
+mod stages;
+mod tee;
+
+pub use benchmark::{Benchmark, BenchmarkReport};
+pub use capability::{Capability, can_convert};
+pub use check::{Check, CheckError, CheckReport};
+pub use compare::{Compare, Comparison, FrameScore};
+pub use concat::Concat;
+pub use diff::{Diff, DiffReport};
+pub use edl_export::EdlExport;
+pub use metadata::{StreamMetadata, StreamMetadataValue, StreamSelector};
+pub use progress::{Progress, ProgressMode, ProgressReport};
+
+use crate::codecs::{
+	CodecId, FlacDecoder, FlacEncoder, PcmDecoder, PcmEncoder, RawVideoDecoder, RawVideoEncoder,
+};
+use crate::container::avi::StreamType;
+use crate::container::metadata::{CoverArt, Id3v2Tag, VorbisComment};
+use crate::container::ogg::NO_GRANULE;
+use crate::container::{
+	AviReader, AviWriter, FlacFormat, FlacReader, FlacWriter, Looped, MediaType, Mp3Reader,
+	Mp3Writer, Mp4Reader, Mp4Writer, OggReader, OggWriter, SinkFormat, SinkWriter, WavFormat,
+	WavReader, WavWriter, Y4mFormat, Y4mReader, Y4mWriter,
+};
+use crate::core::{
+	Decoder, Demuxer, Encoder, Frame, FrameAudio, FrameData, Muxer, Packet, StreamFormat, Timebase,
+	Transform, log,
+};
+use crate::edl::{EditDecisionList, EdlCutter, EdlEntry};
+use crate::io::{
+	AllowAll, BufferedWriter, FileAdapter, IoError, IoErrorKind, IoResult, MediaInput, MediaOutput,
+	Overwrite, PIPE, PathPolicy, is_pipe,
+};
+use crate::transform::video::stabilize::smooth_corrections;
+use crate::transform::{
+	CropDetect, FilterGraph, FrameRateConverter, Loudness, LoudnessMeter, MotionEstimator, Overlay,
+	ReplayGain, TransformChain, TransformSpec,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+pub struct Transcoder {
+	input_path: String,
+	output_path: Option<String>,
+	show_mode: bool,
+	transforms: Vec<String>,
+	transform_specs: Vec<TransformSpec>,
+	codec: Option<CodecId>,
+	edl: Option<EditDecisionList>,
+	trim: Option<(f64, Option<f64>)>,
+	// times the input is played; None repeats until the trim end
+	passes: Option<u32>,
+	auto_fade: Option<f64>,
+	stream_metadata: Vec<StreamMetadata>,
+	replaygain: bool,
+	// front cover for wav (id3 APIC), flac (PICTURE) or mp4 (covr) output
+	cover: Option<CoverArt>,
+	reverse: bool,
+	// second y4m input, its top-left position and its opacity
+	overlay: Option<(String, (u32, u32), f32)>,
+	filter_graph: Option<String>,
+	// container of the output over its extension, and of an input whose path
+	// names none
+	format: Option<MediaType>,
+	// container of the input over its extension
+	input_format: Option<MediaType>,
+	// null or hash output in place of a container
+	sink: Option<SinkFormat>,
+	// further inputs the filter graph reads as [in1], [in2], ...
+	inputs: Vec<String>,
+	progress: ProgressMode,
+	// threads the decode, transform and encode stages are spread over
+	threads: usize,
+	benchmark: Option<Arc<Benchmark>>,
+	// further outputs written from the same decoded frames as the main one
+	tee: Vec<String>,
+	overwrite: Overwrite,
+	path_policy: Arc<dyn PathPolicy>,
+}
+
+impl Transcoder {
+	pub fn new(
+		input_path: String,
+		output_path: Option<String>,
+		show_mode: bool,
+		transforms: Vec<String>,
+	) -> Self {
+		Self {
+			input_path,
+			output_path,
+			show_mode,
+			transforms,
+			transform_specs: Vec::new(),
+			codec: None,
+			edl: None,
+			trim: None,
+			passes: Some(1),
+			auto_fade: None,
+			stream_metadata: Vec::new(),
+			replaygain: false,
+			cover: None,
+			reverse: false,
+			overlay: None,
+			filter_graph: None,
+			format: None,
+			input_format: None,
+			sink: None,
+			inputs: Vec::new(),
+			progress: ProgressMode::Off,
+			threads: 1,
+			benchmark: None,
+			tee: Vec::new(),
+			overwrite: Overwrite::Refuse,
+			path_policy: Arc::new(AllowAll),
+		}
+	}
+
+	// run after the string transforms passed to `new`
+	pub fn with_transform_specs(mut self, specs: Vec<TransformSpec>) -> Self {
+		self.transform_specs = specs;
+		self
+	}
+
+	// output codec; see `can_convert` for what each conversion accepts
+	pub fn with_codec(mut self, codec: CodecId) -> Self {
+		self.codec = Some(codec);
+		self
+	}
+
+	pub fn with_edl(mut self, edl: EditDecisionList) -> Self {
+		self.edl = Some(edl);
+		self
+	}
+
+	// keep only `start..end` seconds of the input (to the end when `end` is
+	// None). wav input seeks straight to `start` instead of decoding up to it.
+	pub fn with_trim(mut self, start: f64, end: Option<f64>) -> Self {
+		self.trim = Some((start, end));
+		self
+	}
+
+	// play the input `passes` times back to back, or forever with None (which
+	// needs a trim end to stop at). pts keep increasing across passes, and a
+	// trim applies to the looped timeline.
+	pub fn with_loop(mut self, passes: Option<u32>) -> Self {
+		self.passes = passes;
+		self
+	}
+
+	pub fn with_auto_fade(mut self, seconds: f64) -> Self {
+		self.auto_fade = Some(seconds);
+		self
+	}
+
+	pub fn with_stream_metadata(mut self, stream_metadata: Vec<StreamMetadata>) -> Self {
+		self.stream_metadata = stream_metadata;
+		self
+	}
+
+	// tag wav (id3 chunk) or flac (vorbis comments) output with track gain and peak
+	pub fn with_replaygain(mut self, replaygain: bool) -> Self {
+		self.replaygain = replaygain;
+		self
+	}
+
+	pub fn with_cover(mut self, cover: CoverArt) -> Self {
+		self.cover = Some(cover);
+		self
+	}
+
+	// write video frames last to first. frames are held in memory until the
+	// input ends, so this is meant for short clips; pts still count upwards.
+	pub fn with_reverse(mut self, reverse: bool) -> Self {
+		self.reverse = reverse;
+		self
+	}
+
+	// composites another y4m onto every frame after the transforms run; see
+	// `transform::Overlay`
+	pub fn with_overlay(mut self, path: String, x: u32, y: u32, alpha: f32) -> Self {
+		self.overlay = Some((path, (x, y), alpha));
+		self
+	}
+
+	// runs after the transforms; see `transform::FilterGraph` for the syntax
+	pub fn with_filter_graph(mut self, graph: String) -> Self {
+		self.filter_graph = Some(graph);
+		self
+	}
+
+	// the container written to stdout when the output is "-", or to a file
+	// whatever its extension; an input read from "-" or without a known
+	// extension is taken to be one too
+	pub fn with_format(mut self, format: MediaType) -> Self {
+		self.format = Some(format);
+		self
+	}
+
+	// the container of the input, for one whose extension is wrong or that is
+	// read from "-" while writing another container
+	pub fn with_input_format(mut self, format: MediaType) -> Self {
+		self.input_format = Some(format);
+		self
+	}
+
+	// inputs after the first, opened alongside it and read by the filter graph
+	// as [in1], [in2], ... (wav or flac for audio, y4m for video)
+	// decode and encode as usual but discard the packets, or print their digest
+	// to the output path (stdout when there is none) instead of writing a file
+	pub fn with_sink(mut self, sink: SinkFormat) -> Self {
+		self.sink = Some(sink);
+		self
+	}
+
+	pub fn with_inputs(mut self, inputs: Vec<String>) -> Self {
+		self.inputs = inputs;
+		self
+	}
+
+	// report time, percent, speed and eta on stderr while converting
+	pub fn with_progress(mut self, mode: ProgressMode) -> Self {
+		self.progress = mode;
+		self
+	}
+
+	// 1 runs a conversion on the calling thread; more let decoding, the
+	// transforms and encoding overlap. the output is the same either way
+	pub fn with_threads(mut self, threads: usize) -> Self {
+		self.threads = threads.max(1);
+		self
+	}
+
+	// more outputs fed the frames the main output gets, each with its own
+	// encoder and container: wav or flac beside a wav conversion, y4m beside
+	// a y4m one. the input is read and transformed once for all of them
+	pub fn with_tee(mut self, outputs: Vec<String>) -> Self {
+		self.tee = outputs;
+		self
+	}
+
+	// counts the packets read, frames decoded and input seconds converted
+	// into `benchmark`; the caller reads it back once the run is over
+	pub fn with_benchmark(mut self, benchmark: Arc<Benchmark>) -> Self {
+		self.benchmark = Some(benchmark);
+		self
+	}
+
+	// an existing output file fails the run unless this says to replace or
+	// skip it; a skipped run writes nothing and succeeds
+	pub fn with_overwrite(mut self, overwrite: Overwrite) -> Self {
+		self.overwrite = overwrite;
+		self
+	}
+
+	pub fn with_path_policy(mut self, path_policy: Arc<dyn PathPolicy>) -> Self {
+		self.path_policy = path_policy;
+		self
+	}
+
+	pub fn run(&self) -> std::io::Result<()> {
+		self.run_io().map_err(std::io::Error::from)
+	}
+
+	// the input's container header and first frames as text, for the caller
+	// to print
+	pub fn show(&self) -> std::io::Result<String> {
+		if is_pipe(&self.input_path) && self.input_type() == MediaType::Unknown {
+			return Err(IoError::invalid_input("reading a pipe needs a format (-f)").into());
+		}
+		self.run_show(self.input_type()).map_err(std::io::Error::from)
+	}
+
+	fn run_io(&self) -> IoResult<()> {
+		let input_type = self.input_type();
+		let output_type = self.output_type();
+		// a digest printed to stdout is not a container that needs naming
+		let piped = is_pipe(&self.input_path)
+			|| (self.sink.is_none() && self.output_path.as_deref().is_some_and(is_pipe));
+		if piped && (input_type == MediaType::Unknown || output_type == MediaType::Unknown) {
+			return Err(IoError::invalid_input("reading or writing a pipe needs a format (-f)"));
+		}
+
+		if self.show_mode {
+			return Err(IoError::invalid_input(
+				"a show-mode pipeline writes nothing; `show` returns its summary",
+			));
+		}
+
+		if let Some(path) = self.output_path.as_deref().filter(|path| !is_pipe(path))
+			&& self.sink != Some(SinkFormat::Null)
+			&& !self.overwrite.check(Path::new(path))?
+		{
+			return Ok(());
+		}
+
+		if self.edl.is_some()
+			&& !matches!(
+				(input_type, output_type),
+				(MediaType::Wav, MediaType::Wav) | (MediaType::Y4m, MediaType::Y4m)
+			) {
+			return Err(IoError::invalid_input("edl requires a decoded pipeline (wav or y4m)"));
+		}
+
+		if let Some((start, end)) = self.trim {
+			if self.edl.is_some() {
+				return Err(IoError::invalid_input("a time range cannot be combined with an edl"));
+			}
+			if !(start.is_finite() && start >= 0.0) || end.is_some_and(|end| end <= start || end.is_nan())
+			{
+				return Err(IoError::invalid_input("time range must start at or after 0 and end after it"));
+			}
+			// mp3 and ogg are cut on frame and page boundaries
+			if !matches!(
+				(input_type, output_type),
+				(MediaType::Wav, MediaType::Wav)
+					| (MediaType::Y4m, MediaType::Y4m)
+					| (MediaType::Mp3, MediaType::Mp3)
+					| (MediaType::Ogg, MediaType::Ogg)
+			) {
+				return Err(IoError::invalid_input("trimming requires wav, y4m, mp3 or ogg in and out"));
+			}
+		}
+
+		if self.passes != Some(1) {
+			if self.passes == Some(0) {
+				return Err(IoError::invalid_input("loop count must be at least 1"));
+			}
+			if self.passes.is_none() && self.trim.is_none_or(|(_, end)| end.is_none()) {
+				return Err(IoError::invalid_input("looping forever needs an end time to stop at"));
+			}
+			if !matches!(
+				(input_type, output_type),
+				(MediaType::Wav, MediaType::Wav) | (MediaType::Y4m, MediaType::Y4m)
+			) {
+				return Err(IoError::invalid_input("looping requires a decoded pipeline (wav or y4m)"));
+			}
+		}
+
+		if self.auto_fade.is_some() && self.edl.is_none() && self.trim.is_none() {
+			return Err(IoError::invalid_input("auto fade requires an edl to split on"));
+		}
+
+		if self.reverse && !(input_type == MediaType::Y4m && output_type == MediaType::Y4m) {
+			return Err(IoError::invalid_input("reverse requires y4m input and output"));
+		}
+
+		if let Some((path, _, alpha)) = &self.overlay {
+			if !(input_type == MediaType::Y4m && output_type == MediaType::Y4m)
+				|| MediaType::from_extension(path) != MediaType::Y4m
+			{
+				return Err(IoError::invalid_input("overlay requires y4m inputs and output"));
+			}
+			if !(0.0..=1.0).contains(alpha) {
+				return Err(IoError::invalid_input("overlay alpha must be between 0 and 1"));
+			}
+		}
+
+		if !self.inputs.is_empty() {
+			if self.filter_graph.is_none() {
+				return Err(IoError::invalid_input(
+					"extra inputs are read by a filter graph as [in1], [in2], ...",
+				));
+			}
+			if !matches!(
+				(input_type, output_type),
+				(MediaType::Wav, MediaType::Wav) | (MediaType::Y4m, MediaType::Y4m)
+			) {
+				return Err(IoError::invalid_input("extra inputs require a decoded pipeline (wav or y4m)"));
+			}
+		}
+
+		if !self.stream_metadata.is_empty() && output_type != MediaType::Mp4 {
+			return Err(IoError::invalid_input("stream metadata is only supported for mp4 output"));
+		}
+
+		if self.replaygain && self.sink.is_some() {
+			return Err(IoError::invalid_input("replaygain tags need a file to be written to"));
+		}
+		if self.replaygain && !matches!(output_type, MediaType::Wav | MediaType::Flac) {
+			return Err(IoError::invalid_input("replaygain tags are only written to wav or flac output"));
+		}
+		if self.replaygain
+			&& output_type == MediaType::Wav
+			&& self.output_path.as_deref().is_some_and(is_pipe)
+		{
+			return Err(IoError::invalid_input("replaygain tags cannot be added to wav on a pipe"));
+		}
+
+		if self.cover.is_some() {
+			if self.sink.is_some() {
+				return Err(IoError::invalid_input("cover art needs a file to be written to"));
+			}
+			if !matches!(output_type, MediaType::Wav | MediaType::Flac | MediaType::Mp4) {
+				return Err(IoError::invalid_input(
+					"cover art is only embedded in wav, flac or mp4 output",
+				));
+			}
+			if output_type == MediaType::Wav && self.output_path.as_deref().is_some_and(is_pipe) {
+				return Err(IoError::invalid_input("cover art cannot be added to wav on a pipe"));
+			}
+		}
+
+		if !self.tee.is_empty() {
+			if self.sink.is_some() {
+				return Err(IoError::invalid_input("tee outputs need the main output to be a file"));
+			}
+			if !matches!(
+				(input_type, output_type),
+				(MediaType::Wav, MediaType::Wav) | (MediaType::Y4m, MediaType::Y4m)
+			) {
+				return Err(IoError::unsupported(
+					"tee outputs need a decoded pipeline: wav or y4m input and a first output of the same",
+				));
+			}
+			if self.replaygain || self.cover.is_some() {
+				return Err(IoError::invalid_input("replaygain tags and cover art take a single output"));
+			}
+		}
+
+		match can_convert(input_type, output_type, self.codec) {
+			Capability::Unsupported if self.codec.is_some_and(|codec| codec.is_big_endian()) => {
+				return Err(IoError::unsupported(
+					"big-endian pcm cannot be written, wav only holds little-endian",
+				));
+			}
+			Capability::Unsupported if self.codec.is_some() => {
+				return Err(IoError::unsupported("codec is not supported for this conversion"));
+			}
+			Capability::RequiresFeature(_) => {
+				return Err(IoError::unsupported("conversion needs a cargo feature that is not enabled"));
+			}
+			_ => {}
+		}
+
+		log::info(
+			"transcode",
+			format_args!(
+				"{} ({:?}) -> {} ({:?})",
+				self.input_path,
+				input_type,
+				self.output_path.as_deref().unwrap_or("-"),
+				output_type
+			),
+		);
+		match (input_type, output_type) {
+			(MediaType::Flac, _) if self.sink.is_some() => self.run_flac_to_sink(),
+			(MediaType::Wav, MediaType::Wav) => self.run_wav_to_wav(),
+			(MediaType::Wav, MediaType::Flac) => self.run_wav_to_flac(),
+			(MediaType::Flac, MediaType::Wav) => self.run_flac_to_wav(),
+			(MediaType::Flac, MediaType::Flac) => self.run_flac_to_flac(),
+			(MediaType::Y4m, MediaType::Y4m) => self.run_y4m_transcode(),
+			(MediaType::Avi, MediaType::Avi) => self.run_avi_passthrough(),
+			(MediaType::Mp4, MediaType::Mp4) => self.run_mp4_passthrough(),
+			(MediaType::Mp3, MediaType::Mp3) => self.run_mp3_passthrough(),
+			(MediaType::Ogg, MediaType::Ogg) => self.run_ogg_passthrough(),
+			(_, _) => Err(IoError::unsupported("unsupported format conversion")),
+		}
+	}
+
+	fn run_show(&self, media_type: MediaType) -> IoResult<String> {
+		match media_type {
+			MediaType::Wav => self.wav_show(),
+			MediaType::Flac => self.flac_show(),
+			MediaType::Y4m => self.y4m_show(),
+			MediaType::Avi => self.avi_show(),
+			MediaType::Mp4 => self.mp4_show(),
+			MediaType::Mp3 => self.mp3_show(),
+			MediaType::Ogg => self.ogg_show(),
+			MediaType::Unknown => Err(IoError::unsupported("unsupported file format")),
+		}
+	}
+
+	fn wav_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = WavReader::new(input)?;
+		let format = reader.format();
+		let mut decoder = PcmDecoder::new(format);
+
+		let mut out = String::new();
+		out.push_str("Format: WAV\n");
+		out.push_str(&format!("  Channels: {}\n", format.channels));
+		out.push_str(&format!("  Sample Rate: {} Hz\n", format.sample_rate));
+		out.push_str(&format!("  Bit Depth: {}\n", format.bit_depth));
+		out.push_str("\nFrames:\n");
+
+		let mut frame_idx = 0u64;
+		loop {
+			match reader.read_packet()? {
+				Some(packet) => {
+					if let Some(frame) = decoder.decode(packet)? {
+						if let Some(audio_frame) = frame.audio() {
+							out.push_str(&format!(
+								"  Frame {}: pts={}, samples={}, channels={}, rate={}\n",
+								frame_idx,
+								frame.pts,
+								audio_frame.nb_samples,
+								audio_frame.channels,
+								audio_frame.sample_rate
+							));
+						} else if let Some(video_frame) = frame.video() {
+							out.push_str(&format!(
+								"  Frame {}: pts={}, width={}, height={}\n",
+								frame_idx, frame.pts, video_frame.width, video_frame.height
+							));
+						}
+						frame_idx += 1;
+						if frame_idx >= 10 {
+							out.push_str("  ... (showing first 10 frames)\n");
+							break;
+						}
+					}
+				}
+				None => break,
+			}
+		}
+
+		Ok(out)
+	}
+
+	fn flac_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let reader = FlacReader::new(input)?;
+		let format = reader.format();
+
+		let mut out = String::new();
+		out.push_str("Format: FLAC\n");
+		out.push_str(&format!("  Channels: {}\n", format.channels));
+		out.push_str(&format!("  Sample Rate: {} Hz\n", format.sample_rate));
+		out.push_str(&format!("  Bits per Sample: {}\n", format.bits_per_sample));
+		out.push_str(&format!("  Total Samples: {}\n", format.total_samples));
+		out.push_str(&format!("  Min Block Size: {}\n", format.min_block_size));
+		out.push_str(&format!("  Max Block Size: {}\n", format.max_block_size));
+
+		Ok(out)
+	}
+
+	fn y4m_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = Y4mReader::new(input)?;
+		let format = reader.format();
+		let mut decoder = RawVideoDecoder::new(format.clone());
+
+		let mut out = String::new();
+		out.push_str("Format: Y4M\n");
+		out.push_str(&format!("  Resolution: {}x{}\n", format.width, format.height));
+		out.push_str(&format!("  Framerate: {}/{}\n", format.framerate_num, format.framerate_den));
+		out.push_str(&format!("  Colorspace: {:?}\n", format.colorspace));
+		out.push_str("\nFrames:\n");
+
+		let mut frame_idx = 0u64;
+		loop {
+			match reader.read_packet()? {
+				Some(packet) => {
+					if let Some(frame) = decoder.decode(packet)? {
+						out.push_str(&format!(
+							"  Frame {}: pts={}, size={}x{}, fps={}/{}\n",
+							frame_idx,
+							frame.pts,
+							format.width,
+							format.height,
+							format.framerate_num,
+							format.framerate_den
+						));
+						frame_idx += 1;
+						if frame_idx >= 10 {
+							out.push_str("  ... (showing first 10 frames)\n");
+							break;
+						}
+					}
+				}
+				None => break,
+			}
+		}
+
+		Ok(out)
+	}
+
+	fn avi_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let reader = AviReader::new(input)?;
+		let format = reader.format();
+
+		let mut out = String::new();
+		out.push_str("Format: AVI\n");
+		out.push_str(&format!(
+			"  Resolution: {}x{}\n",
+			format.main_header.width, format.main_header.height
+		));
+		out.push_str(&format!("  Total Frames: {}\n", format.main_header.total_frames));
+		out.push_str(&format!(
+			"  Framerate: ~{:.2} fps\n",
+			1_000_000.0 / format.main_header.microseconds_per_frame as f64
+		));
+		out.push_str(&format!("  Streams: {}\n", format.streams.len()));
+
+		for (i, stream) in format.streams.iter().enumerate() {
+			out.push_str(&format!("  Stream {}: {:?}\n", i, stream.header.stream_type));
+		}
+
+		Ok(out)
+	}
+
+	fn mp3_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let reader = Mp3Reader::new(input)?;
+		let format = reader.format();
+
+		let mut out = String::new();
+		out.push_str("Format: MP3\n");
+		out.push_str(&format!("  Layer: {}\n", format.layer));
+		out.push_str(&format!("  Channels: {}\n", format.channels));
+		out.push_str(&format!("  Sample Rate: {} Hz\n", format.sample_rate));
+		if let Some(frames) = format.frames {
+			out.push_str(&format!("  Frames: {}\n", frames));
+		}
+
+		Ok(out)
+	}
+
+	fn ogg_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let reader = OggReader::new(input)?;
+		let format = reader.format();
+
+		let mut out = String::new();
+		out.push_str("Format: OGG\n");
+		out.push_str(&format!("  Codec: {:?}\n", format.codec));
+		out.push_str(&format!("  Channels: {}\n", format.channels));
+		out.push_str(&format!("  Sample Rate: {} Hz\n", format.sample_rate));
+
+		Ok(out)
+	}
+
+	fn mp4_show(&self) -> IoResult<String> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let reader = Mp4Reader::new(input)?;
+		let format = reader.format();
+
+		let mut out = String::new();
+		out.push_str("Format: MP4\n");
+		out.push_str(&format!("  Brand: {}\n", String::from_utf8_lossy(&format.major_brand)));
+		out.push_str(&format!("  Timescale: {}\n", format.timescale));
+		out.push_str(&format!("  Duration: {}\n", format.duration));
+		out.push_str(&format!("  Tracks: {}\n", format.tracks.len()));
+
+		for (i, track) in format.tracks.iter().enumerate() {
+			out.push_str(&format!("  Track {}: {:?}\n", i, track.track_type));
+			if track.width > 0 && track.height > 0 {
+				out.push_str(&format!("    Resolution: {}x{}\n", track.width, track.height));
+			}
+			if track.sample_rate > 0 {
+				out.push_str(&format!("    Sample Rate: {}\n", track.sample_rate));
+				out.push_str(&format!("    Channels: {}\n", track.channels));
+			}
+			out.push_str(&format!("    Language: {}\n", track.language));
+			if !track.disposition.is_empty() {
+				out.push_str(&format!("    Disposition: {}\n", track.disposition.names().join(", ")));
+			}
+		}
+
+		Ok(out)
+	}
+
+	fn run_wav_to_wav(&self) -> IoResult<()> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = WavReader::new(input)?;
+		let format = reader.format();
+
+		let codec = self.codec.unwrap_or(format.codec_id());
+		let bit_depth = codec.pcm_bytes_per_sample().map(|b| b as u16 * 8).unwrap_or(format.bit_depth);
+		// an impossible chain fails here, before the output exists. the encoder
+		// quantizes to the output's depth, so the chain hands audio on as is
+		let transform_chain = self.build_transform_chain()?.unquantized();
+		let input_format =
+			StreamFormat::Audio { sample_rate: format.sample_rate, channels: format.channels };
+		let StreamFormat::Audio { sample_rate, channels } = transform_chain.negotiate(input_format)?
+		else {
+			return Err(IoError::invalid_data("transform chain turned audio into video"));
+		};
+		let output_format = WavFormat { bit_depth, sample_rate, channels };
+
+		let mut writer = self.open_output(|path| self.wav_writer(path, output_format))?;
+		let policy = self.path_policy.as_ref();
+		let mut legs = self.open_tee(|path| tee::audio_leg(path, output_format, codec, policy))?;
+
+		let mut decoder = PcmDecoder::new(format);
+		let timebase = Timebase::new(1, format.sample_rate);
+		let mut encoder = PcmEncoder::new(timebase).with_codec(codec);
+
+		let mut cutter = self.build_cutter();
+		if let Some((start, _)) = self.trim.filter(|&(start, _)| start > 0.0) {
+			let frame = reader.seek_to_frame((start * format.sample_rate as f64).round() as u64)?;
+			let seconds = frame as f64 / format.sample_rate as f64;
+			cutter = cutter.map(|cutter| cutter.starting_at(seconds));
+		}
+		let mut progress =
+			self.progress(Some(reader.total_frames() as f64 / format.sample_rate.max(1) as f64));
+		let bytes_per_frame = format.bytes_per_frame().max(1);
+		let (path, policy) = (self.input_path.clone(), self.path_policy.clone());
+		let mut reader = Looped::new(
+			reader,
+			self.passes,
+			move || WavReader::new(MediaInput::open(&path, policy.as_ref())?),
+			move |packet| (packet.size() / bytes_per_frame) as i64,
+		);
+		// measures exactly what gets written, after cuts and transforms
+		let mut meter = self.replaygain.then(LoudnessMeter::new);
+		let write = |processed: Frame| -> IoResult<()> {
+			// channel mixers and resampling change what the header has to say
+			if let Some(audio) = processed.audio() {
+				if let Some(file) = writer.file() {
+					file.set_format(WavFormat {
+						channels: audio.channels,
+						sample_rate: audio.sample_rate,
+						..output_format
+					});
+				}
+				if let Some(meter) = &mut meter {
+					meter.push(audio);
+				}
+			}
+			for leg in &mut legs {
+				leg.write(&processed)?;
+			}
+			if let Some(pkt) = encoder.encode(processed)? {
+				writer.write_packet(pkt)?;
+			}
+			Ok(())
+		};
+
+		let read = || -> IoResult<Option<Frame>> {
+			while let Some(packet) = reader.read_packet()? {
+				progress.update(packet.timebase.to_seconds(packet.pts));
+				let Some(frame) = decoder.decode(packet)? else {
+					continue;
+				};
+				progress.count_frame();
+				match Self::apply_edl(&mut cutter, frame) {
+					Some(frame) => return Ok(Some(frame)),
+					None if cutter.as_ref().is_some_and(EdlCutter::is_finished) => break,
+					None => {}
+				}
+			}
+			Ok(None)
+		};
+		stages::run_stages(self.threads, read, transform_chain, write)?;
+		if let Some(file) = writer.file()
+			&& let Some(tag) =
+				self.id3_tag(meter.map(|meter| ReplayGain::from_loudness(meter.loudness())))
+		{
+			file.set_id3(tag);
+		}
+		writer.finalize()?;
+		for leg in &mut legs {
+			leg.finish()?;
+		}
+		progress.finish();
+		Ok(())
+	}
+
+	fn run_wav_to_flac(&self) -> IoResult<()> {
+		let output_path = self.require_output()?;
+
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = WavReader::new(input)?;
+		let wav_format = reader.format();
+
+		let mut transform_chain = self.build_transform_chain()?;
+		let input_format =
+			StreamFormat::Audio { sample_rate: wav_format.sample_rate, channels: wav_format.channels };
+		let StreamFormat::Audio { sample_rate, channels } = transform_chain.negotiate(input_format)?
+		else {
+			return Err(IoError::invalid_data("transform chain turned audio into video"));
+		};
+		// untouched samples are encoded at their own depth; anything that went
+		// through the transforms leaves them as s16
+		let untouched = transform_chain.is_empty() && matches!(wav_format.bit_depth, 8 | 16 | 24);
+		let bits_per_sample = if untouched { wav_format.bit_depth as u8 } else { 16 };
+		let flac_format =
+			FlacFormat { sample_rate, channels, bits_per_sample, ..FlacFormat::default() };
+
+		let mut progress =
+			self.progress(Some(reader.total_frames() as f64 / wav_format.sample_rate.max(1) as f64));
+		let output = MediaOutput::create(&output_path, self.path_policy.as_ref())?;
+		let mut writer = FlacWriter::new(output, flac_format)?;
+		if self.replaygain {
+			writer = writer.with_vorbis_comment(self.replaygain_comment()?);
+		}
+		if let Some(cover) = &self.cover {
+			writer = writer.with_picture(cover.clone());
+		}
+
+		let mut decoder = PcmDecoder::new(wav_format);
+		let block_size = FlacFormat::default().max_block_size;
+		let mut encoder = FlacEncoder::new(sample_rate, channels, bits_per_sample, block_size);
+		let mut encode = |frame: Frame| -> IoResult<()> {
+			if let Some(packet) = encoder.encode(frame)? {
+				writer.write_packet(packet)?;
+			}
+			Ok(())
+		};
+		while let Some(packet) = reader.read_packet()? {
+			progress.update(packet.timebase.to_seconds(packet.pts));
+			if untouched {
+				// wav keeps 8 bit samples unsigned, flac signed
+				let mut data = packet.data;
+				if bits_per_sample == 8 {
+					data.iter_mut().for_each(|byte| *byte ^= 0x80);
+				}
+				let audio = FrameAudio::new(data, sample_rate, channels);
+				encode(Frame::new_audio(audio, packet.timebase, packet.stream_index).with_pts(packet.pts))?;
+			} else if let Some(frame) = decoder.decode(packet)? {
+				progress.count_frame();
+				encode(transform_chain.apply(frame)?)?;
+			}
+		}
+		if let Some(tail) = transform_chain.flush()? {
+			encode(tail)?;
+		}
+		transform_chain.finish()?;
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	fn run_flac_to_wav(&self) -> IoResult<()> {
+		let output_path = self.require_output()?;
+
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = FlacReader::new(input)?;
+		let flac_format = reader.format();
+
+		let wav_format = crate::container::WavFormat {
+			sample_rate: flac_format.sample_rate,
+			channels: flac_format.channels,
+			bit_depth: flac_format.bits_per_sample as u16,
+		};
+
+		let mut progress = self.progress(Self::flac_duration(flac_format));
+		let mut writer = self.wav_writer(&output_path, wav_format)?;
+		let gain = match self.replaygain {
+			true => Some(ReplayGain::from_loudness(self.measure_loudness()?)),
+			false => None,
+		};
+		if let Some(tag) = self.id3_tag(gain) {
+			writer.set_id3(tag);
+		}
+
+		loop {
+			match reader.read_packet()? {
+				Some(packet) => {
+					progress.update(packet.timebase.to_seconds(packet.pts));
+					writer.write_packet(packet)?;
+				}
+				None => break,
+			}
+		}
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	fn run_flac_to_flac(&self) -> IoResult<()> {
+		let output_path = self.require_output()?;
+
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = FlacReader::new(input)?;
+		let format = reader.format().clone();
+
+		let mut progress = self.progress(Self::flac_duration(&format));
+		let output = MediaOutput::create(&output_path, self.path_policy.as_ref())?;
+		let mut writer = FlacWriter::new(output, format)?;
+		if self.replaygain {
+			writer = writer.with_vorbis_comment(self.replaygain_comment()?);
+		}
+		if let Some(cover) = &self.cover {
+			writer = writer.with_picture(cover.clone());
+		}
+
+		loop {
+			match reader.read_packet()? {
+				Some(packet) => {
+					progress.update(packet.timebase.to_seconds(packet.pts));
+					writer.write_packet(packet)?;
+				}
+				None => break,
+			}
+		}
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	// flac packets are decoded and encoded as s16 pcm, so the digest covers the
+	// decoder rather than the compressed frames
+	fn run_flac_to_sink(&self) -> IoResult<()> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = FlacReader::new(input)?;
+		let format = reader.format().clone();
+
+		let mut transform_chain = self.build_transform_chain()?;
+		transform_chain.negotiate(StreamFormat::Audio {
+			sample_rate: format.sample_rate,
+			channels: format.channels,
+		})?;
+		let mut progress = self.progress(Self::flac_duration(&format));
+		let mut writer = self.open_sink(self.sink.unwrap_or(SinkFormat::Null))?;
+
+		let mut decoder = FlacDecoder::new(&format);
+		let mut encoder = PcmEncoder::new(Timebase::new(1, format.sample_rate));
+		let mut cutter = self.build_cutter();
+		let mut write = |frame: Frame| -> IoResult<()> {
+			let Some(frame) = Self::apply_edl(&mut cutter, frame) else {
+				return Ok(());
+			};
+			let processed =
+				if transform_chain.is_empty() { frame } else { transform_chain.apply(frame)? };
+			if let Some(pkt) = encoder.encode(processed)? {
+				writer.write_packet(pkt)?;
+			}
+			Ok(())
+		};
+
+		while let Some(packet) = reader.read_packet()? {
+			progress.update(packet.timebase.to_seconds(packet.pts));
+			if let Some(frame) = decoder.decode(packet)? {
+				progress.count_frame();
+				write(frame)?;
+			}
+		}
+		while let Some(frame) = decoder.flush()? {
+			progress.count_frame();
+			write(frame)?;
+		}
+		if let Some(tail) = transform_chain.flush()?
+			&& let Some(pkt) = encoder.encode(tail)?
+		{
+			writer.write_packet(pkt)?;
+		}
+		transform_chain.finish()?;
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	fn run_y4m_transcode(&self) -> IoResult<()> {
+		let (format, mut reader) = self.open_y4m()?;
+		let mut specs = self.transform_specs()?;
+		// dropping and repeating frames does not fit a chain that turns each
+		// frame into one, so a trailing fps runs on what leaves it
+		let mut frame_rate = match specs.last().map(TransformSpec::target) {
+			Some(&TransformSpec::Fps { num, den }) => {
+				if num == 0 || den == 0 {
+					return Err(IoError::invalid_data("fps rate must be positive"));
+				}
+				specs.pop();
+				Some(FrameRateConverter::new(format.framerate_num, format.framerate_den, num, den))
+			}
+			_ => None,
+		};
+		let transform_chain = self.chain_from_specs(specs)?;
+		let input_format = StreamFormat::Video {
+			width: format.width,
+			height: format.height,
+			format: format.video_format(),
+		};
+		let StreamFormat::Video { width, height, format: video_format } =
+			transform_chain.negotiate(input_format)?
+		else {
+			return Err(IoError::invalid_data("transform chain turned video into audio"));
+		};
+
+		let mut writer = self.open_output(|path| {
+			let output = MediaOutput::create(path, self.path_policy.as_ref())?;
+			let buf_writer: BufferedWriter<MediaOutput> = BufferedWriter::new(output);
+			Y4mWriter::new(buf_writer, format.clone())
+		})?;
+		if let Some(file) = writer.file() {
+			file.match_format(width, height, video_format)?;
+		}
+		let policy = self.path_policy.as_ref();
+		let mut legs = self.open_tee(|path| {
+			tee::video_leg(path, format.clone(), (width, height, video_format), policy)
+		})?;
+
+		let timebase = Timebase::new(format.framerate_den, format.framerate_num);
+		let mut decoder = RawVideoDecoder::new(format);
+		let mut encoder = RawVideoEncoder::new(timebase);
+		let mut overlay = self.build_overlay()?;
+		let mut cutter = self.build_cutter();
+		let skip_until = self.trim.map(|(start, _)| start).unwrap_or(0.0);
+		let mut reversed = self.reverse.then(Vec::new);
+		// a y4m header does not say how many frames follow
+		let mut progress = self.progress(None);
+		let mut write = |frame: Frame, writer: &mut Output<Y4mWriter<_>>| -> IoResult<()> {
+			if let (Some(video), Some(file)) = (frame.video(), writer.file()) {
+				file.match_frame(video)?;
+				file.match_timebase(frame.timebase);
+			}
+			for leg in &mut legs {
+				leg.write(&frame)?;
+			}
+			if let Some(pkt) = encoder.encode(frame)? {
+				writer.write_packet(pkt)?;
+			}
+			Ok(())
+		};
+
+		let read = || -> IoResult<Option<Frame>> {
+			while let Some(packet) = reader.read_packet()? {
+				progress.update(packet.timebase.to_seconds(packet.pts));
+				// raw frames before the in-point are dropped without decoding
+				if packet.timebase.to_seconds(packet.pts) < skip_until {
+					continue;
+				}
+				let Some(frame) = decoder.decode(packet)? else {
+					continue;
+				};
+				progress.count_frame();
+				match Self::apply_edl(&mut cutter, frame) {
+					Some(frame) => return Ok(Some(frame)),
+					None if cutter.as_ref().is_some_and(EdlCutter::is_finished) => break,
+					None => {}
+				}
+			}
+			Ok(None)
+		};
+		let mut deliver = |frame: Frame| -> IoResult<()> {
+			match &mut reversed {
+				Some(frames) => frames.push(frame),
+				None => write(frame, &mut writer)?,
+			}
+			Ok(())
+		};
+		let sink = |processed: Frame| -> IoResult<()> {
+			let processed = match &mut overlay {
+				Some(overlay) => overlay.apply(processed)?,
+				None => processed,
+			};
+			match &mut frame_rate {
+				Some(converter) => converter.process(processed)?.into_iter().try_for_each(&mut deliver),
+				None => deliver(processed),
+			}
+		};
+		stages::run_stages(self.threads, read, transform_chain, sink)?;
+		if let Some(converter) = &mut frame_rate {
+			converter.flush()?.into_iter().try_for_each(&mut deliver)?;
+		}
+
+		if let Some(frames) = reversed {
+			// the reversed frames take over the forward timestamps
+			let timestamps: Vec<i64> = frames.iter().map(|frame| frame.pts).collect();
+			for (frame, pts) in frames.into_iter().rev().zip(timestamps) {
+				write(frame.with_pts(pts), &mut writer)?;
+			}
+		}
+		writer.finalize()?;
+		for leg in &mut legs {
+			leg.finish()?;
+		}
+		progress.finish();
+		Ok(())
+	}
+
+	// the y4m input, repeated as `with_loop` asks
+	fn open_y4m(&self) -> IoResult<(Y4mFormat, Looped<Y4mReader<MediaInput>>)> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let reader = Y4mReader::new(input)?;
+		let format = reader.format();
+		let (path, policy) = (self.input_path.clone(), self.path_policy.clone());
+		let reader = Looped::new(
+			reader,
+			self.passes,
+			move || Y4mReader::new(MediaInput::open(&path, policy.as_ref())?),
+			|_| 1,
+		);
+		Ok((format, reader))
+	}
+
+	// frames of the y4m input left after loops, trims and edl cuts
+	fn count_y4m_frames(&self) -> IoResult<u64> {
+		let (format, mut reader) = self.open_y4m()?;
+		let mut decoder = RawVideoDecoder::new(format);
+		let mut cutter = self.build_cutter();
+		let mut count = 0;
+		while let Some(packet) = reader.read_packet()? {
+			if let Some(frame) = decoder.decode(packet)? {
+				if Self::apply_edl(&mut cutter, frame).is_some() {
+					count += 1;
+				} else if cutter.as_ref().is_some_and(EdlCutter::is_finished) {
+					break;
+				}
+			}
+		}
+		Ok(count)
+	}
+
+	fn run_avi_passthrough(&self) -> IoResult<()> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = AviReader::new(input)?;
+		let format = reader.format().clone();
+
+		// avi packets are stamped with their byte offset, so time is counted in
+		// frames of the video stream instead
+		let frame_seconds = format.main_header.microseconds_per_frame as f64 / 1_000_000.0;
+		let video =
+			format.streams.iter().position(|stream| stream.header.stream_type == StreamType::Video);
+		let duration =
+			(frame_seconds > 0.0).then_some(format.main_header.total_frames as f64 * frame_seconds);
+		let mut progress = self.progress(duration);
+		let mut frames = 0u64;
+		let mut writer = self.open_output(|path| {
+			AviWriter::new(MediaOutput::create_buffered(path, self.path_policy.as_ref())?, format)
+		})?;
+
+		loop {
+			match reader.read_packet()? {
+				Some(packet) => {
+					if Some(packet.stream_index) == video {
+						frames += 1;
+						progress.update(frames as f64 * frame_seconds);
+					}
+					writer.write_packet(packet)?;
+				}
+				None => break,
+			}
+		}
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	fn run_mp4_passthrough(&self) -> IoResult<()> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = Mp4Reader::new(input)?;
+		let mut format = reader.format().clone();
+		for metadata in &self.stream_metadata {
+			metadata.apply_mp4(&mut format)?;
+		}
+
+		let duration = (format.timescale > 0).then(|| format.duration as f64 / format.timescale as f64);
+		// mp4 packets are stamped with their sample number within the track
+		let samples = format.tracks.iter().map(|track| track.sample_sizes.len()).max().unwrap_or(0);
+		let mut progress = self.progress(duration);
+		let mut writer = self.open_output(|path| {
+			let writer =
+				Mp4Writer::new(MediaOutput::create_buffered(path, self.path_policy.as_ref())?, format)?;
+			Ok(match &self.cover {
+				Some(cover) => writer.with_cover(cover.clone()),
+				None => writer,
+			})
+		})?;
+
+		loop {
+			match reader.read_packet()? {
+				Some(packet) => {
+					if let Some(duration) = duration.filter(|_| samples > 0) {
+						progress.update(packet.pts as f64 / samples as f64 * duration);
+					}
+					writer.write_packet(packet)?;
+				}
+				None => break,
+			}
+		}
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	// frames go across whole, so a trim keeps every frame it touches. the
+	// input's info header is replaced by one counting what was written
+	fn run_mp3_passthrough(&self) -> IoResult<()> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = Mp3Reader::new(input)?;
+		let format = *reader.format();
+		let rate = format.sample_rate as f64;
+		let (start, end) = self.trim.unwrap_or((0.0, None));
+		let (first, last) = ((start * rate).round() as i64, end.map(|end| (end * rate).round() as i64));
+
+		let duration =
+			format.frames.map(|frames| frames as f64 * format.samples_per_frame() as f64 / rate);
+		let mut progress = self.progress(duration);
+		let mut writer = self.open_output(|path| {
+			let writer = Mp3Writer::new(MediaOutput::create_buffered(path, self.path_policy.as_ref())?);
+			Ok(match reader.id3_tag() {
+				Some(tag) => writer.with_id3(tag.to_vec()),
+				None => writer,
+			})
+		})?;
+
+		let samples = format.samples_per_frame() as i64;
+		while let Some(packet) = reader.read_packet()? {
+			if last.is_some_and(|last| packet.pts >= last) {
+				break;
+			}
+			if packet.pts + samples <= first {
+				continue;
+			}
+			progress.update(packet.pts as f64 / rate);
+			writer.write_packet(packet)?;
+		}
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	// pages go across whole, so a trim keeps every page it touches; the last
+	// page's granule is pulled in to the trim end, which decoders honour by
+	// dropping the samples past it
+	fn run_ogg_passthrough(&self) -> IoResult<()> {
+		let input = MediaInput::open(&self.input_path, self.path_policy.as_ref())?;
+		let mut reader = OggReader::new(input)?;
+		let format = *reader.format();
+		let (start, end) = self.trim.unwrap_or((0.0, None));
+		let (first, last) = (format.granule_at(start), end.map(|end| format.granule_at(end)));
+		let rate = format.granule_rate().max(1) as f64;
+		let timebase = Timebase::new(1, format.granule_rate().max(1));
+
+		let mut progress = self.progress(None);
+		let mut writer = self.open_output(|path| {
+			let writer = OggWriter::new(MediaOutput::create(path, self.path_policy.as_ref())?);
+			Ok(match last {
+				Some(last) => writer.with_end_granule(last),
+				None => writer,
+			})
+		})?;
+
+		let mut audio = false;
+		let mut granule = 0i64;
+		while let Some(page) = reader.read_page()? {
+			let page_start = granule;
+			if page.granule != NO_GRANULE {
+				granule = page.granule;
+			}
+			// header pages end at granule 0, before any audio
+			audio |= page.granule > 0;
+			if audio {
+				if last.is_some_and(|last| page_start >= last) {
+					break;
+				}
+				// a page on which no packet ends is kept once the cut is behind it
+				let page_end = if page.granule == NO_GRANULE { page_start } else { page.granule };
+				if page_end <= first && page_start < first {
+					continue;
+				}
+				progress.update((page_start - format.pre_skip as i64).max(0) as f64 / rate);
+			}
+			writer.write_packet(Packet::new(page.to_bytes(), 0, timebase).with_pts(page_start))?;
+		}
+
+		writer.finalize()?;
+		progress.finish();
+		Ok(())
+	}
+
+	// `with_input_format` names the input's container; otherwise it goes by its
+	// extension, and `with_format` stands in when that says nothing
+	fn input_type(&self) -> MediaType {
+		if let Some(format) = self.input_format {
+			return format;
+		}
+		match self.media_type(&self.input_path) {
+			MediaType::Unknown => self.format.unwrap_or(MediaType::Unknown),
+			media_type => media_type,
+		}
+	}
+
+	// a sink takes whatever the input container holds
+	fn output_type(&self) -> MediaType {
+		if self.sink.is_some() {
+			return self.input_type();
+		}
+		match (self.format, self.output_path.as_deref()) {
+			(Some(format), Some(_)) => format,
+			(None, Some(path)) => self.media_type(path),
+			(_, None) => self.input_type(),
+		}
+	}
+
+	// "-" has no extension to go by
+	fn media_type(&self, path: &str) -> MediaType {
+		match is_pipe(path) {
+			true => MediaType::Unknown,
+			false => MediaType::from_extension(path),
+		}
+	}
+
+	// a streamed header when the wav goes to a pipe, since it cannot be patched
+	fn wav_writer(&self, path: &str, format: WavFormat) -> IoResult<WavWriter<MediaOutput>> {
+		let output = MediaOutput::create(path, self.path_policy.as_ref())?;
+		if output.is_pipe() {
+			WavWriter::streaming(output, format)
+		} else {
+			WavWriter::new(output, format)
+		}
+	}
+
+	// a meter over the input seconds this run reads, from the in-point to the
+	// out-point or the end of the container's `duration` (times the passes)
+	// the muxer `open` creates on the output path, or the sink standing in for it
+	fn open_output<M: Muxer>(&self, open: impl FnOnce(&str) -> IoResult<M>) -> IoResult<Output<M>> {
+		match self.sink {
+			Some(sink) => Ok(Output::Sink(self.open_sink(sink)?)),
+			None => Ok(Output::File(open(&self.require_output()?)?)),
+		}
+	}
+
+	// a leg per tee output, leaving out the ones an existing file says to skip
+	fn open_tee(
+		&self,
+		open: impl Fn(&str) -> IoResult<Box<dyn tee::TeeLeg>>,
+	) -> IoResult<Vec<Box<dyn tee::TeeLeg>>> {
+		let mut legs = Vec::new();
+		for path in &self.tee {
+			if is_pipe(path) || self.overwrite.check(Path::new(path))? {
+				legs.push(open(path)?);
+			}
+		}
+		Ok(legs)
+	}
+
+	// a digest goes to the output path, or stdout when there is none
+	fn open_sink(&self, sink: SinkFormat) -> IoResult<SinkWriter<MediaOutput>> {
+		if sink == SinkFormat::Null {
+			return Ok(SinkWriter::null());
+		}
+		let path = self.output_path.as_deref().unwrap_or(PIPE);
+		Ok(SinkWriter::new(MediaOutput::create(path, self.path_policy.as_ref())?, sink))
+	}
+
+	fn progress(&self, duration: Option<f64>) -> Progress {
+		let (start, end) = self.trim.unwrap_or((0.0, None));
+		let total = self.passes.and_then(|passes| Some(duration? * passes as f64));
+		let end = match (end, total) {
+			(Some(end), Some(total)) => Some(end.min(total)),
+			(end, total) => end.or(total),
+		};
+		let progress = Progress::new(self.progress, start, end);
+		match &self.benchmark {
+			Some(benchmark) => progress.with_benchmark(benchmark.clone()),
+			None => progress,
+		}
+	}
+
+	fn flac_duration(format: &FlacFormat) -> Option<f64> {
+		// streams that did not know their length up front record 0 samples
+		(format.sample_rate > 0 && format.total_samples > 0)
+			.then(|| format.total_samples as f64 / format.sample_rate as f64)
+	}
+
+	fn require_output(&self) -> IoResult<String> {
+		self.output_path.clone().ok_or_else(|| {
+			IoError::with_message(IoErrorKind::InvalidData, "output path required for transcoding")
+		})
+	}
+
+	fn build_cutter(&self) -> Option<EdlCutter> {
+		let trim = self.trim.map(|(start, end)| {
+			let mut edl = EditDecisionList::new();
+			edl.add(EdlEntry::new(start, end.unwrap_or(f64::INFINITY)));
+			edl
+		});
+		let cutter = EdlCutter::new(trim.as_ref().or(self.edl.as_ref())?);
+		Some(match self.auto_fade {
+			Some(seconds) => cutter.with_fade(seconds),
+			None => cutter,
+		})
+	}
+
+	fn apply_edl(cutter: &mut Option<EdlCutter>, frame: Frame) -> Option<Frame> {
+		match cutter {
+			Some(cutter) => cutter.cut(frame),
+			None => Some(frame),
+		}
+	}
+
+	fn build_overlay(&self) -> IoResult<Option<Overlay>> {
+		let Some((path, (x, y), alpha)) = &self.overlay else {
+			return Ok(None);
+		};
+		let mut reader = Y4mReader::new(FileAdapter::open_with(path, self.path_policy.as_ref())?)?;
+		let mut decoder = RawVideoDecoder::new(reader.format());
+		let next = move || {
+			while let Some(packet) = reader.read_packet()? {
+				if let Some(Frame { data: FrameData::Video(video), .. }) = decoder.decode(packet)? {
+					return Ok(Some(video));
+				}
+			}
+			Ok(None)
+		};
+		Ok(Some(Overlay::new(next).with_position(*x, *y).with_alpha(*alpha)))
+	}
+
+	fn build_transform_chain(&self) -> IoResult<TransformChain> {
+		self.chain_from_specs(self.transform_specs()?)
+	}
+
+	// the transforms, followed by the filter graph when there is one
+	fn chain_from_specs(&self, specs: Vec<TransformSpec>) -> IoResult<TransformChain> {
+		let mut chain = TransformChain::from_specs_with(specs, self.path_policy.as_ref())?;
+		if let Some(graph) = &self.filter_graph {
+			let mut graph = FilterGraph::parse(graph)?;
+			if graph.inputs() != (1..=self.inputs.len()).collect::<Vec<_>>() {
+				return Err(IoError::invalid_data(
+					"the filter graph must read each extra input once, as [in1], [in2], ...",
+				));
+			}
+			for (index, path) in self.inputs.iter().enumerate() {
+				let (format, source) = source::open_source(path, self.path_policy.as_ref())?;
+				graph = graph.with_input(index + 1, format, source);
+			}
+			chain.add(Box::new(graph));
+		}
+		Ok(chain)
+	}
+
+	fn transform_specs(&self) -> IoResult<Vec<TransformSpec>> {
+		let mut specs = Vec::with_capacity(self.transforms.len() + self.transform_specs.len());
+		for spec in &self.transforms {
+			specs.extend(TransformSpec::parse_list(spec)?);
+		}
+		specs.extend(self.transform_specs.iter().cloned());
+
+		// loudnorm has to know the loudness of whatever reaches it, so the input
+		// is decoded once up front through the transforms placed before it
+		for idx in 0..specs.len() {
+			if let TransformSpec::Loudnorm { measured: None, .. } = specs[idx].target() {
+				let loudness = self.measure_through(&specs[..idx])?;
+				if let TransformSpec::Loudnorm { measured, .. } = specs[idx].target_mut() {
+					*measured = Some(loudness);
+				}
+			}
+			// likewise the crop has to be known before the y4m header is written
+			if let &TransformSpec::CropDetect { limit, round, window, apply: true, measured: None } =
+				specs[idx].target()
+				&& self.input_type() == MediaType::Y4m
+			{
+				let detect = CropDetect::new().with_limit(limit).with_round(round).with_window(window);
+				let crop = self.detect_crop(&specs[..idx], detect)?;
+				if let TransformSpec::CropDetect { measured, .. } = specs[idx].target_mut() {
+					*measured = crop;
+				}
+			}
+			// a video fade out has to know where the input ends
+			if let TransformSpec::VideoFade { fade_out: 1.., total_frames: None, .. } =
+				specs[idx].target()
+				&& self.input_type() == MediaType::Y4m
+			{
+				let total = self.count_y4m_frames()?;
+				if let TransformSpec::VideoFade { total_frames, .. } = specs[idx].target_mut() {
+					*total_frames = Some(total);
+				}
+			}
+			// stabilization works from the motion of the whole input
+			if let &TransformSpec::Stabilize { search, smoothing, corrections: None } =
+				specs[idx].target()
+			{
+				let motions = self.estimate_motion(&specs[..idx], search)?;
+				if let TransformSpec::Stabilize { corrections, .. } = specs[idx].target_mut() {
+					*corrections = Some(smooth_corrections(&motions, smoothing));
+				}
+			}
+		}
+		Ok(specs)
+	}
+
+	// motion into each frame of the y4m input, after `specs`
+	fn estimate_motion(&self, specs: &[TransformSpec], search: u32) -> IoResult<Vec<(i32, i32)>> {
+		if self.input_type() != MediaType::Y4m {
+			return Err(IoError::invalid_data("stabilize requires y4m input"));
+		}
+		let mut chain =
+			TransformChain::from_specs_with(specs.iter().cloned(), self.path_policy.as_ref())?;
+		let mut reader =
+			Y4mReader::new(MediaInput::open(&self.input_path, self.path_policy.as_ref())?)?;
+		let mut decoder = RawVideoDecoder::new(reader.format());
+		let mut estimator = MotionEstimator::new(search);
+		while let Some(packet) = reader.read_packet()? {
+			if let Some(frame) = decoder.decode(packet)? {
+				estimator.apply(chain.apply(frame)?)?;
+			}
+		}
+		Ok(estimator.motions().to_vec())
+	}
+
+	// runs the y4m input through `specs` until `detect` has seen its window
+	fn detect_crop(
+		&self,
+		specs: &[TransformSpec],
+		mut detect: CropDetect,
+	) -> IoResult<Option<(u32, u32, u32, u32)>> {
+		let mut chain =
+			TransformChain::from_specs_with(specs.iter().cloned(), self.path_policy.as_ref())?;
+		let mut reader =
+			Y4mReader::new(MediaInput::open(&self.input_path, self.path_policy.as_ref())?)?;
+		let mut decoder = RawVideoDecoder::new(reader.format());
+		while !detect.is_done() {
+			let Some(packet) = reader.read_packet()? else {
+				break;
+			};
+			if let Some(frame) = decoder.decode(packet)? {
+				detect.apply(chain.apply(frame)?)?;
+			}
+		}
+		eprint!("{}", detect.to_text());
+		Ok(detect.geometry())
+	}
+
+	// flac metadata precedes the audio, so the input is measured up front
+	fn replaygain_comment(&self) -> IoResult<VorbisComment> {
+		let gain = ReplayGain::from_loudness(self.measure_loudness()?);
+		let mut comment = VorbisComment::new();
+		for (key, value) in gain.tags() {
+			comment.add_comment(key, value);
+		}
+		Ok(comment)
+	}
+
+	// the id3 chunk for wav output, when there is anything to put in it
+	fn id3_tag(&self, gain: Option<ReplayGain>) -> Option<Id3v2Tag> {
+		if gain.is_none() && self.cover.is_none() {
+			return None;
+		}
+		let mut tag = Id3v2Tag::new();
+		for (key, value) in gain.iter().flat_map(ReplayGain::tags) {
+			tag.set_user_text(key, &value);
+		}
+		if let Some(cover) = &self.cover {
+			tag.set_picture(cover);
+		}
+		Some(tag)
+	}
+
+	// integrated loudness and true peak of the input after any edl cuts
+	pub fn measure_loudness(&self) -> IoResult<Loudness> {
+		self.measure_through(&[])
+	}
+
+	// the transforms ahead of loudnorm hand it their audio unquantized, so
+	// they are measured that way too
+	fn measure_through(&self, specs: &[TransformSpec]) -> IoResult<Loudness> {
+		let chain = TransformChain::from_specs_with(specs.iter().cloned(), self.path_policy.as_ref())?;
+		let mut chain = chain.unquantized();
+		let mut cutter = self.build_cutter();
+		let mut meter = LoudnessMeter::new();
+
+		decode_audio_as(&self.input_path, self.input_type(), self.path_policy.as_ref(), |frame| {
+			if let Some(frame) = Self::apply_edl(&mut cutter, frame) {
+				meter.apply(chain.apply(frame)?)?;
+			}
+			Ok(())
+		})?;
+		if let Some(tail) = chain.flush()? {
+			meter.apply(tail)?;
+		}
+
+		Ok(meter.loudness())
+	}
+}
+
+// where a run's packets go: the container file, or a null or hash sink
+enum Output<M: Muxer> {
+	File(M),
+	Sink(SinkWriter<MediaOutput>),
+}
+
+impl<M: Muxer> Output<M> {
+	// the container, for the header calls a sink has no use for
+	fn file(&mut self) -> Option<&mut M> {
+		match self {
+			Output::File(muxer) => Some(muxer),
+			Output::Sink(_) => None,
+		}
+	}
+}
+
+impl<M: Muxer> Muxer for Output<M> {
+	fn write_packet(&mut self, packet: Packet) -> IoResult<()> {
+		match self {
+			Output::File(muxer) => muxer.write_packet(packet),
+			Output::Sink(sink) => sink.write_packet(packet),
+		}
+	}
+
+	fn finalize(&mut self) -> IoResult<()> {
+		match self {
+			Output::File(muxer) => muxer.finalize(),
+			Output::Sink(sink) => sink.finalize(),
+		}
+	}
+}
+
+// decodes a wav or flac file and hands each frame to `each`
+pub(crate) fn decode_audio(
+	path: &str,
+	policy: &dyn PathPolicy,
+	each: impl FnMut(Frame) -> IoResult<()>,
+) -> IoResult<()> {
+	decode_audio_as(path, MediaType::from_extension(path), policy, each)
+}
+
+// `decode_audio` for a path whose container is already known, such as "-"
+pub(crate) fn decode_audio_as(
+	path: &str,
+	media_type: MediaType,
+	policy: &dyn PathPolicy,
+	mut each: impl FnMut(Frame) -> IoResult<()>,
+) -> IoResult<()> {
+	let input = MediaInput::open(path, policy)?;
+	match media_type {
+		MediaType::Wav => {
+			let mut reader = WavReader::new(input)?;
+			let mut decoder = PcmDecoder::new(reader.format());
+			while let Some(packet) = reader.read_packet()? {
+				if let Some(frame) = decoder.decode(packet)? {
+					each(frame)?;
+				}
+			}
+		}
+		MediaType::Flac => {
+			let mut reader = FlacReader::new(input)?;
+			let mut decoder = FlacDecoder::new(reader.format());
+			while let Some(packet) = reader.read_packet()? {
+				if let Some(frame) = decoder.decode(packet)? {
+					each(frame)?;
+				}
+			}
+			while let Some(frame) = decoder.flush()? {
+				each(frame)?;
+			}
+		}
+		_ => return Err(IoError::invalid_data("audio input must be wav or flac")),
+	}
+	Ok(())
+}
@@ -0,0 +1,186 @@
+use super::benchmark::Benchmark;
+use crate::show::units::format_duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+	#[default]
+	Off,
+	// one line on stderr, rewritten in place
+	Line,
+	// a json object per line on stderr, for front ends to parse
+	Json,
+}
+
+impl ProgressMode {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"off" | "none" => Some(ProgressMode::Off),
+			"line" | "text" => Some(ProgressMode::Line),
+			"json" => Some(ProgressMode::Json),
+			_ => None,
+		}
+	}
+}
+
+// where a run stands. `time` counts input seconds from the in-point and
+// `duration` is how much there is to get through, when the container says
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressReport {
+	pub time: f64,
+	pub duration: Option<f64>,
+	// input seconds per wall clock second
+	pub speed: Option<f64>,
+	pub done: bool,
+}
+
+impl ProgressReport {
+	pub fn percent(&self) -> Option<f64> {
+		let duration = self.duration.filter(|&d| d > 0.0)?;
+		Some((self.time / duration * 100.0).clamp(0.0, 100.0))
+	}
+
+	pub fn eta(&self) -> Option<f64> {
+		if self.done {
+			return Some(0.0);
+		}
+		let speed = self.speed.filter(|&s| s > 0.0)?;
+		Some((self.duration? - self.time).max(0.0) / speed)
+	}
+
+	// time=00:00:12.000 / 00:01:00.000  20.0%  speed=4.00x  eta=00:00:12.000
+	pub fn to_line(&self) -> String {
+		let mut line = format!("time={}", format_duration(self.time));
+		if let Some(duration) = self.duration {
+			line.push_str(&format!(" / {}", format_duration(duration)));
+		}
+		if let Some(percent) = self.percent() {
+			line.push_str(&format!("  {:.1}%", percent));
+		}
+		if let Some(speed) = self.speed {
+			line.push_str(&format!("  speed={:.2}x", speed));
+		}
+		if let Some(eta) = self.eta() {
+			line.push_str(&format!("  eta={}", format_duration(eta)));
+		}
+		line
+	}
+
+	pub fn to_json(&self) -> String {
+		let number = |value: Option<f64>| match value.filter(|v| v.is_finite()) {
+			Some(value) => format!("{:.3}", value),
+			None => "null".to_string(),
+		};
+		format!(
+			"{{\"time\":{},\"duration\":{},\"percent\":{},\"speed\":{},\"eta\":{},\"done\":{}}}",
+			number(Some(self.time)),
+			number(self.duration),
+			number(self.percent()),
+			number(self.speed),
+			number(self.eta()),
+			self.done
+		)
+	}
+}
+
+// follows the input position of a run and reports it on stderr at most every
+// `interval`, plus once at the end
+pub struct Progress {
+	mode: ProgressMode,
+	start: f64,
+	end: Option<f64>,
+	position: f64,
+	started: Instant,
+	last: Option<Instant>,
+	interval: Duration,
+	benchmark: Option<Arc<Benchmark>>,
+}
+
+impl Progress {
+	// `start..end` is the stretch of input seconds the run goes through; `end`
+	// is None when the container does not say how long it is
+	pub fn new(mode: ProgressMode, start: f64, end: Option<f64>) -> Self {
+		Self {
+			mode,
+			start,
+			end: end.filter(|end| end.is_finite()),
+			position: start,
+			started: Instant::now(),
+			last: None,
+			interval: Duration::from_millis(500),
+			benchmark: None,
+		}
+	}
+
+	pub fn with_interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+
+	// every update counts a packet into `benchmark`, and `finish` records how
+	// far the run got
+	pub fn with_benchmark(mut self, benchmark: Arc<Benchmark>) -> Self {
+		self.benchmark = Some(benchmark);
+		self
+	}
+
+	// a frame came out of the decoder
+	pub fn count_frame(&self) {
+		if let Some(benchmark) = &self.benchmark {
+			benchmark.count_frame();
+		}
+	}
+
+	// the input has been read up to `seconds`, one packet further
+	pub fn update(&mut self, seconds: f64) {
+		if let Some(benchmark) = &self.benchmark {
+			benchmark.count_packet();
+		}
+		// the last packet read can run a little past the out-point
+		let seconds = self.end.map_or(seconds, |end| seconds.min(end));
+		self.position = self.position.max(seconds);
+		if self.mode == ProgressMode::Off {
+			return;
+		}
+		let now = Instant::now();
+		if self.last.is_some_and(|last| now.duration_since(last) < self.interval) {
+			return;
+		}
+		self.last = Some(now);
+		self.emit(false);
+	}
+
+	pub fn finish(&mut self) {
+		// a finished run got through everything, whatever the last packet said
+		if let Some(end) = self.end {
+			self.position = self.position.max(end);
+		}
+		if let Some(benchmark) = &self.benchmark {
+			benchmark.set_media_time(self.report(true).time);
+		}
+		self.emit(true);
+	}
+
+	pub fn report(&self, done: bool) -> ProgressReport {
+		let time = (self.position - self.start).max(0.0);
+		let elapsed = self.started.elapsed().as_secs_f64();
+		ProgressReport {
+			time,
+			duration: self.end.map(|end| (end - self.start).max(0.0)),
+			speed: (elapsed > 0.0).then(|| time / elapsed),
+			done,
+		}
+	}
+
+	fn emit(&self, done: bool) {
+		let report = self.report(done);
+		match self.mode {
+			ProgressMode::Off => {}
+			// padded so a shorter line fully covers the one before it
+			ProgressMode::Line if done => eprintln!("\r{:<72}", report.to_line()),
+			ProgressMode::Line => eprint!("\r{:<72}", report.to_line()),
+			ProgressMode::Json => eprintln!("{}", report.to_json()),
+		}
+	}
+}
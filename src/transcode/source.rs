@@ -0,0 +1,98 @@
+use crate::codecs::{FlacDecoder, PcmDecoder, RawVideoDecoder};
+use crate::container::{FlacReader, MediaType, WavReader, Y4mReader};
+use crate::core::{Decoder, Demuxer, Frame, FrameAudio, FrameData, StreamFormat};
+use crate::io::{FileAdapter, IoError, IoResult, PathPolicy};
+use crate::transform::FrameSource;
+
+// opens a further input for a filter graph: its stream format and a source of
+// its decoded frames. video hands over a picture per call; audio is cut to the
+// length of the frame the graph was given, so inputs line up sample for sample
+pub(crate) fn open_source(
+	path: &str,
+	policy: &dyn PathPolicy,
+) -> IoResult<(StreamFormat, FrameSource)> {
+	let (format, mut frames) = open_frames(path, policy)?;
+	let source: FrameSource = match format {
+		StreamFormat::Video { .. } => Box::new(move |_| frames.next()),
+		StreamFormat::Audio { sample_rate, channels } => {
+			let mut pending: Vec<f32> = Vec::new();
+			Box::new(move |reference| {
+				let wanted = reference.audio().map(|audio| audio.nb_samples).unwrap_or(0);
+				let wanted = wanted * channels as usize;
+				while pending.len() < wanted {
+					match frames.next()? {
+						Some(Frame { data: FrameData::Audio(audio), .. }) => {
+							pending.extend(audio.samples_f32());
+						}
+						Some(_) => {}
+						None => break,
+					}
+				}
+				if pending.is_empty() {
+					return Ok(None);
+				}
+				let samples: Vec<f32> = pending.drain(..wanted.min(pending.len())).collect();
+				let audio = FrameAudio::from_f32(&samples, sample_rate, channels);
+				Ok(Some(Frame::new_audio(audio, reference.timebase, reference.stream_index)))
+			})
+		}
+	};
+	Ok((format, source))
+}
+
+// a wav, flac or y4m file's stream format and its frames, decoded one by one
+pub(crate) fn open_frames(path: &str, policy: &dyn PathPolicy) -> IoResult<(StreamFormat, Frames)> {
+	let input = FileAdapter::open_with(path, policy)?;
+	let (format, demuxer, decoder): (StreamFormat, Box<dyn Demuxer + Send>, Box<dyn Decoder + Send>) =
+		match MediaType::from_extension(path) {
+			MediaType::Wav => {
+				let reader = WavReader::new(input)?;
+				let format = reader.format();
+				let stream =
+					StreamFormat::Audio { sample_rate: format.sample_rate, channels: format.channels };
+				(stream, Box::new(reader), Box::new(PcmDecoder::new(format)))
+			}
+			MediaType::Flac => {
+				let reader = FlacReader::new(input)?;
+				let format = reader.format().clone();
+				let stream =
+					StreamFormat::Audio { sample_rate: format.sample_rate, channels: format.channels };
+				(stream, Box::new(reader), Box::new(FlacDecoder::new(&format)))
+			}
+			MediaType::Y4m => {
+				let reader = Y4mReader::new(input)?;
+				let format = reader.format();
+				let stream = StreamFormat::Video {
+					width: format.width,
+					height: format.height,
+					format: format.video_format(),
+				};
+				(stream, Box::new(reader), Box::new(RawVideoDecoder::new(format)))
+			}
+			_ => return Err(IoError::invalid_data("input must be wav, flac or y4m")),
+		};
+
+	Ok((format, Frames { demuxer, decoder, done: false }))
+}
+
+pub(crate) struct Frames {
+	demuxer: Box<dyn Demuxer + Send>,
+	decoder: Box<dyn Decoder + Send>,
+	done: bool,
+}
+
+impl Frames {
+	pub(crate) fn next(&mut self) -> IoResult<Option<Frame>> {
+		while !self.done {
+			match self.demuxer.read_packet()? {
+				Some(packet) => {
+					if let Some(frame) = self.decoder.decode(packet)? {
+						return Ok(Some(frame));
+					}
+				}
+				None => self.done = true,
+			}
+		}
+		self.decoder.flush()
+	}
+}
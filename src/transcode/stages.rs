@@ -0,0 +1,83 @@
+use crate::core::{Frame, Transform};
+use crate::io::{IoError, IoErrorKind, IoResult};
+use crate::transform::TransformChain;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+
+// frames a stage may run ahead of the one after it. enough to ride out a slow
+// write or a transform that works in bursts, few enough that a long video is
+// never held in memory
+const QUEUE_DEPTH: usize = 8;
+
+// decoded frames from `source` through `chain` into `sink`. one thread runs it
+// all in turn; two put decoding on a thread of its own; three or more also
+// give the transforms their own, splitting a long chain across up to
+// `threads - 2` of them. stages are joined by bounded queues, so a heavy chain
+// keeps working while the file is read and written
+pub(crate) fn run_stages(
+	threads: usize,
+	mut source: impl FnMut() -> IoResult<Option<Frame>> + Send,
+	chain: TransformChain,
+	mut sink: impl FnMut(Frame) -> IoResult<()>,
+) -> IoResult<()> {
+	if threads <= 1 {
+		let mut chain = chain;
+		return drain(std::iter::from_fn(|| source().transpose()), &mut chain, &mut sink);
+	}
+
+	std::thread::scope(|scope| {
+		let (sender, mut frames) = sync_channel(QUEUE_DEPTH);
+		scope.spawn(move || {
+			loop {
+				let frame = source().transpose();
+				let stop = !matches!(frame, Some(Ok(_)));
+				if frame.is_some_and(|frame| sender.send(frame).is_err()) || stop {
+					break;
+				}
+			}
+		});
+
+		let mut last = TransformChain::new();
+		if threads == 2 || chain.is_empty() {
+			last = chain;
+		} else {
+			for mut part in chain.split(threads - 2) {
+				let (sender, next) = sync_channel(QUEUE_DEPTH);
+				scope.spawn(move || transform_stage(frames, &mut part, sender));
+				frames = next;
+			}
+		}
+		drain(frames.into_iter(), &mut last, &mut sink)
+	})
+}
+
+fn transform_stage(
+	frames: Receiver<IoResult<Frame>>,
+	chain: &mut TransformChain,
+	sender: SyncSender<IoResult<Frame>>,
+) {
+	let mut forward = |frame: Frame| sender.send(Ok(frame)).map_err(|_| stopped());
+	if let Err(e) = drain(frames.into_iter(), chain, &mut forward) {
+		// nobody is left to tell when the next stage is what stopped
+		let _ = sender.send(Err(e));
+	}
+}
+
+// every frame through the chain, then its flushed tail
+fn drain(
+	frames: impl Iterator<Item = IoResult<Frame>>,
+	chain: &mut TransformChain,
+	sink: &mut impl FnMut(Frame) -> IoResult<()>,
+) -> IoResult<()> {
+	for frame in frames {
+		let frame = frame?;
+		sink(if chain.is_empty() { frame } else { chain.apply(frame)? })?;
+	}
+	if let Some(tail) = chain.flush()? {
+		sink(tail)?;
+	}
+	chain.finish()
+}
+
+fn stopped() -> IoError {
+	IoError::with_message(IoErrorKind::Other, "pipeline stage stopped early")
+}
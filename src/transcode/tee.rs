@@ -0,0 +1,131 @@
+use crate::codecs::{CodecId, FlacEncoder, PcmEncoder, RawVideoEncoder};
+use crate::container::{
+	FlacFormat, FlacWriter, MediaType, WavFormat, WavWriter, Y4mFormat, Y4mWriter,
+};
+use crate::core::{Encoder, Frame, Muxer, Timebase, VideoFormat};
+use crate::io::{BufferedWriter, IoError, IoResult, MediaOutput, PathPolicy};
+
+// one more output of a run, fed a copy of every frame the main output gets:
+// an encoder and a muxer of its own, so a single decode can land in several
+// files and containers
+pub(crate) trait TeeLeg {
+	fn write(&mut self, frame: &Frame) -> IoResult<()>;
+	fn finish(&mut self) -> IoResult<()>;
+}
+
+// a wav or flac file of the processed audio
+pub(crate) fn audio_leg(
+	path: &str,
+	format: WavFormat,
+	codec: CodecId,
+	policy: &dyn PathPolicy,
+) -> IoResult<Box<dyn TeeLeg>> {
+	let output = MediaOutput::create(path, policy)?;
+	match MediaType::from_extension(path) {
+		MediaType::Wav => {
+			let encoder = PcmEncoder::new(Timebase::new(1, format.sample_rate)).with_codec(codec);
+			let writer = if output.is_pipe() {
+				WavWriter::streaming(output, format)?
+			} else {
+				WavWriter::new(output, format)?
+			};
+			Ok(Box::new(WavLeg { writer, encoder, format }))
+		}
+		// frames leave the chain as s16, so 16 bits holds them exactly
+		MediaType::Flac => {
+			let flac_format = FlacFormat {
+				sample_rate: format.sample_rate,
+				channels: format.channels,
+				bits_per_sample: 16,
+				..FlacFormat::default()
+			};
+			let encoder =
+				FlacEncoder::new(format.sample_rate, format.channels, 16, flac_format.max_block_size);
+			Ok(Box::new(FlacLeg { writer: FlacWriter::new(output, flac_format)?, encoder }))
+		}
+		_ => Err(IoError::unsupported("tee outputs of audio must be wav or flac")),
+	}
+}
+
+// a y4m file of the processed video
+pub(crate) fn video_leg(
+	path: &str,
+	format: Y4mFormat,
+	(width, height, video_format): (u32, u32, VideoFormat),
+	policy: &dyn PathPolicy,
+) -> IoResult<Box<dyn TeeLeg>> {
+	if MediaType::from_extension(path) != MediaType::Y4m {
+		return Err(IoError::unsupported("tee outputs of video must be y4m"));
+	}
+	let timebase = Timebase::new(format.framerate_den, format.framerate_num);
+	let output = BufferedWriter::new(MediaOutput::create(path, policy)?);
+	let mut writer = Y4mWriter::new(output, format)?;
+	writer.match_format(width, height, video_format)?;
+	Ok(Box::new(Y4mLeg { writer, encoder: RawVideoEncoder::new(timebase) }))
+}
+
+struct WavLeg {
+	writer: WavWriter<MediaOutput>,
+	encoder: PcmEncoder,
+	format: WavFormat,
+}
+
+impl TeeLeg for WavLeg {
+	fn write(&mut self, frame: &Frame) -> IoResult<()> {
+		if let Some(audio) = frame.audio() {
+			self.writer.set_format(WavFormat {
+				channels: audio.channels,
+				sample_rate: audio.sample_rate,
+				..self.format
+			});
+		}
+		if let Some(packet) = self.encoder.encode(frame.clone())? {
+			self.writer.write_packet(packet)?;
+		}
+		Ok(())
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		self.writer.finalize()
+	}
+}
+
+struct FlacLeg {
+	writer: FlacWriter<MediaOutput>,
+	encoder: FlacEncoder,
+}
+
+impl TeeLeg for FlacLeg {
+	fn write(&mut self, frame: &Frame) -> IoResult<()> {
+		if let Some(packet) = self.encoder.encode(frame.clone())? {
+			self.writer.write_packet(packet)?;
+		}
+		Ok(())
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		self.writer.finalize()
+	}
+}
+
+struct Y4mLeg {
+	writer: Y4mWriter<BufferedWriter<MediaOutput>>,
+	encoder: RawVideoEncoder,
+}
+
+impl TeeLeg for Y4mLeg {
+	fn write(&mut self, frame: &Frame) -> IoResult<()> {
+		if let Some(video) = frame.video() {
+			self.writer.match_frame(video)?;
+			self.writer.match_timebase(frame.timebase);
+		}
+		if let Some(packet) = self.encoder.encode(frame.clone())? {
+			self.writer.write_packet(packet)?;
+		}
+		Ok(())
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		self.writer.finalize()
+	}
+}
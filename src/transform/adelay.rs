@@ -0,0 +1,103 @@
+use crate::core::{ChannelLayout, Frame, FrameAudio, Timebase, Transform};
+use crate::io::IoResult;
+use std::collections::VecDeque;
+
+// shifts individual channels later by inserting silence in front of them,
+// e.g. to line up a room mic with a close mic. channels past the end of the
+// list are not delayed. the output runs longer than the input by the largest
+// delay, which `flush` hands back at the end.
+pub struct Adelay {
+	delays_ms: Vec<f32>,
+	sample_rate: u32,
+	// per channel, primed with the delay's worth of silence
+	lines: Vec<VecDeque<i16>>,
+	next_pts: Option<i64>,
+	timebase: Timebase,
+	stream_index: usize,
+	layout: ChannelLayout,
+}
+
+impl Adelay {
+	pub fn new(delays_ms: Vec<f32>) -> Self {
+		Self {
+			delays_ms,
+			sample_rate: 0,
+			lines: Vec::new(),
+			next_pts: None,
+			timebase: Timebase::new(1, 1),
+			stream_index: 0,
+			layout: ChannelLayout::Mono,
+		}
+	}
+
+	fn configure(&mut self, sample_rate: u32, channels: usize) {
+		self.sample_rate = sample_rate;
+		self.lines = (0..channels)
+			.map(|ch| {
+				let ms = self.delays_ms.get(ch).copied().unwrap_or(0.0) as f64;
+				let samples = (ms * sample_rate as f64 / 1000.0).round() as usize;
+				VecDeque::from(vec![0; samples])
+			})
+			.collect();
+	}
+
+	fn output_frame(&mut self, data: Vec<u8>) -> Frame {
+		let channels = self.lines.len().max(1);
+		let nb_samples = data.len() / 2 / channels;
+		let audio = FrameAudio::new(data, self.sample_rate, channels as u8)
+			.with_nb_samples(nb_samples)
+			.with_layout(self.layout);
+		let pts = self.next_pts.unwrap_or(0);
+		self.next_pts = Some(pts + nb_samples as i64);
+		Frame::new_audio(audio, self.timebase, self.stream_index).with_pts(pts)
+	}
+}
+
+impl Transform for Adelay {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some(audio) = frame.audio() else {
+			return Ok(frame);
+		};
+
+		let channels = audio.channels.max(1) as usize;
+		if self.sample_rate != audio.sample_rate || self.lines.len() != channels {
+			self.configure(audio.sample_rate, channels);
+		}
+		self.next_pts.get_or_insert(frame.pts);
+		self.timebase = frame.timebase;
+		self.stream_index = frame.stream_index;
+		self.layout = audio.layout;
+
+		let mut output = Vec::with_capacity(audio.data.len());
+		for sample_frame in audio.data.chunks_exact(channels * 2) {
+			for (line, bytes) in self.lines.iter_mut().zip(sample_frame.chunks_exact(2)) {
+				line.push_back(i16::from_le_bytes([bytes[0], bytes[1]]));
+				let delayed = line.pop_front().unwrap_or(0);
+				output.extend_from_slice(&delayed.to_le_bytes());
+			}
+		}
+
+		Ok(self.output_frame(output))
+	}
+
+	fn name(&self) -> &'static str {
+		"adelay"
+	}
+
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		let longest = self.lines.iter().map(VecDeque::len).max().unwrap_or(0);
+		if longest == 0 {
+			return Ok(None);
+		}
+
+		let mut output = Vec::with_capacity(longest * self.lines.len() * 2);
+		for _ in 0..longest {
+			for line in &mut self.lines {
+				let delayed = line.pop_front().unwrap_or(0);
+				output.extend_from_slice(&delayed.to_le_bytes());
+			}
+		}
+
+		Ok(Some(self.output_frame(output)))
+	}
+}
@@ -0,0 +1,139 @@
+use crate::core::{Frame, Transform};
+use crate::io::IoResult;
+
+// running totals for one channel. levels are in dBFS against i16 full scale;
+// a sample at either rail counts as clipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChannelStats {
+	pub samples: u64,
+	pub peak: u16,
+	sum: i64,
+	sum_squares: f64,
+	pub zero_crossings: u64,
+	pub clipped: u64,
+	last: i16,
+}
+
+impl ChannelStats {
+	fn push(&mut self, sample: i16) {
+		if self.samples > 0 && (self.last < 0) != (sample < 0) {
+			self.zero_crossings += 1;
+		}
+		if sample == i16::MAX || sample == i16::MIN {
+			self.clipped += 1;
+		}
+		self.peak = self.peak.max(sample.unsigned_abs());
+		self.sum += sample as i64;
+		self.sum_squares += (sample as f64) * (sample as f64);
+		self.samples += 1;
+		self.last = sample;
+	}
+
+	pub fn peak_db(&self) -> f64 {
+		20.0 * (self.peak as f64 / 32768.0).log10()
+	}
+
+	pub fn rms_db(&self) -> f64 {
+		let rms = (self.sum_squares / self.samples.max(1) as f64).sqrt();
+		20.0 * (rms / 32768.0).log10()
+	}
+
+	// mean sample value as a fraction of full scale
+	pub fn dc_offset(&self) -> f64 {
+		self.sum as f64 / self.samples.max(1) as f64 / 32768.0
+	}
+
+	pub fn to_json(&self) -> String {
+		format!(
+			"{{\"dc_offset\":{:.6},\"peak_db\":{},\"rms_db\":{},\"zero_crossings\":{},\"clipped\":{}}}",
+			self.dc_offset(),
+			json_db(self.peak_db()),
+			json_db(self.rms_db()),
+			self.zero_crossings,
+			self.clipped
+		)
+	}
+}
+
+// digital silence has no finite level
+fn json_db(db: f64) -> String {
+	if db.is_finite() { format!("{:.2}", db) } else { "null".to_string() }
+}
+
+// passes audio through untouched and measures every channel, printing the
+// totals once the input ends
+pub struct AStats {
+	json: bool,
+	channels: Vec<ChannelStats>,
+}
+
+impl AStats {
+	pub fn new() -> Self {
+		Self { json: false, channels: Vec::new() }
+	}
+
+	pub fn with_json(mut self, json: bool) -> Self {
+		self.json = json;
+		self
+	}
+
+	pub fn stats(&self) -> &[ChannelStats] {
+		&self.channels
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut out = String::new();
+		for (idx, stats) in self.channels.iter().enumerate() {
+			out.push_str(&format!("Channel: {}\n", idx + 1));
+			out.push_str(&format!("DC offset: {:.6}\n", stats.dc_offset()));
+			out.push_str(&format!("Peak level dB: {:.2}\n", stats.peak_db()));
+			out.push_str(&format!("RMS level dB: {:.2}\n", stats.rms_db()));
+			out.push_str(&format!("Zero crossings: {}\n", stats.zero_crossings));
+			out.push_str(&format!("Clipped samples: {}\n", stats.clipped));
+		}
+		out
+	}
+
+	pub fn to_json(&self) -> String {
+		let channels: Vec<String> = self.channels.iter().map(ChannelStats::to_json).collect();
+		format!("{{\"astats\":[{}]}}", channels.join(","))
+	}
+}
+
+impl Default for AStats {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for AStats {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		if let Some(audio_frame) = frame.audio() {
+			let channels = audio_frame.channels.max(1) as usize;
+			if self.channels.len() < channels {
+				self.channels.resize(channels, ChannelStats::default());
+			}
+
+			for sample_frame in audio_frame.data.chunks_exact(channels * 2) {
+				for (stats, bytes) in self.channels.iter_mut().zip(sample_frame.chunks_exact(2)) {
+					stats.push(i16::from_le_bytes([bytes[0], bytes[1]]));
+				}
+			}
+		}
+
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"astats"
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		if self.json {
+			eprintln!("{}", self.to_json());
+		} else {
+			eprint!("{}", self.to_text());
+		}
+		Ok(())
+	}
+}
@@ -1,19 +1,24 @@
-use crate::core::{Frame, Transform};
-use crate::io::IoResult;
-
-#[derive(Debug, Clone, Copy)]
-pub enum ChannelLayout {
-	Mono,
-	Stereo,
+pub use crate::core::ChannelLayout;
+
+use crate::core::{Frame, StreamFormat, Transform};
+use crate::io::{IoError, IoResult};
+use std::f32::consts::FRAC_1_SQRT_2;
+
+enum Mixing {
+	Layout(ChannelLayout),
+	// one row per output channel, one column per input channel
+	Matrix(Vec<Vec<f32>>),
+	// input channel index for each output channel
+	Map(Vec<usize>),
 }
 
 pub struct ChannelMixer {
-	target_layout: ChannelLayout,
+	mixing: Mixing,
 }
 
 impl ChannelMixer {
 	pub fn new(target_layout: ChannelLayout) -> Self {
-		Self { target_layout }
+		Self { mixing: Mixing::Layout(target_layout) }
 	}
 
 	pub fn mono_to_stereo() -> Self {
@@ -24,56 +29,105 @@ impl ChannelMixer {
 		Self::new(ChannelLayout::Mono)
 	}
 
-	fn convert_mono_to_stereo(samples: &[i16]) -> Vec<i16> {
-		let mut output = Vec::with_capacity(samples.len() * 2);
-		for &sample in samples {
-			output.push(sample);
-			output.push(sample);
+	// 5.1 -> stereo, whatever the input says it is
+	pub fn downmix_51_to_stereo() -> Self {
+		Self { mixing: Mixing::Matrix(downmix_51_stereo()) }
+	}
+
+	// every row must have one gain per input channel; the frame being mixed has
+	// to have exactly that many channels.
+	pub fn from_matrix(matrix: Vec<Vec<f32>>) -> IoResult<Self> {
+		let inputs = matrix.first().map(Vec::len).unwrap_or(0);
+		if inputs == 0 || matrix.len() > u8::MAX as usize {
+			return Err(IoError::invalid_data("channel matrix needs at least one row and column"));
+		}
+		if matrix.iter().any(|row| row.len() != inputs) {
+			return Err(IoError::invalid_data("channel matrix rows must all be the same length"));
+		}
+		Ok(Self { mixing: Mixing::Matrix(matrix) })
+	}
+
+	// picks output channels from the input by index, e.g. [1, 0] swaps left and right
+	pub fn remap(map: Vec<usize>) -> IoResult<Self> {
+		if map.is_empty() || map.len() > u8::MAX as usize {
+			return Err(IoError::invalid_data("channel map needs at least one channel"));
 		}
-		output
+		Ok(Self { mixing: Mixing::Map(map) })
 	}
 
-	fn convert_stereo_to_mono(samples: &[i16]) -> Vec<i16> {
-		let mut output = Vec::with_capacity(samples.len() / 2);
-		for pair in samples.chunks(2) {
-			if pair.len() == 2 {
-				let mixed = ((pair[0] as i32 + pair[1] as i32) / 2) as i16;
-				output.push(mixed);
+	fn layout_matrix(src: ChannelLayout, dst: ChannelLayout) -> Option<Vec<Vec<f32>>> {
+		match (src, dst) {
+			(ChannelLayout::Mono, ChannelLayout::Stereo) => Some(vec![vec![1.0], vec![1.0]]),
+			(ChannelLayout::Stereo, ChannelLayout::Mono) => Some(vec![vec![0.5, 0.5]]),
+			(ChannelLayout::Surround51, ChannelLayout::Stereo) => Some(downmix_51_stereo()),
+			(ChannelLayout::Surround51, ChannelLayout::Mono) => {
+				let stereo = downmix_51_stereo();
+				Some(vec![stereo[0].iter().zip(&stereo[1]).map(|(l, r)| (l + r) / 2.0).collect()])
 			}
+			_ => None,
+		}
+	}
+}
+
+// ITU-R BS.775 coefficients (centre and surrounds at -3 dB, LFE dropped),
+// scaled down so a full scale signal on every channel cannot clip
+fn downmix_51_stereo() -> Vec<Vec<f32>> {
+	let norm = 1.0 / (1.0 + 2.0 * FRAC_1_SQRT_2);
+	let side = FRAC_1_SQRT_2 * norm;
+	vec![vec![norm, 0.0, side, 0.0, side, 0.0], vec![0.0, norm, side, 0.0, 0.0, side]]
+}
+
+fn mix(samples: &[i16], inputs: usize, matrix: &[Vec<f32>]) -> Vec<i16> {
+	let mut output = Vec::with_capacity(samples.len() / inputs * matrix.len());
+	for sample_frame in samples.chunks_exact(inputs) {
+		for row in matrix {
+			let value: f32 = row.iter().zip(sample_frame).map(|(gain, &s)| gain * s as f32).sum();
+			output.push(value.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16);
 		}
-		output
 	}
+	output
 }
 
 impl Transform for ChannelMixer {
 	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
 		if let Some(audio_frame) = frame.audio_mut() {
-			let src_channels = audio_frame.channels;
-			let target_channels = match self.target_layout {
-				ChannelLayout::Mono => 1,
-				ChannelLayout::Stereo => 2,
+			let inputs = audio_frame.channels as usize;
+			let matrix = match &self.mixing {
+				Mixing::Layout(target) if audio_frame.channels == target.channels() => {
+					return Ok(frame);
+				}
+				Mixing::Layout(target) => match Self::layout_matrix(audio_frame.layout, *target) {
+					Some(matrix) => matrix,
+					None => return Ok(frame),
+				},
+				Mixing::Matrix(matrix) => {
+					if matrix[0].len() != inputs {
+						return Err(IoError::invalid_data(
+							"channel matrix does not match the input channel count",
+						));
+					}
+					matrix.clone()
+				}
+				Mixing::Map(map) => {
+					if map.iter().any(|&idx| idx >= inputs) {
+						return Err(IoError::invalid_data("channel map refers to a missing input channel"));
+					}
+					map
+						.iter()
+						.map(|&idx| (0..inputs).map(|ch| if ch == idx { 1.0 } else { 0.0 }).collect())
+						.collect()
+				}
 			};
 
-			if src_channels == target_channels {
-				return Ok(frame);
-			}
-
 			let input_samples: Vec<i16> =
 				audio_frame.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+			let output_samples = mix(&input_samples, inputs, &matrix);
 
-			let output_samples = match (src_channels, target_channels) {
-				(1, 2) => Self::convert_mono_to_stereo(&input_samples),
-				(2, 1) => Self::convert_stereo_to_mono(&input_samples),
-				_ => input_samples,
-			};
-
-			let output_data: Vec<u8> = output_samples.iter().flat_map(|s| s.to_le_bytes()).collect();
-
-			let nb_samples = output_samples.len() / target_channels as usize;
-
-			audio_frame.data = output_data;
+			let target_channels = matrix.len() as u8;
+			audio_frame.data = output_samples.iter().flat_map(|s| s.to_le_bytes()).collect();
 			audio_frame.channels = target_channels;
-			audio_frame.nb_samples = nb_samples;
+			audio_frame.layout = ChannelLayout::from_channels(target_channels);
+			audio_frame.nb_samples = output_samples.len() / target_channels as usize;
 		}
 
 		Ok(frame)
@@ -82,4 +136,35 @@ impl Transform for ChannelMixer {
 	fn name(&self) -> &'static str {
 		"channel_mixer"
 	}
+
+	// the input layout is taken from the channel count, as a wav header gives it
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let StreamFormat::Audio { sample_rate, channels: inputs } = input else {
+			return Ok(input);
+		};
+		let channels = match &self.mixing {
+			Mixing::Layout(target) => {
+				let source = ChannelLayout::from_channels(inputs);
+				match Self::layout_matrix(source, *target) {
+					Some(matrix) if inputs != target.channels() => matrix.len() as u8,
+					_ => inputs,
+				}
+			}
+			Mixing::Matrix(matrix) => {
+				if matrix[0].len() != inputs as usize {
+					return Err(IoError::invalid_data(
+						"channel matrix does not match the input channel count",
+					));
+				}
+				matrix.len() as u8
+			}
+			Mixing::Map(map) => {
+				if map.iter().any(|&idx| idx >= inputs as usize) {
+					return Err(IoError::invalid_data("channel map refers to a missing input channel"));
+				}
+				map.len() as u8
+			}
+		};
+		Ok(StreamFormat::Audio { sample_rate, channels })
+	}
 }
@@ -0,0 +1,57 @@
+use crate::core::{Frame, Transform};
+use crate::io::IoResult;
+use std::f64::consts::PI;
+
+// one-pole dc blocker, y[n] = x[n] - x[n-1] + r * y[n-1], with r set so the
+// response is 3 dB down at `cutoff`
+pub struct DcRemove {
+	cutoff: f64,
+	r: f64,
+	sample_rate: u32,
+	// (previous input, previous output) per channel
+	states: Vec<(f64, f64)>,
+}
+
+impl DcRemove {
+	pub fn new(cutoff: f64) -> Self {
+		Self { cutoff, r: 0.0, sample_rate: 0, states: Vec::new() }
+	}
+}
+
+impl Default for DcRemove {
+	fn default() -> Self {
+		Self::new(5.0)
+	}
+}
+
+impl Transform for DcRemove {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(audio_frame) = frame.audio_mut() {
+			if self.sample_rate != audio_frame.sample_rate {
+				self.sample_rate = audio_frame.sample_rate;
+				self.r = (-2.0 * PI * self.cutoff / audio_frame.sample_rate.max(1) as f64).exp();
+			}
+
+			let channels = audio_frame.channels.max(1) as usize;
+			if self.states.len() != channels {
+				self.states = vec![(0.0, 0.0); channels];
+			}
+
+			for sample_frame in audio_frame.data.chunks_exact_mut(channels * 2) {
+				for (state, bytes) in self.states.iter_mut().zip(sample_frame.chunks_exact_mut(2)) {
+					let x = i16::from_le_bytes([bytes[0], bytes[1]]) as f64;
+					let y = x - state.0 + self.r * state.1;
+					*state = (x, y);
+					let output = y.round().clamp(-32768.0, 32767.0) as i16;
+					bytes.copy_from_slice(&output.to_le_bytes());
+				}
+			}
+		}
+
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"dcremove"
+	}
+}
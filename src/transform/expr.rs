@@ -0,0 +1,435 @@
+use super::TransformSpec;
+use crate::core::{Frame, StreamFormat, Transform};
+use crate::io::{IoError, IoErrorKind, IoResult};
+
+// what an expression can read: `t` (seconds), `n` (frame index), `w` and `h`
+// (video size, 0 for audio)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Variables {
+	pub t: f64,
+	pub n: f64,
+	pub w: f64,
+	pub h: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Var {
+	T,
+	N,
+	W,
+	H,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Func {
+	Sin,
+	Cos,
+	Tan,
+	Abs,
+	Sqrt,
+	Exp,
+	Log,
+	Floor,
+	Ceil,
+	Round,
+	Min,
+	Max,
+	Pow,
+	Clip,
+}
+
+impl Func {
+	fn from_name(name: &str) -> Option<Self> {
+		Some(match name {
+			"sin" => Func::Sin,
+			"cos" => Func::Cos,
+			"tan" => Func::Tan,
+			"abs" => Func::Abs,
+			"sqrt" => Func::Sqrt,
+			"exp" => Func::Exp,
+			"log" => Func::Log,
+			"floor" => Func::Floor,
+			"ceil" => Func::Ceil,
+			"round" => Func::Round,
+			"min" => Func::Min,
+			"max" => Func::Max,
+			"pow" => Func::Pow,
+			"clip" => Func::Clip,
+			_ => return None,
+		})
+	}
+
+	fn arity(&self) -> usize {
+		match self {
+			Func::Min | Func::Max | Func::Pow => 2,
+			Func::Clip => 3,
+			_ => 1,
+		}
+	}
+}
+
+// arithmetic over numbers, the variables above and PI/E, with + - * / % ^,
+// parentheses and sin cos tan abs sqrt exp log floor ceil round min max pow
+// clip(x,lo,hi), e.g. `0.5+0.5*sin(2*PI*t)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+	Number(f64),
+	Var(Var),
+	Neg(Box<Expr>),
+	Binary(char, Box<Expr>, Box<Expr>),
+	Call(Func, Vec<Expr>),
+}
+
+impl Expr {
+	pub fn parse(text: &str) -> IoResult<Self> {
+		let tokens = tokenize(text)?;
+		let mut parser = Parser { tokens, pos: 0 };
+		let expr = parser.sum()?;
+		if parser.pos != parser.tokens.len() {
+			return Err(invalid());
+		}
+		Ok(expr)
+	}
+
+	pub fn eval(&self, vars: &Variables) -> f64 {
+		match self {
+			Expr::Number(value) => *value,
+			Expr::Var(Var::T) => vars.t,
+			Expr::Var(Var::N) => vars.n,
+			Expr::Var(Var::W) => vars.w,
+			Expr::Var(Var::H) => vars.h,
+			Expr::Neg(inner) => -inner.eval(vars),
+			Expr::Binary(op, left, right) => {
+				let (a, b) = (left.eval(vars), right.eval(vars));
+				match op {
+					'+' => a + b,
+					'-' => a - b,
+					'*' => a * b,
+					'/' => a / b,
+					'%' => a % b,
+					_ => a.powf(b),
+				}
+			}
+			Expr::Call(func, args) => {
+				let args: Vec<f64> = args.iter().map(|arg| arg.eval(vars)).collect();
+				match func {
+					Func::Sin => args[0].sin(),
+					Func::Cos => args[0].cos(),
+					Func::Tan => args[0].tan(),
+					Func::Abs => args[0].abs(),
+					Func::Sqrt => args[0].sqrt(),
+					Func::Exp => args[0].exp(),
+					Func::Log => args[0].ln(),
+					Func::Floor => args[0].floor(),
+					Func::Ceil => args[0].ceil(),
+					Func::Round => args[0].round(),
+					Func::Min => args[0].min(args[1]),
+					Func::Max => args[0].max(args[1]),
+					Func::Pow => args[0].powf(args[1]),
+					Func::Clip => args[0].max(args[1]).min(args[2]),
+				}
+			}
+		}
+	}
+
+	pub fn is_constant(&self) -> bool {
+		match self {
+			Expr::Number(_) => true,
+			Expr::Var(_) => false,
+			Expr::Neg(inner) => inner.is_constant(),
+			Expr::Binary(_, left, right) => left.is_constant() && right.is_constant(),
+			Expr::Call(_, args) => args.iter().all(Expr::is_constant),
+		}
+	}
+}
+
+fn invalid() -> IoError {
+	IoError::with_message(
+		IoErrorKind::InvalidData,
+		"invalid expression (e.g., 0.5+0.5*sin(2*PI*t) or w/2)",
+	)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	Number(f64),
+	Name(String),
+	Op(char),
+	Open,
+	Close,
+	Comma,
+}
+
+fn tokenize(text: &str) -> IoResult<Vec<Token>> {
+	let chars: Vec<char> = text.chars().collect();
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	while i < chars.len() {
+		let c = chars[i];
+		let start = i;
+		i += 1;
+		match c {
+			' ' => {}
+			'0'..='9' | '.' => {
+				while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+					i += 1;
+				}
+				let number: String = chars[start..i].iter().collect();
+				tokens.push(Token::Number(number.parse().map_err(|_| invalid())?));
+			}
+			'a'..='z' | 'A'..='Z' | '_' => {
+				while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+					i += 1;
+				}
+				tokens.push(Token::Name(chars[start..i].iter().collect()));
+			}
+			'+' | '-' | '*' | '/' | '%' | '^' => tokens.push(Token::Op(c)),
+			'(' => tokens.push(Token::Open),
+			')' => tokens.push(Token::Close),
+			',' => tokens.push(Token::Comma),
+			_ => return Err(invalid()),
+		}
+	}
+	Ok(tokens)
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	pos: usize,
+}
+
+impl Parser {
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.pos).cloned();
+		self.pos += 1;
+		token
+	}
+
+	fn eat(&mut self, token: &Token) -> bool {
+		let found = self.tokens.get(self.pos) == Some(token);
+		if found {
+			self.pos += 1;
+		}
+		found
+	}
+
+	fn binary(&mut self, ops: &[char], operand: fn(&mut Self) -> IoResult<Expr>) -> IoResult<Expr> {
+		let mut expr = operand(self)?;
+		while let Some(Token::Op(op)) = self.tokens.get(self.pos).cloned() {
+			if !ops.contains(&op) {
+				break;
+			}
+			self.pos += 1;
+			expr = Expr::Binary(op, Box::new(expr), Box::new(operand(self)?));
+		}
+		Ok(expr)
+	}
+
+	fn sum(&mut self) -> IoResult<Expr> {
+		self.binary(&['+', '-'], Self::product)
+	}
+
+	fn product(&mut self) -> IoResult<Expr> {
+		self.binary(&['*', '/', '%'], Self::unary)
+	}
+
+	fn unary(&mut self) -> IoResult<Expr> {
+		if self.eat(&Token::Op('-')) {
+			return Ok(Expr::Neg(Box::new(self.unary()?)));
+		}
+		if self.eat(&Token::Op('+')) {
+			return self.unary();
+		}
+		let base = self.atom()?;
+		// right associative and tighter than a leading minus: -2^2 is -4
+		if self.eat(&Token::Op('^')) {
+			return Ok(Expr::Binary('^', Box::new(base), Box::new(self.unary()?)));
+		}
+		Ok(base)
+	}
+
+	fn atom(&mut self) -> IoResult<Expr> {
+		match self.next().ok_or_else(invalid)? {
+			Token::Number(value) => Ok(Expr::Number(value)),
+			Token::Open => {
+				let expr = self.sum()?;
+				if !self.eat(&Token::Close) {
+					return Err(invalid());
+				}
+				Ok(expr)
+			}
+			Token::Name(name) => match name.as_str() {
+				"t" => Ok(Expr::Var(Var::T)),
+				"n" => Ok(Expr::Var(Var::N)),
+				"w" | "iw" => Ok(Expr::Var(Var::W)),
+				"h" | "ih" => Ok(Expr::Var(Var::H)),
+				"PI" => Ok(Expr::Number(std::f64::consts::PI)),
+				"E" => Ok(Expr::Number(std::f64::consts::E)),
+				_ => {
+					let func = Func::from_name(&name).ok_or_else(invalid)?;
+					if !self.eat(&Token::Open) {
+						return Err(invalid());
+					}
+					let mut args = vec![self.sum()?];
+					while self.eat(&Token::Comma) {
+						args.push(self.sum()?);
+					}
+					if !self.eat(&Token::Close) || args.len() != func.arity() {
+						return Err(invalid());
+					}
+					Ok(Expr::Call(func, args))
+				}
+			},
+			_ => Err(invalid()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+	Text(String),
+	Expr(Expr),
+}
+
+// a transform spec with expressions in place of some parameter values. each
+// value between `=`, `:` and top-level `,` that reads a variable becomes an
+// expression; everything else (numbers, `white`, `1280x720`, option keys)
+// stays as written. drawtext text and file paths are never read as
+// expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecTemplate {
+	pieces: Vec<Piece>,
+}
+
+impl SpecTemplate {
+	pub fn parse(spec: &str) -> Self {
+		let mut pieces = Vec::new();
+		let Some((name, value)) = spec.split_once('=') else {
+			return Self { pieces: vec![Piece::Text(spec.to_string())] };
+		};
+		pieces.push(Piece::Text(format!("{}=", name)));
+		if matches!(name, "lut3d" | "drawtext") {
+			pieces.push(Piece::Text(value.to_string()));
+			return Self { pieces };
+		}
+
+		let mut depth = 0;
+		let mut field = String::new();
+		for c in value.chars() {
+			match c {
+				'(' => depth += 1,
+				')' => depth -= 1,
+				_ => {}
+			}
+			if depth == 0 && c == '=' {
+				// an option key, as in x=w/2
+				pieces.push(Piece::Text(std::mem::take(&mut field)));
+				pieces.push(Piece::Text(c.to_string()));
+			} else if depth == 0 && matches!(c, ':' | ',') {
+				push_field(&mut pieces, &field);
+				pieces.push(Piece::Text(c.to_string()));
+				field.clear();
+			} else {
+				field.push(c);
+			}
+		}
+		push_field(&mut pieces, &field);
+		Self { pieces }
+	}
+
+	pub fn has_expressions(&self) -> bool {
+		self.pieces.iter().any(|piece| matches!(piece, Piece::Expr(_)))
+	}
+
+	// the spec with every expression replaced by its value; whole values are
+	// written without a decimal point so integer parameters accept them
+	pub fn render(&self, vars: &Variables) -> IoResult<String> {
+		let mut spec = String::new();
+		for piece in &self.pieces {
+			match piece {
+				Piece::Text(text) => spec.push_str(text),
+				Piece::Expr(expr) => {
+					let value = expr.eval(vars);
+					if !value.is_finite() {
+						return Err(IoError::invalid_data("transform expression is not a finite number"));
+					}
+					if value.fract() == 0.0 && value.abs() < 1e15 {
+						spec.push_str(&(value as i64).to_string());
+					} else {
+						spec.push_str(&value.to_string());
+					}
+				}
+			}
+		}
+		Ok(spec)
+	}
+}
+
+fn push_field(pieces: &mut Vec<Piece>, field: &str) {
+	match Expr::parse(field) {
+		Ok(expr) if !expr.is_constant() => pieces.push(Piece::Expr(expr)),
+		_ => pieces.push(Piece::Text(field.to_string())),
+	}
+}
+
+// a transform whose parameters change per frame. the spec is re-rendered for
+// every frame and the transform rebuilt only when the values change, so a
+// filter with state keeps it while its parameters hold still.
+pub struct Evaluated {
+	template: SpecTemplate,
+	frame_index: u64,
+	current: Option<(String, Box<dyn Transform>)>,
+}
+
+impl Evaluated {
+	pub fn new(spec: &str) -> Self {
+		Self { template: SpecTemplate::parse(spec), frame_index: 0, current: None }
+	}
+}
+
+impl Transform for Evaluated {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let (w, h) =
+			frame.video().map_or((0.0, 0.0), |video| (video.width as f64, video.height as f64));
+		let t = frame.timebase.to_seconds(frame.pts);
+		let vars = Variables { t, n: self.frame_index as f64, w, h };
+		self.frame_index += 1;
+
+		let spec = self.template.render(&vars)?;
+		let transform = match self.current.take() {
+			Some((current, transform)) if current == spec => transform,
+			_ => TransformSpec::parse(&spec)?.build()?,
+		};
+		let (_, transform) = self.current.insert((spec, transform));
+		transform.apply(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"expression"
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		match &mut self.current {
+			Some((_, transform)) => transform.finish(),
+			None => Ok(()),
+		}
+	}
+
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		match &mut self.current {
+			Some((_, transform)) => transform.flush(),
+			None => Ok(None),
+		}
+	}
+
+	// checked with the values of the first frame; a size that moves later is
+	// caught by the muxer instead
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let (w, h) = match input {
+			StreamFormat::Video { width, height, .. } => (width as f64, height as f64),
+			StreamFormat::Audio { .. } => (0.0, 0.0),
+		};
+		let spec = self.template.render(&Variables { t: 0.0, n: 0.0, w, h })?;
+		TransformSpec::parse(&spec)?.build()?.negotiate(input)
+	}
+}
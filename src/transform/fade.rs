@@ -103,6 +103,9 @@ impl Transform for FadeOut {
 	}
 }
 
+// mixes the tail of one stream into the head of the next. the previous stream's
+// frames go through `feed_previous`, which keeps only the last `duration`
+// worth of samples; after `start_crossfade` the next frames are blended with it.
 pub struct Crossfade {
 	duration_samples: usize,
 	current_sample: usize,
@@ -126,23 +129,47 @@ impl Crossfade {
 
 	pub fn start_crossfade(&mut self) {
 		self.in_crossfade = true;
+		self.current_sample = 0;
+		// a tail shorter than the overlap sits at the end of the buffer, so the
+		// blend starts from silence
+		let filled = self.buffer_pos.min(self.crossfade_buffer.len());
+		self.crossfade_buffer.rotate_left(filled);
+		let silence = self.crossfade_buffer.len() - filled;
+		self.crossfade_buffer[..silence].fill(0);
 		self.buffer_pos = 0;
 	}
 
 	pub fn feed_previous(&mut self, frame: &Frame) {
 		if let Some(audio_frame) = frame.audio() {
-			let channels = audio_frame.channels as usize;
+			let capacity = self.crossfade_buffer.len();
 			let samples: Vec<i16> =
 				audio_frame.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+			let tail = &samples[samples.len().saturating_sub(capacity)..];
 
-			let start_sample = samples.len().saturating_sub(self.duration_samples * channels);
-			for (i, &sample) in samples[start_sample..].iter().enumerate() {
-				if self.buffer_pos + i < self.crossfade_buffer.len() {
-					self.crossfade_buffer[self.buffer_pos + i] = sample;
+			// ring buffer; `buffer_pos` counts every sample fed so far
+			for &sample in tail {
+				if capacity > 0 {
+					self.crossfade_buffer[self.buffer_pos % capacity] = sample;
 				}
+				self.buffer_pos += 1;
+			}
+			if self.buffer_pos > capacity {
+				let start = self.buffer_pos % capacity.max(1);
+				self.crossfade_buffer.rotate_left(start);
+				self.buffer_pos = capacity;
 			}
 		}
 	}
+
+	// samples per channel still to be blended; a next stream shorter than the
+	// overlap can feed this much silence to flush the rest of the tail
+	pub fn remaining(&self) -> usize {
+		if self.in_crossfade { self.duration_samples - self.current_sample } else { 0 }
+	}
+
+	pub fn is_active(&self) -> bool {
+		self.in_crossfade
+	}
 }
 
 impl Transform for Crossfade {
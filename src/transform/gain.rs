@@ -14,15 +14,11 @@ impl Gain {
 impl Transform for Gain {
 	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
 		if let Some(audio_frame) = frame.audio_mut() {
-			let samples = audio_frame.data.len() / 2;
-			for i in 0..samples {
-				let offset = i * 2;
-				let sample = i16::from_le_bytes([audio_frame.data[offset], audio_frame.data[offset + 1]]);
-				let amplified = (sample as f32 * self.factor).clamp(-32768.0, 32767.0) as i16;
-				let bytes = amplified.to_le_bytes();
-				audio_frame.data[offset] = bytes[0];
-				audio_frame.data[offset + 1] = bytes[1];
+			let mut samples = audio_frame.samples_f32();
+			for sample in &mut samples {
+				*sample *= self.factor;
 			}
+			audio_frame.set_samples_f32(&samples);
 		}
 		Ok(frame)
 	}
@@ -30,4 +26,8 @@ impl Transform for Gain {
 	fn name(&self) -> &'static str {
 		"gain"
 	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
 }
@@ -0,0 +1,371 @@
+use super::{Overlay, TransformChain, TransformSpec};
+use crate::core::{Frame, FrameAudio, FrameData, FrameVideo, StreamFormat, Transform};
+use crate::io::{IoError, IoErrorKind, IoResult};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// pad every graph reads its frame from
+const INPUT: usize = 0;
+
+// hands a graph the next frame of another input. it sees the frame the graph
+// was given, so audio can be cut to the same length, and returns None once the
+// input runs out
+pub type FrameSource = Box<dyn FnMut(&Frame) -> IoResult<Option<Frame>> + Send>;
+
+// a further input, read through the pad named in1, in2, ...
+struct Source {
+	input: usize,
+	pad: usize,
+	feed: Option<(StreamFormat, FrameSource)>,
+}
+
+// the filter a graph statement runs. chains take one pad and give one; split
+// copies its input to every output; overlay composites its second input onto
+// its first; amix averages audio from all of its inputs
+enum Filter {
+	Chain(TransformChain),
+	Split,
+	Overlay { overlay: Overlay, top: Arc<Mutex<Option<FrameVideo>>> },
+	Amix,
+}
+
+struct Node {
+	inputs: Vec<usize>,
+	filter: Filter,
+	outputs: Vec<usize>,
+}
+
+// a small filter graph in the ffmpeg style, e.g.
+//
+//   [in]split[a][b];[a]scale=320x180[small];[b][small]overlay=16:16[out]
+//
+// statements are separated by ';'. each names its input pads, then either a
+// chain of `--apply` transforms or one of split, overlay=x:y[:alpha] and amix,
+// then its output pads. `in` is the frame handed to the graph and `out` the
+// frame it returns; a first statement without inputs reads `in` and a last one
+// without outputs writes `out`. every pad is written once and read once, so
+// one branch feeding two needs a split. statements run in dependency order,
+// once per frame.
+//
+// `in1`, `in2`, ... read further inputs handed over with `with_input`, e.g.
+// `[in][in1]amix` or `[in][in1]overlay=8:8`. once one of them runs out the
+// branches it feeds fall silent: amix mixes the rest and overlay keeps the
+// last picture it had.
+pub struct FilterGraph {
+	nodes: Vec<Node>,
+	pads: usize,
+	output: usize,
+	sources: Vec<Source>,
+}
+
+impl FilterGraph {
+	pub fn parse(graph: &str) -> IoResult<Self> {
+		let statements: Vec<&str> =
+			graph.split(';').map(str::trim).filter(|statement| !statement.is_empty()).collect();
+		if statements.is_empty() {
+			return Err(IoError::invalid_data("filter graph is empty"));
+		}
+
+		let mut labels: HashMap<String, usize> = HashMap::from([("in".to_string(), INPUT)]);
+		let mut pad = |label: String| {
+			let next = labels.len();
+			*labels.entry(label).or_insert(next)
+		};
+		let last = statements.len() - 1;
+		let mut nodes = Vec::with_capacity(statements.len());
+		for (index, statement) in statements.iter().enumerate() {
+			let (mut inputs, body, mut outputs) = split_statement(statement)?;
+			if index == 0 && inputs.is_empty() {
+				inputs.push("in".to_string());
+			}
+			if index == last && outputs.is_empty() {
+				outputs.push("out".to_string());
+			}
+			let filter = parse_filter(body, inputs.len(), outputs.len())?;
+			let inputs = inputs.into_iter().map(&mut pad).collect();
+			let outputs = outputs.into_iter().map(&mut pad).collect();
+			nodes.push(Node { inputs, filter, outputs });
+		}
+		let output = pad("out".to_string());
+		let pads = labels.len();
+
+		let mut sources: Vec<Source> = labels
+			.iter()
+			.filter_map(|(label, &pad)| {
+				let input: usize = label.strip_prefix("in")?.parse().ok().filter(|&input| input > 0)?;
+				if *label != format!("in{}", input) {
+					return None;
+				}
+				Some(Source { input, pad, feed: None })
+			})
+			.collect();
+		sources.sort_by_key(|source| source.input);
+		let fed: Vec<usize> = sources.iter().map(|source| source.pad).collect();
+		let nodes = order_nodes(nodes, pads, output, &fed)?;
+		Ok(Self { nodes, pads, output, sources })
+	}
+
+	// feeds `[inN]` from `source`, whose frames have `format`
+	pub fn with_input(mut self, input: usize, format: StreamFormat, source: FrameSource) -> Self {
+		if let Some(slot) = self.sources.iter_mut().find(|slot| slot.input == input) {
+			slot.feed = Some((format, source));
+		}
+		self
+	}
+
+	// the further inputs the graph reads, as the N of each `[inN]`
+	pub fn inputs(&self) -> Vec<usize> {
+		self.sources.iter().map(|source| source.input).collect()
+	}
+}
+
+impl Transform for FilterGraph {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let mut pads: Vec<Option<Frame>> = vec![None; self.pads];
+		for source in &mut self.sources {
+			let (_, next) = source.feed.as_mut().ok_or_else(missing_input)?;
+			pads[source.pad] = next(&frame)?;
+		}
+		pads[INPUT] = Some(frame);
+		// a pad left empty traces back to an input that ran out
+		for node in &mut self.nodes {
+			let inputs: Vec<Option<Frame>> = node.inputs.iter().map(|&pad| pads[pad].take()).collect();
+			if inputs[0].is_none() {
+				continue;
+			}
+			let outputs = node.filter.run(inputs, node.outputs.len())?;
+			for (&pad, frame) in node.outputs.iter().zip(outputs) {
+				pads[pad] = Some(frame);
+			}
+		}
+		pads[self.output].take().ok_or_else(missing_frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"graph"
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		for node in &mut self.nodes {
+			if let Filter::Chain(chain) = &mut node.filter {
+				chain.finish()?;
+			}
+		}
+		Ok(())
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let mut pads: Vec<Option<StreamFormat>> = vec![None; self.pads];
+		pads[INPUT] = Some(input);
+		for source in &self.sources {
+			let (format, _) = source.feed.as_ref().ok_or_else(missing_input)?;
+			pads[source.pad] = Some(*format);
+		}
+		for node in &self.nodes {
+			let mut inputs = Vec::with_capacity(node.inputs.len());
+			for &pad in &node.inputs {
+				inputs.push(pads[pad].take().ok_or_else(missing_frame)?);
+			}
+			let output = match &node.filter {
+				Filter::Chain(chain) => chain.negotiate(inputs[0])?,
+				Filter::Split | Filter::Overlay { .. } => inputs[0],
+				Filter::Amix => {
+					let mut audio =
+						inputs.iter().filter(|format| matches!(format, StreamFormat::Audio { .. }));
+					let first = audio.next();
+					if audio.any(|format| Some(format) != first) {
+						return Err(IoError::invalid_data(
+							"amix inputs need the same channels and sample rate",
+						));
+					}
+					inputs[0]
+				}
+			};
+			node.outputs.iter().for_each(|&pad| pads[pad] = Some(output));
+		}
+		pads[self.output].take().ok_or_else(missing_frame)
+	}
+}
+
+impl Filter {
+	// the first input is always there; later ones are None once their input ran out
+	fn run(&mut self, inputs: Vec<Option<Frame>>, outputs: usize) -> IoResult<Vec<Frame>> {
+		let mut inputs = inputs.into_iter();
+		let first = inputs.next().flatten().ok_or_else(missing_frame)?;
+		match self {
+			Filter::Chain(chain) => Ok(vec![chain.apply(first)?]),
+			Filter::Split => Ok(vec![first; outputs]),
+			Filter::Overlay { overlay, top } => {
+				if let Some(upper) = inputs.next().flatten()
+					&& let (Some(video), Ok(mut slot)) = (upper.video(), top.lock())
+				{
+					*slot = Some(video.clone());
+				}
+				Ok(vec![overlay.apply(first)?])
+			}
+			Filter::Amix => Ok(vec![amix(std::iter::once(first).chain(inputs.flatten()).collect())?]),
+		}
+	}
+}
+
+fn missing_frame() -> IoError {
+	IoError::invalid_data("filter graph pad has no frame")
+}
+
+fn missing_input() -> IoError {
+	IoError::invalid_data("filter graph reads an [inN] input that was not given")
+}
+
+// leading and trailing [label]s around the filter text
+fn split_statement(statement: &str) -> IoResult<(Vec<String>, &str, Vec<String>)> {
+	let invalid = || {
+		IoError::with_message(
+			IoErrorKind::InvalidData,
+			"filter graph statements look like [in]scale=320x180[small]",
+		)
+	};
+	let mut rest = statement;
+	let mut inputs = Vec::new();
+	while let Some(after) = rest.strip_prefix('[') {
+		let (label, tail) = after.split_once(']').ok_or_else(invalid)?;
+		inputs.push(check_label(label)?);
+		rest = tail.trim_start();
+	}
+	let mut outputs = Vec::new();
+	while let Some(before) = rest.strip_suffix(']') {
+		let (head, label) = before.rsplit_once('[').ok_or_else(invalid)?;
+		outputs.insert(0, check_label(label)?);
+		rest = head.trim_end();
+	}
+	if rest.is_empty() {
+		return Err(invalid());
+	}
+	Ok((inputs, rest, outputs))
+}
+
+fn check_label(label: &str) -> IoResult<String> {
+	if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+		return Err(IoError::invalid_data("filter graph pad names are letters, digits and _"));
+	}
+	Ok(label.to_string())
+}
+
+fn parse_filter(body: &str, inputs: usize, outputs: usize) -> IoResult<Filter> {
+	let (name, value) = body.split_once('=').unwrap_or((body, ""));
+	match name {
+		"split" => {
+			let count = if value.is_empty() { outputs } else { value.parse().unwrap_or(0) };
+			if inputs != 1 || count == 0 || count != outputs {
+				return Err(IoError::invalid_data("split takes one pad and gives as many as it names"));
+			}
+			Ok(Filter::Split)
+		}
+		"overlay" => {
+			if inputs != 2 || outputs != 1 {
+				return Err(IoError::invalid_data("overlay takes two pads, the base then the top"));
+			}
+			let invalid = || {
+				IoError::with_message(
+					IoErrorKind::InvalidData,
+					"overlay takes x:y[:alpha] (e.g., overlay=16:16 or overlay=0:0:0.5)",
+				)
+			};
+			let fields: Vec<&str> = value.split(':').filter(|field| !field.is_empty()).collect();
+			let (x, y, alpha) = match fields.as_slice() {
+				[] => (0, 0, 1.0),
+				[x, y] => (x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?, 1.0),
+				[x, y, alpha] => (
+					x.parse().map_err(|_| invalid())?,
+					y.parse().map_err(|_| invalid())?,
+					alpha.parse().map_err(|_| invalid())?,
+				),
+				_ => return Err(invalid()),
+			};
+			let top = Arc::new(Mutex::new(None));
+			let source = top.clone();
+			let overlay = Overlay::new(move || Ok(source.lock().ok().and_then(|mut top| top.take())))
+				.with_position(x, y)
+				.with_alpha(alpha);
+			Ok(Filter::Overlay { overlay, top })
+		}
+		"amix" => {
+			if inputs < 2 || outputs != 1 || !value.is_empty() {
+				return Err(IoError::invalid_data("amix takes two or more pads and gives one"));
+			}
+			Ok(Filter::Amix)
+		}
+		_ => {
+			if inputs != 1 || outputs != 1 {
+				return Err(IoError::invalid_data("a transform chain takes one pad and gives one"));
+			}
+			Ok(Filter::Chain(TransformChain::from_specs(TransformSpec::parse_list(body)?)?))
+		}
+	}
+}
+
+// dependency order, checking that every pad has one writer and one reader
+fn order_nodes(nodes: Vec<Node>, pads: usize, output: usize, fed: &[usize]) -> IoResult<Vec<Node>> {
+	let mut writers = vec![0usize; pads];
+	let mut readers = vec![0usize; pads];
+	writers[INPUT] = 1;
+	fed.iter().for_each(|&pad| writers[pad] += 1);
+	readers[output] = 1;
+	for node in &nodes {
+		node.inputs.iter().for_each(|&pad| readers[pad] += 1);
+		node.outputs.iter().for_each(|&pad| writers[pad] += 1);
+	}
+	if writers.iter().chain(&readers).any(|&count| count > 1) {
+		return Err(IoError::invalid_data("filter graph pads are written and read once; use split"));
+	}
+	if writers.iter().zip(&readers).any(|(&written, &read)| written != read) {
+		return Err(IoError::invalid_data("filter graph has a pad without a source or a reader"));
+	}
+
+	let mut ready = vec![false; pads];
+	ready[INPUT] = true;
+	fed.iter().for_each(|&pad| ready[pad] = true);
+	let mut pending: Vec<Node> = nodes;
+	let mut ordered = Vec::with_capacity(pending.len());
+	while !pending.is_empty() {
+		let Some(index) = pending.iter().position(|node| node.inputs.iter().all(|&pad| ready[pad]))
+		else {
+			return Err(IoError::invalid_data("filter graph has a cycle"));
+		};
+		let node = pending.remove(index);
+		node.outputs.iter().for_each(|&pad| ready[pad] = true);
+		ordered.push(node);
+	}
+	Ok(ordered)
+}
+
+// averages the inputs sample by sample, padding shorter ones with silence.
+// video inputs are not mixed; the first one passes through
+fn amix(inputs: Vec<Frame>) -> IoResult<Frame> {
+	let mut inputs = inputs.into_iter();
+	let Some(base) = inputs.next() else {
+		return Err(missing_frame());
+	};
+	let Some(audio) = base.audio() else {
+		return Ok(base);
+	};
+	let (sample_rate, channels, layout) = (audio.sample_rate, audio.channels, audio.layout);
+	let mut mixed = audio.samples_f32();
+	let mut count = 1;
+	for frame in inputs {
+		let Some(other) = frame.audio() else {
+			continue;
+		};
+		if other.channels != channels || other.sample_rate != sample_rate {
+			return Err(IoError::invalid_data("amix inputs need the same channels and sample rate"));
+		}
+		let samples = other.samples_f32();
+		if samples.len() > mixed.len() {
+			mixed.resize(samples.len(), 0.0);
+		}
+		mixed.iter_mut().zip(samples).for_each(|(mix, sample)| *mix += sample);
+		count += 1;
+	}
+	mixed.iter_mut().for_each(|mix| *mix /= count as f32);
+	let audio = FrameAudio::from_f32(&mixed, sample_rate, channels).with_layout(layout);
+	Ok(base.with_data(FrameData::Audio(audio)))
+}
@@ -88,23 +88,14 @@ impl Transform for Highpass {
 				self.states = (0..audio_frame.channels as usize).map(|_| BiquadState::default()).collect();
 			}
 
-			let channels = audio_frame.channels as usize;
-			let samples_per_channel = audio_frame.nb_samples;
-
-			for i in 0..samples_per_channel {
-				for ch in 0..channels {
-					let offset = (i * channels + ch) * 2;
-					let sample = i16::from_le_bytes([audio_frame.data[offset], audio_frame.data[offset + 1]]);
-					let sample_f = sample as f32 / 32768.0;
-
-					let processed = self.process_sample(sample_f, ch);
-					let output = (processed * 32767.0).clamp(-32768.0, 32767.0) as i16;
-
-					let bytes = output.to_le_bytes();
-					audio_frame.data[offset] = bytes[0];
-					audio_frame.data[offset + 1] = bytes[1];
+			let channels = audio_frame.channels.max(1) as usize;
+			let mut samples = audio_frame.samples_f32();
+			for sample_frame in samples.chunks_exact_mut(channels) {
+				for (ch, sample) in sample_frame.iter_mut().enumerate() {
+					*sample = self.process_sample(*sample, ch);
 				}
 			}
+			audio_frame.set_samples_f32(&samples);
 		}
 
 		Ok(frame)
@@ -113,4 +104,8 @@ impl Transform for Highpass {
 	fn name(&self) -> &'static str {
 		"highpass"
 	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
 }
@@ -0,0 +1,33 @@
+use crate::core::{Frame, Transform};
+use crate::io::IoResult;
+
+// flips polarity; -32768 has no positive counterpart and becomes 32767
+pub struct Invert;
+
+impl Invert {
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+impl Default for Invert {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for Invert {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(audio_frame) = frame.audio_mut() {
+			for bytes in audio_frame.data.chunks_exact_mut(2) {
+				let sample = i16::from_le_bytes([bytes[0], bytes[1]]);
+				bytes.copy_from_slice(&sample.saturating_neg().to_le_bytes());
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"invert"
+	}
+}
@@ -0,0 +1,83 @@
+use super::{Highpass, Lowpass};
+use crate::core::{Frame, FrameAudio, Timebase, Transform};
+use crate::io::IoResult;
+
+// removes whatever is mixed dead center, usually the lead vocal, by taking
+// the left channel minus the right. both outputs carry that difference.
+// kick, bass and cymbals tend to sit in the center too, so `with_protection`
+// adds the mid signal back below `low` Hz and above `high` Hz.
+pub struct Karaoke {
+	protection: Option<(Lowpass, Highpass)>,
+}
+
+impl Karaoke {
+	pub fn new() -> Self {
+		Self { protection: None }
+	}
+
+	pub fn with_protection(mut self, low: f32, high: f32) -> Self {
+		self.protection = Some((Lowpass::new(low), Highpass::new(high)));
+		self
+	}
+
+	// the mid signal outside the vocal band, one value per sample frame
+	fn protected(&mut self, mid: &[i16], sample_rate: u32) -> IoResult<Vec<i32>> {
+		let Some((lowpass, highpass)) = &mut self.protection else {
+			return Ok(vec![0; mid.len()]);
+		};
+
+		let data: Vec<u8> = mid.iter().flat_map(|s| s.to_le_bytes()).collect();
+		let frame = |data: Vec<u8>| {
+			let audio = FrameAudio::new(data, sample_rate, 1).with_nb_samples(mid.len());
+			Frame::new_audio(audio, Timebase::new(1, sample_rate.max(1)), 0)
+		};
+		let low = lowpass.apply(frame(data.clone()))?;
+		let high = highpass.apply(frame(data))?;
+
+		let samples = |frame: &Frame| -> Vec<i32> {
+			let data = frame.audio().map(|a| a.data.as_slice()).unwrap_or(&[]);
+			data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]]) as i32).collect()
+		};
+		Ok(samples(&low).iter().zip(samples(&high)).map(|(l, h)| l + h).collect())
+	}
+}
+
+impl Default for Karaoke {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for Karaoke {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(audio_frame) = frame.audio() else {
+			return Ok(frame);
+		};
+		if audio_frame.channels != 2 {
+			return Ok(frame);
+		}
+
+		let sample_rate = audio_frame.sample_rate;
+		let pairs: Vec<(i32, i32)> = audio_frame
+			.data
+			.chunks_exact(4)
+			.map(|c| (i16::from_le_bytes([c[0], c[1]]) as i32, i16::from_le_bytes([c[2], c[3]]) as i32))
+			.collect();
+		let mid: Vec<i16> = pairs.iter().map(|&(l, r)| ((l + r) / 2) as i16).collect();
+		let protected = self.protected(&mid, sample_rate)?;
+
+		if let Some(audio_frame) = frame.audio_mut() {
+			for ((pair, (l, r)), keep) in audio_frame.data.chunks_exact_mut(4).zip(pairs).zip(protected) {
+				let output = (l - r + keep).clamp(-32768, 32767) as i16;
+				pair[..2].copy_from_slice(&output.to_le_bytes());
+				pair[2..].copy_from_slice(&output.to_le_bytes());
+			}
+		}
+
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"karaoke"
+	}
+}
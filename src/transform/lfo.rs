@@ -0,0 +1,51 @@
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfoShape {
+	Sine,
+	Triangle,
+}
+
+// low frequency oscillator shared by the modulation effects. the phase is kept
+// in cycles (0..1) so changing the sample rate between frames does not jump.
+#[derive(Debug, Clone)]
+pub struct Lfo {
+	rate_hz: f32,
+	shape: LfoShape,
+	phase: f32,
+}
+
+impl Lfo {
+	pub fn new(rate_hz: f32) -> Self {
+		Self { rate_hz, shape: LfoShape::Sine, phase: 0.0 }
+	}
+
+	pub fn with_shape(mut self, shape: LfoShape) -> Self {
+		self.shape = shape;
+		self
+	}
+
+	pub fn with_phase(mut self, phase: f32) -> Self {
+		self.phase = phase.rem_euclid(1.0);
+		self
+	}
+
+	pub fn value(&self) -> f32 {
+		match self.shape {
+			LfoShape::Sine => (2.0 * PI * self.phase).sin(),
+			LfoShape::Triangle => 1.0 - 4.0 * ((self.phase + 0.25).rem_euclid(1.0) - 0.5).abs(),
+		}
+	}
+
+	// current value in -1..1, then advance one sample
+	pub fn next(&mut self, sample_rate: u32) -> f32 {
+		let value = self.value();
+		self.phase = (self.phase + self.rate_hz / sample_rate.max(1) as f32).rem_euclid(1.0);
+		value
+	}
+
+	// same as `next` but mapped to 0..1
+	pub fn next_unipolar(&mut self, sample_rate: u32) -> f32 {
+		(self.next(sample_rate) + 1.0) * 0.5
+	}
+}
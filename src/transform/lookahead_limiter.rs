@@ -0,0 +1,261 @@
+use super::loudness::{TAPS_PER_PHASE, oversample_filter};
+use crate::core::{ChannelLayout, Frame, FrameAudio, SampleFormat, Timebase, Transform};
+use crate::io::IoResult;
+use std::collections::VecDeque;
+
+// the oversampled points computed at input frame n lie between frames n - 6
+// and n - 5, so with true peaks on the audio waits this long to line up
+const TRUE_PEAK_ALIGN: usize = TAPS_PER_PHASE / 2;
+
+// brickwall limiter that delays the audio by `lookahead` so the gain is
+// already down when a peak comes out. the gain for each sample is the
+// smallest gain any sample in the next `lookahead` needs, released slowly
+// and then averaged over the same window, so it ramps down ahead of a peak
+// and reaches it before the peak leaves the delay line. channels share one
+// gain to keep the stereo image. sample peaks never exceed the ceiling;
+// inter-sample peaks are only held under it with `with_true_peak`, which
+// finds them by 4x oversampling as the loudness meter does.
+pub struct LookaheadLimiter {
+	ceiling_db: f32,
+	lookahead_ms: f32,
+	release_ms: f32,
+	// oversampling phases, when limiting true peaks
+	phases: Option<Vec<[f64; TAPS_PER_PHASE]>>,
+	history: Vec<[f64; TAPS_PER_PHASE]>,
+	// frames waiting for the oversampled peaks around them
+	align: VecDeque<f32>,
+	last_true_peak: f64,
+	ceiling: f64,
+	sample_rate: u32,
+	channels: usize,
+	// lookahead in sample frames
+	window: usize,
+	release_coeff: f64,
+	delay: VecDeque<f32>,
+	// (sample frame index, gain) with increasing gains, front is the minimum
+	minimum: VecDeque<(u64, f64)>,
+	released: f64,
+	smoothing: VecDeque<f64>,
+	smoothing_sum: f64,
+	index: u64,
+	// where the next output frame starts, so the delay does not shift timing
+	next_pts: Option<i64>,
+	timebase: Timebase,
+	stream_index: usize,
+	layout: ChannelLayout,
+	format: SampleFormat,
+}
+
+impl LookaheadLimiter {
+	pub fn new(ceiling_db: f32) -> Self {
+		Self {
+			ceiling_db,
+			lookahead_ms: 5.0,
+			release_ms: 50.0,
+			phases: None,
+			history: Vec::new(),
+			align: VecDeque::new(),
+			last_true_peak: 0.0,
+			ceiling: 0.0,
+			sample_rate: 0,
+			channels: 0,
+			window: 0,
+			release_coeff: 0.0,
+			delay: VecDeque::new(),
+			minimum: VecDeque::new(),
+			released: 1.0,
+			smoothing: VecDeque::new(),
+			smoothing_sum: 0.0,
+			index: 0,
+			next_pts: None,
+			timebase: Timebase::new(1, 1),
+			stream_index: 0,
+			layout: ChannelLayout::Mono,
+			format: SampleFormat::S16,
+		}
+	}
+
+	pub fn with_lookahead(mut self, lookahead_ms: f32) -> Self {
+		self.lookahead_ms = lookahead_ms;
+		self
+	}
+
+	pub fn with_release(mut self, release_ms: f32) -> Self {
+		self.release_ms = release_ms;
+		self
+	}
+
+	pub fn with_true_peak(mut self) -> Self {
+		self.phases = Some(oversample_filter());
+		self
+	}
+
+	// the output trails the input by this many sample frames until `flush`
+	pub fn latency(&self) -> usize {
+		(self.align.len() + self.delay.len()) / self.channels.max(1)
+	}
+
+	fn configure(&mut self, sample_rate: u32, channels: usize) {
+		self.sample_rate = sample_rate;
+		self.channels = channels;
+		// a whole number of s16 steps, so rounding to s16 cannot land above it
+		self.ceiling = (32767.0 * 10f64.powf(self.ceiling_db as f64 / 20.0)).floor() / 32768.0;
+		let rate = sample_rate.max(1) as f64;
+		self.window = ((self.lookahead_ms as f64 * rate / 1000.0).round() as usize).max(1);
+		let release_samples = (self.release_ms as f64 * rate / 1000.0).max(1.0);
+		self.release_coeff = (-1.0 / release_samples).exp();
+		self.history = vec![[0.0; TAPS_PER_PHASE]; channels];
+		self.align.clear();
+		self.last_true_peak = 0.0;
+		self.delay.clear();
+		self.minimum.clear();
+		self.released = 1.0;
+		self.smoothing = VecDeque::from(vec![1.0; self.window + 1]);
+		self.smoothing_sum = (self.window + 1) as f64;
+	}
+
+	// takes the gain the newest input frame needs and returns the gain for
+	// the frame leaving the delay line
+	fn advance(&mut self, needed: f64) -> f64 {
+		let index = self.index;
+		self.index += 1;
+
+		while self.minimum.back().is_some_and(|&(_, gain)| gain >= needed) {
+			self.minimum.pop_back();
+		}
+		self.minimum.push_back((index, needed));
+		while self.minimum.front().is_some_and(|&(at, _)| at + (self.window as u64) < index) {
+			self.minimum.pop_front();
+		}
+		let target = self.minimum.front().map(|&(_, gain)| gain).unwrap_or(1.0);
+
+		self.released = if target < self.released {
+			target
+		} else {
+			target - (target - self.released) * self.release_coeff
+		};
+
+		self.smoothing.push_back(self.released);
+		self.smoothing_sum += self.released;
+		if let Some(oldest) = self.smoothing.pop_front() {
+			self.smoothing_sum -= oldest;
+		}
+		self.smoothing_sum / self.smoothing.len() as f64
+	}
+
+	// one input sample frame in, and one limited frame out once the delay is full
+	fn push(&mut self, sample_frame: &[f32], output: &mut Vec<f32>) {
+		let Some(phases) = &self.phases else {
+			self.limit(sample_frame, sample_peak(sample_frame), output);
+			return;
+		};
+
+		let mut true_peak = 0.0f64;
+		for (history, &sample) in self.history.iter_mut().zip(sample_frame) {
+			history.rotate_right(1);
+			history[0] = sample as f64;
+			for taps in phases {
+				let value: f64 = taps.iter().zip(history.iter()).map(|(c, x)| c * x).sum();
+				true_peak = true_peak.max(value.abs());
+			}
+		}
+		let previous = std::mem::replace(&mut self.last_true_peak, true_peak);
+
+		self.align.extend(sample_frame);
+		if self.align.len() > TRUE_PEAK_ALIGN * self.channels {
+			// the points on either side of the frame leaving
+			let aligned: Vec<f32> = self.align.drain(..self.channels).collect();
+			let peak = sample_peak(&aligned).max(previous).max(true_peak);
+			self.limit(&aligned, peak, output);
+		}
+	}
+
+	fn limit(&mut self, sample_frame: &[f32], peak: f64, output: &mut Vec<f32>) {
+		self.delay.extend(sample_frame);
+		let needed = if peak > self.ceiling { self.ceiling / peak } else { 1.0 };
+		let gain = self.advance(needed);
+		if self.delay.len() > self.window * self.channels {
+			self.emit(gain, output);
+		}
+	}
+
+	fn emit(&mut self, gain: f64, output: &mut Vec<f32>) {
+		for sample in self.delay.drain(..self.channels) {
+			let limited = (sample as f64 * gain).clamp(-self.ceiling, self.ceiling);
+			output.push(limited as f32);
+		}
+	}
+
+	fn output_frame(&mut self, samples: Vec<f32>) -> Frame {
+		let mut audio = FrameAudio::from_f32(&samples, self.sample_rate, self.channels as u8)
+			.with_layout(self.layout);
+		audio.convert(self.format);
+		let pts = self.next_pts.unwrap_or(0);
+		self.next_pts = Some(pts + audio.nb_samples as i64);
+		Frame::new_audio(audio, self.timebase, self.stream_index).with_pts(pts)
+	}
+}
+
+impl Transform for LookaheadLimiter {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some(audio) = frame.audio() else {
+			return Ok(frame);
+		};
+
+		let channels = audio.channels.max(1) as usize;
+		if self.sample_rate != audio.sample_rate || self.channels != channels {
+			self.configure(audio.sample_rate, channels);
+		}
+		self.next_pts.get_or_insert(frame.pts);
+		self.timebase = frame.timebase;
+		self.stream_index = frame.stream_index;
+		self.layout = audio.layout;
+		self.format = audio.format;
+
+		let samples = audio.samples_f32();
+		let mut output = Vec::with_capacity(samples.len());
+		for sample_frame in samples.chunks_exact(channels) {
+			self.push(sample_frame, &mut output);
+		}
+
+		Ok(self.output_frame(output))
+	}
+
+	fn name(&self) -> &'static str {
+		"lookahead_limiter"
+	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
+
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		if self.latency() == 0 {
+			return Ok(None);
+		}
+
+		// silence after the end needs no reduction; feeding it moves the last
+		// frames out of the delay line with the gains they were promised
+		let mut output = Vec::with_capacity(self.latency() * self.channels);
+		if !self.align.is_empty() {
+			let silence = vec![0.0; self.channels];
+			for _ in 0..TRUE_PEAK_ALIGN {
+				self.push(&silence, &mut output);
+			}
+			self.align.clear();
+		}
+		let pending = self.delay.len() / self.channels;
+		for step in 0..self.window {
+			let gain = self.advance(1.0);
+			if step >= self.window - pending {
+				self.emit(gain, &mut output);
+			}
+		}
+
+		Ok(Some(self.output_frame(output)))
+	}
+}
+
+fn sample_peak(sample_frame: &[f32]) -> f64 {
+	sample_frame.iter().fold(0.0f64, |peak, &sample| peak.max((sample as f64).abs()))
+}
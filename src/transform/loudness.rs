@@ -0,0 +1,397 @@
+use super::LookaheadLimiter;
+use crate::core::{ChannelLayout, Frame, FrameAudio, SampleFormat, Transform};
+use crate::io::IoResult;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+// EBU Tech 3342 loudness range: 3s windows, gated 20 LU below their mean
+const RANGE_GATE: f64 = -20.0;
+const SHORT_TERM_STEPS: usize = 30;
+const BLOCK_STEPS: usize = 4;
+// gating histogram: 0.01 LU bins from the absolute gate up to +10 LUFS
+const BIN_WIDTH: f64 = 0.01;
+const HISTOGRAM_TOP: f64 = 10.0;
+const OVERSAMPLE: usize = 4;
+pub(crate) const TAPS_PER_PHASE: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+	// gated integrated loudness in LUFS, -inf for silence
+	pub integrated: f64,
+	// 4x oversampled peak in dBTP
+	pub true_peak: f64,
+}
+
+// ReplayGain 2.0 reference loudness
+const REPLAYGAIN_REFERENCE: f64 = -18.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+	// gain in dB that brings the track to -18 LUFS
+	pub track_gain: f64,
+	// true peak as a linear amplitude, 1.0 being full scale
+	pub track_peak: f64,
+}
+
+impl ReplayGain {
+	pub fn from_loudness(loudness: Loudness) -> Self {
+		let track_gain = if loudness.integrated.is_finite() {
+			REPLAYGAIN_REFERENCE - loudness.integrated
+		} else {
+			0.0
+		};
+		Self { track_gain, track_peak: 10f64.powf(loudness.true_peak / 20.0) }
+	}
+
+	// tag names and values as players expect them, e.g. "-3.21 dB" and "0.988312"
+	pub fn tags(&self) -> [(&'static str, String); 2] {
+		[
+			("REPLAYGAIN_TRACK_GAIN", format!("{:.2} dB", self.track_gain)),
+			("REPLAYGAIN_TRACK_PEAK", format!("{:.6}", self.track_peak)),
+		]
+	}
+}
+
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+	b: [f64; 3],
+	a: [f64; 2],
+	x: [f64; 2],
+	y: [f64; 2],
+}
+
+impl Biquad {
+	fn process(&mut self, input: f64) -> f64 {
+		let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+			- self.a[0] * self.y[0]
+			- self.a[1] * self.y[1];
+		self.x = [input, self.x[0]];
+		self.y = [output, self.y[0]];
+		output
+	}
+}
+
+// the two BS.1770 pre-filters (head shelf, then the RLB high-pass) derived for
+// any sample rate
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+	let rate = sample_rate as f64;
+
+	let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+	let k = (PI * f0 / rate).tan();
+	let vh = 10f64.powf(gain_db / 20.0);
+	let vb = vh.powf(0.4996667741545416);
+	let a0 = 1.0 + k / q + k * k;
+	let shelf = Biquad {
+		b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+		a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+		..Biquad::default()
+	};
+
+	let (f0, q) = (38.13547087602444, 0.5003270373238773);
+	let k = (PI * f0 / rate).tan();
+	let a0 = 1.0 + k / q + k * k;
+	let highpass = Biquad {
+		b: [1.0, -2.0, 1.0],
+		a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+		..Biquad::default()
+	};
+
+	[shelf, highpass]
+}
+
+// lfe is left out and the surrounds count 1.41x, as BS.1770 specifies
+fn channel_weights(layout: ChannelLayout) -> Vec<f64> {
+	match layout {
+		ChannelLayout::Surround51 => vec![1.0, 1.0, 1.0, 0.0, 1.41, 1.41],
+		other => vec![1.0; other.channels() as usize],
+	}
+}
+
+// windowed sinc split into OVERSAMPLE phases, each normalized to unity gain.
+// phase p of the output at input sample n falls at n - 5.875 + p / 4
+pub(crate) fn oversample_filter() -> Vec<[f64; TAPS_PER_PHASE]> {
+	let len = OVERSAMPLE * TAPS_PER_PHASE;
+	let center = (len - 1) as f64 / 2.0;
+	let mut phases = vec![[0.0; TAPS_PER_PHASE]; OVERSAMPLE];
+	for (phase, taps) in phases.iter_mut().enumerate() {
+		for (tap, coeff) in taps.iter_mut().enumerate() {
+			let i = tap * OVERSAMPLE + phase;
+			let x = (i as f64 - center) / OVERSAMPLE as f64;
+			let sinc = if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+			let window = 0.42 - 0.5 * (2.0 * PI * i as f64 / (len - 1) as f64).cos()
+				+ 0.08 * (4.0 * PI * i as f64 / (len - 1) as f64).cos();
+			*coeff = sinc * window;
+		}
+		let sum: f64 = taps.iter().sum();
+		taps.iter_mut().for_each(|c| *c /= sum);
+	}
+	phases
+}
+
+fn loudness(energy: f64) -> f64 {
+	-0.691 + 10.0 * energy.log10()
+}
+
+// blocks above the absolute gate by loudness, each bin keeping how many fell
+// in it and their summed energy, so an hour of audio takes no more memory than
+// a second. gated means are exact but for blocks in the bin the gate lands in
+struct Histogram {
+	counts: Vec<u64>,
+	energy: Vec<f64>,
+}
+
+impl Histogram {
+	fn new() -> Self {
+		let bins = ((HISTOGRAM_TOP - ABSOLUTE_GATE) / BIN_WIDTH).round() as usize;
+		Self { counts: vec![0; bins], energy: vec![0.0; bins] }
+	}
+
+	fn add(&mut self, energy: f64) {
+		let level = loudness(energy);
+		if level > ABSOLUTE_GATE {
+			let bin = (((level - ABSOLUTE_GATE) / BIN_WIDTH) as usize).min(self.counts.len() - 1);
+			self.counts[bin] += 1;
+			self.energy[bin] += energy;
+		}
+	}
+
+	// the first bin wholly above `gate`
+	fn first_above(&self, gate: f64) -> usize {
+		(((gate - ABSOLUTE_GATE) / BIN_WIDTH).ceil().max(0.0) as usize).min(self.counts.len())
+	}
+
+	// mean energy of the blocks louder than `gate`, None when there are none
+	fn mean_above(&self, gate: f64) -> Option<f64> {
+		let from = self.first_above(gate);
+		let count: u64 = self.counts[from..].iter().sum();
+		(count > 0).then(|| self.energy[from..].iter().sum::<f64>() / count as f64)
+	}
+
+	// loudness of the block at `p` (0 to 1) of those louder than `gate`, in
+	// increasing order
+	fn percentile_above(&self, gate: f64, p: f64) -> Option<f64> {
+		let from = self.first_above(gate);
+		let count: u64 = self.counts[from..].iter().sum();
+		if count == 0 {
+			return None;
+		}
+		let rank = ((count - 1) as f64 * p).round() as u64;
+		let mut seen = 0;
+		for (bin, &n) in self.counts.iter().enumerate().skip(from) {
+			seen += n;
+			if seen > rank {
+				return Some(ABSOLUTE_GATE + (bin as f64 + 0.5) * BIN_WIDTH);
+			}
+		}
+		None
+	}
+}
+
+struct ChannelState {
+	filters: [Biquad; 2],
+	weight: f64,
+	// filtered energy of the current 100ms step
+	energy: f64,
+	history: [f64; TAPS_PER_PHASE],
+}
+
+// integrated loudness and true peak as in EBU R128 / ITU-R BS.1770: 400ms
+// blocks every 100ms, gated at -70 LUFS and then 10 LU below the ungated mean.
+// only the last 3s of steps are kept; blocks and short-term windows go into
+// histograms. usable directly or as a pass-through transform.
+pub struct LoudnessMeter {
+	sample_rate: u32,
+	channels: Vec<ChannelState>,
+	phases: Vec<[f64; TAPS_PER_PHASE]>,
+	step_len: usize,
+	step_pos: usize,
+	steps: VecDeque<f64>,
+	blocks: Histogram,
+	short_term: Histogram,
+	peak: f64,
+}
+
+impl LoudnessMeter {
+	pub fn new() -> Self {
+		Self {
+			sample_rate: 0,
+			channels: Vec::new(),
+			phases: oversample_filter(),
+			step_len: 0,
+			step_pos: 0,
+			steps: VecDeque::with_capacity(SHORT_TERM_STEPS),
+			blocks: Histogram::new(),
+			short_term: Histogram::new(),
+			peak: 0.0,
+		}
+	}
+
+	fn configure(&mut self, audio: &FrameAudio) {
+		self.sample_rate = audio.sample_rate;
+		self.step_len = (audio.sample_rate as usize / 10).max(1);
+		self.channels = channel_weights(audio.layout)
+			.into_iter()
+			.map(|weight| ChannelState {
+				filters: k_weighting(audio.sample_rate),
+				weight,
+				energy: 0.0,
+				history: [0.0; TAPS_PER_PHASE],
+			})
+			.collect();
+	}
+
+	pub fn push(&mut self, audio: &FrameAudio) {
+		let channels = audio.channels.max(1) as usize;
+		if self.sample_rate != audio.sample_rate || self.channels.len() != channels {
+			self.configure(audio);
+		}
+
+		let samples: Vec<f64> = audio.samples_f32().into_iter().map(f64::from).collect();
+		for sample_frame in samples.chunks_exact(channels) {
+			for (state, &sample) in self.channels.iter_mut().zip(sample_frame) {
+				let filtered = state.filters.iter_mut().fold(sample, |x, f| f.process(x));
+				state.energy += filtered * filtered;
+
+				state.history.rotate_right(1);
+				state.history[0] = sample;
+				for taps in &self.phases {
+					let value: f64 = taps.iter().zip(&state.history).map(|(c, x)| c * x).sum();
+					self.peak = self.peak.max(value.abs());
+				}
+				self.peak = self.peak.max(sample.abs());
+			}
+
+			self.step_pos += 1;
+			if self.step_pos == self.step_len {
+				self.end_step();
+			}
+		}
+	}
+
+	fn end_step(&mut self) {
+		let len = self.step_len as f64;
+		let energy: f64 = self.channels.iter().map(|c| c.weight * c.energy / len).sum();
+		self.channels.iter_mut().for_each(|c| c.energy = 0.0);
+		self.step_pos = 0;
+
+		if self.steps.len() == SHORT_TERM_STEPS {
+			self.steps.pop_front();
+		}
+		self.steps.push_back(energy);
+		if self.steps.len() >= BLOCK_STEPS {
+			let block = self.steps.iter().rev().take(BLOCK_STEPS).sum::<f64>() / BLOCK_STEPS as f64;
+			self.blocks.add(block);
+		}
+		if self.steps.len() == SHORT_TERM_STEPS {
+			self.short_term.add(self.steps.iter().sum::<f64>() / SHORT_TERM_STEPS as f64);
+		}
+	}
+
+	pub fn loudness(&self) -> Loudness {
+		let integrated = self
+			.blocks
+			.mean_above(ABSOLUTE_GATE)
+			.and_then(|mean| self.blocks.mean_above(loudness(mean) + RELATIVE_GATE))
+			.map_or(f64::NEG_INFINITY, loudness);
+
+		Loudness { integrated, true_peak: 20.0 * self.peak.log10() }
+	}
+
+	// loudness range in LU: the spread between the 10th and 95th percentile
+	// of the 3s short-term loudness, taken every 100ms. 0 for anything
+	// shorter than one window or silent throughout
+	pub fn loudness_range(&self) -> f64 {
+		let Some(mean) = self.short_term.mean_above(ABSOLUTE_GATE) else {
+			return 0.0;
+		};
+		let threshold = loudness(mean) + RANGE_GATE;
+		let percentile = |p: f64| self.short_term.percentile_above(threshold, p).unwrap_or(0.0);
+		percentile(0.95) - percentile(0.10)
+	}
+}
+
+impl Default for LoudnessMeter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for LoudnessMeter {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		if let Some(audio_frame) = frame.audio() {
+			self.push(audio_frame);
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"loudness_meter"
+	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
+}
+
+// second pass of a two-pass loudness normalization: one static gain taking the
+// measured loudness to the target, followed by a lookahead limiter holding
+// true peaks under the ceiling. the gain is applied in f32 so nothing clips
+// before the limiter gets to it
+pub struct Loudnorm {
+	gain: f64,
+	limiter: LookaheadLimiter,
+	// what frames arrive as, and leave as again
+	format: SampleFormat,
+}
+
+impl Loudnorm {
+	pub fn new(target_lufs: f64, true_peak_db: f64, measured: Loudness) -> Self {
+		let gain_db =
+			if measured.integrated.is_finite() { target_lufs - measured.integrated } else { 0.0 };
+		Self {
+			gain: 10f64.powf(gain_db / 20.0),
+			limiter: LookaheadLimiter::new(true_peak_db as f32).with_true_peak().with_release(100.0),
+			format: SampleFormat::S16,
+		}
+	}
+
+	pub fn gain_db(&self) -> f64 {
+		20.0 * self.gain.log10()
+	}
+
+	fn restore(&self, mut frame: Frame) -> Frame {
+		if let Some(audio) = frame.audio_mut() {
+			audio.convert(self.format);
+		}
+		frame
+	}
+}
+
+impl Transform for Loudnorm {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(audio) = frame.audio_mut() else {
+			return Ok(frame);
+		};
+		self.format = audio.format;
+		let samples: Vec<f32> =
+			audio.samples_f32().iter().map(|&sample| (sample as f64 * self.gain) as f32).collect();
+		*audio =
+			FrameAudio::from_f32(&samples, audio.sample_rate, audio.channels).with_layout(audio.layout);
+
+		self.limiter.apply(frame).map(|frame| self.restore(frame))
+	}
+
+	fn name(&self) -> &'static str {
+		"loudnorm"
+	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
+
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		Ok(self.limiter.flush()?.map(|frame| self.restore(frame)))
+	}
+}
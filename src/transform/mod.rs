@@ -1,148 +1,84 @@
+pub mod adelay;
+pub mod astats;
 pub mod channel_mixer;
+pub mod dc_remove;
 pub mod eq;
+pub mod expr;
 pub mod fade;
 pub mod gain;
+pub mod graph;
 pub mod highpass;
+pub mod invert;
+pub mod karaoke;
+pub mod lfo;
+pub mod lookahead_limiter;
+pub mod loudness;
 pub mod lowpass;
+pub mod modulation;
 pub mod normalize;
+pub mod pan;
 pub mod peak_limiter;
+pub mod registry;
 pub mod resample;
 pub mod rms_limiter;
+pub mod silence_detect;
+pub mod spec;
+pub mod speed;
+pub mod stream;
 pub mod video;
 pub mod volume;
 
+pub use adelay::Adelay;
+pub use astats::{AStats, ChannelStats};
 pub use channel_mixer::{ChannelLayout, ChannelMixer};
+pub use dc_remove::DcRemove;
 pub use eq::{EqBand, Equalizer, FilterType};
+pub use expr::{Evaluated, Expr, SpecTemplate, Variables};
 pub use fade::{Crossfade, FadeIn, FadeOut};
 pub use gain::Gain;
+pub use graph::{FilterGraph, FrameSource};
 pub use highpass::Highpass;
+pub use invert::Invert;
+pub use karaoke::Karaoke;
+pub use lfo::{Lfo, LfoShape};
+pub use lookahead_limiter::LookaheadLimiter;
+pub use loudness::{Loudness, LoudnessMeter, Loudnorm, ReplayGain};
 pub use lowpass::Lowpass;
+pub use modulation::{Chorus, Flanger, Tremolo, Vibrato};
 pub use normalize::Normalize;
+pub use pan::{Balance, Pan};
 pub use peak_limiter::PeakLimiter;
 pub use resample::Resample;
 pub use rms_limiter::RmsLimiter;
+pub use silence_detect::{SilenceDetect, SilenceRange};
+pub use spec::TransformSpec;
+pub use speed::Speed;
+pub use stream::StreamTarget;
 pub use video::{
-	Blur, Brightness, Contrast, Crop, Flip, FlipDirection, FrameRateConverter, Pad, Rotate,
-	RotateAngle, Scale, ScaleMode,
+	Blur, Brightness, ColorConvert, ColorMatrix, Contrast, Crop, CropDetect, DrawBox, DrawText, Flip,
+	FlipDirection, FrameRateConverter, GaussianBlur, Grayscale, Lut, Lut3D, MotionEstimator, Negate,
+	Overlay, Pad, Pixelate, Rotate, RotateAngle, Saturation, Scale, ScaleFit, ScaleMode, Sharpen,
+	Stabilize, Tblend, VideoFade, Vignette,
 };
 pub use volume::Volume;
 
-use crate::core::Transform;
-use crate::io::{IoError, IoErrorKind, IoResult};
+use crate::core::{Frame, SampleFormat, StreamFormat, Transform};
+use crate::io::{AllowAll, IoResult, PathPolicy};
 
 pub fn parse_transform(spec: &str) -> IoResult<Box<dyn Transform>> {
-	let parts: Vec<&str> = spec.splitn(2, '=').collect();
-	let name = parts[0];
-
-	match name {
-		"volume" => {
-			let factor = parts
-				.get(1)
-				.ok_or_else(|| {
-					IoError::with_message(
-						IoErrorKind::InvalidData,
-						"volume requires a value (e.g., volume=2.0)",
-					)
-				})?
-				.parse::<f32>()
-				.map_err(|_| {
-					IoError::with_message(IoErrorKind::InvalidData, "volume value must be a number")
-				})?;
-			Ok(Box::new(Volume::new(factor)))
-		}
-		"normalize" => {
-			let peak = parts.get(1).map(|v| v.parse::<f32>().unwrap_or(0.95)).unwrap_or(0.95);
-			Ok(Box::new(Normalize::new(peak)))
-		}
-		"highpass" => {
-			let cutoff = parts
-				.get(1)
-				.ok_or_else(|| {
-					IoError::with_message(
-						IoErrorKind::InvalidData,
-						"highpass requires cutoff frequency (e.g., highpass=200)",
-					)
-				})?
-				.parse::<f32>()
-				.map_err(|_| {
-					IoError::with_message(IoErrorKind::InvalidData, "highpass cutoff must be a number")
-				})?;
-			Ok(Box::new(Highpass::new(cutoff)))
-		}
-		"lowpass" => {
-			let cutoff = parts
-				.get(1)
-				.ok_or_else(|| {
-					IoError::with_message(
-						IoErrorKind::InvalidData,
-						"lowpass requires cutoff frequency (e.g., lowpass=5000)",
-					)
-				})?
-				.parse::<f32>()
-				.map_err(|_| {
-					IoError::with_message(IoErrorKind::InvalidData, "lowpass cutoff must be a number")
-				})?;
-			Ok(Box::new(Lowpass::new(cutoff)))
-		}
-		"fadein" => {
-			let duration_ms = parts
-				.get(1)
-				.ok_or_else(|| {
-					IoError::with_message(
-						IoErrorKind::InvalidData,
-						"fadein requires duration in ms (e.g., fadein=1000)",
-					)
-				})?
-				.parse::<f32>()
-				.map_err(|_| {
-					IoError::with_message(IoErrorKind::InvalidData, "fadein duration must be a number")
-				})?;
-			Ok(Box::new(FadeIn::new(duration_ms, 44100)))
-		}
-		"resample" => {
-			let rate = parts
-				.get(1)
-				.ok_or_else(|| {
-					IoError::with_message(
-						IoErrorKind::InvalidData,
-						"resample requires target rate (e.g., resample=48000)",
-					)
-				})?
-				.parse::<u32>()
-				.map_err(|_| {
-					IoError::with_message(IoErrorKind::InvalidData, "resample rate must be a number")
-				})?;
-			Ok(Box::new(Resample::new(rate)))
-		}
-		"mono" => Ok(Box::new(ChannelMixer::stereo_to_mono())),
-		"stereo" => Ok(Box::new(ChannelMixer::mono_to_stereo())),
-		"eq3" => {
-			let params = parts.get(1).unwrap_or(&"0,0,0");
-			let values: Vec<f32> = params.split(',').filter_map(|v| v.parse::<f32>().ok()).collect();
-			let bass = values.first().copied().unwrap_or(0.0);
-			let mid = values.get(1).copied().unwrap_or(0.0);
-			let treble = values.get(2).copied().unwrap_or(0.0);
-			Ok(Box::new(Equalizer::three_band(bass, mid, treble)))
-		}
-		"peak_limiter" | "limiter" => {
-			let threshold_db = parts.get(1).map(|v| v.parse::<f32>().unwrap_or(-1.0)).unwrap_or(-1.0);
-			Ok(Box::new(PeakLimiter::new(threshold_db)))
-		}
-		"rms_limiter" => {
-			let threshold_db = parts.get(1).map(|v| v.parse::<f32>().unwrap_or(-10.0)).unwrap_or(-10.0);
-			Ok(Box::new(RmsLimiter::new(threshold_db, 50.0, 44100)))
-		}
-		_ => Err(IoError::with_message(IoErrorKind::InvalidData, "unknown transform")),
-	}
+	TransformSpec::parse(spec)?.build()
 }
 
 pub struct TransformChain {
 	transforms: Vec<Box<dyn Transform>>,
+	// false for the leading parts of a split chain, which hand their audio on
+	// as is so the part after them sees exactly what the whole chain would
+	quantize: bool,
 }
 
 impl TransformChain {
 	pub fn new() -> Self {
-		Self { transforms: Vec::new() }
+		Self { transforms: Vec::new(), quantize: true }
 	}
 
 	pub fn add(&mut self, transform: Box<dyn Transform>) {
@@ -152,6 +88,51 @@ impl TransformChain {
 	pub fn is_empty(&self) -> bool {
 		self.transforms.is_empty()
 	}
+
+	pub fn from_specs(specs: impl IntoIterator<Item = TransformSpec>) -> IoResult<Self> {
+		Self::from_specs_with(specs, &AllowAll)
+	}
+
+	pub fn from_specs_with(
+		specs: impl IntoIterator<Item = TransformSpec>,
+		policy: &dyn PathPolicy,
+	) -> IoResult<Self> {
+		let mut chain = Self::new();
+		for spec in specs {
+			chain.add(spec.build_with(policy)?);
+		}
+		Ok(chain)
+	}
+
+	// hands audio on as is, for a chain followed by something that quantizes
+	// anyway: the rest of a longer chain, or an encoder
+	pub fn unquantized(mut self) -> Self {
+		self.quantize = false;
+		self
+	}
+
+	// cuts the chain into at most `parts` consecutive chains of about the same
+	// length, to run one after another on separate threads. only the last one
+	// quantizes audio on the way out
+	pub fn split(self, parts: usize) -> Vec<TransformChain> {
+		let parts = parts.clamp(1, self.transforms.len().max(1));
+		let (size, extra) = (self.transforms.len() / parts, self.transforms.len() % parts);
+		let mut transforms = self.transforms.into_iter();
+		let mut chains: Vec<TransformChain> = (0..parts)
+			.map(|part| TransformChain {
+				transforms: transforms.by_ref().take(size + usize::from(part < extra)).collect(),
+				quantize: false,
+			})
+			.collect();
+		if let Some(last) = chains.last_mut() {
+			last.quantize = self.quantize;
+		}
+		chains
+	}
+
+	fn quantized(&self, frame: Frame) -> Frame {
+		if self.quantize { convert(frame, SampleFormat::S16) } else { frame }
+	}
 }
 
 impl Default for TransformChain {
@@ -160,15 +141,76 @@ impl Default for TransformChain {
 	}
 }
 
+// audio stays in f32 across consecutive transforms that accept it and is
+// only quantized to s16 in front of one that does not, or on the way out
 impl Transform for TransformChain {
-	fn apply(&mut self, mut frame: crate::core::Frame) -> IoResult<crate::core::Frame> {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
 		for transform in &mut self.transforms {
-			frame = transform.apply(frame)?;
+			frame = apply_keeping_metadata(transform.as_mut(), frame)?;
 		}
-		Ok(frame)
+		Ok(self.quantized(frame))
 	}
 
 	fn name(&self) -> &'static str {
 		"chain"
 	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		self.transforms.iter().try_fold(input, |format, transform| transform.negotiate(format))
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		for transform in &mut self.transforms {
+			transform.finish()?;
+		}
+		Ok(())
+	}
+
+	// a transform's leftovers still run through everything after it
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		let mut tail: Option<Frame> = None;
+		for transform in &mut self.transforms {
+			if let Some(frame) = tail.take() {
+				tail = Some(apply_keeping_metadata(transform.as_mut(), frame)?);
+			}
+			if let Some(flushed) = transform.flush()? {
+				tail = Some(match tail {
+					Some(frame) => append_audio(frame, flushed),
+					None => flushed,
+				});
+			}
+		}
+		Ok(tail.map(|frame| self.quantized(frame)))
+	}
+}
+
+// side data survives transforms that build a fresh frame for their output
+fn apply_keeping_metadata(transform: &mut dyn Transform, frame: Frame) -> IoResult<Frame> {
+	let metadata = (!frame.metadata.is_empty()).then(|| frame.metadata.clone());
+	let mut frame = transform.apply(convert_for(transform, frame))?;
+	if let Some(metadata) = metadata {
+		frame.metadata.inherit(metadata);
+	}
+	Ok(frame)
+}
+
+fn convert_for(transform: &dyn Transform, frame: Frame) -> Frame {
+	let format = if transform.supports_f32() { SampleFormat::F32 } else { SampleFormat::S16 };
+	convert(frame, format)
+}
+
+fn convert(mut frame: Frame, format: SampleFormat) -> Frame {
+	if let Some(audio) = frame.audio_mut() {
+		audio.convert(format);
+	}
+	frame
+}
+
+fn append_audio(mut frame: Frame, mut next: Frame) -> Frame {
+	if let (Some(audio), Some(next)) = (frame.audio_mut(), next.audio_mut()) {
+		next.convert(audio.format);
+		audio.data.extend_from_slice(&next.data);
+		audio.nb_samples += next.nb_samples;
+	}
+	frame
 }
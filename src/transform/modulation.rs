@@ -0,0 +1,223 @@
+use super::lfo::Lfo;
+use crate::core::{Frame, Transform};
+use crate::io::IoResult;
+
+// fractional delay line, one per channel
+struct DelayLine {
+	buffer: Vec<f32>,
+	pos: usize,
+}
+
+impl DelayLine {
+	fn new(len: usize) -> Self {
+		Self { buffer: vec![0.0; len.max(2)], pos: 0 }
+	}
+
+	fn push(&mut self, sample: f32) {
+		self.buffer[self.pos] = sample;
+		self.pos = (self.pos + 1) % self.buffer.len();
+	}
+
+	// `delay` samples behind the newest pushed sample, linearly interpolated
+	fn read(&self, delay: f32) -> f32 {
+		let len = self.buffer.len();
+		let delay = delay.clamp(0.0, (len - 2) as f32);
+		let whole = delay as usize;
+		let frac = delay - whole as f32;
+		let newest = (self.pos + len - 1) % len;
+		let a = self.buffer[(newest + len - whole) % len];
+		let b = self.buffer[(newest + len - whole - 1) % len];
+		a + (b - a) * frac
+	}
+}
+
+// runs `process(lfo, line, sample) -> sample` over every sample with one lfo
+// step per sample frame, rebuilding the delay lines when the layout changes
+fn modulate<F>(
+	frame: &mut Frame,
+	lfo: &mut Lfo,
+	lines: &mut Vec<DelayLine>,
+	max_delay_ms: f32,
+	mut process: F,
+) where
+	F: FnMut(f32, &mut DelayLine, f32, u32) -> f32,
+{
+	let Some(audio) = frame.audio_mut() else {
+		return;
+	};
+	let channels = audio.channels.max(1) as usize;
+	let sample_rate = audio.sample_rate;
+	let delay_len = (max_delay_ms * sample_rate as f32 / 1000.0).ceil() as usize + 2;
+	if lines.len() != channels || lines[0].buffer.len() != delay_len {
+		*lines = (0..channels).map(|_| DelayLine::new(delay_len)).collect();
+	}
+
+	for sample_frame in audio.data.chunks_exact_mut(channels * 2) {
+		let lfo_value = lfo.next_unipolar(sample_rate);
+		for (ch, bytes) in sample_frame.chunks_exact_mut(2).enumerate() {
+			let input = i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0;
+			let output = process(lfo_value, &mut lines[ch], input, sample_rate);
+			let output = (output * 32768.0).round().clamp(-32768.0, 32767.0) as i16;
+			bytes.copy_from_slice(&output.to_le_bytes());
+		}
+	}
+}
+
+fn ms_to_samples(ms: f32, sample_rate: u32) -> f32 {
+	ms * sample_rate as f32 / 1000.0
+}
+
+pub struct Tremolo {
+	lfo: Lfo,
+	depth: f32,
+}
+
+impl Tremolo {
+	pub fn new(rate_hz: f32, depth: f32) -> Self {
+		Self { lfo: Lfo::new(rate_hz), depth: depth.clamp(0.0, 1.0) }
+	}
+}
+
+impl Transform for Tremolo {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(audio) = frame.audio_mut() {
+			let channels = audio.channels.max(1) as usize;
+			for sample_frame in audio.data.chunks_exact_mut(channels * 2) {
+				let gain = 1.0 - self.depth * self.lfo.next_unipolar(audio.sample_rate);
+				for bytes in sample_frame.chunks_exact_mut(2) {
+					let sample = i16::from_le_bytes([bytes[0], bytes[1]]) as f32 * gain;
+					bytes.copy_from_slice(&(sample.round() as i16).to_le_bytes());
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"tremolo"
+	}
+}
+
+// pitch wobble: only the modulated delay is heard
+pub struct Vibrato {
+	lfo: Lfo,
+	depth_ms: f32,
+	lines: Vec<DelayLine>,
+}
+
+impl Vibrato {
+	pub fn new(rate_hz: f32, depth_ms: f32) -> Self {
+		Self { lfo: Lfo::new(rate_hz), depth_ms: depth_ms.max(0.0), lines: Vec::new() }
+	}
+}
+
+impl Transform for Vibrato {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let depth_ms = self.depth_ms;
+		modulate(&mut frame, &mut self.lfo, &mut self.lines, depth_ms, |lfo, line, x, rate| {
+			line.push(x);
+			line.read(ms_to_samples(depth_ms * lfo, rate))
+		});
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"vibrato"
+	}
+}
+
+// dry signal plus a copy delayed by `delay_ms` and swept by `depth_ms`
+pub struct Chorus {
+	lfo: Lfo,
+	delay_ms: f32,
+	depth_ms: f32,
+	mix: f32,
+	lines: Vec<DelayLine>,
+}
+
+impl Chorus {
+	pub fn new(rate_hz: f32, depth_ms: f32, mix: f32) -> Self {
+		Self {
+			lfo: Lfo::new(rate_hz),
+			delay_ms: 20.0,
+			depth_ms: depth_ms.max(0.0),
+			mix: mix.clamp(0.0, 1.0),
+			lines: Vec::new(),
+		}
+	}
+
+	pub fn with_delay(mut self, delay_ms: f32) -> Self {
+		self.delay_ms = delay_ms.max(0.0);
+		self
+	}
+}
+
+impl Default for Chorus {
+	fn default() -> Self {
+		Self::new(1.5, 3.0, 0.5)
+	}
+}
+
+impl Transform for Chorus {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let (delay_ms, depth_ms, mix) = (self.delay_ms, self.depth_ms, self.mix);
+		let max_delay = delay_ms + depth_ms;
+		modulate(&mut frame, &mut self.lfo, &mut self.lines, max_delay, |lfo, line, x, rate| {
+			line.push(x);
+			let wet = line.read(ms_to_samples(delay_ms + depth_ms * lfo, rate));
+			x * (1.0 - mix) + wet * mix
+		});
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"chorus"
+	}
+}
+
+// a chorus with a very short delay whose output is fed back into the line
+pub struct Flanger {
+	lfo: Lfo,
+	delay_ms: f32,
+	depth_ms: f32,
+	feedback: f32,
+	mix: f32,
+	lines: Vec<DelayLine>,
+}
+
+impl Flanger {
+	pub fn new(rate_hz: f32, depth_ms: f32, feedback: f32, mix: f32) -> Self {
+		Self {
+			lfo: Lfo::new(rate_hz),
+			delay_ms: 1.0,
+			depth_ms: depth_ms.max(0.0),
+			feedback: feedback.clamp(-0.95, 0.95),
+			mix: mix.clamp(0.0, 1.0),
+			lines: Vec::new(),
+		}
+	}
+}
+
+impl Default for Flanger {
+	fn default() -> Self {
+		Self::new(0.5, 2.0, 0.5, 0.5)
+	}
+}
+
+impl Transform for Flanger {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let (delay_ms, depth_ms, feedback, mix) =
+			(self.delay_ms, self.depth_ms, self.feedback, self.mix);
+		let max_delay = delay_ms + depth_ms;
+		modulate(&mut frame, &mut self.lfo, &mut self.lines, max_delay, |lfo, line, x, rate| {
+			let wet = line.read(ms_to_samples(delay_ms + depth_ms * lfo, rate));
+			line.push(x + wet * feedback);
+			x * (1.0 - mix) + wet * mix
+		});
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"flanger"
+	}
+}
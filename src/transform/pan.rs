@@ -0,0 +1,100 @@
+use crate::core::{ChannelLayout, Frame, Transform};
+use crate::io::IoResult;
+use std::f32::consts::FRAC_PI_4;
+
+fn read_samples(data: &[u8]) -> Vec<i16> {
+	data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+fn to_sample(value: f32) -> i16 {
+	value.round().clamp(-32768.0, 32767.0) as i16
+}
+
+// places a mono source in the stereo field with constant power, so the
+// perceived loudness does not dip in the middle. -1 is hard left, 1 hard
+// right. stereo input is folded to mono first.
+pub struct Pan {
+	position: f32,
+}
+
+impl Pan {
+	pub fn new(position: f32) -> Self {
+		Self { position: position.clamp(-1.0, 1.0) }
+	}
+
+	pub fn gains(&self) -> (f32, f32) {
+		let angle = (self.position + 1.0) * FRAC_PI_4;
+		(angle.cos(), angle.sin())
+	}
+}
+
+impl Transform for Pan {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(audio_frame) = frame.audio_mut() {
+			let channels = audio_frame.channels as usize;
+			if channels != 1 && channels != 2 {
+				return Ok(frame);
+			}
+
+			let samples = read_samples(&audio_frame.data);
+			let (left, right) = self.gains();
+			let mut output = Vec::with_capacity(samples.len() / channels * 4);
+			for sample_frame in samples.chunks_exact(channels) {
+				let mono = sample_frame.iter().map(|&s| s as f32).sum::<f32>() / channels as f32;
+				output.extend_from_slice(&to_sample(mono * left).to_le_bytes());
+				output.extend_from_slice(&to_sample(mono * right).to_le_bytes());
+			}
+
+			audio_frame.nb_samples = output.len() / 4;
+			audio_frame.data = output;
+			audio_frame.channels = 2;
+			audio_frame.layout = ChannelLayout::Stereo;
+		}
+
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"pan"
+	}
+}
+
+// tilts an existing stereo image: positive values turn the left channel down,
+// negative values the right. the louder side is never boosted.
+pub struct Balance {
+	balance: f32,
+}
+
+impl Balance {
+	pub fn new(balance: f32) -> Self {
+		Self { balance: balance.clamp(-1.0, 1.0) }
+	}
+
+	pub fn gains(&self) -> (f32, f32) {
+		if self.balance >= 0.0 { (1.0 - self.balance, 1.0) } else { (1.0, 1.0 + self.balance) }
+	}
+}
+
+impl Transform for Balance {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(audio_frame) = frame.audio_mut() {
+			if audio_frame.channels != 2 {
+				return Ok(frame);
+			}
+
+			let (left, right) = self.gains();
+			for pair in audio_frame.data.chunks_exact_mut(4) {
+				let l = i16::from_le_bytes([pair[0], pair[1]]) as f32 * left;
+				let r = i16::from_le_bytes([pair[2], pair[3]]) as f32 * right;
+				pair[..2].copy_from_slice(&to_sample(l).to_le_bytes());
+				pair[2..].copy_from_slice(&to_sample(r).to_le_bytes());
+			}
+		}
+
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"balance"
+	}
+}
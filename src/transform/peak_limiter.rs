@@ -23,13 +23,10 @@ impl PeakLimiter {
 impl Transform for PeakLimiter {
 	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
 		if let Some(audio_frame) = frame.audio_mut() {
-			let samples = audio_frame.data.len() / 2;
-
-			for i in 0..samples {
-				let offset = i * 2;
-				let sample = i16::from_le_bytes([audio_frame.data[offset], audio_frame.data[offset + 1]]);
-				let sample_f = sample as f32 / 32768.0;
+			let mut samples = audio_frame.samples_f32();
 
+			for sample in &mut samples {
+				let sample_f = *sample;
 				let peak = sample_f.abs();
 				let target_gain = if peak > self.threshold { self.threshold / peak } else { 1.0 };
 
@@ -40,11 +37,9 @@ impl Transform for PeakLimiter {
 						self.current_gain * self.release_coeff + target_gain * (1.0 - self.release_coeff);
 				}
 
-				let limited = (sample_f * self.current_gain * 32767.0).clamp(-32768.0, 32767.0) as i16;
-				let bytes = limited.to_le_bytes();
-				audio_frame.data[offset] = bytes[0];
-				audio_frame.data[offset + 1] = bytes[1];
+				*sample = sample_f * self.current_gain;
 			}
+			audio_frame.set_samples_f32(&samples);
 		}
 
 		Ok(frame)
@@ -53,4 +48,8 @@ impl Transform for PeakLimiter {
 	fn name(&self) -> &'static str {
 		"peak_limiter"
 	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
 }
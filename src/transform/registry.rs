@@ -0,0 +1,80 @@
+use super::TransformSpec;
+use crate::core::Transform;
+use crate::io::{IoError, IoErrorKind, IoResult};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+// builds a transform from the text after `name=`, or None for a bare name
+pub type TransformFactory = Arc<dyn Fn(Option<&str>) -> IoResult<Box<dyn Transform>> + Send + Sync>;
+
+static REGISTRY: RwLock<BTreeMap<String, TransformFactory>> = RwLock::new(BTreeMap::new());
+
+// makes `name` resolve in `parse_transform`, `TransformSpec::parse` and so
+// `--apply`, for transforms that live outside this crate:
+//
+//   registry::register("bitcrush", |args| {
+//       let bits = args.unwrap_or("8").parse().map_err(|_| IoError::invalid_data("bits"))?;
+//       Ok(Box::new(Bitcrush::new(bits)))
+//   })?;
+//
+// names start with a letter and hold letters, digits and _. built-in names
+// and names already taken are refused rather than shadowed
+pub fn register<F>(name: &str, factory: F) -> IoResult<()>
+where
+	F: Fn(Option<&str>) -> IoResult<Box<dyn Transform>> + Send + Sync + 'static,
+{
+	let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+		&& name != "json";
+	if !valid {
+		return Err(IoError::invalid_data("transform names are a letter then letters, digits and _"));
+	}
+	if is_builtin(name) {
+		return Err(IoError::with_message(IoErrorKind::AlreadyExists, "transform name is built in"));
+	}
+	let mut registry = REGISTRY.write().map_err(|_| poisoned())?;
+	if registry.contains_key(name) {
+		return Err(IoError::with_message(
+			IoErrorKind::AlreadyExists,
+			"transform name is already registered",
+		));
+	}
+	registry.insert(name.to_string(), Arc::new(factory));
+	Ok(())
+}
+
+// true when something was registered under `name`
+pub fn unregister(name: &str) -> bool {
+	REGISTRY.write().is_ok_and(|mut registry| registry.remove(name).is_some())
+}
+
+pub fn is_registered(name: &str) -> bool {
+	REGISTRY.read().is_ok_and(|registry| registry.contains_key(name))
+}
+
+// registered names in sorted order
+pub fn names() -> Vec<String> {
+	REGISTRY.read().map(|registry| registry.keys().cloned().collect()).unwrap_or_default()
+}
+
+pub fn build(name: &str, args: Option<&str>) -> IoResult<Box<dyn Transform>> {
+	// cloned out so the factory runs without holding the lock
+	let factory = REGISTRY.read().map_err(|_| poisoned())?.get(name).cloned();
+	match factory {
+		Some(factory) => factory(args),
+		None => Err(IoError::with_message(IoErrorKind::NotFound, "transform is not registered")),
+	}
+}
+
+// a name the spec parser knows fails with anything but "unknown transform",
+// even without parameters
+fn is_builtin(name: &str) -> bool {
+	match TransformSpec::parse(name) {
+		Ok(_) => true,
+		Err(e) => e.message() != Some(super::spec::UNKNOWN_TRANSFORM),
+	}
+}
+
+fn poisoned() -> IoError {
+	IoError::with_message(IoErrorKind::Other, "transform registry lock is poisoned")
+}
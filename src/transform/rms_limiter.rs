@@ -36,13 +36,11 @@ impl RmsLimiter {
 impl Transform for RmsLimiter {
 	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
 		if let Some(audio_frame) = frame.audio_mut() {
-			let samples = audio_frame.data.len() / 2;
+			let mut samples = audio_frame.samples_f32();
 			let threshold_linear = Self::db_to_linear(self.threshold_db);
 
-			for i in 0..samples {
-				let offset = i * 2;
-				let sample = i16::from_le_bytes([audio_frame.data[offset], audio_frame.data[offset + 1]]);
-				let sample_f = sample as f32 / 32768.0;
+			for sample in &mut samples {
+				let sample_f = *sample;
 
 				self.rms_buffer[self.buffer_pos] = sample_f * sample_f;
 				self.buffer_pos = (self.buffer_pos + 1) % self.window_samples;
@@ -57,11 +55,9 @@ impl Transform for RmsLimiter {
 						self.current_gain * self.release_coeff + target_gain * (1.0 - self.release_coeff);
 				}
 
-				let limited = (sample_f * self.current_gain * 32767.0).clamp(-32768.0, 32767.0) as i16;
-				let bytes = limited.to_le_bytes();
-				audio_frame.data[offset] = bytes[0];
-				audio_frame.data[offset + 1] = bytes[1];
+				*sample = sample_f * self.current_gain;
 			}
+			audio_frame.set_samples_f32(&samples);
 		}
 		Ok(frame)
 	}
@@ -69,4 +65,8 @@ impl Transform for RmsLimiter {
 	fn name(&self) -> &'static str {
 		"rms_limiter"
 	}
+
+	fn supports_f32(&self) -> bool {
+		true
+	}
 }
@@ -0,0 +1,142 @@
+use crate::core::{Frame, SideData, Transform};
+use crate::io::IoResult;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceRange {
+	pub start: f64,
+	pub end: f64,
+}
+
+impl SilenceRange {
+	pub fn duration(&self) -> f64 {
+		self.end - self.start
+	}
+}
+
+// passes audio through untouched and records every stretch that stays below
+// the noise floor for at least `min_duration` seconds. the ranges are printed
+// once the input ends.
+pub struct SilenceDetect {
+	threshold: f32,
+	min_duration: f64,
+	json: bool,
+	ranges: Vec<SilenceRange>,
+	silence_start: Option<f64>,
+	end: f64,
+}
+
+impl SilenceDetect {
+	pub fn new(noise_db: f32, min_duration: f64) -> Self {
+		Self {
+			threshold: 10f32.powf(noise_db / 20.0) * 32768.0,
+			min_duration: min_duration.max(0.0),
+			json: false,
+			ranges: Vec::new(),
+			silence_start: None,
+			end: 0.0,
+		}
+	}
+
+	pub fn with_json(mut self, json: bool) -> Self {
+		self.json = json;
+		self
+	}
+
+	// ranges found so far; a silence still running at the end of the input
+	// only shows up after `close`
+	pub fn ranges(&self) -> &[SilenceRange] {
+		&self.ranges
+	}
+
+	pub fn close(&mut self) -> &[SilenceRange] {
+		if let Some(start) = self.silence_start.take() {
+			self.push_range(start, self.end);
+		}
+		&self.ranges
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut out = String::new();
+		for range in &self.ranges {
+			out.push_str(&format!(
+				"silence_start: {:.3} silence_end: {:.3} silence_duration: {:.3}\n",
+				range.start,
+				range.end,
+				range.duration()
+			));
+		}
+		out
+	}
+
+	pub fn to_json(&self) -> String {
+		let ranges: Vec<String> = self
+			.ranges
+			.iter()
+			.map(|r| {
+				format!(
+					"{{\"start\":{:.3},\"end\":{:.3},\"duration\":{:.3}}}",
+					r.start,
+					r.end,
+					r.duration()
+				)
+			})
+			.collect();
+		format!("{{\"silence\":[{}]}}", ranges.join(","))
+	}
+
+	fn push_range(&mut self, start: f64, end: f64) {
+		if end > start && end - start >= self.min_duration {
+			self.ranges.push(SilenceRange { start, end });
+		}
+	}
+}
+
+impl Default for SilenceDetect {
+	fn default() -> Self {
+		Self::new(-60.0, 2.0)
+	}
+}
+
+impl Transform for SilenceDetect {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let frame_start = frame.pts as f64 * frame.timebase.num as f64 / frame.timebase.den as f64;
+		if let Some(audio_frame) = frame.audio() {
+			let channels = audio_frame.channels.max(1) as usize;
+			let rate = audio_frame.sample_rate.max(1) as f64;
+
+			let samples: Vec<i16> =
+				audio_frame.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+			for (idx, sample_frame) in samples.chunks_exact(channels).enumerate() {
+				let time = frame_start + idx as f64 / rate;
+				let peak = sample_frame.iter().map(|&s| (s as f32).abs()).fold(0.0, f32::max);
+				if peak < self.threshold {
+					self.silence_start.get_or_insert(time);
+				} else if let Some(start) = self.silence_start.take() {
+					self.push_range(start, time);
+				}
+			}
+			self.end = frame_start + (samples.len() / channels) as f64 / rate;
+		}
+
+		// a frame ending in silence carries the time it began
+		match self.silence_start {
+			Some(start) => Ok(frame.with_metadata("silence_start", SideData::Number(start))),
+			None => Ok(frame),
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		"silencedetect"
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		self.close();
+		// stderr, so the report never lands in media written to `-o -`
+		if self.json {
+			eprintln!("{}", self.to_json());
+		} else {
+			eprint!("{}", self.to_text());
+		}
+		Ok(())
+	}
+}
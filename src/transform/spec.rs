@@ -0,0 +1,1223 @@
+use super::expr::{Evaluated, SpecTemplate, Variables};
+use super::stream::StreamTarget;
+use super::video::drawbox::parse_color;
+use super::video::lut::{compose_tables, curve_table, gamma_table, lift_gain_table};
+use super::{
+	AStats, Adelay, Balance, Blur, Brightness, ChannelMixer, Chorus, ColorConvert, Contrast, Crop,
+	CropDetect, DcRemove, DrawBox, DrawText, Equalizer, FadeIn, Flanger, Flip, FlipDirection, Gain,
+	GaussianBlur, Grayscale, Highpass, Invert, Karaoke, LookaheadLimiter, Loudness, Loudnorm,
+	Lowpass, Lut, Lut3D, Negate, Normalize, Pad, Pan, PeakLimiter, Pixelate, Resample, RmsLimiter,
+	Rotate, RotateAngle, Saturation, Scale, ScaleFit, ScaleMode, Sharpen, SilenceDetect, Speed,
+	Stabilize, Tblend, Tremolo, Vibrato, VideoFade, Vignette, Volume,
+};
+use crate::core::{FrameKind, Transform, VideoFormat};
+use crate::io::{AllowAll, IoError, IoErrorKind, IoResult, PathPolicy};
+
+pub(crate) const UNKNOWN_TRANSFORM: &str = "unknown transform";
+
+// typed form of every transform the `--apply name=value` syntax knows about.
+// library code builds chains from these directly; the cli parses its strings
+// into them first.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformSpec {
+	Gain(f32),
+	Volume(f32),
+	Normalize {
+		peak: f32,
+	},
+	Highpass {
+		cutoff: f32,
+	},
+	Lowpass {
+		cutoff: f32,
+	},
+	DcRemove {
+		cutoff: f64,
+	},
+	Invert,
+	FadeIn {
+		duration_ms: f32,
+	},
+	// milliseconds per channel, in channel order
+	Adelay(Vec<f32>),
+	Resample {
+		rate: u32,
+	},
+	Speed(f64),
+	// the frame rate of y4m output, as frames per `den` seconds; frames are
+	// dropped or repeated, so it runs after the chain and has to come last
+	Fps {
+		num: u32,
+		den: u32,
+	},
+	Tremolo {
+		rate: f32,
+		depth: f32,
+	},
+	Vibrato {
+		rate: f32,
+		depth_ms: f32,
+	},
+	Chorus {
+		rate: f32,
+		depth_ms: f32,
+		mix: f32,
+	},
+	Flanger {
+		rate: f32,
+		depth_ms: f32,
+		feedback: f32,
+		mix: f32,
+	},
+	Pan(f32),
+	Balance(f32),
+	// `protect` keeps the center below and above these frequencies in Hz
+	Karaoke {
+		protect: Option<(f32, f32)>,
+	},
+	Mono,
+	Stereo,
+	Downmix,
+	ChannelMap(Vec<usize>),
+	Matrix(Vec<Vec<f32>>),
+	Eq3 {
+		bass: f32,
+		mid: f32,
+		treble: f32,
+	},
+	PeakLimiter {
+		threshold_db: f32,
+	},
+	LookaheadLimiter {
+		ceiling_db: f32,
+		lookahead_ms: f32,
+	},
+	RmsLimiter {
+		threshold_db: f32,
+	},
+	SilenceDetect {
+		noise_db: f32,
+		min_duration: f64,
+		json: bool,
+	},
+	AStats {
+		json: bool,
+	},
+	Contrast(f32),
+	Saturation(f32),
+	Grayscale,
+	Negate,
+	// gamma, then lift/gain, then the curve; `plane` 0..3 grades one plane
+	// instead of the master (luma, or every rgb channel)
+	Lut {
+		gamma: f32,
+		lift: f32,
+		gain: f32,
+		curve: Vec<(u8, u8)>,
+		plane: Option<usize>,
+	},
+	Vignette(f32),
+	// oldest frame first; the last weight is the current frame's
+	Tblend(Vec<f32>),
+	// frame counts; a fade out needs `total_frames`, which `Transcoder`
+	// counts itself when it is left empty
+	VideoFade {
+		fade_in: u64,
+		fade_out: u64,
+		total_frames: Option<u64>,
+	},
+	// per-frame shifts come from a first pass over the input, which
+	// `Transcoder` runs itself when `corrections` is left empty
+	Stabilize {
+		search: u32,
+		smoothing: usize,
+		corrections: Option<Vec<(i32, i32)>>,
+	},
+	// prints the crop that removes black borders; with `apply` the crop is
+	// made too, from `measured` (x, y, width, height), which `Transcoder`
+	// fills in with a first pass when it is left empty
+	CropDetect {
+		limit: u8,
+		round: u32,
+		window: usize,
+		apply: bool,
+		measured: Option<(u32, u32, u32, u32)>,
+	},
+	// geometry transforms take their source size from each frame, so a size
+	// change flows on to everything after them in the chain
+	Scale {
+		width: u32,
+		height: u32,
+		mode: ScaleMode,
+		fit: ScaleFit,
+	},
+	// `position` is the top-left x, y; None centres the region
+	Crop {
+		width: u32,
+		height: u32,
+		position: Option<(u32, u32)>,
+	},
+	// `position` places the frame inside the padded area; None centres it.
+	// `color` is y, u, v
+	Pad {
+		width: u32,
+		height: u32,
+		position: Option<(u32, u32)>,
+		color: [u8; 3],
+	},
+	Rotate(RotateAngle),
+	Flip(FlipDirection),
+	Blur(u32),
+	// added to luma as a fraction of full scale, -1 to 1
+	Brightness(f32),
+	// `region` is x, y, width, height; None covers the whole frame
+	Pixelate {
+		block: u32,
+		region: Option<(u32, u32, u32, u32)>,
+	},
+	// path of a .cube file, loaded when the chain is built
+	Lut3D(String),
+	// planar pixel format the video is converted to
+	Format(VideoFormat),
+	Sharpen {
+		amount: f32,
+		radius: u32,
+	},
+	GaussianBlur {
+		sigma: f32,
+	},
+	DrawText {
+		text: String,
+		x: u32,
+		y: u32,
+		color: u8,
+		scale: u32,
+	},
+	// `color` is y, u, v; a `thickness` of None fills the box
+	DrawBox {
+		x: u32,
+		y: u32,
+		width: u32,
+		height: u32,
+		color: [u8; 3],
+		thickness: Option<u32>,
+	},
+	// a box around the region side data named `key`, e.g. "drawbox=from=cropdetect"
+	DrawRegion {
+		key: String,
+		color: [u8; 3],
+		thickness: Option<u32>,
+	},
+	// `spec` only sees frames of one kind, and with `index` only the nth
+	// stream of that kind, e.g. "a:gain=2" or "v:0:scale=1280x720"
+	Stream {
+		kind: FrameKind,
+		index: Option<usize>,
+		spec: Box<TransformSpec>,
+	},
+	// a spec whose parameters read t, n, w or h, kept as written and
+	// evaluated for every frame, e.g. "gain=0.5+0.5*sin(2*PI*t)"
+	Expression(String),
+	// a transform added through `registry::register`, with the text after '='
+	Custom {
+		name: String,
+		args: Option<String>,
+	},
+	// `measured` comes from a first pass over the input; `Transcoder` runs
+	// that pass itself when it is left empty
+	Loudnorm {
+		target_lufs: f64,
+		true_peak: f64,
+		measured: Option<Loudness>,
+	},
+}
+
+impl TransformSpec {
+	// several specs joined by commas, e.g. "contrast=1.2,saturation=0.8". a comma
+	// only starts a new spec when a name follows it, so "adelay=0,20" stays whole;
+	// `json` is the one word that appears as a parameter (silencedetect=-50,2,json)
+	pub fn parse_list(specs: &str) -> IoResult<Vec<Self>> {
+		let mut parts: Vec<String> = Vec::new();
+		for piece in specs.split(',') {
+			let starts_name =
+				piece.trim_start().starts_with(|c: char| c.is_ascii_alphabetic()) && piece.trim() != "json";
+			// commas inside an expression's parentheses separate arguments
+			let open =
+				parts.last().is_some_and(|last| last.matches('(').count() > last.matches(')').count());
+			match parts.last_mut() {
+				Some(last) if !starts_name || open => {
+					last.push(',');
+					last.push_str(piece);
+				}
+				_ => parts.push(piece.to_string()),
+			}
+		}
+		parts.iter().map(|part| Self::parse(part.trim())).collect()
+	}
+
+	pub fn parse(spec: &str) -> IoResult<Self> {
+		if let Some((kind, index, rest)) = split_stream_prefix(spec)? {
+			let spec = Box::new(Self::parse(rest)?);
+			return Ok(TransformSpec::Stream { kind, index, spec });
+		}
+
+		// checked against a sample frame so mistakes surface before the run
+		let template = SpecTemplate::parse(spec);
+		if template.has_expressions() {
+			let sample = Variables { t: 0.0, n: 0.0, w: 1920.0, h: 1080.0 };
+			Self::parse(&template.render(&sample)?)?;
+			return Ok(TransformSpec::Expression(spec.to_string()));
+		}
+
+		let parts: Vec<&str> = spec.splitn(2, '=').collect();
+		let name = parts[0];
+
+		match name {
+			"gain" => {
+				let factor = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"gain requires a factor (e.g., gain=1.5)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "gain factor must be a number")
+					})?;
+				Ok(TransformSpec::Gain(factor))
+			}
+			"volume" => {
+				let factor = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"volume requires a value (e.g., volume=2.0)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "volume value must be a number")
+					})?;
+				Ok(TransformSpec::Volume(factor))
+			}
+			"normalize" => {
+				let peak = parts.get(1).map(|v| v.parse::<f32>().unwrap_or(0.95)).unwrap_or(0.95);
+				Ok(TransformSpec::Normalize { peak })
+			}
+			"highpass" => {
+				let cutoff = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"highpass requires cutoff frequency (e.g., highpass=200)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "highpass cutoff must be a number")
+					})?;
+				Ok(TransformSpec::Highpass { cutoff })
+			}
+			"lowpass" => {
+				let cutoff = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"lowpass requires cutoff frequency (e.g., lowpass=5000)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "lowpass cutoff must be a number")
+					})?;
+				Ok(TransformSpec::Lowpass { cutoff })
+			}
+			"dcremove" => {
+				let cutoff = match parts.get(1) {
+					Some(value) => value.parse::<f64>().map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "dcremove cutoff must be a number")
+					})?,
+					None => 5.0,
+				};
+				Ok(TransformSpec::DcRemove { cutoff })
+			}
+			"invert" => Ok(TransformSpec::Invert),
+			"fadein" => {
+				let duration_ms = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"fadein requires duration in ms (e.g., fadein=1000)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "fadein duration must be a number")
+					})?;
+				Ok(TransformSpec::FadeIn { duration_ms })
+			}
+			"resample" => {
+				let rate = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"resample requires target rate (e.g., resample=48000)",
+						)
+					})?
+					.parse::<u32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "resample rate must be a number")
+					})?;
+				Ok(TransformSpec::Resample { rate })
+			}
+			"speed" => {
+				let factor = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"speed requires a factor (e.g., speed=1.5)",
+						)
+					})?
+					.parse::<f64>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "speed factor must be a number")
+					})?;
+				Ok(TransformSpec::Speed(factor))
+			}
+			"fps" | "framerate" => {
+				let rate = parts.get(1).ok_or_else(|| {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"fps requires a rate (e.g., fps=24 or fps=30000/1001)",
+					)
+				})?;
+				let (num, den) = rate.split_once('/').unwrap_or((rate, "1"));
+				match (num.parse::<u32>(), den.parse::<u32>()) {
+					(Ok(num), Ok(den)) => Ok(TransformSpec::Fps { num, den }),
+					_ => Err(IoError::with_message(
+						IoErrorKind::InvalidData,
+						"fps rate must be a whole number or num/den",
+					)),
+				}
+			}
+			"tremolo" => {
+				let params = parse_params(parts.get(1))?;
+				let rate = params.first().copied().unwrap_or(5.0);
+				let depth = params.get(1).copied().unwrap_or(0.5);
+				Ok(TransformSpec::Tremolo { rate, depth })
+			}
+			"vibrato" => {
+				let params = parse_params(parts.get(1))?;
+				let rate = params.first().copied().unwrap_or(5.0);
+				let depth_ms = params.get(1).copied().unwrap_or(1.0);
+				Ok(TransformSpec::Vibrato { rate, depth_ms })
+			}
+			"chorus" => {
+				let params = parse_params(parts.get(1))?;
+				let rate = params.first().copied().unwrap_or(1.5);
+				let depth_ms = params.get(1).copied().unwrap_or(3.0);
+				let mix = params.get(2).copied().unwrap_or(0.5);
+				Ok(TransformSpec::Chorus { rate, depth_ms, mix })
+			}
+			"flanger" => {
+				let params = parse_params(parts.get(1))?;
+				let rate = params.first().copied().unwrap_or(0.5);
+				let depth_ms = params.get(1).copied().unwrap_or(2.0);
+				let feedback = params.get(2).copied().unwrap_or(0.5);
+				let mix = params.get(3).copied().unwrap_or(0.5);
+				Ok(TransformSpec::Flanger { rate, depth_ms, feedback, mix })
+			}
+			"contrast" | "saturation" => {
+				let factor = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"contrast and saturation require a factor (e.g., contrast=1.2)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "video factor must be a number")
+					})?;
+				if name == "contrast" {
+					Ok(TransformSpec::Contrast(factor))
+				} else {
+					Ok(TransformSpec::Saturation(factor))
+				}
+			}
+			"format" | "pix_fmt" => match parts.get(1).copied() {
+				Some("yuv420" | "yuv420p") => Ok(TransformSpec::Format(VideoFormat::YUV420)),
+				Some("yuv422" | "yuv422p") => Ok(TransformSpec::Format(VideoFormat::YUV422)),
+				Some("yuv444" | "yuv444p") => Ok(TransformSpec::Format(VideoFormat::YUV444)),
+				Some("gray" | "mono") => Ok(TransformSpec::Format(VideoFormat::GRAY8)),
+				_ => Err(IoError::invalid_data("format needs yuv420, yuv422, yuv444 or gray")),
+			},
+			"scale" => {
+				// scale=1280x720 or scale=1280:720, then :fit or :fill to keep the
+				// aspect ratio and :nearest or :bilinear for the sampling
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"scale needs a size (e.g., scale=1280x720 or scale=640:360:fit:nearest)",
+					)
+				};
+				let (width, height, rest) = parse_size(parts.get(1)).ok_or_else(invalid)?;
+				let (mut mode, mut fit) = (ScaleMode::Bilinear, ScaleFit::Stretch);
+				for option in rest {
+					match option {
+						"bilinear" => mode = ScaleMode::Bilinear,
+						"nearest" => mode = ScaleMode::NearestNeighbor,
+						"stretch" => fit = ScaleFit::Stretch,
+						"fit" | "letterbox" => fit = ScaleFit::Fit,
+						"fill" => fit = ScaleFit::Fill,
+						_ => return Err(invalid()),
+					}
+				}
+				Ok(TransformSpec::Scale { width, height, mode, fit })
+			}
+			"crop" => {
+				// crop=w:h centres the region; crop=w:h:x:y places it
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"crop takes w:h[:x:y] (e.g., crop=640:360 or crop=640:360:0:60)",
+					)
+				};
+				let (width, height, rest) = parse_size(parts.get(1)).ok_or_else(invalid)?;
+				let position = match rest.as_slice() {
+					[] => None,
+					[x, y] => Some((x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?)),
+					_ => return Err(invalid()),
+				};
+				Ok(TransformSpec::Crop { width, height, position })
+			}
+			"pad" => {
+				// pad=w:h[:x:y][:color]; the frame is centred without x:y
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"pad takes w:h[:x:y][:color] (e.g., pad=1920:1080 or pad=1920:1080:0:140:white)",
+					)
+				};
+				let (width, height, rest) = parse_size(parts.get(1)).ok_or_else(invalid)?;
+				let (position, color) = match rest.as_slice() {
+					[] => (None, None),
+					[color] => (None, Some(*color)),
+					[x, y] => (Some((*x, *y)), None),
+					[x, y, color] => (Some((*x, *y)), Some(*color)),
+					_ => return Err(invalid()),
+				};
+				let position = match position {
+					Some((x, y)) => {
+						Some((x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?))
+					}
+					None => None,
+				};
+				let color = match color {
+					Some(color) => parse_color(color).ok_or_else(invalid)?,
+					None => [16, 128, 128],
+				};
+				Ok(TransformSpec::Pad { width, height, position, color })
+			}
+			"rotate" => match parts.get(1).copied() {
+				Some("90") => Ok(TransformSpec::Rotate(RotateAngle::Rotate90)),
+				Some("180") => Ok(TransformSpec::Rotate(RotateAngle::Rotate180)),
+				Some("270" | "-90") => Ok(TransformSpec::Rotate(RotateAngle::Rotate270)),
+				_ => Err(IoError::invalid_data("rotate needs 90, 180 or 270 degrees")),
+			},
+			"hflip" => Ok(TransformSpec::Flip(FlipDirection::Horizontal)),
+			"vflip" => Ok(TransformSpec::Flip(FlipDirection::Vertical)),
+			"blur" => {
+				let radius = parse_params(parts.get(1))?.first().copied().unwrap_or(1.0);
+				if radius.fract() != 0.0 || radius < 0.0 {
+					return Err(IoError::invalid_data("blur radius must be a whole number of pixels"));
+				}
+				Ok(TransformSpec::Blur(radius as u32))
+			}
+			"brightness" => {
+				let value = parse_params(parts.get(1))?.first().copied().ok_or_else(|| {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"brightness requires an offset from -1 to 1 (e.g., brightness=0.1)",
+					)
+				})?;
+				Ok(TransformSpec::Brightness(value))
+			}
+			"grayscale" => Ok(TransformSpec::Grayscale),
+			"negate" => Ok(TransformSpec::Negate),
+			"drawtext" => {
+				// drawtext=text=%{pts}:x=10:y=10:color=235:size=2
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"drawtext takes text=, x=, y=, color= and size= (e.g., drawtext=text=%{pts}:x=10)",
+					)
+				};
+				let (mut text, mut x, mut y, mut color, mut scale) = (String::new(), 0, 0, 235, 2);
+				for option in parts.get(1).copied().unwrap_or("").split(':') {
+					let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+					match key {
+						"text" => text = value.to_string(),
+						"x" => x = value.parse().map_err(|_| invalid())?,
+						"y" => y = value.parse().map_err(|_| invalid())?,
+						"color" => color = value.parse().map_err(|_| invalid())?,
+						"size" => scale = value.parse().map_err(|_| invalid())?,
+						_ => return Err(invalid()),
+					}
+				}
+				if text.is_empty() {
+					return Err(invalid());
+				}
+				Ok(TransformSpec::DrawText { text, x, y, color, scale })
+			}
+			"drawbox" => {
+				// drawbox=x=10:y=10:w=64:h=48:color=red:t=2 (t=fill fills it); from=KEY
+				// takes the box from a region an earlier transform attached
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"drawbox takes x=, y=, w=, h=, from=, color= and t= (e.g., drawbox=x=10:y=10:w=64:h=48)",
+					)
+				};
+				let (mut x, mut y, mut width, mut height) = (0, 0, 0, 0);
+				let (mut color, mut thickness, mut from) = ([235, 128, 128], Some(2), None);
+				for option in parts.get(1).copied().unwrap_or("").split(':') {
+					let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+					match key {
+						"x" => x = value.parse().map_err(|_| invalid())?,
+						"y" => y = value.parse().map_err(|_| invalid())?,
+						"w" => width = value.parse().map_err(|_| invalid())?,
+						"h" => height = value.parse().map_err(|_| invalid())?,
+						"color" => color = parse_color(value).ok_or_else(invalid)?,
+						"t" if value == "fill" => thickness = None,
+						"t" => thickness = Some(value.parse().map_err(|_| invalid())?),
+						"from" if !value.is_empty() => from = Some(value.to_string()),
+						_ => return Err(invalid()),
+					}
+				}
+				match from {
+					Some(key) => Ok(TransformSpec::DrawRegion { key, color, thickness }),
+					None => Ok(TransformSpec::DrawBox { x, y, width, height, color, thickness }),
+				}
+			}
+			"lut" | "curves" => {
+				// lut=gamma=1.2:lift=0.05:gain=0.95:curve=0/0,128/150,255/255:plane=y
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"lut takes gamma=, lift=, gain=, curve=in/out,... and plane= (e.g., lut=gamma=1.2)",
+					)
+				};
+				let (mut gamma, mut lift, mut gain, mut curve, mut plane) =
+					(1.0, 0.0, 1.0, Vec::new(), None);
+				for option in parts.get(1).copied().unwrap_or("").split(':') {
+					let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+					match key {
+						"gamma" => gamma = value.parse().map_err(|_| invalid())?,
+						"lift" => lift = value.parse().map_err(|_| invalid())?,
+						"gain" => gain = value.parse().map_err(|_| invalid())?,
+						"curve" => {
+							curve = value
+								.split(',')
+								.map(|point| {
+									let (input, output) = point.split_once('/')?;
+									Some((input.parse().ok()?, output.parse().ok()?))
+								})
+								.collect::<Option<_>>()
+								.ok_or_else(invalid)?;
+						}
+						"plane" => {
+							plane = match value {
+								"y" | "r" => Some(0),
+								"u" | "g" => Some(1),
+								"v" | "b" => Some(2),
+								"master" => None,
+								_ => return Err(invalid()),
+							}
+						}
+						_ => return Err(invalid()),
+					}
+				}
+				Ok(TransformSpec::Lut { gamma, lift, gain, curve, plane })
+			}
+			"cropdetect" => {
+				// cropdetect=limit=24:round=2:window=30:apply
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"cropdetect takes limit=, round=, window= and apply (e.g., cropdetect=limit=24:apply)",
+					)
+				};
+				let (mut limit, mut round, mut window, mut apply) = (24, 2, 30, false);
+				for option in parts.get(1).copied().unwrap_or("").split(':').filter(|o| !o.is_empty()) {
+					match option.split_once('=') {
+						None if option == "apply" => apply = true,
+						Some(("limit", value)) => limit = value.parse().map_err(|_| invalid())?,
+						Some(("round", value)) => round = value.parse().map_err(|_| invalid())?,
+						Some(("window", value)) => window = value.parse().map_err(|_| invalid())?,
+						_ => return Err(invalid()),
+					}
+				}
+				if round == 0 || window == 0 {
+					return Err(invalid());
+				}
+				Ok(TransformSpec::CropDetect { limit, round, window, apply, measured: None })
+			}
+			"stabilize" | "deshake" => {
+				// stabilize=search=16:smoothing=15
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"stabilize takes search= and smoothing= (e.g., stabilize=search=16:smoothing=15)",
+					)
+				};
+				let (mut search, mut smoothing) = (16, 15);
+				for option in parts.get(1).copied().unwrap_or("").split(':').filter(|o| !o.is_empty()) {
+					match option.split_once('=') {
+						Some(("search", value)) => search = value.parse().map_err(|_| invalid())?,
+						Some(("smoothing", value)) => smoothing = value.parse().map_err(|_| invalid())?,
+						_ => return Err(invalid()),
+					}
+				}
+				if search == 0 || search > 64 {
+					return Err(IoError::invalid_data("stabilize search must be 1 to 64 pixels"));
+				}
+				Ok(TransformSpec::Stabilize { search, smoothing, corrections: None })
+			}
+			"vfade" => {
+				// vfade=in=30:out=30
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"vfade takes in= and out= frame counts (e.g., vfade=in=30:out=30)",
+					)
+				};
+				let (mut fade_in, mut fade_out) = (0, 0);
+				for option in parts.get(1).copied().unwrap_or("").split(':') {
+					match option.split_once('=') {
+						Some(("in", value)) => fade_in = value.parse().map_err(|_| invalid())?,
+						Some(("out", value)) => fade_out = value.parse().map_err(|_| invalid())?,
+						_ => return Err(invalid()),
+					}
+				}
+				Ok(TransformSpec::VideoFade { fade_in, fade_out, total_frames: None })
+			}
+			"tblend" => {
+				// tblend=3 averages three frames; tblend=1,2,4 weighs them
+				let params = parse_params(parts.get(1))?;
+				match params.as_slice() {
+					[] => Ok(TransformSpec::Tblend(vec![1.0; 2])),
+					[frames] if frames.fract() == 0.0 && *frames >= 1.0 && *frames <= 64.0 => {
+						Ok(TransformSpec::Tblend(vec![1.0; *frames as usize]))
+					}
+					[_] => {
+						Err(IoError::invalid_data("tblend frame count must be a whole number from 1 to 64"))
+					}
+					weights => Ok(TransformSpec::Tblend(weights.to_vec())),
+				}
+			}
+			"vignette" => {
+				let strength = parse_params(parts.get(1))?.first().copied().unwrap_or(0.5);
+				if !(0.0..=1.0).contains(&strength) {
+					return Err(IoError::invalid_data("vignette strength must be between 0 and 1"));
+				}
+				Ok(TransformSpec::Vignette(strength))
+			}
+			"pixelate" => {
+				// pixelate=size=16:x=100:y=40:w=120:h=160 (no region covers the frame)
+				let invalid = || {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"pixelate takes size=, x=, y=, w= and h= (e.g., pixelate=size=16:x=0:y=0:w=64:h=64)",
+					)
+				};
+				let (mut block, mut region) = (16, None);
+				for option in parts.get(1).copied().unwrap_or("").split(':').filter(|o| !o.is_empty()) {
+					let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+					let value: u32 = value.parse().map_err(|_| invalid())?;
+					let rect = region.get_or_insert((0, 0, u32::MAX, u32::MAX));
+					match key {
+						"size" => block = value,
+						"x" => rect.0 = value,
+						"y" => rect.1 = value,
+						"w" => rect.2 = value,
+						"h" => rect.3 = value,
+						_ => return Err(invalid()),
+					}
+				}
+				if block == 0 {
+					return Err(invalid());
+				}
+				Ok(TransformSpec::Pixelate { block, region })
+			}
+			"lut3d" => match parts.get(1) {
+				Some(path) if !path.is_empty() => Ok(TransformSpec::Lut3D(path.to_string())),
+				_ => Err(IoError::invalid_data("lut3d needs a .cube file (e.g., lut3d=grade.cube)")),
+			},
+			"gblur" | "gaussian_blur" => {
+				let sigma = parse_params(parts.get(1))?.first().copied().unwrap_or(2.0);
+				Ok(TransformSpec::GaussianBlur { sigma })
+			}
+			"sharpen" => {
+				// sharpen=amount,radius
+				let params = parse_params(parts.get(1))?;
+				let amount = params.first().copied().unwrap_or(1.0);
+				let radius = params.get(1).copied().unwrap_or(1.0);
+				if radius.fract() != 0.0 || radius < 0.0 {
+					return Err(IoError::invalid_data("sharpen radius must be a whole number of pixels"));
+				}
+				Ok(TransformSpec::Sharpen { amount, radius: radius as u32 })
+			}
+			"pan" | "balance" => {
+				let value = parts
+					.get(1)
+					.ok_or_else(|| {
+						IoError::with_message(
+							IoErrorKind::InvalidData,
+							"pan and balance require a position from -1 to 1 (e.g., pan=-0.3)",
+						)
+					})?
+					.parse::<f32>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "pan position must be a number")
+					})?;
+				if name == "pan" {
+					Ok(TransformSpec::Pan(value))
+				} else {
+					Ok(TransformSpec::Balance(value))
+				}
+			}
+			"karaoke" => {
+				// karaoke[=low_hz,high_hz]
+				let params = parse_params(parts.get(1))?;
+				let protect = match params[..] {
+					[] => None,
+					[low, high] => Some((low, high)),
+					_ => {
+						return Err(IoError::with_message(
+							IoErrorKind::InvalidData,
+							"karaoke takes two protection frequencies (e.g., karaoke=200,8000)",
+						));
+					}
+				};
+				Ok(TransformSpec::Karaoke { protect })
+			}
+			"adelay" => {
+				// adelay=ms_ch0,ms_ch1,...
+				let delays = parse_params(parts.get(1))?;
+				if delays.is_empty() {
+					return Err(IoError::with_message(
+						IoErrorKind::InvalidData,
+						"adelay requires a delay per channel in ms (e.g., adelay=0,20)",
+					));
+				}
+				Ok(TransformSpec::Adelay(delays))
+			}
+			"mono" => Ok(TransformSpec::Mono),
+			"stereo" => Ok(TransformSpec::Stereo),
+			"downmix" => Ok(TransformSpec::Downmix),
+			"channelmap" => {
+				let value = parts.get(1).ok_or_else(|| {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"channelmap requires input channel indices (e.g., channelmap=1,0)",
+					)
+				})?;
+				let map = value
+					.split(',')
+					.map(|v| v.trim().parse::<usize>())
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(|_| {
+						IoError::with_message(IoErrorKind::InvalidData, "channelmap indices must be numbers")
+					})?;
+				Ok(TransformSpec::ChannelMap(map))
+			}
+			"matrix" => {
+				// rows are output channels separated by '|', e.g. matrix=0.5,0.5|1,0
+				let value = parts.get(1).ok_or_else(|| {
+					IoError::with_message(
+						IoErrorKind::InvalidData,
+						"matrix requires rows of gains (e.g., matrix=0.5,0.5|1,0)",
+					)
+				})?;
+				let rows = value.split('|').map(|row| parse_params(Some(&row))).collect::<IoResult<_>>()?;
+				Ok(TransformSpec::Matrix(rows))
+			}
+			"eq3" => {
+				let params = parts.get(1).unwrap_or(&"0,0,0");
+				let values: Vec<f32> = params.split(',').filter_map(|v| v.parse::<f32>().ok()).collect();
+				let bass = values.first().copied().unwrap_or(0.0);
+				let mid = values.get(1).copied().unwrap_or(0.0);
+				let treble = values.get(2).copied().unwrap_or(0.0);
+				Ok(TransformSpec::Eq3 { bass, mid, treble })
+			}
+			"peak_limiter" | "limiter" => {
+				let threshold_db = parts.get(1).map(|v| v.parse::<f32>().unwrap_or(-1.0)).unwrap_or(-1.0);
+				Ok(TransformSpec::PeakLimiter { threshold_db })
+			}
+			"lookahead_limiter" | "brickwall" => {
+				// lookahead_limiter=ceiling_db,lookahead_ms
+				let params = parse_params(parts.get(1))?;
+				let ceiling_db = params.first().copied().unwrap_or(-1.0);
+				let lookahead_ms = params.get(1).copied().unwrap_or(5.0);
+				Ok(TransformSpec::LookaheadLimiter { ceiling_db, lookahead_ms })
+			}
+			"rms_limiter" => {
+				let threshold_db = parts.get(1).map(|v| v.parse::<f32>().unwrap_or(-10.0)).unwrap_or(-10.0);
+				Ok(TransformSpec::RmsLimiter { threshold_db })
+			}
+			"loudnorm" => {
+				// loudnorm=target_lufs,true_peak_dbtp
+				let params = parse_params(parts.get(1))?;
+				let target_lufs = params.first().copied().unwrap_or(-16.0) as f64;
+				let true_peak = params.get(1).copied().unwrap_or(-1.5) as f64;
+				Ok(TransformSpec::Loudnorm { target_lufs, true_peak, measured: None })
+			}
+			"astats" => match parts.get(1).copied() {
+				None => Ok(TransformSpec::AStats { json: false }),
+				Some("json") => Ok(TransformSpec::AStats { json: true }),
+				Some(_) => Err(IoError::with_message(
+					IoErrorKind::InvalidData,
+					"astats takes no value other than json (e.g., astats=json)",
+				)),
+			},
+			"silencedetect" => {
+				// silencedetect=noise_db,seconds[,json]
+				let value = parts.get(1).copied().unwrap_or("");
+				let json = value.ends_with("json");
+				let numbers = value.trim_end_matches("json").trim_end_matches(',');
+				let params = if numbers.is_empty() { Vec::new() } else { parse_params(Some(&numbers))? };
+				let noise_db = params.first().copied().unwrap_or(-60.0);
+				let min_duration = params.get(1).copied().unwrap_or(2.0) as f64;
+				Ok(TransformSpec::SilenceDetect { noise_db, min_duration, json })
+			}
+			_ if super::registry::is_registered(name) => Ok(TransformSpec::Custom {
+				name: name.to_string(),
+				args: parts.get(1).map(|args| args.to_string()),
+			}),
+			_ => Err(IoError::with_message(IoErrorKind::InvalidData, UNKNOWN_TRANSFORM)),
+		}
+	}
+
+	// the spec itself, or the one a stream prefix wraps; the transcoder looks
+	// through the prefix to run first passes
+	pub fn target(&self) -> &TransformSpec {
+		match self {
+			TransformSpec::Stream { spec, .. } => spec.target(),
+			spec => spec,
+		}
+	}
+
+	pub fn target_mut(&mut self) -> &mut TransformSpec {
+		match self {
+			TransformSpec::Stream { spec, .. } => spec.target_mut(),
+			spec => spec,
+		}
+	}
+
+	// values are checked here rather than in `parse` so typed specs get the
+	// same errors as strings from the command line
+	pub fn build(&self) -> IoResult<Box<dyn Transform>> {
+		self.build_with(&AllowAll)
+	}
+
+	// as `build`, opening any file a transform reads (a .cube) through `policy`
+	pub fn build_with(&self, policy: &dyn PathPolicy) -> IoResult<Box<dyn Transform>> {
+		let transform: Box<dyn Transform> = match self {
+			TransformSpec::Gain(factor) => Box::new(Gain::new(*factor)),
+			TransformSpec::Volume(factor) => Box::new(Volume::new(*factor)),
+			TransformSpec::Normalize { peak } => Box::new(Normalize::new(*peak)),
+			TransformSpec::Highpass { cutoff } => Box::new(Highpass::new(*cutoff)),
+			TransformSpec::Lowpass { cutoff } => Box::new(Lowpass::new(*cutoff)),
+			TransformSpec::DcRemove { cutoff } => {
+				if !(cutoff.is_finite() && *cutoff > 0.0) {
+					return Err(IoError::invalid_data("dcremove cutoff must be positive"));
+				}
+				Box::new(DcRemove::new(*cutoff))
+			}
+			TransformSpec::Invert => Box::new(Invert::new()),
+			TransformSpec::FadeIn { duration_ms } => Box::new(FadeIn::new(*duration_ms, 44100)),
+			TransformSpec::Resample { rate } => {
+				if *rate == 0 {
+					return Err(IoError::invalid_data("resample rate must be positive"));
+				}
+				Box::new(Resample::new(*rate))
+			}
+			TransformSpec::Speed(factor) => {
+				if !(factor.is_finite() && *factor > 0.0) {
+					return Err(IoError::invalid_data("speed factor must be positive"));
+				}
+				Box::new(Speed::new(*factor))
+			}
+			TransformSpec::Fps { num, den } => {
+				if *num == 0 || *den == 0 {
+					return Err(IoError::invalid_data("fps rate must be positive"));
+				}
+				return Err(IoError::unsupported(
+					"fps only works as the last transform of a y4m transcode",
+				));
+			}
+			TransformSpec::Adelay(delays) => {
+				if delays.iter().any(|ms| !(ms.is_finite() && *ms >= 0.0)) {
+					return Err(IoError::invalid_data("adelay delays must not be negative"));
+				}
+				Box::new(Adelay::new(delays.clone()))
+			}
+			TransformSpec::Tremolo { rate, depth } => Box::new(Tremolo::new(*rate, *depth)),
+			TransformSpec::Vibrato { rate, depth_ms } => Box::new(Vibrato::new(*rate, *depth_ms)),
+			TransformSpec::Chorus { rate, depth_ms, mix } => {
+				Box::new(Chorus::new(*rate, *depth_ms, *mix))
+			}
+			TransformSpec::Flanger { rate, depth_ms, feedback, mix } => {
+				Box::new(Flanger::new(*rate, *depth_ms, *feedback, *mix))
+			}
+			TransformSpec::Pan(position) => Box::new(Pan::new(check_position(*position)?)),
+			TransformSpec::Balance(position) => Box::new(Balance::new(check_position(*position)?)),
+			TransformSpec::Karaoke { protect: None } => Box::new(Karaoke::new()),
+			TransformSpec::Karaoke { protect: Some((low, high)) } => {
+				if !(low.is_finite() && *low > 0.0 && high > low && high.is_finite()) {
+					return Err(IoError::invalid_data("karaoke protection needs 0 < low < high"));
+				}
+				Box::new(Karaoke::new().with_protection(*low, *high))
+			}
+			TransformSpec::Mono => Box::new(ChannelMixer::stereo_to_mono()),
+			TransformSpec::Stereo => Box::new(ChannelMixer::mono_to_stereo()),
+			TransformSpec::Downmix => Box::new(ChannelMixer::downmix_51_to_stereo()),
+			TransformSpec::ChannelMap(map) => Box::new(ChannelMixer::remap(map.clone())?),
+			TransformSpec::Matrix(rows) => Box::new(ChannelMixer::from_matrix(rows.clone())?),
+			TransformSpec::Eq3 { bass, mid, treble } => {
+				Box::new(Equalizer::three_band(*bass, *mid, *treble))
+			}
+			TransformSpec::PeakLimiter { threshold_db } => Box::new(PeakLimiter::new(*threshold_db)),
+			TransformSpec::LookaheadLimiter { ceiling_db, lookahead_ms } => {
+				if !(ceiling_db.is_finite() && *ceiling_db <= 0.0) {
+					return Err(IoError::invalid_data("limiter ceiling must be at or below 0 dBFS"));
+				}
+				if !(lookahead_ms.is_finite() && *lookahead_ms > 0.0) {
+					return Err(IoError::invalid_data("limiter lookahead must be positive"));
+				}
+				Box::new(LookaheadLimiter::new(*ceiling_db).with_lookahead(*lookahead_ms))
+			}
+			TransformSpec::AStats { json } => Box::new(AStats::new().with_json(*json)),
+			TransformSpec::SilenceDetect { noise_db, min_duration, json } => {
+				if *min_duration < 0.0 {
+					return Err(IoError::invalid_data("silence duration must not be negative"));
+				}
+				Box::new(SilenceDetect::new(*noise_db, *min_duration).with_json(*json))
+			}
+			TransformSpec::Loudnorm { target_lufs, true_peak, measured } => {
+				let Some(measured) = measured else {
+					return Err(IoError::invalid_data("loudnorm needs the measured loudness of its input"));
+				};
+				if *true_peak > 0.0 {
+					return Err(IoError::invalid_data("loudnorm true peak must be at or below 0 dBTP"));
+				}
+				Box::new(Loudnorm::new(*target_lufs, *true_peak, *measured))
+			}
+			// the chain sizes planes from each frame, so no dimensions are needed
+			TransformSpec::Contrast(factor) => Box::new(Contrast::new(0, 0, check_factor(*factor)?)),
+			TransformSpec::Saturation(factor) => Box::new(Saturation::new(0, 0, check_factor(*factor)?)),
+			TransformSpec::Scale { width, height, mode, fit } => {
+				if *width == 0 || *height == 0 {
+					return Err(IoError::invalid_data("scale needs a width and height"));
+				}
+				Box::new(Scale::new(0, 0, *width, *height).with_mode(*mode).with_fit(*fit))
+			}
+			TransformSpec::Crop { width, height, position } => {
+				if *width == 0 || *height == 0 {
+					return Err(IoError::invalid_data("crop needs a width and height"));
+				}
+				match position {
+					Some((x, y)) => Box::new(Crop::new(u32::MAX, u32::MAX, *x, *y, *width, *height)),
+					None => Box::new(Crop::center(u32::MAX, u32::MAX, *width, *height)),
+				}
+			}
+			TransformSpec::Pad { width, height, position, color } => {
+				let pad = match position {
+					Some((x, y)) => Pad::new(0, 0, *width, *height, *x, *y),
+					None => Pad::center(0, 0, *width, *height),
+				};
+				Box::new(pad.with_color(color[0], color[1], color[2]))
+			}
+			TransformSpec::Rotate(angle) => Box::new(Rotate::new(0, 0, *angle)),
+			TransformSpec::Flip(direction) => Box::new(Flip::new(0, 0, *direction)),
+			TransformSpec::Blur(radius) => Box::new(Blur::new(0, 0, *radius)),
+			TransformSpec::Brightness(offset) => {
+				if !(-1.0..=1.0).contains(offset) {
+					return Err(IoError::invalid_data("brightness must be between -1 and 1"));
+				}
+				Box::new(Brightness::new(0, 0, *offset))
+			}
+			TransformSpec::Lut { gamma, lift, gain, curve, plane } => {
+				let mut table = compose_tables(&gamma_table(*gamma)?, &lift_gain_table(*lift, *gain));
+				if !curve.is_empty() {
+					table = compose_tables(&table, &curve_table(curve)?);
+				}
+				match plane {
+					Some(plane) => Box::new(Lut::identity().with_plane(*plane, table)),
+					None => Box::new(Lut::new(table)),
+				}
+			}
+			TransformSpec::CropDetect { limit, round, window, apply, measured } => {
+				let detect = CropDetect::new().with_limit(*limit).with_round(*round).with_window(*window);
+				match (apply, measured) {
+					(true, Some(crop)) => Box::new(detect.with_crop(*crop)),
+					_ => Box::new(detect),
+				}
+			}
+			TransformSpec::Stabilize { corrections, .. } => {
+				let Some(corrections) = corrections else {
+					return Err(IoError::invalid_data("stabilize needs the motion of its input"));
+				};
+				Box::new(Stabilize::new(corrections.clone()))
+			}
+			TransformSpec::VideoFade { fade_in, fade_out, total_frames } => {
+				match (fade_out, total_frames) {
+					(0, _) => Box::new(VideoFade::fade_in(*fade_in)),
+					(_, Some(total)) => {
+						Box::new(VideoFade::fade_in(*fade_in).with_fade_out(*fade_out, *total))
+					}
+					(_, None) => {
+						return Err(IoError::invalid_data("vfade out needs the frame count of its input"));
+					}
+				}
+			}
+			TransformSpec::Tblend(weights) => Box::new(Tblend::with_weights(weights.clone())?),
+			TransformSpec::Vignette(strength) => Box::new(Vignette::new(*strength)),
+			TransformSpec::Pixelate { block, region } => match region {
+				Some((x, y, width, height)) => {
+					Box::new(Pixelate::new(*block).with_region(*x, *y, *width, *height))
+				}
+				None => Box::new(Pixelate::new(*block)),
+			},
+			TransformSpec::Lut3D(path) => Box::new(Lut3D::load_with(path, policy)?),
+			TransformSpec::Format(format) => Box::new(ColorConvert::new(*format)),
+			TransformSpec::Expression(spec) => Box::new(Evaluated::new(spec)),
+			TransformSpec::Custom { name, args } => super::registry::build(name, args.as_deref())?,
+			TransformSpec::Stream { kind, index, spec } => {
+				Box::new(StreamTarget::new(*kind, spec.build_with(policy)?).with_index(*index))
+			}
+			TransformSpec::Grayscale => Box::new(Grayscale::new()),
+			TransformSpec::Negate => Box::new(Negate::new()),
+			TransformSpec::DrawText { text, x, y, color, scale } => {
+				if *scale == 0 {
+					return Err(IoError::invalid_data("drawtext size must be at least 1"));
+				}
+				Box::new(
+					DrawText::new(text.clone()).with_position(*x, *y).with_color(*color).with_scale(*scale),
+				)
+			}
+			TransformSpec::DrawBox { x, y, width, height, color, thickness } => {
+				if *width == 0 || *height == 0 {
+					return Err(IoError::invalid_data("drawbox needs a width and height"));
+				}
+				let drawbox = DrawBox::new(*x, *y, *width, *height).with_color(*color);
+				match thickness {
+					Some(0) => return Err(IoError::invalid_data("drawbox thickness must be at least 1")),
+					Some(t) => Box::new(drawbox.with_thickness(*t)),
+					None => Box::new(drawbox.filled()),
+				}
+			}
+			TransformSpec::DrawRegion { key, color, thickness } => {
+				let drawbox = DrawBox::from_metadata(key).with_color(*color);
+				match thickness {
+					Some(0) => return Err(IoError::invalid_data("drawbox thickness must be at least 1")),
+					Some(t) => Box::new(drawbox.with_thickness(*t)),
+					None => Box::new(drawbox.filled()),
+				}
+			}
+			TransformSpec::GaussianBlur { sigma } => {
+				if !(sigma.is_finite() && *sigma > 0.0) {
+					return Err(IoError::invalid_data("blur sigma must be positive"));
+				}
+				Box::new(GaussianBlur::new(*sigma))
+			}
+			TransformSpec::Sharpen { amount, radius } => {
+				if !(amount.is_finite() && *amount >= 0.0) {
+					return Err(IoError::invalid_data("sharpen amount must not be negative"));
+				}
+				if *radius == 0 {
+					return Err(IoError::invalid_data("sharpen radius must be at least 1"));
+				}
+				Box::new(Sharpen::new(*amount, *radius))
+			}
+			TransformSpec::RmsLimiter { threshold_db } => {
+				Box::new(RmsLimiter::new(*threshold_db, 50.0, 44100))
+			}
+		};
+		Ok(transform)
+	}
+}
+
+fn check_position(position: f32) -> IoResult<f32> {
+	if !(-1.0..=1.0).contains(&position) {
+		return Err(IoError::invalid_data("pan position must be between -1 and 1"));
+	}
+	Ok(position)
+}
+
+fn check_factor(factor: f32) -> IoResult<f32> {
+	if !(factor.is_finite() && factor >= 0.0) {
+		return Err(IoError::invalid_data("video factor must not be negative"));
+	}
+	Ok(factor)
+}
+
+// "a:" or "v:", optionally followed by a stream number as in "a:1:"
+fn split_stream_prefix(spec: &str) -> IoResult<Option<(FrameKind, Option<usize>, &str)>> {
+	let kind = match spec.get(..2) {
+		Some("a:") => FrameKind::Audio,
+		Some("v:") => FrameKind::Video,
+		_ => return Ok(None),
+	};
+	let rest = &spec[2..];
+	match rest.split_once(':') {
+		Some((number, tail)) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => {
+			let index = number.parse().map_err(|_| IoError::invalid_data("invalid stream number"))?;
+			Ok(Some((kind, Some(index), tail)))
+		}
+		_ => Ok(Some((kind, None, rest))),
+	}
+}
+
+// "WxH" or "W:H", then any further colon separated fields
+fn parse_size<'a>(value: Option<&&'a str>) -> Option<(u32, u32, Vec<&'a str>)> {
+	let mut fields: Vec<&str> = value?.split(':').collect();
+	let (width, height) = match fields[0].split_once('x') {
+		Some(size) => {
+			fields.remove(0);
+			size
+		}
+		None if fields.len() >= 2 => {
+			let size = (fields[0], fields[1]);
+			fields.drain(..2);
+			size
+		}
+		None => return None,
+	};
+	Some((width.parse().ok()?, height.parse().ok()?, fields))
+}
+
+// comma separated numbers, e.g. the "5,0.5" of tremolo=5,0.5
+fn parse_params(value: Option<&&str>) -> IoResult<Vec<f32>> {
+	let Some(value) = value else {
+		return Ok(Vec::new());
+	};
+	value
+		.split(',')
+		.map(|v| {
+			v.trim().parse::<f32>().map_err(|_| {
+				IoError::with_message(IoErrorKind::InvalidData, "transform parameters must be numbers")
+			})
+		})
+		.collect()
+}
@@ -0,0 +1,93 @@
+use super::resample::interpolate;
+use crate::core::{Frame, FrameData, Timebase, Transform};
+use crate::io::IoResult;
+
+// the cheap speed change: audio is resampled in place so pitch moves with the
+// tempo, video keeps every frame and only its clock runs faster, which a y4m
+// header picks up as a higher rate. both stay on the same timeline, so `fps`
+// after this drops or repeats frames to match the audio.
+pub struct Speed {
+	factor: f64,
+}
+
+impl Speed {
+	pub fn new(factor: f64) -> Self {
+		Self { factor }
+	}
+
+	pub fn factor(&self) -> f64 {
+		self.factor
+	}
+
+	fn apply_audio(&self, frame: Frame) -> Frame {
+		let Some(audio) = frame.audio() else {
+			return frame;
+		};
+		let channels = audio.channels.max(1) as usize;
+		let input: Vec<i16> =
+			audio.data.chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+
+		let planes: Vec<Vec<i16>> = (0..channels)
+			.map(|ch| {
+				let plane: Vec<i16> = input.iter().skip(ch).step_by(channels).copied().collect();
+				interpolate(&plane, self.factor)
+			})
+			.collect();
+
+		let nb_samples = planes.first().map_or(0, Vec::len);
+		let mut data = Vec::with_capacity(nb_samples * channels * 2);
+		for idx in 0..nb_samples {
+			for plane in &planes {
+				data.extend_from_slice(&plane[idx].to_le_bytes());
+			}
+		}
+
+		let mut out = audio.clone();
+		out.data = data;
+		out.nb_samples = nb_samples;
+		let pts = (frame.pts as f64 / self.factor).round() as i64;
+		frame.with_data(FrameData::Audio(out)).with_pts(pts)
+	}
+
+	// the pts stays an integer frame index, the tick gets shorter instead
+	fn apply_video(&self, mut frame: Frame) -> Frame {
+		frame.timebase = scale_timebase(frame.timebase, self.factor);
+		frame
+	}
+}
+
+impl Transform for Speed {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		if self.factor == 1.0 {
+			return Ok(frame);
+		}
+		Ok(match frame.data {
+			FrameData::Audio(_) => self.apply_audio(frame),
+			FrameData::Video(_) => self.apply_video(frame),
+		})
+	}
+
+	fn name(&self) -> &'static str {
+		"speed"
+	}
+}
+
+const SPEED_PRECISION: u64 = 1000;
+
+fn scale_timebase(timebase: Timebase, factor: f64) -> Timebase {
+	let scaled = ((factor * SPEED_PRECISION as f64).round() as u64).max(1);
+	let num = timebase.num as u64 * SPEED_PRECISION;
+	let den = timebase.den as u64 * scaled;
+	let divisor = gcd(num, den);
+	let (num, den) = (num / divisor, den / divisor);
+	if num > u32::MAX as u64 || den > u32::MAX as u64 {
+		// fall back to a rounded tick rather than overflowing
+		let seconds = num as f64 / den as f64;
+		return Timebase::new(1, (1.0 / seconds).round().max(1.0) as u32);
+	}
+	Timebase::new(num as u32, den as u32)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+	if b == 0 { a } else { gcd(b, a % b) }
+}
@@ -0,0 +1,73 @@
+use crate::core::{Frame, FrameKind, StreamFormat, Transform};
+use crate::io::IoResult;
+
+// runs a transform on frames of one kind only, and with an index on the nth
+// stream of that kind; everything else passes through untouched. streams are
+// numbered in the order their first frame arrives, so a:1 is the second audio
+// stream whatever its index in the container.
+pub struct StreamTarget {
+	kind: FrameKind,
+	index: Option<usize>,
+	streams: Vec<usize>,
+	inner: Box<dyn Transform>,
+}
+
+impl StreamTarget {
+	pub fn new(kind: FrameKind, inner: Box<dyn Transform>) -> Self {
+		Self { kind, index: None, streams: Vec::new(), inner }
+	}
+
+	pub fn with_index(mut self, index: Option<usize>) -> Self {
+		self.index = index;
+		self
+	}
+
+	fn is_target(&mut self, frame: &Frame) -> bool {
+		if frame.kind() != self.kind {
+			return false;
+		}
+		let position = match self.streams.iter().position(|&stream| stream == frame.stream_index) {
+			Some(position) => position,
+			None => {
+				self.streams.push(frame.stream_index);
+				self.streams.len() - 1
+			}
+		};
+		self.index.is_none_or(|index| index == position)
+	}
+}
+
+impl Transform for StreamTarget {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		if self.is_target(&frame) { self.inner.apply(frame) } else { Ok(frame) }
+	}
+
+	fn name(&self) -> &'static str {
+		self.inner.name()
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		self.inner.finish()
+	}
+
+	fn supports_f32(&self) -> bool {
+		self.inner.supports_f32()
+	}
+
+	fn flush(&mut self) -> IoResult<Option<Frame>> {
+		self.inner.flush()
+	}
+
+	// negotiation is for the first stream of a kind, as the transcoder sees it
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let kind = match input {
+			StreamFormat::Audio { .. } => FrameKind::Audio,
+			StreamFormat::Video { .. } => FrameKind::Video,
+		};
+		if kind == self.kind && self.index.is_none_or(|index| index == 0) {
+			self.inner.negotiate(input)
+		} else {
+			Ok(input)
+		}
+	}
+}
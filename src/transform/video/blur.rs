@@ -1,4 +1,5 @@
-use crate::core::Frame;
+use super::{planar_dimensions, planar_format};
+use crate::core::{Frame, StreamFormat, Transform};
 use crate::io::IoResult;
 
 pub struct Blur {
@@ -15,7 +16,8 @@ impl Blur {
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
 			let y_size = (self.width * self.height) as usize;
-			let uv_size = y_size / 4;
+			let (uv_w, uv_h) = video_frame.format.chroma_dimensions(self.width, self.height);
+			let uv_size = (uv_w * uv_h) as usize;
 
 			let src_y = &video_frame.data[0..y_size];
 			let src_u = &video_frame.data[y_size..y_size + uv_size];
@@ -25,12 +27,10 @@ impl Blur {
 			let (dst_y, dst_uv) = dst_data.split_at_mut(y_size);
 			let (dst_u, dst_v) = dst_uv.split_at_mut(uv_size);
 
-			self.box_blur(src_y, dst_y, self.width, self.height);
+			box_blur(src_y, dst_y, self.width, self.height, self.radius);
 
-			let uv_w = self.width / 2;
-			let uv_h = self.height / 2;
-			self.box_blur(src_u, dst_u, uv_w, uv_h);
-			self.box_blur(src_v, dst_v, uv_w, uv_h);
+			box_blur(src_u, dst_u, uv_w, uv_h, self.radius);
+			box_blur(src_v, dst_v, uv_w, uv_h, self.radius);
 
 			let new_video = crate::core::FrameVideo::new(
 				dst_data,
@@ -45,35 +45,68 @@ impl Blur {
 			Ok(frame.clone())
 		}
 	}
+}
 
-	fn box_blur(&self, src: &[u8], dst: &mut [u8], width: u32, height: u32) {
-		let r = self.radius as i32;
+impl Transform for Blur {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some((width, height)) = planar_dimensions(&frame)? else {
+			return Ok(frame);
+		};
+		(self.width, self.height) = (width, height);
+		self.apply_yuv420(&frame)
+	}
 
-		for y in 0..height as i32 {
-			for x in 0..width as i32 {
-				let mut sum: u32 = 0;
-				let mut count: u32 = 0;
+	fn name(&self) -> &'static str {
+		"blur"
+	}
 
-				for dy in -r..=r {
-					for dx in -r..=r {
-						let nx = x + dx;
-						let ny = y + dy;
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		planar_format(input)?;
+		Ok(input)
+	}
+}
 
-						if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-							let idx = (ny as u32 * width + nx as u32) as usize;
-							if idx < src.len() {
-								sum += src[idx] as u32;
-								count += 1;
-							}
-						}
-					}
-				}
+// mean of the (2r+1)² neighbourhood, shrunk at the edges. the window is a
+// rectangle, so running sums along rows and then down columns give every
+// pixel in constant time whatever the radius.
+pub(super) fn box_blur(src: &[u8], dst: &mut [u8], width: u32, height: u32, radius: u32) {
+	let (w, h, r) = (width as usize, height as usize, radius as usize);
+	if w == 0 || h == 0 || src.len() < w * h || dst.len() < w * h {
+		return;
+	}
+	let span = |pos: usize, len: usize| (pos + r).min(len - 1) - pos.saturating_sub(r) + 1;
 
-				let dst_idx = (y as u32 * width + x as u32) as usize;
-				if dst_idx < dst.len() && count > 0 {
-					dst[dst_idx] = (sum / count) as u8;
-				}
+	let mut row_sums = vec![0u32; w * h];
+	for (row, sums) in src.chunks_exact(w).zip(row_sums.chunks_exact_mut(w)) {
+		let mut sum: u32 = row[..(r + 1).min(w)].iter().map(|&v| v as u32).sum();
+		for x in 0..w {
+			sums[x] = sum;
+			if x + r + 1 < w {
+				sum += row[x + r + 1] as u32;
+			}
+			if x >= r {
+				sum -= row[x - r] as u32;
 			}
 		}
 	}
+
+	let mut column_sums = vec![0u32; w];
+	for sums in row_sums.chunks_exact(w).take(r + 1) {
+		column_sums.iter_mut().zip(sums).for_each(|(total, &s)| *total += s);
+	}
+	for y in 0..h {
+		let rows = span(y, h) as u32;
+		let out = &mut dst[y * w..(y + 1) * w];
+		for (x, (value, &total)) in out.iter_mut().zip(&column_sums).enumerate() {
+			*value = (total / (rows * span(x, w) as u32)) as u8;
+		}
+		if y + r + 1 < h {
+			let entering = &row_sums[(y + r + 1) * w..(y + r + 2) * w];
+			column_sums.iter_mut().zip(entering).for_each(|(total, &s)| *total += s);
+		}
+		if y >= r {
+			let leaving = &row_sums[(y - r) * w..(y - r + 1) * w];
+			column_sums.iter_mut().zip(leaving).for_each(|(total, &s)| *total -= s);
+		}
+	}
 }
@@ -1,4 +1,4 @@
-use crate::core::Frame;
+use crate::core::{Frame, Transform, VideoFormat};
 use crate::io::IoResult;
 
 pub struct Brightness {
@@ -39,3 +39,24 @@ impl Brightness {
 		}
 	}
 }
+
+// in a chain the luma plane is sized from each frame; packed rgb passes through
+impl Transform for Brightness {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video_mut()
+			&& (video_frame.format.has_chroma_planes() || video_frame.format == VideoFormat::GRAY8)
+		{
+			let y_size = (video_frame.width * video_frame.height) as usize;
+			let y_size = y_size.min(video_frame.data.len());
+			let offset = self.factor * 255.0;
+			for value in &mut video_frame.data[..y_size] {
+				*value = (*value as f32 + offset).clamp(0.0, 255.0) as u8;
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"brightness"
+	}
+}
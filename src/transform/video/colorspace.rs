@@ -0,0 +1,242 @@
+use crate::core::{Frame, FrameVideo, StreamFormat, Transform, VideoFormat};
+use crate::io::{IoError, IoResult};
+
+// yuv <-> rgb for 8-bit limited-range video (y in 16..=235, chroma in 16..=240)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMatrix {
+	// standard definition; what y4m files without a tag usually hold
+	#[default]
+	Bt601,
+	// hd
+	Bt709,
+}
+
+impl ColorMatrix {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"bt601" | "601" => Some(ColorMatrix::Bt601),
+			"bt709" | "709" => Some(ColorMatrix::Bt709),
+			_ => None,
+		}
+	}
+
+	// luma weights of red and blue
+	fn weights(&self) -> (f32, f32) {
+		match self {
+			ColorMatrix::Bt601 => (0.299, 0.114),
+			ColorMatrix::Bt709 => (0.2126, 0.0722),
+		}
+	}
+
+	pub fn rgb_to_yuv(&self, [r, g, b]: [u8; 3]) -> [u8; 3] {
+		let (kr, kb) = self.weights();
+		let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+		let luma = kr * r + (1.0 - kr - kb) * g + kb * b;
+		let pb = (b - luma) / (2.0 * (1.0 - kb));
+		let pr = (r - luma) / (2.0 * (1.0 - kr));
+		[16.0 + 219.0 * luma, 128.0 + 224.0 * pb, 128.0 + 224.0 * pr]
+			.map(|v| v.round().clamp(0.0, 255.0) as u8)
+	}
+
+	pub fn yuv_to_rgb(&self, [y, u, v]: [u8; 3]) -> [u8; 3] {
+		let (kr, kb) = self.weights();
+		let luma = (y as f32 - 16.0) / 219.0;
+		let pb = (u as f32 - 128.0) / 224.0;
+		let pr = (v as f32 - 128.0) / 224.0;
+		let r = luma + 2.0 * (1.0 - kr) * pr;
+		let b = luma + 2.0 * (1.0 - kb) * pb;
+		let g = (luma - kr * r - kb * b) / (1.0 - kr - kb);
+		[r, g, b].map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8)
+	}
+}
+
+fn is_rgb(format: VideoFormat) -> bool {
+	matches!(format, VideoFormat::RGB24 | VideoFormat::RGBA32)
+}
+
+// packed rgb24 from any format; subsampled chroma is taken from the covering
+// sample and alpha is dropped
+pub fn to_rgb24(frame: &FrameVideo, matrix: ColorMatrix) -> IoResult<FrameVideo> {
+	let (width, height) = (frame.width, frame.height);
+	if frame.data.len() < frame.format.frame_size(width, height) {
+		return Err(IoError::invalid_data("video frame is smaller than its format"));
+	}
+	let pixels = (width * height) as usize;
+	let data = match frame.format {
+		VideoFormat::RGB24 => frame.data[..pixels * 3].to_vec(),
+		VideoFormat::RGBA32 => {
+			frame.data.chunks_exact(4).take(pixels).flat_map(|p| [p[0], p[1], p[2]]).collect()
+		}
+		VideoFormat::GRAY8 => {
+			frame.data[..pixels].iter().flat_map(|&y| matrix.yuv_to_rgb([y, 128, 128])).collect()
+		}
+		format => {
+			let (w, h) = (width as usize, height as usize);
+			let (sub_x, sub_y) = format.chroma_subsampling();
+			let (uv_w, uv_h) = format.chroma_dimensions(width, height);
+			let (uv_w, uv_h) = (uv_w as usize, uv_h as usize);
+			let (luma, chroma) = frame.data.split_at(pixels);
+			let (u_plane, v_plane) = chroma.split_at(uv_w * uv_h);
+
+			let mut data = Vec::with_capacity(pixels * 3);
+			for y in 0..h {
+				let uv_row = (y / sub_y as usize).min(uv_h.saturating_sub(1)) * uv_w;
+				for x in 0..w {
+					let uv_idx = uv_row + (x / sub_x as usize).min(uv_w.saturating_sub(1));
+					data.extend(matrix.yuv_to_rgb([luma[y * w + x], u_plane[uv_idx], v_plane[uv_idx]]));
+				}
+			}
+			data
+		}
+	};
+	Ok(FrameVideo::new(data, width, height, VideoFormat::RGB24))
+}
+
+// planar yuv (or gray8) from packed rgb; each chroma sample averages the
+// pixels it covers
+pub fn to_yuv(
+	frame: &FrameVideo,
+	format: VideoFormat,
+	matrix: ColorMatrix,
+) -> IoResult<FrameVideo> {
+	if !is_rgb(frame.format) {
+		return Err(IoError::invalid_data("yuv conversion needs rgb input"));
+	}
+	if is_rgb(format) {
+		return Err(IoError::invalid_data("yuv conversion needs a planar target format"));
+	}
+	let (width, height) = (frame.width, frame.height);
+	if frame.data.len() < frame.format.frame_size(width, height) {
+		return Err(IoError::invalid_data("video frame is smaller than its format"));
+	}
+
+	let step = if frame.format == VideoFormat::RGB24 { 3 } else { 4 };
+	let yuv: Vec<[u8; 3]> = frame
+		.data
+		.chunks_exact(step)
+		.take((width * height) as usize)
+		.map(|p| matrix.rgb_to_yuv([p[0], p[1], p[2]]))
+		.collect();
+
+	let mut data: Vec<u8> = yuv.iter().map(|p| p[0]).collect();
+	let (uv_w, uv_h) = format.chroma_dimensions(width, height);
+	let (sub_x, sub_y) = format.chroma_subsampling();
+	for plane in [1, 2].map(|channel| yuv.iter().map(|p| p[channel]).collect::<Vec<u8>>()) {
+		for cy in 0..uv_h {
+			for cx in 0..uv_w {
+				let (mut sum, mut count) = (0u32, 0u32);
+				for y in cy * sub_y..((cy + 1) * sub_y).min(height) {
+					for x in cx * sub_x..((cx + 1) * sub_x).min(width) {
+						sum += plane[(y * width + x) as usize] as u32;
+						count += 1;
+					}
+				}
+				data.push(((sum + count / 2) / count.max(1)) as u8);
+			}
+		}
+	}
+	Ok(FrameVideo::new(data, width, height, format))
+}
+
+// moves planar yuv (or gray8) to another chroma layout: chroma is repeated
+// when upsampling and averaged when subsampling; gray8 gains neutral chroma
+pub fn convert_chroma(frame: &FrameVideo, format: VideoFormat) -> IoResult<FrameVideo> {
+	if is_rgb(frame.format) || is_rgb(format) {
+		return Err(IoError::invalid_data("chroma conversion needs planar formats"));
+	}
+	let (width, height) = (frame.width, frame.height);
+	if frame.data.len() < frame.format.frame_size(width, height) {
+		return Err(IoError::invalid_data("video frame is smaller than its format"));
+	}
+	let pixels = (width * height) as usize;
+	let mut data = frame.data[..pixels].to_vec();
+	if !format.has_chroma_planes() {
+		return Ok(FrameVideo::new(data, width, height, format));
+	}
+
+	let (src_w, src_h) = frame.format.chroma_dimensions(width, height);
+	let (src_sub_x, src_sub_y) = frame.format.chroma_subsampling();
+	let (dst_w, dst_h) = format.chroma_dimensions(width, height);
+	let (dst_sub_x, dst_sub_y) = format.chroma_subsampling();
+	let src_size = (src_w * src_h) as usize;
+	for plane in 0..2 {
+		if !frame.format.has_chroma_planes() {
+			data.resize(data.len() + (dst_w * dst_h) as usize, 128);
+			continue;
+		}
+		let src = &frame.data[pixels + plane * src_size..pixels + (plane + 1) * src_size];
+		for cy in 0..dst_h {
+			for cx in 0..dst_w {
+				// the source samples covering this destination sample's pixels
+				let x0 = (cx * dst_sub_x / src_sub_x).min(src_w - 1);
+				let y0 = (cy * dst_sub_y / src_sub_y).min(src_h - 1);
+				let x1 = ((cx + 1) * dst_sub_x).div_ceil(src_sub_x).clamp(x0 + 1, src_w);
+				let y1 = ((cy + 1) * dst_sub_y).div_ceil(src_sub_y).clamp(y0 + 1, src_h);
+				let mut sum = 0u32;
+				for y in y0..y1 {
+					for x in x0..x1 {
+						sum += src[(y * src_w + x) as usize] as u32;
+					}
+				}
+				let count = (x1 - x0) * (y1 - y0);
+				data.push(((sum + count / 2) / count) as u8);
+			}
+		}
+	}
+	Ok(FrameVideo::new(data, width, height, format))
+}
+
+// converts frames to another pixel format: packed rgb, planar yuv or gray8
+pub struct ColorConvert {
+	format: VideoFormat,
+	matrix: ColorMatrix,
+}
+
+impl ColorConvert {
+	pub fn new(format: VideoFormat) -> Self {
+		Self { format, matrix: ColorMatrix::default() }
+	}
+
+	pub fn with_matrix(mut self, matrix: ColorMatrix) -> Self {
+		self.matrix = matrix;
+		self
+	}
+}
+
+impl Transform for ColorConvert {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		if video_frame.format == self.format {
+			return Ok(frame);
+		}
+		*video_frame = match (is_rgb(video_frame.format), is_rgb(self.format)) {
+			(_, true) => {
+				let rgb = to_rgb24(video_frame, self.matrix)?;
+				if self.format == VideoFormat::RGBA32 {
+					let data = rgb.data.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect();
+					FrameVideo::new(data, rgb.width, rgb.height, VideoFormat::RGBA32)
+				} else {
+					rgb
+				}
+			}
+			(true, false) => to_yuv(video_frame, self.format, self.matrix)?,
+			(false, false) => convert_chroma(video_frame, self.format)?,
+		};
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"colorspace"
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		match input {
+			StreamFormat::Video { width, height, .. } => {
+				Ok(StreamFormat::Video { width, height, format: self.format })
+			}
+			audio => Ok(audio),
+		}
+	}
+}
@@ -1,4 +1,4 @@
-use crate::core::Frame;
+use crate::core::{Frame, Transform, VideoFormat};
 use crate::io::IoResult;
 
 pub struct Contrast {
@@ -15,15 +15,9 @@ impl Contrast {
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
 			let y_size = (self.width * self.height) as usize;
-			let _uv_size = y_size / 4;
 
 			let mut dst_data = video_frame.data.clone();
-
-			for i in 0..y_size {
-				let y = dst_data[i] as f32;
-				let adjusted = ((y - 128.0) * self.factor + 128.0).clamp(0.0, 255.0);
-				dst_data[i] = adjusted as u8;
-			}
+			scale_around_mid(&mut dst_data[..y_size], self.factor);
 
 			let new_video = crate::core::FrameVideo::new(
 				dst_data,
@@ -39,3 +33,28 @@ impl Contrast {
 		}
 	}
 }
+
+// stretches 8-bit values away from (or toward) mid grey
+pub(super) fn scale_around_mid(values: &mut [u8], factor: f32) {
+	for value in values {
+		*value = ((*value as f32 - 128.0) * factor + 128.0).round().clamp(0.0, 255.0) as u8;
+	}
+}
+
+// in a chain the luma plane is sized from each frame; packed rgb passes through
+impl Transform for Contrast {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video_mut()
+			&& (video_frame.format.has_chroma_planes() || video_frame.format == VideoFormat::GRAY8)
+		{
+			let y_size = (video_frame.width * video_frame.height) as usize;
+			let y_size = y_size.min(video_frame.data.len());
+			scale_around_mid(&mut video_frame.data[..y_size], self.factor);
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"contrast"
+	}
+}
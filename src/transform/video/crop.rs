@@ -1,5 +1,6 @@
-use crate::core::Frame;
-use crate::io::IoResult;
+use super::{planar_dimensions, planar_format};
+use crate::core::{Frame, StreamFormat, Transform};
+use crate::io::{IoError, IoResult};
 
 pub struct Crop {
 	src_width: u32,
@@ -8,6 +9,8 @@ pub struct Crop {
 	y: u32,
 	width: u32,
 	height: u32,
+	// re-centred on each frame when used as a transform
+	centered: bool,
 }
 
 impl Crop {
@@ -16,13 +19,26 @@ impl Crop {
 		let y = y.min(src_height);
 		let width = width.min(src_width - x);
 		let height = height.min(src_height - y);
-		Self { src_width, src_height, x, y, width, height }
+		Self { src_width, src_height, x, y, width, height, centered: false }
 	}
 
 	pub fn center(src_width: u32, src_height: u32, width: u32, height: u32) -> Self {
 		let x = (src_width.saturating_sub(width)) / 2;
 		let y = (src_height.saturating_sub(height)) / 2;
-		Self::new(src_width, src_height, x, y, width, height)
+		Self { centered: true, ..Self::new(src_width, src_height, x, y, width, height) }
+	}
+
+	// this crop placed on a frame of the given size
+	fn within(&self, width: u32, height: u32) -> IoResult<Crop> {
+		let crop = if self.centered {
+			Crop::center(width, height, self.width, self.height)
+		} else {
+			Crop::new(width, height, self.x, self.y, self.width, self.height)
+		};
+		if crop.width == 0 || crop.height == 0 {
+			return Err(IoError::invalid_data("crop region lies outside the frame"));
+		}
+		Ok(crop)
 	}
 
 	pub fn output_dimensions(&self) -> (u32, u32) {
@@ -31,15 +47,18 @@ impl Crop {
 
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
+			let pix_fmt = video_frame.format;
 			let src_y_size = (self.src_width * self.src_height) as usize;
-			let src_uv_size = src_y_size / 4;
+			let (src_uv_w, src_uv_h) = pix_fmt.chroma_dimensions(self.src_width, self.src_height);
+			let src_uv_size = (src_uv_w * src_uv_h) as usize;
 
 			let src_y = &video_frame.data[0..src_y_size];
 			let src_u = &video_frame.data[src_y_size..src_y_size + src_uv_size];
 			let src_v = &video_frame.data[src_y_size + src_uv_size..src_y_size + 2 * src_uv_size];
 
 			let dst_y_size = (self.width * self.height) as usize;
-			let dst_uv_size = dst_y_size / 4;
+			let (uv_w, uv_h) = pix_fmt.chroma_dimensions(self.width, self.height);
+			let dst_uv_size = (uv_w * uv_h) as usize;
 
 			let mut dst_data = vec![0u8; dst_y_size + 2 * dst_uv_size];
 			let (dst_y, dst_uv) = dst_data.split_at_mut(dst_y_size);
@@ -47,21 +66,15 @@ impl Crop {
 
 			self.crop_plane(src_y, dst_y, self.src_width, self.x, self.y, self.width, self.height);
 
-			let uv_x = self.x / 2;
-			let uv_y = self.y / 2;
-			let uv_w = self.width / 2;
-			let uv_h = self.height / 2;
-			let src_uv_w = self.src_width / 2;
+			let (sub_x, sub_y) = pix_fmt.chroma_subsampling();
+			let uv_x = self.x / sub_x;
+			let uv_y = self.y / sub_y;
 
 			self.crop_plane(src_u, dst_u, src_uv_w, uv_x, uv_y, uv_w, uv_h);
 			self.crop_plane(src_v, dst_v, src_uv_w, uv_x, uv_y, uv_w, uv_h);
 
-			let new_video = crate::core::FrameVideo::new(
-				dst_data,
-				video_frame.width,
-				video_frame.height,
-				video_frame.format,
-			);
+			let new_video =
+				crate::core::FrameVideo::new(dst_data, self.width, self.height, video_frame.format);
 			Ok(
 				Frame::new_video(new_video, frame.timebase.clone(), frame.stream_index).with_pts(frame.pts),
 			)
@@ -92,3 +105,26 @@ impl Crop {
 		}
 	}
 }
+
+// in a chain the region is clamped to each frame rather than to the size
+// given at construction
+impl Transform for Crop {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some((width, height)) = planar_dimensions(&frame)? else {
+			return Ok(frame);
+		};
+		self.within(width, height)?.apply_yuv420(&frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"crop"
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let Some((width, height, format)) = planar_format(input)? else {
+			return Ok(input);
+		};
+		let (width, height) = self.within(width, height)?.output_dimensions();
+		Ok(StreamFormat::Video { width, height, format })
+	}
+}
@@ -0,0 +1,151 @@
+use super::crop::Crop;
+use super::planar_format;
+use crate::core::{Frame, FrameVideo, SideData, StreamFormat, Transform};
+use crate::io::IoResult;
+
+// the smallest rectangle (x0, y0, x1, y1, exclusive) holding every luma row
+// and column whose average is above `limit`; None when the frame is all black
+pub fn detect_borders(frame: &FrameVideo, limit: u8) -> Option<(u32, u32, u32, u32)> {
+	let (width, height) = (frame.width as usize, frame.height as usize);
+	let luma = frame.data.get(..width * height)?;
+	let bright = |sum: u64, count: usize| sum > limit as u64 * count as u64;
+	let rows: Vec<usize> = (0..height)
+		.filter(|&y| bright(luma[y * width..(y + 1) * width].iter().map(|&v| v as u64).sum(), width))
+		.collect();
+	let columns: Vec<usize> = (0..width)
+		.filter(|&x| bright((0..height).map(|y| luma[y * width + x] as u64).sum(), height))
+		.collect();
+	let (&top, &bottom) = (rows.first()?, rows.last()?);
+	let (&left, &right) = (columns.first()?, columns.last()?);
+	Some((left as u32, top as u32, right as u32 + 1, bottom as u32 + 1))
+}
+
+// finds black borders over the first `window` frames with picture in them and
+// reports the crop that removes them. once given a crop it applies it instead
+pub struct CropDetect {
+	limit: u8,
+	round: u32,
+	window: usize,
+	seen: usize,
+	size: Option<(u32, u32)>,
+	bounds: Option<(u32, u32, u32, u32)>,
+	crop: Option<(u32, u32, u32, u32)>,
+}
+
+impl CropDetect {
+	pub fn new() -> Self {
+		Self { limit: 24, round: 2, window: 30, seen: 0, size: None, bounds: None, crop: None }
+	}
+
+	// luma average at or below which a row or column counts as black
+	pub fn with_limit(mut self, limit: u8) -> Self {
+		self.limit = limit;
+		self
+	}
+
+	// the crop's width and height are multiples of this
+	pub fn with_round(mut self, round: u32) -> Self {
+		self.round = round.max(1);
+		self
+	}
+
+	pub fn with_window(mut self, frames: usize) -> Self {
+		self.window = frames.max(1);
+		self
+	}
+
+	// x, y, width, height cropped from every frame
+	pub fn with_crop(mut self, crop: (u32, u32, u32, u32)) -> Self {
+		self.crop = Some(crop);
+		self
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.seen >= self.window
+	}
+
+	// x, y, width, height; the whole frame when nothing but black was seen,
+	// None before any frame
+	pub fn geometry(&self) -> Option<(u32, u32, u32, u32)> {
+		let (width, height) = self.size?;
+		let Some((x0, y0, x1, y1)) = self.bounds else {
+			return Some((0, 0, width, height));
+		};
+		// even offsets keep subsampled chroma aligned
+		let (x0, y0) = (x0.next_multiple_of(2), y0.next_multiple_of(2));
+		let round = |span: u32| (span / self.round * self.round).max(self.round.min(span));
+		let (w, h) = (round(x1.saturating_sub(x0)), round(y1.saturating_sub(y0)));
+		let x = x0 + (x1.saturating_sub(x0) - w) / 2 / 2 * 2;
+		let y = y0 + (y1.saturating_sub(y0) - h) / 2 / 2 * 2;
+		Some((x, y, w, h))
+	}
+
+	pub fn to_text(&self) -> String {
+		match self.geometry() {
+			Some((x, y, w, h)) => format!("cropdetect: crop={}:{}:{}:{}\n", w, h, x, y),
+			None => "cropdetect: no video\n".to_string(),
+		}
+	}
+
+	fn observe(&mut self, frame: &FrameVideo) {
+		self.size = Some((frame.width, frame.height));
+		if self.is_done() {
+			return;
+		}
+		let Some((x0, y0, x1, y1)) = detect_borders(frame, self.limit) else {
+			return;
+		};
+		self.bounds = Some(match self.bounds {
+			Some((a0, b0, a1, b1)) => (a0.min(x0), b0.min(y0), a1.max(x1), b1.max(y1)),
+			None => (x0, y0, x1, y1),
+		});
+		self.seen += 1;
+	}
+}
+
+impl Default for CropDetect {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for CropDetect {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video() else {
+			return Ok(frame);
+		};
+		match self.crop {
+			Some((x, y, w, h)) => {
+				Crop::new(video_frame.width, video_frame.height, x, y, w, h).apply_yuv420(&frame)
+			}
+			// the crop found so far rides along as the "cropdetect" region
+			None => {
+				self.observe(video_frame);
+				match self.geometry() {
+					Some((x, y, w, h)) => Ok(frame.with_metadata("cropdetect", SideData::Region(x, y, w, h))),
+					None => Ok(frame),
+				}
+			}
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		"cropdetect"
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let (Some((x, y, w, h)), Some((width, height, format))) = (self.crop, planar_format(input)?)
+		else {
+			return Ok(input);
+		};
+		let (width, height) = Crop::new(width, height, x, y, w, h).output_dimensions();
+		Ok(StreamFormat::Video { width, height, format })
+	}
+
+	fn finish(&mut self) -> IoResult<()> {
+		if self.crop.is_none() {
+			eprint!("{}", self.to_text());
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,123 @@
+use super::colorspace::ColorMatrix;
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// outlines (or fills) a rectangle given in luma pixels. `color` is a y, u, v
+// triple; chroma samples under any painted pixel take the colour, so thin
+// lines on subsampled formats bleed by up to one chroma sample.
+pub struct DrawBox {
+	x: u32,
+	y: u32,
+	width: u32,
+	height: u32,
+	color: [u8; 3],
+	// None fills the box
+	thickness: Option<u32>,
+	// a frame's region side data under this name, when set, replaces the box
+	region_from: Option<String>,
+}
+
+impl DrawBox {
+	pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+		Self { x, y, width, height, color: [235, 128, 128], thickness: Some(2), region_from: None }
+	}
+
+	// outlines whatever region an earlier transform left under `key`, e.g.
+	// "cropdetect"; frames without it are left alone
+	pub fn from_metadata(key: &str) -> Self {
+		Self { region_from: Some(key.to_string()), ..Self::new(0, 0, 0, 0) }
+	}
+
+	pub fn with_color(mut self, color: [u8; 3]) -> Self {
+		self.color = color;
+		self
+	}
+
+	pub fn with_thickness(mut self, thickness: u32) -> Self {
+		self.thickness = Some(thickness.max(1));
+		self
+	}
+
+	pub fn filled(mut self) -> Self {
+		self.thickness = None;
+		self
+	}
+
+	fn covers(&self, px: u32, py: u32) -> bool {
+		let (right, bottom) = (self.x + self.width, self.y + self.height);
+		if px < self.x || py < self.y || px >= right || py >= bottom {
+			return false;
+		}
+		match self.thickness {
+			None => true,
+			Some(t) => px < self.x + t || py < self.y + t || px + t >= right || py + t >= bottom,
+		}
+	}
+}
+
+// a colour name or RRGGBB hex, as bt.601 yuv
+pub fn parse_color(name: &str) -> Option<[u8; 3]> {
+	let hex = match name {
+		"white" => "ffffff",
+		"black" => "000000",
+		"red" => "ff0000",
+		"green" => "00ff00",
+		"blue" => "0000ff",
+		"yellow" => "ffff00",
+		other => other.trim_start_matches('#'),
+	};
+	if hex.len() != 6 {
+		return None;
+	}
+	let channel = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+	Some(ColorMatrix::Bt601.rgb_to_yuv([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+impl Transform for DrawBox {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(key) = &self.region_from {
+			let Some((x, y, width, height)) = frame.metadata.region(key) else {
+				return Ok(frame);
+			};
+			(self.x, self.y, self.width, self.height) = (x, y, width, height);
+		}
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		let format = video_frame.format;
+		if !format.has_chroma_planes() && format != VideoFormat::GRAY8 {
+			return Ok(frame);
+		}
+		let (width, height) = (video_frame.width, video_frame.height);
+		if video_frame.data.len() < format.frame_size(width, height) {
+			return Ok(frame);
+		}
+
+		let y_size = (width * height) as usize;
+		let (uv_w, uv_h) = format.chroma_dimensions(width, height);
+		let uv_size = (uv_w * uv_h) as usize;
+		let (sub_x, sub_y) = format.chroma_subsampling();
+		let (luma, chroma) = video_frame.data.split_at_mut(y_size);
+		let (u_plane, v_plane) = chroma.split_at_mut(uv_size);
+
+		for py in self.y..(self.y + self.height).min(height) {
+			for px in self.x..(self.x + self.width).min(width) {
+				if !self.covers(px, py) {
+					continue;
+				}
+				luma[(py * width + px) as usize] = self.color[0];
+				let (cx, cy) = (px / sub_x, py / sub_y);
+				if cx < uv_w && cy < uv_h {
+					let idx = (cy * uv_w + cx) as usize;
+					u_plane[idx] = self.color[1];
+					v_plane[idx] = self.color[2];
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"drawbox"
+	}
+}
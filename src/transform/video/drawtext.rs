@@ -0,0 +1,148 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// burns text into the luma plane with a built-in 5x7 font. `%{pts}` expands to
+// the frame's timestamp as HH:MM:SS.mmm and `%{n}` to its index from 0.
+// letters are drawn in upper case; characters the font lacks show as '?'.
+pub struct DrawText {
+	text: String,
+	x: u32,
+	y: u32,
+	color: u8,
+	scale: u32,
+	frame_number: u64,
+}
+
+impl DrawText {
+	pub fn new(text: impl Into<String>) -> Self {
+		Self { text: text.into(), x: 0, y: 0, color: 235, scale: 2, frame_number: 0 }
+	}
+
+	pub fn with_position(mut self, x: u32, y: u32) -> Self {
+		self.x = x;
+		self.y = y;
+		self
+	}
+
+	// luma value of the glyphs; 235 is video white
+	pub fn with_color(mut self, color: u8) -> Self {
+		self.color = color;
+		self
+	}
+
+	// each font pixel becomes a scale x scale block
+	pub fn with_scale(mut self, scale: u32) -> Self {
+		self.scale = scale.max(1);
+		self
+	}
+
+	fn expand(&self, frame: &Frame) -> String {
+		let seconds = frame.timebase.to_seconds(frame.pts).max(0.0);
+		let millis = (seconds * 1000.0).round() as u64;
+		let timecode = format!(
+			"{:02}:{:02}:{:02}.{:03}",
+			millis / 3_600_000,
+			millis / 60_000 % 60,
+			millis / 1000 % 60,
+			millis % 1000
+		);
+		self.text.replace("%{pts}", &timecode).replace("%{n}", &self.frame_number.to_string())
+	}
+}
+
+const GLYPH_WIDTH: u32 = 5;
+// one blank column between characters
+const ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+// rows top to bottom, bit 4 is the leftmost column
+fn glyph(c: char) -> [u8; 7] {
+	match c.to_ascii_uppercase() {
+		' ' => [0; 7],
+		'0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+		'1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+		'2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+		'3' => [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+		'4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+		'5' => [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+		'6' => [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+		'7' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+		'8' => [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+		'9' => [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+		'A' => [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+		'B' => [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+		'C' => [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+		'D' => [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+		'E' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+		'F' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+		'G' => [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+		'H' => [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+		'I' => [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+		'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+		'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+		'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+		'M' => [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+		'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+		'O' => [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+		'P' => [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+		'Q' => [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+		'R' => [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+		'S' => [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+		'T' => [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+		'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+		'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+		'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+		'X' => [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+		'Y' => [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+		'Z' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+		':' => [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00],
+		'.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+		'-' => [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+		'/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+		'_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f],
+		_ => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+	}
+}
+
+impl Transform for DrawText {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let text = self.expand(&frame);
+		self.frame_number += 1;
+
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		if !video_frame.format.has_chroma_planes() && video_frame.format != VideoFormat::GRAY8 {
+			return Ok(frame);
+		}
+		let (width, height) = (video_frame.width, video_frame.height);
+		if video_frame.data.len() < (width * height) as usize {
+			return Ok(frame);
+		}
+
+		let scale = self.scale;
+		for (idx, c) in text.chars().enumerate() {
+			let left = self.x + idx as u32 * ADVANCE * scale;
+			if left >= width {
+				break;
+			}
+			for (row, bits) in glyph(c).iter().enumerate() {
+				for col in 0..GLYPH_WIDTH {
+					if bits & (0x10 >> col) == 0 {
+						continue;
+					}
+					let (px, py) = (left + col * scale, self.y + row as u32 * scale);
+					for dy in py..(py + scale).min(height) {
+						for dx in px..(px + scale).min(width) {
+							video_frame.data[(dy * width + dx) as usize] = self.color;
+						}
+					}
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"drawtext"
+	}
+}
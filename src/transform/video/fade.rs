@@ -0,0 +1,77 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// ramps the picture up from black over the first `fade_in` frames and down to
+// black over the last `fade_out`. luma heads to video black and chroma to
+// neutral grey, so colour drains out with the light
+pub struct VideoFade {
+	fade_in: u64,
+	fade_out: u64,
+	total_frames: u64,
+	current_frame: u64,
+}
+
+impl VideoFade {
+	pub fn fade_in(frames: u64) -> Self {
+		Self { fade_in: frames, fade_out: 0, total_frames: 0, current_frame: 0 }
+	}
+
+	// `total_frames` is how many frames will reach the transform
+	pub fn fade_out(frames: u64, total_frames: u64) -> Self {
+		Self::fade_in(0).with_fade_out(frames, total_frames)
+	}
+
+	pub fn with_fade_out(mut self, frames: u64, total_frames: u64) -> Self {
+		self.fade_out = frames;
+		self.total_frames = total_frames;
+		self
+	}
+
+	fn gain(&self) -> f32 {
+		let n = self.current_frame;
+		let mut gain = 1.0;
+		if n < self.fade_in {
+			gain = n as f32 / self.fade_in as f32;
+		}
+		let fade_start = self.total_frames.saturating_sub(self.fade_out);
+		if self.fade_out > 0 && n >= fade_start {
+			gain = gain.min(1.0 - ((n - fade_start + 1) as f32 / self.fade_out as f32).min(1.0));
+		}
+		gain
+	}
+}
+
+impl Transform for VideoFade {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let gain = self.gain();
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		self.current_frame += 1;
+		if gain >= 1.0 {
+			return Ok(frame);
+		}
+		let toward =
+			|value: &mut u8, rest: f32| *value = (rest + (*value as f32 - rest) * gain).round() as u8;
+		match video_frame.format {
+			VideoFormat::RGB24 => video_frame.data.iter_mut().for_each(|v| toward(v, 0.0)),
+			VideoFormat::RGBA32 => {
+				for pixel in video_frame.data.chunks_exact_mut(4) {
+					pixel[..3].iter_mut().for_each(|v| toward(v, 0.0));
+				}
+			}
+			_ => {
+				let y_size =
+					((video_frame.width * video_frame.height) as usize).min(video_frame.data.len());
+				let (luma, chroma) = video_frame.data.split_at_mut(y_size);
+				luma.iter_mut().for_each(|v| toward(v, 16.0));
+				chroma.iter_mut().for_each(|v| toward(v, 128.0));
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"video_fade"
+	}
+}
@@ -1,7 +1,8 @@
-use crate::core::Frame;
+use super::{planar_dimensions, planar_format};
+use crate::core::{Frame, StreamFormat, Transform};
 use crate::io::IoResult;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FlipDirection {
 	Horizontal,
 	Vertical,
@@ -29,7 +30,8 @@ impl Flip {
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
 			let y_size = (self.width * self.height) as usize;
-			let uv_size = y_size / 4;
+			let (uv_w, uv_h) = video_frame.format.chroma_dimensions(self.width, self.height);
+			let uv_size = (uv_w * uv_h) as usize;
 
 			let src_y = &video_frame.data[0..y_size];
 			let src_u = &video_frame.data[y_size..y_size + uv_size];
@@ -41,8 +43,6 @@ impl Flip {
 
 			self.flip_plane(src_y, dst_y, self.width, self.height);
 
-			let uv_w = self.width / 2;
-			let uv_h = self.height / 2;
 			self.flip_plane(src_u, dst_u, uv_w, uv_h);
 			self.flip_plane(src_v, dst_v, uv_w, uv_h);
 
@@ -77,3 +77,25 @@ impl Flip {
 		}
 	}
 }
+
+impl Transform for Flip {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some((width, height)) = planar_dimensions(&frame)? else {
+			return Ok(frame);
+		};
+		(self.width, self.height) = (width, height);
+		self.apply_yuv420(&frame)
+	}
+
+	fn name(&self) -> &'static str {
+		match self.direction {
+			FlipDirection::Horizontal => "hflip",
+			FlipDirection::Vertical => "vflip",
+		}
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		planar_format(input)?;
+		Ok(input)
+	}
+}
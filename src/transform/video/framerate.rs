@@ -1,14 +1,18 @@
-use crate::core::Frame;
+use crate::core::{Frame, Timebase};
 use crate::io::IoResult;
 
+// frames are placed by their timestamps rather than counted, so anything that
+// retimes the stream upstream (a speed change, an edl cut) is honoured. each
+// output slot repeats the newest input frame that started at or before it.
 pub struct FrameRateConverter {
 	src_fps_num: u32,
 	src_fps_den: u32,
 	dst_fps_num: u32,
 	dst_fps_den: u32,
-	frame_count: u64,
 	output_count: u64,
 	last_frame: Option<Frame>,
+	last_time: f64,
+	last_duration: Option<f64>,
 }
 
 impl FrameRateConverter {
@@ -18,9 +22,10 @@ impl FrameRateConverter {
 			src_fps_den,
 			dst_fps_num,
 			dst_fps_den,
-			frame_count: 0,
 			output_count: 0,
 			last_frame: None,
+			last_time: 0.0,
+			last_duration: None,
 		}
 	}
 
@@ -33,47 +38,54 @@ impl FrameRateConverter {
 	}
 
 	pub fn process(&mut self, frame: Frame) -> IoResult<Vec<Frame>> {
-		let src_fps = self.src_fps_num as f64 / self.src_fps_den as f64;
-		let dst_fps = self.dst_fps_num as f64 / self.dst_fps_den as f64;
+		let time = frame.timebase.to_seconds(frame.pts);
+		let output_frames = self.fill_until(time);
 
-		let src_time = self.frame_count as f64 / src_fps;
-		let next_src_time = (self.frame_count + 1) as f64 / src_fps;
+		if self.last_frame.is_some() {
+			self.last_duration = Some(time - self.last_time);
+		}
+		self.last_time = time;
+		self.last_frame = Some(frame);
 
-		let mut output_frames = Vec::new();
+		Ok(output_frames)
+	}
 
-		loop {
-			let output_time = self.output_count as f64 / dst_fps;
+	pub fn flush(&mut self) -> IoResult<Vec<Frame>> {
+		let src_duration = self.src_fps_den as f64 / self.src_fps_num as f64;
+		let end = self.last_time + self.last_duration.unwrap_or(src_duration);
+		let output_frames = self.fill_until(end);
+		self.last_frame = None;
+		Ok(output_frames)
+	}
 
-			if output_time >= next_src_time {
-				break;
-			}
+	// output slots before the first frame are skipped, not filled
+	fn fill_until(&mut self, time: f64) -> Vec<Frame> {
+		let dst_fps = self.dst_fps_num as f64 / self.dst_fps_den as f64;
+		let timebase = Timebase::new(self.dst_fps_den, self.dst_fps_num);
+		let mut output_frames = Vec::new();
 
-			if output_time >= src_time {
-				let mut out_frame = frame.clone();
+		while (self.output_count as f64 / dst_fps) < time - TIME_EPSILON {
+			if let Some(last) = &self.last_frame {
+				// a gap in the input is skipped rather than filled with copies
+				if output_frames.len() > MAX_DUPLICATES {
+					let slot = ((time - TIME_EPSILON) * dst_fps).ceil() as u64;
+					self.output_count = slot.max(self.output_count + 1);
+					break;
+				}
+				let mut out_frame = last.clone();
 				out_frame.pts = self.output_count as i64;
+				out_frame.timebase = timebase;
 				output_frames.push(out_frame);
-				self.output_count += 1;
-			} else if output_time < src_time {
-				if let Some(ref last) = self.last_frame {
-					let mut out_frame = last.clone();
-					out_frame.pts = self.output_count as i64;
-					output_frames.push(out_frame);
-				}
-				self.output_count += 1;
-			}
-
-			if output_frames.len() > 10 {
-				break;
 			}
+			self.output_count += 1;
 		}
 
-		self.last_frame = Some(frame);
-		self.frame_count += 1;
-
-		Ok(output_frames)
-	}
-
-	pub fn flush(&mut self) -> IoResult<Vec<Frame>> {
-		Ok(Vec::new())
+		output_frames
 	}
 }
+
+// a frame that starts exactly on a slot owns it despite float rounding
+const TIME_EPSILON: f64 = 1e-9;
+
+// copies of one input frame beyond the first
+const MAX_DUPLICATES: usize = 10;
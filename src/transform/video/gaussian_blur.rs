@@ -0,0 +1,102 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// gaussian blur done as a horizontal then a vertical 1d pass, so the cost per
+// pixel grows with the kernel width rather than its area. chroma planes use a
+// sigma shrunk by their subsampling so the blur looks the same on every plane.
+pub struct GaussianBlur {
+	sigma: f32,
+}
+
+impl GaussianBlur {
+	pub fn new(sigma: f32) -> Self {
+		Self { sigma }
+	}
+}
+
+// normalized weights for offsets -r..=r, with r = ceil(3 sigma)
+fn kernel(sigma: f32) -> Vec<f32> {
+	if sigma <= 0.0 {
+		return vec![1.0];
+	}
+	let radius = (3.0 * sigma).ceil() as i32;
+	let weights: Vec<f32> =
+		(-radius..=radius).map(|x| (-(x * x) as f32 / (2.0 * sigma * sigma)).exp()).collect();
+	let total: f32 = weights.iter().sum();
+	weights.into_iter().map(|w| w / total).collect()
+}
+
+// edges repeat the outermost pixel
+fn blur_plane(plane: &mut [u8], width: usize, height: usize, sigma_x: f32, sigma_y: f32) {
+	if width == 0 || height == 0 {
+		return;
+	}
+	let horizontal = kernel(sigma_x);
+	let vertical = kernel(sigma_y);
+	let (rx, ry) = ((horizontal.len() / 2) as isize, (vertical.len() / 2) as isize);
+
+	let mut rows = vec![0f32; width * height];
+	for y in 0..height {
+		let row = &plane[y * width..(y + 1) * width];
+		for x in 0..width {
+			rows[y * width + x] = horizontal
+				.iter()
+				.enumerate()
+				.map(|(k, w)| {
+					let sx = (x as isize + k as isize - rx).clamp(0, width as isize - 1) as usize;
+					w * row[sx] as f32
+				})
+				.sum();
+		}
+	}
+
+	for y in 0..height {
+		for x in 0..width {
+			let value: f32 = vertical
+				.iter()
+				.enumerate()
+				.map(|(k, w)| {
+					let sy = (y as isize + k as isize - ry).clamp(0, height as isize - 1) as usize;
+					w * rows[sy * width + x]
+				})
+				.sum();
+			plane[y * width + x] = value.round().clamp(0.0, 255.0) as u8;
+		}
+	}
+}
+
+impl Transform for GaussianBlur {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		let format = video_frame.format;
+		if !format.has_chroma_planes() && format != VideoFormat::GRAY8 {
+			return Ok(frame);
+		}
+
+		let (width, height) = (video_frame.width as usize, video_frame.height as usize);
+		let (uv_w, uv_h) = format.chroma_dimensions(video_frame.width, video_frame.height);
+		let (uv_w, uv_h) = (uv_w as usize, uv_h as usize);
+		let (sub_x, sub_y) = format.chroma_subsampling();
+		let y_size = width * height;
+		let uv_size = uv_w * uv_h;
+		if y_size + 2 * uv_size > video_frame.data.len() {
+			return Ok(frame);
+		}
+
+		let (luma, chroma) = video_frame.data.split_at_mut(y_size);
+		blur_plane(luma, width, height, self.sigma, self.sigma);
+		if uv_size > 0 {
+			let (sigma_x, sigma_y) = (self.sigma / sub_x as f32, self.sigma / sub_y as f32);
+			for plane in chroma[..2 * uv_size].chunks_exact_mut(uv_size) {
+				blur_plane(plane, uv_w, uv_h, sigma_x, sigma_y);
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"gaussian_blur"
+	}
+}
@@ -0,0 +1,47 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// drops all colour: yuv chroma goes to neutral, packed rgb to its bt.601 luma
+pub struct Grayscale;
+
+impl Grayscale {
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+impl Default for Grayscale {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for Grayscale {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video_mut() {
+			let y_size = (video_frame.width * video_frame.height) as usize;
+			match video_frame.format {
+				VideoFormat::RGB24 | VideoFormat::RGBA32 => {
+					let step = if video_frame.format == VideoFormat::RGB24 { 3 } else { 4 };
+					for pixel in video_frame.data.chunks_exact_mut(step) {
+						let luma = 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32;
+						let luma = luma.round().min(255.0) as u8;
+						pixel[..3].fill(luma);
+					}
+				}
+				format => {
+					let (uv_w, uv_h) = format.chroma_dimensions(video_frame.width, video_frame.height);
+					let end = (y_size + 2 * (uv_w * uv_h) as usize).min(video_frame.data.len());
+					if y_size < end {
+						video_frame.data[y_size..end].fill(128);
+					}
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"grayscale"
+	}
+}
@@ -0,0 +1,125 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::{IoError, IoResult};
+
+pub type LutTable = [u8; 256];
+
+pub fn identity_table() -> LutTable {
+	std::array::from_fn(|i| i as u8)
+}
+
+// gamma above 1 brightens the mid-tones, below 1 darkens them
+pub fn gamma_table(gamma: f32) -> IoResult<LutTable> {
+	if !(gamma > 0.0 && gamma.is_finite()) {
+		return Err(IoError::invalid_data("gamma must be positive"));
+	}
+	Ok(std::array::from_fn(|i| to_u8(255.0 * (i as f32 / 255.0).powf(1.0 / gamma))))
+}
+
+// black lands on `lift` and white on `gain`, both as fractions of full scale
+pub fn lift_gain_table(lift: f32, gain: f32) -> LutTable {
+	std::array::from_fn(|i| to_u8(255.0 * (lift + i as f32 / 255.0 * (gain - lift))))
+}
+
+// straight lines between (input, output) points; inputs outside the first and
+// last point keep their outputs
+pub fn curve_table(points: &[(u8, u8)]) -> IoResult<LutTable> {
+	if points.len() < 2 {
+		return Err(IoError::invalid_data("a curve needs at least two points"));
+	}
+	if points.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+		return Err(IoError::invalid_data("curve points must have increasing inputs"));
+	}
+	Ok(std::array::from_fn(|i| {
+		let x = i as u8;
+		let upper = points.iter().position(|&(input, _)| input >= x).unwrap_or(points.len() - 1);
+		let (x1, y1) = points[upper];
+		if upper == 0 || x >= x1 {
+			return y1;
+		}
+		let (x0, y0) = points[upper - 1];
+		let t = (x - x0) as f32 / (x1 - x0) as f32;
+		to_u8(y0 as f32 + t * (y1 as f32 - y0 as f32))
+	}))
+}
+
+// `first`, then `then`
+pub fn compose_tables(first: &LutTable, then: &LutTable) -> LutTable {
+	std::array::from_fn(|i| then[first[i] as usize])
+}
+
+fn to_u8(value: f32) -> u8 {
+	value.round().clamp(0.0, 255.0) as u8
+}
+
+// per-plane lookup tables. the master table grades luma on planar frames and
+// every channel on packed rgb; a plane table (0..3 for y, u, v or r, g, b)
+// replaces it for that plane. alpha is left alone
+pub struct Lut {
+	master: LutTable,
+	planes: [Option<LutTable>; 3],
+}
+
+impl Lut {
+	pub fn new(master: LutTable) -> Self {
+		Self { master, planes: [None; 3] }
+	}
+
+	pub fn identity() -> Self {
+		Self::new(identity_table())
+	}
+
+	pub fn with_plane(mut self, plane: usize, table: LutTable) -> Self {
+		if let Some(slot) = self.planes.get_mut(plane) {
+			*slot = Some(table);
+		}
+		self
+	}
+
+	fn table(&self, plane: usize, rgb: bool) -> Option<&LutTable> {
+		match &self.planes[plane] {
+			Some(table) => Some(table),
+			None if plane == 0 || rgb => Some(&self.master),
+			None => None,
+		}
+	}
+}
+
+impl Transform for Lut {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		match video_frame.format {
+			VideoFormat::RGB24 | VideoFormat::RGBA32 => {
+				let step = if video_frame.format == VideoFormat::RGB24 { 3 } else { 4 };
+				let tables: Vec<&LutTable> = (0..3).filter_map(|plane| self.table(plane, true)).collect();
+				for pixel in video_frame.data.chunks_exact_mut(step) {
+					for (value, table) in pixel.iter_mut().zip(&tables) {
+						*value = table[*value as usize];
+					}
+				}
+			}
+			format => {
+				let y_size = (video_frame.width * video_frame.height) as usize;
+				let (uv_w, uv_h) = format.chroma_dimensions(video_frame.width, video_frame.height);
+				let uv_size = (uv_w * uv_h) as usize;
+				let len = video_frame.data.len();
+				let ranges = [0..y_size, y_size..y_size + uv_size, y_size + uv_size..y_size + 2 * uv_size];
+				for (plane, range) in ranges.into_iter().enumerate() {
+					let Some(table) = self.table(plane, false) else {
+						continue;
+					};
+					let range = range.start.min(len)..range.end.min(len);
+					for value in &mut video_frame.data[range] {
+						*value = table[*value as usize];
+					}
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"lut"
+	}
+}
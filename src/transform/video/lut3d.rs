@@ -0,0 +1,157 @@
+use super::colorspace::{ColorMatrix, to_rgb24, to_yuv};
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::{AllowAll, FileAdapter, IoError, IoResult, MediaRead, PathPolicy};
+
+// a 65 point cube is about 8 MiB of text; this leaves room for finer ones
+// without reading whatever a path happens to point at into memory
+const MAX_CUBE_BYTES: usize = 64 << 20;
+
+// a 3D colour lookup table as found in .cube files. yuv frames go through rgb
+// and back, so grading a camera log profile costs two colour conversions
+pub struct Lut3D {
+	size: usize,
+	domain_min: [f32; 3],
+	domain_max: [f32; 3],
+	// red varies fastest, then green, then blue
+	table: Vec<[f32; 3]>,
+	matrix: ColorMatrix,
+}
+
+impl Lut3D {
+	pub fn load(path: &str) -> IoResult<Self> {
+		Self::load_with(path, &AllowAll)
+	}
+
+	// opens the file through `policy`, so a rooted server cannot read a cube
+	// from outside its root
+	pub fn load_with(path: &str, policy: &dyn PathPolicy) -> IoResult<Self> {
+		let mut file = FileAdapter::open_with(path, policy)?;
+		let (mut data, mut buf) = (Vec::new(), [0u8; 64 * 1024]);
+		loop {
+			let n = file.read(&mut buf)?;
+			if n == 0 {
+				break;
+			}
+			data.extend_from_slice(&buf[..n]);
+			if data.len() > MAX_CUBE_BYTES {
+				return Err(IoError::invalid_data(".cube file is larger than 64 MiB"));
+			}
+		}
+		let text =
+			String::from_utf8(data).map_err(|_| IoError::invalid_data(".cube file is not text"))?;
+		Self::parse(&text)
+	}
+
+	pub fn parse(text: &str) -> IoResult<Self> {
+		let invalid = || IoError::invalid_data("malformed .cube line");
+		let (mut size, mut domain_min, mut domain_max) = (0, [0.0; 3], [1.0; 3]);
+		let mut table = Vec::new();
+		for line in text.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			let triple = |values: &[&str]| -> IoResult<[f32; 3]> {
+				match values {
+					[r, g, b] => Ok([
+						r.parse().map_err(|_| invalid())?,
+						g.parse().map_err(|_| invalid())?,
+						b.parse().map_err(|_| invalid())?,
+					]),
+					_ => Err(invalid()),
+				}
+			};
+			match fields[0] {
+				"TITLE" => {}
+				"LUT_1D_SIZE" => return Err(IoError::invalid_data("1D .cube files are not supported")),
+				"LUT_3D_SIZE" => size = fields.get(1).and_then(|f| f.parse().ok()).ok_or_else(invalid)?,
+				"DOMAIN_MIN" => domain_min = triple(&fields[1..])?,
+				"DOMAIN_MAX" => domain_max = triple(&fields[1..])?,
+				_ => table.push(triple(&fields)?),
+			}
+		}
+
+		if !(2..=256).contains(&size) {
+			return Err(IoError::invalid_data(".cube needs a LUT_3D_SIZE from 2 to 256"));
+		}
+		if table.len() != size * size * size {
+			return Err(IoError::invalid_data(".cube entry count does not match LUT_3D_SIZE"));
+		}
+		if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+			return Err(IoError::invalid_data(".cube domain max must exceed its min"));
+		}
+		Ok(Self { size, domain_min, domain_max, table, matrix: ColorMatrix::default() })
+	}
+
+	pub fn with_matrix(mut self, matrix: ColorMatrix) -> Self {
+		self.matrix = matrix;
+		self
+	}
+
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	// trilinear interpolation between the eight surrounding entries
+	pub fn lookup(&self, rgb: [u8; 3]) -> [u8; 3] {
+		let last = (self.size - 1) as f32;
+		let mut base = [0usize; 3];
+		let mut frac = [0f32; 3];
+		for c in 0..3 {
+			let value = rgb[c] as f32 / 255.0;
+			let position = (value - self.domain_min[c]) / (self.domain_max[c] - self.domain_min[c]);
+			let position = position.clamp(0.0, 1.0) * last;
+			base[c] = (position.floor() as usize).min(self.size - 2);
+			frac[c] = position - base[c] as f32;
+		}
+
+		let entry = |r: usize, g: usize, b: usize| self.table[(b * self.size + g) * self.size + r];
+		let mut out = [0f32; 3];
+		for corner in 0..8 {
+			let (dr, dg, db) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+			let weight = [dr, dg, db]
+				.iter()
+				.zip(frac)
+				.map(|(&d, f)| if d == 1 { f } else { 1.0 - f })
+				.product::<f32>();
+			if weight == 0.0 {
+				continue;
+			}
+			let value = entry(base[0] + dr, base[1] + dg, base[2] + db);
+			for c in 0..3 {
+				out[c] += weight * value[c];
+			}
+		}
+		out.map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8)
+	}
+
+	fn grade_rgb(&self, data: &mut [u8], step: usize) {
+		for pixel in data.chunks_exact_mut(step) {
+			let graded = self.lookup([pixel[0], pixel[1], pixel[2]]);
+			pixel[..3].copy_from_slice(&graded);
+		}
+	}
+}
+
+impl Transform for Lut3D {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		match video_frame.format {
+			VideoFormat::RGB24 => self.grade_rgb(&mut video_frame.data, 3),
+			VideoFormat::RGBA32 => self.grade_rgb(&mut video_frame.data, 4),
+			format => {
+				let mut rgb = to_rgb24(video_frame, self.matrix)?;
+				self.grade_rgb(&mut rgb.data, 3);
+				// gray frames keep only the luma of the grade
+				*video_frame = to_yuv(&rgb, format, self.matrix)?;
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"lut3d"
+	}
+}
@@ -1,19 +1,85 @@
+use crate::core::{Frame, StreamFormat, VideoFormat};
+use crate::io::{IoError, IoResult};
+
 pub mod blur;
 pub mod brightness;
+pub mod colorspace;
 pub mod contrast;
 pub mod crop;
+pub mod cropdetect;
+pub mod drawbox;
+pub mod drawtext;
+pub mod fade;
 pub mod flip;
 pub mod framerate;
+pub mod gaussian_blur;
+pub mod grayscale;
+pub mod lut;
+pub mod lut3d;
+pub mod negate;
+pub mod overlay;
 pub mod pad;
+pub mod pixelate;
 pub mod rotate;
+pub mod saturation;
 pub mod scale;
+pub mod sharpen;
+pub mod stabilize;
+pub mod tblend;
+pub mod vignette;
 
 pub use blur::Blur;
 pub use brightness::Brightness;
+pub use colorspace::{ColorConvert, ColorMatrix};
 pub use contrast::Contrast;
 pub use crop::Crop;
+pub use cropdetect::CropDetect;
+pub use drawbox::DrawBox;
+pub use drawtext::DrawText;
+pub use fade::VideoFade;
 pub use flip::{Flip, FlipDirection};
 pub use framerate::FrameRateConverter;
+pub use gaussian_blur::GaussianBlur;
+pub use grayscale::Grayscale;
+pub use lut::Lut;
+pub use lut3d::Lut3D;
+pub use negate::Negate;
+pub use overlay::Overlay;
 pub use pad::Pad;
+pub use pixelate::Pixelate;
 pub use rotate::{Rotate, RotateAngle};
-pub use scale::{Scale, ScaleMode};
+pub use saturation::Saturation;
+pub use scale::{Scale, ScaleFit, ScaleMode};
+pub use sharpen::Sharpen;
+pub use stabilize::{MotionEstimator, Stabilize};
+pub use tblend::Tblend;
+pub use vignette::Vignette;
+
+// dimensions of a planar video frame for the geometry transforms, which walk
+// the frame plane by plane. audio frames yield none; packed rgb or a short
+// buffer is an error rather than a panic on slicing
+pub(crate) fn planar_dimensions(frame: &Frame) -> IoResult<Option<(u32, u32)>> {
+	let Some(video_frame) = frame.video() else {
+		return Ok(None);
+	};
+	let format = video_frame.format;
+	if !format.has_chroma_planes() && format != VideoFormat::GRAY8 {
+		return Err(IoError::invalid_data("geometry transforms need planar yuv or gray frames"));
+	}
+	if video_frame.data.len() < format.frame_size(video_frame.width, video_frame.height) {
+		return Err(IoError::invalid_data("video frame is shorter than its dimensions"));
+	}
+	Ok(Some((video_frame.width, video_frame.height)))
+}
+
+// `planar_dimensions` for a negotiated format: none for audio, an error for
+// packed rgb
+pub(crate) fn planar_format(input: StreamFormat) -> IoResult<Option<(u32, u32, VideoFormat)>> {
+	let StreamFormat::Video { width, height, format } = input else {
+		return Ok(None);
+	};
+	if !format.has_chroma_planes() && format != VideoFormat::GRAY8 {
+		return Err(IoError::invalid_data("geometry transforms need planar yuv or gray frames"));
+	}
+	Ok(Some((width, height, format)))
+}
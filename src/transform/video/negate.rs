@@ -0,0 +1,49 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// photographic negative. luma and rgb are inverted; chroma is mirrored around
+// 128 so neutral greys stay neutral, and alpha is left alone
+pub struct Negate;
+
+impl Negate {
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+impl Default for Negate {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for Negate {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video_mut() {
+			let y_size = (video_frame.width * video_frame.height) as usize;
+			match video_frame.format {
+				VideoFormat::RGB24 => video_frame.data.iter_mut().for_each(|v| *v = 255 - *v),
+				VideoFormat::RGBA32 => {
+					for pixel in video_frame.data.chunks_exact_mut(4) {
+						pixel[..3].iter_mut().for_each(|v| *v = 255 - *v);
+					}
+				}
+				format => {
+					let (uv_w, uv_h) = format.chroma_dimensions(video_frame.width, video_frame.height);
+					let y_end = y_size.min(video_frame.data.len());
+					let end = (y_size + 2 * (uv_w * uv_h) as usize).min(video_frame.data.len());
+					let (luma, rest) = video_frame.data.split_at_mut(y_end);
+					luma.iter_mut().for_each(|v| *v = 255 - *v);
+					for v in &mut rest[..end - y_end] {
+						*v = (256 - *v as u16).min(255) as u8;
+					}
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"negate"
+	}
+}
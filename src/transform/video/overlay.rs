@@ -0,0 +1,101 @@
+use crate::core::{Frame, FrameVideo, Transform, VideoFormat};
+use crate::io::{IoError, IoResult};
+
+type OverlaySource = Box<dyn FnMut() -> IoResult<Option<FrameVideo>> + Send>;
+
+// composites a second picture onto every frame with its top-left corner at
+// (x, y), blended at `alpha` (1 covers the frame, 0 leaves it alone). the
+// overlay advances one frame per input frame and its last frame stays up once
+// it runs out, so a single-frame source works as a still watermark. both
+// inputs must share a planar pixel format; anything past the edge is cropped.
+pub struct Overlay {
+	source: OverlaySource,
+	current: Option<FrameVideo>,
+	x: u32,
+	y: u32,
+	alpha: f32,
+}
+
+impl Overlay {
+	pub fn new(source: impl FnMut() -> IoResult<Option<FrameVideo>> + Send + 'static) -> Self {
+		Self { source: Box::new(source), current: None, x: 0, y: 0, alpha: 1.0 }
+	}
+
+	pub fn still(image: FrameVideo) -> Self {
+		let mut image = Some(image);
+		Self::new(move || Ok(image.take()))
+	}
+
+	pub fn with_position(mut self, x: u32, y: u32) -> Self {
+		self.x = x;
+		self.y = y;
+		self
+	}
+
+	pub fn with_alpha(mut self, alpha: f32) -> Self {
+		self.alpha = alpha.clamp(0.0, 1.0);
+		self
+	}
+}
+
+// (offset, width, height) of each plane in a planar frame
+fn planes(format: VideoFormat, width: u32, height: u32) -> Vec<(usize, u32, u32)> {
+	let y_size = (width * height) as usize;
+	if !format.has_chroma_planes() {
+		return vec![(0, width, height)];
+	}
+	let (uv_w, uv_h) = format.chroma_dimensions(width, height);
+	let uv_size = (uv_w * uv_h) as usize;
+	vec![(0, width, height), (y_size, uv_w, uv_h), (y_size + uv_size, uv_w, uv_h)]
+}
+
+impl Transform for Overlay {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		if let Some(next) = (self.source)()? {
+			self.current = Some(next);
+		}
+		let Some(overlay) = &self.current else {
+			return Ok(frame);
+		};
+
+		let format = video_frame.format;
+		if overlay.format != format || !(format.has_chroma_planes() || format == VideoFormat::GRAY8) {
+			return Err(IoError::invalid_data("overlay needs the same planar pixel format as the input"));
+		}
+		if overlay.data.len() < format.frame_size(overlay.width, overlay.height)
+			|| video_frame.data.len() < format.frame_size(video_frame.width, video_frame.height)
+		{
+			return Err(IoError::invalid_data("overlay frame is shorter than its dimensions"));
+		}
+
+		let (sub_x, sub_y) = format.chroma_subsampling();
+		let dst_planes = planes(format, video_frame.width, video_frame.height);
+		let src_planes = planes(format, overlay.width, overlay.height);
+		for (idx, (&(dst_off, dst_w, dst_h), &(src_off, src_w, src_h))) in
+			dst_planes.iter().zip(&src_planes).enumerate()
+		{
+			let (x, y) = if idx == 0 { (self.x, self.y) } else { (self.x / sub_x, self.y / sub_y) };
+			if x >= dst_w || y >= dst_h {
+				continue;
+			}
+			let cols = src_w.min(dst_w - x) as usize;
+			for row in 0..src_h.min(dst_h - y) as usize {
+				let src_start = src_off + row * src_w as usize;
+				let dst_start = dst_off + (y as usize + row) * dst_w as usize + x as usize;
+				let src = &overlay.data[src_start..src_start + cols];
+				let dst = &mut video_frame.data[dst_start..dst_start + cols];
+				for (d, &s) in dst.iter_mut().zip(src) {
+					*d = (s as f32 * self.alpha + *d as f32 * (1.0 - self.alpha)).round() as u8;
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"overlay"
+	}
+}
@@ -1,5 +1,6 @@
-use crate::core::Frame;
-use crate::io::IoResult;
+use super::{planar_dimensions, planar_format};
+use crate::core::{Frame, StreamFormat, Transform};
+use crate::io::{IoError, IoResult};
 
 pub struct Pad {
 	src_width: u32,
@@ -11,6 +12,8 @@ pub struct Pad {
 	fill_y: u8,
 	fill_u: u8,
 	fill_v: u8,
+	// re-centred on each frame when used as a transform
+	centered: bool,
 }
 
 impl Pad {
@@ -32,13 +35,14 @@ impl Pad {
 			fill_y: 16,
 			fill_u: 128,
 			fill_v: 128,
+			centered: false,
 		}
 	}
 
 	pub fn center(src_width: u32, src_height: u32, dst_width: u32, dst_height: u32) -> Self {
 		let x = (dst_width.saturating_sub(src_width)) / 2;
 		let y = (dst_height.saturating_sub(src_height)) / 2;
-		Self::new(src_width, src_height, dst_width, dst_height, x, y)
+		Self { centered: true, ..Self::new(src_width, src_height, dst_width, dst_height, x, y) }
 	}
 
 	pub fn with_color(mut self, y: u8, u: u8, v: u8) -> Self {
@@ -58,15 +62,18 @@ impl Pad {
 
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
+			let pix_fmt = video_frame.format;
 			let src_y_size = (self.src_width * self.src_height) as usize;
-			let src_uv_size = src_y_size / 4;
+			let (src_uv_w, src_uv_h) = pix_fmt.chroma_dimensions(self.src_width, self.src_height);
+			let src_uv_size = (src_uv_w * src_uv_h) as usize;
 
 			let src_y = &video_frame.data[0..src_y_size];
 			let src_u = &video_frame.data[src_y_size..src_y_size + src_uv_size];
 			let src_v = &video_frame.data[src_y_size + src_uv_size..src_y_size + 2 * src_uv_size];
 
 			let dst_y_size = (self.dst_width * self.dst_height) as usize;
-			let dst_uv_size = dst_y_size / 4;
+			let (dst_uv_w, dst_uv_h) = pix_fmt.chroma_dimensions(self.dst_width, self.dst_height);
+			let dst_uv_size = (dst_uv_w * dst_uv_h) as usize;
 
 			let mut dst_data = vec![self.fill_y; dst_y_size + 2 * dst_uv_size];
 
@@ -87,22 +94,16 @@ impl Pad {
 					self.y,
 				);
 
-				let src_uv_w = self.src_width / 2;
-				let src_uv_h = self.src_height / 2;
-				let dst_uv_w = self.dst_width / 2;
-				let uv_x = self.x / 2;
-				let uv_y = self.y / 2;
+				let (sub_x, sub_y) = pix_fmt.chroma_subsampling();
+				let uv_x = self.x / sub_x;
+				let uv_y = self.y / sub_y;
 
 				self.copy_plane(src_u, dst_u, src_uv_w, src_uv_h, dst_uv_w, uv_x, uv_y);
 				self.copy_plane(src_v, dst_v, src_uv_w, src_uv_h, dst_uv_w, uv_x, uv_y);
 			}
 
-			let new_video = crate::core::FrameVideo::new(
-				dst_data,
-				video_frame.width,
-				video_frame.height,
-				video_frame.format,
-			);
+			let new_video =
+				crate::core::FrameVideo::new(dst_data, self.dst_width, self.dst_height, video_frame.format);
 			Ok(
 				Frame::new_video(new_video, frame.timebase.clone(), frame.stream_index).with_pts(frame.pts),
 			)
@@ -133,3 +134,39 @@ impl Pad {
 		}
 	}
 }
+
+impl Pad {
+	// this pad placed around a frame of the given size
+	fn around(&self, width: u32, height: u32) -> IoResult<Pad> {
+		let (x, y) = if self.centered {
+			((self.dst_width.saturating_sub(width)) / 2, (self.dst_height.saturating_sub(height)) / 2)
+		} else {
+			(self.x, self.y)
+		};
+		if x + width > self.dst_width || y + height > self.dst_height {
+			return Err(IoError::invalid_data("pad size must hold the whole frame"));
+		}
+		Ok(Pad { src_width: width, src_height: height, x, y, ..*self })
+	}
+}
+
+impl Transform for Pad {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some((width, height)) = planar_dimensions(&frame)? else {
+			return Ok(frame);
+		};
+		self.around(width, height)?.apply_yuv420(&frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"pad"
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let Some((width, height, format)) = planar_format(input)? else {
+			return Ok(input);
+		};
+		let pad = self.around(width, height)?;
+		Ok(StreamFormat::Video { width: pad.dst_width, height: pad.dst_height, format })
+	}
+}
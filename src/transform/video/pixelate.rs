@@ -0,0 +1,96 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// mosaic: every block of `block` x `block` pixels takes its average, e.g. to
+// mask a face or a screen. blocks are aligned to the region's top-left corner
+// and clipped at its edges
+pub struct Pixelate {
+	block: u32,
+	// x, y, width, height in luma pixels; None covers the whole frame
+	region: Option<(u32, u32, u32, u32)>,
+}
+
+impl Pixelate {
+	pub fn new(block: u32) -> Self {
+		Self { block: block.max(1), region: None }
+	}
+
+	pub fn with_region(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+		self.region = Some((x, y, width, height));
+		self
+	}
+}
+
+// averages blocks of a single-channel plane, or of one channel of packed
+// pixels when `step` is above 1
+fn pixelate_plane(
+	data: &mut [u8],
+	plane_w: u32,
+	plane_h: u32,
+	step: usize,
+	region: (u32, u32, u32, u32),
+	block: (u32, u32),
+) {
+	let (x0, y0) = (region.0.min(plane_w), region.1.min(plane_h));
+	let x1 = region.0.saturating_add(region.2).min(plane_w);
+	let y1 = region.1.saturating_add(region.3).min(plane_h);
+	let index = |x: u32, y: u32| (y * plane_w + x) as usize * step;
+	for by in (y0..y1).step_by(block.1.max(1) as usize) {
+		for bx in (x0..x1).step_by(block.0.max(1) as usize) {
+			let (ex, ey) = ((bx + block.0).min(x1), (by + block.1).min(y1));
+			let count = (ex - bx) * (ey - by);
+			let sum: u32 = (by..ey)
+				.flat_map(|y| (bx..ex).map(move |x| (x, y)))
+				.map(|(x, y)| data[index(x, y)] as u32)
+				.sum();
+			let average = ((sum + count / 2) / count) as u8;
+			for y in by..ey {
+				for x in bx..ex {
+					data[index(x, y)] = average;
+				}
+			}
+		}
+	}
+}
+
+impl Transform for Pixelate {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		let (width, height, format) = (video_frame.width, video_frame.height, video_frame.format);
+		if video_frame.data.len() < format.frame_size(width, height) {
+			return Ok(frame);
+		}
+		let region = self.region.unwrap_or((0, 0, width, height));
+		let block = (self.block, self.block);
+		match format {
+			VideoFormat::RGB24 | VideoFormat::RGBA32 => {
+				let step = if format == VideoFormat::RGB24 { 3 } else { 4 };
+				for channel in 0..3 {
+					pixelate_plane(&mut video_frame.data[channel..], width, height, step, region, block);
+				}
+			}
+			_ => {
+				let y_size = (width * height) as usize;
+				let (luma, chroma) = video_frame.data.split_at_mut(y_size);
+				pixelate_plane(luma, width, height, 1, region, block);
+
+				let (uv_w, uv_h) = format.chroma_dimensions(width, height);
+				let (sub_x, sub_y) = format.chroma_subsampling();
+				let uv_size = (uv_w * uv_h) as usize;
+				let (x, y, w, h) = region;
+				let uv_region = (x / sub_x, y / sub_y, w.div_ceil(sub_x), h.div_ceil(sub_y));
+				let uv_block = (self.block.div_ceil(sub_x), self.block.div_ceil(sub_y));
+				for plane in chroma[..2 * uv_size].chunks_exact_mut(uv_size.max(1)) {
+					pixelate_plane(plane, uv_w, uv_h, 1, uv_region, uv_block);
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"pixelate"
+	}
+}
@@ -1,7 +1,9 @@
-use crate::core::Frame;
-use crate::io::IoResult;
+use super::{planar_dimensions, planar_format};
+use crate::core::VideoFormat;
+use crate::core::{Frame, StreamFormat, Transform};
+use crate::io::{IoError, IoResult};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RotateAngle {
 	Rotate90,
 	Rotate180,
@@ -40,8 +42,15 @@ impl Rotate {
 
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
+			let pix_fmt = video_frame.format;
+			let quarter_turn = !matches!(self.angle, RotateAngle::Rotate180);
+			if quarter_turn && pix_fmt == VideoFormat::YUV422 {
+				return Err(IoError::invalid_data("90/270 degree rotation does not support yuv422p"));
+			}
+
 			let y_size = (self.width * self.height) as usize;
-			let uv_size = y_size / 4;
+			let (src_uv_w, src_uv_h) = pix_fmt.chroma_dimensions(self.width, self.height);
+			let uv_size = (src_uv_w * src_uv_h) as usize;
 
 			let src_y = &video_frame.data[0..y_size];
 			let src_u = &video_frame.data[y_size..y_size + uv_size];
@@ -49,7 +58,7 @@ impl Rotate {
 
 			let (dst_w, dst_h) = self.output_dimensions();
 			let dst_y_size = (dst_w * dst_h) as usize;
-			let dst_uv_size = dst_y_size / 4;
+			let dst_uv_size = uv_size;
 
 			let mut dst_data = vec![0u8; dst_y_size + 2 * dst_uv_size];
 			let (dst_y, dst_uv) = dst_data.split_at_mut(dst_y_size);
@@ -57,17 +66,10 @@ impl Rotate {
 
 			self.rotate_plane(src_y, dst_y, self.width, self.height);
 
-			let src_uv_w = self.width / 2;
-			let src_uv_h = self.height / 2;
 			self.rotate_plane(src_u, dst_u, src_uv_w, src_uv_h);
 			self.rotate_plane(src_v, dst_v, src_uv_w, src_uv_h);
 
-			let new_video = crate::core::FrameVideo::new(
-				dst_data,
-				video_frame.width,
-				video_frame.height,
-				video_frame.format,
-			);
+			let new_video = crate::core::FrameVideo::new(dst_data, dst_w, dst_h, video_frame.format);
 			Ok(
 				Frame::new_video(new_video, frame.timebase.clone(), frame.stream_index).with_pts(frame.pts),
 			)
@@ -99,3 +101,28 @@ impl Rotate {
 		}
 	}
 }
+
+impl Transform for Rotate {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some((width, height)) = planar_dimensions(&frame)? else {
+			return Ok(frame);
+		};
+		(self.width, self.height) = (width, height);
+		self.apply_yuv420(&frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"rotate"
+	}
+
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let Some((width, height, format)) = planar_format(input)? else {
+			return Ok(input);
+		};
+		if self.angle != RotateAngle::Rotate180 && format == VideoFormat::YUV422 {
+			return Err(IoError::invalid_data("90/270 degree rotation does not support yuv422p"));
+		}
+		let (width, height) = Rotate::new(width, height, self.angle).output_dimensions();
+		Ok(StreamFormat::Video { width, height, format })
+	}
+}
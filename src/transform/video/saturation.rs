@@ -0,0 +1,57 @@
+use super::contrast::scale_around_mid;
+use crate::core::{Frame, Transform};
+use crate::io::IoResult;
+
+// scales the chroma planes around neutral; 0 gives greyscale
+pub struct Saturation {
+	factor: f32,
+	width: u32,
+	height: u32,
+}
+
+impl Saturation {
+	pub fn new(width: u32, height: u32, factor: f32) -> Self {
+		Self { factor, width, height }
+	}
+
+	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video() {
+			let y_size = (self.width * self.height) as usize;
+			let uv_size = y_size / 4;
+
+			let mut dst_data = video_frame.data.clone();
+			scale_around_mid(&mut dst_data[y_size..y_size + 2 * uv_size], self.factor);
+
+			let new_video = crate::core::FrameVideo::new(
+				dst_data,
+				video_frame.width,
+				video_frame.height,
+				video_frame.format,
+			);
+			Ok(Frame::new_video(new_video, frame.timebase, frame.stream_index).with_pts(frame.pts))
+		} else {
+			Ok(frame.clone())
+		}
+	}
+}
+
+// in a chain the planes are sized from each frame; formats without chroma
+// planes pass through
+impl Transform for Saturation {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video_mut() {
+			let y_size = (video_frame.width * video_frame.height) as usize;
+			let (uv_w, uv_h) =
+				video_frame.format.chroma_dimensions(video_frame.width, video_frame.height);
+			let end = (y_size + 2 * (uv_w * uv_h) as usize).min(video_frame.data.len());
+			if y_size < end {
+				scale_around_mid(&mut video_frame.data[y_size..end], self.factor);
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"saturation"
+	}
+}
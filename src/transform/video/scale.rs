@@ -1,23 +1,43 @@
-use crate::core::Frame;
+use super::{Crop, Pad, planar_dimensions, planar_format};
+use crate::core::{Frame, StreamFormat, Transform, VideoFormat};
 use crate::io::IoResult;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ScaleMode {
 	NearestNeighbor,
 	Bilinear,
 }
 
+// how the source aspect ratio meets the target size. `Fit` scales to fit
+// inside and letterboxes the rest in black; `Fill` scales to cover and crops
+// the overflow from the centre
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScaleFit {
+	Stretch,
+	Fit,
+	Fill,
+}
+
+#[derive(Clone, Copy)]
 pub struct Scale {
 	target_width: u32,
 	target_height: u32,
 	src_width: u32,
 	src_height: u32,
 	mode: ScaleMode,
+	fit: ScaleFit,
 }
 
 impl Scale {
 	pub fn new(src_width: u32, src_height: u32, target_width: u32, target_height: u32) -> Self {
-		Self { target_width, target_height, src_width, src_height, mode: ScaleMode::Bilinear }
+		Self {
+			target_width,
+			target_height,
+			src_width,
+			src_height,
+			mode: ScaleMode::Bilinear,
+			fit: ScaleFit::Stretch,
+		}
 	}
 
 	pub fn with_mode(mut self, mode: ScaleMode) -> Self {
@@ -25,13 +45,68 @@ impl Scale {
 		self
 	}
 
+	pub fn with_fit(mut self, fit: ScaleFit) -> Self {
+		self.fit = fit;
+		self
+	}
+
+	// size the picture is resampled to before any pad or crop, kept to whole
+	// chroma samples so the planes stay aligned
+	pub fn scaled_dimensions(&self, format: VideoFormat) -> (u32, u32) {
+		let (target_w, target_h) = (self.target_width, self.target_height);
+		if self.fit == ScaleFit::Stretch || self.src_width == 0 || self.src_height == 0 {
+			return (target_w, target_h);
+		}
+		let ratio_w = target_w as f64 / self.src_width as f64;
+		let ratio_h = target_h as f64 / self.src_height as f64;
+		let (sub_x, sub_y) = format.chroma_subsampling();
+		if self.fit == ScaleFit::Fit {
+			let ratio = ratio_w.min(ratio_h);
+			let fit = |src: u32, target: u32, sub: u32| {
+				let size = ((src as f64 * ratio).round() as u32).min(target);
+				(size / sub * sub).max(sub).min(target)
+			};
+			(fit(self.src_width, target_w, sub_x), fit(self.src_height, target_h, sub_y))
+		} else {
+			let ratio = ratio_w.max(ratio_h);
+			let fill = |src: u32, target: u32, sub: u32| {
+				((src as f64 * ratio).round() as u32).max(target).div_ceil(sub) * sub
+			};
+			(fill(self.src_width, target_w, sub_x), fill(self.src_height, target_h, sub_y))
+		}
+	}
+
 	pub fn apply_yuv420(&self, frame: &Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video() else {
+			return Ok(frame.clone());
+		};
+		let (width, height) = self.scaled_dimensions(video_frame.format);
+		let (target_w, target_h) = (self.target_width, self.target_height);
+		if (width, height) == (target_w, target_h) {
+			return self.resample(frame);
+		}
+		let inner = Scale { target_width: width, target_height: height, ..*self };
+		let scaled = inner.resample(frame)?;
+		// offsets are centred, then kept on a chroma sample boundary
+		let (sub_x, sub_y) = video_frame.format.chroma_subsampling();
+		let x = width.abs_diff(target_w) / 2 / sub_x * sub_x;
+		let y = height.abs_diff(target_h) / 2 / sub_y * sub_y;
+		match self.fit {
+			ScaleFit::Fill => Crop::new(width, height, x, y, target_w, target_h).apply_yuv420(&scaled),
+			_ => Pad::new(width, height, target_w, target_h, x, y).with_black().apply_yuv420(&scaled),
+		}
+	}
+
+	fn resample(&self, frame: &Frame) -> IoResult<Frame> {
 		if let Some(video_frame) = frame.video() {
+			let pix_fmt = video_frame.format;
 			let src_y_size = (self.src_width * self.src_height) as usize;
-			let src_uv_size = src_y_size / 4;
+			let (src_uv_w, src_uv_h) = pix_fmt.chroma_dimensions(self.src_width, self.src_height);
+			let src_uv_size = (src_uv_w * src_uv_h) as usize;
 
 			let dst_y_size = (self.target_width * self.target_height) as usize;
-			let dst_uv_size = dst_y_size / 4;
+			let (dst_uv_w, dst_uv_h) = pix_fmt.chroma_dimensions(self.target_width, self.target_height);
+			let dst_uv_size = (dst_uv_w * dst_uv_h) as usize;
 
 			let src_y = &video_frame.data[0..src_y_size];
 			let src_u = &video_frame.data[src_y_size..src_y_size + src_uv_size];
@@ -50,18 +125,13 @@ impl Scale {
 				self.target_height,
 			);
 
-			let src_uv_w = self.src_width / 2;
-			let src_uv_h = self.src_height / 2;
-			let dst_uv_w = self.target_width / 2;
-			let dst_uv_h = self.target_height / 2;
-
 			self.scale_plane(src_u, dst_u, src_uv_w, src_uv_h, dst_uv_w, dst_uv_h);
 			self.scale_plane(src_v, dst_v, src_uv_w, src_uv_h, dst_uv_w, dst_uv_h);
 
 			let new_video = crate::core::FrameVideo::new(
 				dst_data,
-				video_frame.width,
-				video_frame.height,
+				self.target_width,
+				self.target_height,
 				video_frame.format,
 			);
 			Ok(
@@ -113,6 +183,9 @@ impl Scale {
 		}
 	}
 
+	// fixed point with 8-bit weights. each source row the output needs is
+	// resampled across once into a u16 buffer, and output rows blend two of
+	// those buffers in a straight loop the compiler can vectorize
 	fn scale_bilinear(
 		&self,
 		src: &[u8],
@@ -122,41 +195,85 @@ impl Scale {
 		dst_w: u32,
 		dst_h: u32,
 	) {
-		let x_ratio = (src_w as f64 - 1.0) / (dst_w as f64 - 1.0).max(1.0);
-		let y_ratio = (src_h as f64 - 1.0) / (dst_h as f64 - 1.0).max(1.0);
+		let (src_w, src_h) = (src_w as usize, src_h as usize);
+		let (dst_w, dst_h) = (dst_w as usize, dst_h as usize);
+		if src_w == 0 || src_h == 0 || src.len() < src_w * src_h || dst.len() < dst_w * dst_h {
+			return;
+		}
+		// a 4:2:0 frame one pixel across has no chroma to fill
+		if dst_w == 0 || dst_h == 0 {
+			return;
+		}
+		let columns = bilinear_taps(src_w, dst_w);
+		let rows = bilinear_taps(src_h, dst_h);
 
-		for y in 0..dst_h {
-			for x in 0..dst_w {
-				let src_x = x as f64 * x_ratio;
-				let src_y = y as f64 * y_ratio;
+		let resample_row = |row: usize, out: &mut Vec<u16>| {
+			let line = &src[row * src_w..(row + 1) * src_w];
+			out.clear();
+			out.extend(
+				columns
+					.iter()
+					.map(|&(x0, x1, frac)| line[x0] as u16 * (256 - frac) + line[x1] as u16 * frac),
+			);
+		};
 
-				let x0 = src_x.floor() as u32;
-				let y0 = src_y.floor() as u32;
-				let x1 = (x0 + 1).min(src_w - 1);
-				let y1 = (y0 + 1).min(src_h - 1);
+		let (mut upper, mut lower) = (Vec::with_capacity(dst_w), Vec::with_capacity(dst_w));
+		let mut cached: Option<(usize, usize)> = None;
+		for (&(y0, y1, frac), out) in rows.iter().zip(dst.chunks_exact_mut(dst_w)) {
+			match cached {
+				Some(rows) if rows == (y0, y1) => {}
+				Some((_, previous)) if previous == y0 => {
+					std::mem::swap(&mut upper, &mut lower);
+					resample_row(y1, &mut lower);
+				}
+				_ => {
+					resample_row(y0, &mut upper);
+					resample_row(y1, &mut lower);
+				}
+			}
+			cached = Some((y0, y1));
 
-				let x_frac = src_x - x0 as f64;
-				let y_frac = src_y - y0 as f64;
+			let (top, bottom) = (256 - frac as u32, frac as u32);
+			for ((value, &a), &b) in out.iter_mut().zip(&upper).zip(&lower) {
+				*value = ((a as u32 * top + b as u32 * bottom) >> 16) as u8;
+			}
+		}
+	}
+}
 
-				let get_pixel = |px: u32, py: u32| -> f64 {
-					let idx = (py * src_w + px) as usize;
-					if idx < src.len() { src[idx] as f64 } else { 0.0 }
-				};
+// (first, second, weight of second out of 256) for each output position,
+// mapping the end samples of both sides onto each other
+fn bilinear_taps(src: usize, dst: usize) -> Vec<(usize, usize, u16)> {
+	let span = (src - 1) as u64 * 256;
+	let steps = dst.saturating_sub(1).max(1) as u64;
+	(0..dst as u64)
+		.map(|i| {
+			let position = i * span / steps;
+			let first = (position >> 8) as usize;
+			(first, (first + 1).min(src - 1), (position & 255) as u16)
+		})
+		.collect()
+}
 
-				let p00 = get_pixel(x0, y0);
-				let p10 = get_pixel(x1, y0);
-				let p01 = get_pixel(x0, y1);
-				let p11 = get_pixel(x1, y1);
+// in a chain the source size is taken from each frame
+impl Transform for Scale {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		let Some((width, height)) = planar_dimensions(&frame)? else {
+			return Ok(frame);
+		};
+		(self.src_width, self.src_height) = (width, height);
+		self.apply_yuv420(&frame)
+	}
 
-				let top = p00 * (1.0 - x_frac) + p10 * x_frac;
-				let bottom = p01 * (1.0 - x_frac) + p11 * x_frac;
-				let value = top * (1.0 - y_frac) + bottom * y_frac;
+	fn name(&self) -> &'static str {
+		"scale"
+	}
 
-				let dst_idx = (y * dst_w + x) as usize;
-				if dst_idx < dst.len() {
-					dst[dst_idx] = value.clamp(0.0, 255.0) as u8;
-				}
-			}
-		}
+	// fit pads and fill crops back out to the target, so every mode ends there
+	fn negotiate(&self, input: StreamFormat) -> IoResult<StreamFormat> {
+		let Some((_, _, format)) = planar_format(input)? else {
+			return Ok(input);
+		};
+		Ok(StreamFormat::Video { width: self.target_width, height: self.target_height, format })
 	}
 }
@@ -0,0 +1,45 @@
+use super::blur::box_blur;
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// unsharp mask on the luma plane: adds back `amount` times the detail a box
+// blur of `radius` would remove. chroma is left alone to avoid colour fringes.
+pub struct Sharpen {
+	amount: f32,
+	radius: u32,
+}
+
+impl Sharpen {
+	pub fn new(amount: f32, radius: u32) -> Self {
+		Self { amount, radius }
+	}
+}
+
+impl Transform for Sharpen {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		if !video_frame.format.has_chroma_planes() && video_frame.format != VideoFormat::GRAY8 {
+			return Ok(frame);
+		}
+
+		let y_size = (video_frame.width * video_frame.height) as usize;
+		if y_size > video_frame.data.len() || self.radius == 0 {
+			return Ok(frame);
+		}
+		let luma = &mut video_frame.data[..y_size];
+		let mut blurred = vec![0u8; y_size];
+		box_blur(luma, &mut blurred, video_frame.width, video_frame.height, self.radius);
+
+		for (value, &soft) in luma.iter_mut().zip(&blurred) {
+			let detail = *value as f32 - soft as f32;
+			*value = (*value as f32 + self.amount * detail).round().clamp(0.0, 255.0) as u8;
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"sharpen"
+	}
+}
@@ -0,0 +1,211 @@
+use crate::core::{Frame, FrameVideo, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// enough blocks for a robust median without searching every block in hd
+const MAX_BLOCKS: u32 = 64;
+
+// blocks whose luma range is below this are too flat to match reliably
+const MIN_BLOCK_CONTRAST: u8 = 12;
+
+fn block_sad(
+	prev: &[u8],
+	cur: &[u8],
+	width: usize,
+	(x, y): (usize, usize),
+	size: usize,
+	(dx, dy): (i32, i32),
+) -> u32 {
+	let (cx, cy) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+	let mut sad = 0;
+	for row in 0..size {
+		let a = &prev[(y + row) * width + x..][..size];
+		let b = &cur[(cy + row) * width + cx..][..size];
+		sad += a.iter().zip(b).map(|(&p, &c)| p.abs_diff(c) as u32).sum::<u32>();
+	}
+	sad
+}
+
+fn median(values: &mut [i32]) -> i32 {
+	values.sort_unstable();
+	values[values.len() / 2]
+}
+
+// global translation (dx, dy) of the picture from `prev` to `cur`, both luma
+// planes of `width` x `height`: blocks on a grid are matched within `search`
+// pixels and the median vector wins, which shrugs off a few moving objects
+pub fn estimate_motion(
+	prev: &[u8],
+	cur: &[u8],
+	width: u32,
+	height: u32,
+	search: u32,
+) -> (i32, i32) {
+	let (w, h, s) = (width as usize, height as usize, search as usize);
+	let size = if w.min(h) >= 16 + 2 * s { 16 } else { 8 };
+	if w < size + 2 * s || h < size + 2 * s || prev.len() < w * h || cur.len() < w * h {
+		return (0, 0);
+	}
+	let (span_x, span_y) = (w - size - 2 * s, h - size - 2 * s);
+	let per_side = (MAX_BLOCKS as f64).sqrt() as usize;
+	let (step_x, step_y) = ((span_x / per_side).max(size), (span_y / per_side).max(size));
+
+	let (mut xs, mut ys) = (Vec::new(), Vec::new());
+	for y in (s..=s + span_y).step_by(step_y) {
+		for x in (s..=s + span_x).step_by(step_x) {
+			let rows = (y..y + size).map(|row| &prev[row * w + x..][..size]);
+			let (lo, hi) = rows.flatten().fold((255u8, 0u8), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+			if hi - lo < MIN_BLOCK_CONTRAST {
+				continue;
+			}
+			let mut best = (u32::MAX, 0i32, 0i32);
+			let range = -(s as i32)..=s as i32;
+			for dy in range.clone() {
+				for dx in range.clone() {
+					let sad = block_sad(prev, cur, w, (x, y), size, (dx, dy));
+					// ties go to the smaller move
+					let closer = dx.abs() + dy.abs() < best.1.abs() + best.2.abs();
+					if sad < best.0 || (sad == best.0 && closer) {
+						best = (sad, dx, dy);
+					}
+				}
+			}
+			xs.push(best.1);
+			ys.push(best.2);
+		}
+	}
+	if xs.is_empty() {
+		return (0, 0);
+	}
+	(median(&mut xs), median(&mut ys))
+}
+
+// per-frame shift that moves the camera path onto its moving average over
+// `radius` frames either side. `motions[i]` is the move into frame i, so the
+// first entry is normally (0, 0)
+pub fn smooth_corrections(motions: &[(i32, i32)], radius: usize) -> Vec<(i32, i32)> {
+	let mut path = Vec::with_capacity(motions.len());
+	let (mut x, mut y) = (0i64, 0i64);
+	for &(dx, dy) in motions {
+		x += dx as i64;
+		y += dy as i64;
+		path.push((x, y));
+	}
+	(0..path.len())
+		.map(|i| {
+			let window = &path[i.saturating_sub(radius)..(i + radius + 1).min(path.len())];
+			let n = window.len() as f64;
+			let mean_x = window.iter().map(|p| p.0 as f64).sum::<f64>() / n;
+			let mean_y = window.iter().map(|p| p.1 as f64).sum::<f64>() / n;
+			((mean_x - path[i].0 as f64).round() as i32, (mean_y - path[i].1 as f64).round() as i32)
+		})
+		.collect()
+}
+
+// pass one: passes frames through and records the motion into each
+pub struct MotionEstimator {
+	search: u32,
+	previous: Option<Vec<u8>>,
+	motions: Vec<(i32, i32)>,
+}
+
+impl MotionEstimator {
+	pub fn new(search: u32) -> Self {
+		Self { search, previous: None, motions: Vec::new() }
+	}
+
+	pub fn motions(&self) -> &[(i32, i32)] {
+		&self.motions
+	}
+}
+
+impl Transform for MotionEstimator {
+	fn apply(&mut self, frame: Frame) -> IoResult<Frame> {
+		if let Some(video_frame) = frame.video() {
+			let luma = &video_frame.data
+				[..((video_frame.width * video_frame.height) as usize).min(video_frame.data.len())];
+			let motion = match &self.previous {
+				Some(previous) => {
+					estimate_motion(previous, luma, video_frame.width, video_frame.height, self.search)
+				}
+				None => (0, 0),
+			};
+			self.motions.push(motion);
+			self.previous = Some(luma.to_vec());
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"motion_estimator"
+	}
+}
+
+// pass two: shifts frame n (by its pts) by `corrections[n]`, repeating edge
+// pixels into the uncovered border
+pub struct Stabilize {
+	corrections: Vec<(i32, i32)>,
+}
+
+impl Stabilize {
+	pub fn new(corrections: Vec<(i32, i32)>) -> Self {
+		Self { corrections }
+	}
+}
+
+fn shift_plane(src: &[u8], width: usize, height: usize, dx: i32, dy: i32) -> Vec<u8> {
+	let mut out = Vec::with_capacity(width * height);
+	for y in 0..height {
+		let sy = (y as i32 - dy).clamp(0, height as i32 - 1) as usize;
+		for x in 0..width {
+			let sx = (x as i32 - dx).clamp(0, width as i32 - 1) as usize;
+			out.push(src[sy * width + sx]);
+		}
+	}
+	out
+}
+
+fn shift_frame(video: &FrameVideo, dx: i32, dy: i32) -> Vec<u8> {
+	let (width, height) = (video.width as usize, video.height as usize);
+	let mut data = shift_plane(&video.data[..width * height], width, height, dx, dy);
+	let (uv_w, uv_h) = video.format.chroma_dimensions(video.width, video.height);
+	let (sub_x, sub_y) = video.format.chroma_subsampling();
+	let uv_size = (uv_w * uv_h) as usize;
+	for plane in 0..2 {
+		if uv_size == 0 {
+			break;
+		}
+		let start = width * height + plane * uv_size;
+		let (cdx, cdy) = (dx / sub_x as i32, dy / sub_y as i32);
+		data.extend(shift_plane(
+			&video.data[start..start + uv_size],
+			uv_w as usize,
+			uv_h as usize,
+			cdx,
+			cdy,
+		));
+	}
+	data
+}
+
+impl Transform for Stabilize {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let index = usize::try_from(frame.pts).ok();
+		let Some(&(dx, dy)) = index.and_then(|i| self.corrections.get(i)) else {
+			return Ok(frame);
+		};
+		if let Some(video_frame) = frame.video_mut() {
+			let planar =
+				video_frame.format.has_chroma_planes() || video_frame.format == VideoFormat::GRAY8;
+			let complete = video_frame.data.len()
+				>= video_frame.format.frame_size(video_frame.width, video_frame.height);
+			if planar && complete && (dx, dy) != (0, 0) {
+				video_frame.data = shift_frame(video_frame, dx, dy);
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"stabilize"
+	}
+}
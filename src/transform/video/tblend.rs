@@ -0,0 +1,63 @@
+use crate::core::{Frame, Transform};
+use crate::io::{IoError, IoResult};
+use std::collections::VecDeque;
+
+// each output frame is a weighted average of the current frame and the ones
+// before it: motion blur, or temporal noise reduction on static shots. the
+// first frames blend what history there is, with the weights renormalized
+pub struct Tblend {
+	// oldest first; the last weight belongs to the current frame
+	weights: Vec<f32>,
+	history: VecDeque<Vec<u8>>,
+	shape: Option<(u32, u32, usize)>,
+}
+
+impl Tblend {
+	// an even average over `frames` frames
+	pub fn new(frames: usize) -> Self {
+		Self { weights: vec![1.0; frames.max(1)], history: VecDeque::new(), shape: None }
+	}
+
+	pub fn with_weights(weights: Vec<f32>) -> IoResult<Self> {
+		if weights.is_empty() || weights.iter().any(|w| !(w.is_finite() && *w >= 0.0)) {
+			return Err(IoError::invalid_data("tblend weights must be non-negative numbers"));
+		}
+		if weights.iter().all(|w| *w == 0.0) {
+			return Err(IoError::invalid_data("tblend needs a non-zero weight"));
+		}
+		Ok(Self { weights, history: VecDeque::new(), shape: None })
+	}
+}
+
+impl Transform for Tblend {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		// a change of size or format starts the history over
+		let shape = (video_frame.width, video_frame.height, video_frame.data.len());
+		if self.shape != Some(shape) {
+			self.history.clear();
+			self.shape = Some(shape);
+		}
+		self.history.push_back(video_frame.data.clone());
+		if self.history.len() > self.weights.len() {
+			self.history.pop_front();
+		}
+
+		let weights = &self.weights[self.weights.len() - self.history.len()..];
+		let total: f32 = weights.iter().sum();
+		if total <= 0.0 {
+			return Ok(frame);
+		}
+		for (idx, value) in video_frame.data.iter_mut().enumerate() {
+			let sum: f32 = self.history.iter().zip(weights).map(|(data, w)| data[idx] as f32 * w).sum();
+			*value = (sum / total).round().clamp(0.0, 255.0) as u8;
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"tblend"
+	}
+}
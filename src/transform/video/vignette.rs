@@ -0,0 +1,60 @@
+use crate::core::{Frame, Transform, VideoFormat};
+use crate::io::IoResult;
+
+// darkens towards the corners: brightness falls off with the square of the
+// distance from the centre, reaching `1 - strength` at the corners. planar
+// frames only touch luma, scaled towards video black
+pub struct Vignette {
+	strength: f32,
+}
+
+impl Vignette {
+	pub fn new(strength: f32) -> Self {
+		Self { strength: strength.clamp(0.0, 1.0) }
+	}
+
+	fn gains(&self, width: u32, height: u32) -> Vec<f32> {
+		let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+		let corner = (cx * cx + cy * cy).max(f32::EPSILON);
+		let mut gains = Vec::with_capacity((width * height) as usize);
+		for y in 0..height {
+			for x in 0..width {
+				let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+				gains.push(1.0 - self.strength * (dx * dx + dy * dy) / corner);
+			}
+		}
+		gains
+	}
+}
+
+impl Transform for Vignette {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		let Some(video_frame) = frame.video_mut() else {
+			return Ok(frame);
+		};
+		let gains = self.gains(video_frame.width, video_frame.height);
+		let darken = |value: &mut u8, gain: f32, black: f32| {
+			if *value as f32 > black {
+				*value = (black + (*value as f32 - black) * gain).round() as u8;
+			}
+		};
+		match video_frame.format {
+			VideoFormat::RGB24 | VideoFormat::RGBA32 => {
+				let step = if video_frame.format == VideoFormat::RGB24 { 3 } else { 4 };
+				for (pixel, &gain) in video_frame.data.chunks_exact_mut(step).zip(&gains) {
+					pixel[..3].iter_mut().for_each(|v| darken(v, gain, 0.0));
+				}
+			}
+			_ => {
+				for (value, &gain) in video_frame.data.iter_mut().zip(&gains) {
+					darken(value, gain, 16.0);
+				}
+			}
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"vignette"
+	}
+}
@@ -1,5 +1,8 @@
 use clap::Parser;
-use ffmpreg::cli::Args;
+use ffmpreg::cli::{Args, normalize_args};
+use ffmpreg::core::LogLevel;
+use ffmpreg::io::Overwrite;
+use ffmpreg::show::ShowFormat;
 
 #[test]
 fn test_args_basic() {
@@ -113,3 +116,355 @@ fn test_args_all_options() {
 	assert_eq!(args.codec, Some("pcm".to_string()));
 	assert!(!args.show);
 }
+
+#[test]
+fn test_args_stream_metadata() {
+	let argv = ["ffmpreg", "-i", "in.mp4", "-o", "out.mp4", "--metadata:s:a:0", "language=eng"];
+	let args = Args::try_parse_from(normalize_args(argv.iter().map(|s| s.to_string()))).unwrap();
+
+	assert_eq!(args.stream_metadata, vec!["s:a:0:language=eng".to_string()]);
+}
+
+#[test]
+fn test_args_repeated_input() {
+	let argv = ["ffmpreg", "-i", "a.wav", "-i", "b.wav", "--input=c.wav", "-o", "out.wav"];
+	let args = Args::try_parse_from(normalize_args(argv.iter().map(|s| s.to_string()))).unwrap();
+
+	assert_eq!(args.input, "a.wav");
+	assert_eq!(args.inputs(), vec!["a.wav", "b.wav", "c.wav"]);
+	assert_eq!(args.output, Some("out.wav".to_string()));
+}
+
+#[test]
+fn test_args_job_file() {
+	let args = Args::try_parse_from(["ffmpreg", "--job", "jobs.toml"]).unwrap();
+	assert_eq!(args.job.as_deref(), Some("jobs.toml"));
+
+	assert!(Args::try_parse_from(["ffmpreg"]).is_err());
+	assert!(Args::try_parse_from(["ffmpreg", "--job", "jobs.toml", "-i", "in.wav"]).is_err());
+
+	// without a file, the config's [[job]] tables
+	let args = Args::try_parse_from(["ffmpreg", "--job"]).unwrap();
+	assert_eq!(args.job.as_deref(), Some(""));
+}
+
+#[test]
+fn test_args_preset() {
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "a.wav", "-o", "b", "--preset", "podcast"]).unwrap();
+	assert_eq!(args.preset.as_deref(), Some("podcast"));
+	assert_eq!(args.config, None);
+
+	let args = Args::try_parse_from([
+		"ffmpreg",
+		"-i",
+		"a.wav",
+		"-o",
+		"b",
+		"--preset",
+		"mine",
+		"--config",
+		"presets.toml",
+	])
+	.unwrap();
+	assert_eq!(args.config.as_deref(), Some("presets.toml"));
+
+	assert!(Args::try_parse_from(["ffmpreg", "--job", "jobs.toml", "--preset", "podcast"]).is_err());
+}
+
+#[test]
+fn test_args_cover() {
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "a.wav", "-o", "a.flac", "--cover", "art.jpg"]).unwrap();
+	assert_eq!(args.cover.as_deref(), Some("art.jpg"));
+
+	assert!(
+		Args::try_parse_from(["ffmpreg", "-i", "a.wav", "--show", "--cover", "art.jpg"]).is_err()
+	);
+}
+
+#[test]
+fn test_args_watch() {
+	let args = Args::try_parse_from(["ffmpreg", "--watch", "inbox", "-o", "out"]).unwrap();
+	assert_eq!(args.watch.as_deref(), Some("inbox"));
+	assert_eq!(args.settle, "2s");
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "--watch", "inbox", "-o", "out", "--settle", "500ms"])
+			.unwrap();
+	assert_eq!(args.settle, "500ms");
+
+	// -o can come from the config file instead
+	assert!(Args::try_parse_from(["ffmpreg", "--watch", "inbox"]).is_ok());
+	assert!(
+		Args::try_parse_from(["ffmpreg", "--watch", "inbox", "-i", "a.wav", "-o", "out"]).is_err()
+	);
+}
+
+#[test]
+fn test_args_pipes_with_format() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "-", "-f", "wav", "-o", "-"]).unwrap();
+
+	assert_eq!(args.input, "-");
+	assert_eq!(args.output.as_deref(), Some("-"));
+	assert_eq!(args.format.as_deref(), Some("wav"));
+}
+
+#[test]
+fn test_args_progress() {
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav", "--progress"]).unwrap();
+	assert_eq!(args.progress.as_deref(), Some("line"));
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "--progress", "json", "-o", "out.wav"])
+			.unwrap();
+	assert_eq!(args.progress.as_deref(), Some("json"));
+	assert_eq!(args.output.as_deref(), Some("out.wav"));
+}
+
+#[test]
+fn test_args_error_format() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.error_format, "text");
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav", "--error-format", "json"])
+			.unwrap();
+	assert_eq!(args.error_format, "json");
+}
+
+#[test]
+fn test_args_benchmark() {
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "null", "--benchmark"]).unwrap();
+	assert!(args.benchmark);
+
+	assert!(Args::try_parse_from(["ffmpreg", "-i", "in.wav", "--show", "--benchmark"]).is_err());
+}
+
+#[test]
+fn test_args_threads() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.threads(), 1);
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav", "--threads", "4"]).unwrap();
+	assert_eq!(args.threads(), 4);
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav", "--threads", "0"]).unwrap();
+	assert!(args.threads() >= 1);
+}
+
+#[test]
+fn test_args_overwrite_flags() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.overwrite(), Overwrite::Refuse);
+
+	let args = Args::try_parse_from(["ffmpreg", "-y", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.overwrite(), Overwrite::Replace);
+
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav", "-n"]).unwrap();
+	assert_eq!(args.overwrite(), Overwrite::Skip);
+
+	assert!(Args::try_parse_from(["ffmpreg", "-y", "-n", "-i", "in.wav", "-o", "out.wav"]).is_err());
+}
+
+#[test]
+fn test_args_log_level() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.log_level(), Some(LogLevel::Warn));
+
+	let args = Args::try_parse_from(["ffmpreg", "-vv", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.verbose, 2);
+	assert_eq!(args.log_level(), Some(LogLevel::Debug));
+
+	let args = Args::try_parse_from(["ffmpreg", "-q", "-i", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.log_level(), Some(LogLevel::Off));
+
+	let args = Args::try_parse_from([
+		"ffmpreg",
+		"-q",
+		"--log-level",
+		"error",
+		"-i",
+		"in.wav",
+		"-o",
+		"out.wav",
+	])
+	.unwrap();
+	assert_eq!(args.log_level(), Some(LogLevel::Error));
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "--log-level", "loud", "-i", "in.wav", "-o", "out.wav"])
+			.unwrap();
+	assert_eq!(args.log_level(), None);
+
+	assert!(Args::try_parse_from(["ffmpreg", "-q", "-v", "-i", "in.wav", "-o", "out.wav"]).is_err());
+}
+
+#[test]
+fn test_args_recursive_extensions() {
+	let args = Args::try_parse_from([
+		"ffmpreg",
+		"-i",
+		"music/",
+		"-o",
+		"out/",
+		"--recursive",
+		"--ext",
+		"wav,flac",
+	])
+	.unwrap();
+	assert!(args.recursive);
+	assert_eq!(args.extensions, vec!["wav".to_string(), "flac".to_string()]);
+
+	let args = Args::try_parse_from(["ffmpreg", "-i", "music/", "-o", "out/"]).unwrap();
+	assert!(!args.recursive);
+	assert!(args.extensions.is_empty());
+}
+
+#[test]
+fn test_args_skip_existing_and_resume() {
+	let args = Args::try_parse_from([
+		"ffmpreg",
+		"-i",
+		"in/*.wav",
+		"-o",
+		"out/",
+		"--skip-existing",
+		"--resume",
+		"batch.manifest",
+	])
+	.unwrap();
+	assert_eq!(args.overwrite(), Overwrite::Skip);
+	assert_eq!(args.resume.as_deref(), Some("batch.manifest"));
+}
+
+#[test]
+fn test_args_on_error() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in/*.wav", "-o", "out/"]).unwrap();
+	assert_eq!(args.on_error, "continue");
+
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in/*.wav", "-o", "out/", "--on-error", "stop"])
+			.unwrap();
+	assert_eq!(args.on_error, "stop");
+}
+
+#[test]
+fn test_args_repeated_output() {
+	let argv = ["ffmpreg", "-i", "in.wav", "-o", "out.wav", "-o", "out.flac", "--output=copy.wav"];
+	let args = Args::try_parse_from(normalize_args(argv.iter().map(|s| s.to_string()))).unwrap();
+
+	assert_eq!(args.output, Some("out.wav".to_string()));
+	assert_eq!(args.more_outputs, vec!["out.flac", "copy.wav"]);
+}
+
+#[test]
+fn test_args_help_man() {
+	// the man page needs no input
+	let args = Args::try_parse_from(["ffmpreg", "--help-man"]).unwrap();
+	assert!(args.help_man);
+}
+
+#[test]
+fn test_args_compat() {
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "--show", "--compat", "ffprobe"]).unwrap();
+	assert_eq!(args.compat.as_deref(), Some("ffprobe"));
+}
+
+#[test]
+fn test_args_print_format() {
+	let args =
+		Args::try_parse_from(["ffmpreg", "-i", "in.wav", "--show", "--print-format", "csv"]).unwrap();
+	assert_eq!(args.print_format.as_deref(), Some("csv"));
+	assert_eq!(ShowFormat::from_name("xml"), Some(ShowFormat::Xml));
+	assert_eq!(ShowFormat::from_name("yaml"), None);
+}
+
+#[test]
+fn test_args_packets() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--show", "--packets"]).unwrap();
+	assert!(args.packets);
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--show"]).unwrap();
+	assert!(!args.packets);
+}
+
+#[test]
+fn test_args_count_frames() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.y4m", "--show", "--count-frames"]).unwrap();
+	assert!(args.count_frames && !args.packets);
+}
+
+#[test]
+fn test_args_bitrate() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--bitrate"]).unwrap();
+	assert_eq!(args.bitrate.as_deref(), Some("summary"));
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--bitrate", "csv"]).unwrap();
+	assert_eq!(args.bitrate.as_deref(), Some("csv"));
+	assert!(Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--bitrate", "--show"]).is_err());
+}
+
+#[test]
+fn test_args_keyframes() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--keyframes", "--json"]).unwrap();
+	assert!(args.keyframes && args.json);
+	assert!(Args::try_parse_from(["ffmpreg", "-i", "in.mp4", "--keyframes", "--show"]).is_err());
+}
+
+#[test]
+fn test_args_waveform() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "--waveform", "out.png"]).unwrap();
+	assert_eq!(args.waveform.as_deref(), Some("out.png"));
+	// with no file, drawn in the terminal
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.wav", "--waveform"]).unwrap();
+	assert_eq!(args.waveform.as_deref(), Some("-"));
+}
+
+#[test]
+fn test_args_stats() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.flac", "--stats", "--json"]).unwrap();
+	assert!(args.stats && args.json);
+	assert!(Args::try_parse_from(["ffmpreg", "-i", "in.flac", "--stats", "--bitrate"]).is_err());
+}
+
+#[test]
+fn test_args_diff() {
+	let args = Args::try_parse_from(["ffmpreg", "--diff", "a.wav", "b.flac", "--json"]).unwrap();
+	assert_eq!(args.diff, vec!["a.wav", "b.flac"]);
+	assert!(args.input.is_empty() && args.json);
+	assert!(Args::try_parse_from(["ffmpreg", "--diff", "a.wav"]).is_err());
+	assert!(Args::try_parse_from(["ffmpreg", "-i", "c.wav", "--diff", "a.wav", "b.wav"]).is_err());
+}
+
+#[test]
+fn test_args_export_edl() {
+	let argv =
+		["ffmpreg", "-i", "in.wav", "--apply", "silencedetect=-50,1", "--export-edl", "cuts.edl"];
+	let args = Args::try_parse_from(argv).unwrap();
+	assert_eq!(args.export_edl.as_deref(), Some("cuts.edl"));
+	assert_eq!(args.transforms, vec!["silencedetect=-50,1"]);
+}
+
+#[test]
+fn test_args_chunks() {
+	let args = Args::try_parse_from(["ffmpreg", "-i", "in.avi", "--chunks", "--json"]).unwrap();
+	assert!(args.chunks && args.json);
+	assert!(Args::try_parse_from(["ffmpreg", "-i", "in.avi", "--chunks", "--show"]).is_err());
+}
+
+#[test]
+fn test_args_help_keeps_braces() {
+	// clap renders `{n}` in help as a line break and has no escape for it
+	fn check(command: &clap::Command) {
+		for arg in command.get_arguments() {
+			let help = arg.get_help().map(|help| help.to_string()).unwrap_or_default();
+			assert!(!help.contains("{n}"), "--{} help: {}", arg.get_id(), help);
+		}
+		command.get_subcommands().for_each(check);
+	}
+	check(&ffmpreg::cli::completions::cli());
+}
@@ -0,0 +1,44 @@
+use clap::Parser;
+use ffmpreg::cli::bench::{BenchArgs, BenchCommand, decode_null};
+use tempfile::tempdir;
+
+#[test]
+fn test_bench_args_decode_null() {
+	let args = BenchArgs::try_parse_from(["bench", "decode", "in.wav", "--null"]).unwrap();
+	let BenchCommand::Decode { input, null } = args.command;
+	assert_eq!(input, "in.wav");
+	assert!(null);
+}
+
+#[test]
+fn test_bench_args_requires_sink() {
+	assert!(BenchArgs::try_parse_from(["bench", "decode", "in.wav"]).is_err());
+}
+
+#[test]
+fn test_decode_null_wav() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("in.wav");
+	std::fs::write(&path, crate::common::create_test_wav_data()).unwrap();
+
+	let stats = decode_null(path.to_str().unwrap()).unwrap();
+	assert!(stats.decoded);
+	assert!(stats.packets > 0);
+	assert_eq!(stats.frames, stats.packets);
+	assert_eq!(stats.frame_bytes, 1024 * 2);
+}
+
+#[test]
+fn test_decode_null_y4m() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("in.y4m");
+	std::fs::write(&path, crate::common::create_test_y4m_data()).unwrap();
+
+	let stats = decode_null(path.to_str().unwrap()).unwrap();
+	assert_eq!(stats.frames, 3);
+}
+
+#[test]
+fn test_decode_null_unknown_format() {
+	assert!(decode_null("input.xyz").is_err());
+}
@@ -0,0 +1,124 @@
+use clap::{CommandFactory, Parser};
+use ffmpreg::cli::command::Command;
+use ffmpreg::cli::completions::Shell;
+use ffmpreg::cli::{Args, normalize_args};
+use tempfile::tempdir;
+
+fn parse_command(argv: &[&str]) -> Result<Command, clap::Error> {
+	let argv = std::iter::once("ffmpreg").chain(argv.iter().copied()).map(String::from);
+	let mut args = Args::try_parse_from(normalize_args(argv))?;
+	Ok(args.command.take().expect("a command"))
+}
+
+// the flag form a convert, probe, batch or analyze stands for
+fn parse(argv: &[&str]) -> Result<Args, clap::Error> {
+	match parse_command(argv)? {
+		Command::Convert(convert) => Ok((*convert).into()),
+		Command::Probe(probe) => Ok(probe.into()),
+		Command::Batch(batch) => Ok(batch.into()),
+		Command::Analyze(analyze) => Ok(analyze.into()),
+		command => panic!("{:?} is a tool", command),
+	}
+}
+
+#[test]
+fn test_command_definitions() {
+	Args::command().debug_assert();
+	assert!(matches!(parse_command(&["analyse", "in.wav"]), Ok(Command::Analyze(_))));
+	assert!(parse_command(&["show", "in.wav"]).is_err());
+}
+
+#[test]
+fn test_command_tools() {
+	let command = parse_command(&["serve", "--listen", "127.0.0.1:9000"]).unwrap();
+	assert!(matches!(command, Command::Serve(serve) if serve.listen == "127.0.0.1:9000"));
+	assert!(matches!(parse_command(&["bench", "decode", "in.wav", "--null"]), Ok(Command::Bench(_))));
+	assert!(matches!(parse_command(&["vad", "-i", "in.wav"]), Ok(Command::Vad(_))));
+
+	let command = parse_command(&["completions", "zsh"]).unwrap();
+	assert!(matches!(command, Command::Completions(completions) if completions.shell == Shell::Zsh));
+	assert!(parse_command(&["completions", "powershell"]).is_err());
+}
+
+#[test]
+fn test_command_leaves_flag_form() {
+	let argv = ["ffmpreg", "-i", "a.wav", "-i", "b.wav", "-o", "out.wav"];
+	let args = Args::try_parse_from(normalize_args(argv.map(String::from))).unwrap();
+	assert!(args.command.is_none());
+	assert_eq!(args.inputs(), vec!["a.wav", "b.wav"]);
+
+	// flags of the flag form do not go before a command
+	assert!(parse(&["-v", "convert", "in.wav", "out.wav"]).is_err());
+	assert!(Args::try_parse_from(["ffmpreg"]).is_err());
+}
+
+#[test]
+fn test_command_convert() {
+	let args = parse(&["convert", "in.wav", "out.flac", "--apply", "volume=2"]).unwrap();
+	assert!(args.command.is_none());
+	assert_eq!(args.input, "in.wav");
+	assert_eq!(args.output.as_deref(), Some("out.flac"));
+	assert_eq!(args.transforms, vec!["volume=2"]);
+	assert!(!args.show);
+	assert_eq!(args.on_error, "continue");
+
+	let args = parse(&["convert", "in.wav", "-o", "out.wav"]).unwrap();
+	assert_eq!(args.output.as_deref(), Some("out.wav"));
+	// -o beside OUTPUT writes a further output from the same decode
+	let args = parse(&["convert", "in.wav", "a.wav", "-o", "b.flac", "-o", "c.wav"]).unwrap();
+	assert_eq!(args.output.as_deref(), Some("a.wav"));
+	assert_eq!(args.more_outputs, vec!["b.flac", "c.wav"]);
+
+	// -i after FILE adds inputs for a filter graph or a join
+	let args = parse(&["convert", "a.wav", "-i", "b.wav", "-o", "c.wav"]).unwrap();
+	assert_eq!(args.inputs(), vec!["a.wav", "b.wav"]);
+
+	assert!(parse(&["convert", "*.wav", "out"]).is_err());
+}
+
+#[test]
+fn test_command_refuses_other_flags() {
+	assert!(parse(&["convert", "in.wav", "out.wav", "--recursive"]).is_err());
+	assert!(parse(&["probe", "in.wav", "--codec", "pcm_u8"]).is_err());
+	assert!(parse(&["probe", "in.wav", "-o", "out.wav"]).is_err());
+	// logging and the config go with every command
+	let args = parse(&["probe", "in.wav", "-v", "--config", "c.toml"]).unwrap();
+	assert_eq!(args.verbose, 1);
+	assert_eq!(args.config.as_deref(), Some("c.toml"));
+	assert!(parse(&["probe"]).is_err());
+}
+
+#[test]
+fn test_command_probe() {
+	let args = parse(&["probe", "in.mp4", "--json", "--stream", "1"]).unwrap();
+	assert_eq!(args.input, "in.mp4");
+	assert!(args.show);
+	assert!(args.json);
+	assert_eq!(args.stream, Some(1));
+	assert_eq!(args.frames, 10);
+}
+
+#[test]
+fn test_command_batch() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().to_str().unwrap();
+	let args = parse(&["batch", input, "-o", "out", "--recursive"]).unwrap();
+	assert_eq!(args.input, input);
+	assert!(args.recursive);
+
+	let args = parse(&["batch", "music/*.wav", "-o", "out"]).unwrap();
+	assert_eq!(args.input, "music/*.wav");
+
+	assert!(parse(&["batch", "one.wav", "-o", "out"]).is_err());
+}
+
+#[test]
+fn test_command_analyze() {
+	let args = parse(&["analyze", "in.wav"]).unwrap();
+	assert!(args.show);
+	assert_eq!(args.transforms, vec!["astats"]);
+
+	let args = parse(&["analyze", "a.y4m", "--compare", "b.y4m"]).unwrap();
+	assert!(!args.show);
+	assert_eq!(args.compare.as_deref(), Some("b.y4m"));
+}
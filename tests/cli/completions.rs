@@ -0,0 +1,47 @@
+use ffmpreg::cli::completions::{SHELLS, Shell, cli};
+
+#[test]
+fn test_completions_shell_names() {
+	for shell in SHELLS {
+		assert_eq!(Shell::from_name(shell.name()), Some(shell));
+	}
+	assert_eq!(Shell::from_name("ZSH"), Some(Shell::Zsh));
+	assert_eq!(Shell::from_name("powershell"), None);
+}
+
+#[test]
+fn test_completions_cover_commands_and_flags() {
+	let tree = cli();
+	let names: Vec<&str> = tree.get_subcommands().map(|command| command.get_name()).collect();
+	for name in ["convert", "probe", "batch", "analyze", "bench", "serve", "completions"] {
+		assert!(names.contains(&name), "{} missing", name);
+	}
+
+	for shell in SHELLS {
+		let script = Shell::script(&shell);
+		for word in ["convert", "probe", "threads", "error-format", "bench"] {
+			assert!(script.contains(word), "{} script lacks {}", shell.name(), word);
+		}
+		// hidden flags and the completions command stay out of the scripts
+		assert!(!script.contains("more-input"), "{}", shell.name());
+		// nested commands, command aliases and flag aliases, as clap_complete would
+		for word in ["decode", "null", "analyse", "skip-existing"] {
+			assert!(script.contains(word), "{} script lacks {}", shell.name(), word);
+		}
+	}
+
+	let bash = Shell::Bash.script();
+	assert!(bash.contains("complete -o filenames -F _ffmpreg ffmpreg"));
+	// `completions` takes the shell names as its argument
+	assert!(bash.contains("words=\"bash zsh fish\""));
+	assert!(bash.contains("ffmpreg__bench,decode)\n\t\t\t\tcmd=\"ffmpreg__bench__decode\""));
+	let zsh = Shell::Zsh.script();
+	assert!(zsh.starts_with("#compdef ffmpreg\n"));
+	assert!(zsh.contains("\t\tanalyze|analyse)\n"));
+	assert!(zsh.contains("_ffmpreg__bench__decode() {\n"));
+	let fish = Shell::Fish.script();
+	assert!(fish.contains("-n '__fish_seen_subcommand_from convert' -s o -l output"));
+	assert!(fish.contains(
+		"-n '__fish_seen_subcommand_from bench; and __fish_seen_subcommand_from decode' -l null"
+	));
+}
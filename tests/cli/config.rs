@@ -0,0 +1,83 @@
+use ffmpreg::cli::Config;
+use ffmpreg::container::MediaType;
+use ffmpreg::core::LogLevel;
+
+#[test]
+fn test_config_defaults() {
+	let config = Config::parse(
+		r#"
+output = "converted/"
+log-level = "info"
+apply = ["dcremove"]
+
+[[job]]
+input = "inbox/*.wav"
+output = "mastered/"
+apply = ["loudnorm=-16"]
+"#,
+	)
+	.unwrap();
+
+	assert_eq!(config.output.as_deref(), Some("converted/"));
+	assert_eq!(config.log_level, Some(LogLevel::Info));
+	assert_eq!(config.apply, vec!["dcremove"]);
+	assert_eq!(config.jobs.len(), 1);
+	assert_eq!(config.jobs[0].input, "inbox/*.wav");
+	assert!(config.presets.is_empty());
+
+	assert_eq!(Config::parse("").unwrap(), Config::default());
+	assert_eq!(Config::parse("apply = \"mono\"\n").unwrap().apply, vec!["mono"]);
+}
+
+#[test]
+fn test_config_errors() {
+	assert!(Config::parse("log-level = \"loud\"\n").is_err());
+	assert!(Config::parse("outptu = \"out\"\n").is_err());
+	assert!(Config::parse("output = 3\n").is_err());
+	assert!(Config::parse("[[job]]\noutput = \"out\"\n").is_err());
+	assert!(Config::parse("[job]\ninput = \"a.wav\"\n").is_err());
+}
+
+#[test]
+fn test_config_presets() {
+	let config = Config::parse(
+		r#"
+# house style
+[[preset]]
+name = "interview"
+codec = "pcm_s24le"
+rate = 48000
+apply = ["highpass=100", "loudnorm=-19"]
+container = "wav"
+
+[[preset]]
+name = "archive"
+container = "wav"
+"#,
+	)
+	.unwrap();
+
+	let interview = config.preset("interview").unwrap();
+	assert_eq!(interview.codec.as_deref(), Some("pcm_s24le"));
+	assert_eq!(interview.transforms(), vec!["resample=48000", "highpass=100", "loudnorm=-19"]);
+	assert_eq!(interview.container, Some(MediaType::Wav));
+
+	// a config preset replaces the built-in of the same name
+	assert_eq!(config.preset("archive").unwrap().container, Some(MediaType::Wav));
+	assert!(config.preset("podcast").is_some());
+	assert!(config.preset("missing").is_none());
+	assert_eq!(
+		config.preset_names(),
+		vec!["podcast", "archive", "voicemail", "web-video", "interview"]
+	);
+}
+
+#[test]
+fn test_config_preset_errors() {
+	assert!(Config::parse("[[preset]]\ncodec = \"flac\"\n").is_err());
+	assert!(Config::parse("[[preset]]\nname = \"a\"\ncodec = \"mp3\"\n").is_err());
+	assert!(Config::parse("[[preset]]\nname = \"a\"\nrate = 44100.5\n").is_err());
+	assert!(Config::parse("[[preset]]\nname = \"a\"\ncontainer = \"mkv\"\n").is_err());
+	assert!(Config::parse("[[preset]]\nname = \"a\"\nbitrate = 128\n").is_err());
+	assert!(Config::parse("[preset]\nname = \"a\"\n").is_err());
+}
@@ -0,0 +1,40 @@
+use ffmpreg::cli::contact_sheet::{ContactSheetSpec, contact_sheet, write_contact_sheet};
+use tempfile::tempdir;
+
+#[test]
+fn test_contact_sheet_spec_parse() {
+	let spec = ContactSheetSpec::parse("4x3").unwrap();
+	assert_eq!((spec.columns, spec.rows, spec.tile_width), (4, 3, 240));
+	assert_eq!(ContactSheetSpec::parse("2x5,width=320").unwrap().tile_width, 320);
+	assert!(ContactSheetSpec::parse("0x3").is_err());
+	assert!(ContactSheetSpec::parse("4").is_err());
+	assert!(ContactSheetSpec::parse("4x3,width=8").is_err());
+	assert!(ContactSheetSpec::parse("4x3,height=80").is_err());
+}
+
+#[test]
+fn test_contact_sheet_tiles_frames_in_order() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("clip.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let input = input.to_str().unwrap();
+
+	// three frames on a 2x2 grid: the last cell stays empty
+	let spec = ContactSheetSpec::parse("2x2,width=16").unwrap();
+	let sheet = contact_sheet(input, &spec).unwrap();
+	assert_eq!((sheet.width, sheet.height), (2 * 16 + 3 * 4, 2 * 16 + 3 * 4));
+	assert_eq!(sheet.pixel(0, 0), [32, 32, 32]);
+	assert_eq!(sheet.pixel(4 + 20 + 8, 4 + 20 + 8), [32, 32, 32]);
+
+	// later frames are brighter; the bottom-right of each tile is clear of the timestamp
+	let corner = |column: u32, row: u32| sheet.pixel(4 + column * 20 + 15, 4 + row * 20 + 15)[0];
+	assert!(corner(0, 0) < corner(1, 0) && corner(1, 0) < corner(0, 1));
+	// the timestamp is burnt in near each tile's top-left
+	let stamped = (6..13).any(|y| (6..20).any(|x| sheet.pixel(x, y)[0] > 200));
+	assert!(stamped);
+
+	let output = dir.path().join("sheet.png");
+	write_contact_sheet(input, &spec, output.to_str().unwrap()).unwrap();
+	assert!(std::fs::read(&output).unwrap().starts_with(b"\x89PNG"));
+	assert!(write_contact_sheet(input, &spec, "sheet.gif").is_err());
+}
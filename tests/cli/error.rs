@@ -0,0 +1,130 @@
+use clap::Parser;
+use ffmpreg::cli::Args;
+use ffmpreg::cli::error::{self, CliError, ErrorFormat, ErrorKind};
+use ffmpreg::io::IoError;
+use ffmpreg::transcode::Transcoder;
+use tempfile::tempdir;
+
+#[test]
+fn test_error_kind_exit_codes() {
+	let codes: Vec<i32> = [
+		ErrorKind::Failure,
+		ErrorKind::InvalidInput,
+		ErrorKind::Unsupported,
+		ErrorKind::Io,
+		ErrorKind::CorruptData,
+	]
+	.iter()
+	.map(ErrorKind::exit_code)
+	.collect();
+	assert_eq!(codes, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_error_kind_of_io_error() {
+	use std::io::{Error, ErrorKind as Io};
+	assert_eq!(ErrorKind::of(&Error::from(Io::NotFound)), ErrorKind::Io);
+	assert_eq!(ErrorKind::of(&Error::from(Io::PermissionDenied)), ErrorKind::Io);
+	assert_eq!(ErrorKind::of(&Error::from(Io::InvalidInput)), ErrorKind::InvalidInput);
+	assert_eq!(ErrorKind::of(&Error::from(Io::Unsupported)), ErrorKind::Unsupported);
+	assert_eq!(ErrorKind::of(&Error::from(Io::UnexpectedEof)), ErrorKind::CorruptData);
+	assert_eq!(ErrorKind::of(&Error::other("batch failed")), ErrorKind::Failure);
+
+	// the library's kinds survive the trip through std::io::Error
+	let error: CliError = IoError::invalid_data("not a RIFF file").into();
+	assert_eq!(error.kind, ErrorKind::CorruptData);
+	let error: CliError = IoError::unsupported("unsupported format conversion").into();
+	assert_eq!(error.kind, ErrorKind::Unsupported);
+}
+
+#[test]
+fn test_error_render() {
+	let error = CliError::new(ErrorKind::Unsupported, "can't \"mux\" that");
+	assert_eq!(error.render(ErrorFormat::Text), "Error: can't \"mux\" that");
+	assert_eq!(
+		error.render(ErrorFormat::Json),
+		r#"{"error":{"code":3,"kind":"unsupported","message":"can't \"mux\" that"}}"#
+	);
+
+	assert_eq!(ErrorFormat::from_name("JSON"), Some(ErrorFormat::Json));
+	assert_eq!(ErrorFormat::from_name("text"), Some(ErrorFormat::Text));
+	assert_eq!(ErrorFormat::from_name("xml"), None);
+}
+
+#[test]
+fn test_error_scan_format() {
+	let scan = |args: &[&str]| error::scan_format(args.iter().map(|arg| arg.to_string()));
+	assert_eq!(scan(&["ffmpreg", "--bogus", "--error-format", "json"]), Some(ErrorFormat::Json));
+	assert_eq!(scan(&["ffmpreg", "--error-format=json", "-i"]), Some(ErrorFormat::Json));
+	assert_eq!(
+		scan(&["ffmpreg", "--error-format", "json", "--error-format", "text"]),
+		Some(ErrorFormat::Text)
+	);
+	assert_eq!(scan(&["ffmpreg", "--error-format", "xml"]), None);
+	assert_eq!(scan(&["ffmpreg", "--", "--error-format", "json"]), None);
+	assert_eq!(scan(&["ffmpreg", "-i", "in.wav"]), None);
+}
+
+#[test]
+fn test_error_from_clap() {
+	let rejected = Args::try_parse_from(["ffmpreg", "--bogus"]).unwrap_err();
+	let error = CliError::from_clap(&rejected);
+	assert_eq!(error.kind, ErrorKind::InvalidInput);
+	assert_eq!(error.message, "unexpected argument '--bogus' found");
+	assert_eq!(
+		error.render(ErrorFormat::Json),
+		r#"{"error":{"code":2,"kind":"invalid_input","message":"unexpected argument '--bogus' found"}}"#
+	);
+}
+
+#[test]
+fn test_error_from_argument_file() {
+	let error = CliError::from_argument_file("cuts.edl", IoError::invalid_data("bad line").into());
+	assert_eq!(error.kind, ErrorKind::InvalidInput);
+	assert!(error.message.starts_with("cuts.edl: "));
+
+	let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+	assert_eq!(CliError::from_argument_file("art.jpg", missing).kind, ErrorKind::Io);
+}
+
+#[test]
+fn test_transcoder_error_kinds() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+	let run = |output: &str, reverse: bool| {
+		let output = dir.path().join(output);
+		let result = Transcoder::new(
+			input.to_str().unwrap().to_string(),
+			Some(output.to_str().unwrap().to_string()),
+			false,
+			vec![],
+		)
+		.with_reverse(reverse)
+		.run();
+		ErrorKind::of(&result.unwrap_err())
+	};
+
+	assert_eq!(run("out.avi", false), ErrorKind::Unsupported);
+	assert_eq!(run("out.wav", true), ErrorKind::InvalidInput);
+
+	let missing = Transcoder::new(
+		dir.path().join("missing.wav").to_str().unwrap().to_string(),
+		Some(dir.path().join("out.wav").to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.run();
+	assert_eq!(ErrorKind::of(&missing.unwrap_err()), ErrorKind::Io);
+
+	let garbage = dir.path().join("garbage.wav");
+	std::fs::write(&garbage, b"definitely not a wav file at all").unwrap();
+	let corrupt = Transcoder::new(
+		garbage.to_str().unwrap().to_string(),
+		Some(dir.path().join("out.wav").to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.run();
+	assert_eq!(ErrorKind::of(&corrupt.unwrap_err()), ErrorKind::CorruptData);
+}
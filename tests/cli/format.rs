@@ -0,0 +1,53 @@
+use ffmpreg::cli::format::{SizeUnits, format_duration, format_size, format_thousands};
+
+#[test]
+fn test_format_duration_hms_millis() {
+	assert_eq!(format_duration(0.0), "00:00:00.000");
+	assert_eq!(format_duration(1.5), "00:00:01.500");
+	assert_eq!(format_duration(61.0125), "00:01:01.013");
+	assert_eq!(format_duration(3723.004), "01:02:03.004");
+	assert_eq!(format_duration(-2.25), "-00:00:02.250");
+}
+
+#[test]
+fn test_format_duration_rounds_up_to_next_second() {
+	assert_eq!(format_duration(59.9996), "00:01:00.000");
+}
+
+#[test]
+fn test_format_size_binary() {
+	assert_eq!(format_size(512, SizeUnits::Binary), "512 B");
+	assert_eq!(format_size(1024, SizeUnits::Binary), "1.00 KiB");
+	assert_eq!(format_size(1536 * 1024, SizeUnits::Binary), "1.50 MiB");
+	assert_eq!(format_size(3 * 1024 * 1024 * 1024, SizeUnits::Binary), "3.00 GiB");
+}
+
+#[test]
+fn test_format_size_decimal() {
+	assert_eq!(format_size(999, SizeUnits::Decimal), "999 B");
+	assert_eq!(format_size(1000, SizeUnits::Decimal), "1.00 kB");
+	assert_eq!(format_size(2_500_000, SizeUnits::Decimal), "2.50 MB");
+}
+
+#[test]
+fn test_format_size_bytes() {
+	assert_eq!(format_size(1_234_567, SizeUnits::Bytes), "1,234,567 B");
+}
+
+#[test]
+fn test_format_thousands() {
+	assert_eq!(format_thousands(0), "0");
+	assert_eq!(format_thousands(999), "999");
+	assert_eq!(format_thousands(1000), "1,000");
+	assert_eq!(format_thousands(44100), "44,100");
+	assert_eq!(format_thousands(1_000_000), "1,000,000");
+}
+
+#[test]
+fn test_size_units_from_name() {
+	assert_eq!(SizeUnits::from_name("binary"), Some(SizeUnits::Binary));
+	assert_eq!(SizeUnits::from_name("SI"), Some(SizeUnits::Decimal));
+	assert_eq!(SizeUnits::from_name("bytes"), Some(SizeUnits::Bytes));
+	assert_eq!(SizeUnits::from_name("metric"), None);
+	assert_eq!(SizeUnits::default(), SizeUnits::Binary);
+}
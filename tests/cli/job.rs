@@ -0,0 +1,99 @@
+use crate::common::create_test_wav_from_samples;
+use ffmpreg::cli::{JobConfig, JobFile};
+use ffmpreg::container::WavReader;
+use ffmpreg::io::Cursor;
+use tempfile::tempdir;
+
+#[test]
+fn test_job_file_parse_toml() {
+	let file = JobFile::parse_toml(
+		r#"
+[[job]]
+input = "in.wav"
+output = "out.wav"
+apply = ["gain=2", "normalize"]
+codec = "pcm_s24le"
+ss = "1:30"
+duration = 10
+loop = "inf"
+auto-fade = "200ms"
+replaygain = true
+
+[[job]]
+input = "raw/*.wav"
+output = "mastered/"
+apply = "highpass=80"
+"#,
+	)
+	.unwrap();
+
+	assert_eq!(file.jobs.len(), 2);
+	let first = &file.jobs[0];
+	assert_eq!(first.input, "in.wav");
+	assert_eq!(first.output.as_deref(), Some("out.wav"));
+	assert_eq!(first.apply, vec!["gain=2", "normalize"]);
+	assert_eq!(first.codec.as_deref(), Some("pcm_s24le"));
+	assert_eq!((first.ss, first.duration), (Some(90.0), Some(10.0)));
+	assert_eq!(first.passes, Some(None));
+	assert_eq!(first.auto_fade, Some(0.2));
+	assert!(first.replaygain && !first.is_batch());
+
+	let second = &file.jobs[1];
+	assert_eq!(second.apply, vec!["highpass=80"]);
+	assert!(second.is_batch());
+}
+
+#[test]
+fn test_job_file_parse_json() {
+	let many = JobFile::parse_json(
+		r#"{"jobs": [{"input": "a.wav", "output": "b.wav", "transforms": ["volume=0.5"]}]}"#,
+	)
+	.unwrap();
+	assert_eq!(many.jobs[0].apply, vec!["volume=0.5"]);
+
+	// a single job can sit at the top level
+	let single = JobFile::parse_json(r#"{"input": "a.wav", "reverse": true}"#).unwrap();
+	assert_eq!(
+		single.jobs,
+		vec![JobConfig { input: "a.wav".into(), reverse: true, ..Default::default() }]
+	);
+}
+
+#[test]
+fn test_job_file_rejects_bad_options() {
+	assert!(JobFile::parse_toml("output = \"x.wav\"").is_err());
+	assert!(JobFile::parse_toml("input = \"a.wav\"\nspeed = 2").is_err());
+	assert!(JobFile::parse_toml("input = \"a.wav\"\ncodec = \"mp9\"").is_err());
+	assert!(JobFile::parse_toml("input = \"a.wav\"\nto = 5\nduration = 2").is_err());
+	assert!(JobFile::parse_toml("input = \"a.wav\"\nloop = 1.5").is_err());
+	assert!(JobFile::parse_json(r#"{"jobs": []}"#).is_err());
+}
+
+#[test]
+fn test_job_file_runs_single_and_batch_jobs() {
+	let dir = tempdir().unwrap();
+	let raw = dir.path().join("raw");
+	std::fs::create_dir(&raw).unwrap();
+	for name in ["a.wav", "b.wav"] {
+		std::fs::write(raw.join(name), create_test_wav_from_samples(&[1000, -1000, 500], 44100, 1))
+			.unwrap();
+	}
+	let job_path = dir.path().join("job.toml");
+	let root = dir.path().to_str().unwrap();
+	std::fs::write(
+		&job_path,
+		format!(
+			"[[job]]\ninput = '{root}/raw/a.wav'\noutput = '{root}/single.wav'\napply = [\"gain=2\"]\n\n\
+			 [[job]]\ninput = '{root}/raw/*.wav'\noutput = '{root}/out/'\n"
+		),
+	)
+	.unwrap();
+
+	JobFile::load(job_path.to_str().unwrap()).unwrap().run().unwrap();
+
+	let single = std::fs::read(dir.path().join("single.wav")).unwrap();
+	let reader = WavReader::new(Cursor::new(single)).unwrap();
+	assert_eq!(reader.format().sample_rate, 44100);
+	assert!(dir.path().join("out/a.wav").exists());
+	assert!(dir.path().join("out/b.wav").exists());
+}
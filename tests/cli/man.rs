@@ -0,0 +1,21 @@
+use ffmpreg::cli::completions::cli;
+use ffmpreg::cli::man::render;
+
+#[test]
+fn test_man_page() {
+	let page = render(&cli());
+	assert!(page.starts_with(".TH FFMPREG 1 "));
+	for section in [".SH NAME", ".SH SYNOPSIS", ".SH OPTIONS", ".SH COMMANDS", ".SH EXIT STATUS"] {
+		assert!(page.contains(section), "{} missing", section);
+	}
+	assert!(page.contains("\\fB\\-i\\fR, \\fB\\-\\-input\\fR \\fIFILE\\fR"));
+	assert!(page.contains(".SS \"ffmpreg convert\""));
+	assert!(page.contains("[default: text]"));
+	assert!(!page.contains("more\\-input"));
+
+	// no line of help text may be read as a roff request
+	for line in page.lines().filter(|line| line.starts_with('.')) {
+		let request = line.split_whitespace().next().unwrap();
+		assert!([".TH", ".SH", ".SS", ".TP", ".br"].contains(&request), "{}", line);
+	}
+}
@@ -0,0 +1,32 @@
+use ffmpreg::cli::manifest::BatchManifest;
+use tempfile::tempdir;
+
+#[test]
+fn test_manifest_survives_reopen() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("batch.manifest");
+
+	let mut manifest = BatchManifest::open(&path).unwrap();
+	manifest.start("in/a.wav", "out/a.wav").unwrap();
+	manifest.finish("in/a.wav", "out/a.wav").unwrap();
+	manifest.start("in/tab\there.wav", "out/b.wav").unwrap();
+	assert!(manifest.is_done("in/a.wav", "out/a.wav"));
+	drop(manifest);
+
+	let manifest = BatchManifest::open(&path).unwrap();
+	assert!(manifest.is_done("in/a.wav", "out/a.wav"));
+	assert!(!manifest.is_unfinished("in/a.wav", "out/a.wav"));
+	assert!(manifest.is_unfinished("in/tab\there.wav", "out/b.wav"));
+	assert!(!manifest.is_done("in/a.wav", "elsewhere/a.wav"));
+}
+
+#[test]
+fn test_manifest_ignores_truncated_line() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("batch.manifest");
+	std::fs::write(&path, "done\tin/a.wav\tout/a.wav\ndone\tin/b.w").unwrap();
+
+	let manifest = BatchManifest::open(&path).unwrap();
+	assert!(manifest.is_done("in/a.wav", "out/a.wav"));
+	assert!(!manifest.is_done("in/b.wav", ""));
+}
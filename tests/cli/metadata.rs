@@ -0,0 +1,77 @@
+use ffmpreg::cli::StreamMetadata;
+use ffmpreg::cli::metadata::{StreamMetadataValue, StreamSelector};
+use ffmpreg::container::Mp4Format;
+use ffmpreg::container::metadata::Disposition;
+use ffmpreg::container::mp4::{Mp4Track, TrackType};
+
+fn format_with_tracks(types: &[TrackType]) -> Mp4Format {
+	let tracks = types
+		.iter()
+		.enumerate()
+		.map(|(i, &track_type)| Mp4Track { track_id: i as u32 + 1, track_type, ..Mp4Track::default() })
+		.collect();
+	Mp4Format { tracks, ..Mp4Format::default() }
+}
+
+#[test]
+fn test_stream_metadata_parse_language() {
+	let metadata = StreamMetadata::parse("s:a:0", "language=ENG").unwrap();
+	assert_eq!(metadata.selector, Some(StreamSelector::Audio));
+	assert_eq!(metadata.index, Some(0));
+	assert_eq!(metadata.value, StreamMetadataValue::Language("eng".to_string()));
+}
+
+#[test]
+fn test_stream_metadata_parse_disposition() {
+	let metadata = StreamMetadata::parse("s:1", "disposition=default+commentary").unwrap();
+	assert_eq!(metadata.selector, None);
+	assert_eq!(metadata.index, Some(1));
+	assert_eq!(
+		metadata.value,
+		StreamMetadataValue::Disposition(Disposition { default: true, commentary: true })
+	);
+}
+
+#[test]
+fn test_stream_metadata_parse_errors() {
+	assert!(StreamMetadata::parse("a:0", "language=eng").is_err());
+	assert!(StreamMetadata::parse("s:a:x", "language=eng").is_err());
+	assert!(StreamMetadata::parse("s:a:0", "language=english").is_err());
+	assert!(StreamMetadata::parse("s:a:0", "disposition=forced").is_err());
+	assert!(StreamMetadata::parse("s:a:0", "title=x").is_err());
+	assert!(StreamMetadata::parse("s:a:0", "language").is_err());
+}
+
+#[test]
+fn test_stream_metadata_parse_combined() {
+	let metadata = StreamMetadata::parse_combined("s:v:0:language=fra").unwrap();
+	assert_eq!(metadata.selector, Some(StreamSelector::Video));
+	assert_eq!(metadata.value, StreamMetadataValue::Language("fra".to_string()));
+}
+
+#[test]
+fn test_stream_metadata_apply_counts_per_type() {
+	let mut format = format_with_tracks(&[TrackType::Audio, TrackType::Video, TrackType::Audio]);
+	StreamMetadata::parse("s:a:1", "language=spa").unwrap().apply_mp4(&mut format).unwrap();
+
+	assert_eq!(format.tracks[0].language, "und");
+	assert_eq!(format.tracks[1].language, "und");
+	assert_eq!(format.tracks[2].language, "spa");
+}
+
+#[test]
+fn test_stream_metadata_apply_all_of_type() {
+	let mut format = format_with_tracks(&[TrackType::Audio, TrackType::Video, TrackType::Audio]);
+	StreamMetadata::parse("s:a", "disposition=0").unwrap().apply_mp4(&mut format).unwrap();
+
+	assert!(format.tracks[0].disposition.is_empty());
+	assert!(format.tracks[1].disposition.default);
+	assert!(format.tracks[2].disposition.is_empty());
+}
+
+#[test]
+fn test_stream_metadata_apply_no_match() {
+	let mut format = format_with_tracks(&[TrackType::Video]);
+	let metadata = StreamMetadata::parse("s:a:0", "language=eng").unwrap();
+	assert!(metadata.apply_mp4(&mut format).is_err());
+}
@@ -1,2 +1,21 @@
 mod args;
+mod bench;
+mod command;
+mod completions;
+mod config;
+mod contact_sheet;
+mod error;
+mod format;
+mod job;
+mod man;
+mod manifest;
+mod metadata;
 mod pipeline;
+mod preset;
+mod preview;
+mod sample;
+mod slice;
+mod template;
+mod vad;
+mod watch;
+mod waveform;
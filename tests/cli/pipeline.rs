@@ -1,4 +1,7 @@
-use ffmpreg::cli::{Pipeline, is_batch_pattern, is_directory};
+use crate::common::create_test_wav_from_samples;
+use ffmpreg::cli::{BatchPipeline, ErrorPolicy, Pipeline, is_batch_pattern, is_directory};
+use ffmpreg::container::{MediaType, WavReader};
+use ffmpreg::io::{Cursor, Overwrite};
 use std::fs::{self, File};
 use std::io::Write;
 use tempfile::tempdir;
@@ -108,8 +111,11 @@ fn test_pipeline_wav_show_mode() {
 
 	let pipeline = Pipeline::new(input_path.to_str().unwrap().to_string(), None, true, vec![]);
 
-	let result = pipeline.run();
-	assert!(result.is_ok());
+	let summary = pipeline.show().unwrap();
+	assert!(summary.starts_with("Format: WAV\n"));
+	assert!(summary.contains("  Frame 0: pts=0"));
+	// the summary is returned, not printed, so running writes nothing
+	assert!(pipeline.run().is_err());
 }
 
 #[test]
@@ -193,8 +199,9 @@ fn test_pipeline_y4m_show_mode() {
 
 	let pipeline = Pipeline::new(input_path.to_str().unwrap().to_string(), None, true, vec![]);
 
-	let result = pipeline.run();
-	assert!(result.is_ok());
+	let summary = pipeline.show().unwrap();
+	assert!(summary.starts_with("Format: Y4M\n"));
+	assert!(summary.contains("\nFrames:\n  Frame 0:"));
 }
 
 #[test]
@@ -265,3 +272,265 @@ fn test_pipeline_invalid_transform() {
 	let result = pipeline.run();
 	assert!(result.is_err());
 }
+
+#[test]
+fn test_pipeline_wav_downmix_rewrites_header() {
+	let dir = tempdir().unwrap();
+	let input_path = dir.path().join("surround.wav");
+	let output_path = dir.path().join("stereo.wav");
+
+	// odd frame count so packets cannot line up with 4096 bytes by accident
+	let samples: Vec<i16> = (0..1001 * 6).map(|i| (i % 6) as i16 * 100).collect();
+	fs::write(&input_path, create_test_wav_from_samples(&samples, 48000, 6)).unwrap();
+
+	let pipeline = Pipeline::new(
+		input_path.to_str().unwrap().to_string(),
+		Some(output_path.to_str().unwrap().to_string()),
+		false,
+		vec!["downmix".to_string()],
+	);
+	pipeline.run().unwrap();
+
+	let reader = WavReader::new(Cursor::new(fs::read(&output_path).unwrap())).unwrap();
+	assert_eq!(reader.format().channels, 2);
+	assert_eq!(reader.format().sample_rate, 48000);
+
+	let output = fs::read(&output_path).unwrap();
+	assert_eq!(output.len(), 44 + 1001 * 4);
+}
+
+#[test]
+fn test_pipeline_refuses_existing_output() {
+	let dir = tempdir().unwrap();
+	let input_path = dir.path().join("input.wav");
+	let output_path = dir.path().join("output.wav");
+	fs::write(&input_path, create_test_wav()).unwrap();
+	fs::write(&output_path, b"keep").unwrap();
+
+	let pipeline = |overwrite| {
+		Pipeline::new(
+			input_path.to_str().unwrap().to_string(),
+			Some(output_path.to_str().unwrap().to_string()),
+			false,
+			vec![],
+		)
+		.with_overwrite(overwrite)
+	};
+
+	assert!(pipeline(Overwrite::Refuse).run().is_err());
+	assert_eq!(fs::read(&output_path).unwrap(), b"keep");
+
+	pipeline(Overwrite::Skip).run().unwrap();
+	assert_eq!(fs::read(&output_path).unwrap(), b"keep");
+
+	pipeline(Overwrite::Replace).run().unwrap();
+	assert_eq!(fs::read(&output_path).unwrap(), create_test_wav());
+}
+
+#[test]
+fn test_batch_pipeline_overwrite() {
+	let dir = tempdir().unwrap();
+	let input_dir = dir.path().join("in");
+	let output_dir = dir.path().join("out");
+	fs::create_dir_all(&input_dir).unwrap();
+	fs::create_dir_all(&output_dir).unwrap();
+	fs::write(input_dir.join("a.wav"), create_test_wav()).unwrap();
+	fs::write(input_dir.join("b.wav"), create_test_wav()).unwrap();
+	fs::write(output_dir.join("b.wav"), b"keep").unwrap();
+
+	let batch = |overwrite| {
+		BatchPipeline::new(
+			format!("{}/*.wav", input_dir.to_str().unwrap()),
+			output_dir.to_str().unwrap().to_string(),
+			false,
+			vec![],
+		)
+		.with_overwrite(overwrite)
+	};
+
+	// refused before anything is written
+	assert!(batch(Overwrite::Refuse).run().is_err());
+	assert!(!output_dir.join("a.wav").exists());
+
+	batch(Overwrite::Skip).run().unwrap();
+	assert!(output_dir.join("a.wav").exists());
+	assert_eq!(fs::read(output_dir.join("b.wav")).unwrap(), b"keep");
+
+	batch(Overwrite::Replace).run().unwrap();
+	assert_eq!(fs::read(output_dir.join("b.wav")).unwrap(), create_test_wav());
+}
+
+#[test]
+fn test_batch_pipeline_recursive_directory() {
+	let dir = tempdir().unwrap();
+	let input_dir = dir.path().join("in");
+	fs::create_dir_all(input_dir.join("a/b")).unwrap();
+	fs::write(input_dir.join("top.wav"), create_test_wav()).unwrap();
+	fs::write(input_dir.join("a/mid.wav"), create_test_wav()).unwrap();
+	fs::write(input_dir.join("a/b/deep.wav"), create_test_wav()).unwrap();
+	fs::write(input_dir.join("a/notes.txt"), b"not media").unwrap();
+	fs::write(input_dir.join("a/b/clip.y4m"), create_test_y4m()).unwrap();
+
+	// the output lives inside the input and is not walked into
+	let output_dir = input_dir.join("out");
+	let batch = |recursive: bool, extensions: Vec<String>| {
+		BatchPipeline::new(
+			input_dir.to_str().unwrap().to_string(),
+			output_dir.to_str().unwrap().to_string(),
+			false,
+			vec![],
+		)
+		.with_recursive(recursive)
+		.with_extensions(extensions)
+		.with_overwrite(Overwrite::Replace)
+	};
+
+	batch(false, vec![]).run().unwrap();
+	assert!(output_dir.join("top.wav").exists());
+	assert!(!output_dir.join("a").exists());
+
+	batch(true, vec![]).run().unwrap();
+	assert_eq!(fs::read(output_dir.join("a/b/deep.wav")).unwrap(), create_test_wav());
+	assert!(output_dir.join("a/mid.wav").exists());
+	assert!(output_dir.join("a/b/clip.y4m").exists());
+	assert!(!output_dir.join("a/notes.txt").exists());
+	assert!(!output_dir.join("out").exists());
+
+	fs::remove_dir_all(&output_dir).unwrap();
+	batch(true, vec![".Y4M".to_string()]).run().unwrap();
+	assert!(output_dir.join("a/b/clip.y4m").exists());
+	assert!(!output_dir.join("top.wav").exists());
+}
+
+#[test]
+fn test_batch_pipeline_output_template() {
+	let dir = tempdir().unwrap();
+	let input_dir = dir.path().join("in");
+	fs::create_dir_all(&input_dir).unwrap();
+	fs::write(input_dir.join("a.wav"), create_test_wav()).unwrap();
+	fs::write(input_dir.join("b.wav"), create_test_wav()).unwrap();
+
+	let batch = |template: &str| {
+		BatchPipeline::new(
+			format!("{}/*.wav", input_dir.to_str().unwrap()),
+			dir.path().join(template).to_str().unwrap().to_string(),
+			false,
+			vec![],
+		)
+	};
+
+	batch("out/{index:02}_{name}_{rate}hz.{ext}").run().unwrap();
+	assert_eq!(fs::read(dir.path().join("out/01_a_44100hz.wav")).unwrap(), create_test_wav());
+	assert!(dir.path().join("out/02_b_44100hz.wav").exists());
+
+	// every input would land on the same file
+	assert!(batch("same/{rate}.wav").run().is_err());
+	assert!(!dir.path().join("same").exists());
+}
+
+#[test]
+fn test_batch_pipeline_output_format() {
+	let dir = tempdir().unwrap();
+	let input_dir = dir.path().join("in");
+	let output_dir = dir.path().join("out");
+	fs::create_dir_all(&input_dir).unwrap();
+	fs::write(input_dir.join("a.wav"), create_test_wav()).unwrap();
+
+	BatchPipeline::new(
+		input_dir.to_str().unwrap().to_string(),
+		output_dir.to_str().unwrap().to_string(),
+		false,
+		vec![],
+	)
+	.with_output_format(MediaType::Flac)
+	.run()
+	.unwrap();
+
+	assert!(output_dir.join("a.flac").is_file());
+	assert!(!output_dir.join("a.wav").exists());
+	assert_eq!(&fs::read(output_dir.join("a.flac")).unwrap()[..4], b"fLaC");
+}
+
+#[test]
+fn test_batch_pipeline_resume() {
+	let dir = tempdir().unwrap();
+	let input_dir = dir.path().join("in");
+	let output_dir = dir.path().join("out");
+	let manifest = dir.path().join("batch.manifest");
+	fs::create_dir_all(&input_dir).unwrap();
+	for name in ["a.wav", "b.wav", "c.wav"] {
+		fs::write(input_dir.join(name), create_test_wav()).unwrap();
+	}
+	let batch = || {
+		BatchPipeline::new(
+			input_dir.to_str().unwrap().to_string(),
+			output_dir.to_str().unwrap().to_string(),
+			false,
+			vec![],
+		)
+		.with_resume(&manifest)
+	};
+
+	// a run cut off while writing b: a is done, b is half written, c untouched
+	batch().run().unwrap();
+	fs::write(output_dir.join("a.wav"), b"finished earlier").unwrap();
+	fs::write(output_dir.join("b.wav"), b"partial").unwrap();
+	fs::remove_file(output_dir.join("c.wav")).unwrap();
+	let text = fs::read_to_string(&manifest).unwrap();
+	let cut = text.match_indices("done\t").nth(1).unwrap().0;
+	fs::write(&manifest, &text[..cut]).unwrap();
+
+	batch().run().unwrap();
+	assert_eq!(fs::read(output_dir.join("a.wav")).unwrap(), b"finished earlier");
+	assert_eq!(fs::read(output_dir.join("b.wav")).unwrap(), create_test_wav());
+	assert_eq!(fs::read(output_dir.join("c.wav")).unwrap(), create_test_wav());
+
+	// everything is recorded now, so a third run writes nothing
+	fs::write(output_dir.join("c.wav"), b"left alone").unwrap();
+	batch().run().unwrap();
+	assert_eq!(fs::read(output_dir.join("c.wav")).unwrap(), b"left alone");
+}
+
+#[test]
+fn test_batch_pipeline_error_policy() {
+	let dir = tempdir().unwrap();
+	let input_dir = dir.path().join("in");
+	let output_dir = dir.path().join("out");
+	fs::create_dir_all(&input_dir).unwrap();
+	fs::write(input_dir.join("a.wav"), create_test_wav()).unwrap();
+	fs::write(input_dir.join("b.wav"), b"RIFF").unwrap();
+	fs::write(input_dir.join("c.wav"), create_test_wav()).unwrap();
+
+	let batch = |on_error| {
+		BatchPipeline::new(
+			input_dir.to_str().unwrap().to_string(),
+			output_dir.to_str().unwrap().to_string(),
+			false,
+			vec![],
+		)
+		.with_on_error(on_error)
+		.with_overwrite(Overwrite::Replace)
+	};
+
+	let report = batch(ErrorPolicy::Continue).run_report().unwrap();
+	assert_eq!((report.files, report.converted, report.not_run), (3, 2, 0));
+	assert_eq!(report.failed.len(), 1);
+	assert!(report.failed[0].0.ends_with("b.wav"));
+	assert!(output_dir.join("c.wav").exists());
+	assert!(report.summary().starts_with("Processed 3 files: 2 converted, 1 failed,"));
+	assert!(report.summary().contains("\n  failed: "));
+	assert!(batch(ErrorPolicy::Continue).run().is_err());
+
+	fs::remove_dir_all(&output_dir).unwrap();
+	let report = batch(ErrorPolicy::Stop).run_report().unwrap();
+	assert_eq!((report.converted, report.failed.len(), report.not_run), (1, 1, 1));
+	assert!(!output_dir.join("c.wav").exists());
+}
+
+#[test]
+fn test_error_policy_from_name() {
+	assert_eq!(ErrorPolicy::from_name("continue"), Some(ErrorPolicy::Continue));
+	assert_eq!(ErrorPolicy::from_name("STOP"), Some(ErrorPolicy::Stop));
+	assert_eq!(ErrorPolicy::from_name("retry"), None);
+	assert_eq!(ErrorPolicy::default(), ErrorPolicy::Continue);
+}
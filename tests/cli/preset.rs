@@ -0,0 +1,36 @@
+use ffmpreg::cli::preset::{BUILTIN_PRESETS, Preset};
+use ffmpreg::codecs::CodecId;
+use ffmpreg::transform::TransformSpec;
+
+#[test]
+fn test_builtin_presets_parse() {
+	for name in BUILTIN_PRESETS {
+		let preset = Preset::builtin(name).unwrap();
+		assert_eq!(preset.name, name);
+		if let Some(codec) = &preset.codec {
+			assert!(CodecId::from_name(codec).is_some(), "{}", name);
+		}
+		for transform in preset.transforms() {
+			assert!(TransformSpec::parse(&transform).is_ok(), "{}: {}", name, transform);
+		}
+	}
+	assert!(Preset::builtin("podcasts").is_none());
+}
+
+#[test]
+fn test_preset_transforms_resample_first() {
+	let preset = Preset::builtin("podcast").unwrap();
+	let transforms = preset.transforms();
+	assert_eq!(transforms[0], "resample=44100");
+	assert_eq!(transforms.last().map(String::as_str), Some("loudnorm=-16,-1.5"));
+}
+
+#[test]
+fn test_preset_output_name() {
+	let preset = Preset::builtin("archive").unwrap();
+	assert_eq!(preset.output_name("take1"), "take1.flac");
+	assert_eq!(preset.output_name("take1.wav"), "take1.wav");
+
+	let preset = Preset::builtin("web-video").unwrap();
+	assert_eq!(preset.output_name("clip"), "clip");
+}
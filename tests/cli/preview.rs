@@ -0,0 +1,72 @@
+use ffmpreg::cli::preview::{loudest_window, make_preview, normalize_rms, preview_path, rms_dbfs};
+use ffmpreg::container::WavReader;
+use ffmpreg::core::Demuxer;
+use ffmpreg::io::Cursor;
+use tempfile::tempdir;
+
+#[test]
+fn test_loudest_window() {
+	let samples = [1, 1, 1, 9, 9, 1, 1, 1];
+	assert_eq!(loudest_window(&samples, 1, 2), 3);
+	assert_eq!(loudest_window(&samples, 1, 20), 0);
+
+	// stereo frames: energy is summed across channels
+	let stereo = [0, 0, 5, 5, 0, 9, 0, 0];
+	assert_eq!(loudest_window(&stereo, 2, 1), 2);
+}
+
+#[test]
+fn test_rms_dbfs() {
+	assert!((rms_dbfs(&[16384, -16384]) - -6.0206).abs() < 0.01);
+	assert_eq!(rms_dbfs(&[]), f64::NEG_INFINITY);
+}
+
+#[test]
+fn test_normalize_rms() {
+	let mut samples = vec![1000, -1000, 1000, -1000];
+	let gain = normalize_rms(&mut samples, rms_dbfs(&[2000]));
+	assert!((gain - 6.0206).abs() < 0.01);
+	assert_eq!(samples, vec![2000, -2000, 2000, -2000]);
+
+	let mut silence = vec![0; 4];
+	assert_eq!(normalize_rms(&mut silence, -14.0), 0.0);
+
+	// a spike would clip at the target RMS, so the gain stops with the peak at -1 dBFS
+	let mut spiky = vec![100i16; 1000];
+	spiky[500] = 16000;
+	let gain = normalize_rms(&mut spiky, -14.0);
+	let ceiling = 32768.0 * 10f64.powf(-1.0 / 20.0);
+	assert!((gain - 20.0 * (ceiling / 16000.0).log10()).abs() < 1e-9, "{}", gain);
+	assert!(spiky[500] as f64 <= ceiling.round() && spiky[500] > 29000);
+	assert!(rms_dbfs(&spiky) < -14.0);
+}
+
+#[test]
+fn test_preview_path() {
+	assert_eq!(preview_path("music/song.flac", "out", "_preview"), "out/song_preview.wav");
+}
+
+#[test]
+fn test_make_preview_picks_loud_section() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+
+	// one quiet second, one loud half second, one quiet second at 100 Hz
+	let mut samples = vec![100i16; 100];
+	samples.extend((0..50).map(|i| if i % 2 == 0 { 8000 } else { -8000 }));
+	samples.extend(vec![100i16; 100]);
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 100, 1)).unwrap();
+
+	let clip = make_preview(input.to_str().unwrap(), output.to_str().unwrap(), 0.5, -20.0).unwrap();
+	assert_eq!(clip.start, 1.0);
+	assert_eq!(clip.duration, 0.5);
+
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut written = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		written.extend(packet.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])));
+	}
+	assert_eq!(written.len(), 50);
+	assert!((rms_dbfs(&written) - -20.0).abs() < 0.01);
+}
@@ -0,0 +1,44 @@
+use ffmpreg::cli::sample::{FrameSampleSpec, render_template, sample_frames};
+use tempfile::tempdir;
+
+#[test]
+fn test_frame_sample_spec_parse() {
+	let spec = FrameSampleSpec::parse("every=2s,scale=256x256,crop=224x200").unwrap();
+	assert_eq!(spec.every, 2.0);
+	assert_eq!(spec.scale, Some((256, 256)));
+	assert_eq!(spec.crop, Some((224, 200)));
+
+	assert_eq!(FrameSampleSpec::parse("every=500ms").unwrap().scale, None);
+	assert!(FrameSampleSpec::parse("scale=10x10").is_err());
+	assert!(FrameSampleSpec::parse("every=1s,crop=10").is_err());
+	assert!(FrameSampleSpec::parse("every=1s,fps=2").is_err());
+}
+
+#[test]
+fn test_render_template() {
+	assert_eq!(render_template("frames/{stem}_{n}.png", "clips/a.y4m", 3), "frames/a_3.png");
+}
+
+#[test]
+fn test_sample_frames_from_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("clip.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let template = format!("{}/out/{{stem}}_{{n}}.png", dir.path().to_str().unwrap());
+
+	// 3 frames at 30 fps, one image every two frames
+	let spec = FrameSampleSpec::parse("every=66ms,scale=16x16,crop=4x2").unwrap();
+	let written = sample_frames(input.to_str().unwrap(), &spec, &template).unwrap();
+	assert_eq!(written.len(), 2);
+	assert!(written[1].ends_with("out/clip_1.png"));
+
+	let png = std::fs::read(&written[0]).unwrap();
+	assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 4);
+	assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 2);
+}
+
+#[test]
+fn test_sample_frames_rejects_audio() {
+	let spec = FrameSampleSpec::parse("every=1s").unwrap();
+	assert!(sample_frames("song.wav", &spec, "{stem}.png").is_err());
+}
@@ -0,0 +1,95 @@
+use ffmpreg::cli::slice::{
+	Manifest, ManifestRow, SliceFormat, SliceOptions, mix_to_mono, slice_file, window_ranges,
+};
+use ffmpreg::container::WavReader;
+use ffmpreg::core::Demuxer;
+use ffmpreg::io::Cursor;
+use tempfile::tempdir;
+
+#[test]
+fn test_window_ranges_with_overlap() {
+	assert_eq!(window_ranges(10, 4, 0.5), vec![(0, 4), (2, 6), (4, 8), (6, 10)]);
+	assert_eq!(window_ranges(10, 4, 0.0), vec![(0, 4), (4, 8)]);
+	assert_eq!(window_ranges(3, 4, 0.5), vec![]);
+}
+
+#[test]
+fn test_mix_to_mono() {
+	assert_eq!(mix_to_mono(&[100, 300, -50, 50], 2), vec![200, 0]);
+	assert_eq!(mix_to_mono(&[1, 2, 3], 1), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_manifest_csv_quotes_fields() {
+	let mut manifest = Manifest::new();
+	manifest.rows.push(ManifestRow {
+		file: "a_00000.wav".to_string(),
+		source: "rec, take 1.wav".to_string(),
+		start: 0.0,
+		end: 10.0,
+	});
+	assert_eq!(
+		manifest.to_csv(),
+		"file,source,start,end\na_00000.wav,\"rec, take 1.wav\",0.000,10.000\n"
+	);
+}
+
+#[test]
+fn test_slice_options_validate() {
+	let options =
+		SliceOptions { window: 1.0, overlap: 1.0, sample_rate: 100, format: SliceFormat::Wav };
+	assert!(options.validate().is_err());
+	assert!(SliceOptions { overlap: 0.5, ..options }.validate().is_ok());
+	assert_eq!(SliceFormat::from_name("f32"), Some(SliceFormat::F32));
+	assert_eq!(SliceFormat::from_name("mp3"), None);
+}
+
+fn write_stereo_input(dir: &std::path::Path) -> String {
+	// 3 s of stereo at 100 Hz, both channels at 1000
+	let samples = vec![1000i16; 600];
+	let input = dir.join("rec.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 100, 2)).unwrap();
+	input.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_slice_file_wav_windows() {
+	let dir = tempdir().unwrap();
+	let input = write_stereo_input(dir.path());
+	let out = dir.path().to_str().unwrap();
+
+	let options =
+		SliceOptions { window: 1.0, overlap: 0.5, sample_rate: 50, format: SliceFormat::Wav };
+	let mut manifest = Manifest::new();
+	assert_eq!(slice_file(&input, out, &options, &mut manifest).unwrap(), 5);
+
+	assert_eq!(manifest.rows[1].file, "rec_00001.wav");
+	assert_eq!((manifest.rows[1].start, manifest.rows[1].end), (0.5, 1.5));
+
+	let data = std::fs::read(dir.path().join("rec_00004.wav")).unwrap();
+	let mut reader = WavReader::new(Cursor::new(data)).unwrap();
+	assert_eq!(reader.format().channels, 1);
+	assert_eq!(reader.format().sample_rate, 50);
+	let mut total = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		total += packet.data.len() / 2;
+	}
+	assert_eq!(total, 50);
+}
+
+#[test]
+fn test_slice_file_raw_f32() {
+	let dir = tempdir().unwrap();
+	let input = write_stereo_input(dir.path());
+	let out = dir.path().to_str().unwrap();
+
+	let options =
+		SliceOptions { window: 2.0, overlap: 0.0, sample_rate: 100, format: SliceFormat::F32 };
+	let mut manifest = Manifest::new();
+	assert_eq!(slice_file(&input, out, &options, &mut manifest).unwrap(), 1);
+
+	let data = std::fs::read(dir.path().join("rec_00000.f32")).unwrap();
+	assert_eq!(data.len(), 200 * 4);
+	let first = f32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+	assert!((first - 1000.0 / 32768.0).abs() < 1e-6);
+}
@@ -0,0 +1,62 @@
+use ffmpreg::cli::template::{TemplateVars, civil_from_days, is_template, render};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn vars() -> TemplateVars {
+	TemplateVars {
+		name: "take".to_string(),
+		index: 7,
+		codec: "pcm_s16le".to_string(),
+		rate: "48000".to_string(),
+		date: "2024-03-01".to_string(),
+		ext: "wav".to_string(),
+		dir: "live/day1".to_string(),
+	}
+}
+
+#[test]
+fn test_render_template() {
+	let vars = vars();
+	assert_eq!(render("out/{name}_{rate}hz.{ext}", &vars).unwrap(), "out/take_48000hz.wav");
+	assert_eq!(render("{index:03}-{stem}.{ext}", &vars).unwrap(), "007-take.wav");
+	assert_eq!(
+		render("{date}/{dir}/{codec}_{index}.flac", &vars).unwrap(),
+		"2024-03-01/live/day1/pcm_s16le_7.flac"
+	);
+	assert_eq!(render("plain.wav", &vars).unwrap(), "plain.wav");
+}
+
+#[test]
+fn test_render_template_errors() {
+	assert!(render("{nmae}.wav", &vars()).is_err());
+	assert!(render("{name.wav", &vars()).is_err());
+	assert!(render("{index:x}.wav", &vars()).is_err());
+	assert!(is_template("out/{name}.wav"));
+	assert!(!is_template("out/"));
+}
+
+#[test]
+fn test_template_vars_probe_input() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("clip.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let input = input.to_str().unwrap();
+
+	let vars =
+		TemplateVars::for_input("{name}_{codec}_{rate}fps.{ext}", input, Path::new("a/clip.y4m"), 2);
+	assert_eq!((vars.name.as_str(), vars.ext.as_str(), vars.dir.as_str()), ("clip", "y4m", "a"));
+	assert_eq!((vars.codec.as_str(), vars.rate.as_str()), ("rawvideo", "30"));
+	assert_eq!(vars.index, 2);
+
+	// the input is only opened when the template asks for what is inside
+	let vars = TemplateVars::for_input("{name}.{ext}", input, Path::new("clip.y4m"), 1);
+	assert!(vars.codec.is_empty() && vars.dir.is_empty());
+}
+
+#[test]
+fn test_civil_from_days() {
+	assert_eq!(civil_from_days(0), (1970, 1, 1));
+	assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+	assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+	assert_eq!(civil_from_days(-1), (1969, 12, 31));
+}
@@ -0,0 +1,66 @@
+use ffmpreg::cli::slice::Manifest;
+use ffmpreg::cli::vad::{VadOptions, detect_speech, split_file};
+use tempfile::tempdir;
+
+const RATE: u32 = 16000;
+
+// 200 Hz tone bursts at the given (start, end) seconds, silence elsewhere
+fn bursts(total: f64, spans: &[(f64, f64)]) -> Vec<i16> {
+	let len = (total * RATE as f64) as usize;
+	(0..len)
+		.map(|n| {
+			let t = n as f64 / RATE as f64;
+			if spans.iter().any(|&(start, end)| t >= start && t < end) {
+				(8000.0 * (2.0 * std::f64::consts::PI * 200.0 * t).sin()) as i16
+			} else {
+				0
+			}
+		})
+		.collect()
+}
+
+#[test]
+fn test_detect_speech_segments() {
+	let samples = bursts(3.0, &[(0.5, 1.0), (1.1, 1.4), (2.0, 2.5), (2.9, 3.0)]);
+	let options = VadOptions { padding: 0.0, ..VadOptions::default() };
+
+	// the 100 ms pause is bridged and the 100 ms blip is too short to keep
+	assert_eq!(detect_speech(&samples, RATE, &options), vec![(8000, 22400), (32000, 40000)]);
+
+	let padded = VadOptions { padding: 0.1, ..options };
+	assert_eq!(detect_speech(&samples, RATE, &padded), vec![(6400, 24000), (30400, 41600)]);
+}
+
+#[test]
+fn test_detect_speech_rejects_hiss() {
+	// alternating full-scale samples are loud but cross zero every sample
+	let hiss: Vec<i16> = (0..RATE).map(|n| if n % 2 == 0 { 8000 } else { -8000 }).collect();
+	assert!(detect_speech(&hiss, RATE, &VadOptions::default()).is_empty());
+	assert!(detect_speech(&vec![0; RATE as usize], RATE, &VadOptions::default()).is_empty());
+}
+
+#[test]
+fn test_vad_options_validate() {
+	assert!(VadOptions::default().validate().is_ok());
+	assert!(VadOptions { sample_rate: 0, ..VadOptions::default() }.validate().is_err());
+	assert!(VadOptions { padding: -0.1, ..VadOptions::default() }.validate().is_err());
+}
+
+#[test]
+fn test_split_file_writes_segments() {
+	let dir = tempdir().unwrap();
+	let samples: Vec<i16> =
+		bursts(3.0, &[(0.5, 1.0), (2.0, 2.5)]).into_iter().flat_map(|s| [s, s]).collect();
+	let input = dir.path().join("call.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, RATE, 2)).unwrap();
+	let input = input.to_str().unwrap().to_string();
+
+	let mut manifest = Manifest::new();
+	let options = VadOptions::default();
+	assert_eq!(split_file(&input, dir.path().to_str().unwrap(), &options, &mut manifest).unwrap(), 2);
+
+	assert_eq!(manifest.rows[1].file, "call_00001.wav");
+	assert_eq!((manifest.rows[1].start, manifest.rows[1].end), (1.9, 2.6));
+	let data = std::fs::read(dir.path().join("call_00000.wav")).unwrap();
+	assert_eq!(data.len(), 44 + 11200 * 2);
+}
@@ -0,0 +1,73 @@
+use crate::common::create_test_wav_from_samples;
+use ffmpreg::cli::watch::Watcher;
+use std::fs;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn test_wav() -> Vec<u8> {
+	let samples: Vec<i16> = (0..256).map(|i| (i * 40) as i16).collect();
+	create_test_wav_from_samples(&samples, 8000, 1)
+}
+
+#[test]
+fn test_watcher_waits_for_file_to_settle() {
+	let dir = tempdir().unwrap();
+	let inbox = dir.path().join("in");
+	fs::create_dir_all(&inbox).unwrap();
+	let mut watcher =
+		Watcher::new(&inbox, dir.path().join("out"), vec![]).with_settle(Duration::from_secs(2));
+	watcher.prime().unwrap();
+
+	let start = Instant::now();
+	let path = inbox.join("a.wav");
+	fs::write(&path, &test_wav()[..20]).unwrap();
+	assert!(watcher.poll(start).unwrap().is_empty());
+
+	// still growing: the wait starts over
+	fs::write(&path, test_wav()).unwrap();
+	assert!(watcher.poll(start + Duration::from_secs(3)).unwrap().is_empty());
+	assert!(watcher.poll(start + Duration::from_secs(4)).unwrap().is_empty());
+
+	assert_eq!(watcher.poll(start + Duration::from_secs(5)).unwrap(), vec![path]);
+	// converted once, not on every poll after
+	assert!(watcher.poll(start + Duration::from_secs(9)).unwrap().is_empty());
+}
+
+#[test]
+fn test_watcher_ignores_existing_and_unwanted_files() {
+	let dir = tempdir().unwrap();
+	let inbox = dir.path().join("in");
+	fs::create_dir_all(&inbox).unwrap();
+	fs::write(inbox.join("old.wav"), test_wav()).unwrap();
+	let mut watcher =
+		Watcher::new(&inbox, dir.path().join("out"), vec![]).with_settle(Duration::ZERO);
+	watcher.prime().unwrap();
+
+	fs::write(inbox.join("notes.txt"), b"not media").unwrap();
+	fs::write(inbox.join("new.wav"), test_wav()).unwrap();
+	let now = Instant::now();
+	assert!(watcher.poll(now).unwrap().is_empty());
+	assert_eq!(watcher.poll(now).unwrap(), vec![inbox.join("new.wav")]);
+}
+
+#[test]
+fn test_watcher_converts_into_output_dir() {
+	let dir = tempdir().unwrap();
+	let inbox = dir.path().join("in");
+	let output_dir = dir.path().join("out");
+	fs::create_dir_all(inbox.join("day1")).unwrap();
+	let mut watcher =
+		Watcher::new(&inbox, &output_dir, vec![]).with_recursive(true).with_settle(Duration::ZERO);
+	watcher.prime().unwrap();
+
+	let path = inbox.join("day1").join("take.wav");
+	fs::write(&path, test_wav()).unwrap();
+	let now = Instant::now();
+	watcher.poll(now).unwrap();
+	let ready = watcher.poll(now).unwrap();
+	assert_eq!(ready, vec![path.clone()]);
+
+	let output = watcher.convert(&path).unwrap();
+	assert_eq!(output, output_dir.join("day1").join("take.wav"));
+	assert_eq!(fs::read(&output).unwrap(), test_wav());
+}
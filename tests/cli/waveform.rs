@@ -0,0 +1,44 @@
+use ffmpreg::cli::waveform::{Waveform, run_waveform, waveform};
+use tempfile::tempdir;
+
+#[test]
+fn test_waveform_peaks_per_column() {
+	let left = vec![0.5, -0.5, 0.25, 0.0];
+	let right = vec![0.0; 4];
+	let wave = Waveform::from_planes(&[left, right], 4, 2);
+	assert_eq!(wave.channels[0], vec![(-0.5, 0.5), (0.0, 0.25)]);
+	assert_eq!(wave.channels[1], vec![(0.0, 0.0), (0.0, 0.0)]);
+	assert_eq!(wave.duration(), 1.0);
+
+	// silence is a line through the middle, a full swing fills the column
+	let wave = Waveform::from_planes(&[vec![0.0, 0.0, -1.0, 1.0]], 4, 2);
+	let text = wave.to_text(4);
+	let lines: Vec<&str> = text.lines().collect();
+	assert_eq!(lines, vec!["  channel 0", "   █", "   █", "  ██", "   █"]);
+
+	let image = wave.to_image(10);
+	assert_eq!((image.width, image.height), (2, 10));
+	assert_eq!(image.pixel(0, 0), [32, 32, 32]);
+	assert_eq!(image.pixel(0, 5), image.pixel(1, 0));
+	assert_eq!(image.pixel(1, 9), image.pixel(1, 0));
+}
+
+#[test]
+fn test_waveform_from_file() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let samples: Vec<i16> = (0..8000).map(|i| if i < 4000 { 0 } else { 16384 }).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+	let input = input.to_str().unwrap();
+
+	// the first half silent, the second at half scale
+	let wave = waveform(input, 4).unwrap();
+	assert_eq!(wave.samples, 8000);
+	assert_eq!(wave.channels[0][..2], [(0.0, 0.0), (0.0, 0.0)]);
+	assert_eq!(wave.channels[0][3], (0.0, 0.5));
+
+	let output = dir.path().join("wave.png");
+	run_waveform(input, Some(output.to_str().unwrap())).unwrap();
+	assert!(std::fs::read(&output).unwrap().starts_with(b"\x89PNG"));
+	assert!(run_waveform(input, Some("wave.gif")).is_err());
+}
@@ -146,3 +146,71 @@ fn test_flac_encoder_varying_signal() {
 
 	assert!(!packet.data.is_empty());
 }
+
+#[test]
+fn test_flac_planar_roundtrip() {
+	let left: Vec<i16> = (0..1024).map(|i| (3000.0 * (i as f64 * 0.05).sin()) as i16).collect();
+	let right: Vec<i16> = (0..1024).map(|i| (-2000.0 * (i as f64 * 0.02).cos()) as i16).collect();
+	let packed: Vec<u8> = left
+		.iter()
+		.zip(&right)
+		.flat_map(|(l, r)| [l.to_le_bytes(), r.to_le_bytes()])
+		.flatten()
+		.collect();
+	let timebase = Timebase::new(1, 44100);
+
+	let planar_frame = Frame::new_audio(
+		FrameAudio::from_planes_i16(&[left.clone(), right.clone()], 44100),
+		timebase,
+		0,
+	);
+	let packed_frame = Frame::new_audio(FrameAudio::new(packed, 44100, 2), timebase, 0);
+
+	// planar input skips the deinterleave but encodes the same block
+	let packet = FlacEncoder::new(44100, 2, 16, 1024).encode(planar_frame).unwrap().unwrap();
+	let expected = FlacEncoder::new(44100, 2, 16, 1024).encode(packed_frame).unwrap().unwrap();
+	assert_eq!(packet.data, expected.data);
+
+	let mut decoder = FlacDecoder::new(&create_default_format()).with_planar(true);
+	let frame = decoder.decode(packet).unwrap().unwrap();
+	let audio = frame.audio().unwrap();
+	assert_eq!(audio.format, ffmpreg::core::SampleFormat::S16P);
+	assert_eq!(audio.nb_samples, 1024);
+	assert_eq!(audio.planes_i16(), vec![left, right]);
+}
+
+#[test]
+fn test_flac_roundtrip_with_steps() {
+	// a sawtooth's wrap leaves a residual far above the rest; full-scale jumps
+	// between extremes need more than the largest rice parameter
+	let saw: Vec<i16> = (0..1024).map(|i| ((i * 37) % 20000 - 10000) as i16).collect();
+	let square: Vec<i16> = (0..1024).map(|i| if i % 3 == 0 { i16::MAX } else { i16::MIN }).collect();
+	let timebase = Timebase::new(1, 44100);
+	for samples in [saw, square] {
+		let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+		let frame = Frame::new_audio(FrameAudio::new(data, 44100, 1), timebase, 0);
+		let packet = FlacEncoder::new(44100, 1, 16, 1024).encode(frame).unwrap().unwrap();
+
+		let format = FlacFormat { channels: 1, ..create_default_format() };
+		let frame = FlacDecoder::new(&format).with_planar(true).decode(packet).unwrap().unwrap();
+		assert_eq!(frame.audio().unwrap().planes_i16(), vec![samples]);
+	}
+}
+
+#[test]
+fn test_flac_frame_crcs() {
+	use ffmpreg::codecs::flac::frame::{crc8, crc16};
+	// the check values of CRC-8 (poly 0x07) and CRC-16/BUYPASS (poly 0x8005)
+	assert_eq!(crc8(b"123456789"), 0xf4);
+	assert_eq!(crc16(b"123456789"), 0xfee8);
+
+	// an encoded frame carries both: the header's, and the whole frame's last
+	let mut encoder = FlacEncoder::new(44100, 2, 16, 4096);
+	let samples: Vec<u8> = (0..8192).flat_map(|i| ((i % 300) as i16).to_le_bytes()).collect();
+	let frame = Frame::new_audio(FrameAudio::new(samples, 44100, 2), Timebase::new(1, 44100), 0);
+	let data = encoder.encode(frame).unwrap().unwrap().data;
+	let (body, crc) = data.split_at(data.len() - 2);
+	assert_eq!(crc16(body), u16::from_be_bytes([crc[0], crc[1]]));
+	// sync, codes and a one byte frame number; 4096 has a block size code of its own
+	assert_eq!(crc8(&data[..5]), data[5]);
+}
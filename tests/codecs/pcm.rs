@@ -1,6 +1,6 @@
-use ffmpreg::codecs::{PcmDecoder, PcmEncoder};
+use ffmpreg::codecs::{CodecId, PcmDecoder, PcmEncoder};
 use ffmpreg::container::WavFormat;
-use ffmpreg::core::{Decoder, Encoder, Frame, FrameAudio, Packet, Timebase};
+use ffmpreg::core::{Decoder, Encoder, Frame, FrameAudio, Packet, SampleFormat, Timebase};
 
 fn create_test_format() -> WavFormat {
 	WavFormat { channels: 1, sample_rate: 44100, bit_depth: 16 }
@@ -164,3 +164,114 @@ fn test_pcm_multiple_packets() {
 		assert_eq!(output.pts, i as i64 * 128);
 	}
 }
+
+#[test]
+fn test_codec_id_from_name() {
+	assert_eq!(CodecId::from_name("pcm_s16be"), Some(CodecId::PcmS16Be));
+	assert_eq!(CodecId::from_name("s24le"), Some(CodecId::PcmS24Le));
+	assert_eq!(CodecId::from_name("pcm"), Some(CodecId::PcmS16Le));
+	assert_eq!(CodecId::from_name("PCM_U8"), Some(CodecId::PcmU8));
+	assert_eq!(CodecId::from_name("mp3"), None);
+	assert_eq!(CodecId::PcmS32Be.name(), "pcm_s32be");
+}
+
+#[test]
+fn test_codec_id_pcm_properties() {
+	assert!(CodecId::PcmS24Be.is_pcm());
+	assert!(CodecId::PcmS24Be.is_big_endian());
+	assert!(!CodecId::Flac.is_pcm());
+	assert_eq!(CodecId::PcmU8.pcm_bytes_per_sample(), Some(1));
+	assert_eq!(CodecId::PcmS32Le.pcm_bytes_per_sample(), Some(4));
+	assert_eq!(CodecId::pcm_from_bit_depth(24, true), Some(CodecId::PcmS24Be));
+	assert_eq!(CodecId::pcm_from_bit_depth(12, false), None);
+}
+
+#[test]
+fn test_wav_format_codec_id() {
+	let format = WavFormat { channels: 2, sample_rate: 44100, bit_depth: 24 };
+	assert_eq!(format.codec_id(), CodecId::PcmS24Le);
+	assert_eq!(create_test_format().codec_id(), CodecId::PcmS16Le);
+}
+
+#[test]
+fn test_pcm_decoder_s16be() {
+	let timebase = Timebase::new(1, 44100);
+	let mut decoder = PcmDecoder::new(create_test_format()).with_codec(CodecId::PcmS16Be);
+
+	let packet = Packet::new(vec![0x12, 0x34, 0xFF, 0xFE], 0, timebase);
+	let frame = decoder.decode(packet).unwrap().unwrap();
+	let audio = frame.audio().unwrap();
+
+	assert_eq!(audio.nb_samples, 2);
+	assert_eq!(audio.data, vec![0x34, 0x12, 0xFE, 0xFF]);
+}
+
+#[test]
+fn test_pcm_decoder_u8() {
+	let timebase = Timebase::new(1, 44100);
+	let mut decoder = PcmDecoder::new(create_test_format()).with_codec(CodecId::PcmU8);
+
+	let packet = Packet::new(vec![128, 255, 0], 0, timebase);
+	let frame = decoder.decode(packet).unwrap().unwrap();
+	let audio = frame.audio().unwrap();
+	let samples: Vec<i16> = audio.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+
+	assert_eq!(audio.nb_samples, 3);
+	assert_eq!(samples, vec![0, 127 << 8, -32768]);
+}
+
+#[test]
+fn test_pcm_decoder_s24_keeps_every_bit() {
+	let timebase = Timebase::new(1, 44100);
+	let mut le = PcmDecoder::new(create_test_format()).with_codec(CodecId::PcmS24Le);
+	let mut be = PcmDecoder::new(create_test_format()).with_codec(CodecId::PcmS24Be);
+
+	let le_frame = le.decode(Packet::new(vec![0xAA, 0x34, 0x12], 0, timebase)).unwrap().unwrap();
+	let be_frame = be.decode(Packet::new(vec![0x12, 0x34, 0xAA], 0, timebase)).unwrap().unwrap();
+
+	let expected = vec![0x1234AA as f32 / 8388608.0];
+	assert_eq!(le_frame.audio().unwrap().format, SampleFormat::F32);
+	assert_eq!(le_frame.audio().unwrap().samples_f32(), expected);
+	assert_eq!(be_frame.audio().unwrap().samples_f32(), expected);
+}
+
+#[test]
+fn test_pcm_s24_roundtrip_is_exact() {
+	let timebase = Timebase::new(1, 44100);
+	let samples: Vec<i32> = vec![0, 1, -1, 0x7fffff, -0x800000, 0x1234AA, -0x0fedcb];
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()[..3].to_vec()).collect();
+
+	let mut decoder = PcmDecoder::new(create_test_format()).with_codec(CodecId::PcmS24Le);
+	let mut encoder = PcmEncoder::new(timebase).with_codec(CodecId::PcmS24Le);
+	let frame = decoder.decode(Packet::new(data.clone(), 0, timebase)).unwrap().unwrap();
+	assert_eq!(frame.audio().unwrap().nb_samples, samples.len());
+	assert_eq!(encoder.encode(frame).unwrap().unwrap().data, data);
+}
+
+#[test]
+fn test_pcm_variant_roundtrip() {
+	let timebase = Timebase::new(1, 44100);
+	let samples: Vec<i16> = vec![0, 256, -256, 32512, -32768];
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+	for codec in [
+		CodecId::PcmU8,
+		CodecId::PcmS16Be,
+		CodecId::PcmS24Le,
+		CodecId::PcmS24Be,
+		CodecId::PcmS32Le,
+		CodecId::PcmS32Be,
+	] {
+		let mut encoder = PcmEncoder::new(timebase).with_codec(codec);
+		let mut decoder = PcmDecoder::new(create_test_format()).with_codec(codec);
+
+		let audio = FrameAudio::new(data.clone(), 44100, 1);
+		let frame = Frame::new_audio(audio, timebase, 0);
+		let packet = encoder.encode(frame).unwrap().unwrap();
+		assert_eq!(packet.size(), samples.len() * codec.pcm_bytes_per_sample().unwrap());
+
+		let mut decoded = decoder.decode(packet).unwrap().unwrap().audio().unwrap().clone();
+		decoded.convert(SampleFormat::S16);
+		assert_eq!(decoded.data, data, "roundtrip failed for {:?}", codec);
+	}
+}
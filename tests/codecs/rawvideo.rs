@@ -174,3 +174,70 @@ fn test_rawvideo_frame_size_c420() {
 	let chroma = luma / 4 * 2;
 	assert_eq!(format.frame_size(), luma + chroma);
 }
+
+fn create_format_with(colorspace: ffmpreg::container::y4m::Colorspace) -> Y4mFormat {
+	Y4mFormat { colorspace: Some(colorspace), ..create_test_format() }
+}
+
+#[test]
+fn test_rawvideo_frame_size_non_420() {
+	use ffmpreg::container::y4m::Colorspace;
+
+	let luma = 16 * 16;
+	assert_eq!(create_format_with(Colorspace::C422).frame_size(), luma * 2);
+	assert_eq!(create_format_with(Colorspace::C444).frame_size(), luma * 3);
+	assert_eq!(create_format_with(Colorspace::Mono).frame_size(), luma);
+}
+
+#[test]
+fn test_rawvideo_decoder_reports_pixel_format() {
+	use ffmpreg::container::y4m::Colorspace;
+
+	let timebase = Timebase::new(1, 30);
+	let cases = [
+		(None, VideoFormat::YUV420),
+		(Some(Colorspace::C420jpeg), VideoFormat::YUV420),
+		(Some(Colorspace::C422), VideoFormat::YUV422),
+		(Some(Colorspace::C444), VideoFormat::YUV444),
+		(Some(Colorspace::Mono), VideoFormat::GRAY8),
+	];
+
+	for (colorspace, expected) in cases {
+		let format = Y4mFormat { colorspace, ..create_test_format() };
+		let data = vec![0u8; format.frame_size()];
+		let mut decoder = RawVideoDecoder::new(format);
+
+		let frame = decoder.decode(Packet::new(data, 0, timebase)).unwrap().unwrap();
+		assert_eq!(frame.video().unwrap().format, expected);
+	}
+}
+
+#[test]
+fn test_video_format_chroma_dimensions() {
+	assert_eq!(VideoFormat::YUV420.chroma_dimensions(16, 8), (8, 4));
+	assert_eq!(VideoFormat::YUV422.chroma_dimensions(16, 8), (8, 8));
+	assert_eq!(VideoFormat::YUV444.chroma_dimensions(16, 8), (16, 8));
+	assert_eq!(VideoFormat::GRAY8.chroma_dimensions(16, 8), (0, 0));
+	assert_eq!(VideoFormat::RGB24.frame_size(4, 4), 48);
+}
+
+#[test]
+fn test_flip_yuv422_frame() {
+	use ffmpreg::transform::Flip;
+
+	let (width, height) = (4u32, 2u32);
+	let luma: Vec<u8> = (0..8).collect();
+	let u_plane: Vec<u8> = vec![10, 11, 12, 13];
+	let v_plane: Vec<u8> = vec![20, 21, 22, 23];
+	let data = [luma, u_plane, v_plane].concat();
+
+	let video = FrameVideo::new(data, width, height, VideoFormat::YUV422);
+	let frame = Frame::new_video(video, Timebase::new(1, 30), 0);
+	let flipped = Flip::horizontal(width, height).apply_yuv420(&frame).unwrap();
+
+	let out = &flipped.video().unwrap().data;
+	assert_eq!(out.len(), 16);
+	assert_eq!(&out[0..4], &[3, 2, 1, 0]);
+	assert_eq!(&out[8..12], &[11, 10, 13, 12]);
+	assert_eq!(&out[12..16], &[21, 20, 23, 22]);
+}
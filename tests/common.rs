@@ -37,6 +37,32 @@ pub fn create_test_wav_data() -> Vec<u8> {
 	wav
 }
 
+pub fn create_test_wav_from_samples(samples: &[i16], sample_rate: u32, channels: u16) -> Vec<u8> {
+	let data_size = samples.len() as u32 * 2;
+	let mut wav = Vec::new();
+
+	wav.extend_from_slice(b"RIFF");
+	wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+	wav.extend_from_slice(b"WAVE");
+
+	wav.extend_from_slice(b"fmt ");
+	wav.extend_from_slice(&16u32.to_le_bytes());
+	wav.extend_from_slice(&1u16.to_le_bytes());
+	wav.extend_from_slice(&channels.to_le_bytes());
+	wav.extend_from_slice(&sample_rate.to_le_bytes());
+	wav.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes());
+	wav.extend_from_slice(&(channels * 2).to_le_bytes());
+	wav.extend_from_slice(&16u16.to_le_bytes());
+
+	wav.extend_from_slice(b"data");
+	wav.extend_from_slice(&data_size.to_le_bytes());
+	for sample in samples {
+		wav.extend_from_slice(&sample.to_le_bytes());
+	}
+
+	wav
+}
+
 pub fn create_test_wav_stereo_data() -> Vec<u8> {
 	let sample_rate: u32 = 44100;
 	let channels: u16 = 2;
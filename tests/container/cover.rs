@@ -0,0 +1,111 @@
+use ffmpreg::container::metadata::{CoverArt, Id3v2Tag};
+use ffmpreg::container::mp4::{Mp4Track, TrackType};
+use ffmpreg::container::{FlacFormat, FlacReader, FlacWriter, Mp4Format, Mp4Reader, Mp4Writer};
+use ffmpreg::core::{Muxer, Packet, Timebase};
+use ffmpreg::io::{Cursor, FileAdapter};
+use tempfile::tempdir;
+
+// a png header and IHDR; nothing reads past the size
+fn tiny_png() -> Vec<u8> {
+	let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+	png.extend_from_slice(&13u32.to_be_bytes());
+	png.extend_from_slice(b"IHDR");
+	png.extend_from_slice(&64u32.to_be_bytes());
+	png.extend_from_slice(&48u32.to_be_bytes());
+	png.extend_from_slice(&[8, 2, 0, 0, 0, 0, 0, 0, 0]);
+	png
+}
+
+fn tiny_jpeg() -> Vec<u8> {
+	let mut jpeg = vec![0xFF, 0xD8];
+	// an APP0 segment to step over, then SOF0 with 8-bit 120x90
+	jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+	jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 0x08, 0x00, 0x5A, 0x00, 0x78, 0x01, 0x01]);
+	jpeg.extend_from_slice(&[0x11, 0x00, 0xFF, 0xD9]);
+	jpeg
+}
+
+#[test]
+fn test_cover_art_detects_image() {
+	let png = CoverArt::from_bytes(tiny_png()).unwrap();
+	assert_eq!((png.mime, png.width, png.height), ("image/png", 64, 48));
+	assert!(png.is_png());
+
+	let jpeg = CoverArt::from_bytes(tiny_jpeg()).unwrap();
+	assert_eq!((jpeg.mime, jpeg.width, jpeg.height), ("image/jpeg", 120, 90));
+
+	assert!(CoverArt::from_bytes(b"GIF89a".to_vec()).is_err());
+	assert!(CoverArt::from_bytes(Vec::new()).is_err());
+}
+
+#[test]
+fn test_cover_art_apic_frame() {
+	let cover = CoverArt::from_bytes(tiny_jpeg()).unwrap();
+	let mut tag = Id3v2Tag::new();
+	tag.set_picture(&cover);
+
+	let apic = &tag.frames["APIC"];
+	assert_eq!(&apic[..12], b"\x03image/jpeg\0");
+	// front cover, empty description, then the image
+	assert_eq!(&apic[12..14], &[3, 0]);
+	assert_eq!(&apic[14..], cover.data.as_slice());
+	assert!(tag.to_bytes().windows(4).any(|w| w == b"APIC"));
+}
+
+#[test]
+fn test_flac_writer_picture_block() {
+	let cover = CoverArt::from_bytes(tiny_png()).unwrap();
+	let format =
+		FlacFormat { sample_rate: 44100, channels: 1, bits_per_sample: 16, ..FlacFormat::default() };
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("cover.flac");
+	let path = path.to_str().unwrap();
+	let mut writer =
+		FlacWriter::new(FileAdapter::create(path).unwrap(), format).unwrap().with_picture(cover);
+	writer.write_packet(Packet::new(vec![0; 8], 0, Timebase::new(1, 44100))).unwrap();
+	writer.finalize().unwrap();
+	let bytes = std::fs::read(path).unwrap();
+
+	// STREAMINFO is no longer last; the PICTURE block after it is
+	assert_eq!(bytes[4], 0x00);
+	let picture = 4 + 4 + 34;
+	assert_eq!(bytes[picture], 0x80 | 0x06);
+	let body = &bytes[picture + 4..];
+	assert_eq!(&body[..4], &3u32.to_be_bytes());
+	assert_eq!(&body[8..17], b"image/png");
+	assert_eq!(&body[21..25], &64u32.to_be_bytes());
+	assert_eq!(&body[25..29], &48u32.to_be_bytes());
+
+	// readers step over it to the audio
+	assert!(FlacReader::new(Cursor::new(bytes)).is_ok());
+}
+
+#[test]
+fn test_mp4_writer_covr_atom() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("cover.mp4");
+	let path = path.to_str().unwrap();
+	let cover = CoverArt::from_bytes(tiny_jpeg()).unwrap();
+	let track = Mp4Track {
+		track_type: TrackType::Audio,
+		sample_rate: 48000,
+		channels: 2,
+		..Mp4Track::default()
+	};
+	let format = Mp4Format { tracks: vec![track], ..Mp4Format::default() };
+
+	let mut writer =
+		Mp4Writer::new(FileAdapter::create(path).unwrap(), format).unwrap().with_cover(cover.clone());
+	writer.write_packet(Packet::new(vec![1; 16], 0, Timebase::new(1, 48000))).unwrap();
+	writer.finalize().unwrap();
+
+	let bytes = std::fs::read(path).unwrap();
+	let covr = bytes.windows(4).position(|w| w == b"covr").expect("no covr atom");
+	assert_eq!(&bytes[covr + 8..covr + 12], b"data");
+	assert_eq!(&bytes[covr + 12..covr + 16], &13u32.to_be_bytes());
+	assert_eq!(&bytes[covr + 20..covr + 20 + cover.data.len()], cover.data.as_slice());
+	assert!(bytes.windows(8).any(|w| w == b"mdirappl"));
+
+	let reader = Mp4Reader::new(Cursor::new(bytes)).unwrap();
+	assert_eq!(reader.format().tracks.len(), 1);
+}
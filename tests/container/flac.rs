@@ -0,0 +1,53 @@
+use ffmpreg::codecs::FlacEncoder;
+use ffmpreg::container::{FlacFormat, FlacReader, FlacWriter};
+use ffmpreg::core::{Demuxer, Encoder, Frame, FrameAudio, Muxer, Timebase};
+use ffmpreg::io::FileAdapter;
+use std::path::Path;
+use tempfile::tempdir;
+
+// interleaved 16-bit samples as a flac file of `block_size` sample frames
+pub(crate) fn create_test_flac(
+	path: &Path,
+	samples: &[i16],
+	sample_rate: u32,
+	channels: u8,
+	block_size: u16,
+) {
+	let format = FlacFormat {
+		min_block_size: block_size,
+		max_block_size: block_size,
+		sample_rate,
+		channels,
+		bits_per_sample: 16,
+		total_samples: (samples.len() / channels as usize) as u64,
+		..FlacFormat::default()
+	};
+	let mut writer =
+		FlacWriter::new(FileAdapter::create(path.to_str().unwrap()).unwrap(), format).unwrap();
+	let mut encoder = FlacEncoder::new(sample_rate, channels, 16, block_size);
+	for (i, block) in samples.chunks(block_size as usize * channels as usize).enumerate() {
+		let data = block.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+		let audio = FrameAudio::new(data, sample_rate, channels);
+		let pts = (i * block_size as usize) as i64;
+		let frame = Frame::new_audio(audio, Timebase::new(1, sample_rate), 0).with_pts(pts);
+		writer.write_packet(encoder.encode(frame).unwrap().unwrap()).unwrap();
+	}
+	writer.finalize().unwrap();
+}
+
+#[test]
+fn test_flac_reader_packets_are_whole_frames() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("in.flac");
+	let samples: Vec<i16> = (0..5000).map(|i| ((i * 7919) % 20000 - 10000) as i16).collect();
+	create_test_flac(&path, &samples, 8000, 1, 1024);
+
+	let mut reader = FlacReader::new(FileAdapter::open(path.to_str().unwrap()).unwrap()).unwrap();
+	let mut starts = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		assert_eq!(&packet.data[..2], &[0xFF, 0xF8]);
+		starts.push(packet.pts);
+	}
+	// the last frame is short, and still starts where the others left off
+	assert_eq!(starts, vec![0, 1024, 2048, 3072, 4096]);
+}
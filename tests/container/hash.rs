@@ -0,0 +1,76 @@
+use ffmpreg::container::hash::{Md5, Sha256, to_hex};
+use ffmpreg::container::{SinkFormat, SinkWriter};
+use ffmpreg::core::{Muxer, Packet, Timebase};
+
+fn md5(data: &[u8]) -> String {
+	let mut md5 = Md5::new();
+	md5.update(data);
+	to_hex(&md5.finish())
+}
+
+fn sha256(data: &[u8]) -> String {
+	let mut sha256 = Sha256::new();
+	sha256.update(data);
+	to_hex(&sha256.finish())
+}
+
+#[test]
+fn test_md5_vectors() {
+	assert_eq!(md5(b""), "d41d8cd98f00b204e9800998ecf8427e");
+	assert_eq!(md5(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+	assert_eq!(
+		md5(b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"),
+		"57edf4a22be3c955ac49da2e2107b67a"
+	);
+}
+
+#[test]
+fn test_sha256_vectors() {
+	assert_eq!(sha256(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+	assert_eq!(sha256(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+	assert_eq!(
+		sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+		"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+	);
+}
+
+#[test]
+fn test_hash_updates_across_blocks() {
+	let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+	let mut split = Md5::new();
+	for chunk in data.chunks(37) {
+		split.update(chunk);
+	}
+	assert_eq!(to_hex(&split.finish()), md5(&data));
+
+	let mut split = Sha256::new();
+	for chunk in data.chunks(63) {
+		split.update(chunk);
+	}
+	assert_eq!(to_hex(&split.finish()), sha256(&data));
+}
+
+#[test]
+fn test_sink_writer_digests_packets() {
+	let timebase = Timebase::new(1, 8000);
+	let mut sink = SinkWriter::new(Vec::new(), SinkFormat::Md5);
+	sink.write_packet(Packet::new(b"a".to_vec(), 0, timebase)).unwrap();
+	sink.write_packet(Packet::new(b"bc".to_vec(), 0, timebase)).unwrap();
+	sink.finalize().unwrap();
+	assert_eq!((sink.packets(), sink.bytes()), (2, 3));
+	assert_eq!(sink.into_inner().unwrap(), b"MD5=900150983cd24fb0d6963f7d28e17f72\n");
+
+	let mut sink = SinkWriter::<Vec<u8>>::null();
+	sink.write_packet(Packet::new(b"abc".to_vec(), 0, timebase)).unwrap();
+	sink.finalize().unwrap();
+	assert_eq!(sink.packets(), 1);
+	assert!(sink.into_inner().is_none());
+}
+
+#[test]
+fn test_sink_format_from_name() {
+	assert_eq!(SinkFormat::from_name("MD5"), Some(SinkFormat::Md5));
+	assert_eq!(SinkFormat::from_name("sha256"), Some(SinkFormat::Sha256));
+	assert_eq!(SinkFormat::from_name("null"), Some(SinkFormat::Null));
+	assert_eq!(SinkFormat::from_name("wav"), None);
+}
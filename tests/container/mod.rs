@@ -1,3 +1,10 @@
+mod cover;
+pub(crate) mod flac;
+mod hash;
+pub(crate) mod mp3;
+mod mp4;
+pub(crate) mod ogg;
+mod riff;
 mod roundtrip;
 mod wav;
 mod y4m;
@@ -0,0 +1,87 @@
+use ffmpreg::container::mp3::{FrameHeader, info_tag_frames};
+use ffmpreg::container::{Mp3Reader, Mp3Writer};
+use ffmpreg::core::{Demuxer, Muxer};
+use ffmpreg::io::{Cursor, FileAdapter};
+use tempfile::tempdir;
+
+// an id3v2 tag with a title, a xing header claiming 1000 frames, then `frames` mpeg-1
+// layer iii frames at 44.1 kHz and 128 kbps (417 bytes, 1152 samples each)
+// whose payload bytes hold their index
+pub(crate) fn create_test_mp3_data(frames: u8) -> Vec<u8> {
+	let header = [0xFF, 0xFB, 0x90, 0x44];
+	let mut mp3 = b"ID3\x04\x00\x00\x00\x00\x00\x10".to_vec();
+	mp3.extend_from_slice(b"TIT2\x00\x00\x00\x06\x00\x00\x00Title");
+
+	let mut xing = vec![0u8; 417];
+	xing[..4].copy_from_slice(&header);
+	xing[36..40].copy_from_slice(b"Xing");
+	xing[40..44].copy_from_slice(&1u32.to_be_bytes());
+	xing[44..48].copy_from_slice(&1000u32.to_be_bytes());
+	mp3.extend_from_slice(&xing);
+
+	for index in 0..frames {
+		let mut frame = vec![index; 417];
+		frame[..4].copy_from_slice(&header);
+		mp3.extend_from_slice(&frame);
+	}
+	mp3
+}
+
+#[test]
+fn test_mp3_frame_header() {
+	let header = FrameHeader::parse([0xFF, 0xFB, 0x90, 0x44]).unwrap();
+	assert_eq!((header.layer, header.sample_rate, header.channels), (3, 44100, 2));
+	assert_eq!(header.bitrate(), 128_000);
+	assert_eq!((header.frame_len(), header.samples()), (417, 1152));
+	// free format and the reserved rate are not frames
+	assert!(FrameHeader::parse([0xFF, 0xFB, 0x00, 0x44]).is_none());
+	assert!(FrameHeader::parse([0xFF, 0xFB, 0x9C, 0x44]).is_none());
+}
+
+#[test]
+fn test_mp3_reader_skips_tags() {
+	let mut reader = Mp3Reader::new(Cursor::new(create_test_mp3_data(4))).unwrap();
+	assert_eq!(reader.id3_tag().map(|tag| tag.len()), Some(26));
+	assert_eq!(reader.format().frames, Some(1000));
+
+	let mut packets = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		packets.push((packet.pts, packet.data[4]));
+	}
+	assert_eq!(packets, vec![(0, 0), (1152, 1), (2304, 2), (3456, 3)]);
+}
+
+#[test]
+fn test_mp3_writer_counts_frames() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("out.mp3");
+	let mut reader = Mp3Reader::new(Cursor::new(create_test_mp3_data(6))).unwrap();
+	let output = FileAdapter::create(path.to_str().unwrap()).unwrap();
+	let mut writer = Mp3Writer::new(output).with_id3(reader.id3_tag().unwrap().to_vec());
+	while let Some(packet) = reader.read_packet().unwrap() {
+		// the third frame again overlaps, and dropping the fifth leaves a gap
+		if packet.pts == 2 * 1152 {
+			writer.write_packet(packet.clone()).unwrap();
+		}
+		if packet.pts != 4 * 1152 {
+			writer.write_packet(packet).unwrap();
+		}
+	}
+	writer.finalize().unwrap();
+
+	let output = std::fs::read(&path).unwrap();
+	assert!(output.starts_with(b"ID3"));
+	let info = &output[26..];
+	let header = FrameHeader::parse(info[..4].try_into().unwrap()).unwrap();
+	assert_eq!(info_tag_frames(info, &header), Some(5));
+	assert_eq!(&info[36..40], b"Info");
+	assert_eq!(u32::from_be_bytes(info[48..52].try_into().unwrap()), 6 * 417);
+
+	let mut reader = Mp3Reader::new(Cursor::new(output)).unwrap();
+	assert_eq!(reader.format().frames, Some(5));
+	let mut payloads = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		payloads.push(packet.data[4]);
+	}
+	assert_eq!(payloads, vec![0, 1, 2, 3, 5]);
+}
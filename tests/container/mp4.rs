@@ -0,0 +1,70 @@
+use ffmpreg::container::metadata::Disposition;
+use ffmpreg::container::mp4::{Mp4Track, TrackType, pack_language, unpack_language};
+use ffmpreg::container::{Mp4Format, Mp4Reader, Mp4Writer};
+use ffmpreg::core::{Muxer, Packet, Timebase};
+use ffmpreg::io::Cursor;
+use ffmpreg::io::FileAdapter;
+use tempfile::tempdir;
+
+fn write_mp4(format: Mp4Format) -> Vec<u8> {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("out.mp4");
+	let path = path.to_str().unwrap();
+
+	let output = FileAdapter::create(path).unwrap();
+	let mut writer = Mp4Writer::new(output, format.clone()).unwrap();
+	for idx in 0..format.tracks.len() {
+		writer.write_packet(Packet::new(vec![idx as u8; 16], idx, Timebase::new(1, 1000))).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	std::fs::read(path).unwrap()
+}
+
+fn two_track_format() -> Mp4Format {
+	let video = Mp4Track { track_id: 1, track_type: TrackType::Video, ..Mp4Track::default() };
+	let audio = Mp4Track {
+		track_id: 2,
+		track_type: TrackType::Audio,
+		timescale: 48000,
+		sample_rate: 48000,
+		channels: 2,
+		language: "eng".to_string(),
+		disposition: Disposition { default: false, commentary: true },
+		..Mp4Track::default()
+	};
+	Mp4Format { tracks: vec![video, audio], ..Mp4Format::default() }
+}
+
+#[test]
+fn test_mp4_language_packing() {
+	assert_eq!(pack_language("und"), 0x55C4);
+	assert_eq!(unpack_language(0x55C4), "und");
+	assert_eq!(unpack_language(pack_language("eng")), "eng");
+	assert_eq!(unpack_language(0), "und");
+}
+
+#[test]
+fn test_mp4_track_defaults() {
+	let track = Mp4Track::default();
+	assert_eq!(track.language, "und");
+	assert!(track.disposition.default);
+	assert!(!track.disposition.commentary);
+}
+
+#[test]
+fn test_mp4_language_disposition_roundtrip() {
+	let data = write_mp4(two_track_format());
+	let reader = Mp4Reader::new(Cursor::new(data)).unwrap();
+	let format = reader.format().clone();
+
+	assert_eq!(format.tracks.len(), 2);
+	assert_eq!(format.tracks[0].track_type, TrackType::Video);
+	assert_eq!(format.tracks[0].language, "und");
+	assert_eq!(format.tracks[0].disposition, Disposition { default: true, commentary: false });
+
+	assert_eq!(format.tracks[1].track_type, TrackType::Audio);
+	assert_eq!(format.tracks[1].timescale, 48000);
+	assert_eq!(format.tracks[1].language, "eng");
+	assert_eq!(format.tracks[1].disposition, Disposition { default: false, commentary: true });
+}
@@ -0,0 +1,101 @@
+use ffmpreg::container::ogg::{OggCodec, OggPage, PAGE_CONTINUED, crc32};
+use ffmpreg::container::{OggReader, OggWriter};
+use ffmpreg::core::{Demuxer, Muxer};
+use ffmpreg::io::{Cursor, FileAdapter};
+use tempfile::tempdir;
+
+pub(crate) fn ogg_page(
+	flags: u8,
+	granule: i64,
+	sequence: u32,
+	lacing: &[u8],
+	body: &[u8],
+) -> Vec<u8> {
+	let (lacing, body) = (lacing.to_vec(), body.to_vec());
+	OggPage { flags, granule, serial: 7, sequence, lacing, body }.to_bytes()
+}
+
+// a vorbis stream at 8 kHz: the identification header on the first page, a
+// header page at granule 0, then ten audio pages of 0.1 s whose bytes hold
+// their number. page 5 starts a packet that ends on page 6
+pub(crate) fn create_test_ogg_data() -> Vec<u8> {
+	let mut id = b"\x01vorbis".to_vec();
+	id.extend_from_slice(&0u32.to_le_bytes());
+	id.push(1);
+	id.extend_from_slice(&8000u32.to_le_bytes());
+	id.resize(30, 0);
+
+	let mut ogg = ogg_page(0x02, 0, 0, &[30], &id);
+	ogg.extend(ogg_page(0, 0, 1, &[20, 40], &[0xEE; 60]));
+	for page in 1..=10u8 {
+		let (lacing, flags): (&[u8], u8) = match page {
+			5 => (&[50, 255], 0),
+			6 => (&[10, 50], 0x01),
+			10 => (&[50], 0x04),
+			_ => (&[50], 0),
+		};
+		let body = vec![page; lacing.iter().map(|&len| len as usize).sum()];
+		ogg.extend(ogg_page(flags, page as i64 * 800, page as u32 + 1, lacing, &body));
+	}
+	ogg
+}
+
+fn pages(data: &[u8]) -> Vec<OggPage> {
+	let mut reader = OggReader::new(Cursor::new(data.to_vec())).unwrap();
+	std::iter::from_fn(|| reader.read_page().unwrap()).collect()
+}
+
+#[test]
+fn test_ogg_reader_format_and_pts() {
+	let mut reader = OggReader::new(Cursor::new(create_test_ogg_data())).unwrap();
+	let format = *reader.format();
+	assert_eq!((format.codec, format.sample_rate, format.channels), (OggCodec::Vorbis, 8000, 1));
+	assert_eq!(format.granule_at(0.5), 4000);
+
+	let mut starts = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		starts.push(packet.pts);
+	}
+	assert_eq!(starts, vec![0, 0, 0, 800, 1600, 2400, 3200, 4000, 4800, 5600, 6400, 7200]);
+}
+
+#[test]
+fn test_ogg_page_crc() {
+	let page = ogg_page(0, 800, 3, &[4], b"abcd");
+	let mut zeroed = page.clone();
+	zeroed[22..26].fill(0);
+	assert_eq!(u32::from_le_bytes(page[22..26].try_into().unwrap()), crc32(&zeroed));
+	// the value every ogg implementation gives for "123456789"
+	assert_eq!(crc32(b"123456789"), 0x89A1_897F);
+}
+
+#[test]
+fn test_ogg_writer_rebases_after_a_cut() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("out.ogg");
+	let source = pages(&create_test_ogg_data());
+	let output = FileAdapter::create(path.to_str().unwrap()).unwrap();
+	let mut writer = OggWriter::new(output).with_end_granule(6000);
+	let mut start = 0;
+	for page in source {
+		let page_start = start;
+		start = page.granule.max(start);
+		// pages 1 to 5 are cut, page 9 on ends the stream
+		if (800..=4000).contains(&page.granule) || page.granule > 6400 {
+			continue;
+		}
+		writer.write_page(page, page_start).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	let written = pages(&std::fs::read(&path).unwrap());
+	let granules: Vec<i64> = written.iter().map(|page| page.granule).collect();
+	assert_eq!(granules, vec![0, 0, 800, 1600, 2000]);
+	let sequences: Vec<u32> = written.iter().map(|page| page.sequence).collect();
+	assert_eq!(sequences, vec![0, 1, 2, 3, 4]);
+	assert_eq!(written[0].flags, 0x02);
+	assert_eq!(written[4].flags, 0x04);
+	// the end of the packet page 5 began is gone with it
+	assert_eq!(written[2].flags & PAGE_CONTINUED, 0);
+	assert_eq!((written[2].lacing.as_slice(), written[2].body.len()), (&[50u8][..], 50));
+}
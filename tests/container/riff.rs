@@ -0,0 +1,63 @@
+use ffmpreg::container::riff::{RiffChunk, fourcc_text, walk};
+use ffmpreg::container::{AviFormat, AviWriter};
+use ffmpreg::core::{Muxer, Packet, Timebase};
+use ffmpreg::io::{Cursor, FileAdapter};
+use tempfile::tempdir;
+
+fn names(chunks: &[RiffChunk]) -> Vec<(String, usize)> {
+	chunks.iter().map(|chunk| (fourcc_text(&chunk.fourcc), chunk.depth)).collect()
+}
+
+#[test]
+fn test_riff_walk_wav() {
+	let wav = crate::common::create_test_wav_data();
+	let chunks = walk(&mut Cursor::new(wav.clone())).unwrap();
+	let expected = [("RIFF", 0), ("fmt ", 1), ("data", 1)];
+	assert_eq!(names(&chunks), expected.map(|(name, depth)| (name.to_string(), depth)));
+	assert_eq!(chunks[0].form, Some(*b"WAVE"));
+	assert_eq!((chunks[1].offset, chunks[1].size), (12, 16));
+	assert_eq!(chunks[2].size as usize, wav.len() - 44);
+	assert!(chunks.iter().all(|chunk| chunk.problem.is_none()));
+}
+
+#[test]
+fn test_riff_walk_avi() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("out.avi");
+	let output = FileAdapter::create(path.to_str().unwrap()).unwrap();
+	let mut writer = AviWriter::new(output, AviFormat::default()).unwrap();
+	writer.write_packet(Packet::new(vec![1; 5], 0, Timebase::new(1, 30))).unwrap();
+	writer.write_packet(Packet::new(vec![2; 4], 0, Timebase::new(1, 30))).unwrap();
+	writer.finalize().unwrap();
+
+	let chunks = walk(&mut Cursor::new(std::fs::read(&path).unwrap())).unwrap();
+	let lists: Vec<_> = chunks.iter().filter_map(|chunk| chunk.form).collect();
+	assert_eq!(lists, vec![*b"AVI ", *b"hdrl", *b"movi"]);
+	// the odd chunk's pad byte is stepped over to reach the next
+	let packets: Vec<_> = chunks.iter().filter(|chunk| &chunk.fourcc == b"00dc").collect();
+	assert_eq!(packets.len(), 2);
+	assert_eq!(packets[1].offset, packets[0].offset + 8 + 6);
+	assert_eq!(packets[0].depth, 2);
+	assert_eq!(chunks.last().unwrap().fourcc, *b"idx1");
+}
+
+#[test]
+fn test_riff_walk_reports_problems() {
+	// a data chunk claiming more than the file holds, then stray bytes
+	let mut wav = crate::common::create_test_wav_data();
+	let len = wav.len();
+	wav[40..44].copy_from_slice(&(len as u32).to_le_bytes());
+	let chunks = walk(&mut Cursor::new(wav)).unwrap();
+	assert!(chunks[2].problem.as_deref().unwrap().contains("past the end"));
+
+	// a writer that left the RIFF size at 0
+	let mut wav = crate::common::create_test_wav_data();
+	wav[4..8].copy_from_slice(&[0; 4]);
+	wav.extend_from_slice(&[0, 0, 0]);
+	let chunks = walk(&mut Cursor::new(wav)).unwrap();
+	assert!(chunks[0].problem.as_deref().unwrap().contains("size is 0"));
+	assert_eq!(fourcc_text(&chunks[3].fourcc), "????");
+
+	assert!(walk(&mut Cursor::new(b"fLaC\0\0\0\0\0\0\0\0".to_vec())).is_err());
+	assert_eq!(fourcc_text(&[b'a', 0, b' ', 0xff]), "a\\x00 \\xff");
+}
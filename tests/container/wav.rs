@@ -1,6 +1,6 @@
 use ffmpreg::container::{WavFormat, WavReader, WavWriter};
 use ffmpreg::core::{Demuxer, Muxer, Packet, Timebase};
-use ffmpreg::io::Cursor;
+use ffmpreg::io::{Cursor, PipeWriter};
 
 fn create_test_wav() -> Vec<u8> {
 	let sample_rate: u32 = 44100;
@@ -149,6 +149,21 @@ fn test_wav_writer_basic() {
 	writer.finalize().unwrap();
 }
 
+#[test]
+fn test_wav_writer_streaming_never_seeks() {
+	let format = WavFormat { channels: 1, sample_rate: 44100, bit_depth: 16 };
+	let timebase = Timebase::new(1, 44100);
+
+	let mut writer = WavWriter::streaming(PipeWriter::streaming(Vec::new()), format).unwrap();
+	writer.write_packet(Packet::new(vec![0u8; 1024], 0, timebase)).unwrap();
+	writer.finalize().unwrap();
+
+	// the patching writer has to seek back to the header
+	let mut writer = WavWriter::new(PipeWriter::streaming(Vec::new()), format).unwrap();
+	writer.write_packet(Packet::new(vec![0u8; 1024], 0, timebase)).unwrap();
+	assert!(writer.finalize().is_err());
+}
+
 #[test]
 fn test_wav_roundtrip() {
 	let original_wav = create_test_wav();
@@ -190,3 +205,91 @@ fn test_wav_pts_increments() {
 		last_pts = Some(packet.pts);
 	}
 }
+
+#[test]
+fn test_wav_reader_packets_hold_whole_frames() {
+	let samples = vec![0i16; 6 * 1000];
+	let cursor = Cursor::new(crate::common::create_test_wav_from_samples(&samples, 48000, 6));
+	let mut reader = WavReader::new(cursor).unwrap();
+
+	let mut total = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		assert_eq!(packet.size() % 12, 0);
+		total += packet.size();
+	}
+	assert_eq!(total, 12000);
+}
+
+#[test]
+fn test_wav_reader_seek_to_frame() {
+	let samples: Vec<i16> = (0..8000).flat_map(|n| [n as i16, -(n as i16)]).collect();
+	let cursor = Cursor::new(crate::common::create_test_wav_from_samples(&samples, 8000, 2));
+	let mut reader = WavReader::new(cursor).unwrap();
+
+	reader.read_packet().unwrap();
+	assert_eq!(reader.seek_to_frame(5000).unwrap(), 5000);
+	let packet = reader.read_packet().unwrap().unwrap();
+	assert_eq!(packet.pts, 5000);
+	assert_eq!(&packet.data[..4], &[0x88, 0x13, 0x78, 0xec]);
+
+	// seeking backwards and past the end both work
+	assert_eq!(reader.seek_to_frame(10).unwrap(), 10);
+	assert_eq!(reader.read_packet().unwrap().unwrap().pts, 10);
+	assert_eq!(reader.seek_to_frame(1_000_000).unwrap(), 8000);
+	assert!(reader.read_packet().unwrap().is_none());
+}
+
+// a mono 16 bit header with the given fmt chunk and two samples of data
+fn wav_with_fmt(fmt: &[u8]) -> Vec<u8> {
+	let mut wav = Vec::new();
+	wav.extend_from_slice(b"RIFF");
+	wav.extend_from_slice(&(4 + 8 + fmt.len() as u32 + 8 + 4).to_le_bytes());
+	wav.extend_from_slice(b"WAVE");
+	wav.extend_from_slice(b"fmt ");
+	wav.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+	wav.extend_from_slice(fmt);
+	wav.extend_from_slice(b"data");
+	wav.extend_from_slice(&4u32.to_le_bytes());
+	wav.extend_from_slice(&[1, 0, 2, 0]);
+	wav
+}
+
+fn fmt_chunk(tag: u16, bits: u16) -> Vec<u8> {
+	let mut fmt = Vec::new();
+	fmt.extend_from_slice(&tag.to_le_bytes());
+	fmt.extend_from_slice(&1u16.to_le_bytes());
+	fmt.extend_from_slice(&8000u32.to_le_bytes());
+	fmt.extend_from_slice(&(8000 * bits as u32 / 8).to_le_bytes());
+	fmt.extend_from_slice(&(bits / 8).to_le_bytes());
+	fmt.extend_from_slice(&bits.to_le_bytes());
+	fmt
+}
+
+fn extensible_fmt_chunk(subformat: u16, bits: u16) -> Vec<u8> {
+	let mut fmt = fmt_chunk(0xFFFE, bits);
+	fmt.extend_from_slice(&22u16.to_le_bytes());
+	fmt.extend_from_slice(&bits.to_le_bytes());
+	fmt.extend_from_slice(&4u32.to_le_bytes());
+	fmt.extend_from_slice(&subformat.to_le_bytes());
+	fmt.extend_from_slice(&[0, 0, 0, 0, 0x10, 0, 0x80, 0, 0, 0xAA, 0, 0x38, 0x9B, 0x71]);
+	fmt
+}
+
+#[test]
+fn test_wav_reader_accepts_extensible_pcm() {
+	let wav = wav_with_fmt(&extensible_fmt_chunk(1, 16));
+	let mut reader = WavReader::new(Cursor::new(wav)).unwrap();
+	assert_eq!(reader.format().bit_depth, 16);
+	assert_eq!(reader.read_packet().unwrap().unwrap().data, [1, 0, 2, 0]);
+}
+
+#[test]
+fn test_wav_reader_rejects_float() {
+	for fmt in [fmt_chunk(3, 32), extensible_fmt_chunk(3, 32)] {
+		let err = WavReader::new(Cursor::new(wav_with_fmt(&fmt))).err().unwrap();
+		assert!(matches!(err.kind(), ffmpreg::io::IoErrorKind::Unsupported));
+		assert!(err.to_string().contains("floating point"), "{}", err);
+	}
+	let err = WavReader::new(Cursor::new(wav_with_fmt(&fmt_chunk(2, 16)))).err().unwrap();
+	assert!(matches!(err.kind(), ffmpreg::io::IoErrorKind::Unsupported));
+}
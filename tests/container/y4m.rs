@@ -217,3 +217,40 @@ fn test_y4m_frame_size_calculation() {
 
 	assert_eq!(format.frame_size(), expected);
 }
+
+#[test]
+fn test_y4m_reader_parses_c422_colorspace() {
+	use ffmpreg::container::y4m::Colorspace;
+
+	let mut y4m = b"YUV4MPEG2 W4 H2 F25:1 Ip C422\n".to_vec();
+	y4m.extend_from_slice(b"FRAME\n");
+	y4m.extend_from_slice(&[7u8; 16]);
+
+	let mut reader = Y4mReader::new(Cursor::new(y4m)).unwrap();
+	let format = reader.format();
+	assert_eq!(format.colorspace, Some(Colorspace::C422));
+	assert_eq!(format.frame_size(), 16);
+
+	let packet = reader.read_packet().unwrap().unwrap();
+	assert_eq!(packet.data.len(), 16);
+	assert!(reader.read_packet().unwrap().is_none());
+}
+
+#[test]
+fn test_y4m_mono_roundtrip_keeps_colorspace() {
+	use ffmpreg::container::y4m::Colorspace;
+
+	let mut y4m = b"YUV4MPEG2 W4 H4 F25:1 Ip Cmono\n".to_vec();
+	y4m.extend_from_slice(b"FRAME\n");
+	y4m.extend_from_slice(&[42u8; 16]);
+
+	let mut reader = Y4mReader::new(Cursor::new(y4m)).unwrap();
+	let format = reader.format();
+	assert_eq!(format.colorspace, Some(Colorspace::Mono));
+
+	let mut writer = Y4mWriter::new(Cursor::new(Vec::new()), format).unwrap();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		writer.write_packet(packet).unwrap();
+	}
+	writer.finalize().unwrap();
+}
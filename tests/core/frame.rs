@@ -1,4 +1,4 @@
-use ffmpreg::core::{Frame, FrameAudio, FrameVideo, Timebase, VideoFormat};
+use ffmpreg::core::{Frame, FrameAudio, FrameVideo, Metadata, SideData, Timebase, VideoFormat};
 
 #[test]
 fn test_frame_audio_creation() {
@@ -110,3 +110,77 @@ fn test_frame_kind() {
 		ffmpreg::core::FrameKind::Audio => panic!("Expected Video frame"),
 	}
 }
+
+#[test]
+fn test_frame_audio_sample_format_conversion() {
+	use ffmpreg::core::SampleFormat;
+
+	let samples: [i16; 4] = [0, 16384, -32768, 32767];
+	let data = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let mut audio = FrameAudio::new(data, 48000, 2).with_nb_samples(2);
+	assert_eq!(audio.format, SampleFormat::S16);
+	assert_eq!(audio.samples_f32(), vec![0.0, 0.5, -1.0, 32767.0 / 32768.0]);
+
+	audio.convert(SampleFormat::F32);
+	assert_eq!(audio.data.len(), 16);
+	assert_eq!(audio.nb_samples, 2);
+
+	// f32 keeps headroom above full scale until it goes back to s16
+	audio.set_samples_f32(&[0.0, 0.5, -1.5, 2.0]);
+	assert_eq!(audio.samples_f32(), vec![0.0, 0.5, -1.5, 2.0]);
+	audio.convert(SampleFormat::S16);
+	let back: Vec<i16> = audio.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+	assert_eq!(back, vec![0, 16384, -32768, 32767]);
+
+	let built = FrameAudio::from_f32(&[0.25, -0.25], 48000, 1);
+	assert_eq!((built.format, built.nb_samples), (SampleFormat::F32, 2));
+}
+
+#[test]
+fn test_frame_audio_planar_layout() {
+	use ffmpreg::core::SampleFormat;
+
+	let planes = vec![vec![1i16, 2, 3], vec![-1i16, -2, -3]];
+	let mut audio = FrameAudio::from_planes_i16(&planes, 48000);
+	assert!(audio.format.is_planar());
+	assert_eq!((audio.channels, audio.nb_samples), (2, 3));
+	let stored: Vec<i16> = audio.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+	assert_eq!(stored, vec![1, 2, 3, -1, -2, -3]);
+	assert_eq!(audio.planes_i16(), planes);
+
+	// interleaved views and conversions go through the same planes
+	assert_eq!(audio.samples_f32().len(), 6);
+	audio.convert(SampleFormat::S16);
+	let packed: Vec<i16> = audio.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+	assert_eq!(packed, vec![1, -1, 2, -2, 3, -3]);
+	assert_eq!(audio.planes_i16(), planes);
+
+	audio.convert(SampleFormat::F32P);
+	assert_eq!(audio.data.len(), 24);
+	assert_eq!(audio.planes_f32()[1], vec![-1.0 / 32768.0, -2.0 / 32768.0, -3.0 / 32768.0]);
+	assert_eq!(audio.planes_i16(), planes);
+}
+
+#[test]
+fn test_frame_metadata() {
+	let audio = FrameAudio::new(vec![0; 4], 44100, 1);
+	let mut frame = Frame::new_audio(audio, Timebase::new(1, 44100), 0)
+		.with_metadata("loudness", SideData::Number(-23.0))
+		.with_metadata("label", SideData::Text("intro".into()));
+	assert_eq!(frame.metadata.number("loudness"), Some(-23.0));
+	assert_eq!(frame.metadata.region("loudness"), None);
+
+	frame.metadata.set("loudness", SideData::Number(-20.0));
+	frame.metadata.set("face", SideData::Region(1, 2, 3, 4));
+	let keys: Vec<&str> = frame.metadata.iter().map(|(key, _)| key).collect();
+	assert_eq!(keys, vec!["loudness", "label", "face"]);
+	assert_eq!(frame.metadata.number("loudness"), Some(-20.0));
+	assert_eq!(frame.metadata.region("face"), Some((1, 2, 3, 4)));
+
+	assert_eq!(frame.metadata.remove("label"), Some(SideData::Text("intro".into())));
+	let mut later = Metadata::new();
+	later.set("face", SideData::Region(0, 0, 1, 1));
+	later.inherit(frame.metadata.clone());
+	assert_eq!(later.len(), 2);
+	assert_eq!(later.region("face"), Some((0, 0, 1, 1)));
+}
@@ -0,0 +1,37 @@
+use ffmpreg::core::LogLevel;
+use ffmpreg::core::log::format_record;
+
+#[test]
+fn test_log_level_from_name() {
+	assert_eq!(LogLevel::from_name("off"), Some(LogLevel::Off));
+	assert_eq!(LogLevel::from_name("quiet"), Some(LogLevel::Off));
+	assert_eq!(LogLevel::from_name("Warning"), Some(LogLevel::Warn));
+	assert_eq!(LogLevel::from_name("warn"), Some(LogLevel::Warn));
+	assert_eq!(LogLevel::from_name("TRACE"), Some(LogLevel::Trace));
+	assert_eq!(LogLevel::from_name("loud"), None);
+}
+
+#[test]
+fn test_log_level_from_verbosity() {
+	assert_eq!(LogLevel::from_verbosity(0), LogLevel::Warn);
+	assert_eq!(LogLevel::from_verbosity(1), LogLevel::Info);
+	assert_eq!(LogLevel::from_verbosity(2), LogLevel::Debug);
+	assert_eq!(LogLevel::from_verbosity(5), LogLevel::Trace);
+}
+
+#[test]
+fn test_log_level_ordering() {
+	assert!(LogLevel::Off < LogLevel::Error);
+	assert!(LogLevel::Error < LogLevel::Warn);
+	assert!(LogLevel::Debug < LogLevel::Trace);
+}
+
+#[test]
+fn test_log_format_record() {
+	let record =
+		format_record(LogLevel::Warn, "wav", format_args!("skipped '{}' chunk ({} bytes)", "LIST", 26));
+	assert_eq!(record, "warning: [wav] skipped 'LIST' chunk (26 bytes)");
+
+	let record = format_record(LogLevel::Debug, "mp4", format_args!("box moov"));
+	assert_eq!(record, "debug: [mp4] box moov");
+}
@@ -1,4 +1,5 @@
 mod frame;
+mod log;
 mod packet;
 mod time;
 mod traits;
@@ -1,4 +1,4 @@
-use ffmpreg::core::Timebase;
+use ffmpreg::core::{Timebase, parse_duration, parse_timestamp};
 
 #[test]
 fn test_timebase_creation() {
@@ -58,3 +58,23 @@ fn test_timebase_copy() {
 	let tb2 = tb1;
 	assert_eq!(tb1, tb2);
 }
+
+#[test]
+fn test_parse_timestamp() {
+	assert_eq!(parse_timestamp("1.5"), Some(1.5));
+	assert_eq!(parse_timestamp("01:02"), Some(62.0));
+	assert_eq!(parse_timestamp("1:00:00.25"), Some(3600.25));
+	assert_eq!(parse_timestamp("-2"), Some(-2.0));
+	assert_eq!(parse_timestamp(""), None);
+	assert_eq!(parse_timestamp("1:xx"), None);
+}
+
+#[test]
+fn test_parse_duration() {
+	assert_eq!(parse_duration("200ms"), Some(0.2));
+	assert_eq!(parse_duration("1.5s"), Some(1.5));
+	assert_eq!(parse_duration("0.25"), Some(0.25));
+	assert_eq!(parse_duration("00:01"), Some(1.0));
+	assert_eq!(parse_duration("-1s"), None);
+	assert_eq!(parse_duration("fast"), None);
+}
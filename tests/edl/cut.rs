@@ -0,0 +1,184 @@
+use ffmpreg::container::{WavReader, Y4mReader};
+use ffmpreg::core::{Demuxer, Frame, FrameAudio, FrameVideo, Timebase, VideoFormat};
+use ffmpreg::edl::{EditDecisionList, EdlCutter, EdlEntry};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use tempfile::tempdir;
+
+fn audio_frame(samples: Vec<i16>, pts: i64) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, 10, 1);
+	Frame::new_audio(audio, Timebase::new(1, 10), 0).with_pts(pts)
+}
+
+fn video_frame(pts: i64) -> Frame {
+	let video = FrameVideo::new(vec![0; 6], 2, 2, VideoFormat::YUV420);
+	Frame::new_video(video, Timebase::new(1, 1), 0).with_pts(pts)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+fn edl(ranges: &[(f64, f64)]) -> EditDecisionList {
+	let mut edl = EditDecisionList::new();
+	for &(start, end) in ranges {
+		edl.add(EdlEntry::new(start, end));
+	}
+	edl
+}
+
+#[test]
+fn test_cutter_audio_sample_accurate() {
+	let mut cutter = EdlCutter::new(&edl(&[(0.2, 0.5), (0.8, 1.2)]));
+
+	let first = cutter.cut(audio_frame((0..10).collect(), 0)).unwrap();
+	assert_eq!(samples(&first), vec![2, 3, 4, 8, 9]);
+	assert_eq!(first.pts, 0);
+	assert_eq!(first.audio().unwrap().nb_samples, 5);
+
+	let second = cutter.cut(audio_frame((10..20).collect(), 10)).unwrap();
+	assert_eq!(samples(&second), vec![10, 11]);
+	assert_eq!(second.pts, 5);
+
+	assert!(cutter.is_finished());
+	assert!(cutter.cut(audio_frame((20..30).collect(), 20)).is_none());
+}
+
+#[test]
+fn test_cutter_audio_fades_range_boundaries() {
+	let mut cutter = EdlCutter::new(&edl(&[(0.0, 1.0), (1.5, 1.7)])).with_fade(0.2);
+
+	let first = cutter.cut(audio_frame(vec![1000; 10], 0)).unwrap();
+	assert_eq!(samples(&first), vec![0, 500, 1000, 1000, 1000, 1000, 1000, 1000, 1000, 500]);
+
+	// a range shorter than two fades ramps up and straight back down
+	let second = cutter.cut(audio_frame(vec![1000; 10], 10)).unwrap();
+	assert_eq!(samples(&second), vec![0, 1000]);
+}
+
+#[test]
+fn test_cutter_audio_drops_frames_outside_ranges() {
+	let mut cutter = EdlCutter::new(&edl(&[(1.0, 1.5)]));
+	assert!(cutter.cut(audio_frame(vec![1; 10], 0)).is_none());
+	assert!(!cutter.is_finished());
+	let kept = cutter.cut(audio_frame((0..10).collect(), 10)).unwrap();
+	assert_eq!(samples(&kept), vec![0, 1, 2, 3, 4]);
+	assert_eq!(kept.pts, 0);
+}
+
+#[test]
+fn test_cutter_video_renumbers_pts() {
+	let mut cutter = EdlCutter::new(&edl(&[(1.0, 3.0), (5.0, 6.0)]));
+	let kept: Vec<i64> =
+		(0..8).filter_map(|pts| cutter.cut(video_frame(pts))).map(|f| f.pts).collect();
+	assert_eq!(kept, vec![0, 1, 2]);
+	assert!(cutter.is_finished());
+}
+
+#[test]
+fn test_cutter_empty_edl_keeps_nothing() {
+	let mut cutter = EdlCutter::new(&EditDecisionList::new());
+	assert!(cutter.is_finished());
+	assert!(cutter.cut(video_frame(0)).is_none());
+}
+
+#[test]
+fn test_pipeline_wav_with_edl() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let source = WavReader::new(Cursor::new(std::fs::read(&input).unwrap())).unwrap();
+	let sample_rate = source.format().sample_rate as f64;
+
+	let pipeline = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_edl(edl(&[(0.0, 100.0 / sample_rate), (200.0 / sample_rate, 250.0 / sample_rate)]));
+	pipeline.run().unwrap();
+
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let bytes_per_frame = reader.format().bytes_per_frame();
+	let mut total = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		total += packet.data.len();
+	}
+	assert_eq!(total / bytes_per_frame, 150);
+}
+
+#[test]
+fn test_pipeline_y4m_with_edl() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	let mut source = Y4mReader::new(Cursor::new(std::fs::read(&input).unwrap())).unwrap();
+	let fps = source.format().framerate_num as f64 / source.format().framerate_den as f64;
+	assert!(source.read_packet().unwrap().is_some());
+
+	let pipeline = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_edl(edl(&[(1.0 / fps, 2.0 / fps)]));
+	pipeline.run().unwrap();
+
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut frames = 0;
+	while reader.read_packet().unwrap().is_some() {
+		frames += 1;
+	}
+	assert_eq!(frames, 1);
+}
+
+#[test]
+fn test_pipeline_edl_rejects_passthrough() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.flac");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let pipeline = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_edl(edl(&[(0.0, 1.0)]));
+	assert!(pipeline.run().is_err());
+}
+
+#[test]
+fn test_pipeline_auto_fade_requires_edl() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let pipeline = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(dir.path().join("out.wav").to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_auto_fade(0.2);
+	assert!(pipeline.run().is_err());
+}
+
+#[test]
+fn test_cutter_audio_starting_after_seek() {
+	// the input was opened at 1.0s, so the first frame covers 1.0..2.0
+	let mut cutter = EdlCutter::new(&edl(&[(1.5, 2.2)])).starting_at(1.0);
+	let first = cutter.cut(audio_frame((10..20).collect(), 0)).unwrap();
+	assert_eq!(samples(&first), vec![15, 16, 17, 18, 19]);
+	let second = cutter.cut(audio_frame((20..30).collect(), 10)).unwrap();
+	assert_eq!(samples(&second), vec![20, 21]);
+	assert!(cutter.is_finished());
+}
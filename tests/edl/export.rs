@@ -0,0 +1,66 @@
+use ffmpreg::container::WavReader;
+use ffmpreg::core::Demuxer;
+use ffmpreg::edl::{EditDecisionList, EdlEntry};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::{EdlExport, Transcoder};
+use tempfile::tempdir;
+
+// a second of tone, a second of silence, a second of tone at 8 kHz
+fn tone_gap_tone() -> Vec<i16> {
+	let tone = |i: usize| ((i as f64 * 0.3).sin() * 8000.0) as i16;
+	(0..8000).map(tone).chain(std::iter::repeat_n(0, 8000)).chain((0..8000).map(tone)).collect()
+}
+
+#[test]
+fn test_export_edl_round_trip() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&tone_gap_tone(), 8000, 1))
+		.unwrap();
+	let input = input.to_str().unwrap().to_string();
+
+	for name in ["cuts.edl", "cuts.json"] {
+		let path = dir.path().join(name);
+		EdlExport::new(input.clone(), vec!["highpass=20,silencedetect=-60,0.5".to_string()])
+			.run(path.to_str().unwrap())
+			.unwrap();
+
+		let edl = EditDecisionList::load(path.to_str().unwrap()).unwrap();
+		assert_eq!(edl.source.as_deref(), Some(input.as_str()));
+		let ranges: Vec<(f64, f64)> = edl.entries.iter().map(|e| (e.start, e.end)).collect();
+		assert_eq!(ranges.len(), 2, "{:?}", ranges);
+		assert!((ranges[0].1 - 1.0).abs() < 0.05 && (ranges[1].0 - 2.0).abs() < 0.05, "{:?}", ranges);
+		assert_eq!(ranges[1].1, 3.0);
+
+		// rendering the list drops the silence
+		let output = dir.path().join("out.wav");
+		Transcoder::new(input.clone(), Some(output.to_str().unwrap().to_string()), false, vec![])
+			.with_edl(edl)
+			.with_overwrite(ffmpreg::io::Overwrite::Replace)
+			.run()
+			.unwrap();
+		let mut reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+		let mut frames = 0;
+		while let Some(packet) = reader.read_packet().unwrap() {
+			frames += packet.size() / 2;
+		}
+		assert!((frames as i64 - 16000).abs() < 800, "{}", frames);
+	}
+}
+
+#[test]
+fn test_export_edl_needs_silencedetect() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&[0; 800], 8000, 1)).unwrap();
+	let export = |transforms: &[&str]| {
+		EdlExport::new(
+			input.to_str().unwrap().to_string(),
+			transforms.iter().map(|t| t.to_string()).collect(),
+		)
+		.analyze()
+	};
+	assert!(export(&["volume=0.5"]).is_err());
+	// all silence keeps nothing
+	assert_eq!(export(&["silencedetect=-60,0.05"]).unwrap().entries, Vec::<EdlEntry>::new());
+}
@@ -0,0 +1,77 @@
+use ffmpreg::container::metadata::{Chapter, MediaMetadata};
+use ffmpreg::edl::{EditDecisionList, EdlEntry, EdlSource};
+
+#[test]
+fn test_edl_parse_text() {
+	let text = "# source: in.wav\n0 1.5 intro\n\n# comment\n00:01:00 00:01:02.5\n";
+	let edl = EditDecisionList::parse(text).unwrap();
+
+	assert_eq!(edl.source.as_deref(), Some("in.wav"));
+	assert_eq!(edl.entries.len(), 2);
+	assert_eq!(edl.entries[0], EdlEntry::new(0.0, 1.5).with_label("intro"));
+	assert_eq!(edl.entries[1].start, 60.0);
+	assert_eq!(edl.entries[1].end, 62.5);
+	assert!(edl.entries[1].label.is_none());
+}
+
+#[test]
+fn test_edl_parse_text_invalid() {
+	assert!(EditDecisionList::parse("abc 1.0").is_err());
+	assert!(EditDecisionList::parse("2.0 1.0").is_err());
+}
+
+#[test]
+fn test_edl_parse_json() {
+	let text =
+		r#"{"source":"a.y4m","entries":[{"start":0.5,"end":1,"label":"x \"y\""},{"start":2,"end":3}]}"#;
+	let edl = EditDecisionList::parse(text).unwrap();
+
+	assert_eq!(edl.source.as_deref(), Some("a.y4m"));
+	assert_eq!(edl.entries.len(), 2);
+	assert_eq!(edl.entries[0].label.as_deref(), Some("x \"y\""));
+	assert_eq!(edl.total_duration(), 1.5);
+}
+
+#[test]
+fn test_edl_parse_json_missing_entries() {
+	assert!(EditDecisionList::parse(r#"{"source":"a.wav"}"#).is_err());
+}
+
+#[test]
+fn test_edl_text_roundtrip() {
+	let mut edl = EditDecisionList::new().with_source("in.wav");
+	edl.add(EdlEntry::new(0.25, 1.0).with_label("first cut"));
+	edl.add(EdlEntry::new(2.0, 4.0));
+
+	let parsed = EditDecisionList::parse(&edl.to_text()).unwrap();
+	assert_eq!(parsed, edl);
+}
+
+#[test]
+fn test_edl_json_roundtrip() {
+	let mut edl = EditDecisionList::new().with_source("dir\\in.wav");
+	edl.add(EdlEntry::new(0.25, 1.0).with_label("line\nbreak"));
+
+	let parsed = EditDecisionList::parse(&edl.to_json()).unwrap();
+	assert_eq!(parsed, edl);
+}
+
+#[test]
+fn test_edl_from_removals() {
+	let edl = EditDecisionList::from_removals(&[(3.0, 4.0), (1.0, 2.0)], 5.0);
+	let ranges: Vec<(f64, f64)> = edl.entries.iter().map(|e| (e.start, e.end)).collect();
+	assert_eq!(ranges, vec![(0.0, 1.0), (2.0, 3.0), (4.0, 5.0)]);
+}
+
+#[test]
+fn test_edl_from_metadata_chapters() {
+	let metadata = MediaMetadata::new()
+		.with_chapter(Chapter::new(0, 0, 1000).with_title("one"))
+		.with_chapter(Chapter::new(1, 1000, 2500));
+
+	let edl = metadata.edit_decisions();
+	assert_eq!(edl.entries.len(), 2);
+	assert_eq!(edl.entries[0].label.as_deref(), Some("one"));
+	assert_eq!(edl.entries[1].start, 1.0);
+	assert_eq!(edl.entries[1].end, 2.5);
+}
@@ -0,0 +1,3 @@
+mod cut;
+mod export;
+mod list;
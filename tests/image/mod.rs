@@ -0,0 +1,2 @@
+mod png;
+mod rgb;
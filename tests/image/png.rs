@@ -0,0 +1,40 @@
+use ffmpreg::image::RgbImage;
+use ffmpreg::image::png::{adler32, crc32, encode};
+
+#[test]
+fn test_checksums() {
+	assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+	assert_eq!(crc32(b"IEND"), 0xae42_6082);
+	assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+}
+
+#[test]
+fn test_png_layout() {
+	let image = RgbImage::new(2, 1, vec![255, 0, 0, 0, 255, 0]);
+	let png = encode(&image);
+
+	assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+	assert_eq!(&png[12..16], b"IHDR");
+	assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 2);
+	assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 1);
+	assert_eq!(&png[24..26], &[8, 2]);
+	assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+
+	// stored block: filter byte + 6 bytes of pixels
+	let idat = 8 + 25;
+	assert_eq!(&png[idat + 4..idat + 8], b"IDAT");
+	let payload = &png[idat + 8..];
+	assert_eq!(&payload[..2], &[0x78, 0x01]);
+	assert_eq!(payload[2], 1);
+	assert_eq!(u16::from_le_bytes([payload[3], payload[4]]), 7);
+	assert_eq!(&payload[7..14], &[0, 255, 0, 0, 0, 255, 0]);
+}
+
+#[test]
+fn test_png_splits_large_rows_into_stored_blocks() {
+	let image = RgbImage::new(200, 200, vec![7; 200 * 200 * 3]);
+	let png = encode(&image);
+	// 120200 bytes of scanlines need two stored blocks
+	assert!(png.len() > 120_200);
+	assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+}
@@ -0,0 +1,47 @@
+use ffmpreg::core::{FrameVideo, VideoFormat};
+use ffmpreg::image::RgbImage;
+
+#[test]
+fn test_from_yuv420_gray() {
+	let mut data = vec![128u8; 16];
+	data.extend(vec![128u8; 8]);
+	let frame = FrameVideo::new(data, 4, 4, VideoFormat::YUV420);
+	let image = RgbImage::from_frame(&frame).unwrap();
+
+	assert_eq!((image.width, image.height), (4, 4));
+	assert_eq!(image.pixel(3, 3), [130, 130, 130]);
+}
+
+#[test]
+fn test_from_yuv444_red() {
+	let frame = FrameVideo::new(vec![82, 90, 240], 1, 1, VideoFormat::YUV444);
+	let [r, g, b] = RgbImage::from_frame(&frame).unwrap().pixel(0, 0);
+	assert!(r > 250 && g < 5 && b < 5);
+}
+
+#[test]
+fn test_from_frame_too_small() {
+	let frame = FrameVideo::new(vec![0; 4], 4, 4, VideoFormat::YUV420);
+	assert!(RgbImage::from_frame(&frame).is_err());
+}
+
+#[test]
+fn test_resize_and_center_crop() {
+	let image = RgbImage::new(2, 1, vec![0, 0, 0, 200, 200, 200]);
+	let resized = image.resize(4, 2);
+	assert_eq!((resized.width, resized.height), (4, 2));
+	assert_eq!(resized.pixel(0, 0), [0, 0, 0]);
+	assert_eq!(resized.pixel(1, 1), [50, 50, 50]);
+	assert_eq!(resized.pixel(3, 0), [200, 200, 200]);
+
+	let cropped = resized.center_crop(2, 1);
+	assert_eq!((cropped.width, cropped.height), (2, 1));
+	assert_eq!(cropped.pixel(0, 0), [50, 50, 50]);
+	assert_eq!(cropped.center_crop(10, 10), cropped);
+}
+
+#[test]
+fn test_to_ppm() {
+	let ppm = RgbImage::new(1, 1, vec![1, 2, 3]).to_ppm();
+	assert_eq!(ppm, b"P6\n1 1\n255\n\x01\x02\x03".to_vec());
+}
@@ -0,0 +1,28 @@
+use ffmpreg::io::json::{self, JsonValue};
+
+#[test]
+fn test_json_parse_nested() {
+	let value =
+		json::parse(r#"{"a": [1, 2.5, -3e2], "b": {"c": true, "d": null}, "e": "xA"}"#).unwrap();
+
+	let a = value.get("a").and_then(JsonValue::as_array).unwrap();
+	assert_eq!(a.len(), 3);
+	assert_eq!(a[2].as_f64(), Some(-300.0));
+	assert_eq!(value.get("b").and_then(|b| b.get("c")).and_then(JsonValue::as_bool), Some(true));
+	assert!(value.get("b").and_then(|b| b.get("d")).unwrap().is_null());
+	assert_eq!(value.get("e").and_then(JsonValue::as_str), Some("xA"));
+}
+
+#[test]
+fn test_json_parse_invalid() {
+	assert!(json::parse("{\"a\": }").is_err());
+	assert!(json::parse("[1, 2").is_err());
+	assert!(json::parse("{} extra").is_err());
+}
+
+#[test]
+fn test_json_escape_roundtrip() {
+	let text = "quote \" slash \\ tab \t";
+	let parsed = json::parse(&format!("\"{}\"", json::escape(text))).unwrap();
+	assert_eq!(parsed.as_str(), Some(text));
+}
@@ -1,3 +1,7 @@
+mod json;
+mod pipe;
+mod policy;
 mod reader;
 mod seek;
+mod toml;
 mod writer;
@@ -0,0 +1,31 @@
+use ffmpreg::io::{MediaSeek, MediaWrite, PipeWriter, SeekFrom, WritePrimitives, is_pipe};
+
+#[test]
+fn test_is_pipe() {
+	assert!(is_pipe("-"));
+	assert!(!is_pipe("-.wav"));
+	assert!(!is_pipe("input.wav"));
+}
+
+#[test]
+fn test_streaming_pipe_writes_through() {
+	let mut writer = PipeWriter::streaming(Vec::new());
+	writer.write_all(&[1, 2, 3]).unwrap();
+	assert_eq!(writer.seek(SeekFrom::Current(0)).unwrap(), 3);
+	assert_eq!(writer.seek(SeekFrom::Start(3)).unwrap(), 3);
+	assert!(writer.seek(SeekFrom::Start(0)).is_err());
+	MediaWrite::flush(&mut writer).unwrap();
+	assert_eq!(writer.into_inner(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_buffered_pipe_patches_before_flush() {
+	let mut writer = PipeWriter::buffered(Vec::new());
+	writer.write_all(&[0, 0, 3, 4]).unwrap();
+	writer.seek(SeekFrom::Start(0)).unwrap();
+	writer.write_all(&[1, 2]).unwrap();
+	writer.seek(SeekFrom::End(0)).unwrap();
+	writer.write_all(&[5]).unwrap();
+	MediaWrite::flush(&mut writer).unwrap();
+	assert_eq!(writer.into_inner(), vec![1, 2, 3, 4, 5]);
+}
@@ -0,0 +1,109 @@
+use ffmpreg::io::FileAdapter;
+use ffmpreg::io::{AllowAll, Overwrite, PathAccess, PathPolicy, RootedPolicy};
+use ffmpreg::io::{IoErrorKind, policy};
+use tempfile::tempdir;
+
+#[test]
+fn test_allow_all() {
+	assert!(AllowAll.check("/etc/passwd".as_ref(), PathAccess::Read).is_ok());
+	assert!(AllowAll.check("/etc/passwd".as_ref(), PathAccess::Write).is_ok());
+}
+
+#[test]
+fn test_rooted_policy_allows_paths_inside_root() {
+	let dir = tempdir().unwrap();
+	let policy = RootedPolicy::new().with_root(dir.path()).unwrap();
+
+	assert!(policy.check(&dir.path().join("in.wav"), PathAccess::Read).is_ok());
+	assert!(policy.check(&dir.path().join("sub/new/out.wav"), PathAccess::Write).is_ok());
+	assert!(policy.check(&dir.path().join("sub/../out.wav"), PathAccess::Write).is_ok());
+}
+
+#[test]
+fn test_rooted_policy_rejects_escapes() {
+	let dir = tempdir().unwrap();
+	let root = dir.path().join("root");
+	std::fs::create_dir(&root).unwrap();
+	let policy = RootedPolicy::new().with_root(&root).unwrap();
+
+	let err = policy.check(&root.join("../secret.wav"), PathAccess::Read).unwrap_err();
+	assert!(matches!(err.kind(), IoErrorKind::PermissionDenied));
+	assert!(policy.check(&dir.path().join("rootless/a.wav"), PathAccess::Read).is_err());
+	assert!(policy.check("/etc/passwd".as_ref(), PathAccess::Read).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_rooted_policy_rejects_symlink_escape() {
+	let dir = tempdir().unwrap();
+	let root = dir.path().join("root");
+	let outside = dir.path().join("outside");
+	std::fs::create_dir(&root).unwrap();
+	std::fs::create_dir(&outside).unwrap();
+	std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+	let policy = RootedPolicy::new().with_root(&root).unwrap();
+	assert!(policy.check(&root.join("link/a.wav"), PathAccess::Write).is_err());
+	assert!(policy.check(&root.join("link/../a.wav"), PathAccess::Write).is_err());
+}
+
+#[test]
+fn test_rooted_policy_separate_read_and_write_roots() {
+	let dir = tempdir().unwrap();
+	let inputs = dir.path().join("in");
+	let outputs = dir.path().join("out");
+	std::fs::create_dir(&inputs).unwrap();
+	std::fs::create_dir(&outputs).unwrap();
+
+	let policy =
+		RootedPolicy::new().with_read_root(&inputs).unwrap().with_write_root(&outputs).unwrap();
+	assert!(policy.check(&inputs.join("a.wav"), PathAccess::Read).is_ok());
+	assert!(policy.check(&inputs.join("a.wav"), PathAccess::Write).is_err());
+	assert!(policy.check(&outputs.join("a.wav"), PathAccess::Write).is_ok());
+	assert!(policy.check(&outputs.join("a.wav"), PathAccess::Read).is_err());
+}
+
+#[test]
+fn test_rooted_policy_missing_root() {
+	let dir = tempdir().unwrap();
+	assert!(RootedPolicy::new().with_root(dir.path().join("missing")).is_err());
+}
+
+#[test]
+fn test_resolve_removes_dot_components() {
+	let dir = tempdir().unwrap();
+	let real = dir.path().canonicalize().unwrap();
+	let resolved = policy::resolve(&dir.path().join("./a/../b.wav")).unwrap();
+	assert_eq!(resolved, real.join("b.wav"));
+}
+
+#[test]
+fn test_file_adapter_checks_policy() {
+	let dir = tempdir().unwrap();
+	let root = dir.path().join("root");
+	std::fs::create_dir(&root).unwrap();
+	let policy = RootedPolicy::new().with_root(&root).unwrap();
+
+	let inside = root.join("a.wav");
+	let outside = dir.path().join("b.wav");
+	assert!(FileAdapter::create_with(inside.to_str().unwrap(), &policy).is_ok());
+	assert!(FileAdapter::create_with(outside.to_str().unwrap(), &policy).is_err());
+	assert!(!outside.exists());
+}
+
+#[test]
+fn test_overwrite_existing_file() {
+	let dir = tempdir().unwrap();
+	let existing = dir.path().join("out.wav");
+	std::fs::write(&existing, b"keep").unwrap();
+	let missing = dir.path().join("new.wav");
+
+	let err = Overwrite::Refuse.check(&existing).unwrap_err();
+	assert!(matches!(err.kind(), IoErrorKind::AlreadyExists));
+	assert!(!Overwrite::Skip.check(&existing).unwrap());
+	assert!(Overwrite::Replace.check(&existing).unwrap());
+
+	for overwrite in [Overwrite::Refuse, Overwrite::Skip, Overwrite::Replace] {
+		assert!(overwrite.check(&missing).unwrap());
+	}
+}
@@ -0,0 +1,58 @@
+use ffmpreg::io::json::JsonValue;
+use ffmpreg::io::toml;
+
+#[test]
+fn test_toml_parse_tables_and_values() {
+	let value = toml::parse(
+		r#"
+# a comment
+name = "mix" # trailing comment
+rate = 48_000
+gain = -1.5
+dry = false
+path = 'C:\audio\in.wav'
+
+[limits]
+peak = 0.9
+
+[[job]]
+input = "a.wav"
+apply = [
+	"gain=2",
+	"normalize", # keep this one
+]
+
+[[job]]
+input = "b.wav"
+point = { x = 1, y = 2 }
+"#,
+	)
+	.unwrap();
+
+	assert_eq!(value.get("name").and_then(JsonValue::as_str), Some("mix"));
+	assert_eq!(value.get("rate").and_then(JsonValue::as_f64), Some(48000.0));
+	assert_eq!(value.get("gain").and_then(JsonValue::as_f64), Some(-1.5));
+	assert_eq!(value.get("dry").and_then(JsonValue::as_bool), Some(false));
+	assert_eq!(value.get("path").and_then(JsonValue::as_str), Some(r"C:\audio\in.wav"));
+	assert_eq!(
+		value.get("limits").and_then(|t| t.get("peak")).and_then(JsonValue::as_f64),
+		Some(0.9)
+	);
+
+	let jobs = value.get("job").and_then(JsonValue::as_array).unwrap();
+	assert_eq!(jobs.len(), 2);
+	assert_eq!(jobs[0].get("apply").and_then(JsonValue::as_array).map(<[_]>::len), Some(2));
+	assert_eq!(jobs[1].get("input").and_then(JsonValue::as_str), Some("b.wav"));
+	assert_eq!(jobs[1].get("point").and_then(|p| p.get("y")).and_then(JsonValue::as_f64), Some(2.0));
+}
+
+#[test]
+fn test_toml_parse_invalid() {
+	assert!(toml::parse("a = ").is_err());
+	assert!(toml::parse("a = 1\na = 2").is_err());
+	assert!(toml::parse("a = \"open").is_err());
+	assert!(toml::parse("a = 1 2").is_err());
+	assert!(toml::parse("a.b = 1").is_err());
+	assert!(toml::parse("[t]\n[t]").is_err());
+	assert!(toml::parse("list = [1, 2").is_err());
+}
@@ -1,9 +1,16 @@
+#[cfg(feature = "cli")]
 mod cli;
 mod codecs;
 mod common;
 mod container;
 mod core;
+mod edl;
+mod image;
 mod io;
+#[cfg(feature = "cli")]
+mod serve;
+mod show;
+mod transcode;
 mod transform;
 
 use ffmpreg::codecs::{PcmDecoder, PcmEncoder, RawVideoDecoder, RawVideoEncoder};
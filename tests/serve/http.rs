@@ -0,0 +1,45 @@
+use ffmpreg::serve::http::percent_decode;
+use ffmpreg::serve::{Request, Response};
+
+#[test]
+fn test_request_read_from_stream() {
+	let raw = b"POST /jobs?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\n\r\nbody";
+	let request = Request::read_from(&raw[..]).unwrap();
+
+	assert_eq!(request.method, "POST");
+	assert_eq!(request.path, "/jobs");
+	assert_eq!(request.query_param("x"), Some("1"));
+	assert_eq!(request.body, b"body");
+}
+
+#[test]
+fn test_request_malformed() {
+	assert!(Request::read_from(&b"\r\n\r\n"[..]).is_err());
+	assert!(Request::read_from(&b"GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n"[..]).is_err());
+}
+
+#[test]
+fn test_request_query_decoding() {
+	let request = Request::new("GET", "/probe?path=%2Ftmp%2Fa%20b.wav&frames=3");
+	assert_eq!(request.query_param("path"), Some("/tmp/a b.wav"));
+	assert_eq!(request.query_param("frames"), Some("3"));
+	assert_eq!(request.query_param("missing"), None);
+}
+
+#[test]
+fn test_percent_decode_invalid_escape() {
+	assert_eq!(percent_decode("100%"), "100%");
+	assert_eq!(percent_decode("a%zzb"), "a%zzb");
+	assert_eq!(percent_decode("a+b"), "a b");
+}
+
+#[test]
+fn test_response_write() {
+	let mut out = Vec::new();
+	Response::json(201, "{}").write_to(&mut out).unwrap();
+	let text = String::from_utf8(out).unwrap();
+
+	assert!(text.starts_with("HTTP/1.1 201 Created\r\n"));
+	assert!(text.contains("Content-Length: 2\r\n"));
+	assert!(text.ends_with("\r\n\r\n{}"));
+}
@@ -0,0 +1,3 @@
+mod http;
+mod queue;
+mod routes;
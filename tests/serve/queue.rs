@@ -0,0 +1,154 @@
+use ffmpreg::io::{IoResult, PathAccess, PathPolicy};
+use ffmpreg::serve::{ConcurrencyLimits, JobClass, JobQueue, JobSpec, JobState, ServeArgs};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+const PAUSED: ConcurrencyLimits = ConcurrencyLimits { audio: 0, video: 0 };
+
+fn spec(input: &str, priority: i32) -> JobSpec {
+	JobSpec { input: input.to_string(), output: "out".to_string(), transforms: vec![], priority }
+}
+
+fn wait_idle(queue: &JobQueue) {
+	let deadline = Instant::now() + Duration::from_secs(5);
+	while !queue.is_idle() && Instant::now() < deadline {
+		std::thread::sleep(Duration::from_millis(5));
+	}
+}
+
+#[test]
+fn test_job_class_from_input() {
+	assert_eq!(spec("a.wav", 0).class(), JobClass::Audio);
+	assert_eq!(spec("a.flac", 0).class(), JobClass::Audio);
+	assert_eq!(spec("a.y4m", 0).class(), JobClass::Video);
+	assert_eq!(spec("a.mp4", 0).class(), JobClass::Video);
+}
+
+#[test]
+fn test_queue_pending_orders_by_priority_then_id() {
+	let queue = JobQueue::new().with_limits(PAUSED);
+	queue.submit(spec("a.wav", 0)).unwrap();
+	queue.submit(spec("b.wav", 5)).unwrap();
+	queue.submit(spec("c.wav", 0)).unwrap();
+	queue.submit(spec("d.y4m", 5)).unwrap();
+
+	let order: Vec<u64> = queue.pending().iter().map(|j| j.id).collect();
+	assert_eq!(order, vec![2, 4, 1, 3]);
+}
+
+#[test]
+fn test_queue_limit_zero_keeps_jobs_queued() {
+	let queue = JobQueue::new().with_limits(PAUSED);
+	let job = queue.submit(spec("a.wav", 0)).unwrap();
+	queue.dispatch();
+	assert_eq!(queue.get(job.id).unwrap().state, JobState::Queued);
+	assert!(!queue.is_idle());
+}
+
+#[test]
+fn test_queue_persists_and_recovers() {
+	let dir = tempdir().unwrap();
+	let state_path = dir.path().join("queue.json");
+
+	let queue = JobQueue::new().with_limits(PAUSED).with_state_file(&state_path).unwrap();
+	queue.submit(spec("a.wav", 1)).unwrap();
+	queue.submit(spec("b.y4m", 2)).unwrap();
+	assert!(state_path.exists());
+
+	let restored = JobQueue::new().with_limits(PAUSED).with_state_file(&state_path).unwrap();
+	let jobs = restored.list();
+	assert_eq!(jobs.len(), 2);
+	assert_eq!(jobs[1].spec.input, "b.y4m");
+	assert_eq!(jobs[1].spec.priority, 2);
+
+	let next = restored.submit(spec("c.wav", 0)).unwrap();
+	assert_eq!(next.id, 3);
+}
+
+#[test]
+fn test_queue_requeues_running_jobs_on_restart() {
+	let dir = tempdir().unwrap();
+	let state_path = dir.path().join("queue.json");
+	std::fs::write(
+		&state_path,
+		r#"{"next_id":7,"jobs":[
+			{"id":6,"status":"running","priority":0,"input":"a.wav","output":"b.wav","transforms":[]},
+			{"id":7,"status":"failed","priority":0,"input":"c.wav","output":"d.wav","transforms":[],"error":"boom"}
+		]}"#,
+	)
+	.unwrap();
+
+	let queue = JobQueue::new().with_limits(PAUSED).with_state_file(&state_path).unwrap();
+	assert_eq!(queue.get(6).unwrap().state, JobState::Queued);
+	assert_eq!(queue.get(7).unwrap().state, JobState::Failed("boom".to_string()));
+	assert_eq!(queue.submit(spec("e.wav", 0)).unwrap().id, 8);
+}
+
+#[test]
+fn test_queue_invalid_state_file() {
+	let dir = tempdir().unwrap();
+	let state_path = dir.path().join("queue.json");
+	std::fs::write(&state_path, "{\"jobs\":[{\"id\":1,\"status\":\"lost\"}]}").unwrap();
+	assert!(JobQueue::new().with_state_file(&state_path).is_err());
+}
+
+#[test]
+fn test_queue_runs_all_jobs_within_limits() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let limits = ConcurrencyLimits { audio: 1, video: 1 };
+	let queue = JobQueue::new().with_limits(limits);
+	for idx in 0..3 {
+		let output = dir.path().join(format!("out{}.wav", idx));
+		let spec = JobSpec {
+			input: input.to_str().unwrap().to_string(),
+			output: output.to_str().unwrap().to_string(),
+			transforms: vec![],
+			priority: idx,
+		};
+		queue.submit(spec).unwrap();
+	}
+
+	wait_idle(&queue);
+	assert!(queue.list().iter().all(|j| j.state == JobState::Done));
+}
+
+// stands in for a job that hits a bug
+#[derive(Debug)]
+struct Panics;
+
+impl PathPolicy for Panics {
+	fn check(&self, _path: &Path, _access: PathAccess) -> IoResult<()> {
+		panic!("policy exploded")
+	}
+}
+
+#[test]
+fn test_queue_frees_slot_after_panic() {
+	let limits = ConcurrencyLimits { audio: 1, video: 1 };
+	let queue = JobQueue::new().with_limits(limits).with_path_policy(Arc::new(Panics));
+	let spec = |input: &str| JobSpec { output: "out.wav".to_string(), ..spec(input, 0) };
+	let first = queue.submit(spec("a.wav")).unwrap();
+	let second = queue.submit(spec("b.wav")).unwrap();
+
+	wait_idle(&queue);
+	for id in [first.id, second.id] {
+		let JobState::Failed(error) = queue.get(id).unwrap().state else {
+			panic!("job {} did not fail", id);
+		};
+		assert_eq!(error, "job panicked: policy exploded");
+	}
+}
+
+#[test]
+fn test_serve_rejects_zero_jobs() {
+	use clap::Parser;
+	assert!(ServeArgs::try_parse_from(["serve", "--audio-jobs", "0"]).is_err());
+	assert!(ServeArgs::try_parse_from(["serve", "--video-jobs", "0"]).is_err());
+	let args = ServeArgs::try_parse_from(["serve", "--audio-jobs", "3"]).unwrap();
+	assert_eq!((args.audio_jobs, args.video_jobs), (3, 1));
+}
@@ -0,0 +1,143 @@
+use ffmpreg::io::RootedPolicy;
+use ffmpreg::io::json::{self, JsonValue};
+use ffmpreg::serve::{JobQueue, JobSpec, JobState, Request, Server, route};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn wait_for_job(jobs: &JobQueue, id: u64) -> JobState {
+	let deadline = Instant::now() + Duration::from_secs(5);
+	loop {
+		let state = jobs.get(id).unwrap().state;
+		if state.is_finished() || Instant::now() > deadline {
+			return state;
+		}
+		std::thread::sleep(Duration::from_millis(5));
+	}
+}
+
+#[test]
+fn test_job_spec_from_json() {
+	let spec =
+		JobSpec::from_json(r#"{"input":"a.wav","output":"b.wav","transforms":["volume=2"]}"#).unwrap();
+	assert_eq!(spec.input, "a.wav");
+	assert_eq!(spec.output, "b.wav");
+	assert_eq!(spec.transforms, vec!["volume=2".to_string()]);
+
+	assert!(JobSpec::from_json(r#"{"input":"a.wav"}"#).is_err());
+	assert!(JobSpec::from_json(r#"{"input":"a.wav","output":"b.wav","transforms":[1]}"#).is_err());
+}
+
+#[test]
+fn test_route_submit_and_poll() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let jobs = JobQueue::new();
+	let body = format!(
+		"{{\"input\":\"{}\",\"output\":\"{}\",\"transforms\":[\"volume=0.5\"]}}",
+		json::escape(input.to_str().unwrap()),
+		json::escape(output.to_str().unwrap())
+	);
+	let response = route(&Request::new("POST", "/jobs").with_body(body), &jobs);
+	assert_eq!(response.status, 201);
+
+	let created = json::parse(&response.body).unwrap();
+	let id = created.get("id").and_then(JsonValue::as_f64).unwrap() as u64;
+	assert_eq!(wait_for_job(&jobs, id), JobState::Done);
+	assert!(output.exists());
+
+	let response = route(&Request::new("GET", &format!("/jobs/{}", id)), &jobs);
+	assert_eq!(response.status, 200);
+	let polled = json::parse(&response.body).unwrap();
+	assert_eq!(polled.get("status").and_then(JsonValue::as_str), Some("done"));
+
+	let response = route(&Request::new("GET", "/jobs"), &jobs);
+	assert_eq!(json::parse(&response.body).unwrap().as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_route_failed_job_reports_error() {
+	let jobs = JobQueue::new();
+	let body = r#"{"input":"/nonexistent/in.wav","output":"/nonexistent/out.wav"}"#;
+	let response = route(&Request::new("POST", "/jobs").with_body(body), &jobs);
+	assert_eq!(response.status, 201);
+
+	let state = wait_for_job(&jobs, 1);
+	assert!(matches!(state, JobState::Failed(_)));
+	let polled = json::parse(&route(&Request::new("GET", "/jobs/1"), &jobs).body).unwrap();
+	assert!(polled.get("error").is_some());
+}
+
+#[test]
+fn test_route_errors() {
+	let jobs = JobQueue::new();
+	assert_eq!(route(&Request::new("GET", "/jobs/42"), &jobs).status, 404);
+	assert_eq!(route(&Request::new("GET", "/nope"), &jobs).status, 404);
+	assert_eq!(route(&Request::new("DELETE", "/jobs"), &jobs).status, 405);
+	assert_eq!(route(&Request::new("POST", "/jobs").with_body("{"), &jobs).status, 400);
+	assert_eq!(route(&Request::new("GET", "/probe"), &jobs).status, 400);
+}
+
+#[test]
+fn test_route_probe() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let jobs = JobQueue::new();
+	let target = format!("/probe?path={}&frames=1", input.to_str().unwrap());
+	let response = route(&Request::new("GET", &target), &jobs);
+	assert_eq!(response.status, 200);
+
+	let info = json::parse(&response.body).unwrap();
+	assert_eq!(info.get("streams").and_then(JsonValue::as_array).unwrap().len(), 1);
+	assert_eq!(info.get("frames").and_then(JsonValue::as_array).unwrap().len(), 1);
+}
+
+#[test]
+fn test_server_over_tcp() {
+	let server = Server::bind("127.0.0.1:0").unwrap();
+	let addr = server.local_addr().unwrap();
+	std::thread::spawn(move || server.run());
+
+	let mut stream = TcpStream::connect(addr).unwrap();
+	stream.write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).unwrap();
+
+	assert!(response.starts_with("HTTP/1.1 200 OK"));
+	assert!(response.ends_with("{\"status\":\"ok\"}"));
+}
+
+#[test]
+fn test_route_enforces_path_policy() {
+	let dir = tempdir().unwrap();
+	let root = dir.path().join("root");
+	std::fs::create_dir(&root).unwrap();
+	let input = root.join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let policy = RootedPolicy::new().with_root(&root).unwrap();
+	let jobs = JobQueue::new().with_path_policy(Arc::new(policy));
+
+	let escape = root.join("../out.wav");
+	let body = format!(
+		"{{\"input\":\"{}\",\"output\":\"{}\"}}",
+		json::escape(input.to_str().unwrap()),
+		json::escape(escape.to_str().unwrap())
+	);
+	let response = route(&Request::new("POST", "/jobs").with_body(body), &jobs);
+	assert_eq!(response.status, 403);
+	assert!(jobs.list().is_empty());
+
+	let response = route(&Request::new("GET", "/probe?path=/etc/passwd.wav"), &jobs);
+	assert_eq!(response.status, 403);
+
+	let probe = format!("/probe?path={}", input.to_str().unwrap());
+	assert_eq!(route(&Request::new("GET", &probe), &jobs).status, 200);
+}
@@ -0,0 +1,52 @@
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{BitrateMode, BitrateStats, PacketInfo, Show, ShowFormat, ShowOptions};
+use tempfile::tempdir;
+
+fn packet(time: f64, size: usize) -> PacketInfo {
+	PacketInfo { stream_index: 0, offset: 0, size, pts: 0, dts: 0, keyframe: true, time }
+}
+
+#[test]
+fn test_bitrate_buckets() {
+	// a spike in the second second, and a file ending halfway through the third
+	let packets = [packet(0.0, 1000), packet(0.5, 1000), packet(1.2, 8000), packet(2.1, 500)];
+	let stats = BitrateStats::from_packets(&packets, 2.5);
+	assert_eq!(stats.bytes, vec![2000, 8000, 500]);
+	assert_eq!(stats.rates(), vec![16000.0, 64000.0, 8000.0]);
+	assert_eq!(stats.max(), Some((1, 64000.0)));
+	assert_eq!(stats.min(), Some((2, 8000.0)));
+	assert_eq!(stats.average(), 10500.0 * 8.0 / 2.5);
+
+	// seconds with nothing in them still count
+	let stats = BitrateStats::from_packets(&[packet(3.0, 10)], 0.0);
+	assert_eq!(stats.bytes, vec![0, 0, 0, 10]);
+	assert_eq!(BitrateMode::from_name("Sparkline"), Some(BitrateMode::Sparkline));
+	assert_eq!(BitrateMode::from_name("graph"), None);
+}
+
+#[test]
+fn test_bitrate_output() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let samples: Vec<i16> = vec![0; 20000];
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+	let path = input.to_str().unwrap().to_string();
+	let show = |format, mode| {
+		let opts = ShowOptions { format, bitrate: Some(mode), ..ShowOptions::default() };
+		Show::new(path.clone(), opts).to_bitrate().unwrap()
+	};
+
+	let text = show(ShowFormat::Human, BitrateMode::Sparkline);
+	assert!(text.contains("avg 128 kb/s"));
+	let bars = text.lines().last().unwrap();
+	assert_eq!(bars.trim_start().strip_prefix("0s ").unwrap().chars().count(), 3);
+
+	let csv = show(ShowFormat::Human, BitrateMode::Csv);
+	assert_eq!(csv.lines().next(), Some("second,bytes,kbps"));
+	assert_eq!(csv.lines().count(), 4);
+
+	let json = parse(show(ShowFormat::Json, BitrateMode::Summary).trim()).unwrap();
+	assert_eq!(json.get("avg_kbps"), Some(&JsonValue::Number(128.0)));
+	let Some(JsonValue::Array(seconds)) = json.get("kbps") else { panic!("no timeline") };
+	assert_eq!(seconds.len(), 3);
+}
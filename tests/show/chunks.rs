@@ -0,0 +1,30 @@
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{Show, ShowFormat, ShowOptions};
+use tempfile::tempdir;
+
+#[test]
+fn test_chunks_listing() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+	let path = input.to_str().unwrap().to_string();
+
+	let text = Show::new(path.clone(), ShowOptions::default()).to_chunks().unwrap();
+	let lines: Vec<&str> = text.lines().collect();
+	assert_eq!(lines[1], "  RIFF 'WAVE'  offset 0  size 2,084");
+	assert!(lines[2].starts_with("    fmt "));
+
+	let opts = ShowOptions { format: ShowFormat::Json, ..ShowOptions::default() };
+	let json = parse(Show::new(path, opts).to_chunks().unwrap().trim()).unwrap();
+	let Some(JsonValue::Array(chunks)) = json.get("chunks") else { panic!("no chunks") };
+	assert_eq!(chunks.len(), 3);
+	assert_eq!(chunks[2].get("fourcc"), Some(&JsonValue::String("data".to_string())));
+	assert_eq!(chunks[2].get("depth"), Some(&JsonValue::Number(1.0)));
+	assert_eq!(chunks[0].get("problem"), Some(&JsonValue::Null));
+
+	let flac = dir.path().join("in.flac");
+	std::fs::write(&flac, b"fLaC").unwrap();
+	assert!(
+		Show::new(flac.to_str().unwrap().to_string(), ShowOptions::default()).to_chunks().is_err()
+	);
+}
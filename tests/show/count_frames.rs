@@ -0,0 +1,84 @@
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{MediaInfo, Show, ShowFormat, ShowOptions, StreamInfo};
+use tempfile::tempdir;
+
+fn analyze(path: &std::path::Path, count_frames: bool) -> MediaInfo {
+	let opts = ShowOptions { count_frames, ..ShowOptions::default() };
+	Show::new(path.to_str().unwrap().to_string(), opts).analyze().unwrap()
+}
+
+fn frame_count(info: &MediaInfo) -> Option<u64> {
+	match &info.streams[0] {
+		StreamInfo::Video(video) => video.frame_count,
+		StreamInfo::Audio(audio) => audio.frame_count,
+	}
+}
+
+#[test]
+fn test_wav_duration_from_data_chunk() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	// a chunk before the data and another after it, neither of them audio
+	let wav = crate::common::create_test_wav_from_samples(&[100; 8000], 8000, 1);
+	let (header, data) = wav.split_at(36);
+	let mut file = header.to_vec();
+	file.extend_from_slice(b"LIST\x14\0\0\0");
+	file.extend_from_slice(&[0; 20]);
+	file.extend_from_slice(data);
+	file.extend_from_slice(b"junk\x64\0\0\0");
+	file.extend_from_slice(&[0; 100]);
+	std::fs::write(&input, &file).unwrap();
+
+	let info = analyze(&input, false);
+	assert_eq!(info.file.duration, 1.0);
+	assert_eq!(frame_count(&info), None);
+
+	// cut short, only what is in the file counts
+	std::fs::write(&input, &file[..file.len() - 108 - 4000]).unwrap();
+	assert_eq!(analyze(&input, false).file.duration, 0.75);
+	let info = analyze(&input, true);
+	assert_eq!(info.file.duration, 0.75);
+	assert_eq!(frame_count(&info), Some(3));
+	assert!(info.packets.is_none());
+}
+
+#[test]
+fn test_y4m_count_frames() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let data = crate::common::create_test_y4m_data();
+	std::fs::write(&input, &data).unwrap();
+	assert_eq!(analyze(&input, false).file.duration, 0.1);
+
+	// FRAME lines with parameters throw the estimate out, counting does not
+	let (header, frames) = data.split_at(data.len() - 3 * 102);
+	let mut file = header.to_vec();
+	for frame in frames.chunks(102) {
+		file.extend_from_slice(b"FRAME Ixyzxyzxyzxyzxyzxyz Xcomment=comment=comment\n");
+		file.extend_from_slice(&frame[6..]);
+	}
+	std::fs::write(&input, &file).unwrap();
+	assert_ne!(analyze(&input, false).file.duration, 0.1);
+	let info = analyze(&input, true);
+	assert_eq!(info.file.duration, 0.1);
+	assert_eq!(frame_count(&info), Some(3));
+
+	let opts = ShowOptions { format: ShowFormat::Json, count_frames: true, ..ShowOptions::default() };
+	let json = Show::new(input.to_str().unwrap().to_string(), opts).to_json().unwrap();
+	let json = parse(&json).unwrap();
+	let Some(JsonValue::Array(streams)) = json.get("streams") else { panic!("no streams") };
+	assert_eq!(streams[0].get("frames"), Some(&JsonValue::Number(3.0)));
+}
+
+#[test]
+fn test_y4m_count_frames_truncated() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let data = crate::common::create_test_y4m_data();
+	// the last frame loses its tail
+	std::fs::write(&input, &data[..data.len() - 40]).unwrap();
+
+	let info = analyze(&input, true);
+	assert_eq!(frame_count(&info), Some(2));
+	assert!((info.file.duration - 2.0 / 30.0).abs() < 1e-9);
+}
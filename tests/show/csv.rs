@@ -0,0 +1,36 @@
+use ffmpreg::show::{Show, ShowFormat, ShowOptions};
+use tempfile::tempdir;
+
+#[test]
+fn test_csv_rows() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in, take 2.wav");
+	let samples: Vec<i16> = (0..16000).map(|i| (i % 100) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 2)).unwrap();
+
+	let opts = ShowOptions { format: ShowFormat::Csv, frame_limit: 3, ..ShowOptions::default() };
+	let csv = Show::new(input.to_str().unwrap().to_string(), opts).to_csv().unwrap();
+	let rows: Vec<&str> = csv.lines().collect();
+	let header: Vec<&str> = rows[0].split(',').collect();
+	assert_eq!(header[0], "kind");
+
+	// a file row, a row per stream, then a row per frame
+	assert!(rows[1].starts_with("file,"));
+	// the comma in the path is quoted
+	assert!(rows[1].contains(&format!("\"{}\"", input.to_str().unwrap())));
+	assert_eq!(rows.len(), 1 + 1 + 1 + 3);
+
+	let stream: Vec<&str> = rows[2].split(',').collect();
+	assert_eq!(stream.len(), header.len());
+	let column = |name: &str| header.iter().position(|h| *h == name).unwrap();
+	assert_eq!(stream[column("kind")], "stream");
+	assert_eq!(stream[column("codec")], "pcm_s16le");
+	assert_eq!(stream[column("sample_rate")], "8000");
+	assert_eq!(stream[column("channels")], "2");
+
+	let frame: Vec<&str> = rows[4].split(',').collect();
+	assert_eq!(frame[column("kind")], "frame");
+	assert_eq!(frame[column("index")], "1");
+	assert_eq!(frame[column("keyframe")], "1");
+	assert!(frame[column("pts")].parse::<i64>().unwrap() > 0);
+}
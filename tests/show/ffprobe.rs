@@ -0,0 +1,108 @@
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{Show, ShowFormat, ShowOptions};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn probe(path: &Path) -> JsonValue {
+	let opts = ShowOptions {
+		format: ShowFormat::Json,
+		ffprobe: true,
+		frame_limit: 2,
+		..ShowOptions::default()
+	};
+	let json = Show::new(path.to_str().unwrap().to_string(), opts).to_json().unwrap();
+	parse(&json).unwrap()
+}
+
+fn keys(value: &JsonValue) -> Vec<&str> {
+	value.as_object().unwrap().iter().map(|(key, _)| key.as_str()).collect()
+}
+
+#[test]
+fn test_ffprobe_wav() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let samples: Vec<i16> = (0..16000).map(|i| (i % 100) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 2)).unwrap();
+
+	let probe = probe(&input);
+	assert_eq!(keys(&probe), vec!["frames", "streams", "format"]);
+
+	let format = probe.get("format").unwrap();
+	assert_eq!(format.get("format_name").and_then(JsonValue::as_str), Some("wav"));
+	assert_eq!(format.get("nb_streams").and_then(JsonValue::as_f64), Some(1.0));
+	// ffprobe quotes durations, sizes and rates
+	assert_eq!(format.get("duration").and_then(JsonValue::as_str), Some("1.000000"));
+	assert!(format.get("size").and_then(JsonValue::as_str).is_some());
+
+	let stream = &probe.get("streams").unwrap().as_array().unwrap()[0];
+	assert_eq!(stream.get("codec_name").and_then(JsonValue::as_str), Some("pcm_s16le"));
+	assert_eq!(stream.get("codec_type").and_then(JsonValue::as_str), Some("audio"));
+	assert_eq!(stream.get("sample_rate").and_then(JsonValue::as_str), Some("8000"));
+	assert_eq!(stream.get("channels").and_then(JsonValue::as_f64), Some(2.0));
+	assert_eq!(stream.get("time_base").and_then(JsonValue::as_str), Some("1/8000"));
+	assert_eq!(stream.get("sample_fmt").and_then(JsonValue::as_str), Some("s16"));
+	assert!(stream.get("disposition").and_then(|d| d.get("default")).is_some());
+
+	let frames = probe.get("frames").unwrap().as_array().unwrap();
+	assert_eq!(frames.len(), 2);
+	assert_eq!(frames[0].get("media_type").and_then(JsonValue::as_str), Some("audio"));
+	assert_eq!(frames[0].get("key_frame").and_then(JsonValue::as_f64), Some(1.0));
+	let pts = frames[1].get("pts").and_then(JsonValue::as_f64).unwrap();
+	let pts_time = frames[1].get("pts_time").and_then(JsonValue::as_str).unwrap();
+	assert_eq!(pts_time, format!("{:.6}", pts / 8000.0));
+}
+
+#[test]
+fn test_ffprobe_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	let probe = probe(&input);
+	let format = probe.get("format").unwrap();
+	assert_eq!(format.get("format_name").and_then(JsonValue::as_str), Some("yuv4mpegpipe"));
+
+	let stream = &probe.get("streams").unwrap().as_array().unwrap()[0];
+	assert_eq!(stream.get("codec_name").and_then(JsonValue::as_str), Some("rawvideo"));
+	assert_eq!(stream.get("codec_type").and_then(JsonValue::as_str), Some("video"));
+	assert_eq!(stream.get("pix_fmt").and_then(JsonValue::as_str), Some("yuv420p"));
+	let rate = stream.get("r_frame_rate").and_then(JsonValue::as_str).unwrap();
+	let (num, den) = rate.split_once('/').unwrap();
+	assert_eq!(
+		stream.get("time_base").and_then(JsonValue::as_str),
+		Some(format!("{}/{}", den, num).as_str())
+	);
+
+	let frames = probe.get("frames").unwrap().as_array().unwrap();
+	assert_eq!(frames[0].get("media_type").and_then(JsonValue::as_str), Some("video"));
+	assert!(frames[0].get("width").is_some());
+}
+
+#[test]
+fn test_ffprobe_y4m_jpeg_range_and_aspect() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let mut data = b"YUV4MPEG2 W64 H36 F25:1 Ip A1:1 C420jpeg\n".to_vec();
+	data.extend_from_slice(b"FRAME\n");
+	data.extend_from_slice(&[0; 64 * 36 * 3 / 2]);
+	std::fs::write(&input, &data).unwrap();
+
+	let probe = probe(&input);
+	let stream = &probe.get("streams").unwrap().as_array().unwrap()[0];
+	assert_eq!(stream.get("pix_fmt").and_then(JsonValue::as_str), Some("yuvj420p"));
+	assert_eq!(stream.get("sample_aspect_ratio").and_then(JsonValue::as_str), Some("1:1"));
+	assert_eq!(stream.get("display_aspect_ratio").and_then(JsonValue::as_str), Some("16:9"));
+}
+
+#[test]
+fn test_show_json_sample_fmt() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+	let opts = ShowOptions { format: ShowFormat::Json, ..ShowOptions::default() };
+	let json = Show::new(input.to_str().unwrap().to_string(), opts).to_json().unwrap();
+	let json = parse(&json).unwrap();
+	let stream = &json.get("streams").unwrap().as_array().unwrap()[0];
+	assert_eq!(stream.get("sample_fmt").and_then(JsonValue::as_str), Some("s16"));
+}
@@ -0,0 +1,81 @@
+use ffmpreg::codecs::FlacEncoder;
+use ffmpreg::container::mp4::{Mp4Track, TrackType};
+use ffmpreg::container::{AviFormat, AviWriter, FlacFormat, FlacWriter, Mp4Format, Mp4Writer};
+use ffmpreg::core::{Encoder, Frame, FrameAudio, Muxer, Packet, Timebase};
+use ffmpreg::io::FileAdapter;
+use ffmpreg::show::{MediaInfo, Show, ShowOptions};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn show(path: &Path, frame_limit: usize) -> MediaInfo {
+	let opts = ShowOptions { frame_limit, ..ShowOptions::default() };
+	Show::new(path.to_str().unwrap().to_string(), opts).analyze().unwrap()
+}
+
+#[test]
+fn test_frames_flac() {
+	let dir = tempdir().unwrap();
+	let flac = dir.path().join("out.flac");
+	let format = FlacFormat {
+		min_block_size: 1024,
+		max_block_size: 1024,
+		sample_rate: 8000,
+		channels: 1,
+		bits_per_sample: 16,
+		..FlacFormat::default()
+	};
+	let mut writer =
+		FlacWriter::new(FileAdapter::create(flac.to_str().unwrap()).unwrap(), format).unwrap();
+	let mut encoder = FlacEncoder::new(8000, 1, 16, 1024);
+	let mut sizes = Vec::new();
+	for block in 0..3 {
+		let samples: Vec<u8> = (0..1024).flat_map(|i| ((i * block) as i16).to_le_bytes()).collect();
+		let frame = Frame::new_audio(FrameAudio::new(samples, 8000, 1), Timebase::new(1, 8000), 0);
+		let packet = encoder.encode(frame).unwrap().unwrap();
+		sizes.push(packet.data.len());
+		writer.write_packet(packet).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	let info = show(&flac, 1);
+	assert_eq!(info.frames.len(), 1);
+	assert!(info.frames[0].keyframe && info.frames[0].size > 0);
+	// a frame starts on its sync code
+	assert!(info.frames[0].hex.starts_with("ff f8"));
+
+	// one entry per encoded frame, not per chunk the reader hands over
+	let info = show(&flac, 10);
+	assert_eq!(info.frames.iter().map(|frame| frame.size).collect::<Vec<_>>(), sizes);
+}
+
+#[test]
+fn test_frames_avi_and_mp4() {
+	let dir = tempdir().unwrap();
+	let avi = dir.path().join("out.avi");
+	let mut writer =
+		AviWriter::new(FileAdapter::create(avi.to_str().unwrap()).unwrap(), AviFormat::default())
+			.unwrap();
+	for i in 0..5u8 {
+		writer.write_packet(Packet::new(vec![i; 10 + i as usize], 0, Timebase::new(1, 30))).unwrap();
+	}
+	writer.finalize().unwrap();
+	let info = show(&avi, 3);
+	assert_eq!(info.frames.iter().map(|frame| frame.size).collect::<Vec<_>>(), vec![10, 11, 12]);
+	assert_eq!(info.frames[2].pts, 2);
+
+	// frames of the first track only, the audio samples between them skipped
+	let video = Mp4Track { track_id: 1, track_type: TrackType::Video, ..Mp4Track::default() };
+	let audio = Mp4Track { track_id: 2, track_type: TrackType::Audio, ..Mp4Track::default() };
+	let format = Mp4Format { tracks: vec![video, audio], ..Mp4Format::default() };
+	let mp4 = dir.path().join("out.mp4");
+	let mut writer =
+		Mp4Writer::new(FileAdapter::create(mp4.to_str().unwrap()).unwrap(), format).unwrap();
+	for i in 0..6u8 {
+		let packet = Packet::new(vec![i; 20 + i as usize], (i % 2) as usize, Timebase::new(1, 1000));
+		writer.write_packet(packet).unwrap();
+	}
+	writer.finalize().unwrap();
+	let info = show(&mp4, 10);
+	assert_eq!(info.frames.iter().map(|frame| frame.size).collect::<Vec<_>>(), vec![20, 22, 24]);
+	assert!(info.frames[1].hex.starts_with("02"));
+}
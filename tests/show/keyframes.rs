@@ -0,0 +1,89 @@
+use ffmpreg::container::avi::{AviStream, AviStreamHeader, BitmapInfoHeader, StreamType};
+use ffmpreg::container::mp4::{Mp4Track, TrackType};
+use ffmpreg::container::{AviFormat, AviWriter, Mp4Format, Mp4Writer};
+use ffmpreg::core::{Muxer, Packet, Timebase};
+use ffmpreg::io::FileAdapter;
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{Show, ShowFormat, ShowOptions, StreamKeyframes};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn keyframes(path: &Path) -> Vec<StreamKeyframes> {
+	let opts = ShowOptions { keyframes: true, ..ShowOptions::default() };
+	Show::new(path.to_str().unwrap().to_string(), opts).keyframes().unwrap()
+}
+
+fn packet(size: usize, stream_index: usize, keyframe: bool) -> Packet {
+	let mut packet = Packet::new(vec![0; size], stream_index, Timebase::new(1, 30));
+	packet.keyframe = keyframe;
+	packet
+}
+
+#[test]
+fn test_keyframes_avi_index() {
+	let dir = tempdir().unwrap();
+	let avi = dir.path().join("out.avi");
+	let header = AviStreamHeader {
+		stream_type: StreamType::Video,
+		scale: 1,
+		rate: 4,
+		..AviStreamHeader::default()
+	};
+	let video =
+		AviStream { header, video_format: Some(BitmapInfoHeader::default()), audio_format: None };
+	let format = AviFormat { streams: vec![video], ..AviFormat::default() };
+	let mut writer =
+		AviWriter::new(FileAdapter::create(avi.to_str().unwrap()).unwrap(), format).unwrap();
+	for i in 0..5 {
+		writer.write_packet(packet(11, 0, i % 3 == 0)).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	let streams = keyframes(&avi);
+	assert_eq!(streams.len(), 1);
+	let times: Vec<f64> = streams[0].keyframes.iter().map(|k| k.time).collect();
+	assert_eq!(times, vec![0.0, 0.75]);
+	// each 11 byte chunk takes 20 with its header and pad byte
+	assert_eq!(streams[0].keyframes[1].offset - streams[0].keyframes[0].offset, 60);
+}
+
+#[test]
+fn test_keyframes_mp4_stss() {
+	let dir = tempdir().unwrap();
+	let video = Mp4Track { track_id: 1, track_type: TrackType::Video, ..Mp4Track::default() };
+	let audio = Mp4Track { track_id: 2, track_type: TrackType::Audio, ..Mp4Track::default() };
+	let format = Mp4Format { tracks: vec![video, audio], ..Mp4Format::default() };
+	let mp4 = dir.path().join("out.mp4");
+	let mut writer =
+		Mp4Writer::new(FileAdapter::create(mp4.to_str().unwrap()).unwrap(), format).unwrap();
+	for i in 0..4 {
+		writer.write_packet(packet(20, 0, i == 0 || i == 2)).unwrap();
+		writer.write_packet(packet(8, 1, false)).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	// the audio track is left out, and only the sync samples are listed
+	let streams = keyframes(&mp4);
+	assert_eq!(streams.len(), 1);
+	assert_eq!(streams[0].stream_index, 0);
+	let pts: Vec<i64> = streams[0].keyframes.iter().map(|k| k.pts).collect();
+	assert_eq!(pts, vec![0, 2048]);
+	assert_eq!(streams[0].keyframes[1].offset - streams[0].keyframes[0].offset, 56);
+
+	let opts = ShowOptions { format: ShowFormat::Json, keyframes: true, ..ShowOptions::default() };
+	let json = Show::new(mp4.to_str().unwrap().to_string(), opts).to_keyframes().unwrap();
+	let json = parse(json.trim()).unwrap();
+	let Some(JsonValue::Array(streams)) = json.get("streams") else { panic!("no streams") };
+	let Some(JsonValue::Array(listed)) = streams[0].get("keyframes") else { panic!("no keyframes") };
+	assert_eq!(listed.len(), 2);
+}
+
+#[test]
+fn test_keyframes_audio_only() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+	let opts = ShowOptions { keyframes: true, ..ShowOptions::default() };
+	let text = Show::new(input.to_str().unwrap().to_string(), opts).to_keyframes().unwrap();
+	assert!(text.ends_with("  no video streams\n"));
+}
@@ -0,0 +1,11 @@
+mod bitrate;
+mod chunks;
+mod count_frames;
+mod csv;
+mod ffprobe;
+mod frames;
+mod keyframes;
+mod packets;
+mod stats;
+mod tags;
+mod xml;
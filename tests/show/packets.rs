@@ -0,0 +1,139 @@
+use ffmpreg::codecs::FlacEncoder;
+use ffmpreg::container::mp4::{Mp4Track, TrackType};
+use ffmpreg::container::{
+	AviFormat, AviWriter, FlacFormat, FlacWriter, Mp4Format, Mp4Writer, WavReader, Y4mReader,
+};
+use ffmpreg::core::{Demuxer, Encoder, Frame, FrameAudio, Muxer, Packet, Timebase};
+use ffmpreg::io::{Cursor, FileAdapter};
+use ffmpreg::show::{PacketInfo, Show, ShowOptions};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn packets(path: &Path) -> Vec<PacketInfo> {
+	let opts = ShowOptions { packets: true, ..ShowOptions::default() };
+	Show::new(path.to_str().unwrap().to_string(), opts).analyze().unwrap().packets.unwrap()
+}
+
+// the bytes at each offset are the packet the demuxer hands over
+fn assert_offsets(path: &Path, listed: &[PacketInfo], mut demuxer: impl Demuxer) {
+	let bytes = std::fs::read(path).unwrap();
+	for info in listed {
+		let packet = demuxer.read_packet().unwrap().unwrap();
+		let offset = info.offset as usize;
+		assert_eq!(info.size, packet.data.len());
+		assert_eq!(&bytes[offset..offset + info.size], &packet.data[..], "packet at {}", offset);
+		assert_eq!(info.stream_index, packet.stream_index);
+	}
+	assert!(demuxer.read_packet().unwrap().is_none());
+}
+
+#[test]
+fn test_packets_wav() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let samples: Vec<i16> = (0..5000).map(|i| i as i16).collect();
+	let data = crate::common::create_test_wav_from_samples(&samples, 8000, 1);
+	std::fs::write(&input, &data).unwrap();
+
+	let listed = packets(&input);
+	assert_eq!(listed.len(), 3);
+	assert_eq!(listed[0].offset, 44);
+	assert!(listed.iter().all(|packet| packet.keyframe && packet.dts == packet.pts));
+	assert_offsets(&input, &listed, WavReader::new(Cursor::new(data)).unwrap());
+}
+
+#[test]
+fn test_packets_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let data = crate::common::create_test_y4m_data();
+	std::fs::write(&input, &data).unwrap();
+
+	let listed = packets(&input);
+	assert!(!listed.is_empty());
+	// past each FRAME line, not on it
+	assert_eq!(&data[listed[0].offset as usize - 6..listed[0].offset as usize], b"FRAME\n");
+	assert_offsets(&input, &listed, Y4mReader::new(Cursor::new(data)).unwrap());
+}
+
+#[test]
+fn test_packets_flac() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.flac");
+	let format = FlacFormat {
+		min_block_size: 1024,
+		max_block_size: 1024,
+		sample_rate: 8000,
+		channels: 1,
+		bits_per_sample: 16,
+		..FlacFormat::default()
+	};
+	let mut writer =
+		FlacWriter::new(FileAdapter::create(input.to_str().unwrap()).unwrap(), format).unwrap();
+	let mut encoder = FlacEncoder::new(8000, 1, 16, 1024);
+	let mut encoded = Vec::new();
+	for block in 0..4 {
+		let samples: Vec<u8> = (0..1024).flat_map(|i| ((i * block) as i16).to_le_bytes()).collect();
+		let frame = Frame::new_audio(FrameAudio::new(samples, 8000, 1), Timebase::new(1, 8000), 0);
+		let packet = encoder.encode(frame).unwrap().unwrap();
+		encoded.push(packet.data.clone());
+		writer.write_packet(packet).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	// each listed packet is exactly one encoded frame, its pts in samples
+	let listed = packets(&input);
+	let bytes = std::fs::read(&input).unwrap();
+	assert_eq!(listed.len(), encoded.len());
+	for (i, (info, frame)) in listed.iter().zip(&encoded).enumerate() {
+		let offset = info.offset as usize;
+		assert_eq!(&bytes[offset..offset + info.size], &frame[..]);
+		assert_eq!(info.pts, i as i64 * 1024);
+		assert_eq!(info.time, i as f64 * 0.128);
+	}
+}
+
+#[test]
+fn test_packets_mp4_and_avi() {
+	let dir = tempdir().unwrap();
+	let video = Mp4Track { track_id: 1, track_type: TrackType::Video, ..Mp4Track::default() };
+	let audio = Mp4Track { track_id: 2, track_type: TrackType::Audio, ..Mp4Track::default() };
+	let format = Mp4Format { tracks: vec![video, audio], ..Mp4Format::default() };
+
+	let mp4 = dir.path().join("out.mp4");
+	let mut writer =
+		Mp4Writer::new(FileAdapter::create(mp4.to_str().unwrap()).unwrap(), format).unwrap();
+	for i in 0..4u8 {
+		let packet = Packet::new(vec![i; 13 + i as usize], (i % 2) as usize, Timebase::new(1, 1000));
+		writer.write_packet(packet).unwrap();
+	}
+	writer.finalize().unwrap();
+	// listed from the sample tables, each pointing at the bytes written
+	let listed = packets(&mp4);
+	assert_eq!(listed.iter().map(|p| p.stream_index).collect::<Vec<_>>(), vec![0, 1, 0, 1]);
+	let bytes = std::fs::read(&mp4).unwrap();
+	for (i, info) in listed.iter().enumerate() {
+		let offset = info.offset as usize;
+		assert_eq!(info.size, 13 + i);
+		assert!(bytes[offset..offset + info.size].iter().all(|&byte| byte == i as u8));
+	}
+	// nothing wrote an stss, so every sample is a sync sample
+	assert!(listed.iter().all(|p| p.keyframe));
+
+	// odd chunks are padded, which the offsets step over
+	let avi = dir.path().join("out.avi");
+	let mut writer =
+		AviWriter::new(FileAdapter::create(avi.to_str().unwrap()).unwrap(), AviFormat::default())
+			.unwrap();
+	for i in 0..3u8 {
+		writer.write_packet(Packet::new(vec![i; 7], 0, Timebase::new(1, 30))).unwrap();
+	}
+	writer.finalize().unwrap();
+	let listed = packets(&avi);
+	assert_eq!(listed.len(), 3);
+	// the writer flags every idx1 entry a keyframe
+	assert!(listed.iter().all(|p| p.keyframe));
+	let reader =
+		ffmpreg::container::AviReader::new(FileAdapter::open(avi.to_str().unwrap()).unwrap());
+	assert_offsets(&avi, &listed, reader.unwrap());
+}
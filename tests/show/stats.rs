@@ -0,0 +1,72 @@
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{Show, ShowFormat, ShowOptions};
+use tempfile::tempdir;
+
+fn show(path: &str, format: ShowFormat, stream_filter: Option<usize>) -> Show {
+	let opts = ShowOptions { format, stats: true, stream_filter, ..ShowOptions::default() };
+	Show::new(path.to_string(), opts)
+}
+
+#[test]
+fn test_stats_square_wave() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	// a half scale square wave: rms and sample peak both at -6 dBFS
+	let samples: Vec<i16> =
+		(0..16000).map(|i| if i / 20 % 2 == 0 { 16384 } else { -16384 }).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 2)).unwrap();
+	let input = input.to_str().unwrap();
+
+	let stats = show(input, ShowFormat::Human, None).stats().unwrap();
+	assert_eq!(stats.len(), 1);
+	assert!((stats[0].rms - -6.02).abs() < 0.01, "{}", stats[0].rms);
+	assert!(stats[0].true_peak >= -6.03, "{}", stats[0].true_peak);
+	assert!(stats[0].integrated.is_finite());
+	// a second of audio is shorter than one short-term window
+	assert_eq!(stats[0].range, 0.0);
+
+	let text = show(input, ShowFormat::Human, None).to_stats().unwrap();
+	assert!(text.contains("stream 0: integrated "));
+	assert!(text.contains("rms -6.0 dBFS"));
+	let json = parse(show(input, ShowFormat::Json, None).to_stats().unwrap().trim()).unwrap();
+	let Some(JsonValue::Array(streams)) = json.get("streams") else { panic!("no streams") };
+	assert_eq!(streams[0].get("rms_dbfs"), Some(&JsonValue::Number(-6.02)));
+
+	assert!(show(input, ShowFormat::Human, Some(1)).stats().unwrap().is_empty());
+}
+
+#[test]
+fn test_stats_silence_and_video() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("silent.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&[0; 8000], 8000, 1)).unwrap();
+	let json = show(input.to_str().unwrap(), ShowFormat::Json, None).to_stats().unwrap();
+	let json = parse(json.trim()).unwrap();
+	let Some(JsonValue::Array(streams)) = json.get("streams") else { panic!("no streams") };
+	assert_eq!(streams[0].get("integrated_lufs"), Some(&JsonValue::Null));
+
+	let input = dir.path().join("in.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	assert!(show(input.to_str().unwrap(), ShowFormat::Human, None).stats().is_err());
+}
+
+#[test]
+fn test_stats_multi_frame_flac() {
+	let dir = tempdir().unwrap();
+	// four seconds of a tone that swells, in 1024 sample frames
+	let samples: Vec<i16> = (0..32000)
+		.map(|i| ((i as f64 * 0.07).sin() * (2000.0 + i as f64 / 2.0)) as i16)
+		.flat_map(|sample| [sample, sample])
+		.collect();
+	let wav = dir.path().join("in.wav");
+	std::fs::write(&wav, crate::common::create_test_wav_from_samples(&samples, 8000, 2)).unwrap();
+	let flac = dir.path().join("in.flac");
+	crate::container::flac::create_test_flac(&flac, &samples, 8000, 2, 1024);
+
+	let flac_stats = show(flac.to_str().unwrap(), ShowFormat::Human, None).stats().unwrap();
+	assert!(flac_stats[0].integrated.is_finite(), "{:?}", flac_stats);
+	// every frame measured: the same numbers as the wav of the same samples
+	let wav_stats = show(wav.to_str().unwrap(), ShowFormat::Human, None).stats().unwrap();
+	assert_eq!(flac_stats, wav_stats);
+	assert!(flac_stats[0].range > 0.0);
+}
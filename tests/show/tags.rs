@@ -0,0 +1,189 @@
+use ffmpreg::container::metadata::{Id3v2Tag, VorbisComment};
+use ffmpreg::container::{FlacFormat, FlacWriter, WavFormat, WavWriter};
+use ffmpreg::core::{Muxer, Packet, Timebase};
+use ffmpreg::io::FileAdapter;
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::show::{Show, ShowOptions};
+use ffmpreg::transcode::Transcoder;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn tags(path: &Path) -> BTreeMap<String, String> {
+	Show::new(path.to_str().unwrap().to_string(), ShowOptions::default()).analyze().unwrap().tags
+}
+
+fn chunk(fourcc: &[u8; 4], body: &[u8]) -> Vec<u8> {
+	let mut out = fourcc.to_vec();
+	out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+	out.extend_from_slice(body);
+	if body.len() % 2 == 1 {
+		out.push(0);
+	}
+	out
+}
+
+#[test]
+fn test_tags_wav_info_and_id3() {
+	let dir = tempdir().unwrap();
+
+	// a LIST INFO after the audio, as most taggers put it
+	let mut wav = crate::common::create_test_wav_data();
+	let mut info = b"INFO".to_vec();
+	info.extend(chunk(b"INAM", b"Song\0"));
+	info.extend(chunk(b"IART", b"Someone\0\0\0"));
+	info.extend(chunk(b"IENG", b"Engineer\0"));
+	wav.extend(chunk(b"LIST", &info));
+	let size = (wav.len() - 8) as u32;
+	wav[4..8].copy_from_slice(&size.to_le_bytes());
+	let input = dir.path().join("info.wav");
+	std::fs::write(&input, wav).unwrap();
+
+	let found = tags(&input);
+	assert_eq!(found.get("title").map(String::as_str), Some("Song"));
+	assert_eq!(found.get("artist").map(String::as_str), Some("Someone"));
+	assert_eq!(found.get("IENG").map(String::as_str), Some("Engineer"));
+
+	// the id3 chunk WavWriter appends
+	let input = dir.path().join("id3.wav");
+	let format = WavFormat { channels: 1, sample_rate: 8000, bit_depth: 16 };
+	let output = FileAdapter::create(input.to_str().unwrap()).unwrap();
+	let mut writer = WavWriter::new(output, format).unwrap();
+	let mut tag = Id3v2Tag::new();
+	tag.set_title("Tagged");
+	tag.set_track("3/9");
+	writer.set_id3(tag);
+	writer.write_packet(Packet::new(vec![0; 64], 0, Timebase::new(1, 8000))).unwrap();
+	writer.finalize().unwrap();
+
+	let found = tags(&input);
+	assert_eq!(found.get("title").map(String::as_str), Some("Tagged"));
+	assert_eq!(found.get("track").map(String::as_str), Some("3/9"));
+}
+
+#[test]
+fn test_tags_flac_json() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.flac");
+	let format =
+		FlacFormat { sample_rate: 8000, channels: 1, bits_per_sample: 16, ..FlacFormat::default() };
+	let mut comment = VorbisComment::new();
+	comment.add_comment("ARTIST", "Band \"Name\"");
+	comment.add_comment("album", "Record");
+	let output = FileAdapter::create(input.to_str().unwrap()).unwrap();
+	let mut writer = FlacWriter::new(output, format).unwrap().with_vorbis_comment(comment);
+	writer.write_packet(Packet::new(vec![0xff, 0xf8, 0, 0], 0, Timebase::new(1, 8000))).unwrap();
+	writer.finalize().unwrap();
+
+	let show = Show::new(input.to_str().unwrap().to_string(), ShowOptions::default());
+	let json = parse(&show.to_json().unwrap()).unwrap();
+	let Some(JsonValue::Object(tags)) = json.get("tags") else { panic!("no tags") };
+	assert_eq!(
+		tags,
+		&vec![
+			("album".to_string(), JsonValue::String("Record".to_string())),
+			("artist".to_string(), JsonValue::String("Band \"Name\"".to_string())),
+		]
+	);
+}
+
+#[test]
+fn test_tags_mp4_ilst() {
+	let boxed = |fourcc: &[u8], body: &[u8]| {
+		let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+		out.extend_from_slice(fourcc);
+		out.extend_from_slice(body);
+		out
+	};
+	let data = |kind: u32, value: &[u8]| {
+		let mut body = kind.to_be_bytes().to_vec();
+		body.extend_from_slice(&[0; 4]);
+		body.extend_from_slice(value);
+		boxed(b"data", &body)
+	};
+	let mut ilst = boxed(b"\xa9nam", &data(1, "Tïtle".as_bytes()));
+	ilst.extend(boxed(b"trkn", &data(0, &[0, 0, 0, 4, 0, 12, 0, 0])));
+	ilst.extend(boxed(b"xxxx", &data(1, b"ignored")));
+	let mut meta = vec![0; 4];
+	meta.extend(boxed(b"hdlr", &[0; 25]));
+	meta.extend(boxed(b"ilst", &ilst));
+	let moov = boxed(b"moov", &boxed(b"udta", &boxed(b"meta", &meta)));
+	let mut mp4 = boxed(b"ftyp", b"isom\0\0\x02\0isom");
+	mp4.extend(moov);
+
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.mp4");
+	std::fs::write(&input, mp4).unwrap();
+	let found = tags(&input);
+	assert_eq!(found.len(), 2);
+	assert_eq!(found.get("title").map(String::as_str), Some("Tïtle"));
+	assert_eq!(found.get("track").map(String::as_str), Some("4/12"));
+}
+
+fn id3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+	let mut out = id.to_vec();
+	out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+	out.extend_from_slice(&[0, 0]);
+	out.extend_from_slice(body);
+	out
+}
+
+#[test]
+fn test_tags_id3_text_frames() {
+	// an id3v2.3 tag from another tagger: utf-16 with a bom, latin-1, a
+	// utf-16be TXXX, a COMM, two values in one frame and a frame with no name
+	let mut frames = id3_frame(b"TPE1", b"\x01\xff\xfeB\0a\0n\0d\0");
+	frames.extend(id3_frame(b"TALB", b"\x00Caf\xe9"));
+	frames.extend(id3_frame(b"TCOM", b"\x03Writer"));
+	frames.extend(id3_frame(b"TCON", b"\x03Rock\0Pop"));
+	frames.extend(id3_frame(b"TXXX", b"\x02\0M\0O\0O\0D\0\0\0c\0a\0l\0m"));
+	frames.extend(id3_frame(b"COMM", b"\x03engshort\0Long comment"));
+	frames.extend(id3_frame(b"TMOO", b"\x03Bright"));
+	frames.extend(id3_frame(b"APIC", b"\x00image/png\0\x03\0"));
+	let mut data = b"ID3\x03\x00\x00".to_vec();
+	let size = frames.len() as u32;
+	data.extend([(size >> 21) as u8 & 0x7F, (size >> 14) as u8 & 0x7F, (size >> 7) as u8 & 0x7F]);
+	data.push(size as u8 & 0x7F);
+	data.extend(frames);
+
+	let tags = Id3v2Tag::parse(&data).unwrap().to_metadata().tags;
+	let tag = |key: &str| tags.get(key).map(String::as_str);
+	assert_eq!(tag("artist"), Some("Band"));
+	assert_eq!(tag("album"), Some("Café"));
+	assert_eq!(tag("composer"), Some("Writer"));
+	assert_eq!(tag("genre"), Some("Rock; Pop"));
+	assert_eq!(tag("MOOD"), Some("calm"));
+	assert_eq!(tag("comment"), Some("Long comment"));
+	assert_eq!(tag("TMOO"), Some("Bright"));
+	assert_eq!(tags.len(), 7);
+}
+
+#[test]
+fn test_tags_replaygain_txxx() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("tone.wav");
+	let samples: Vec<i16> = (0..8000).map(|n| ((n as f64 * 0.2).sin() * 8000.0) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+	let output = dir.path().join("tagged.wav");
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_replaygain(true)
+	.run()
+	.unwrap();
+
+	let found = tags(&output);
+	assert!(found.get("REPLAYGAIN_TRACK_GAIN").is_some_and(|gain| gain.ends_with(" dB")));
+	assert!(found.get("REPLAYGAIN_TRACK_PEAK").is_some_and(|peak| peak.parse::<f64>().is_ok()));
+}
+
+#[test]
+fn test_tags_mp3_id3() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("song.mp3");
+	std::fs::write(&input, crate::container::mp3::create_test_mp3_data(2)).unwrap();
+	assert_eq!(tags(&input).get("title").map(String::as_str), Some("Title"));
+}
@@ -0,0 +1,27 @@
+use ffmpreg::show::{Show, ShowFormat, ShowOptions};
+use tempfile::tempdir;
+
+#[test]
+fn test_xml_tree() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("a&b.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	let opts = ShowOptions { format: ShowFormat::Xml, frame_limit: 2, ..ShowOptions::default() };
+	let xml = Show::new(input.to_str().unwrap().to_string(), opts).to_xml().unwrap();
+
+	assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ffmpreg>\n"));
+	assert!(xml.ends_with("</ffmpreg>\n"));
+	assert!(xml.contains("a&amp;b.y4m"));
+	assert!(xml.contains("<stream index=\"0\" type=\"video\" codec=\"rawvideo (yuv420p)\""));
+	assert_eq!(xml.matches("<frame ").count(), 2);
+	// every element that opens closes
+	for tag in ["ffmpreg", "streams", "stream", "frames"] {
+		assert_eq!(
+			xml.matches(&format!("<{}>", tag)).count() + xml.matches(&format!("<{} ", tag)).count(),
+			xml.matches(&format!("</{}>", tag)).count(),
+			"{}",
+			tag
+		);
+	}
+}
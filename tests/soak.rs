@@ -0,0 +1,284 @@
+// long-running leak check, not part of the normal test run:
+//
+//   cargo test --release --features soak --test soak
+//
+// streams synthetic audio and video for FFMPREG_SOAK_HOURS (default 8) of
+// media time through representative chains into a sink that keeps nothing,
+// sampling live heap, allocation count and rss every ten minutes of media. any
+// buffer that grows with the input length shows up as live heap that keeps
+// climbing after the first checkpoint, and the run exits non-zero.
+
+use ffmpreg::codecs::{PcmDecoder, PcmEncoder, RawVideoDecoder, RawVideoEncoder};
+use ffmpreg::container::y4m::{Colorspace, Interlacing};
+use ffmpreg::container::{WavFormat, WavWriter, Y4mFormat, Y4mWriter};
+use ffmpreg::core::{Decoder, Encoder, Frame, Muxer, Packet, Timebase, Transform};
+use ffmpreg::io::{IoResult, MediaSeek, MediaWrite, SeekFrom};
+use ffmpreg::transform::{
+	Blur, Brightness, Crossfade, FrameRateConverter, Loudness, Scale, TransformChain, TransformSpec,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+		unsafe { System.dealloc(ptr, layout) }
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+		LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+		unsafe { System.realloc(ptr, layout, new_size) }
+	}
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const CHECKPOINT_SECONDS: u64 = 600;
+// live heap may wobble by a few buffers between checkpoints, not more
+const ALLOWED_GROWTH: usize = 1 << 20;
+
+// accepts and forgets everything, but tracks the position so muxers that seek
+// back to patch headers still work
+#[derive(Default)]
+struct NullSink {
+	pos: u64,
+	len: u64,
+}
+
+impl MediaWrite for NullSink {
+	fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+		self.pos += buf.len() as u64;
+		self.len = self.len.max(self.pos);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> IoResult<()> {
+		Ok(())
+	}
+}
+
+impl MediaSeek for NullSink {
+	fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+		self.pos = match pos {
+			SeekFrom::Start(offset) => offset,
+			SeekFrom::Current(offset) => self.pos.saturating_add_signed(offset),
+			SeekFrom::End(offset) => self.len.saturating_add_signed(offset),
+		};
+		Ok(self.pos)
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Checkpoint {
+	media_seconds: u64,
+	allocations: u64,
+	live_bytes: usize,
+	rss_bytes: Option<u64>,
+}
+
+impl Checkpoint {
+	fn take(media_seconds: u64) -> Self {
+		Self {
+			media_seconds,
+			allocations: ALLOCATIONS.load(Ordering::Relaxed),
+			live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+			rss_bytes: rss_bytes(),
+		}
+	}
+
+	fn print(&self, stream: &str) {
+		let rss = self.rss_bytes.map(|b| format!("{:.1} MiB", b as f64 / 1048576.0));
+		println!(
+			"{:<6} {:>6.2}h  allocations {:>12}  live {:>10.1} KiB  rss {}",
+			stream,
+			self.media_seconds as f64 / 3600.0,
+			self.allocations,
+			self.live_bytes as f64 / 1024.0,
+			rss.unwrap_or_else(|| "n/a".to_string())
+		);
+	}
+}
+
+// resident set size from /proc; other platforms report only the heap numbers
+fn rss_bytes() -> Option<u64> {
+	let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+	let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+	Some(pages * 4096)
+}
+
+// the first checkpoint is taken after every buffer has been sized, so growth
+// from there on is growth with input length
+fn check_growth(stream: &str, checkpoints: &[Checkpoint]) -> bool {
+	let (Some(first), Some(last)) = (checkpoints.first(), checkpoints.last()) else {
+		return true;
+	};
+	let growth = last.live_bytes.saturating_sub(first.live_bytes);
+	if growth > ALLOWED_GROWTH {
+		println!("{}: live heap grew by {} bytes over the run", stream, growth);
+		return false;
+	}
+	true
+}
+
+fn soak_audio(seconds: u64) -> IoResult<Vec<Checkpoint>> {
+	let rate = 48000u32;
+	let format = WavFormat { channels: 2, sample_rate: rate, bit_depth: 16 };
+	let timebase = Timebase::new(1, rate);
+	let mut decoder = PcmDecoder::new(format);
+	let mut encoder = PcmEncoder::new(Timebase::new(1, 44100));
+	let mut writer =
+		WavWriter::new(NullSink::default(), WavFormat { channels: 1, sample_rate: 44100, ..format })?;
+
+	let measured = Loudness { integrated: -20.0, true_peak: -6.0 };
+	let mut chain = TransformChain::from_specs([
+		TransformSpec::Highpass { cutoff: 80.0 },
+		TransformSpec::Eq3 { bass: 2.0, mid: 0.0, treble: -1.0 },
+		TransformSpec::Chorus { rate: 1.5, depth_ms: 3.0, mix: 0.5 },
+		TransformSpec::Resample { rate: 44100 },
+		TransformSpec::Mono,
+		TransformSpec::Loudnorm { target_lufs: -16.0, true_peak: -1.5, measured: Some(measured) },
+		TransformSpec::PeakLimiter { threshold_db: -1.0 },
+	])?;
+	let mut crossfade = Crossfade::new(50.0, 44100, 1);
+
+	let frames_per_packet = 1024u64;
+	let total_frames = seconds * rate as u64;
+	let mut checkpoints = Vec::new();
+	let mut next_checkpoint = CHECKPOINT_SECONDS;
+	let mut noise = 0x2545f491u32;
+
+	let mut pts = 0u64;
+	while pts < total_frames {
+		let mut data = Vec::with_capacity(frames_per_packet as usize * 4);
+		for n in pts..pts + frames_per_packet {
+			noise = noise.wrapping_mul(1664525).wrapping_add(1013904223);
+			let t = n as f64 / rate as f64;
+			let tone = (2.0 * std::f64::consts::PI * 220.0 * t).sin() * 8000.0;
+			let sample = (tone + (noise >> 20) as f64 - 2048.0) as i16;
+			data.extend_from_slice(&sample.to_le_bytes());
+			data.extend_from_slice(&(-sample).to_le_bytes());
+		}
+		let packet = Packet::new(data, 0, timebase).with_pts(pts as i64);
+		pts += frames_per_packet;
+
+		let Some(frame) = decoder.decode(packet)? else {
+			continue;
+		};
+		let frame = crossfade.apply(chain.apply(frame)?)?;
+
+		if pts / rate as u64 >= next_checkpoint {
+			// restart the crossfade so its buffer is reused rather than regrown
+			crossfade.feed_previous(&frame);
+			crossfade.start_crossfade();
+			checkpoints.push(Checkpoint::take(next_checkpoint));
+			checkpoints.last().unwrap().print("audio");
+			next_checkpoint += CHECKPOINT_SECONDS;
+		}
+
+		if let Some(packet) = encoder.encode(frame)? {
+			writer.write_packet(packet)?;
+		}
+	}
+
+	chain.finish()?;
+	writer.finalize()?;
+	Ok(checkpoints)
+}
+
+fn soak_video(seconds: u64) -> IoResult<Vec<Checkpoint>> {
+	let (width, height) = (64u32, 48u32);
+	let format = Y4mFormat {
+		width,
+		height,
+		framerate_num: 30,
+		framerate_den: 1,
+		colorspace: Some(Colorspace::C420),
+		interlacing: Interlacing::Progressive,
+		aspect_ratio: None,
+	};
+	let timebase = Timebase::new(1, 30);
+	let mut decoder = RawVideoDecoder::new(format.clone());
+	let mut encoder = RawVideoEncoder::new(Timebase::new(1, 25));
+	let out_format = Y4mFormat { width: 32, height: 24, framerate_num: 25, ..format.clone() };
+	let mut writer = Y4mWriter::new(NullSink::default(), out_format)?;
+
+	let brightness = Brightness::new(width, height, 1.1);
+	let blur = Blur::new(width, height, 1);
+	let scale = Scale::new(width, height, 32, 24);
+	let mut converter = FrameRateConverter::new(30, 1, 25, 1);
+
+	let frame_size = (width * height * 3 / 2) as usize;
+	let total_frames = seconds * 30;
+	let mut checkpoints = Vec::new();
+	let mut next_checkpoint = CHECKPOINT_SECONDS;
+
+	let mut write = |frame: Frame, writer: &mut Y4mWriter<NullSink>| -> IoResult<()> {
+		if let Some(packet) = encoder.encode(frame)? {
+			writer.write_packet(packet)?;
+		}
+		Ok(())
+	};
+
+	for n in 0..total_frames {
+		let data: Vec<u8> = (0..frame_size).map(|i| (i as u64 + n) as u8).collect();
+		let packet = Packet::new(data, 0, timebase).with_pts(n as i64);
+		let Some(frame) = decoder.decode(packet)? else {
+			continue;
+		};
+		let frame = brightness.apply_yuv420(&frame)?;
+		let frame = blur.apply_yuv420(&frame)?;
+		let frame = scale.apply_yuv420(&frame)?;
+		for frame in converter.process(frame)? {
+			write(frame, &mut writer)?;
+		}
+
+		if (n + 1) / 30 >= next_checkpoint {
+			checkpoints.push(Checkpoint::take(next_checkpoint));
+			checkpoints.last().unwrap().print("video");
+			next_checkpoint += CHECKPOINT_SECONDS;
+		}
+	}
+
+	for frame in converter.flush()? {
+		write(frame, &mut writer)?;
+	}
+	writer.finalize()?;
+	Ok(checkpoints)
+}
+
+fn main() {
+	let hours: f64 =
+		std::env::var("FFMPREG_SOAK_HOURS").ok().and_then(|h| h.parse().ok()).unwrap_or(8.0);
+	let seconds = (hours * 3600.0) as u64;
+	println!("soaking {:.2}h of audio and video", hours);
+
+	let mut ok = true;
+	for (stream, result) in [("audio", soak_audio(seconds)), ("video", soak_video(seconds))] {
+		match result {
+			Ok(checkpoints) => ok &= check_growth(stream, &checkpoints),
+			Err(e) => {
+				println!("{}: {}", stream, e);
+				ok = false;
+			}
+		}
+	}
+
+	if !ok {
+		std::process::exit(1);
+	}
+	println!("no growth detected");
+}
@@ -0,0 +1,75 @@
+use ffmpreg::container::SinkFormat;
+use ffmpreg::io::json;
+use ffmpreg::transcode::{Benchmark, BenchmarkReport, Transcoder};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::tempdir;
+
+fn transcoder(input: &str, output: Option<&str>, benchmark: &Arc<Benchmark>) -> Transcoder {
+	Transcoder::new(input.to_string(), output.map(String::from), false, vec![])
+		.with_benchmark(benchmark.clone())
+}
+
+#[test]
+fn test_benchmark_counts_a_decode() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	// one second of mono at 8 kHz
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&[0; 8000], 8000, 1)).unwrap();
+
+	let benchmark = Arc::new(Benchmark::start());
+	transcoder(input.to_str().unwrap(), None, &benchmark).with_sink(SinkFormat::Null).run().unwrap();
+	let report = benchmark.report();
+	assert!(report.packets > 0);
+	assert_eq!(report.frames, report.packets);
+	assert!((report.media - 1.0).abs() < 1e-6, "{}", report.media);
+	if cfg!(target_os = "linux") {
+		assert!(report.cpu.is_some());
+		assert!(report.peak_memory.is_some_and(|bytes| bytes > 0));
+	}
+}
+
+#[test]
+fn test_benchmark_counts_packets_of_a_copy() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.flac");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	let benchmark = Arc::new(Benchmark::start());
+	transcoder(input.to_str().unwrap(), output.to_str(), &benchmark).run().unwrap();
+	let report = benchmark.report();
+	assert!(report.packets > 0);
+	// untouched wav samples go to the flac encoder without being decoded
+	assert_eq!(report.frames, 0);
+}
+
+#[test]
+fn test_benchmark_report_rates() {
+	let report = BenchmarkReport {
+		wall: Duration::from_secs(2),
+		cpu: Some(Duration::from_millis(1500)),
+		media: 60.0,
+		packets: 400,
+		frames: 100,
+		peak_memory: Some(4 * 1024 * 1024),
+	};
+	assert_eq!(report.speed(), Some(30.0));
+	assert_eq!(report.packets_per_second(), 200.0);
+	assert_eq!(report.frames_per_second(), 50.0);
+
+	let text = report.to_text();
+	assert!(text.contains("speed 30.00x"), "{}", text);
+	assert!(text.contains("frames: 100 (50.0/s)"), "{}", text);
+	assert!(text.contains("peak memory: 4"), "{}", text);
+
+	let value = json::parse(&report.to_json()).unwrap();
+	assert_eq!(value.get("speed").and_then(|v| v.as_f64()), Some(30.0));
+	assert_eq!(value.get("cpu").and_then(|v| v.as_f64()), Some(1.5));
+	assert_eq!(value.get("peak_memory").and_then(|v| v.as_f64()), Some(4194304.0));
+
+	let empty = BenchmarkReport { wall: Duration::ZERO, cpu: None, peak_memory: None, ..report };
+	assert_eq!(empty.speed(), None);
+	assert_eq!(empty.frames_per_second(), 0.0);
+	assert!(json::parse(&empty.to_json()).unwrap().get("cpu").unwrap().is_null());
+}
@@ -0,0 +1,118 @@
+use crate::common::create_test_wav_from_samples;
+use ffmpreg::codecs::CodecId;
+use ffmpreg::container::{MediaType, WavFormat, WavReader, WavWriter};
+use ffmpreg::core::{Demuxer, Muxer, Packet, Timebase};
+use ffmpreg::io::{Cursor, FileAdapter};
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::{Capability, can_convert};
+use tempfile::tempdir;
+
+#[test]
+fn test_decoded_conversions_are_supported() {
+	assert_eq!(can_convert(MediaType::Wav, MediaType::Wav, None), Capability::Supported);
+	assert_eq!(
+		can_convert(MediaType::Wav, MediaType::Wav, Some(CodecId::PcmS24Le)),
+		Capability::Supported
+	);
+	assert_eq!(
+		can_convert(MediaType::Y4m, MediaType::Y4m, Some(CodecId::RawVideo)),
+		Capability::Supported
+	);
+}
+
+#[test]
+fn test_copies_are_passthrough_only() {
+	assert_eq!(can_convert(MediaType::Mp4, MediaType::Mp4, None), Capability::PassthroughOnly);
+	assert_eq!(
+		can_convert(MediaType::Wav, MediaType::Flac, Some(CodecId::Flac)),
+		Capability::PassthroughOnly
+	);
+	assert!(can_convert(MediaType::Avi, MediaType::Avi, None).is_possible());
+	assert_eq!(can_convert(MediaType::Mp3, MediaType::Mp3, None), Capability::PassthroughOnly);
+	assert_eq!(can_convert(MediaType::Ogg, MediaType::Ogg, None), Capability::PassthroughOnly);
+}
+
+#[test]
+fn test_impossible_conversions() {
+	assert_eq!(can_convert(MediaType::Wav, MediaType::Y4m, None), Capability::Unsupported);
+	assert_eq!(
+		can_convert(MediaType::Wav, MediaType::Wav, Some(CodecId::PcmS16Be)),
+		Capability::Unsupported
+	);
+	assert_eq!(
+		can_convert(MediaType::Mp4, MediaType::Mp4, Some(CodecId::Adpcm)),
+		Capability::Unsupported
+	);
+	assert!(!can_convert(MediaType::Unknown, MediaType::Wav, None).is_possible());
+}
+
+#[test]
+fn test_transcoder_writes_requested_codec() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, create_test_wav_from_samples(&[0, 256, -256, 1024], 8000, 1)).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_codec(CodecId::PcmS24Le)
+	.run()
+	.unwrap();
+
+	let reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!(reader.format().bit_depth, 24);
+
+	let rejected = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_codec(CodecId::Adpcm)
+	.run();
+	assert!(rejected.is_err());
+}
+
+#[test]
+fn test_transcoder_keeps_24_bit_samples() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	// low bytes a 16-bit detour would drop
+	let data: Vec<u8> = (0..4800i32)
+		.map(|i| (i * 7919 % 0x1000000) - 0x800000)
+		.flat_map(|sample| sample.to_le_bytes()[..3].to_vec())
+		.collect();
+	let format = WavFormat { channels: 2, sample_rate: 48000, bit_depth: 24 };
+	let mut writer =
+		WavWriter::new(FileAdapter::create(input.to_str().unwrap()).unwrap(), format).unwrap();
+	writer.write_packet(Packet::new(data.clone(), 0, Timebase::new(1, 48000))).unwrap();
+	writer.finalize().unwrap();
+
+	let transcode = |codec: Option<CodecId>| {
+		let transcoder = Transcoder::new(
+			input.to_str().unwrap().to_string(),
+			Some(output.to_str().unwrap().to_string()),
+			false,
+			vec![],
+		);
+		match codec {
+			Some(codec) => transcoder.with_codec(codec).run(),
+			None => transcoder.run(),
+		}
+	};
+	let error = transcode(Some(CodecId::PcmS24Be)).unwrap_err();
+	assert!(error.to_string().contains("big-endian"), "{}", error);
+	transcode(None).unwrap();
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!(reader.format().bit_depth, 24);
+	let mut written = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		written.extend(packet.data);
+	}
+	assert_eq!(written, data);
+}
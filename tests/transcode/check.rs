@@ -0,0 +1,121 @@
+use ffmpreg::codecs::FlacEncoder;
+use ffmpreg::container::hash::Md5;
+use ffmpreg::container::{FlacFormat, FlacWriter};
+use ffmpreg::core::{Encoder, Frame, FrameAudio, Muxer, Timebase};
+use ffmpreg::io::FileAdapter;
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::transcode::{Check, CheckReport};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn check(path: &Path) -> CheckReport {
+	Check::new(path.to_str().unwrap().to_string()).verify().unwrap()
+}
+
+// three 1024 sample frames; returns where each frame starts
+fn write_flac(path: &Path, md5: Option<[u8; 16]>) -> Vec<usize> {
+	let mut format = FlacFormat {
+		min_block_size: 1024,
+		max_block_size: 1024,
+		sample_rate: 8000,
+		channels: 1,
+		bits_per_sample: 16,
+		total_samples: 3072,
+		..FlacFormat::default()
+	};
+	if let Some(md5) = md5 {
+		format.md5_signature = md5;
+	}
+	let mut writer =
+		FlacWriter::new(FileAdapter::create(path.to_str().unwrap()).unwrap(), format).unwrap();
+	let mut encoder = FlacEncoder::new(8000, 1, 16, 1024);
+	let mut sizes = Vec::new();
+	for block in 0..3 {
+		let frame =
+			Frame::new_audio(FrameAudio::new(samples(block), 8000, 1), Timebase::new(1, 8000), 0);
+		let packet = encoder.encode(frame).unwrap().unwrap();
+		sizes.push(packet.data.len());
+		writer.write_packet(packet).unwrap();
+	}
+	writer.finalize().unwrap();
+
+	let len = std::fs::metadata(path).unwrap().len() as usize;
+	let mut start = len - sizes.iter().sum::<usize>();
+	sizes
+		.iter()
+		.map(|size| {
+			start += size;
+			start - size
+		})
+		.collect()
+}
+
+fn samples(block: i32) -> Vec<u8> {
+	(0..1024)
+		.flat_map(|i| ((((i + block * 1024) as f64 * 0.05).sin() * 8000.0) as i16).to_le_bytes())
+		.collect()
+}
+
+#[test]
+fn test_check_flac_crc_and_md5() {
+	let dir = tempdir().unwrap();
+	let flac = dir.path().join("good.flac");
+	write_flac(&flac, None);
+	let report = check(&flac);
+	assert!(report.is_ok(), "{:?}", report);
+	assert_eq!((report.frames, report.damaged, report.md5), (3, 0, None));
+
+	// STREAMINFO's md5 is of the decoded samples in order
+	let mut md5 = Md5::new();
+	(0..3).for_each(|block| md5.update(&samples(block)));
+	let digest = md5.finish();
+	write_flac(&flac, Some(digest));
+	assert_eq!(check(&flac).md5, Some(true));
+	let mut wrong = digest;
+	wrong[0] ^= 1;
+	write_flac(&flac, Some(wrong));
+	let report = check(&flac);
+	assert_eq!(report.md5, Some(false));
+	assert!(!report.is_ok());
+}
+
+#[test]
+fn test_check_flac_damaged_frame() {
+	let dir = tempdir().unwrap();
+	let flac = dir.path().join("damaged.flac");
+	let starts = write_flac(&flac, None);
+	let mut data = std::fs::read(&flac).unwrap();
+	data[starts[1] + 20] ^= 0x55;
+	std::fs::write(&flac, &data).unwrap();
+
+	// the frames either side still check out
+	let report = check(&flac);
+	assert_eq!((report.frames, report.damaged), (3, 1));
+	assert_eq!(report.first_error().unwrap().offset, starts[1] as u64);
+	assert!(report.to_text("damaged.flac").starts_with("damaged.flac: 1 of 3 frames damaged\n"));
+	let json = parse(&report.to_json("damaged.flac")).unwrap();
+	assert_eq!(json.get("ok"), Some(&JsonValue::Bool(false)));
+	assert!(Check::new(flac.to_str().unwrap().to_string()).run().is_err());
+}
+
+#[test]
+fn test_check_truncated_wav_and_y4m() {
+	let dir = tempdir().unwrap();
+	let wav = dir.path().join("cut.wav");
+	let data = crate::common::create_test_wav_from_samples(&[100; 4000], 8000, 1);
+	std::fs::write(&wav, &data[..data.len() - 1000]).unwrap();
+	let report = check(&wav);
+	assert!(!report.is_ok());
+	assert_eq!(report.first_error().unwrap().message, "data chunk ends 500 frames short");
+
+	let y4m = dir.path().join("cut.y4m");
+	let data = crate::common::create_test_y4m_data();
+	std::fs::write(&y4m, &data).unwrap();
+	assert!(check(&y4m).is_ok());
+	std::fs::write(&y4m, &data[..data.len() - 10]).unwrap();
+	let report = check(&y4m);
+	assert_eq!((report.frames, report.damaged), (2, 1));
+	// where the last FRAME line starts
+	let frame = data.len() - 96 - 6;
+	assert_eq!(report.first_error().unwrap().offset, frame as u64);
+}
@@ -0,0 +1,57 @@
+use ffmpreg::core::{FrameVideo, VideoFormat};
+use ffmpreg::transcode::compare::{psnr, score_frames, ssim};
+use ffmpreg::transcode::{Compare, Transcoder};
+use tempfile::tempdir;
+
+#[test]
+fn test_psnr_and_ssim_values() {
+	let a = vec![100u8; 64];
+	assert!(psnr(&a, &a).is_infinite());
+	assert!((ssim(&a, &a, 8, 8) - 1.0).abs() < 1e-9);
+
+	// every sample off by 5: mse 25
+	let b = vec![105u8; 64];
+	assert!((psnr(&a, &b) - 34.15).abs() < 0.01);
+
+	let noisy: Vec<u8> = (0..64).map(|i| if i % 2 == 0 { 60 } else { 140 }).collect();
+	assert!(ssim(&a, &noisy, 8, 8) < 0.1);
+	assert!(ssim(&a, &b, 8, 8) > ssim(&a, &noisy, 8, 8));
+
+	let small = FrameVideo::new(vec![0; 6], 2, 2, VideoFormat::YUV420);
+	let large = FrameVideo::new(vec![0; 24], 4, 4, VideoFormat::YUV420);
+	assert!(score_frames(&small, &large).is_err());
+}
+
+#[test]
+fn test_compare_y4m_against_reference() {
+	let dir = tempdir().unwrap();
+	let reference = dir.path().join("ref.y4m");
+	let brighter = dir.path().join("brighter.y4m");
+	std::fs::write(&reference, crate::common::create_test_y4m_data()).unwrap();
+	Transcoder::new(
+		reference.to_str().unwrap().to_string(),
+		Some(brighter.to_str().unwrap().to_string()),
+		false,
+		vec!["lut=lift=0.04".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let path = |p: &std::path::Path| p.to_str().unwrap().to_string();
+	let same = Compare::new(path(&reference), path(&reference)).measure().unwrap();
+	assert_eq!(same.frames.len(), 3);
+	assert!(same.average_psnr().is_infinite());
+	assert!(same.to_json().contains("\"average\":{\"psnr\":null,\"ssim\":1.000000}"));
+
+	let graded = Compare::new(path(&brighter), path(&reference)).measure().unwrap();
+	assert_eq!((graded.frames.len(), graded.unmatched), (3, 0));
+	assert!(
+		graded.average_psnr() > 20.0 && graded.average_psnr() < 40.0,
+		"{}",
+		graded.average_psnr()
+	);
+	assert!(graded.average_ssim() < 1.0);
+	assert!(graded.to_text().contains("average: psnr"));
+
+	assert!(Compare::new(path(&reference), "ref.wav".to_string()).measure().is_err());
+}
@@ -0,0 +1,108 @@
+use ffmpreg::codecs::PcmDecoder;
+use ffmpreg::container::WavReader;
+use ffmpreg::core::{Decoder, Demuxer};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Concat;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write_wav(path: &Path, samples: &[i16], rate: u32, channels: u16) {
+	std::fs::write(path, crate::common::create_test_wav_from_samples(samples, rate, channels))
+		.unwrap();
+}
+
+fn read_wav(path: &Path) -> (u32, u8, Vec<i16>) {
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(path).unwrap())).unwrap();
+	let format = reader.format();
+	let mut decoder = PcmDecoder::new(format);
+	let mut samples = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap() {
+			let data = &frame.audio().unwrap().data;
+			samples.extend(data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])));
+		}
+	}
+	(format.sample_rate, format.channels, samples)
+}
+
+fn concat(dir: &Path, crossfade: f64) -> Concat {
+	let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+	Concat::new(path("a.wav"), path("b.wav"), path("out.wav")).with_crossfade(crossfade)
+}
+
+#[test]
+fn test_concat_crossfades_tail_into_head() {
+	let dir = tempdir().unwrap();
+	write_wav(&dir.path().join("a.wav"), &vec![10000; 48000], 48000, 1);
+	write_wav(&dir.path().join("b.wav"), &vec![20000; 48000], 48000, 1);
+	concat(dir.path(), 0.1).run().unwrap();
+
+	let (rate, channels, samples) = read_wav(&dir.path().join("out.wav"));
+	assert_eq!((rate, channels), (48000, 1));
+	// the 100ms overlap is shared, not repeated
+	assert_eq!(samples.len(), 96000 - 4800);
+	assert_eq!(samples[0], 10000);
+	assert_eq!(samples[43199], 10000);
+	assert!((samples[43200 + 2400] - 15000).abs() < 10, "{}", samples[43200 + 2400]);
+	assert_eq!(*samples.last().unwrap(), 20000);
+}
+
+#[test]
+fn test_concat_without_crossfade_appends() {
+	let dir = tempdir().unwrap();
+	write_wav(&dir.path().join("a.wav"), &[1, 2, 3], 8000, 1);
+	write_wav(&dir.path().join("b.wav"), &[4, 5], 8000, 1);
+	concat(dir.path(), 0.0).run().unwrap();
+
+	assert_eq!(read_wav(&dir.path().join("out.wav")).2, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_concat_joins_more_than_two_inputs() {
+	let dir = tempdir().unwrap();
+	write_wav(&dir.path().join("a.wav"), &[1, 2, 3], 8000, 1);
+	write_wav(&dir.path().join("b.wav"), &[4, 5], 8000, 1);
+	write_wav(&dir.path().join("c.wav"), &[6], 8000, 1);
+	let third = dir.path().join("c.wav").to_str().unwrap().to_string();
+	concat(dir.path(), 0.0).with_input(third).run().unwrap();
+
+	assert_eq!(read_wav(&dir.path().join("out.wav")).2, vec![1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_concat_converts_second_input_format() {
+	let dir = tempdir().unwrap();
+	write_wav(&dir.path().join("a.wav"), &vec![8000; 48000], 48000, 1);
+	let stereo: Vec<i16> = (0..44100).flat_map(|_| [4000, 8000]).collect();
+	write_wav(&dir.path().join("b.wav"), &stereo, 44100, 2);
+	concat(dir.path(), 0.05).run().unwrap();
+
+	let (rate, channels, samples) = read_wav(&dir.path().join("out.wav"));
+	assert_eq!((rate, channels), (48000, 1));
+	let expected = 48000 + 48000 - 2400;
+	assert!((samples.len() as i64 - expected).abs() < 64, "{}", samples.len());
+	// stereo folded to mono averages the channels
+	assert!((samples[samples.len() - 100] - 6000).abs() < 50);
+}
+
+#[test]
+fn test_concat_plays_out_tail_when_second_is_short() {
+	let dir = tempdir().unwrap();
+	write_wav(&dir.path().join("a.wav"), &vec![10000; 4800], 48000, 1);
+	write_wav(&dir.path().join("b.wav"), &vec![0; 480], 48000, 1);
+	concat(dir.path(), 0.05).run().unwrap();
+
+	let samples = read_wav(&dir.path().join("out.wav")).2;
+	assert_eq!(samples.len(), 4800);
+	assert!(samples[2400] > 9990);
+	assert!(samples.last().unwrap().abs() < 10);
+}
+
+#[test]
+fn test_concat_rejects_non_wav_output() {
+	let dir = tempdir().unwrap();
+	write_wav(&dir.path().join("a.wav"), &[0; 10], 8000, 1);
+	write_wav(&dir.path().join("b.wav"), &[0; 10], 8000, 1);
+	let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+	assert!(Concat::new(path("a.wav"), path("b.wav"), path("out.y4m")).run().is_err());
+}
@@ -0,0 +1,54 @@
+use ffmpreg::container::metadata::CoverArt;
+use ffmpreg::transcode::Transcoder;
+use tempfile::tempdir;
+
+fn cover() -> CoverArt {
+	let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+	png.extend_from_slice(&13u32.to_be_bytes());
+	png.extend_from_slice(b"IHDR");
+	png.extend_from_slice(&[0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+	CoverArt::from_bytes(png).unwrap()
+}
+
+fn transcoder(dir: &std::path::Path, output: &str) -> Transcoder {
+	let input = dir.join("in.wav");
+	let samples: Vec<i16> = (0..4800).map(|n| ((n % 100) * 50) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 48000, 1)).unwrap();
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(dir.join(output).to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_cover(cover())
+}
+
+#[test]
+fn test_cover_in_wav_id3_with_replaygain() {
+	let dir = tempdir().unwrap();
+	transcoder(dir.path(), "out.wav").with_replaygain(true).run().unwrap();
+
+	let bytes = std::fs::read(dir.path().join("out.wav")).unwrap();
+	let chunk = bytes.windows(4).position(|w| w == b"id3 ").expect("no id3 chunk");
+	let tag = &bytes[chunk..];
+	assert!(tag.windows(4).any(|w| w == b"APIC"));
+	assert!(tag.windows(9).any(|w| w == b"image/png"));
+	assert!(tag.windows(21).any(|w| w == b"REPLAYGAIN_TRACK_GAIN"));
+}
+
+#[test]
+fn test_cover_in_flac_picture() {
+	let dir = tempdir().unwrap();
+	transcoder(dir.path(), "out.flac").run().unwrap();
+
+	let bytes = std::fs::read(dir.path().join("out.flac")).unwrap();
+	assert_eq!(&bytes[..4], b"fLaC");
+	assert_eq!(bytes[42], 0x80 | 0x06);
+}
+
+#[test]
+fn test_cover_rejects_other_outputs() {
+	let dir = tempdir().unwrap();
+	assert!(transcoder(dir.path(), "out.y4m").run().is_err());
+	assert!(!dir.path().join("out.y4m").exists());
+}
@@ -0,0 +1,137 @@
+use ffmpreg::io::json::{JsonValue, parse};
+use ffmpreg::transcode::{Diff, DiffReport};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn diff(a: &Path, b: &Path) -> DiffReport {
+	Diff::new(a.to_str().unwrap().to_string(), b.to_str().unwrap().to_string()).compare().unwrap()
+}
+
+fn samples() -> Vec<i16> {
+	(0..4096).map(|i| ((i as f64 * 0.05).sin() * 8000.0) as i16).collect()
+}
+
+#[test]
+fn test_diff_wav_against_flac() {
+	let dir = tempdir().unwrap();
+	let wav = dir.path().join("in.wav");
+	std::fs::write(&wav, crate::common::create_test_wav_from_samples(&samples(), 8000, 2)).unwrap();
+
+	let flac = dir.path().join("in.flac");
+	crate::container::flac::create_test_flac(&flac, &samples(), 8000, 2, 2048);
+
+	// one flac frame against the wav's 1024 sample packets, the same samples
+	let report = diff(&wav, &flac);
+	assert!(report.is_identical(), "{:?}", report);
+	assert_eq!(report.lengths, (2048, 2048));
+	assert!(report.to_text("in.wav", "in.flac").starts_with("in.wav and in.flac are identical"));
+}
+
+#[test]
+fn test_diff_wav_against_multi_frame_flac() {
+	let dir = tempdir().unwrap();
+	let samples: Vec<i16> = (0..16384).map(|i| ((i * 7919) % 20000 - 10000) as i16).collect();
+	let wav = dir.path().join("in.wav");
+	std::fs::write(&wav, crate::common::create_test_wav_from_samples(&samples, 8000, 2)).unwrap();
+	let flac = dir.path().join("in.flac");
+	crate::container::flac::create_test_flac(&flac, &samples, 8000, 2, 1024);
+
+	// eight frames, each its own packet rather than cut at a read size
+	let report = diff(&wav, &flac);
+	assert!(report.is_identical(), "{:?}", report);
+	assert_eq!(report.lengths, (8192, 8192));
+}
+
+#[test]
+fn test_diff_first_difference_and_deviation() {
+	let dir = tempdir().unwrap();
+	let a = dir.path().join("a.wav");
+	let b = dir.path().join("b.wav");
+	std::fs::write(&a, crate::common::create_test_wav_from_samples(&samples(), 8000, 2)).unwrap();
+	let mut changed = samples();
+	// the second channel of sample 1000, then a bigger change later
+	changed[2001] += 4;
+	changed[3000] -= 16;
+	std::fs::write(&b, crate::common::create_test_wav_from_samples(&changed, 8000, 2)).unwrap();
+
+	let report = diff(&a, &b);
+	assert!(!report.is_identical());
+	assert_eq!((report.first_difference, report.differences), (Some(1000), 2));
+	assert_eq!(report.max_deviation, 16.0 / 32768.0);
+	let text = report.to_text("a.wav", "b.wav");
+	assert!(text.contains("first difference: sample 1000 (0.125000 s)"), "{}", text);
+	let json = parse(&report.to_json("a.wav", "b.wav")).unwrap();
+	assert_eq!(json.get("identical"), Some(&JsonValue::Bool(false)));
+	assert_eq!(json.get("first_difference"), Some(&JsonValue::Number(1000.0)));
+	let paths = (a.to_str().unwrap().to_string(), b.to_str().unwrap().to_string());
+	assert!(Diff::new(paths.0.clone(), paths.1.clone()).run().is_err());
+	assert!(Diff::new(paths.0.clone(), paths.0).run().is_ok());
+
+	// a shorter copy matches as far as it goes
+	std::fs::write(&b, crate::common::create_test_wav_from_samples(&samples()[..3000], 8000, 2))
+		.unwrap();
+	let report = diff(&a, &b);
+	assert_eq!((report.first_difference, report.lengths), (None, (2048, 1500)));
+	assert!(!report.is_identical());
+	assert!(report.to_text("a.wav", "b.wav").contains("the first 1500 samples match"));
+}
+
+#[test]
+fn test_diff_lengths_and_contents() {
+	let dir = tempdir().unwrap();
+	let a = dir.path().join("a.wav");
+	let b = dir.path().join("b.wav");
+	std::fs::write(&a, crate::common::create_test_wav_from_samples(&samples(), 8000, 2)).unwrap();
+	// shorter, and changed within the part both have
+	let mut changed = samples()[..3000].to_vec();
+	changed[500] += 8;
+	changed[2999] -= 32;
+	std::fs::write(&b, crate::common::create_test_wav_from_samples(&changed, 8000, 2)).unwrap();
+
+	let report = diff(&a, &b);
+	assert_eq!(report.lengths, (2048, 1500));
+	assert_eq!((report.first_difference, report.differences), (Some(250), 2));
+	assert_eq!(report.max_deviation, 32.0 / 32768.0);
+
+	let text = report.to_text("a.wav", "b.wav");
+	assert!(text.contains("first difference: sample 250 (0.031250 s)"), "{}", text);
+	assert!(text.contains("2 samples differ, max deviation 0.000977"), "{}", text);
+	assert!(text.contains("lengths: 2048 vs 1500 samples"), "{}", text);
+	assert!(text.contains("compared over the first 1500 samples"), "{}", text);
+
+	let json = parse(&report.to_json("a.wav", "b.wav")).unwrap();
+	assert_eq!(json.get("identical"), Some(&JsonValue::Bool(false)));
+	assert_eq!(json.get("first_difference"), Some(&JsonValue::Number(250.0)));
+	assert_eq!(json.get("differences"), Some(&JsonValue::Number(2.0)));
+	assert_eq!(json.get("max_deviation"), Some(&JsonValue::Number(32.0 / 32768.0)));
+	assert_eq!(json.get("compared"), Some(&JsonValue::Number(1500.0)));
+	assert_eq!(
+		json.get("lengths"),
+		Some(&JsonValue::Array(vec![JsonValue::Number(2048.0), JsonValue::Number(1500.0)]))
+	);
+}
+
+#[test]
+fn test_diff_y4m_frames() {
+	let dir = tempdir().unwrap();
+	let a = dir.path().join("a.y4m");
+	let b = dir.path().join("b.y4m");
+	let data = crate::common::create_test_y4m_data();
+	std::fs::write(&a, &data).unwrap();
+	let mut changed = data.clone();
+	// a luma byte of the last frame
+	let last = changed.len() - 96 + 10;
+	changed[last] = changed[last].wrapping_add(7);
+	std::fs::write(&b, &changed).unwrap();
+
+	let report = diff(&a, &b);
+	assert_eq!((report.first_difference, report.differences, report.lengths), (Some(2), 1, (3, 3)));
+	assert_eq!(report.max_deviation, 7.0);
+
+	// audio against video is not compared at all
+	let wav = dir.path().join("in.wav");
+	std::fs::write(&wav, crate::common::create_test_wav_from_samples(&samples(), 8000, 1)).unwrap();
+	let report = diff(&wav, &a);
+	assert_ne!(report.formats.0, report.formats.1);
+	assert!(report.to_text("in.wav", "a.y4m").contains("formats: audio 8000 Hz 1 channels vs video"));
+}
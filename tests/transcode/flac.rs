@@ -0,0 +1,50 @@
+use ffmpreg::transcode::{Check, Diff, Transcoder};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn path(path: &Path) -> String {
+	path.to_str().unwrap().to_string()
+}
+
+fn transcode(input: &Path, output: &Path, transforms: &[&str]) {
+	let transforms = transforms.iter().map(|t| t.to_string()).collect();
+	Transcoder::new(path(input), Some(path(output)), false, transforms).run().unwrap();
+}
+
+// one wav packet of a stereo sawtooth, so the flac is a single frame
+fn write_input(dir: &Path) -> std::path::PathBuf {
+	let input = dir.join("in.wav");
+	let samples: Vec<i16> = (0..2048).map(|i| ((i * 37) % 20000 - 10000) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 2)).unwrap();
+	input
+}
+
+#[test]
+fn test_wav_to_flac_encodes_frames() {
+	let dir = tempdir().unwrap();
+	let input = write_input(dir.path());
+	let flac = dir.path().join("out.flac");
+	transcode(&input, &flac, &[]);
+
+	let report = Check::new(path(&flac)).verify().unwrap();
+	assert!(report.is_ok(), "{:?}", report.first_error());
+	assert_eq!(report.frames, 1);
+	// lossless: it decodes to the wav's samples and is smaller than them
+	let diff = Diff::new(path(&input), path(&flac)).compare().unwrap();
+	assert!(diff.is_identical(), "{:?}", diff);
+	assert!(std::fs::metadata(&flac).unwrap().len() < std::fs::metadata(&input).unwrap().len());
+}
+
+#[test]
+fn test_wav_to_flac_applies_transforms() {
+	let dir = tempdir().unwrap();
+	let input = write_input(dir.path());
+	let flac = dir.path().join("out.flac");
+	let wav = dir.path().join("out.wav");
+	transcode(&input, &flac, &["volume=0.5", "mono"]);
+	transcode(&input, &wav, &["volume=0.5", "mono"]);
+
+	let diff = Diff::new(path(&wav), path(&flac)).compare().unwrap();
+	assert!(diff.is_identical(), "{:?}", diff);
+	assert_eq!(diff.lengths, (1024, 1024));
+}
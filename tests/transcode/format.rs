@@ -0,0 +1,103 @@
+use ffmpreg::container::{FlacReader, MediaType, WavReader};
+use ffmpreg::core::Demuxer;
+use ffmpreg::io::Cursor;
+use ffmpreg::show::{Show, ShowOptions};
+use ffmpreg::transcode::Transcoder;
+use tempfile::tempdir;
+
+#[test]
+fn test_format_overrides_missing_extension() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("upload");
+	let output = dir.path().join("upload.tmp");
+	let samples: Vec<i16> = (0..100).map(|i| i * 10).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+
+	let transcoder = |format: Option<MediaType>| {
+		let transcoder = Transcoder::new(
+			input.to_str().unwrap().to_string(),
+			Some(output.to_str().unwrap().to_string()),
+			false,
+			vec![],
+		);
+		match format {
+			Some(format) => transcoder.with_format(format),
+			None => transcoder,
+		}
+	};
+
+	// neither path says what it holds
+	assert!(transcoder(None).run().is_err());
+	transcoder(Some(MediaType::Wav)).run().unwrap();
+
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut bytes = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		bytes += packet.size();
+	}
+	assert_eq!(bytes, samples.len() * 2);
+}
+
+#[test]
+fn test_format_overrides_wrong_extension() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("frames.bin");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	let show = |format: Option<MediaType>| {
+		let show = Show::new(input.to_str().unwrap().to_string(), ShowOptions::default());
+		match format {
+			Some(format) => show.with_format(format),
+			None => show,
+		}
+	};
+	assert!(show(None).analyze().is_err());
+	assert!(show(Some(MediaType::Y4m)).analyze().is_ok());
+}
+
+#[test]
+fn test_format_names_the_output_not_a_known_input() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("a.wav");
+	let samples: Vec<i16> = (0..4096).map(|i| (i % 200) * 50).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+	let input = input.to_str().unwrap();
+
+	// the input's extension holds; -f only says what the output is
+	let output = dir.path().join("out.bin");
+	Transcoder::new(input.to_string(), Some(output.to_str().unwrap().to_string()), false, vec![])
+		.with_format(MediaType::Flac)
+		.run()
+		.unwrap();
+	assert!(FlacReader::new(Cursor::new(std::fs::read(&output).unwrap())).is_ok());
+
+	// --input-format overrides an extension that is wrong
+	let output = dir.path().join("out.wav");
+	let transcoder = |input_format: MediaType| {
+		Transcoder::new(input.to_string(), Some(output.to_str().unwrap().to_string()), false, vec![])
+			.with_input_format(input_format)
+	};
+	assert!(transcoder(MediaType::Flac).run().is_err());
+	transcoder(MediaType::Wav).run().unwrap();
+}
+
+#[test]
+fn test_format_writes_stdout_from_a_file() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("a.wav");
+	let samples: Vec<i16> = (0..4096).map(|i| (i % 200) * 50).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+
+	let output = std::process::Command::new(env!("CARGO_BIN_EXE_ffmpreg"))
+		.args(["-i", input.to_str().unwrap(), "-f", "flac", "-o", "-"])
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	let mut reader = FlacReader::new(Cursor::new(output.stdout)).unwrap();
+	let mut packets = 0;
+	while reader.read_packet().unwrap().is_some() {
+		packets += 1;
+	}
+	assert!(packets > 0);
+}
@@ -0,0 +1,113 @@
+use ffmpreg::container::{Looped, WavReader, Y4mReader};
+use ffmpreg::core::Demuxer;
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn transcoder(input: &Path, output: &Path) -> Transcoder {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+}
+
+fn read_samples(path: &Path) -> Vec<i16> {
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(path).unwrap())).unwrap();
+	let mut samples = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		samples.extend(packet.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])));
+	}
+	samples
+}
+
+#[test]
+fn test_looped_demuxer_continues_pts() {
+	let data = crate::common::create_test_wav_from_samples(&(0..5000).collect::<Vec<i16>>(), 8000, 1);
+	let reader = WavReader::new(Cursor::new(data.clone())).unwrap();
+	let mut looped = Looped::new(
+		reader,
+		Some(3),
+		move || WavReader::new(Cursor::new(data.clone())),
+		|packet| (packet.size() / 2) as i64,
+	);
+
+	let mut expected = 0;
+	let mut total = 0;
+	while let Some(packet) = looped.read_packet().unwrap() {
+		assert_eq!(packet.pts, expected);
+		expected += packet.size() as i64 / 2;
+		total += 1;
+	}
+	assert_eq!(expected, 15000);
+	assert!(total >= 3);
+}
+
+#[test]
+fn test_looped_demuxer_stops_on_empty_input() {
+	let data = crate::common::create_test_wav_from_samples(&[], 8000, 1);
+	let reader = WavReader::new(Cursor::new(data.clone())).unwrap();
+	let mut looped =
+		Looped::new(reader, None, move || WavReader::new(Cursor::new(data.clone())), |_| 0);
+	assert!(looped.read_packet().unwrap().is_none());
+}
+
+#[test]
+fn test_loop_wav_three_times() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let samples: Vec<i16> = (0..100).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+
+	transcoder(&input, &output).with_loop(Some(3)).run().unwrap();
+	let out = read_samples(&output);
+	assert_eq!(out.len(), 300);
+	assert_eq!(&out[100..103], &[0, 1, 2]);
+	assert_eq!(out[299], 99);
+}
+
+#[test]
+fn test_loop_forever_stops_at_trim_end() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let samples: Vec<i16> = (0..800).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+
+	// 0.1s input, keep 0.15s..1.0s of the endless loop
+	transcoder(&input, &output).with_loop(None).with_trim(0.15, Some(1.0)).run().unwrap();
+	let out = read_samples(&output);
+	assert_eq!(out.len(), 6800);
+	assert_eq!(out[0], 400);
+	assert_eq!(out[400], 0);
+}
+
+#[test]
+fn test_loop_y4m_frames() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	transcoder(&input, &output).with_loop(Some(2)).run().unwrap();
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut first_bytes = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		first_bytes.push(packet.data[0]);
+	}
+	assert_eq!(first_bytes, vec![0, 30, 60, 0, 30, 60]);
+}
+
+#[test]
+fn test_loop_rejects_unbounded_or_zero() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&[0; 10], 8000, 1)).unwrap();
+	assert!(transcoder(&input, &output).with_loop(None).run().is_err());
+	assert!(transcoder(&input, &output).with_loop(Some(0)).run().is_err());
+	assert!(transcoder(&input, &output).with_loop(None).with_trim(0.0, None).run().is_err());
+}
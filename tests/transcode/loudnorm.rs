@@ -0,0 +1,74 @@
+use ffmpreg::codecs::PcmDecoder;
+use ffmpreg::container::WavReader;
+use ffmpreg::core::{Decoder, Demuxer};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::LoudnessMeter;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn tone(amplitude_db: f64) -> Vec<i16> {
+	let amplitude = 10f64.powf(amplitude_db / 20.0) * 32767.0;
+	(0..48000 * 3)
+		.flat_map(|n| {
+			let v = (amplitude * (2.0 * std::f64::consts::PI * 440.0 * n as f64 / 48000.0).sin()).round()
+				as i16;
+			[v, v]
+		})
+		.collect()
+}
+
+fn loudness_of(path: &Path) -> f64 {
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(path).unwrap())).unwrap();
+	let mut decoder = PcmDecoder::new(reader.format());
+	let mut meter = LoudnessMeter::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap() {
+			meter.push(frame.audio().unwrap());
+		}
+	}
+	meter.loudness().integrated
+}
+
+#[test]
+fn test_transcoder_two_pass_loudnorm() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("quiet.wav");
+	let output = dir.path().join("loud.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&tone(-35.0), 48000, 2))
+		.unwrap();
+
+	let transcoder = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["volume=0.5".to_string(), "loudnorm=-18,-1".to_string()],
+	);
+	let before = transcoder.measure_loudness().unwrap();
+	assert!(before.integrated < -30.0);
+	transcoder.run().unwrap();
+
+	let integrated = loudness_of(&output);
+	assert!((integrated - -18.0).abs() < 0.2, "{}", integrated);
+}
+
+#[test]
+fn test_loudnorm_measures_what_reaches_it_unclipped() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("loud.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&tone(-6.0), 48000, 2))
+		.unwrap();
+
+	// gain takes the tone past full scale, which loudnorm receives in f32
+	// and the measuring pass has to see the same way
+	let transcoder = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["gain=4".to_string(), "loudnorm=-20".to_string()],
+	);
+	transcoder.run().unwrap();
+	let integrated = loudness_of(&output);
+	assert!((integrated - -20.0).abs() < 0.2, "{}", integrated);
+}
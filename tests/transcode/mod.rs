@@ -0,0 +1,19 @@
+mod benchmark;
+mod capability;
+mod check;
+mod compare;
+mod concat;
+mod cover;
+mod diff;
+mod flac;
+mod format;
+mod looping;
+mod loudnorm;
+mod overlay;
+mod progress;
+mod replaygain;
+mod reverse;
+mod sink;
+mod tee;
+mod threads;
+mod trim;
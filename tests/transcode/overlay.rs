@@ -0,0 +1,56 @@
+use ffmpreg::container::Y4mReader;
+use ffmpreg::core::Demuxer;
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use tempfile::tempdir;
+
+#[test]
+fn test_overlay_still_onto_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let logo = dir.path().join("logo.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let mut still = b"YUV4MPEG2 W2 H2 F30:1 Ip A1:1 C420\nFRAME\n".to_vec();
+	still.extend_from_slice(&[255, 255, 255, 255, 128, 128]);
+	std::fs::write(&logo, still).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_overlay(logo.to_str().unwrap().to_string(), 6, 6, 1.0)
+	.run()
+	.unwrap();
+
+	// 8x8 frames: the logo covers the bottom-right 2x2 of every frame
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut first_bytes = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		first_bytes.push(packet.data[0]);
+		assert_eq!([packet.data[54], packet.data[55], packet.data[62], packet.data[63]], [255; 4]);
+	}
+	assert_eq!(first_bytes, vec![0, 30, 60]);
+}
+
+#[test]
+fn test_overlay_rejects_non_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let path = input.to_str().unwrap().to_string();
+	let output = dir.path().join("out.y4m").to_str().unwrap().to_string();
+
+	let transcoder = |overlay: &str, alpha: f32| {
+		Transcoder::new(path.clone(), Some(output.clone()), false, vec![]).with_overlay(
+			overlay.to_string(),
+			0,
+			0,
+			alpha,
+		)
+	};
+	assert!(transcoder("logo.png", 1.0).run().is_err());
+	assert!(transcoder(&path, 1.5).run().is_err());
+}
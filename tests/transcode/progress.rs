@@ -0,0 +1,72 @@
+use crate::common::create_test_wav_from_samples;
+use ffmpreg::transcode::{Progress, ProgressMode, ProgressReport, Transcoder};
+use tempfile::tempdir;
+
+#[test]
+fn test_progress_mode_from_name() {
+	assert_eq!(ProgressMode::from_name("json"), Some(ProgressMode::Json));
+	assert_eq!(ProgressMode::from_name("Line"), Some(ProgressMode::Line));
+	assert_eq!(ProgressMode::from_name("off"), Some(ProgressMode::Off));
+	assert_eq!(ProgressMode::from_name("xml"), None);
+}
+
+#[test]
+fn test_progress_report_percent_and_eta() {
+	let report = ProgressReport { time: 15.0, duration: Some(60.0), speed: Some(3.0), done: false };
+
+	assert_eq!(report.percent(), Some(25.0));
+	assert_eq!(report.eta(), Some(15.0));
+	assert_eq!(
+		report.to_line(),
+		"time=00:00:15.000 / 00:01:00.000  25.0%  speed=3.00x  eta=00:00:15.000"
+	);
+	assert_eq!(
+		report.to_json(),
+		"{\"time\":15.000,\"duration\":60.000,\"percent\":25.000,\"speed\":3.000,\"eta\":15.000,\"done\":false}"
+	);
+}
+
+#[test]
+fn test_progress_report_without_duration() {
+	let report = ProgressReport { time: 4.0, duration: None, speed: Some(2.0), done: false };
+
+	assert_eq!(report.percent(), None);
+	assert_eq!(report.eta(), None);
+	assert_eq!(report.to_line(), "time=00:00:04.000  speed=2.00x");
+	assert!(report.to_json().contains("\"duration\":null,\"percent\":null"));
+}
+
+#[test]
+fn test_progress_counts_from_the_in_point() {
+	let mut progress = Progress::new(ProgressMode::Off, 10.0, Some(20.0));
+	progress.update(12.5);
+	let report = progress.report(false);
+	assert_eq!(report.time, 2.5);
+	assert_eq!(report.duration, Some(10.0));
+	assert_eq!(report.percent(), Some(25.0));
+
+	// packets past the out-point do not push it over
+	progress.update(20.4);
+	assert_eq!(progress.report(false).time, 10.0);
+}
+
+#[test]
+fn test_transcoder_with_progress_json() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let samples: Vec<i16> = (0..8000).map(|n| (n % 200) as i16).collect();
+	std::fs::write(&input, create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_progress(ProgressMode::Json)
+	.run()
+	.unwrap();
+
+	assert_eq!(std::fs::read(&output).unwrap(), std::fs::read(&input).unwrap());
+}
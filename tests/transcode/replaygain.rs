@@ -0,0 +1,119 @@
+use ffmpreg::codecs::{FlacEncoder, PcmDecoder};
+use ffmpreg::container::{FlacFormat, FlacReader, FlacWriter, WavReader};
+use ffmpreg::core::{Decoder, Demuxer, Encoder, Muxer};
+use ffmpreg::io::{Cursor, FileAdapter};
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::ReplayGain;
+use tempfile::tempdir;
+
+fn tone() -> Vec<i16> {
+	(0..48000 * 2)
+		.map(|n| (6000.0 * (2.0 * std::f64::consts::PI * 440.0 * n as f64 / 48000.0).sin()) as i16)
+		.collect()
+}
+
+fn transcoder(input: &std::path::Path, output: &std::path::Path) -> Transcoder {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+	.with_replaygain(true)
+}
+
+#[test]
+fn test_replaygain_from_loudness() {
+	let gain =
+		ReplayGain::from_loudness(ffmpreg::transform::Loudness { integrated: -23.0, true_peak: -6.0 });
+	assert!((gain.track_gain - 5.0).abs() < 1e-9);
+	let [(gain_key, gain_value), (peak_key, peak_value)] = gain.tags();
+	assert_eq!((gain_key, gain_value.as_str()), ("REPLAYGAIN_TRACK_GAIN", "5.00 dB"));
+	assert_eq!((peak_key, peak_value.as_str()), ("REPLAYGAIN_TRACK_PEAK", "0.501187"));
+}
+
+#[test]
+fn test_replaygain_wav_id3_chunk() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("tone.wav");
+	let output = dir.path().join("tagged.wav");
+	let samples = tone();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 48000, 1)).unwrap();
+
+	let transcoder = transcoder(&input, &output);
+	let expected = ReplayGain::from_loudness(transcoder.measure_loudness().unwrap());
+	transcoder.run().unwrap();
+
+	let bytes = std::fs::read(&output).unwrap();
+	let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+	assert_eq!(riff_size, bytes.len() - 8);
+
+	let chunk = bytes.windows(4).position(|w| w == b"id3 ").expect("no id3 chunk");
+	assert_eq!(&bytes[chunk + 8..chunk + 11], b"ID3");
+	for (key, value) in expected.tags() {
+		let frame = format!("{}\0{}", key, value);
+		assert!(bytes.windows(frame.len()).any(|w| w == frame.as_bytes()), "missing {}", key);
+	}
+
+	// the tag sits after the data chunk and must not be read back as audio
+	let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+	let mut decoder = PcmDecoder::new(reader.format());
+	let mut decoded = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap() {
+			decoded += frame.audio().unwrap().nb_samples;
+		}
+	}
+	assert_eq!(decoded, samples.len());
+}
+
+#[test]
+fn test_replaygain_flac_vorbis_comment() {
+	let dir = tempdir().unwrap();
+	let wav = dir.path().join("tone.wav");
+	let input = dir.path().join("tone.flac");
+	let output = dir.path().join("tagged.flac");
+	std::fs::write(&wav, crate::common::create_test_wav_from_samples(&tone(), 48000, 1)).unwrap();
+
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(&wav).unwrap())).unwrap();
+	let format = reader.format();
+	let flac_format = FlacFormat {
+		sample_rate: format.sample_rate,
+		channels: format.channels,
+		bits_per_sample: 16,
+		..FlacFormat::default()
+	};
+	let mut writer =
+		FlacWriter::new(FileAdapter::create(input.to_str().unwrap()).unwrap(), flac_format).unwrap();
+	let mut decoder = PcmDecoder::new(format);
+	let mut encoder = FlacEncoder::new(format.sample_rate, format.channels, 16, 4096);
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap()
+			&& let Some(packet) = encoder.encode(frame).unwrap()
+		{
+			writer.write_packet(packet).unwrap();
+		}
+	}
+	writer.finalize().unwrap();
+	drop(writer);
+
+	let transcoder = transcoder(&input, &output);
+	let expected = ReplayGain::from_loudness(transcoder.measure_loudness().unwrap());
+	assert!(expected.track_gain.is_finite() && expected.track_peak > 0.0);
+	transcoder.run().unwrap();
+
+	let reader = FlacReader::new(FileAdapter::open(output.to_str().unwrap()).unwrap()).unwrap();
+	let comment = reader.vorbis_comment().expect("no vorbis comment block");
+	for (key, value) in expected.tags() {
+		assert_eq!(comment.get_comment(key), Some(&value));
+	}
+}
+
+#[test]
+fn test_replaygain_rejects_untaggable_output() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("clip.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, b"").unwrap();
+	assert!(transcoder(&input, &output).run().is_err());
+}
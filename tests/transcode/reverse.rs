@@ -0,0 +1,55 @@
+use ffmpreg::container::Y4mReader;
+use ffmpreg::core::Demuxer;
+use ffmpreg::io::{Cursor, Overwrite};
+use ffmpreg::transcode::Transcoder;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn transcoder(input: &Path, output: &Path) -> Transcoder {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+}
+
+fn first_luma_bytes(path: &Path) -> Vec<u8> {
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(path).unwrap())).unwrap();
+	let mut bytes = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		bytes.push(packet.data[0]);
+	}
+	bytes
+}
+
+#[test]
+fn test_reverse_y4m_frames() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	// the three test frames start their luma at 0, 30 and 60
+	transcoder(&input, &output).with_reverse(true).run().unwrap();
+	assert_eq!(first_luma_bytes(&output), vec![60, 30, 0]);
+
+	// reversal happens after the trim and the loop
+	transcoder(&input, &output)
+		.with_overwrite(Overwrite::Replace)
+		.with_loop(Some(2))
+		.with_trim(1.0 / 30.0, None)
+		.with_reverse(true)
+		.run()
+		.unwrap();
+	assert_eq!(first_luma_bytes(&output), vec![60, 30, 0, 60, 30]);
+}
+
+#[test]
+fn test_reverse_requires_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&[0; 10], 8000, 1)).unwrap();
+	assert!(transcoder(&input, &output).with_reverse(true).run().is_err());
+}
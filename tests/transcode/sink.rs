@@ -0,0 +1,72 @@
+use ffmpreg::container::SinkFormat;
+use ffmpreg::container::hash::{Md5, to_hex};
+use ffmpreg::transcode::Transcoder;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn transcoder(input: &Path, output: Option<&Path>) -> Transcoder {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		output.map(|output| output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+}
+
+#[test]
+fn test_md5_sink_hashes_encoded_samples() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("in.md5");
+	let samples: Vec<i16> = (0..400).map(|i| i * 3 - 600).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+
+	transcoder(&input, Some(&output)).with_sink(SinkFormat::Md5).run().unwrap();
+
+	let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let mut md5 = Md5::new();
+	md5.update(&pcm);
+	let expected = format!("MD5={}\n", to_hex(&md5.finish()));
+	assert_eq!(std::fs::read_to_string(&output).unwrap(), expected);
+}
+
+#[test]
+fn test_sha256_sink_follows_transforms() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let plain = dir.path().join("plain.sha256");
+	let flipped = dir.path().join("flipped.sha256");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	transcoder(&input, Some(&plain)).with_sink(SinkFormat::Sha256).run().unwrap();
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(flipped.to_str().unwrap().to_string()),
+		false,
+		vec!["hflip".to_string()],
+	)
+	.with_sink(SinkFormat::Sha256)
+	.run()
+	.unwrap();
+
+	let plain = std::fs::read_to_string(&plain).unwrap();
+	assert!(plain.starts_with("SHA256=") && plain.len() == 7 + 64 + 1);
+	assert_ne!(plain, std::fs::read_to_string(&flipped).unwrap());
+}
+
+#[test]
+fn test_null_sink_writes_nothing() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("null");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&[1, 2, 3, 4], 8000, 1))
+		.unwrap();
+
+	transcoder(&input, Some(&output)).with_sink(SinkFormat::Null).run().unwrap();
+	transcoder(&input, None).with_sink(SinkFormat::Null).run().unwrap();
+	assert!(!output.exists());
+
+	// a broken input still fails, which is what a null run is for
+	std::fs::write(&input, b"RIFF").unwrap();
+	assert!(transcoder(&input, None).with_sink(SinkFormat::Null).run().is_err());
+}
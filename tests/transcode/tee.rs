@@ -0,0 +1,79 @@
+use ffmpreg::container::FlacReader;
+use ffmpreg::io::FileAdapter;
+use ffmpreg::transcode::{Check, Transcoder};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn transcoder(input: &Path, output: &Path, transforms: &[&str]) -> Transcoder {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		transforms.iter().map(|t| t.to_string()).collect(),
+	)
+}
+
+fn path(path: &Path) -> String {
+	path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_tee_wav_and_flac_in_one_pass() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let copy = dir.path().join("copy.wav");
+	let flac = dir.path().join("out.flac");
+	let samples: Vec<i16> = (0..20000).map(|i| ((i * 37) % 20000 - 10000) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 44100, 2)).unwrap();
+
+	transcoder(&input, &output, &["volume=0.5"])
+		.with_tee(vec![path(&copy), path(&flac)])
+		.run()
+		.unwrap();
+
+	// every leg gets the frames the main output does
+	assert_eq!(std::fs::read(&copy).unwrap(), std::fs::read(&output).unwrap());
+	let alone = dir.path().join("alone.wav");
+	transcoder(&input, &alone, &["volume=0.5"]).run().unwrap();
+	assert_eq!(std::fs::read(&alone).unwrap(), std::fs::read(&output).unwrap());
+
+	let reader = FlacReader::new(FileAdapter::open(flac.to_str().unwrap()).unwrap()).unwrap();
+	assert_eq!(reader.format().sample_rate, 44100);
+	assert_eq!(reader.format().channels, 2);
+
+	// the flac leg holds real frames that decode, not raw samples
+	let report = Check::new(path(&flac)).verify().unwrap();
+	assert!(report.is_ok(), "{:?}", report.first_error());
+	assert_eq!(report.frames, 10);
+}
+
+#[test]
+fn test_tee_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	let copy = dir.path().join("copy.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	transcoder(&input, &output, &["negate"]).with_tee(vec![path(&copy)]).run().unwrap();
+	assert_eq!(std::fs::read(&copy).unwrap(), std::fs::read(&output).unwrap());
+}
+
+#[test]
+fn test_tee_rejects_unsupported_legs() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	// a leg has to be a container the decoded frames can go straight into
+	let result =
+		transcoder(&input, &output, &[]).with_tee(vec![path(&dir.path().join("out.y4m"))]).run();
+	assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+
+	// and the main output has to be decoded for there to be frames to share
+	let flac = dir.path().join("out.flac");
+	let result = transcoder(&input, &flac, &[]).with_tee(vec![path(&output)]).run();
+	assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+}
@@ -0,0 +1,69 @@
+use ffmpreg::transcode::Transcoder;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn convert(input: &Path, output: &Path, transforms: &[&str], threads: usize) -> Vec<u8> {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		transforms.iter().map(|t| t.to_string()).collect(),
+	)
+	.with_threads(threads)
+	.run()
+	.unwrap();
+	let bytes = std::fs::read(output).unwrap();
+	std::fs::remove_file(output).unwrap();
+	bytes
+}
+
+#[test]
+fn test_threads_match_sequential_wav() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let samples: Vec<i16> = (0..20000).map(|i| ((i * 37) % 20000 - 10000) as i16).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 44100, 2)).unwrap();
+
+	let chain = ["highpass=80", "lowpass=8000", "brickwall=-3,5", "resample=22050", "volume=0.8"];
+	let sequential = convert(&input, &output, &chain, 1);
+	for threads in [2, 3, 4, 8] {
+		assert_eq!(convert(&input, &output, &chain, threads), sequential, "{} threads", threads);
+	}
+	// with nothing to transform the stages still hand every frame on
+	assert_eq!(convert(&input, &output, &[], 4), convert(&input, &output, &[], 1));
+}
+
+#[test]
+fn test_threads_match_sequential_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	let chain = ["negate", "brightness=0.1", "hflip"];
+	let sequential = convert(&input, &output, &chain, 1);
+	assert_eq!(convert(&input, &output, &chain, 5), sequential);
+}
+
+#[test]
+fn test_threads_report_transform_errors() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_data()).unwrap();
+
+	// a map past the last channel fails on the first frame, inside a stage
+	for threads in [1, 2, 4] {
+		let result = Transcoder::new(
+			input.to_str().unwrap().to_string(),
+			Some(output.to_str().unwrap().to_string()),
+			false,
+			vec!["volume=2".to_string(), "channelmap=0,3".to_string(), "volume=0.5".to_string()],
+		)
+		.with_threads(threads)
+		.run();
+		let error = result.unwrap_err().to_string();
+		assert!(error.contains("missing input channel"), "{}", error);
+	}
+}
@@ -0,0 +1,147 @@
+use ffmpreg::codecs::PcmDecoder;
+use ffmpreg::container::{Mp3Reader, OggReader, WavReader, Y4mReader};
+use ffmpreg::core::{Decoder, Demuxer};
+use ffmpreg::edl::EditDecisionList;
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn transcoder(input: &Path, output: &Path) -> Transcoder {
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![],
+	)
+}
+
+fn read_samples(path: &Path) -> Vec<i16> {
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(path).unwrap())).unwrap();
+	let mut decoder = PcmDecoder::new(reader.format());
+	let mut samples = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap() {
+			let data = &frame.audio().unwrap().data;
+			samples.extend(data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])));
+		}
+	}
+	samples
+}
+
+fn ramp_wav(dir: &Path) -> std::path::PathBuf {
+	// 2 seconds at 8 kHz where every sample holds its own index
+	let samples: Vec<i16> = (0..16000).map(|n| n as i16).collect();
+	let path = dir.join("ramp.wav");
+	std::fs::write(&path, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+	path
+}
+
+#[test]
+fn test_trim_wav_range() {
+	let dir = tempdir().unwrap();
+	let input = ramp_wav(dir.path());
+	let output = dir.path().join("out.wav");
+	transcoder(&input, &output).with_trim(0.5, Some(1.25)).run().unwrap();
+
+	let samples = read_samples(&output);
+	assert_eq!(samples.len(), 6000);
+	assert_eq!(samples.first(), Some(&4000));
+	assert_eq!(samples.last(), Some(&9999));
+}
+
+#[test]
+fn test_trim_wav_open_ended() {
+	let dir = tempdir().unwrap();
+	let input = ramp_wav(dir.path());
+	let output = dir.path().join("out.wav");
+	transcoder(&input, &output).with_trim(1.5, None).run().unwrap();
+
+	let samples = read_samples(&output);
+	assert_eq!(samples.len(), 4000);
+	assert_eq!(samples.first(), Some(&12000));
+}
+
+#[test]
+fn test_trim_wav_with_auto_fade() {
+	let dir = tempdir().unwrap();
+	let samples = vec![1000i16; 16000];
+	let input = dir.path().join("flat.wav");
+	let output = dir.path().join("out.wav");
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 8000, 1)).unwrap();
+	transcoder(&input, &output).with_trim(0.5, Some(1.0)).with_auto_fade(0.1).run().unwrap();
+
+	let samples = read_samples(&output);
+	assert_eq!(samples.len(), 4000);
+	assert_eq!(samples[0], 0);
+	assert_eq!(samples[2000], 1000);
+}
+
+#[test]
+fn test_trim_y4m_frames() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	// three frames at 30 fps; keep only the second
+	transcoder(&input, &output).with_trim(1.0 / 30.0, Some(2.0 / 30.0)).run().unwrap();
+
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let packet = reader.read_packet().unwrap().unwrap();
+	assert_eq!(packet.data[0], 30);
+	assert!(reader.read_packet().unwrap().is_none());
+}
+
+#[test]
+fn test_trim_mp3_recounts_frames() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.mp3");
+	std::fs::write(&input, crate::container::mp3::create_test_mp3_data(20)).unwrap();
+	let output = dir.path().join("out.mp3");
+	// 0.1 s is inside frame 3, and frame 11 starts before 0.3 s
+	transcoder(&input, &output).with_trim(0.1, Some(0.3)).run().unwrap();
+
+	let mut reader = Mp3Reader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert!(reader.id3_tag().is_some());
+	assert_eq!(reader.format().frames, Some(9));
+	let mut frames = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		frames.push(packet.data[4]);
+	}
+	assert_eq!(frames, (3..=11).collect::<Vec<u8>>());
+}
+
+#[test]
+fn test_trim_ogg_rebases_granules() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.ogg");
+	std::fs::write(&input, crate::container::ogg::create_test_ogg_data()).unwrap();
+	let output = dir.path().join("out.ogg");
+	transcoder(&input, &output).with_trim(0.55, Some(0.75)).run().unwrap();
+
+	let mut reader = OggReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let pages: Vec<_> = std::iter::from_fn(|| reader.read_page().unwrap()).collect();
+	let granules: Vec<i64> = pages.iter().map(|page| page.granule).collect();
+	// headers, then pages 6 to 8 starting from 0 and ending at 0.2 s
+	assert_eq!(granules, vec![0, 0, 800, 1600, 2000]);
+	assert_eq!(pages[2].body, vec![6; 50]);
+	assert_eq!(pages.last().unwrap().flags, 0x04);
+}
+
+#[test]
+fn test_trim_rejects_bad_ranges() {
+	let dir = tempdir().unwrap();
+	let input = ramp_wav(dir.path());
+	let output = dir.path().join("out.wav");
+	assert!(transcoder(&input, &output).with_trim(1.0, Some(1.0)).run().is_err());
+	assert!(transcoder(&input, &output).with_trim(-1.0, None).run().is_err());
+	assert!(
+		transcoder(&input, &output)
+			.with_trim(0.0, Some(1.0))
+			.with_edl(EditDecisionList::new())
+			.run()
+			.is_err()
+	);
+	assert!(transcoder(&input, &dir.path().join("out.flac")).with_trim(0.0, None).run().is_err());
+}
@@ -0,0 +1,51 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{Adelay, TransformSpec};
+
+fn frame(samples: &[i16], channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio =
+		FrameAudio::new(data, 1000, channels).with_nb_samples(samples.len() / channels as usize);
+	Frame::new_audio(audio, Timebase::new(1, 1000), 0)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_adelay_shifts_one_channel() {
+	// at 1 kHz one sample is one millisecond
+	let mut delay = Adelay::new(vec![0.0, 2.0]);
+	let first = delay.apply(frame(&[1, 10, 2, 20, 3, 30], 2)).unwrap();
+	assert_eq!(samples(&first), vec![1, 0, 2, 0, 3, 10]);
+	assert_eq!(first.audio().unwrap().nb_samples, 3);
+
+	// the delay line carries across frames
+	let second = delay.apply(frame(&[4, 40], 2)).unwrap();
+	assert_eq!(samples(&second), vec![4, 20]);
+	assert_eq!(second.pts, 3);
+
+	let tail = delay.flush().unwrap().unwrap();
+	assert_eq!(samples(&tail), vec![0, 30, 0, 40]);
+	assert_eq!(tail.pts, 4);
+	assert!(delay.flush().unwrap().is_none());
+}
+
+#[test]
+fn test_adelay_unlisted_channels_pass_through() {
+	let mut delay = Adelay::new(vec![1.0]);
+	let output = delay.apply(frame(&[1, 10, 100, 2, 20, 200], 3)).unwrap();
+	assert_eq!(samples(&output), vec![0, 10, 100, 1, 20, 200]);
+	assert_eq!(samples(&delay.flush().unwrap().unwrap()), vec![2, 0, 0]);
+}
+
+#[test]
+fn test_adelay_specs() {
+	assert_eq!(
+		TransformSpec::parse("adelay=0,12.5").unwrap(),
+		TransformSpec::Adelay(vec![0.0, 12.5])
+	);
+	assert!(TransformSpec::parse("adelay").is_err());
+	assert!(TransformSpec::parse("adelay=a,b").is_err());
+	assert!(TransformSpec::Adelay(vec![-1.0]).build().is_err());
+}
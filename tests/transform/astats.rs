@@ -0,0 +1,73 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::show::{Show, ShowOptions};
+use ffmpreg::transform::{AStats, TransformSpec};
+
+fn frame(samples: &[i16], channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio =
+		FrameAudio::new(data, 48000, channels).with_nb_samples(samples.len() / channels as usize);
+	Frame::new_audio(audio, Timebase::new(1, 48000), 0)
+}
+
+#[test]
+fn test_astats_per_channel() {
+	// left: square wave with an offset and two clipped samples, right: silence
+	let left = [16384i16, -8192, 16384, -8192, i16::MAX, i16::MIN];
+	let input: Vec<i16> = left.iter().flat_map(|&l| [l, 0]).collect();
+
+	let mut astats = AStats::new();
+	let output = astats.apply(frame(&input, 2)).unwrap();
+	assert_eq!(output.audio().unwrap().data.len(), input.len() * 2);
+
+	let stats = astats.stats();
+	assert_eq!(stats.len(), 2);
+	assert_eq!(stats[0].samples, 6);
+	assert_eq!(stats[0].peak, 32768);
+	assert_eq!(stats[0].clipped, 2);
+	assert_eq!(stats[0].zero_crossings, 5);
+	assert!((stats[0].dc_offset() - (16384.0 - 1.0) / 6.0 / 32768.0).abs() < 1e-9);
+	assert!(stats[0].peak_db().abs() < 1e-9);
+
+	assert_eq!(stats[1].zero_crossings, 0);
+	assert_eq!(stats[1].rms_db(), f64::NEG_INFINITY);
+	assert!(astats.to_json().contains("\"peak_db\":null"));
+	assert!(astats.to_text().contains("Channel: 2\n"));
+}
+
+#[test]
+fn test_astats_accumulates_across_frames() {
+	let mut astats = AStats::new();
+	astats.apply(frame(&[1000, 1000], 1)).unwrap();
+	astats.apply(frame(&[-1000, -1000], 1)).unwrap();
+
+	let stats = astats.stats()[0];
+	assert_eq!(stats.samples, 4);
+	assert_eq!(stats.zero_crossings, 1);
+	assert_eq!(stats.dc_offset(), 0.0);
+	assert!((stats.rms_db() - 20.0 * (1000.0f64 / 32768.0).log10()).abs() < 1e-9);
+}
+
+#[test]
+fn test_astats_in_show_json() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("tone.wav");
+	let samples: Vec<i16> =
+		(0..4800).flat_map(|n| [if n % 2 == 0 { 8000 } else { -8000 }, 0]).collect();
+	std::fs::write(&path, crate::common::create_test_wav_from_samples(&samples, 48000, 2)).unwrap();
+	let path = path.to_str().unwrap().to_string();
+
+	let plain = Show::new(path.clone(), ShowOptions::default()).to_json().unwrap();
+	assert!(!plain.contains("\"stats\""));
+
+	let opts = ShowOptions { astats: true, ..ShowOptions::default() };
+	let json = Show::new(path, opts).to_json().unwrap();
+	assert!(json.contains("\"stats\":[{\"dc_offset\":0.000000,\"peak_db\":-12.25"), "{}", json);
+	assert!(json.contains("\"zero_crossings\":4799"), "{}", json);
+}
+
+#[test]
+fn test_astats_specs() {
+	assert_eq!(TransformSpec::parse("astats").unwrap(), TransformSpec::AStats { json: false });
+	assert_eq!(TransformSpec::parse("astats=json").unwrap(), TransformSpec::AStats { json: true });
+	assert!(TransformSpec::parse("astats=xml").is_err());
+}
@@ -166,3 +166,67 @@ fn test_chain_three_transforms() {
 	let max_abs = output.iter().map(|s| s.abs()).max().unwrap();
 	assert!(max_abs > 16000 && max_abs < 16400);
 }
+
+#[test]
+fn test_chain_keeps_headroom_between_f32_stages() {
+	// alone, the first gain would clip everything to the rails
+	let mut chain = TransformChain::new();
+	chain.add(parse_transform("gain=8").unwrap());
+	chain.add(parse_transform("gain=0.125").unwrap());
+
+	let input = vec![20000, -20000, 1234, -7];
+	let result = chain.apply(create_test_frame(input.clone())).unwrap();
+	assert_eq!(result.audio().unwrap().format, ffmpreg::core::SampleFormat::S16);
+	assert_eq!(extract_samples(&result), input);
+}
+
+#[test]
+fn test_chain_quantizes_before_s16_only_transforms() {
+	let mut chain = TransformChain::new();
+	chain.add(parse_transform("gain=8").unwrap());
+	chain.add(parse_transform("invert").unwrap());
+	chain.add(parse_transform("gain=0.125").unwrap());
+
+	let result = chain.apply(create_test_frame(vec![20000, 100])).unwrap();
+	assert_eq!(extract_samples(&result), vec![-4096, -100]);
+}
+
+#[test]
+fn test_chain_accepts_planar_frames() {
+	let audio = FrameAudio::from_planes_i16(&[vec![100, 200], vec![-100, -200]], 44100);
+	let frame = Frame::new_audio(audio, Timebase::new(1, 44100), 0);
+
+	let mut chain = TransformChain::new();
+	chain.add(parse_transform("gain=2").unwrap());
+	chain.add(parse_transform("resample=22050").unwrap());
+	let result = chain.apply(frame).unwrap();
+
+	assert_eq!(result.audio().unwrap().format, ffmpreg::core::SampleFormat::S16);
+	assert_eq!(extract_samples(&result), vec![200, -200]);
+}
+
+#[test]
+fn test_chain_split_matches_whole() {
+	let specs = ["volume=0.3", "volume=1.7", "highpass=200", "volume=0.9", "lowpass=4000"];
+	let build = || {
+		let mut chain = TransformChain::new();
+		for spec in specs {
+			chain.add(parse_transform(spec).unwrap());
+		}
+		chain
+	};
+	let samples: Vec<i16> = (0..512).map(|i| ((i * 97) % 4000 - 2000) as i16).collect();
+	let whole = extract_samples(&build().apply(create_test_frame(samples.clone())).unwrap());
+
+	let parts = build().split(3);
+	assert_eq!(parts.len(), 3);
+	let mut frame = create_test_frame(samples);
+	for mut part in parts {
+		frame = part.apply(frame).unwrap();
+	}
+	assert_eq!(extract_samples(&frame), whole);
+
+	// never more parts than transforms
+	assert_eq!(build().split(10).len(), 5);
+	assert_eq!(TransformChain::new().split(4).len(), 1);
+}
@@ -0,0 +1,85 @@
+use ffmpreg::core::{ChannelLayout, Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{ChannelMixer, parse_transform};
+
+fn create_test_frame(samples: Vec<i16>, channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, 48000, channels);
+	Frame::new_audio(audio, Timebase::new(1, 48000), 0)
+}
+
+fn extract_samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_layout_from_channels() {
+	assert_eq!(ChannelLayout::from_channels(6), ChannelLayout::Surround51);
+	assert_eq!(ChannelLayout::from_channels(4), ChannelLayout::Unknown(4));
+	assert_eq!(ChannelLayout::Surround51.channels(), 6);
+
+	let frame = create_test_frame(vec![0; 12], 6);
+	assert_eq!(frame.audio().unwrap().layout, ChannelLayout::Surround51);
+}
+
+#[test]
+fn test_downmix_51_to_stereo() {
+	// FL FR FC LFE BL BR
+	let frame = create_test_frame(vec![1000, 0, 1000, 20000, 0, 0, 0, 0, 0, 0, 0, 2000], 6);
+	let result = ChannelMixer::downmix_51_to_stereo().apply(frame).unwrap();
+
+	let audio = result.audio().unwrap();
+	assert_eq!(audio.channels, 2);
+	assert_eq!(audio.layout, ChannelLayout::Stereo);
+	assert_eq!(audio.nb_samples, 2);
+	// the lfe is dropped, centre and surrounds are mixed in at -3 dB
+	assert_eq!(extract_samples(&result), vec![707, 293, 0, 586]);
+}
+
+#[test]
+fn test_downmix_never_clips() {
+	let frame = create_test_frame(vec![i16::MAX; 6], 6);
+	let result = ChannelMixer::new(ChannelLayout::Stereo).apply(frame).unwrap();
+	assert_eq!(extract_samples(&result), vec![i16::MAX, i16::MAX]);
+}
+
+#[test]
+fn test_matrix_mix() {
+	let mixer = ChannelMixer::from_matrix(vec![vec![0.5, 0.5], vec![1.0, -1.0], vec![0.0, 2.0]]);
+	let frame = create_test_frame(vec![1000, 3000], 2);
+	let result = mixer.unwrap().apply(frame).unwrap();
+
+	assert_eq!(result.audio().unwrap().channels, 3);
+	assert_eq!(extract_samples(&result), vec![2000, -2000, 6000]);
+}
+
+#[test]
+fn test_matrix_rejects_wrong_channel_count() {
+	assert!(ChannelMixer::from_matrix(vec![vec![1.0, 0.0], vec![1.0]]).is_err());
+
+	let mut mixer = ChannelMixer::from_matrix(vec![vec![1.0, 1.0]]).unwrap();
+	assert!(mixer.apply(create_test_frame(vec![1, 2, 3], 3)).is_err());
+}
+
+#[test]
+fn test_remap_swaps_channels() {
+	let mut mixer = ChannelMixer::remap(vec![1, 0]).unwrap();
+	let result = mixer.apply(create_test_frame(vec![1, 2, 3, 4], 2)).unwrap();
+	assert_eq!(extract_samples(&result), vec![2, 1, 4, 3]);
+
+	assert!(mixer.apply(create_test_frame(vec![1, 2], 1)).is_err());
+}
+
+#[test]
+fn test_parse_channel_transforms() {
+	let mut swap = parse_transform("channelmap=1,0").unwrap();
+	let result = swap.apply(create_test_frame(vec![5, 7], 2)).unwrap();
+	assert_eq!(extract_samples(&result), vec![7, 5]);
+
+	let mut matrix = parse_transform("matrix=0.5,0.5").unwrap();
+	let result = matrix.apply(create_test_frame(vec![100, 300], 2)).unwrap();
+	assert_eq!(extract_samples(&result), vec![200]);
+
+	assert!(parse_transform("downmix").is_ok());
+	assert!(parse_transform("matrix=1,0|1").is_err());
+	assert!(parse_transform("channelmap=left").is_err());
+}
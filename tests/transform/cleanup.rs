@@ -0,0 +1,60 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{DcRemove, Invert, TransformSpec};
+
+fn frame(samples: &[i16], channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	Frame::new_audio(FrameAudio::new(data, 48000, channels), Timebase::new(1, 48000), 0)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_dcremove_settles_offset_to_zero() {
+	// a 1 kHz tone riding on a +4000 offset
+	let input: Vec<i16> = (0..48000)
+		.map(|n| {
+			4000 + (8000.0 * (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / 48000.0).sin()) as i16
+		})
+		.collect();
+	let output = samples(&DcRemove::default().apply(frame(&input, 1)).unwrap());
+
+	let tail = &output[24000..];
+	let mean = tail.iter().map(|&s| s as f64).sum::<f64>() / tail.len() as f64;
+	assert!(mean.abs() < 20.0, "mean {}", mean);
+	let peak = tail.iter().map(|s| s.unsigned_abs()).max().unwrap();
+	assert!((7800..=8200).contains(&peak), "peak {}", peak);
+}
+
+#[test]
+fn test_dcremove_keeps_channels_apart() {
+	let input: Vec<i16> = (0..9600).flat_map(|_| [3000, -3000]).collect();
+	let mut dc = DcRemove::new(20.0);
+	let first = samples(&dc.apply(frame(&input, 2)).unwrap());
+	// state carries across frames
+	let second = samples(&dc.apply(frame(&input, 2)).unwrap());
+
+	assert_eq!(first[0], 3000);
+	assert_eq!(first[1], -3000);
+	assert!(second.iter().all(|s| s.abs() < 10), "{:?}", &second[..4]);
+}
+
+#[test]
+fn test_invert_flips_polarity() {
+	let output =
+		samples(&Invert::new().apply(frame(&[0, 1000, -1000, i16::MAX, i16::MIN], 1)).unwrap());
+	assert_eq!(output, vec![0, -1000, 1000, -i16::MAX, i16::MAX]);
+}
+
+#[test]
+fn test_cleanup_specs() {
+	assert_eq!(TransformSpec::parse("dcremove").unwrap(), TransformSpec::DcRemove { cutoff: 5.0 });
+	assert_eq!(
+		TransformSpec::parse("dcremove=10").unwrap(),
+		TransformSpec::DcRemove { cutoff: 10.0 }
+	);
+	assert_eq!(TransformSpec::parse("invert").unwrap(), TransformSpec::Invert);
+	assert!(TransformSpec::parse("dcremove=abc").is_err());
+	assert!(TransformSpec::DcRemove { cutoff: 0.0 }.build().is_err());
+}
@@ -0,0 +1,84 @@
+use ffmpreg::core::{Frame, FrameAudio, FrameVideo, Timebase, Transform, VideoFormat};
+use ffmpreg::transform::{Expr, SpecTemplate, TransformChain, TransformSpec, Variables};
+
+fn audio_frame(samples: &[i16], pts: i64) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	Frame::new_audio(FrameAudio::new(data, 4, 1), Timebase::new(1, 4), 0).with_pts(pts)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	let audio = frame.audio().unwrap();
+	audio.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_expr_arithmetic_and_functions() {
+	let vars = Variables { t: 0.25, n: 3.0, w: 1920.0, h: 1080.0 };
+	let eval = |text: &str| Expr::parse(text).unwrap().eval(&vars);
+
+	assert_eq!(eval("w/2"), 960.0);
+	assert_eq!(eval("1+2*3-4/2"), 5.0);
+	assert_eq!(eval("-2^2"), -4.0);
+	assert_eq!(eval("2^3^2"), 512.0);
+	assert_eq!(eval("(n+1)%3"), 1.0);
+	assert_eq!(eval("min(w,h)+max(1,clip(n,0,2))"), 1082.0);
+	assert!((eval("0.5+0.5*sin(2*PI*t)") - 1.0).abs() < 1e-12);
+	assert_eq!(eval("floor(iw/7)"), 274.0);
+
+	assert!(Expr::parse("w/").is_err());
+	assert!(Expr::parse("foo(1)").is_err());
+	assert!(Expr::parse("min(1)").is_err());
+	assert!(Expr::parse("(1+2").is_err());
+	assert!(Expr::parse("2 3").is_err());
+}
+
+#[test]
+fn test_spec_template_leaves_plain_values_alone() {
+	let vars = Variables { t: 1.0, n: 0.0, w: 640.0, h: 360.0 };
+	let render = |spec: &str| SpecTemplate::parse(spec).render(&vars).unwrap();
+
+	assert_eq!(render("crop=w/2:h/2:w/4:0"), "crop=320:180:160:0");
+	assert_eq!(render("drawbox=x=w-64:y=10:w=64:h=h/4:t=2"), "drawbox=x=576:y=10:w=64:h=90:t=2");
+	assert_eq!(render("gain=t/4"), "gain=0.25");
+	assert_eq!(render("pad=1920:1080:white"), "pad=1920:1080:white");
+	assert!(!SpecTemplate::parse("lut=curve=0/0,128/150").has_expressions());
+	assert!(!SpecTemplate::parse("drawtext=text=n").has_expressions());
+}
+
+#[test]
+fn test_expression_specs_parse_and_check_early() {
+	assert_eq!(
+		TransformSpec::parse("gain=0.5+0.5*sin(2*PI*t)").unwrap(),
+		TransformSpec::Expression("gain=0.5+0.5*sin(2*PI*t)".to_string())
+	);
+	// commas inside parentheses do not start a new spec
+	let specs = TransformSpec::parse_list("gain=max(t,1),hflip").unwrap();
+	assert_eq!(specs.len(), 2);
+	assert_eq!(specs[0], TransformSpec::Expression("gain=max(t,1)".to_string()));
+	assert_eq!(TransformSpec::parse("gain=2").unwrap(), TransformSpec::Gain(2.0));
+
+	assert!(TransformSpec::parse("crop=w/7:h").is_err());
+	assert!(TransformSpec::parse("rotate=n").is_err());
+}
+
+#[test]
+fn test_gain_expression_follows_time() {
+	let mut chain =
+		TransformChain::from_specs(TransformSpec::parse_list("gain=1+t").unwrap()).unwrap();
+	assert_eq!(samples(&chain.apply(audio_frame(&[100, -100], 0)).unwrap()), vec![100, -100]);
+	assert_eq!(samples(&chain.apply(audio_frame(&[100, -100], 2)).unwrap()), vec![150, -150]);
+	assert_eq!(samples(&chain.apply(audio_frame(&[100, -100], 4)).unwrap()), vec![200, -200]);
+}
+
+#[test]
+fn test_crop_expression_reads_frame_size() {
+	let mut crop = TransformSpec::parse("crop=w/2:h/2:0:0").unwrap().build().unwrap();
+	for (width, height) in [(8, 4), (4, 2)] {
+		let size = VideoFormat::YUV420.frame_size(width, height);
+		let video = FrameVideo::new(vec![50; size], width, height, VideoFormat::YUV420);
+		let frame = Frame::new_video(video, Timebase::new(1, 30), 0);
+		let output = crop.apply(frame).unwrap();
+		let video = output.video().unwrap();
+		assert_eq!((video.width, video.height), (width / 2, height / 2));
+	}
+}
@@ -0,0 +1,46 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::Crossfade;
+
+fn frame(samples: &[i16]) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	Frame::new_audio(FrameAudio::new(data, 1000, 1), Timebase::new(1, 1000), 0)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_crossfade_keeps_tail_across_frames() {
+	// 4 samples of overlap at 1 kHz, fed from two frames
+	let mut crossfade = Crossfade::new(4.0, 1000, 1);
+	crossfade.feed_previous(&frame(&[1, 2, 3000]));
+	crossfade.feed_previous(&frame(&[4000, 4000, 4000]));
+	crossfade.start_crossfade();
+
+	let out = samples(&crossfade.apply(frame(&[0, 0, 0, 0, 7])).unwrap());
+	assert_eq!(out, vec![3000, 3000, 2000, 1000, 7]);
+	assert!(!crossfade.is_active());
+}
+
+#[test]
+fn test_crossfade_short_tail_fades_from_silence() {
+	let mut crossfade = Crossfade::new(4.0, 1000, 1);
+	crossfade.feed_previous(&frame(&[4000, 4000]));
+	crossfade.start_crossfade();
+
+	let out = samples(&crossfade.apply(frame(&[0, 0, 0, 0])).unwrap());
+	assert_eq!(out, vec![0, 0, 2000, 1000]);
+}
+
+#[test]
+fn test_crossfade_restarts() {
+	let mut crossfade = Crossfade::new(2.0, 1000, 1);
+	for _ in 0..2 {
+		crossfade.feed_previous(&frame(&[1000, 1000]));
+		crossfade.start_crossfade();
+		assert_eq!(crossfade.remaining(), 2);
+		assert_eq!(samples(&crossfade.apply(frame(&[0, 0, 0])).unwrap()), vec![1000, 500, 0]);
+		assert_eq!(crossfade.remaining(), 0);
+	}
+}
@@ -0,0 +1,150 @@
+use ffmpreg::codecs::PcmDecoder;
+use ffmpreg::container::{WavReader, Y4mReader};
+use ffmpreg::core::{
+	Decoder, Demuxer, Frame, FrameAudio, FrameVideo, StreamFormat, Timebase, Transform, VideoFormat,
+};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::FilterGraph;
+use tempfile::tempdir;
+
+fn video_frame() -> Frame {
+	let mut data = vec![0, 1, 2, 3, 4, 5, 6, 7];
+	data.extend([90, 160, 20, 240]);
+	let video = FrameVideo::new(data, 4, 2, VideoFormat::YUV420);
+	Frame::new_video(video, Timebase::new(1, 30), 0).with_pts(3)
+}
+
+fn audio_frame(samples: &[i16]) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	Frame::new_audio(FrameAudio::new(data, 44100, 1), Timebase::new(1, 44100), 0)
+}
+
+#[test]
+fn test_graph_overlays_a_processed_branch() {
+	let mut graph =
+		FilterGraph::parse("[in]split[a][b];[a]scale=2x2:nearest,negate[s];[b][s]overlay=2:0[out]")
+			.unwrap();
+	let output = graph.apply(video_frame()).unwrap();
+
+	assert_eq!(output.pts, 3);
+	let video = output.video().unwrap();
+	assert_eq!((video.width, video.height), (4, 2));
+	assert_eq!(&video.data[..8], &[0, 1, 255, 253, 4, 5, 251, 249]);
+	assert_eq!(&video.data[8..], &[90, 166, 20, 236]);
+}
+
+#[test]
+fn test_graph_defaults_in_and_out_pads() {
+	let mut graph = FilterGraph::parse("hflip[a]; [a]vflip").unwrap();
+	let output = graph.apply(video_frame()).unwrap();
+	assert_eq!(&output.video().unwrap().data[..8], &[7, 6, 5, 4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn test_graph_amix_averages_branches() {
+	let mut graph =
+		FilterGraph::parse("[in]split[a][b];[a]volume=3[l];[b]volume=1[r];[l][r]amix").unwrap();
+	let output = graph.apply(audio_frame(&[100, -200, 300])).unwrap();
+	let samples = output.audio().unwrap().samples_f32();
+	let samples: Vec<i16> = samples.iter().map(|s| (s * 32768.0).round() as i16).collect();
+	assert_eq!(samples, vec![200, -400, 600]);
+}
+
+#[test]
+fn test_graph_rejects_bad_wiring() {
+	// a pad read twice, a pad nobody reads, a cycle and a bad name
+	assert!(FilterGraph::parse("[in]hflip[a];[in]vflip[out]").is_err());
+	assert!(FilterGraph::parse("[in]split[a][b];[a]hflip[out]").is_err());
+	assert!(FilterGraph::parse("[in][b]overlay[a];[a]split[b][out]").is_err());
+	assert!(FilterGraph::parse("[in]hflip[a-b]").is_err());
+	assert!(FilterGraph::parse("[in]split=3[a][b];[a][b]overlay[out]").is_err());
+	assert!(FilterGraph::parse("[in]hflip,overlay[out]").is_err());
+	assert!(FilterGraph::parse("").is_err());
+}
+
+#[test]
+fn test_graph_reads_further_inputs() {
+	let mut graph = FilterGraph::parse("[in][in1]amix").unwrap();
+	assert_eq!(graph.inputs(), vec![1]);
+	assert!(graph.apply(audio_frame(&[100])).is_err());
+
+	let mut second = vec![audio_frame(&[300, 300])];
+	let format = StreamFormat::Audio { sample_rate: 44100, channels: 1 };
+	let mut graph = graph.with_input(1, format, Box::new(move |_| Ok(second.pop())));
+	let mixed = graph.apply(audio_frame(&[100, -100])).unwrap().audio().unwrap().samples_f32();
+	let mixed: Vec<i16> = mixed.iter().map(|s| (s * 32768.0).round() as i16).collect();
+	assert_eq!(mixed, vec![200, 100]);
+
+	// once in1 runs out, amix passes `in` through on its own
+	let alone = graph.apply(audio_frame(&[100, -100])).unwrap().audio().unwrap().samples_f32();
+	let alone: Vec<i16> = alone.iter().map(|s| (s * 32768.0).round() as i16).collect();
+	assert_eq!(alone, vec![100, -100]);
+}
+
+#[test]
+fn test_transcoder_mixes_a_second_input() {
+	let dir = tempdir().unwrap();
+	let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+	let wav = crate::common::create_test_wav_from_samples;
+	std::fs::write(path("voice.wav"), wav(&[1000; 4000], 8000, 1)).unwrap();
+	std::fs::write(path("music.wav"), wav(&[3000; 2000], 8000, 1)).unwrap();
+
+	Transcoder::new(path("voice.wav"), Some(path("out.wav")), false, vec![])
+		.with_filter_graph("[in][in1]amix".to_string())
+		.with_inputs(vec![path("music.wav")])
+		.run()
+		.unwrap();
+
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(path("out.wav")).unwrap())).unwrap();
+	let mut decoder = PcmDecoder::new(reader.format());
+	let mut samples = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap() {
+			let data = &frame.audio().unwrap().data;
+			samples.extend(data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])));
+		}
+	}
+	assert_eq!(samples.len(), 4000);
+	assert!((samples[0] - 2000).abs() <= 1, "{}", samples[0]);
+	assert_eq!(samples[3999], 1000);
+
+	// extra inputs need a graph that reads each of them
+	let unread = Transcoder::new(path("voice.wav"), Some(path("out.wav")), false, vec![])
+		.with_inputs(vec![path("music.wav")]);
+	assert!(unread.run().is_err());
+	let unread = Transcoder::new(path("voice.wav"), Some(path("out.wav")), false, vec![])
+		.with_filter_graph("volume=0.5".to_string())
+		.with_inputs(vec![path("music.wav")]);
+	assert!(unread.run().is_err());
+}
+
+#[test]
+fn test_transcoder_runs_filter_graph_after_transforms() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["scale=4x4".to_string()],
+	)
+	.with_filter_graph(
+		"[in]split[a][b];[a]pad=8:4:0:0[wide];[b]hflip[m];[wide][m]overlay=4:0".to_string(),
+	)
+	.run()
+	.unwrap();
+
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!((reader.format().width, reader.format().height), (8, 4));
+	let mut frames = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		// the right half is the left half mirrored
+		assert_eq!(packet.data[7], packet.data[0]);
+		frames += 1;
+	}
+	assert_eq!(frames, 3);
+}
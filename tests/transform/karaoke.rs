@@ -0,0 +1,80 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{Karaoke, TransformSpec};
+use std::f64::consts::PI;
+
+fn frame(samples: &[i16], channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio =
+		FrameAudio::new(data, 48000, channels).with_nb_samples(samples.len() / channels as usize);
+	Frame::new_audio(audio, Timebase::new(1, 48000), 0)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+fn tone(freq: f64, amplitude: f64, n: usize) -> f64 {
+	amplitude * (2.0 * PI * freq * n as f64 / 48000.0).sin()
+}
+
+fn rms(samples: impl Iterator<Item = i16>) -> f64 {
+	let values: Vec<f64> = samples.map(|s| s as f64).collect();
+	(values.iter().map(|v| v * v).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+#[test]
+fn test_karaoke_cancels_center_keeps_sides() {
+	// a centered "vocal" at 1 kHz and a guitar at 330 Hz hard left
+	let input: Vec<i16> = (0..48000)
+		.flat_map(|n| {
+			let vocal = tone(1000.0, 8000.0, n);
+			let guitar = tone(330.0, 4000.0, n);
+			[(vocal + guitar) as i16, vocal as i16]
+		})
+		.collect();
+	let output = samples(&Karaoke::new().apply(frame(&input, 2)).unwrap());
+
+	let guitar = rms((0..48000).map(|n| tone(330.0, 4000.0, n) as i16));
+	let left = rms(output.iter().step_by(2).copied());
+	assert!((left - guitar).abs() < guitar * 0.02, "{} vs {}", left, guitar);
+	// both outputs carry the same difference signal
+	assert!(output.chunks(2).all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn test_karaoke_protection_keeps_centered_bass() {
+	let input: Vec<i16> = (0..48000)
+		.flat_map(|n| {
+			let center = (tone(60.0, 6000.0, n) + tone(1000.0, 6000.0, n)) as i16;
+			[center, center]
+		})
+		.collect();
+
+	let plain = samples(&Karaoke::new().apply(frame(&input, 2)).unwrap());
+	assert!(plain.iter().all(|&s| s == 0));
+
+	let mut karaoke = Karaoke::new().with_protection(200.0, 8000.0);
+	let protected = samples(&karaoke.apply(frame(&input, 2)).unwrap());
+	let bass = rms((0..48000).map(|n| tone(60.0, 6000.0, n) as i16));
+	let kept = rms(protected[24000..].iter().step_by(2).copied());
+	// the bass survives and most of the 1 kHz vocal stays gone
+	assert!(kept > bass * 0.85 && kept < bass * 1.15, "{} vs {}", kept, bass);
+}
+
+#[test]
+fn test_karaoke_leaves_mono_alone() {
+	let input = vec![1000i16, -2000, 3000];
+	assert_eq!(samples(&Karaoke::new().apply(frame(&input, 1)).unwrap()), input);
+}
+
+#[test]
+fn test_karaoke_specs() {
+	assert_eq!(TransformSpec::parse("karaoke").unwrap(), TransformSpec::Karaoke { protect: None });
+	assert_eq!(
+		TransformSpec::parse("karaoke=150,7000").unwrap(),
+		TransformSpec::Karaoke { protect: Some((150.0, 7000.0)) }
+	);
+	assert!(TransformSpec::parse("karaoke=150").is_err());
+	assert!(TransformSpec::Karaoke { protect: Some((8000.0, 200.0)) }.build().is_err());
+	assert!(TransformSpec::Karaoke { protect: Some((0.0, 200.0)) }.build().is_err());
+}
@@ -0,0 +1,153 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{Invert, LookaheadLimiter, LoudnessMeter, TransformChain, TransformSpec};
+
+fn frame(samples: &[i16], channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio =
+		FrameAudio::new(data, 48000, channels).with_nb_samples(samples.len() / channels as usize);
+	Frame::new_audio(audio, Timebase::new(1, 48000), 0)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+// runs `input` through in frames of `chunk` samples and drains the tail
+fn run(transform: &mut dyn Transform, input: &[i16], channels: u8, chunk: usize) -> Vec<i16> {
+	let mut output = Vec::new();
+	for part in input.chunks(chunk) {
+		output.extend(samples(&transform.apply(frame(part, channels)).unwrap()));
+	}
+	if let Some(tail) = transform.flush().unwrap() {
+		output.extend(samples(&tail));
+	}
+	output
+}
+
+fn ceiling(db: f64) -> i16 {
+	(32767.0 * 10f64.powf(db / 20.0)).floor() as i16
+}
+
+#[test]
+fn test_lookahead_limiter_catches_first_peak() {
+	// quiet, then a full scale burst straight away
+	let mut input = vec![1000i16; 200];
+	input.extend((0..4800).map(|n| if n % 2 == 0 { i16::MAX } else { i16::MIN }));
+	input.extend(vec![1000i16; 4800]);
+
+	let mut limiter = LookaheadLimiter::new(-1.0);
+	let output = run(&mut limiter, &input, 1, 1024);
+
+	assert_eq!(output.len(), input.len());
+	let peak = output.iter().map(|s| s.unsigned_abs()).max().unwrap();
+	assert!(peak <= ceiling(-1.0) as u16, "peak {}", peak);
+	// the burst itself comes out at the ceiling, not ducked far below it
+	assert!(peak >= ceiling(-1.0) as u16 - 2, "peak {}", peak);
+}
+
+#[test]
+fn test_lookahead_limiter_true_peak() {
+	// a quarter of the rate at 45 degrees: samples at 0.707 of a peak that
+	// falls between them, at full scale
+	let input: Vec<i16> = (0..9600)
+		.map(|n| (32767.0 * (std::f64::consts::PI * (n as f64 / 2.0 + 0.25)).sin()).round() as i16)
+		.collect();
+	let true_peak = |samples: &[i16]| {
+		let mut meter = LoudnessMeter::new();
+		meter.push(frame(samples, 1).audio().unwrap());
+		meter.loudness().true_peak
+	};
+	assert!(true_peak(&input) > -0.1);
+
+	// the samples are under the ceiling, so only true peak limiting touches them
+	assert_eq!(run(&mut LookaheadLimiter::new(-1.0), &input, 1, 1024), input);
+	let output = run(&mut LookaheadLimiter::new(-1.0).with_true_peak(), &input, 1, 1024);
+	assert_eq!(output.len(), input.len());
+	assert!(true_peak(&output) <= -1.0 + 0.05, "{}", true_peak(&output));
+	assert!(true_peak(&output) > -1.5, "{}", true_peak(&output));
+}
+
+#[test]
+fn test_lookahead_limiter_keeps_timing_and_quiet_audio() {
+	let input: Vec<i16> = (0..9600)
+		.map(|n| (8000.0 * (2.0 * std::f64::consts::PI * 440.0 * n as f64 / 48000.0).sin()) as i16)
+		.collect();
+	// frames shorter than the lookahead still line up once flushed
+	let output = run(&mut LookaheadLimiter::new(-1.0).with_lookahead(10.0), &input, 1, 100);
+	assert_eq!(output, input);
+	let output = run(&mut LookaheadLimiter::new(-1.0).with_true_peak(), &input, 1, 100);
+	assert_eq!(output, input);
+}
+
+#[test]
+fn test_lookahead_limiter_ramps_ahead_of_peak() {
+	let mut input = vec![10000i16; 4800];
+	input[2400] = i16::MAX;
+	let output = run(&mut LookaheadLimiter::new(-6.0), &input, 1, 4800);
+
+	assert!(output[2400] <= ceiling(-6.0));
+	// the gain is already coming down a couple of milliseconds early
+	assert!(output[2300] < 10000 && output[2300] > output[2399], "{}", output[2300]);
+	assert_eq!(output[0], 10000);
+}
+
+#[test]
+fn test_lookahead_limiter_links_channels() {
+	let input: Vec<i16> = (0..4800).flat_map(|_| [30000, 3000]).collect();
+	let output = run(&mut LookaheadLimiter::new(-6.0), &input, 2, 960);
+
+	assert_eq!(output.len(), input.len());
+	let (left, right) = (output[4000] as f64, output[4001] as f64);
+	assert!(left <= ceiling(-6.0) as f64);
+	assert!((left / right - 10.0).abs() < 0.05, "{} {}", left, right);
+}
+
+#[test]
+fn test_chain_flush_runs_tail_through_later_transforms() {
+	let mut chain = TransformChain::new();
+	chain.add(Box::new(LookaheadLimiter::new(0.0)));
+	chain.add(Box::new(Invert::new()));
+
+	let input: Vec<i16> = (0..1000).map(|n| n as i16).collect();
+	let output = run(&mut chain, &input, 1, 300);
+	assert_eq!(output, input.iter().map(|s| -s).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_lookahead_limiter_specs() {
+	assert_eq!(
+		TransformSpec::parse("lookahead_limiter").unwrap(),
+		TransformSpec::LookaheadLimiter { ceiling_db: -1.0, lookahead_ms: 5.0 }
+	);
+	assert_eq!(
+		TransformSpec::parse("brickwall=-0.3,2").unwrap(),
+		TransformSpec::LookaheadLimiter { ceiling_db: -0.3, lookahead_ms: 2.0 }
+	);
+	assert!(TransformSpec::parse("brickwall=loud").is_err());
+	assert!(TransformSpec::LookaheadLimiter { ceiling_db: 1.0, lookahead_ms: 5.0 }.build().is_err());
+	assert!(TransformSpec::LookaheadLimiter { ceiling_db: -1.0, lookahead_ms: 0.0 }.build().is_err());
+}
+
+#[test]
+fn test_lookahead_limiter_wav_output_keeps_length() {
+	let dir = tempfile::tempdir().unwrap();
+	let input = dir.path().join("loud.wav");
+	let output = dir.path().join("limited.wav");
+	let samples: Vec<i16> = (0..48000).map(|n| if n % 100 < 50 { 32000 } else { -32000 }).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 48000, 1)).unwrap();
+
+	ffmpreg::transcode::Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["brickwall=-1".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let bytes = std::fs::read(&output).unwrap();
+	let data = &bytes[44..];
+	assert_eq!(data.len(), samples.len() * 2);
+	let peak = data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]]).unsigned_abs()).max().unwrap();
+	assert!(peak <= ceiling(-1.0) as u16, "peak {}", peak);
+}
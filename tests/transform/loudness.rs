@@ -0,0 +1,135 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{Loudness, LoudnessMeter, Loudnorm, TransformSpec, parse_transform};
+
+fn sine(amplitude_db: f64, seconds: f64, channels: u8) -> FrameAudio {
+	let rate = 48000;
+	let amplitude = 10f64.powf(amplitude_db / 20.0) * 32767.0;
+	let mut data = Vec::new();
+	for n in 0..(seconds * rate as f64) as usize {
+		let value = (amplitude * (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / rate as f64).sin())
+			.round() as i16;
+		for _ in 0..channels {
+			data.extend_from_slice(&value.to_le_bytes());
+		}
+	}
+	FrameAudio::new(data, rate, channels)
+}
+
+#[test]
+fn test_meter_matches_ebu_reference_tone() {
+	// EBU Tech 3341 case 1: stereo 1 kHz at -23 dBFS reads -23 LUFS
+	let mut meter = LoudnessMeter::new();
+	meter.push(&sine(-23.0, 5.0, 2));
+	let loudness = meter.loudness();
+	assert!((loudness.integrated - -23.0).abs() < 0.1, "{}", loudness.integrated);
+	assert!((loudness.true_peak - -23.0).abs() < 0.2, "{}", loudness.true_peak);
+}
+
+#[test]
+fn test_meter_loudness_range() {
+	// EBU Tech 3342 case 1, halved: -20 dBFS then -30 dBFS reads 10 LU
+	let mut meter = LoudnessMeter::new();
+	meter.push(&sine(-20.0, 10.0, 2));
+	meter.push(&sine(-30.0, 10.0, 2));
+	let range = meter.loudness_range();
+	assert!((range - 10.0).abs() < 1.0, "{}", range);
+
+	// a steady tone has none, nor does anything shorter than a 3s window
+	let mut meter = LoudnessMeter::new();
+	meter.push(&sine(-23.0, 10.0, 2));
+	assert!(meter.loudness_range() < 0.1);
+	let mut meter = LoudnessMeter::new();
+	meter.push(&sine(-23.0, 2.0, 2));
+	assert_eq!(meter.loudness_range(), 0.0);
+}
+
+#[test]
+fn test_meter_integrated_of_two_levels() {
+	// both halves are above the relative gate, so the result is the mean of
+	// their energies: 10 log10((10^-2 + 10^-3) / 2) = -22.60 LUFS
+	let mut meter = LoudnessMeter::new();
+	meter.push(&sine(-20.0, 10.0, 2));
+	meter.push(&sine(-30.0, 10.0, 2));
+	assert!((meter.loudness().integrated - -22.60).abs() < 0.1, "{}", meter.loudness().integrated);
+
+	// and a level 12 LU down is gated out altogether
+	let mut meter = LoudnessMeter::new();
+	meter.push(&sine(-20.0, 10.0, 2));
+	meter.push(&sine(-32.0, 2.0, 2));
+	assert!((meter.loudness().integrated - -20.0).abs() < 0.1, "{}", meter.loudness().integrated);
+}
+
+#[test]
+fn test_meter_gates_silence() {
+	let mut meter = LoudnessMeter::new();
+	meter.push(&FrameAudio::new(vec![0; 48000 * 4], 48000, 2));
+	assert_eq!(meter.loudness().integrated, f64::NEG_INFINITY);
+
+	// the silent blocks are gated out; only the few blocks straddling the
+	// start of the tone pull the result down a little
+	meter.push(&sine(-23.0, 3.0, 2));
+	assert!((meter.loudness().integrated - -23.0).abs() < 0.5);
+}
+
+#[test]
+fn test_loudnorm_reaches_target() {
+	let measured = Loudness { integrated: -30.0, true_peak: -30.0 };
+	let mut loudnorm = Loudnorm::new(-16.0, -1.5, measured);
+	assert!((loudnorm.gain_db() - 14.0).abs() < 1e-9);
+
+	let frame = Frame::new_audio(sine(-30.0, 3.0, 2), Timebase::new(1, 48000), 0);
+	let output = loudnorm.apply(frame).unwrap();
+
+	let mut meter = LoudnessMeter::new();
+	meter.push(output.audio().unwrap());
+	assert!((meter.loudness().integrated - -16.0).abs() < 0.1);
+}
+
+#[test]
+fn test_loudnorm_limits_true_peak() {
+	let measured = Loudness { integrated: -23.0, true_peak: -23.0 };
+	let mut loudnorm = Loudnorm::new(-3.0, -6.0, measured);
+	let frame = Frame::new_audio(sine(-23.0, 1.0, 1), Timebase::new(1, 48000), 0);
+	let output = loudnorm.apply(frame).unwrap();
+
+	let ceiling = 10f64.powf(-6.0 / 20.0) * 32768.0;
+	let peak = output
+		.audio()
+		.unwrap()
+		.data
+		.chunks(2)
+		.map(|c| (i16::from_le_bytes([c[0], c[1]]) as f64).abs())
+		.fold(0.0, f64::max);
+	assert!(peak <= ceiling + 1.0, "{} > {}", peak, ceiling);
+}
+
+#[test]
+fn test_loudnorm_spec_needs_measurement() {
+	assert_eq!(
+		TransformSpec::parse("loudnorm").unwrap(),
+		TransformSpec::Loudnorm { target_lufs: -16.0, true_peak: -1.5, measured: None }
+	);
+	assert!(parse_transform("loudnorm=-16").is_err());
+	assert!(parse_transform("loudnorm=-16,1").is_err());
+}
+
+#[test]
+fn test_loudnorm_limits_inter_sample_peaks() {
+	// samples at 0.707 of full scale, peaking between them at 0 dBTP
+	let data: Vec<u8> = (0..48000)
+		.flat_map(|n| {
+			((32767.0 * (std::f64::consts::PI * (n as f64 / 2.0 + 0.25)).sin()).round() as i16)
+				.to_le_bytes()
+		})
+		.collect();
+	let frame = Frame::new_audio(FrameAudio::new(data, 48000, 1), Timebase::new(1, 48000), 0);
+	let measured = Loudness { integrated: -16.0, true_peak: 0.0 };
+	let mut loudnorm = Loudnorm::new(-16.0, -1.0, measured);
+
+	let mut meter = LoudnessMeter::new();
+	meter.push(loudnorm.apply(frame).unwrap().audio().unwrap());
+	let tail = loudnorm.flush().unwrap().unwrap();
+	meter.push(tail.audio().unwrap());
+	assert_eq!(tail.audio().unwrap().format, ffmpreg::core::SampleFormat::S16);
+	assert!(meter.loudness().true_peak <= -1.0 + 0.05, "{}", meter.loudness().true_peak);
+}
@@ -1,2 +1,21 @@
+mod adelay;
+mod astats;
 mod chain;
+mod channel_mixer;
+mod cleanup;
+mod expr;
+mod fade;
+mod graph;
+mod karaoke;
+mod limiter;
+mod loudness;
+mod modulation;
+mod negotiate;
 mod normalize;
+mod pan;
+mod registry;
+mod silence_detect;
+mod spec;
+mod speed;
+mod stream;
+mod video;
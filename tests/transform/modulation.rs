@@ -0,0 +1,94 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{Chorus, Flanger, Lfo, LfoShape, Tremolo, Vibrato, parse_transform};
+
+fn create_test_frame(samples: Vec<i16>, sample_rate: u32) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, sample_rate, 1);
+	Frame::new_audio(audio, Timebase::new(1, sample_rate), 0)
+}
+
+fn extract_samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_lfo_sine_and_triangle() {
+	let mut sine = Lfo::new(1.0);
+	let values: Vec<f32> = (0..4).map(|_| sine.next(4)).collect();
+	let expected = [0.0, 1.0, 0.0, -1.0];
+	for (value, expected) in values.iter().zip(expected) {
+		assert!((value - expected).abs() < 1e-5);
+	}
+
+	let mut triangle = Lfo::new(1.0).with_shape(LfoShape::Triangle);
+	let values: Vec<f32> = (0..8).map(|_| triangle.next(8)).collect();
+	assert_eq!(values, vec![0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -0.5]);
+}
+
+#[test]
+fn test_lfo_unipolar_and_phase() {
+	let mut lfo = Lfo::new(1.0).with_phase(0.75);
+	assert!(lfo.next_unipolar(4).abs() < 1e-5);
+	assert!((lfo.next_unipolar(4) - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn test_tremolo_follows_lfo() {
+	let mut tremolo = Tremolo::new(1.0, 1.0);
+	let frame = tremolo.apply(create_test_frame(vec![1000; 4], 4)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![500, 0, 500, 1000]);
+
+	let mut flat = Tremolo::new(1.0, 0.0);
+	let frame = flat.apply(create_test_frame(vec![1000; 4], 4)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![1000; 4]);
+}
+
+#[test]
+fn test_vibrato_keeps_constant_signal() {
+	let mut vibrato = Vibrato::new(5.0, 2.0);
+	let frame = vibrato.apply(create_test_frame(vec![1000; 400], 8000)).unwrap();
+	let samples = extract_samples(&frame);
+	// once the delay line has filled, a dc input cannot wobble
+	assert!(samples[100..].iter().all(|&s| s == 1000));
+}
+
+#[test]
+fn test_chorus_mix_zero_is_dry() {
+	let input: Vec<i16> = (0..200).map(|i| ((i * 97) % 2000 - 1000) as i16).collect();
+	let mut chorus = Chorus::new(1.5, 3.0, 0.0);
+	let frame = chorus.apply(create_test_frame(input.clone(), 8000)).unwrap();
+	assert_eq!(extract_samples(&frame), input);
+}
+
+#[test]
+fn test_chorus_delays_wet_signal() {
+	let mut input = vec![0i16; 400];
+	input[0] = 10000;
+	let mut chorus = Chorus::new(0.0, 0.0, 0.5).with_delay(10.0);
+	let samples = extract_samples(&chorus.apply(create_test_frame(input, 8000)).unwrap());
+	assert_eq!(samples[0], 5000);
+	assert_eq!(samples[80], 5000);
+	assert!(samples[1..80].iter().all(|&s| s == 0));
+}
+
+#[test]
+fn test_flanger_state_carries_across_frames() {
+	let mut flanger = Flanger::default();
+	let mut impulse = vec![0i16; 64];
+	impulse[0] = 10000;
+	let first = extract_samples(&flanger.apply(create_test_frame(impulse, 8000)).unwrap());
+	let second = extract_samples(&flanger.apply(create_test_frame(vec![0; 64], 8000)).unwrap());
+	assert_eq!(first[0], 5000);
+	assert!(first[1..].iter().any(|&s| s != 0));
+	assert!(second.iter().any(|&s| s != 0));
+}
+
+#[test]
+fn test_parse_modulation_transforms() {
+	for spec in
+		["tremolo", "tremolo=5,0.5", "vibrato=6,1", "chorus=1.5,3,0.4", "flanger=0.5,2,0.6,0.5"]
+	{
+		assert!(parse_transform(spec).is_ok(), "{}", spec);
+	}
+	assert!(parse_transform("chorus=fast").is_err());
+}
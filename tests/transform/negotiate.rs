@@ -0,0 +1,139 @@
+use ffmpreg::container::{WavReader, Y4mReader};
+use ffmpreg::core::{StreamFormat, Transform, VideoFormat};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::{ColorConvert, FilterGraph, TransformChain, TransformSpec};
+use tempfile::tempdir;
+
+fn chain(specs: &[&str]) -> TransformChain {
+	TransformChain::from_specs(specs.iter().map(|spec| TransformSpec::parse(spec).unwrap())).unwrap()
+}
+
+const STEREO_44K: StreamFormat = StreamFormat::Audio { sample_rate: 44100, channels: 2 };
+const HD_420: StreamFormat =
+	StreamFormat::Video { width: 1920, height: 1080, format: VideoFormat::YUV420 };
+
+#[test]
+fn test_negotiate_audio_chain() {
+	let formats = chain(&["gain=2", "resample=48000", "mono"]).negotiate(STEREO_44K).unwrap();
+	assert_eq!(formats, StreamFormat::Audio { sample_rate: 48000, channels: 1 });
+
+	let swapped = chain(&["channelmap=1,0"]).negotiate(STEREO_44K).unwrap();
+	assert_eq!(swapped, STEREO_44K);
+	let matrix = chain(&["matrix=0.5,0.5|1,0|0,1"]).negotiate(STEREO_44K).unwrap();
+	assert_eq!(matrix, StreamFormat::Audio { sample_rate: 44100, channels: 3 });
+}
+
+#[test]
+fn test_negotiate_rejects_impossible_audio_chain() {
+	// the matrix wants three inputs but the mixer before it leaves one
+	assert!(chain(&["mono", "matrix=1,0,0"]).negotiate(STEREO_44K).is_err());
+	assert!(chain(&["channelmap=2,0"]).negotiate(STEREO_44K).is_err());
+}
+
+#[test]
+fn test_negotiate_video_chain() {
+	let formats = chain(&["scale=640x360", "rotate=90", "crop=200:300", "pad=400:400"])
+		.negotiate(HD_420)
+		.unwrap();
+	assert_eq!(formats, StreamFormat::Video { width: 400, height: 400, format: VideoFormat::YUV420 });
+
+	let converted = chain(&["format=yuv444p", "scale=64x48:fit"]).negotiate(HD_420).unwrap();
+	assert_eq!(converted, StreamFormat::Video { width: 64, height: 48, format: VideoFormat::YUV444 });
+}
+
+#[test]
+fn test_negotiate_rejects_impossible_video_chain() {
+	// a crop larger than the frame is clamped; one starting outside it is not
+	assert!(chain(&["scale=320x180", "crop=640:360:0:0"]).negotiate(HD_420).is_ok());
+	assert!(chain(&["scale=320x180", "crop=64:64:400:0"]).negotiate(HD_420).is_err());
+	assert!(chain(&["scale=320x180", "pad=160:90"]).negotiate(HD_420).is_err());
+	assert!(chain(&["format=yuv422p", "rotate=90"]).negotiate(HD_420).is_err());
+
+	let mut rgb = TransformChain::new();
+	rgb.add(Box::new(ColorConvert::new(VideoFormat::RGB24)));
+	rgb.add(TransformSpec::parse("hflip").unwrap().build().unwrap());
+	assert!(rgb.negotiate(HD_420).is_err());
+}
+
+#[test]
+fn test_negotiate_skips_other_kind() {
+	assert_eq!(chain(&["scale=64x64", "v:rotate=90"]).negotiate(STEREO_44K).unwrap(), STEREO_44K);
+	assert_eq!(chain(&["a:mono"]).negotiate(HD_420).unwrap(), HD_420);
+	let mono = chain(&["a:mono"]).negotiate(STEREO_44K).unwrap();
+	assert_eq!(mono, StreamFormat::Audio { sample_rate: 44100, channels: 1 });
+}
+
+#[test]
+fn test_negotiate_expressions_and_graphs() {
+	let halved = chain(&["scale=w/2:h/2"]).negotiate(HD_420).unwrap();
+	assert_eq!(halved, StreamFormat::Video { width: 960, height: 540, format: VideoFormat::YUV420 });
+
+	let graph =
+		FilterGraph::parse("[in]split[a][b];[a]scale=320x180[small];[b][small]overlay=16:16[out]")
+			.unwrap();
+	assert_eq!(graph.negotiate(HD_420).unwrap(), HD_420);
+
+	let mixed = FilterGraph::parse("[in]split[a][b];[a]resample=48000[c];[b][c]amix[out]").unwrap();
+	assert!(mixed.negotiate(STEREO_44K).is_err());
+}
+
+#[test]
+fn test_transcoder_writes_negotiated_wav_header() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let samples: Vec<i16> = (0..2000).map(|n| (n % 200) as i16 * 50).collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&samples, 44100, 2)).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["resample=22050".to_string(), "mono".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!(reader.format().sample_rate, 22050);
+	assert_eq!(reader.format().channels, 1);
+}
+
+#[test]
+fn test_transcoder_rejects_impossible_chain_before_writing() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	let result = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["scale=4x4".to_string(), "crop=2:2:6:6".to_string()],
+	)
+	.run();
+	assert!(result.is_err());
+	assert!(!output.exists());
+}
+
+#[test]
+fn test_transcoder_writes_negotiated_y4m_header() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["scale=8x4".to_string(), "rotate=90".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!((reader.format().width, reader.format().height), (4, 8));
+}
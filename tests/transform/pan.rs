@@ -0,0 +1,65 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{Balance, Pan, parse_transform};
+
+fn create_test_frame(samples: Vec<i16>, channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, 44100, channels);
+	Frame::new_audio(audio, Timebase::new(1, 44100), 0)
+}
+
+fn extract_samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_pan_center_is_constant_power() {
+	let (left, right) = Pan::new(0.0).gains();
+	assert!((left - right).abs() < 1e-6);
+	assert!((left * left + right * right - 1.0).abs() < 1e-6);
+
+	let (left, right) = Pan::new(-1.0).gains();
+	assert!((left - 1.0).abs() < 1e-6 && right.abs() < 1e-6);
+}
+
+#[test]
+fn test_pan_mono_to_stereo() {
+	let mut pan = Pan::new(1.0);
+	let frame = pan.apply(create_test_frame(vec![1000, -2000], 1)).unwrap();
+	let audio = frame.audio().unwrap();
+	assert_eq!(audio.channels, 2);
+	assert_eq!(audio.nb_samples, 2);
+	assert_eq!(extract_samples(&frame), vec![0, 1000, 0, -2000]);
+}
+
+#[test]
+fn test_pan_folds_stereo_input() {
+	let mut pan = Pan::new(-1.0);
+	let frame = pan.apply(create_test_frame(vec![1000, 3000], 2)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![2000, 0]);
+}
+
+#[test]
+fn test_balance_attenuates_one_side() {
+	let mut balance = Balance::new(0.5);
+	let frame = balance.apply(create_test_frame(vec![1000, 1000, -400, 400], 2)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![500, 1000, -200, 400]);
+
+	let mut balance = Balance::new(-1.0);
+	let frame = balance.apply(create_test_frame(vec![1000, 1000], 2)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![1000, 0]);
+}
+
+#[test]
+fn test_balance_ignores_mono() {
+	let mut balance = Balance::new(0.5);
+	let frame = balance.apply(create_test_frame(vec![1000], 1)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![1000]);
+}
+
+#[test]
+fn test_parse_pan_and_balance() {
+	assert_eq!(parse_transform("pan=-0.3").unwrap().name(), "pan");
+	assert_eq!(parse_transform("balance=0.2").unwrap().name(), "balance");
+	assert!(parse_transform("pan").is_err());
+	assert!(parse_transform("pan=2").is_err());
+}
@@ -0,0 +1,88 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::io::{IoError, IoResult};
+use ffmpreg::transform::{TransformChain, TransformSpec, parse_transform, registry};
+
+// the registry is process wide, so every test registers its own names
+
+struct Offset(i16);
+
+impl Transform for Offset {
+	fn apply(&mut self, mut frame: Frame) -> IoResult<Frame> {
+		if let Some(audio) = frame.audio_mut() {
+			let samples: Vec<i16> = audio
+				.data
+				.chunks(2)
+				.map(|c| i16::from_le_bytes([c[0], c[1]]).saturating_add(self.0))
+				.collect();
+			audio.data = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+		}
+		Ok(frame)
+	}
+
+	fn name(&self) -> &'static str {
+		"offset"
+	}
+}
+
+fn offset_factory(args: Option<&str>) -> IoResult<Box<dyn Transform>> {
+	let amount = args
+		.unwrap_or("1")
+		.parse()
+		.map_err(|_| IoError::invalid_data("offset takes a whole number"))?;
+	Ok(Box::new(Offset(amount)))
+}
+
+fn frame(samples: &[i16]) -> Frame {
+	let data = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	Frame::new_audio(FrameAudio::new(data, 44100, 1), Timebase::new(1, 44100), 0)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_registered_transform_resolves_by_name() {
+	registry::register("test_offset", offset_factory).unwrap();
+	assert!(registry::is_registered("test_offset"));
+	assert!(registry::names().contains(&"test_offset".to_string()));
+
+	let mut bare = parse_transform("test_offset").unwrap();
+	assert_eq!(samples(&bare.apply(frame(&[10, 20])).unwrap()), vec![11, 21]);
+
+	let spec = TransformSpec::parse("test_offset=5").unwrap();
+	assert_eq!(spec, TransformSpec::Custom { name: "test_offset".into(), args: Some("5".into()) });
+
+	// custom names mix with built-in ones, stream prefixes and expressions
+	let specs = TransformSpec::parse_list("gain=2,a:test_offset=-3,test_offset=n").unwrap();
+	let mut chain = TransformChain::from_specs(specs).unwrap();
+	assert_eq!(samples(&chain.apply(frame(&[10])).unwrap()), vec![17]);
+	assert_eq!(samples(&chain.apply(frame(&[10])).unwrap()), vec![18]);
+
+	assert!(parse_transform("test_offset=abc").is_err());
+	assert!(registry::unregister("test_offset"));
+	assert!(parse_transform("test_offset").is_err());
+}
+
+#[test]
+fn test_register_refuses_taken_and_invalid_names() {
+	assert!(registry::register("gain", offset_factory).is_err());
+	assert!(registry::register("scale", offset_factory).is_err());
+	assert!(registry::register("9lives", offset_factory).is_err());
+	assert!(registry::register("has=sign", offset_factory).is_err());
+
+	registry::register("test_twice", offset_factory).unwrap();
+	assert!(registry::register("test_twice", offset_factory).is_err());
+	assert!(registry::unregister("test_twice"));
+	assert!(!registry::unregister("test_twice"));
+}
+
+#[test]
+fn test_registered_transform_accepts_closures() {
+	let step = 7;
+	registry::register("test_step", move |_| Ok(Box::new(Offset(step)) as Box<dyn Transform>))
+		.unwrap();
+	let mut transform = TransformSpec::parse("test_step").unwrap().build().unwrap();
+	assert_eq!(samples(&transform.apply(frame(&[0])).unwrap()), vec![7]);
+	registry::unregister("test_step");
+}
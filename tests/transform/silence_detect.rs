@@ -0,0 +1,80 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{SilenceDetect, SilenceRange, TransformSpec};
+
+fn create_test_frame(samples: Vec<i16>, channels: u8, pts: i64) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, 10, channels);
+	Frame::new_audio(audio, Timebase::new(1, 10), 0).with_pts(pts)
+}
+
+#[test]
+fn test_silence_detect_passes_audio_through() {
+	let mut detect = SilenceDetect::default();
+	let samples = vec![0, 1000, -1000, 0];
+	let frame = detect.apply(create_test_frame(samples.clone(), 1, 0)).unwrap();
+	let output: Vec<i16> =
+		frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect();
+	assert_eq!(output, samples);
+}
+
+#[test]
+fn test_silence_detect_ranges_across_frames() {
+	// 10 Hz so every sample is 0.1s
+	let mut detect = SilenceDetect::new(-40.0, 0.3);
+	detect.apply(create_test_frame(vec![5000, 0, 0, 0, 0], 1, 0)).unwrap();
+	detect.apply(create_test_frame(vec![0, 5000, 0, 0, 5000], 1, 5)).unwrap();
+	// a two sample gap is shorter than the minimum and is dropped
+	assert_eq!(detect.ranges(), &[SilenceRange { start: 0.1, end: 0.6 }]);
+
+	detect.apply(create_test_frame(vec![0, 0, 0, 0], 1, 10)).unwrap();
+	let ranges = detect.close();
+	assert_eq!(ranges.len(), 2);
+	assert!((ranges[1].start - 1.0).abs() < 1e-9 && (ranges[1].end - 1.4).abs() < 1e-9);
+}
+
+#[test]
+fn test_silence_detect_needs_every_channel_quiet() {
+	let mut detect = SilenceDetect::new(-40.0, 0.0);
+	detect.apply(create_test_frame(vec![0, 0, 0, 9000, 0, 0], 2, 0)).unwrap();
+	let ranges = detect.close().to_vec();
+	assert_eq!(ranges.len(), 2);
+	assert!((ranges[0].end - 0.1).abs() < 1e-9);
+	assert!((ranges[1].start - 0.2).abs() < 1e-9);
+}
+
+#[test]
+fn test_silence_detect_report() {
+	let mut detect = SilenceDetect::new(-40.0, 0.1);
+	detect.apply(create_test_frame(vec![0, 0, 9000], 1, 0)).unwrap();
+	detect.close();
+	assert_eq!(
+		detect.to_json(),
+		"{\"silence\":[{\"start\":0.000,\"end\":0.200,\"duration\":0.200}]}"
+	);
+	assert_eq!(detect.to_text(), "silence_start: 0.000 silence_end: 0.200 silence_duration: 0.200\n");
+}
+
+#[test]
+fn test_parse_silencedetect() {
+	assert_eq!(
+		TransformSpec::parse("silencedetect").unwrap(),
+		TransformSpec::SilenceDetect { noise_db: -60.0, min_duration: 2.0, json: false }
+	);
+	assert_eq!(
+		TransformSpec::parse("silencedetect=-50,0.5,json").unwrap(),
+		TransformSpec::SilenceDetect { noise_db: -50.0, min_duration: 0.5, json: true }
+	);
+	assert!(TransformSpec::parse("silencedetect=quiet").is_err());
+}
+
+#[test]
+fn test_silence_detect_tags_frames_ending_in_silence() {
+	let mut detect = SilenceDetect::new(-40.0, 0.3);
+	let loud = detect.apply(create_test_frame(vec![5000, 5000], 1, 0)).unwrap();
+	assert!(loud.metadata.is_empty());
+	let quiet = detect.apply(create_test_frame(vec![5000, 0, 0], 1, 2)).unwrap();
+	let start = quiet.metadata.number("silence_start").unwrap();
+	assert!((start - 0.3).abs() < 1e-9);
+	let back = detect.apply(create_test_frame(vec![5000], 1, 5)).unwrap();
+	assert_eq!(back.metadata.number("silence_start"), None);
+}
@@ -0,0 +1,48 @@
+use ffmpreg::core::{Frame, FrameAudio, Timebase, Transform};
+use ffmpreg::transform::{TransformChain, TransformSpec};
+
+fn create_test_frame(samples: Vec<i16>, channels: u8) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, 44100, channels);
+	Frame::new_audio(audio, Timebase::new(1, 44100), 0)
+}
+
+fn extract_samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_parse_into_typed_specs() {
+	assert_eq!(TransformSpec::parse("gain=2").unwrap(), TransformSpec::Gain(2.0));
+	assert_eq!(
+		TransformSpec::parse("resample=48000").unwrap(),
+		TransformSpec::Resample { rate: 48000 }
+	);
+	assert_eq!(
+		TransformSpec::parse("tremolo=4").unwrap(),
+		TransformSpec::Tremolo { rate: 4.0, depth: 0.5 }
+	);
+	assert_eq!(
+		TransformSpec::parse("matrix=1,0|0.5,0.5").unwrap(),
+		TransformSpec::Matrix(vec![vec![1.0, 0.0], vec![0.5, 0.5]])
+	);
+	assert_eq!(TransformSpec::parse("mono").unwrap(), TransformSpec::Mono);
+	assert!(TransformSpec::parse("reverse").is_err());
+}
+
+#[test]
+fn test_build_checks_typed_values() {
+	assert!(TransformSpec::Speed(0.0).build().is_err());
+	assert!(TransformSpec::Pan(1.5).build().is_err());
+	assert!(TransformSpec::Resample { rate: 0 }.build().is_err());
+	assert!(TransformSpec::Matrix(vec![]).build().is_err());
+	assert!(TransformSpec::Balance(-0.5).build().is_ok());
+}
+
+#[test]
+fn test_chain_from_specs() {
+	let mut chain =
+		TransformChain::from_specs([TransformSpec::Gain(2.0), TransformSpec::Stereo]).unwrap();
+	let result = chain.apply(create_test_frame(vec![100, -300], 1)).unwrap();
+	assert_eq!(extract_samples(&result), vec![200, 200, -600, -600]);
+}
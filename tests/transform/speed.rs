@@ -0,0 +1,175 @@
+use ffmpreg::container::Y4mReader;
+use ffmpreg::core::{Demuxer, Frame, FrameAudio, FrameVideo, Timebase, Transform, VideoFormat};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::{FrameRateConverter, Speed, TransformSpec, parse_transform};
+use tempfile::tempdir;
+
+fn create_audio_frame(samples: Vec<i16>, sample_rate: u32, channels: u8, pts: i64) -> Frame {
+	let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+	let audio = FrameAudio::new(data, sample_rate, channels);
+	Frame::new_audio(audio, Timebase::new(1, sample_rate), 0).with_pts(pts)
+}
+
+fn create_video_frame(pts: i64, timebase: Timebase) -> Frame {
+	let video = FrameVideo::new(vec![pts as u8; 6], 2, 2, VideoFormat::YUV420);
+	Frame::new_video(video, timebase, 0).with_pts(pts)
+}
+
+fn extract_samples(frame: &Frame) -> Vec<i16> {
+	frame.audio().unwrap().data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_speed_audio_double() {
+	let mut speed = Speed::new(2.0);
+	let frame =
+		speed.apply(create_audio_frame(vec![0, 10, 20, 30, 40, 50, 60, 70], 8000, 1, 80)).unwrap();
+
+	assert_eq!(extract_samples(&frame), vec![0, 20, 40, 60]);
+	assert_eq!(frame.audio().unwrap().nb_samples, 4);
+	assert_eq!(frame.audio().unwrap().sample_rate, 8000);
+	assert_eq!(frame.pts, 40);
+}
+
+#[test]
+fn test_speed_audio_half_keeps_channels_interleaved() {
+	let mut speed = Speed::new(0.5);
+	let frame = speed.apply(create_audio_frame(vec![0, 100, 10, 110], 8000, 2, 0)).unwrap();
+	assert_eq!(extract_samples(&frame), vec![0, 100, 5, 105, 10, 110, 10, 110]);
+}
+
+#[test]
+fn test_speed_video_rescales_timebase() {
+	let mut speed = Speed::new(2.0);
+	let frame = speed.apply(create_video_frame(3, Timebase::new(1001, 30000))).unwrap();
+	assert_eq!(frame.pts, 3);
+	assert_eq!(frame.timebase, Timebase::new(1001, 60000));
+
+	let mut speed = Speed::new(1.5);
+	let frame = speed.apply(create_video_frame(3, Timebase::new(1, 30))).unwrap();
+	assert_eq!(frame.timebase, Timebase::new(1, 45));
+}
+
+#[test]
+fn test_speed_composes_with_frame_rate_converter() {
+	let mut speed = Speed::new(2.0);
+	let mut converter = FrameRateConverter::new(30, 1, 30, 1);
+
+	let mut video = Vec::new();
+	for pts in 0..4 {
+		let frame = speed.apply(create_video_frame(pts, Timebase::new(1, 30))).unwrap();
+		video.extend(converter.process(frame).unwrap());
+	}
+	video.extend(converter.flush().unwrap());
+
+	// 4 frames at 2x last 2/30 s: every other frame survives
+	let kept: Vec<u8> = video.iter().map(|f| f.video().unwrap().data[0]).collect();
+	assert_eq!(kept, vec![0, 2]);
+	assert_eq!(video[1].pts, 1);
+	assert_eq!(video[1].timebase, Timebase::new(1, 30));
+
+	// the same 4/30 s of audio also shrinks to 2/30 s
+	let audio = speed.apply(create_audio_frame(vec![0; 40], 300, 1, 0)).unwrap();
+	assert_eq!(audio.audio().unwrap().nb_samples as f64 / 300.0, video.len() as f64 / 30.0);
+}
+
+#[test]
+fn test_frame_rate_converter_doubles() {
+	let mut converter = FrameRateConverter::double();
+	let mut out = Vec::new();
+	for pts in 0..3 {
+		out.extend(converter.process(create_video_frame(pts, Timebase::new(1, 30))).unwrap());
+	}
+	out.extend(converter.flush().unwrap());
+
+	let kept: Vec<u8> = out.iter().map(|f| f.video().unwrap().data[0]).collect();
+	assert_eq!(kept, vec![0, 0, 1, 1, 2, 2]);
+}
+
+#[test]
+fn test_parse_speed() {
+	assert!(parse_transform("speed=1.25").is_ok());
+	assert!(parse_transform("speed").is_err());
+	assert!(parse_transform("speed=0").is_err());
+	assert!(parse_transform("speed=fast").is_err());
+}
+
+#[test]
+fn test_frame_rate_converter_caps_duplicates() {
+	let mut converter = FrameRateConverter::new(30, 1, 30, 1);
+	let timebase = Timebase::new(1, 30);
+	assert_eq!(converter.process(create_video_frame(0, timebase)).unwrap().len(), 0);
+	// a one second gap repeats the frame before it only so often
+	let out = converter.process(create_video_frame(30, timebase)).unwrap();
+	assert_eq!(out.len(), 11);
+	assert!(out.iter().all(|f| f.video().unwrap().data[0] == 0));
+	// and the next frame lands on its own slot
+	let out = converter.process(create_video_frame(31, timebase)).unwrap();
+	assert_eq!(out.iter().map(|f| f.pts).collect::<Vec<_>>(), vec![30]);
+}
+
+#[test]
+fn test_parse_fps() {
+	assert_eq!(TransformSpec::parse("fps=24").unwrap(), TransformSpec::Fps { num: 24, den: 1 });
+	assert_eq!(
+		TransformSpec::parse("framerate=30000/1001").unwrap(),
+		TransformSpec::Fps { num: 30000, den: 1001 }
+	);
+	assert!(TransformSpec::parse("fps").is_err());
+	assert!(TransformSpec::parse("fps=fast").is_err());
+	assert!(TransformSpec::parse("fps=0").unwrap().build().is_err());
+	// only a transcode can drop and repeat frames
+	assert!(TransformSpec::parse("fps=24").unwrap().build().is_err());
+}
+
+// frame rate and first luma byte of every frame of a y4m transcode
+fn transcode_y4m(transforms: &str) -> ((u32, u32), Vec<u8>) {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	// the three frames start with luma 0, 30 and 60
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec![transforms.to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let rate = (reader.format().framerate_num, reader.format().framerate_den);
+	let mut firsts = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		firsts.push(packet.data[0]);
+	}
+	(rate, firsts)
+}
+
+#[test]
+fn test_speed_and_fps_retime_y4m() {
+	// every frame is kept and the header runs twice as fast
+	assert_eq!(transcode_y4m("speed=2"), ((60, 1), vec![0, 30, 60]));
+	// back at 30 fps every other frame goes
+	assert_eq!(transcode_y4m("speed=2,fps=30"), ((30, 1), vec![0, 60]));
+	assert_eq!(transcode_y4m("fps=60"), ((60, 1), vec![0, 0, 30, 30, 60, 60]));
+	assert_eq!(transcode_y4m("speed=0.5"), ((15, 1), vec![0, 30, 60]));
+}
+
+#[test]
+fn test_fps_has_to_come_last() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let result = Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(dir.path().join("out.y4m").to_str().unwrap().to_string()),
+		false,
+		vec!["fps=24,negate".to_string()],
+	)
+	.run();
+	assert!(result.is_err());
+}
@@ -0,0 +1,106 @@
+use ffmpreg::codecs::PcmDecoder;
+use ffmpreg::container::WavReader;
+use ffmpreg::core::{Decoder, Demuxer, Frame, FrameAudio, FrameKind, FrameVideo};
+use ffmpreg::core::{Timebase, Transform, VideoFormat};
+use ffmpreg::io::Cursor;
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::{LoudnessMeter, TransformChain, TransformSpec};
+use tempfile::tempdir;
+
+fn audio_frame(stream_index: usize) -> Frame {
+	let data: Vec<u8> = [100i16, -100].iter().flat_map(|s| s.to_le_bytes()).collect();
+	Frame::new_audio(FrameAudio::new(data, 44100, 1), Timebase::new(1, 44100), stream_index)
+}
+
+fn video_frame(stream_index: usize) -> Frame {
+	let video = FrameVideo::new(vec![0, 1, 2, 3, 128, 128], 2, 2, VideoFormat::YUV420);
+	Frame::new_video(video, Timebase::new(1, 30), stream_index)
+}
+
+fn samples(frame: &Frame) -> Vec<i16> {
+	let audio = frame.audio().unwrap();
+	audio.data.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+#[test]
+fn test_stream_prefix_parses() {
+	assert_eq!(
+		TransformSpec::parse("a:gain=2").unwrap(),
+		TransformSpec::Stream {
+			kind: FrameKind::Audio,
+			index: None,
+			spec: Box::new(TransformSpec::Gain(2.0)),
+		}
+	);
+	let specs = TransformSpec::parse_list("a:1:volume=0.5,v:negate").unwrap();
+	assert_eq!(
+		specs[0],
+		TransformSpec::Stream {
+			kind: FrameKind::Audio,
+			index: Some(1),
+			spec: Box::new(TransformSpec::Volume(0.5)),
+		}
+	);
+	assert_eq!(
+		specs[1],
+		TransformSpec::Stream {
+			kind: FrameKind::Video,
+			index: None,
+			spec: Box::new(TransformSpec::Negate)
+		}
+	);
+	assert_eq!(specs[0].target(), &TransformSpec::Volume(0.5));
+	assert!(TransformSpec::parse("a:unknown").is_err());
+	assert!(TransformSpec::parse("s:gain=2").is_err());
+}
+
+#[test]
+fn test_stream_prefix_picks_frames() {
+	let specs = TransformSpec::parse_list("a:gain=2,v:negate").unwrap();
+	let mut chain = TransformChain::from_specs(specs).unwrap();
+
+	let audio = chain.apply(audio_frame(1)).unwrap();
+	assert_eq!(samples(&audio), vec![200, -200]);
+	let video = chain.apply(video_frame(0)).unwrap();
+	assert_eq!(&video.video().unwrap().data[..4], &[255, 254, 253, 252]);
+}
+
+#[test]
+fn test_stream_index_counts_streams_of_its_kind() {
+	// the video stream comes first in the container but does not count
+	let mut second_audio = TransformSpec::parse("a:1:gain=2").unwrap().build().unwrap();
+	second_audio.apply(video_frame(0)).unwrap();
+	assert_eq!(samples(&second_audio.apply(audio_frame(1)).unwrap()), vec![100, -100]);
+	assert_eq!(samples(&second_audio.apply(audio_frame(2)).unwrap()), vec![200, -200]);
+	assert_eq!(samples(&second_audio.apply(audio_frame(1)).unwrap()), vec![100, -100]);
+}
+
+#[test]
+fn test_transcoder_fills_prefixed_loudnorm() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.wav");
+	let output = dir.path().join("out.wav");
+	let tone: Vec<i16> = (0..48000 * 2)
+		.map(|n| (3000.0 * (2.0 * std::f64::consts::PI * 440.0 * n as f64 / 48000.0).sin()) as i16)
+		.collect();
+	std::fs::write(&input, crate::common::create_test_wav_from_samples(&tone, 48000, 1)).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["a:loudnorm=-20,-1".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let mut reader = WavReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut decoder = PcmDecoder::new(reader.format());
+	let mut meter = LoudnessMeter::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		if let Some(frame) = decoder.decode(packet).unwrap() {
+			meter.push(frame.audio().unwrap());
+		}
+	}
+	assert!((meter.loudness().integrated - -20.0).abs() < 0.5, "{}", meter.loudness().integrated);
+}
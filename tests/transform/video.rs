@@ -0,0 +1,1156 @@
+use ffmpreg::container::Y4mReader;
+use ffmpreg::core::{Demuxer, Frame, FrameVideo, Timebase, Transform, VideoFormat};
+use ffmpreg::io::{Cursor, RootedPolicy};
+use ffmpreg::transcode::Transcoder;
+use ffmpreg::transform::video::colorspace::{convert_chroma, to_rgb24, to_yuv};
+use ffmpreg::transform::video::{cropdetect, lut, stabilize};
+use ffmpreg::transform::{
+	Blur, ColorConvert, ColorMatrix, Contrast, Crop, CropDetect, DrawBox, DrawText, GaussianBlur,
+	Grayscale, Lut, Lut3D, Negate, Overlay, Pad, Pixelate, RotateAngle, Saturation, Scale, ScaleFit,
+	ScaleMode, Sharpen, Tblend, TransformChain, TransformSpec, VideoFade, Vignette,
+};
+use std::sync::Arc;
+use tempfile::tempdir;
+
+// 4x2 yuv420: eight luma samples, then two u and two v
+fn yuv420_frame(luma: [u8; 8], u: [u8; 2], v: [u8; 2]) -> Frame {
+	let mut data = luma.to_vec();
+	data.extend_from_slice(&u);
+	data.extend_from_slice(&v);
+	let video = FrameVideo::new(data, 4, 2, VideoFormat::YUV420);
+	Frame::new_video(video, Timebase::new(1, 30), 0).with_pts(7)
+}
+
+#[test]
+fn test_contrast_scales_luma_around_mid_grey() {
+	let frame = yuv420_frame([0, 64, 128, 192, 255, 100, 140, 128], [90, 160], [20, 240]);
+	let output = Contrast::new(4, 2, 1.5).apply(frame).unwrap();
+
+	assert_eq!(output.pts, 7);
+	let data = &output.video().unwrap().data;
+	assert_eq!(&data[..8], &[0, 32, 128, 224, 255, 86, 146, 128]);
+	assert_eq!(&data[8..], &[90, 160, 20, 240]);
+}
+
+#[test]
+fn test_saturation_scales_chroma_around_neutral() {
+	let frame = yuv420_frame([10; 8], [90, 160], [20, 240]);
+	let output = Saturation::new(4, 2, 0.5).apply(frame.clone()).unwrap();
+	let data = &output.video().unwrap().data;
+	assert_eq!(&data[..8], &[10; 8]);
+	assert_eq!(&data[8..], &[109, 144, 74, 184]);
+
+	let grey = Saturation::new(4, 2, 0.0).apply_yuv420(&frame).unwrap();
+	assert_eq!(&grey.video().unwrap().data[8..], &[128; 4]);
+}
+
+#[test]
+fn test_video_transforms_skip_packed_rgb() {
+	let rgb = FrameVideo::new(vec![0, 50, 255], 1, 1, VideoFormat::RGB24);
+	let frame = Frame::new_video(rgb, Timebase::new(1, 30), 0);
+	let output = Saturation::new(1, 1, 2.0).apply(frame).unwrap();
+	let output = Contrast::new(1, 1, 2.0).apply(output).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![0, 50, 255]);
+}
+
+#[test]
+fn test_contrast_saturation_specs() {
+	assert_eq!(
+		TransformSpec::parse_list("contrast=1.2,saturation=0.8").unwrap(),
+		vec![TransformSpec::Contrast(1.2), TransformSpec::Saturation(0.8)]
+	);
+	assert_eq!(
+		TransformSpec::parse_list("adelay=0,20,silencedetect=-50,2,json").unwrap(),
+		vec![
+			TransformSpec::Adelay(vec![0.0, 20.0]),
+			TransformSpec::SilenceDetect { noise_db: -50.0, min_duration: 2.0, json: true },
+		]
+	);
+	assert!(TransformSpec::parse("contrast").is_err());
+	assert!(TransformSpec::Saturation(-1.0).build().is_err());
+}
+
+#[test]
+fn test_y4m_transcode_applies_video_transforms() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["contrast=0.5,saturation=2".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	// first frame's luma counts up from 0 and maps to 64 + y/2; chroma is neutral
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let packet = reader.read_packet().unwrap().unwrap();
+	assert_eq!(&packet.data[..3], &[64, 65, 65]);
+	assert_eq!(packet.data[63], 96);
+	assert_eq!(packet.data[64..], [128; 32]);
+}
+
+#[test]
+fn test_grayscale_neutralizes_chroma() {
+	let frame = yuv420_frame([10, 20, 30, 40, 50, 60, 70, 80], [90, 160], [20, 240]);
+	let output = Grayscale::new().apply(frame).unwrap();
+	let data = &output.video().unwrap().data;
+	assert_eq!(&data[..8], &[10, 20, 30, 40, 50, 60, 70, 80]);
+	assert_eq!(&data[8..], &[128; 4]);
+
+	let rgba = FrameVideo::new(vec![255, 0, 0, 7, 10, 10, 10, 9], 2, 1, VideoFormat::RGBA32);
+	let output = Grayscale::new().apply(Frame::new_video(rgba, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![76, 76, 76, 7, 10, 10, 10, 9]);
+}
+
+#[test]
+fn test_negate_inverts_luma_and_mirrors_chroma() {
+	let frame = yuv420_frame([0, 255, 128, 1, 2, 3, 4, 5], [128, 0], [1, 255]);
+	let output = Negate::new().apply(frame).unwrap();
+	let data = &output.video().unwrap().data;
+	assert_eq!(&data[..8], &[255, 0, 127, 254, 253, 252, 251, 250]);
+	assert_eq!(&data[8..], &[128, 255, 255, 1]);
+
+	let rgb = FrameVideo::new(vec![0, 100, 255], 1, 1, VideoFormat::RGB24);
+	let output = Negate::new().apply(Frame::new_video(rgb, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![255, 155, 0]);
+}
+
+#[test]
+fn test_grayscale_negate_specs() {
+	assert_eq!(
+		TransformSpec::parse_list("grayscale,negate").unwrap(),
+		vec![TransformSpec::Grayscale, TransformSpec::Negate]
+	);
+	assert_eq!(TransformSpec::Negate.build().unwrap().name(), "negate");
+}
+
+#[test]
+fn test_sharpen_steepens_edges() {
+	let frame = yuv420_frame([100, 100, 200, 200, 100, 100, 200, 200], [90, 160], [20, 240]);
+	let output = Sharpen::new(1.0, 1).apply(frame.clone()).unwrap();
+	let data = &output.video().unwrap().data;
+	// flat areas keep their value, both sides of the step overshoot
+	assert_eq!(&data[..4], &[100, 67, 234, 200]);
+	assert_eq!(&data[4..8], &[100, 67, 234, 200]);
+	assert_eq!(&data[8..], &[90, 160, 20, 240]);
+
+	let unchanged = Sharpen::new(0.0, 3).apply(frame.clone()).unwrap();
+	assert_eq!(unchanged.video().unwrap().data, frame.video().unwrap().data);
+}
+
+#[test]
+fn test_sharpen_specs() {
+	assert_eq!(
+		TransformSpec::parse("sharpen").unwrap(),
+		TransformSpec::Sharpen { amount: 1.0, radius: 1 }
+	);
+	assert_eq!(
+		TransformSpec::parse("sharpen=0.5,2").unwrap(),
+		TransformSpec::Sharpen { amount: 0.5, radius: 2 }
+	);
+	assert!(TransformSpec::parse("sharpen=1,1.5").is_err());
+	assert!(TransformSpec::Sharpen { amount: 1.0, radius: 0 }.build().is_err());
+}
+
+#[test]
+fn test_gaussian_blur_spreads_an_impulse_symmetrically() {
+	// 9x9 gray8 with one white pixel in the middle
+	let mut data = vec![0u8; 81];
+	data[40] = 255;
+	let video = FrameVideo::new(data, 9, 9, VideoFormat::GRAY8);
+	let frame = Frame::new_video(video, Timebase::new(1, 30), 0);
+	let output = GaussianBlur::new(1.0).apply(frame).unwrap();
+	let data = &output.video().unwrap().data;
+
+	// 255 * (1 / sum of exp(-x^2 / 2) for x in -3..=3)^2
+	assert_eq!(data[40], 41);
+	assert_eq!(data[39], data[41]);
+	assert_eq!(data[31], data[49]);
+	assert_eq!(data[39], data[31]);
+	assert!(data[39] < data[40] && data[30] < data[39]);
+	// tails round away, so the energy is only roughly kept
+	let total: u32 = data.iter().map(|&v| v as u32).sum();
+	assert!((240..=260).contains(&total), "{}", total);
+}
+
+#[test]
+fn test_gaussian_blur_keeps_flat_planes() {
+	let frame = yuv420_frame([90; 8], [60, 60], [200, 200]);
+	let output = GaussianBlur::new(3.0).apply(frame.clone()).unwrap();
+	assert_eq!(output.video().unwrap().data, frame.video().unwrap().data);
+
+	assert_eq!(
+		TransformSpec::parse("gblur=1.5").unwrap(),
+		TransformSpec::GaussianBlur { sigma: 1.5 }
+	);
+	assert!(TransformSpec::GaussianBlur { sigma: 0.0 }.build().is_err());
+}
+
+// the old per-pixel loop over the clipped window
+fn naive_box_blur(src: &[u8], width: usize, height: usize, r: isize) -> Vec<u8> {
+	let mut out = vec![0u8; width * height];
+	for y in 0..height as isize {
+		for x in 0..width as isize {
+			let (mut sum, mut count) = (0u32, 0u32);
+			for ny in (y - r).max(0)..=(y + r).min(height as isize - 1) {
+				for nx in (x - r).max(0)..=(x + r).min(width as isize - 1) {
+					sum += src[ny as usize * width + nx as usize] as u32;
+					count += 1;
+				}
+			}
+			out[y as usize * width + x as usize] = (sum / count) as u8;
+		}
+	}
+	out
+}
+
+#[test]
+fn test_box_blur_matches_per_pixel_mean() {
+	for (width, height) in [(7usize, 5usize), (10, 6), (1, 1)] {
+		let (uv_w, uv_h) = (width / 2, height / 2);
+		let len = width * height + 2 * uv_w * uv_h;
+		let data: Vec<u8> = (0..len).map(|i| ((i * 7919 + 13) % 251) as u8).collect();
+		let video = FrameVideo::new(data.clone(), width as u32, height as u32, VideoFormat::YUV420);
+		let frame = Frame::new_video(video, Timebase::new(1, 30), 0);
+
+		for radius in [0u32, 1, 2, 3, 9] {
+			let blurred = Blur::new(width as u32, height as u32, radius).apply_yuv420(&frame).unwrap();
+			let out = &blurred.video().unwrap().data;
+			let y_size = width * height;
+			let r = radius as isize;
+			assert_eq!(&out[..y_size], &naive_box_blur(&data[..y_size], width, height, r)[..]);
+			let uv_size = uv_w * uv_h;
+			let u = &data[y_size..y_size + uv_size];
+			assert_eq!(&out[y_size..y_size + uv_size], &naive_box_blur(u, uv_w, uv_h, r)[..]);
+		}
+	}
+}
+
+#[test]
+fn test_overlay_blends_and_crops_at_the_edge() {
+	let main = FrameVideo::new(vec![0; 16], 4, 4, VideoFormat::GRAY8);
+	let logo = FrameVideo::new(vec![200; 4], 2, 2, VideoFormat::GRAY8);
+	let mut overlay = Overlay::still(logo).with_position(3, 3).with_alpha(0.5);
+	let output = overlay.apply(Frame::new_video(main, Timebase::new(1, 30), 0)).unwrap();
+	let data = &output.video().unwrap().data;
+	assert_eq!(data[15], 100);
+	assert_eq!(data.iter().filter(|&&v| v != 0).count(), 1);
+}
+
+#[test]
+fn test_overlay_places_chroma_at_subsampled_position() {
+	let logo = FrameVideo::new(vec![200, 201, 202, 203, 50, 250], 2, 2, VideoFormat::YUV420);
+	let mut overlay = Overlay::still(logo).with_position(2, 0);
+	let frame = yuv420_frame([1, 2, 3, 4, 5, 6, 7, 8], [90, 160], [20, 240]);
+
+	// a still keeps showing on later frames
+	for _ in 0..2 {
+		let output = overlay.apply(frame.clone()).unwrap();
+		let data = &output.video().unwrap().data;
+		assert_eq!(&data[..8], &[1, 2, 200, 201, 5, 6, 202, 203]);
+		assert_eq!(&data[8..], &[90, 50, 20, 250]);
+	}
+
+	let gray = FrameVideo::new(vec![0; 4], 2, 2, VideoFormat::GRAY8);
+	assert!(Overlay::still(gray).apply(frame).is_err());
+}
+
+fn gray_frame(width: u32, height: u32, pts: i64) -> Frame {
+	let video =
+		FrameVideo::new(vec![16; (width * height) as usize], width, height, VideoFormat::GRAY8);
+	Frame::new_video(video, Timebase::new(1, 30), 0).with_pts(pts)
+}
+
+#[test]
+fn test_drawtext_renders_glyph_bits() {
+	let output =
+		DrawText::new("1").with_position(1, 1).with_scale(1).apply(gray_frame(7, 9, 0)).unwrap();
+	let rows: Vec<String> = output
+		.video()
+		.unwrap()
+		.data
+		.chunks(7)
+		.map(|row| row.iter().map(|&v| if v == 235 { '#' } else { '.' }).collect())
+		.collect();
+	assert_eq!(
+		rows,
+		vec![
+			".......", "...#...", "..##...", "...#...", "...#...", "...#...", "...#...", "..###..",
+			".......",
+		]
+	);
+}
+
+#[test]
+fn test_drawtext_expands_timestamp_and_frame_number() {
+	let render = |drawtext: &mut DrawText, pts: i64| {
+		drawtext.apply(gray_frame(160, 20, pts)).unwrap().video().unwrap().data.clone()
+	};
+	// 45 frames at 1/30 s
+	assert_eq!(
+		render(&mut DrawText::new("T %{pts}"), 45),
+		render(&mut DrawText::new("T 00:00:01.500"), 0)
+	);
+
+	let mut counter = DrawText::new("%{n}");
+	assert_eq!(render(&mut counter, 0), render(&mut DrawText::new("0"), 0));
+	assert_eq!(render(&mut counter, 1), render(&mut DrawText::new("1"), 0));
+}
+
+#[test]
+fn test_drawtext_specs() {
+	assert_eq!(
+		TransformSpec::parse("drawtext=text=%{pts}:x=10:y=4:color=20:size=3").unwrap(),
+		TransformSpec::DrawText { text: "%{pts}".to_string(), x: 10, y: 4, color: 20, scale: 3 }
+	);
+	assert!(TransformSpec::parse("drawtext=x=10").is_err());
+	assert!(TransformSpec::parse("drawtext=text=hi:font=big").is_err());
+}
+
+#[test]
+fn test_drawbox_outline() {
+	let mut drawbox = DrawBox::new(1, 1, 4, 3).with_thickness(1).with_color([200, 128, 128]);
+	let output = drawbox.apply(gray_frame(6, 5, 0)).unwrap();
+	let rows: Vec<String> = output
+		.video()
+		.unwrap()
+		.data
+		.chunks(6)
+		.map(|row| row.iter().map(|&v| if v == 200 { '#' } else { '.' }).collect())
+		.collect();
+	assert_eq!(rows, vec!["......", ".####.", ".#..#.", ".####.", "......"]);
+}
+
+#[test]
+fn test_drawbox_fill_paints_chroma() {
+	let red = [81, 90, 240];
+	let frame = yuv420_frame([1, 2, 3, 4, 5, 6, 7, 8], [128, 128], [128, 128]);
+	let output = DrawBox::new(2, 0, 9, 9).with_color(red).filled().apply(frame).unwrap();
+	let data = &output.video().unwrap().data;
+	assert_eq!(&data[..8], &[1, 2, 81, 81, 5, 6, 81, 81]);
+	assert_eq!(&data[8..], &[128, 90, 128, 240]);
+}
+
+#[test]
+fn test_drawbox_specs() {
+	assert_eq!(
+		TransformSpec::parse("drawbox=x=1:y=2:w=30:h=40:color=red:t=fill").unwrap(),
+		TransformSpec::DrawBox {
+			x: 1,
+			y: 2,
+			width: 30,
+			height: 40,
+			color: [81, 90, 240],
+			thickness: None
+		}
+	);
+	let spec = TransformSpec::parse("drawbox=w=3:h=3:color=#ffffff:t=1").unwrap();
+	assert!(matches!(
+		spec,
+		TransformSpec::DrawBox { color: [235, 128, 128], thickness: Some(1), .. }
+	));
+	assert!(TransformSpec::parse("drawbox=w=3:h=3:color=purple").is_err());
+	assert!(TransformSpec::parse("drawbox=x=1").unwrap().build().is_err());
+}
+
+#[test]
+fn test_color_matrix_primaries_round_trip() {
+	for matrix in [ColorMatrix::Bt601, ColorMatrix::Bt709] {
+		for rgb in [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255], [0, 0, 0]] {
+			let back = matrix.yuv_to_rgb(matrix.rgb_to_yuv(rgb));
+			for (a, b) in rgb.iter().zip(back.iter()) {
+				assert!(a.abs_diff(*b) <= 2, "{:?}: {:?} -> {:?}", matrix, rgb, back);
+			}
+		}
+	}
+	assert_eq!(ColorMatrix::Bt601.rgb_to_yuv([255, 255, 255]), [235, 128, 128]);
+	assert_eq!(ColorMatrix::Bt601.rgb_to_yuv([255, 0, 0]), [81, 90, 240]);
+	assert_eq!(ColorMatrix::Bt709.rgb_to_yuv([255, 0, 0]), [63, 102, 240]);
+	assert_eq!(ColorMatrix::from_name("709"), Some(ColorMatrix::Bt709));
+	assert_eq!(ColorMatrix::from_name("srgb"), None);
+}
+
+#[test]
+fn test_colorspace_frames_convert_both_ways() {
+	// left half red, right half blue
+	let mut rgb = Vec::new();
+	for _ in 0..2 {
+		rgb.extend([255, 0, 0, 255, 0, 0, 0, 0, 255, 0, 0, 255]);
+	}
+	let frame = FrameVideo::new(rgb.clone(), 4, 2, VideoFormat::RGB24);
+
+	let yuv = to_yuv(&frame, VideoFormat::YUV420, ColorMatrix::Bt601).unwrap();
+	assert_eq!(yuv.data.len(), 8 + 2 + 2);
+	assert_eq!(&yuv.data[8..], &[90, 240, 240, 110]);
+	assert_eq!(to_rgb24(&yuv, ColorMatrix::Bt601).unwrap().data.len(), rgb.len());
+
+	let gray = to_yuv(&frame, VideoFormat::GRAY8, ColorMatrix::Bt601).unwrap();
+	assert_eq!(gray.data, vec![81, 81, 41, 41, 81, 81, 41, 41]);
+
+	let mut convert = ColorConvert::new(VideoFormat::YUV444);
+	let output = convert.apply(Frame::new_video(frame, Timebase::new(1, 30), 0)).unwrap();
+	let video = output.video().unwrap();
+	assert_eq!(video.format, VideoFormat::YUV444);
+	// 8-bit yuv can be a step off after the round trip
+	let back = to_rgb24(video, ColorMatrix::Bt601).unwrap().data;
+	assert!(back.iter().zip(rgb.iter()).all(|(a, b)| a.abs_diff(*b) <= 1), "{:?}", back);
+}
+
+#[test]
+fn test_colorspace_to_rgb_from_yuv() {
+	let frame = yuv420_frame([16; 8], [128; 2], [128; 2]);
+	let output = ColorConvert::new(VideoFormat::RGB24).apply(frame).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![0; 24]);
+}
+
+#[test]
+fn test_convert_chroma_between_layouts() {
+	let frame = yuv420_frame([50; 8], [100, 200], [10, 30]);
+	let video = frame.video().unwrap();
+
+	let full = convert_chroma(video, VideoFormat::YUV444).unwrap();
+	assert_eq!(full.data.len(), 8 * 3);
+	assert_eq!(&full.data[8..16], &[100, 100, 200, 200, 100, 100, 200, 200]);
+	assert_eq!(&full.data[16..], &[10, 10, 30, 30, 10, 10, 30, 30]);
+	assert_eq!(convert_chroma(&full, VideoFormat::YUV420).unwrap().data, video.data);
+
+	// 4:2:2 keeps both rows; back to 4:2:0 they are averaged
+	let mut data = vec![50; 8];
+	data.extend([100, 200, 110, 210, 10, 30, 20, 40]);
+	let half = FrameVideo::new(data, 4, 2, VideoFormat::YUV422);
+	let quarter = convert_chroma(&half, VideoFormat::YUV420).unwrap();
+	assert_eq!(&quarter.data[8..], &[105, 205, 15, 35]);
+
+	let gray = convert_chroma(video, VideoFormat::GRAY8).unwrap();
+	assert_eq!(gray.data, vec![50; 8]);
+	let back = convert_chroma(&gray, VideoFormat::YUV422).unwrap();
+	assert_eq!(&back.data[8..], &[128; 8]);
+
+	assert!(convert_chroma(video, VideoFormat::RGB24).is_err());
+}
+
+#[test]
+fn test_scale_and_crop_keep_422_planes() {
+	let mut data = vec![90; 8 * 4];
+	data.extend(vec![60; 4 * 4]);
+	data.extend(vec![200; 4 * 4]);
+	let frame =
+		Frame::new_video(FrameVideo::new(data, 8, 4, VideoFormat::YUV422), Timebase::new(1, 30), 0);
+
+	let scaled = Scale::new(8, 4, 4, 2).apply_yuv420(&frame).unwrap();
+	let video = scaled.video().unwrap();
+	assert_eq!((video.width, video.height), (4, 2));
+	assert_eq!(video.data.len(), VideoFormat::YUV422.frame_size(4, 2));
+	assert_eq!(&video.data[8..12], &[60; 4]);
+	assert_eq!(&video.data[12..], &[200; 4]);
+
+	let cropped = Crop::new(8, 4, 2, 1, 4, 2).apply_yuv420(&frame).unwrap();
+	let video = cropped.video().unwrap();
+	assert_eq!((video.width, video.height), (4, 2));
+	assert_eq!(video.data.len(), VideoFormat::YUV422.frame_size(4, 2));
+	assert_eq!(&video.data[12..], &[200; 4]);
+}
+
+#[test]
+fn test_format_spec_rewrites_y4m_colorspace() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	let mut bytes = b"YUV4MPEG2 W4 H2 F30:1 Ip C422\n".to_vec();
+	for _ in 0..2 {
+		bytes.extend(b"FRAME\n");
+		bytes.extend([40; 8]);
+		bytes.extend([100, 200, 110, 210, 10, 30, 20, 40]);
+	}
+	std::fs::write(&input, &bytes).unwrap();
+
+	assert!(TransformSpec::parse("format=rgb").is_err());
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["format=yuv420".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let written = std::fs::read(&output).unwrap();
+	let mut reader = Y4mReader::new(Cursor::new(written)).unwrap();
+	assert_eq!(reader.format().video_format(), VideoFormat::YUV420);
+	let packet = reader.read_packet().unwrap().unwrap();
+	assert_eq!(packet.data[8..], [105, 205, 15, 35]);
+}
+
+#[test]
+fn test_lut_tables() {
+	assert_eq!(lut::identity_table()[77], 77);
+	let gamma = lut::gamma_table(2.0).unwrap();
+	assert_eq!((gamma[0], gamma[64], gamma[255]), (0, 128, 255));
+	assert!(lut::gamma_table(0.0).is_err());
+
+	let lifted = lut::lift_gain_table(0.1, 0.9);
+	assert_eq!((lifted[0], lifted[255]), (26, 230));
+
+	let curve = lut::curve_table(&[(16, 0), (128, 100), (235, 255)]).unwrap();
+	assert_eq!((curve[0], curve[16], curve[72], curve[128], curve[240]), (0, 0, 50, 100, 255));
+	assert!(lut::curve_table(&[(10, 0)]).is_err());
+	assert!(lut::curve_table(&[(10, 0), (10, 5)]).is_err());
+}
+
+#[test]
+fn test_lut_grades_luma_or_one_plane() {
+	let invert: [u8; 256] = std::array::from_fn(|i| 255 - i as u8);
+	let frame = yuv420_frame([0, 10, 20, 30, 40, 50, 60, 70], [100, 110], [120, 130]);
+	let output = Lut::new(invert).apply(frame).unwrap();
+	assert_eq!(
+		output.video().unwrap().data,
+		vec![255, 245, 235, 225, 215, 205, 195, 185, 100, 110, 120, 130]
+	);
+
+	let frame = yuv420_frame([0; 8], [100, 110], [120, 130]);
+	let output = Lut::identity().with_plane(2, invert).apply(frame).unwrap();
+	assert_eq!(&output.video().unwrap().data[8..], &[100, 110, 135, 125]);
+
+	// rgb takes the master table on every channel but leaves alpha
+	let video = FrameVideo::new(vec![10, 20, 30, 40], 1, 1, VideoFormat::RGBA32);
+	let output = Lut::new(invert).apply(Frame::new_video(video, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![245, 235, 225, 40]);
+}
+
+#[test]
+fn test_lut_spec() {
+	let spec = TransformSpec::parse("lut=gamma=1.5:curve=0/16,255/235:plane=v").unwrap();
+	assert_eq!(
+		spec,
+		TransformSpec::Lut {
+			gamma: 1.5,
+			lift: 0.0,
+			gain: 1.0,
+			curve: vec![(0, 16), (255, 235)],
+			plane: Some(2)
+		}
+	);
+	let list = TransformSpec::parse_list("lut=curve=0/0,128/150,255/255,negate").unwrap();
+	assert_eq!(list.len(), 2);
+
+	let mut lut = TransformSpec::parse("lut=lift=0.1:gain=0.9").unwrap().build().unwrap();
+	let output = lut.apply(yuv420_frame([0, 255, 0, 255, 0, 255, 0, 255], [0; 2], [255; 2])).unwrap();
+	assert_eq!(
+		output.video().unwrap().data,
+		vec![26, 230, 26, 230, 26, 230, 26, 230, 0, 0, 255, 255]
+	);
+
+	assert!(TransformSpec::parse("lut=curve=5/5").unwrap().build().is_err());
+	assert!(TransformSpec::parse("lut=plane=w").is_err());
+	assert!(TransformSpec::parse("lut=gamma=-1").unwrap().build().is_err());
+}
+
+// a size-2 cube whose entries come from `map` applied to each corner
+fn cube(map: impl Fn([f32; 3]) -> [f32; 3]) -> String {
+	let mut text = String::from("TITLE \"test\"\n# corners\nLUT_3D_SIZE 2\n");
+	for b in 0..2 {
+		for g in 0..2 {
+			for r in 0..2 {
+				let [r, g, b] = map([r as f32, g as f32, b as f32]);
+				text.push_str(&format!("{} {} {}\n", r, g, b));
+			}
+		}
+	}
+	text
+}
+
+#[test]
+fn test_lut3d_interpolates_cube() {
+	let identity = Lut3D::parse(&cube(|rgb| rgb)).unwrap();
+	assert_eq!(identity.size(), 2);
+	assert_eq!(identity.lookup([12, 128, 250]), [12, 128, 250]);
+
+	let swap = Lut3D::parse(&cube(|[r, g, b]| [b, g, r])).unwrap();
+	assert_eq!(swap.lookup([255, 40, 0]), [0, 40, 255]);
+
+	// half-strength domain: everything at or above 0.5 saturates
+	let text = cube(|rgb| rgb).replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MAX 0.5 0.5 0.5");
+	assert_eq!(Lut3D::parse(&text).unwrap().lookup([64, 128, 200]), [128, 255, 255]);
+
+	assert!(Lut3D::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+	assert!(Lut3D::parse("LUT_1D_SIZE 4\n").is_err());
+	assert!(Lut3D::parse(&cube(|rgb| rgb).replace("1 1 1", "1 x 1")).is_err());
+}
+
+#[test]
+fn test_lut3d_spec_grades_yuv_through_rgb() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("invert.cube");
+	std::fs::write(&path, cube(|rgb| rgb.map(|v| 1.0 - v))).unwrap();
+
+	let mut lut =
+		TransformSpec::parse(&format!("lut3d={}", path.display())).unwrap().build().unwrap();
+	let output = lut.apply(yuv420_frame([16; 8], [128; 2], [128; 2])).unwrap();
+	let video = output.video().unwrap();
+	assert_eq!(video.format, VideoFormat::YUV420);
+	assert_eq!(video.data, [vec![235; 8], vec![128; 4]].concat());
+
+	assert!(TransformSpec::parse("lut3d=missing.cube").unwrap().build().is_err());
+	assert!(TransformSpec::parse("lut3d").is_err());
+}
+
+#[test]
+fn test_lut3d_is_loaded_through_path_policy() {
+	let dir = tempdir().unwrap();
+	let root = dir.path().join("root");
+	std::fs::create_dir(&root).unwrap();
+	let input = root.join("in.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	let outside = dir.path().join("secret.cube");
+	std::fs::write(&outside, cube(|rgb| rgb)).unwrap();
+	let inside = root.join("identity.cube");
+	std::fs::write(&inside, cube(|rgb| rgb)).unwrap();
+
+	let transcode = |cube: &std::path::Path| {
+		let policy = RootedPolicy::new().with_root(&root).unwrap();
+		Transcoder::new(
+			input.to_str().unwrap().to_string(),
+			Some(root.join("out.y4m").to_str().unwrap().to_string()),
+			false,
+			vec![format!("lut3d={}", cube.display())],
+		)
+		.with_path_policy(Arc::new(policy))
+		.run()
+	};
+	let err = transcode(&outside).unwrap_err();
+	assert!(matches!(err.kind(), std::io::ErrorKind::PermissionDenied));
+	transcode(&inside).unwrap();
+}
+
+#[test]
+fn test_vignette_darkens_corners_more_than_centre() {
+	let video = FrameVideo::new(vec![200; 8 * 8], 8, 8, VideoFormat::GRAY8);
+	let frame = Frame::new_video(video, Timebase::new(1, 30), 0);
+	let output = Vignette::new(0.5).apply(frame).unwrap();
+	let data = &output.video().unwrap().data;
+	let (corner, centre) = (data[0], data[3 * 8 + 3]);
+	assert!(corner < centre && centre < 200, "corner {} centre {}", corner, centre);
+	// the corner pixel centre is at 77% of the corner distance: 16 + 184 * (1 - 0.5 * 0.766)
+	assert_eq!(corner, 130);
+	// symmetric about the centre
+	assert_eq!(data[0], data[63]);
+	assert_eq!(data[7], data[56]);
+
+	let frame = yuv420_frame([10; 8], [60; 2], [200; 2]);
+	let output = Vignette::new(1.0).apply(frame).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![10, 10, 10, 10, 10, 10, 10, 10, 60, 60, 200, 200]);
+}
+
+#[test]
+fn test_pixelate_averages_blocks() {
+	let frame = yuv420_frame([0, 10, 20, 30, 40, 50, 60, 70], [100, 200], [0, 50]);
+	let output = Pixelate::new(2).apply(frame).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![25, 25, 45, 45, 25, 25, 45, 45, 100, 200, 0, 50]);
+
+	let frame = yuv420_frame([0, 10, 20, 30, 40, 50, 60, 70], [100, 200], [0, 50]);
+	let output = Pixelate::new(4).with_region(2, 0, 2, 2).apply(frame).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![0, 10, 45, 45, 40, 50, 45, 45, 100, 200, 0, 50]);
+
+	let video = FrameVideo::new(vec![0, 0, 0, 9, 100, 50, 10, 1], 2, 1, VideoFormat::RGBA32);
+	let output = Pixelate::new(2).apply(Frame::new_video(video, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![50, 25, 5, 9, 50, 25, 5, 1]);
+}
+
+#[test]
+fn test_vignette_and_pixelate_specs() {
+	assert_eq!(TransformSpec::parse("vignette").unwrap(), TransformSpec::Vignette(0.5));
+	assert!(TransformSpec::parse("vignette=2").is_err());
+	assert_eq!(
+		TransformSpec::parse("pixelate").unwrap(),
+		TransformSpec::Pixelate { block: 16, region: None }
+	);
+	assert_eq!(
+		TransformSpec::parse("pixelate=size=8:x=2:w=4").unwrap(),
+		TransformSpec::Pixelate { block: 8, region: Some((2, 0, 4, u32::MAX)) }
+	);
+	assert!(TransformSpec::parse("pixelate=size=0").is_err());
+
+	let mut pixelate = TransformSpec::parse("pixelate=size=4:x=2").unwrap().build().unwrap();
+	let output =
+		pixelate.apply(yuv420_frame([0, 10, 20, 30, 40, 50, 60, 70], [0; 2], [0; 2])).unwrap();
+	assert_eq!(&output.video().unwrap().data[..8], &[0, 10, 45, 45, 40, 50, 45, 45]);
+}
+
+// 8x8 yuv420 with `top` black rows and `left` black columns on each side
+fn letterboxed(top: usize, left: usize) -> Vec<u8> {
+	let mut data = Vec::new();
+	for y in 0..8 {
+		for x in 0..8 {
+			let black = y < top || y >= 8 - top || x < left || x >= 8 - left;
+			data.push(if black { 16 } else { 120 });
+		}
+	}
+	data.extend([128; 32]);
+	data
+}
+
+#[test]
+fn test_cropdetect_finds_black_borders() {
+	let frame = FrameVideo::new(letterboxed(2, 0), 8, 8, VideoFormat::YUV420);
+	assert_eq!(cropdetect::detect_borders(&frame, 24), Some((0, 2, 8, 6)));
+	let black = FrameVideo::new([vec![16; 64], vec![128; 32]].concat(), 8, 8, VideoFormat::YUV420);
+	assert_eq!(cropdetect::detect_borders(&black, 24), None);
+
+	let mut detect = CropDetect::new().with_window(2);
+	assert_eq!(detect.geometry(), None);
+	let timebase = Timebase::new(1, 30);
+	detect.apply(Frame::new_video(black, timebase, 0)).unwrap();
+	assert_eq!(detect.geometry(), Some((0, 0, 8, 8)));
+	assert!(!detect.is_done());
+	// the window takes the union over frames with picture in them
+	for data in [letterboxed(2, 0), letterboxed(1, 2)] {
+		let frame = FrameVideo::new(data, 8, 8, VideoFormat::YUV420);
+		detect.apply(Frame::new_video(frame, timebase, 0)).unwrap();
+	}
+	assert!(detect.is_done());
+	assert_eq!(detect.geometry(), Some((0, 2, 8, 4)));
+	assert_eq!(detect.to_text(), "cropdetect: crop=8:4:0:2\n");
+
+	// width and height round down and the shave is split between both sides
+	let mut detect = CropDetect::new().with_round(4).with_window(1);
+	let frame = FrameVideo::new(letterboxed(1, 1), 8, 8, VideoFormat::YUV420);
+	detect.apply(Frame::new_video(frame, timebase, 0)).unwrap();
+	assert_eq!(detect.geometry(), Some((2, 2, 4, 4)));
+}
+
+#[test]
+fn test_drawbox_outlines_cropdetect_region() {
+	// cropdetect leaves its region on the frame and drawbox=from= draws it,
+	// through a scale that builds a new frame in between
+	let specs =
+		TransformSpec::parse_list("cropdetect,scale=8x8,drawbox=from=cropdetect:t=1").unwrap();
+	assert_eq!(
+		specs[2],
+		TransformSpec::DrawRegion {
+			key: "cropdetect".into(),
+			color: [235, 128, 128],
+			thickness: Some(1)
+		}
+	);
+	let mut chain = TransformChain::from_specs(specs).unwrap();
+	let frame = FrameVideo::new(letterboxed(2, 0), 8, 8, VideoFormat::YUV420);
+	let output = chain.apply(Frame::new_video(frame, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.metadata.region("cropdetect"), Some((0, 2, 8, 4)));
+	let luma = &output.video().unwrap().data[..64];
+	assert_eq!(&luma[8..16], &[16; 8]);
+	assert_eq!(&luma[16..24], &[235; 8]);
+	assert_eq!(&luma[24..32], &[235, 120, 120, 120, 120, 120, 120, 235]);
+	assert_eq!(&luma[40..48], &[235; 8]);
+
+	// without the side data the box stays off
+	let mut drawbox = DrawBox::from_metadata("cropdetect");
+	let plain = FrameVideo::new(letterboxed(2, 0), 8, 8, VideoFormat::YUV420);
+	let untouched = drawbox.apply(Frame::new_video(plain, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(untouched.video().unwrap().data, letterboxed(2, 0));
+}
+
+#[test]
+fn test_cropdetect_apply_crops_y4m() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	let mut bytes = b"YUV4MPEG2 W8 H8 F30:1 Ip C420\n".to_vec();
+	for _ in 0..3 {
+		bytes.extend(b"FRAME\n");
+		bytes.extend(letterboxed(2, 0));
+	}
+	std::fs::write(&input, &bytes).unwrap();
+
+	assert_eq!(
+		TransformSpec::parse("cropdetect=limit=30:apply").unwrap(),
+		TransformSpec::CropDetect { limit: 30, round: 2, window: 30, apply: true, measured: None }
+	);
+	assert!(TransformSpec::parse("cropdetect=round=0").is_err());
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["cropdetect=apply".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!((reader.format().width, reader.format().height), (8, 4));
+	let mut frames = 0;
+	while let Some(packet) = reader.read_packet().unwrap() {
+		assert_eq!(packet.data, [vec![120; 32], vec![128; 16]].concat());
+		frames += 1;
+	}
+	assert_eq!(frames, 3);
+}
+
+// blocky pseudo-random texture, shifted by (dx, dy)
+fn texture(width: usize, height: usize, dx: i32, dy: i32) -> Vec<u8> {
+	let sample = |x: i32, y: i32| {
+		let (bx, by) = (x.div_euclid(3) as u32, y.div_euclid(3) as u32);
+		(bx.wrapping_mul(73_856_093) ^ by.wrapping_mul(19_349_663)).wrapping_mul(2_654_435_761) >> 24
+	};
+	(0..height as i32)
+		.flat_map(|y| (0..width as i32).map(move |x| (sample(x - dx, y - dy)) as u8))
+		.collect()
+}
+
+#[test]
+fn test_estimate_motion_finds_global_shift() {
+	let prev = texture(64, 48, 0, 0);
+	for shift in [(3, -2), (0, 0), (-5, 4)] {
+		let cur = texture(64, 48, shift.0, shift.1);
+		assert_eq!(stabilize::estimate_motion(&prev, &cur, 64, 48, 6), shift);
+	}
+	// flat frames give no usable blocks
+	assert_eq!(stabilize::estimate_motion(&[90; 64 * 48], &[90; 64 * 48], 64, 48, 6), (0, 0));
+}
+
+#[test]
+fn test_smooth_corrections_follow_moving_average() {
+	// a one-frame jolt to the right and back
+	let corrections = stabilize::smooth_corrections(&[(0, 0), (0, 0), (6, 0), (-6, 0), (0, 0)], 1);
+	assert_eq!(corrections, vec![(0, 0), (2, 0), (-4, 0), (2, 0), (0, 0)]);
+	// a steady pan is kept
+	let corrections = stabilize::smooth_corrections(&[(0, 0), (2, 1), (2, 1), (2, 1), (2, 1)], 1);
+	assert_eq!(&corrections[1..4], &[(0, 0), (0, 0), (0, 0)]);
+}
+
+#[test]
+fn test_stabilize_y4m_removes_jitter() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("shaky.y4m");
+	let output = dir.path().join("steady.y4m");
+	let jitter = [(0, 0), (3, 2), (-2, 0), (2, -2), (-3, 1), (0, 0)];
+	let mut bytes = b"YUV4MPEG2 W64 H48 F30:1 Ip C420\n".to_vec();
+	for &(dx, dy) in &jitter {
+		bytes.extend(b"FRAME\n");
+		bytes.extend(texture(64, 48, dx, dy));
+		bytes.extend([128; 32 * 24 * 2]);
+	}
+	std::fs::write(&input, &bytes).unwrap();
+
+	assert!(TransformSpec::parse("stabilize").unwrap().build().is_err());
+	assert!(TransformSpec::parse("stabilize=search=0").is_err());
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["stabilize=search=6:smoothing=10".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	// with a window covering the clip every frame lands on the mean position
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut lumas = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		lumas.push(packet.data[..64 * 48].to_vec());
+	}
+	assert_eq!(lumas.len(), jitter.len());
+	let centre = |luma: &[u8]| -> Vec<u8> {
+		(8..40).flat_map(|y| luma[y * 64 + 8..y * 64 + 56].to_vec()).collect()
+	};
+	for luma in &lumas[1..] {
+		assert_eq!(centre(luma), centre(&lumas[0]));
+	}
+}
+
+#[test]
+fn test_video_fade_ramps_to_and_from_black() {
+	let mut fade = VideoFade::fade_in(2).with_fade_out(2, 5);
+	let mut lumas = Vec::new();
+	for _ in 0..5 {
+		let output = fade.apply(yuv420_frame([216; 8], [228; 2], [28; 2])).unwrap();
+		let data = output.video().unwrap().data.clone();
+		lumas.push((data[0], data[8], data[10]));
+	}
+	// in: 0, 1/2, full; out: 1/2, black
+	assert_eq!(
+		lumas,
+		vec![(16, 128, 128), (116, 178, 78), (216, 228, 28), (116, 178, 78), (16, 128, 128)]
+	);
+
+	let mut fade = VideoFade::fade_out(1, 1);
+	let video = FrameVideo::new(vec![200, 100, 50, 77], 1, 1, VideoFormat::RGBA32);
+	let output = fade.apply(Frame::new_video(video, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![0, 0, 0, 77]);
+}
+
+#[test]
+fn test_vfade_spec_counts_frames_for_fade_out() {
+	assert_eq!(
+		TransformSpec::parse("vfade=in=10").unwrap(),
+		TransformSpec::VideoFade { fade_in: 10, fade_out: 0, total_frames: None }
+	);
+	assert!(TransformSpec::parse("vfade=out=3").unwrap().build().is_err());
+	assert!(TransformSpec::parse("vfade=up=3").is_err());
+
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["vfade=out=1".to_string()],
+	)
+	.with_loop(Some(2))
+	.run()
+	.unwrap();
+
+	// only the last of the six looped frames is black
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	let mut firsts = Vec::new();
+	while let Some(packet) = reader.read_packet().unwrap() {
+		firsts.push(packet.data[0]);
+	}
+	assert_eq!(firsts, vec![0, 30, 60, 0, 30, 16]);
+}
+
+#[test]
+fn test_tblend_averages_recent_frames() {
+	let mut blend = Tblend::new(2);
+	let mut firsts = Vec::new();
+	for luma in [0, 100, 50, 50] {
+		let output = blend.apply(yuv420_frame([luma; 8], [128; 2], [128; 2])).unwrap();
+		assert_eq!(output.pts, 7);
+		firsts.push(output.video().unwrap().data[0]);
+	}
+	assert_eq!(firsts, vec![0, 50, 75, 50]);
+
+	// the current frame weighs three times the previous one
+	let mut blend = Tblend::with_weights(vec![1.0, 3.0]).unwrap();
+	blend.apply(yuv420_frame([0; 8], [128; 2], [128; 2])).unwrap();
+	let output = blend.apply(yuv420_frame([200; 8], [128; 2], [128; 2])).unwrap();
+	assert_eq!(output.video().unwrap().data[0], 150);
+
+	// a new frame size starts over
+	let video = FrameVideo::new(vec![90; 6], 2, 2, VideoFormat::YUV420);
+	let output = blend.apply(Frame::new_video(video, Timebase::new(1, 30), 0)).unwrap();
+	assert_eq!(output.video().unwrap().data, vec![90; 6]);
+
+	assert!(Tblend::with_weights(vec![]).is_err());
+	assert!(Tblend::with_weights(vec![0.0, 0.0]).is_err());
+	assert!(Tblend::with_weights(vec![1.0, -1.0]).is_err());
+}
+
+#[test]
+fn test_tblend_spec() {
+	assert_eq!(TransformSpec::parse("tblend").unwrap(), TransformSpec::Tblend(vec![1.0, 1.0]));
+	assert_eq!(TransformSpec::parse("tblend=3").unwrap(), TransformSpec::Tblend(vec![1.0; 3]));
+	assert_eq!(
+		TransformSpec::parse("tblend=1,2,4").unwrap(),
+		TransformSpec::Tblend(vec![1.0, 2.0, 4.0])
+	);
+	assert!(TransformSpec::parse("tblend=2.5").is_err());
+	assert!(TransformSpec::parse("tblend=0,0").unwrap().build().is_err());
+	assert_eq!(TransformSpec::parse_list("tblend=1,3,negate").unwrap().len(), 2);
+}
+
+#[test]
+fn test_geometry_specs_parse() {
+	assert_eq!(
+		TransformSpec::parse("scale=1280x720").unwrap(),
+		TransformSpec::Scale {
+			width: 1280,
+			height: 720,
+			mode: ScaleMode::Bilinear,
+			fit: ScaleFit::Stretch,
+		}
+	);
+	assert_eq!(
+		TransformSpec::parse("scale=640:360:nearest").unwrap(),
+		TransformSpec::Scale {
+			width: 640,
+			height: 360,
+			mode: ScaleMode::NearestNeighbor,
+			fit: ScaleFit::Stretch,
+		}
+	);
+	assert_eq!(
+		TransformSpec::parse("crop=640:360:0:60").unwrap(),
+		TransformSpec::Crop { width: 640, height: 360, position: Some((0, 60)) }
+	);
+	assert_eq!(
+		TransformSpec::parse("pad=1920:1080:white").unwrap(),
+		TransformSpec::Pad { width: 1920, height: 1080, position: None, color: [235, 128, 128] }
+	);
+	assert_eq!(
+		TransformSpec::parse("rotate=-90").unwrap(),
+		TransformSpec::Rotate(RotateAngle::Rotate270)
+	);
+	assert_eq!(TransformSpec::parse_list("hflip,blur=2,brightness=0.1").unwrap().len(), 3);
+	assert!(TransformSpec::parse("scale=1280").is_err());
+	assert!(TransformSpec::parse("crop=640:360:0").is_err());
+	assert!(TransformSpec::parse("rotate=45").is_err());
+	assert!(TransformSpec::parse("blur=1.5").is_err());
+	assert!(TransformSpec::parse("scale=0x720").unwrap().build().is_err());
+	assert!(TransformSpec::parse("brightness=2").unwrap().build().is_err());
+}
+
+#[test]
+fn test_geometry_transforms_take_size_from_each_frame() {
+	let frame = yuv420_frame([0, 1, 2, 3, 4, 5, 6, 7], [90, 160], [20, 240]);
+	let specs = TransformSpec::parse_list("scale=8x4:nearest,crop=4:2:2:1,hflip").unwrap();
+	let output = TransformChain::from_specs(specs).unwrap().apply(frame).unwrap();
+
+	let video = output.video().unwrap();
+	assert_eq!((video.width, video.height), (4, 2));
+	assert_eq!(output.pts, 7);
+	// the crop keeps the middle of the doubled frame, then it is mirrored
+	assert_eq!(&video.data[..8], &[2, 2, 1, 1, 6, 6, 5, 5]);
+	assert_eq!(&video.data[8..], &[160, 90, 240, 20]);
+}
+
+#[test]
+fn test_scale_to_one_pixel_leaves_no_chroma_row() {
+	// a 1x1 4:2:0 frame has no chroma samples to scale into
+	let frame = yuv420_frame([10, 20, 30, 40, 50, 60, 70, 80], [90, 160], [20, 240]);
+	for mode in ["bilinear", "nearest"] {
+		let spec = format!("scale=1x1:{}", mode);
+		let chain = TransformChain::from_specs(TransformSpec::parse_list(&spec).unwrap());
+		let output = chain.unwrap().apply(frame.clone()).unwrap();
+		let video = output.video().unwrap();
+		assert_eq!((video.width, video.height), (1, 1));
+		assert_eq!(video.data[0], 10);
+	}
+}
+
+#[test]
+fn test_rotate_transform_swaps_dimensions() {
+	let frame = yuv420_frame([0, 1, 2, 3, 4, 5, 6, 7], [90, 160], [20, 240]);
+	let mut rotate = TransformSpec::Rotate(RotateAngle::Rotate90).build().unwrap();
+	let output = rotate.apply(frame).unwrap();
+
+	let video = output.video().unwrap();
+	assert_eq!((video.width, video.height), (2, 4));
+	assert_eq!(&video.data[..8], &[4, 0, 5, 1, 6, 2, 7, 3]);
+	assert_eq!(&video.data[8..], &[90, 160, 20, 240]);
+}
+
+#[test]
+fn test_pad_transform_centres_the_frame() {
+	let frame = yuv420_frame([200; 8], [90, 160], [20, 240]);
+	let output = Pad::center(0, 0, 8, 4).apply(frame.clone()).unwrap();
+
+	let video = output.video().unwrap();
+	assert_eq!((video.width, video.height), (8, 4));
+	assert_eq!(&video.data[..8], &[16; 8]);
+	assert_eq!(&video.data[8..16], &[16, 16, 200, 200, 200, 200, 16, 16]);
+	// chroma is 4x2: the frame's 2x1 block lands at (1, 0)
+	assert_eq!(&video.data[32..36], &[128, 90, 160, 128]);
+	assert!(Pad::center(0, 0, 2, 2).apply(frame).is_err());
+}
+
+#[test]
+fn test_geometry_transforms_reject_packed_rgb() {
+	let frame = Frame::new_video(
+		FrameVideo::new(vec![0; 12], 2, 2, VideoFormat::RGB24),
+		Timebase::new(1, 30),
+		0,
+	);
+	assert!(Scale::new(0, 0, 4, 4).apply(frame.clone()).is_err());
+	assert!(Crop::center(2, 2, 1, 1).apply(frame).is_err());
+}
+
+#[test]
+fn test_y4m_header_follows_scaled_frames() {
+	let dir = tempdir().unwrap();
+	let input = dir.path().join("in.y4m");
+	let output = dir.path().join("out.y4m");
+	std::fs::write(&input, crate::common::create_test_y4m_data()).unwrap();
+
+	Transcoder::new(
+		input.to_str().unwrap().to_string(),
+		Some(output.to_str().unwrap().to_string()),
+		false,
+		vec!["scale=4x4,rotate=90,pad=6:4".to_string()],
+	)
+	.run()
+	.unwrap();
+
+	let mut reader = Y4mReader::new(Cursor::new(std::fs::read(&output).unwrap())).unwrap();
+	assert_eq!((reader.format().width, reader.format().height), (6, 4));
+	let packet = reader.read_packet().unwrap().unwrap();
+	assert_eq!(packet.data.len(), 24 + 2 * 6);
+}
+
+#[test]
+fn test_scale_fit_letterboxes_and_fill_crops() {
+	assert_eq!(
+		TransformSpec::parse("scale=1280x720:fill:nearest").unwrap(),
+		TransformSpec::Scale {
+			width: 1280,
+			height: 720,
+			mode: ScaleMode::NearestNeighbor,
+			fit: ScaleFit::Fill,
+		}
+	);
+	assert!(TransformSpec::parse("scale=1280x720:cover").is_err());
+
+	// a 4x2 frame into a 4x6 box: fit keeps 4x2 and adds two rows of black
+	// above and below; fill into 4x4 doubles it to 8x4 and keeps the middle
+	let frame = yuv420_frame([0, 1, 2, 3, 4, 5, 6, 7], [90, 160], [20, 240]);
+	let mut fit = TransformSpec::parse("scale=4x6:fit:nearest").unwrap().build().unwrap();
+	let output = fit.apply(frame.clone()).unwrap();
+	let video = output.video().unwrap();
+	assert_eq!((video.width, video.height), (4, 6));
+	assert_eq!(&video.data[..8], &[16; 8]);
+	assert_eq!(&video.data[8..16], &[0, 1, 2, 3, 4, 5, 6, 7]);
+	assert_eq!(&video.data[16..24], &[16; 8]);
+	assert_eq!(&video.data[24..], &[128, 128, 90, 160, 128, 128, 128, 128, 20, 240, 128, 128]);
+
+	let mut fill = TransformSpec::parse("scale=4x4:fill:nearest").unwrap().build().unwrap();
+	let output = fill.apply(frame).unwrap();
+	let video = output.video().unwrap();
+	assert_eq!((video.width, video.height), (4, 4));
+	assert_eq!(&video.data[..4], &[1, 1, 2, 2]);
+	assert_eq!(&video.data[12..16], &[5, 5, 6, 6]);
+	assert_eq!(&video.data[16..], &[90, 160, 90, 160, 20, 240, 20, 240]);
+
+	let scale = Scale::new(1920, 1080, 1280, 1024).with_fit(ScaleFit::Fit);
+	assert_eq!(scale.scaled_dimensions(VideoFormat::YUV420), (1280, 720));
+	let scale = Scale::new(1920, 1080, 1280, 1024).with_fit(ScaleFit::Fill);
+	assert_eq!(scale.scaled_dimensions(VideoFormat::YUV420), (1820, 1024));
+}
+
+#[test]
+fn test_bilinear_scale_matches_float_reference() {
+	let (src_w, src_h, dst_w, dst_h) = (16u32, 8u32, 7u32, 5u32);
+	let luma: Vec<u8> = (0..src_w * src_h).map(|i| ((i * 37 + i / 5 * 11) % 256) as u8).collect();
+	let mut data = luma.clone();
+	data.extend(vec![128; 2 * 8 * 4]);
+	let frame = Frame::new_video(
+		FrameVideo::new(data, src_w, src_h, VideoFormat::YUV420),
+		Timebase::new(1, 30),
+		0,
+	);
+	let output = Scale::new(src_w, src_h, dst_w, dst_h).apply_yuv420(&frame).unwrap();
+	let data = &output.video().unwrap().data;
+
+	let x_ratio = (src_w - 1) as f64 / (dst_w - 1) as f64;
+	let y_ratio = (src_h - 1) as f64 / (dst_h - 1) as f64;
+	for y in 0..dst_h {
+		for x in 0..dst_w {
+			let (sx, sy) = (x as f64 * x_ratio, y as f64 * y_ratio);
+			let (x0, y0) = (sx.floor() as u32, sy.floor() as u32);
+			let (x1, y1) = ((x0 + 1).min(src_w - 1), (y0 + 1).min(src_h - 1));
+			let (fx, fy) = (sx - x0 as f64, sy - y0 as f64);
+			let at = |px: u32, py: u32| luma[(py * src_w + px) as usize] as f64;
+			let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+			let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+			let expected = top * (1.0 - fy) + bottom * fy;
+			let actual = data[(y * dst_w + x) as usize] as f64;
+			assert!((actual - expected).abs() <= 1.5, "({x}, {y}): {actual} vs {expected}");
+		}
+	}
+	assert_eq!(data[(dst_w * dst_h) as usize..], vec![128; 2 * 3 * 2]);
+}